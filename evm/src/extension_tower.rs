@@ -2,6 +2,9 @@ use core::fmt::Debug;
 use core::ops::{Add, Div, Mul, Neg, Sub};
 
 use ethereum_types::{U256, U512};
+use plonky2::field::bn254_base::Bn254Base;
+use plonky2::field::curve::bn254_tower::{Bn254Fp12, Bn254Fp2, Bn254Fp6};
+use plonky2::field::types::PrimeField;
 use rand::distributions::{Distribution, Standard};
 use rand::Rng;
 
@@ -1319,3 +1322,68 @@ where
         Fp12 { z0, z1 }
     }
 }
+
+impl From<BN254> for Bn254Base {
+    fn from(x: BN254) -> Self {
+        Bn254Base(x.val.0)
+    }
+}
+
+impl From<Bn254Base> for BN254 {
+    fn from(x: Bn254Base) -> Self {
+        let mut limbs = [0; 4];
+        for (limb, digit) in limbs
+            .iter_mut()
+            .zip(x.to_canonical_biguint().to_u64_digits())
+        {
+            *limb = digit;
+        }
+        BN254 { val: U256(limbs) }
+    }
+}
+
+impl From<Fp2<BN254>> for Bn254Fp2 {
+    fn from(x: Fp2<BN254>) -> Self {
+        Bn254Fp2([x.re.into(), x.im.into()])
+    }
+}
+
+impl From<Bn254Fp2> for Fp2<BN254> {
+    fn from(x: Bn254Fp2) -> Self {
+        Fp2 {
+            re: x.0[0].into(),
+            im: x.0[1].into(),
+        }
+    }
+}
+
+impl From<Fp6<BN254>> for Bn254Fp6 {
+    fn from(x: Fp6<BN254>) -> Self {
+        Bn254Fp6([x.t0.into(), x.t1.into(), x.t2.into()])
+    }
+}
+
+impl From<Bn254Fp6> for Fp6<BN254> {
+    fn from(x: Bn254Fp6) -> Self {
+        Fp6 {
+            t0: x.0[0].into(),
+            t1: x.0[1].into(),
+            t2: x.0[2].into(),
+        }
+    }
+}
+
+impl From<Fp12<BN254>> for Bn254Fp12 {
+    fn from(x: Fp12<BN254>) -> Self {
+        Bn254Fp12([x.z0.into(), x.z1.into()])
+    }
+}
+
+impl From<Bn254Fp12> for Fp12<BN254> {
+    fn from(x: Bn254Fp12) -> Self {
+        Fp12 {
+            z0: x.0[0].into(),
+            z1: x.0[1].into(),
+        }
+    }
+}
@@ -0,0 +1,2 @@
+#[cfg(target_feature = "neon")]
+pub mod neon_goldilocks_field;
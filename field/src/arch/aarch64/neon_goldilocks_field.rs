@@ -0,0 +1,541 @@
+use core::arch::aarch64::*;
+use core::fmt;
+use core::fmt::{Debug, Formatter};
+use core::iter::{Product, Sum};
+use core::mem::transmute;
+use core::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use crate::goldilocks_field::GoldilocksField;
+use crate::ops::Square;
+use crate::packed::PackedField;
+use crate::types::{Field, Field64};
+
+/// NEON Goldilocks Field
+///
+/// A NEON register holds two 64-bit lanes, so `NeonGoldilocksField` packs two field elements. As
+/// with the AVX2 packing, we wrap `[GoldilocksField; 2]` rather than `uint64x2_t` to keep the
+/// alignment of `GoldilocksField`, and use the `new` and `get` methods to convert to and from
+/// `uint64x2_t`.
+#[derive(Copy, Clone)]
+#[repr(transparent)]
+pub struct NeonGoldilocksField(pub [GoldilocksField; 2]);
+
+impl NeonGoldilocksField {
+    #[inline]
+    fn new(x: uint64x2_t) -> Self {
+        unsafe { transmute(x) }
+    }
+    #[inline]
+    fn get(&self) -> uint64x2_t {
+        unsafe { transmute(*self) }
+    }
+}
+
+impl Add<Self> for NeonGoldilocksField {
+    type Output = Self;
+    #[inline]
+    fn add(self, rhs: Self) -> Self {
+        Self::new(unsafe { add(self.get(), rhs.get()) })
+    }
+}
+impl Add<GoldilocksField> for NeonGoldilocksField {
+    type Output = Self;
+    #[inline]
+    fn add(self, rhs: GoldilocksField) -> Self {
+        self + Self::from(rhs)
+    }
+}
+impl Add<NeonGoldilocksField> for GoldilocksField {
+    type Output = NeonGoldilocksField;
+    #[inline]
+    fn add(self, rhs: Self::Output) -> Self::Output {
+        Self::Output::from(self) + rhs
+    }
+}
+impl AddAssign<Self> for NeonGoldilocksField {
+    #[inline]
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+impl AddAssign<GoldilocksField> for NeonGoldilocksField {
+    #[inline]
+    fn add_assign(&mut self, rhs: GoldilocksField) {
+        *self = *self + rhs;
+    }
+}
+
+impl Debug for NeonGoldilocksField {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "({:?})", self.get())
+    }
+}
+
+impl Default for NeonGoldilocksField {
+    #[inline]
+    fn default() -> Self {
+        Self::ZEROS
+    }
+}
+
+impl Div<GoldilocksField> for NeonGoldilocksField {
+    type Output = Self;
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    #[inline]
+    fn div(self, rhs: GoldilocksField) -> Self {
+        self * rhs.inverse()
+    }
+}
+impl DivAssign<GoldilocksField> for NeonGoldilocksField {
+    #[allow(clippy::suspicious_op_assign_impl)]
+    #[inline]
+    fn div_assign(&mut self, rhs: GoldilocksField) {
+        *self *= rhs.inverse();
+    }
+}
+
+impl From<GoldilocksField> for NeonGoldilocksField {
+    fn from(x: GoldilocksField) -> Self {
+        Self([x; 2])
+    }
+}
+
+impl Mul<Self> for NeonGoldilocksField {
+    type Output = Self;
+    #[inline]
+    fn mul(self, rhs: Self) -> Self {
+        Self::new(unsafe { mul(self.get(), rhs.get()) })
+    }
+}
+impl Mul<GoldilocksField> for NeonGoldilocksField {
+    type Output = Self;
+    #[inline]
+    fn mul(self, rhs: GoldilocksField) -> Self {
+        self * Self::from(rhs)
+    }
+}
+impl Mul<NeonGoldilocksField> for GoldilocksField {
+    type Output = NeonGoldilocksField;
+    #[inline]
+    fn mul(self, rhs: NeonGoldilocksField) -> Self::Output {
+        Self::Output::from(self) * rhs
+    }
+}
+impl MulAssign<Self> for NeonGoldilocksField {
+    #[inline]
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+impl MulAssign<GoldilocksField> for NeonGoldilocksField {
+    #[inline]
+    fn mul_assign(&mut self, rhs: GoldilocksField) {
+        *self = *self * rhs;
+    }
+}
+
+impl Neg for NeonGoldilocksField {
+    type Output = Self;
+    #[inline]
+    fn neg(self) -> Self {
+        Self::new(unsafe { neg(self.get()) })
+    }
+}
+
+impl Product for NeonGoldilocksField {
+    #[inline]
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.reduce(|x, y| x * y).unwrap_or(Self::ONES)
+    }
+}
+
+unsafe impl PackedField for NeonGoldilocksField {
+    const WIDTH: usize = 2;
+
+    type Scalar = GoldilocksField;
+
+    const ZEROS: Self = Self([GoldilocksField::ZERO; 2]);
+    const ONES: Self = Self([GoldilocksField::ONE; 2]);
+
+    #[inline]
+    fn from_slice(slice: &[Self::Scalar]) -> &Self {
+        assert_eq!(slice.len(), Self::WIDTH);
+        unsafe { &*slice.as_ptr().cast() }
+    }
+    #[inline]
+    fn from_slice_mut(slice: &mut [Self::Scalar]) -> &mut Self {
+        assert_eq!(slice.len(), Self::WIDTH);
+        unsafe { &mut *slice.as_mut_ptr().cast() }
+    }
+    #[inline]
+    fn as_slice(&self) -> &[Self::Scalar] {
+        &self.0[..]
+    }
+    #[inline]
+    fn as_slice_mut(&mut self) -> &mut [Self::Scalar] {
+        &mut self.0[..]
+    }
+
+    #[inline]
+    fn interleave(&self, other: Self, block_len: usize) -> (Self, Self) {
+        let (v0, v1) = (self.get(), other.get());
+        let (res0, res1) = match block_len {
+            1 => unsafe { interleave1(v0, v1) },
+            2 => (v0, v1),
+            _ => panic!("unsupported block_len"),
+        };
+        (Self::new(res0), Self::new(res1))
+    }
+}
+
+impl Square for NeonGoldilocksField {
+    #[inline]
+    fn square(&self) -> Self {
+        Self::new(unsafe { square(self.get()) })
+    }
+}
+
+impl Sub<Self> for NeonGoldilocksField {
+    type Output = Self;
+    #[inline]
+    fn sub(self, rhs: Self) -> Self {
+        Self::new(unsafe { sub(self.get(), rhs.get()) })
+    }
+}
+impl Sub<GoldilocksField> for NeonGoldilocksField {
+    type Output = Self;
+    #[inline]
+    fn sub(self, rhs: GoldilocksField) -> Self {
+        self - Self::from(rhs)
+    }
+}
+impl Sub<NeonGoldilocksField> for GoldilocksField {
+    type Output = NeonGoldilocksField;
+    #[inline]
+    fn sub(self, rhs: NeonGoldilocksField) -> Self::Output {
+        Self::Output::from(self) - rhs
+    }
+}
+impl SubAssign<Self> for NeonGoldilocksField {
+    #[inline]
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+impl SubAssign<GoldilocksField> for NeonGoldilocksField {
+    #[inline]
+    fn sub_assign(&mut self, rhs: GoldilocksField) {
+        *self = *self - rhs;
+    }
+}
+
+impl Sum for NeonGoldilocksField {
+    #[inline]
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.reduce(|x, y| x + y).unwrap_or(Self::ZEROS)
+    }
+}
+
+// Resources:
+// 1. Arm Intrinsics reference for the semantics of each intrinsic:
+//    https://developer.arm.com/architectures/instruction-sets/intrinsics/
+//
+// Unlike AVX2, NEON has unsigned 64-bit comparisons (`vcltq_u64`, `vcgeq_u64`), so none of the
+// 1 << 63 shifting used by the x86 implementations is needed. Comparisons return -1 (all bits 1)
+// for true and 0 for false, so a comparison mask ANDed with a constant conditionally selects it.
+//
+// As in the scalar implementation, values are kept in the range [0, 2^64) and only canonicalized
+// when needed.
+
+const FIELD_ORDER: uint64x2_t = unsafe { transmute([GoldilocksField::ORDER; 2]) };
+const EPSILON: uint64x2_t = unsafe { transmute([GoldilocksField::ORDER.wrapping_neg(); 2]) };
+
+/// Convert to canonical representation.
+#[inline]
+unsafe fn canonicalize(x: uint64x2_t) -> uint64x2_t {
+    // -1 if x >= FIELD_ORDER else 0.
+    let mask = vcgeq_u64(x, FIELD_ORDER);
+    vsubq_u64(x, vandq_u64(mask, FIELD_ORDER))
+}
+
+/// Addition u64 + u64 -> u64. Assumes that x + y < 2^64 + FIELD_ORDER.
+#[inline]
+unsafe fn add_no_double_overflow_64_64(x: uint64x2_t, y: uint64x2_t) -> uint64x2_t {
+    let res_wrapped = vaddq_u64(x, y);
+    let mask = vcltq_u64(res_wrapped, y); // -1 if overflowed else 0.
+    let wrapback_amt = vandq_u64(mask, EPSILON); // -FIELD_ORDER if overflowed else 0.
+    vaddq_u64(res_wrapped, wrapback_amt)
+}
+
+#[inline]
+unsafe fn add(x: uint64x2_t, y: uint64x2_t) -> uint64x2_t {
+    add_no_double_overflow_64_64(x, canonicalize(y))
+}
+
+#[inline]
+unsafe fn sub(x: uint64x2_t, y: uint64x2_t) -> uint64x2_t {
+    let y = canonicalize(y);
+    let mask = vcltq_u64(x, y); // -1 if sub will underflow (y > x) else 0.
+    let wrapback_amt = vandq_u64(mask, EPSILON); // -FIELD_ORDER if underflow else 0.
+    let res_wrapped = vsubq_u64(x, y);
+    vsubq_u64(res_wrapped, wrapback_amt)
+}
+
+#[inline]
+unsafe fn neg(y: uint64x2_t) -> uint64x2_t {
+    vsubq_u64(FIELD_ORDER, canonicalize(y))
+}
+
+/// Full 64-bit by 64-bit multiplication, returning the high and low words of the product.
+#[inline]
+unsafe fn mul64_64(x: uint64x2_t, y: uint64x2_t) -> (uint64x2_t, uint64x2_t) {
+    // Narrow each operand into its low and high 32-bit halves; `vmull_u32` widens them back.
+    let x_lo = vmovn_u64(x);
+    let x_hi = vshrn_n_u64::<32>(x);
+    let y_lo = vmovn_u64(y);
+    let y_hi = vshrn_n_u64::<32>(y);
+
+    // All four pairwise multiplications
+    let mul_ll = vmull_u32(x_lo, y_lo);
+    let mul_lh = vmull_u32(x_lo, y_hi);
+    let mul_hl = vmull_u32(x_hi, y_lo);
+    let mul_hh = vmull_u32(x_hi, y_hi);
+
+    // Bignum addition
+    // Extract high 32 bits of mul_ll and add to mul_hl. This cannot overflow.
+    let mul_ll_hi = vshrq_n_u64::<32>(mul_ll);
+    let t0 = vaddq_u64(mul_hl, mul_ll_hi);
+    // Extract low 32 bits of t0 and add to mul_lh. Again, this cannot overflow.
+    // Also, extract high 32 bits of t0 and add to mul_hh.
+    let t0_lo = vandq_u64(t0, EPSILON);
+    let t0_hi = vshrq_n_u64::<32>(t0);
+    let t1 = vaddq_u64(mul_lh, t0_lo);
+    let t2 = vaddq_u64(mul_hh, t0_hi);
+    // Lastly, extract the high 32 bits of t1 and add to t2.
+    let t1_hi = vshrq_n_u64::<32>(t1);
+    let res_hi = vaddq_u64(t2, t1_hi);
+
+    // Form res_lo by inserting the low half of t1 above the low half of mul_ll.
+    let res_lo = vsliq_n_u64::<32>(mul_ll, t1);
+
+    (res_hi, res_lo)
+}
+
+/// Reduces a 128-bit value, given as its high and low words, to a 64-bit value. The result might
+/// not be in canonical form.
+#[inline]
+unsafe fn reduce128(x: (uint64x2_t, uint64x2_t)) -> uint64x2_t {
+    let (hi0, lo0) = x;
+
+    // lo1 = lo0 - hi_hi0, subtracting EPSILON again on borrow (this cannot underflow).
+    let hi_hi0 = vshrq_n_u64::<32>(hi0);
+    let borrow = vcltq_u64(lo0, hi_hi0);
+    let lo1 = vsubq_u64(vsubq_u64(lo0, hi_hi0), vandq_u64(borrow, EPSILON));
+
+    // t1 = hi_lo0 * EPSILON = (hi_lo0 << 32) - hi_lo0.
+    let hi_lo0 = vandq_u64(hi0, EPSILON);
+    let t1 = vsubq_u64(vshlq_n_u64::<32>(hi0), hi_lo0);
+
+    // t1 <= (2^32 - 1)^2, so this sum cannot overflow twice.
+    add_no_double_overflow_64_64(lo1, t1)
+}
+
+/// Multiply two integers modulo FIELD_ORDER.
+#[inline]
+unsafe fn mul(x: uint64x2_t, y: uint64x2_t) -> uint64x2_t {
+    reduce128(mul64_64(x, y))
+}
+
+/// Square an integer modulo FIELD_ORDER.
+#[inline]
+unsafe fn square(x: uint64x2_t) -> uint64x2_t {
+    mul(x, x)
+}
+
+#[inline]
+unsafe fn interleave1(x: uint64x2_t, y: uint64x2_t) -> (uint64x2_t, uint64x2_t) {
+    let a = vzip1q_u64(x, y);
+    let b = vzip2q_u64(x, y);
+    (a, b)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::arch::aarch64::neon_goldilocks_field::NeonGoldilocksField;
+    use crate::goldilocks_field::GoldilocksField;
+    use crate::ops::Square;
+    use crate::packed::PackedField;
+    use crate::types::{Field, Field64};
+
+    fn test_vals_a() -> [GoldilocksField; 2] {
+        [
+            GoldilocksField::from_noncanonical_u64(14479013849828404771),
+            GoldilocksField::from_noncanonical_u64(9087029921428221768),
+        ]
+    }
+    fn test_vals_b() -> [GoldilocksField; 2] {
+        [
+            GoldilocksField::from_noncanonical_u64(17891926589593242302),
+            GoldilocksField::from_noncanonical_u64(11009798273260028228),
+        ]
+    }
+    /// Values just below and just above the field order, stored without reduction, to exercise
+    /// the canonicalization and carry paths.
+    fn test_vals_edge() -> [GoldilocksField; 2] {
+        [
+            GoldilocksField(GoldilocksField::ORDER - 1),
+            GoldilocksField(u64::MAX),
+        ]
+    }
+
+    #[test]
+    fn test_add() {
+        let a_arr = test_vals_a();
+        let b_arr = test_vals_b();
+
+        let packed_a = *NeonGoldilocksField::from_slice(&a_arr);
+        let packed_b = *NeonGoldilocksField::from_slice(&b_arr);
+        let packed_res = packed_a + packed_b;
+        let arr_res = packed_res.as_slice();
+
+        let expected = a_arr.iter().zip(b_arr).map(|(&a, b)| a + b);
+        for (exp, &res) in expected.zip(arr_res) {
+            assert_eq!(res, exp);
+        }
+    }
+
+    #[test]
+    fn test_mul() {
+        let a_arr = test_vals_a();
+        let b_arr = test_vals_b();
+
+        let packed_a = *NeonGoldilocksField::from_slice(&a_arr);
+        let packed_b = *NeonGoldilocksField::from_slice(&b_arr);
+        let packed_res = packed_a * packed_b;
+        let arr_res = packed_res.as_slice();
+
+        let expected = a_arr.iter().zip(b_arr).map(|(&a, b)| a * b);
+        for (exp, &res) in expected.zip(arr_res) {
+            assert_eq!(res, exp);
+        }
+    }
+
+    #[test]
+    fn test_square() {
+        let a_arr = test_vals_a();
+
+        let packed_a = *NeonGoldilocksField::from_slice(&a_arr);
+        let packed_res = packed_a.square();
+        let arr_res = packed_res.as_slice();
+
+        let expected = a_arr.iter().map(|&a| a.square());
+        for (exp, &res) in expected.zip(arr_res) {
+            assert_eq!(res, exp);
+        }
+    }
+
+    #[test]
+    fn test_neg() {
+        let a_arr = test_vals_a();
+
+        let packed_a = *NeonGoldilocksField::from_slice(&a_arr);
+        let packed_res = -packed_a;
+        let arr_res = packed_res.as_slice();
+
+        let expected = a_arr.iter().map(|&a| -a);
+        for (exp, &res) in expected.zip(arr_res) {
+            assert_eq!(res, exp);
+        }
+    }
+
+    #[test]
+    fn test_sub() {
+        let a_arr = test_vals_a();
+        let b_arr = test_vals_b();
+
+        let packed_a = *NeonGoldilocksField::from_slice(&a_arr);
+        let packed_b = *NeonGoldilocksField::from_slice(&b_arr);
+        let packed_res = packed_a - packed_b;
+        let arr_res = packed_res.as_slice();
+
+        let expected = a_arr.iter().zip(b_arr).map(|(&a, b)| a - b);
+        for (exp, &res) in expected.zip(arr_res) {
+            assert_eq!(res, exp);
+        }
+    }
+
+    #[test]
+    fn test_interleave_is_involution() {
+        let a_arr = test_vals_a();
+        let b_arr = test_vals_b();
+
+        let packed_a = *NeonGoldilocksField::from_slice(&a_arr);
+        let packed_b = *NeonGoldilocksField::from_slice(&b_arr);
+        {
+            // Interleave, then deinterleave.
+            let (x, y) = packed_a.interleave(packed_b, 1);
+            let (res_a, res_b) = x.interleave(y, 1);
+            assert_eq!(res_a.as_slice(), a_arr);
+            assert_eq!(res_b.as_slice(), b_arr);
+        }
+        {
+            let (x, y) = packed_a.interleave(packed_b, 2);
+            let (res_a, res_b) = x.interleave(y, 2);
+            assert_eq!(res_a.as_slice(), a_arr);
+            assert_eq!(res_b.as_slice(), b_arr);
+        }
+    }
+
+    #[test]
+    fn test_edge_values() {
+        let edge = test_vals_edge();
+        let packed_edge = *NeonGoldilocksField::from_slice(&edge);
+        for other in [test_vals_a(), test_vals_b(), edge] {
+            let packed_other = *NeonGoldilocksField::from_slice(&other);
+            let sum = packed_edge + packed_other;
+            let diff = packed_edge - packed_other;
+            let prod = packed_edge * packed_other;
+            let neg = -packed_other;
+            for i in 0..2 {
+                assert_eq!(sum.as_slice()[i], edge[i] + other[i]);
+                assert_eq!(diff.as_slice()[i], edge[i] - other[i]);
+                assert_eq!(prod.as_slice()[i], edge[i] * other[i]);
+                assert_eq!(neg.as_slice()[i], -other[i]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_interleave() {
+        let in_a: [GoldilocksField; 2] = [
+            GoldilocksField::from_noncanonical_u64(0),
+            GoldilocksField::from_noncanonical_u64(1),
+        ];
+        let in_b: [GoldilocksField; 2] = [
+            GoldilocksField::from_noncanonical_u64(10),
+            GoldilocksField::from_noncanonical_u64(11),
+        ];
+        let int1_a: [GoldilocksField; 2] = [
+            GoldilocksField::from_noncanonical_u64(0),
+            GoldilocksField::from_noncanonical_u64(10),
+        ];
+        let int1_b: [GoldilocksField; 2] = [
+            GoldilocksField::from_noncanonical_u64(1),
+            GoldilocksField::from_noncanonical_u64(11),
+        ];
+
+        let packed_a = *NeonGoldilocksField::from_slice(&in_a);
+        let packed_b = *NeonGoldilocksField::from_slice(&in_b);
+        {
+            let (x1, y1) = packed_a.interleave(packed_b, 1);
+            assert_eq!(x1.as_slice(), int1_a);
+            assert_eq!(y1.as_slice(), int1_b);
+        }
+        {
+            let (x2, y2) = packed_a.interleave(packed_b, 2);
+            assert_eq!(x2.as_slice(), in_a);
+            assert_eq!(y2.as_slice(), in_b);
+        }
+    }
+}
@@ -0,0 +1,297 @@
+use core::fmt::{self, Debug, Display, Formatter};
+use core::iter::{Product, Sum};
+use core::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use num::{BigUint, Integer, ToPrimitive};
+use serde::{Deserialize, Serialize};
+
+use crate::extension::quartic::QuarticExtension;
+use crate::extension::quintic::QuinticExtension;
+use crate::extension::{Extendable, Frobenius};
+use crate::types::{Field, Field64, PrimeField, PrimeField64, Sample};
+
+/// The BabyBear field, a 31-bit field with a large two-adic subgroup.
+///
+/// Its order is 2^31 - 2^27 + 1.
+/// ```ignore
+/// P = 15 * 2**27 + 1
+///   = 2013265921
+/// ```
+///
+/// Elements are always stored in canonical form.
+#[derive(Copy, Clone, Default, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[repr(transparent)]
+pub struct BabyBearField(pub u32);
+
+const P: u32 = 0x78000001;
+
+impl Display for BabyBearField {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+impl Debug for BabyBearField {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&self.0, f)
+    }
+}
+
+impl Sample for BabyBearField {
+    #[inline]
+    fn sample<R>(rng: &mut R) -> Self
+    where
+        R: rand::RngCore + ?Sized,
+    {
+        use rand::Rng;
+        Self(rng.gen_range(0..P))
+    }
+}
+
+impl Field for BabyBearField {
+    const ZERO: Self = Self(0);
+    const ONE: Self = Self(1);
+    const TWO: Self = Self(2);
+    const NEG_ONE: Self = Self(P - 1);
+
+    const TWO_ADICITY: usize = 27;
+    const CHARACTERISTIC_TWO_ADICITY: usize = Self::TWO_ADICITY;
+
+    // Sage: `g = GF(p).multiplicative_generator()`
+    const MULTIPLICATIVE_GROUP_GENERATOR: Self = Self(31);
+
+    // Sage: `g^((p - 1) / 2^27)`
+    const POWER_OF_TWO_GENERATOR: Self = Self(440564289);
+
+    const THREE_ADICITY: usize = 1;
+
+    // Sage: `g^((p - 1) / 3)`
+    const POWER_OF_THREE_GENERATOR: Self = Self(1314723123);
+
+    const BITS: usize = 31;
+
+    fn order() -> BigUint {
+        P.into()
+    }
+    fn characteristic() -> BigUint {
+        Self::order()
+    }
+
+    /// Returns the inverse of the field element, using Fermat's little theorem.
+    fn try_inverse(&self) -> Option<Self> {
+        if self.is_zero() {
+            return None;
+        }
+        Some(self.exp_u64(Self::ORDER - 2))
+    }
+
+    fn from_noncanonical_biguint(n: BigUint) -> Self {
+        Self(n.mod_floor(&Self::order()).to_u32().unwrap())
+    }
+
+    #[inline(always)]
+    fn from_canonical_u64(n: u64) -> Self {
+        debug_assert!(n < Self::ORDER);
+        Self(n as u32)
+    }
+
+    fn from_noncanonical_u128(n: u128) -> Self {
+        Self((n % P as u128) as u32)
+    }
+
+    #[inline]
+    fn from_noncanonical_u64(n: u64) -> Self {
+        Self((n % P as u64) as u32)
+    }
+
+    #[inline]
+    fn from_noncanonical_i64(n: i64) -> Self {
+        Self(n.rem_euclid(P as i64) as u32)
+    }
+
+    #[inline]
+    fn multiply_accumulate(&self, x: Self, y: Self) -> Self {
+        // u32 + u32 * u32 cannot overflow a u64.
+        Self::from_noncanonical_u64(self.0 as u64 + x.0 as u64 * y.0 as u64)
+    }
+}
+
+impl PrimeField for BabyBearField {
+    fn to_canonical_biguint(&self) -> BigUint {
+        self.0.into()
+    }
+}
+
+impl Field64 for BabyBearField {
+    const ORDER: u64 = P as u64;
+
+    #[inline]
+    unsafe fn add_canonical_u64(&self, rhs: u64) -> Self {
+        *self + Self(rhs as u32)
+    }
+
+    #[inline]
+    unsafe fn sub_canonical_u64(&self, rhs: u64) -> Self {
+        *self - Self(rhs as u32)
+    }
+}
+
+impl PrimeField64 for BabyBearField {
+    #[inline]
+    fn to_canonical_u64(&self) -> u64 {
+        self.0 as u64
+    }
+
+    #[inline(always)]
+    fn to_noncanonical_u64(&self) -> u64 {
+        self.0 as u64
+    }
+}
+
+impl Neg for BabyBearField {
+    type Output = Self;
+
+    #[inline]
+    fn neg(self) -> Self {
+        if self.is_zero() {
+            Self::ZERO
+        } else {
+            Self(P - self.0)
+        }
+    }
+}
+
+impl Add for BabyBearField {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, rhs: Self) -> Self {
+        // Both operands are below 2^31, so their sum fits in a u32.
+        let sum = self.0 + rhs.0;
+        Self(if sum >= P { sum - P } else { sum })
+    }
+}
+
+impl AddAssign for BabyBearField {
+    #[inline]
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sum for BabyBearField {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ZERO, |acc, x| acc + x)
+    }
+}
+
+impl Sub for BabyBearField {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, rhs: Self) -> Self {
+        let (diff, under) = self.0.overflowing_sub(rhs.0);
+        Self(if under { diff.wrapping_add(P) } else { diff })
+    }
+}
+
+impl SubAssign for BabyBearField {
+    #[inline]
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl Mul for BabyBearField {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, rhs: Self) -> Self {
+        Self::from_noncanonical_u64(self.0 as u64 * rhs.0 as u64)
+    }
+}
+
+impl MulAssign for BabyBearField {
+    #[inline]
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+impl Product for BabyBearField {
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ONE, |acc, x| acc * x)
+    }
+}
+
+impl Div for BabyBearField {
+    type Output = Self;
+
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn div(self, rhs: Self) -> Self::Output {
+        self * rhs.inverse()
+    }
+}
+
+impl DivAssign for BabyBearField {
+    fn div_assign(&mut self, rhs: Self) {
+        *self = *self / rhs;
+    }
+}
+
+impl Frobenius<1> for BabyBearField {}
+
+impl Extendable<4> for BabyBearField {
+    type Extension = QuarticExtension<Self>;
+
+    // Verifiable in Sage with
+    // `R.<x> = GF(p)[]; assert (x^4 - 11).is_irreducible()`.
+    const W: Self = Self(11);
+
+    // DTH_ROOT = W^((ORDER - 1)/4)
+    const DTH_ROOT: Self = Self(1728404513);
+
+    const EXT_MULTIPLICATIVE_GROUP_GENERATOR: [Self; 4] = [
+        Self(1406614696),
+        Self(1406614696),
+        Self(1406614696),
+        Self(799963471),
+    ];
+
+    const EXT_POWER_OF_TWO_GENERATOR: [Self; 4] = [Self(0), Self(0), Self(0), Self(619198945)];
+}
+
+impl Extendable<5> for BabyBearField {
+    type Extension = QuinticExtension<Self>;
+
+    // Verifiable in Sage with
+    // `R.<x> = GF(p)[]; assert (x^5 - 2).is_irreducible()`.
+    const W: Self = Self(2);
+
+    // DTH_ROOT = W^((ORDER - 1)/5)
+    const DTH_ROOT: Self = Self(815036133);
+
+    const EXT_MULTIPLICATIVE_GROUP_GENERATOR: [Self; 5] = [
+        Self(1881136052),
+        Self(1881136052),
+        Self(1881136052),
+        Self(1881136052),
+        Self(1749006183),
+    ];
+
+    const EXT_POWER_OF_TWO_GENERATOR: [Self; 5] = [
+        Self::POWER_OF_TWO_GENERATOR,
+        Self(0),
+        Self(0),
+        Self(0),
+        Self(0),
+    ];
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{test_field_arithmetic, test_small_prime_field_arithmetic};
+
+    test_small_prime_field_arithmetic!(crate::babybear_field::BabyBearField);
+    test_field_arithmetic!(crate::babybear_field::BabyBearField);
+}
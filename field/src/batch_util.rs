@@ -1,3 +1,9 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::slice::{self, ChunksExact, ChunksExactMut};
+
+use crate::extension::quadratic::QuadraticExtension;
+use crate::extension::Extendable;
 use crate::packable::Packable;
 use crate::packed::PackedField;
 use crate::types::Field;
@@ -63,3 +69,518 @@ pub fn batch_add_inplace<F: Field>(out: &mut [F], a: &[F]) {
         *x_out += *x_a;
     }
 }
+
+/// Elementwise inplace subtraction of two slices of field elements.
+/// Implementation be faster than the trivial for loop.
+pub fn batch_sub_inplace<F: Field>(out: &mut [F], a: &[F]) {
+    let n = out.len();
+    assert_eq!(n, a.len(), "both arrays must have the same length");
+
+    // Split out slice of vectors, leaving leftovers as scalars
+    let (out_packed, out_leftovers) =
+        pack_slice_with_leftovers_mut::<<F as Packable>::Packing>(out);
+    let (a_packed, a_leftovers) = pack_slice_with_leftovers::<<F as Packable>::Packing>(a);
+
+    // Subtract packed and the leftovers
+    for (x_out, x_a) in out_packed.iter_mut().zip(a_packed) {
+        *x_out -= *x_a;
+    }
+    for (x_out, x_a) in out_leftovers.iter_mut().zip(a_leftovers) {
+        *x_out -= *x_a;
+    }
+}
+
+/// Inplace multiplication of a slice of field elements by a scalar.
+pub fn batch_scalar_multiply_inplace<F: Field>(out: &mut [F], c: F) {
+    let (out_packed, out_leftovers) =
+        pack_slice_with_leftovers_mut::<<F as Packable>::Packing>(out);
+
+    for x_out in out_packed.iter_mut() {
+        *x_out *= c;
+    }
+    for x_out in out_leftovers.iter_mut() {
+        *x_out *= c;
+    }
+}
+
+/// Computes `out[i] += a[i] * b[i]` for all `i`.
+pub fn batch_multiply_add_inplace<F: Field>(out: &mut [F], a: &[F], b: &[F]) {
+    let n = out.len();
+    assert_eq!(n, a.len(), "all arrays must have the same length");
+    assert_eq!(n, b.len(), "all arrays must have the same length");
+
+    let (out_packed, out_leftovers) =
+        pack_slice_with_leftovers_mut::<<F as Packable>::Packing>(out);
+    let (a_packed, a_leftovers) = pack_slice_with_leftovers::<<F as Packable>::Packing>(a);
+    let (b_packed, b_leftovers) = pack_slice_with_leftovers::<<F as Packable>::Packing>(b);
+
+    for ((x_out, x_a), x_b) in out_packed.iter_mut().zip(a_packed).zip(b_packed) {
+        *x_out += *x_a * *x_b;
+    }
+    for ((x_out, x_a), x_b) in out_leftovers.iter_mut().zip(a_leftovers).zip(b_leftovers) {
+        *x_out += *x_a * *x_b;
+    }
+}
+
+/// Computes `out[i] += c * a[i]` for all `i`.
+pub fn batch_scalar_multiply_add_inplace<F: Field>(out: &mut [F], c: F, a: &[F]) {
+    let n = out.len();
+    assert_eq!(n, a.len(), "both arrays must have the same length");
+
+    let (out_packed, out_leftovers) =
+        pack_slice_with_leftovers_mut::<<F as Packable>::Packing>(out);
+    let (a_packed, a_leftovers) = pack_slice_with_leftovers::<<F as Packable>::Packing>(a);
+
+    for (x_out, x_a) in out_packed.iter_mut().zip(a_packed) {
+        *x_out += *x_a * c;
+    }
+    for (x_out, x_a) in out_leftovers.iter_mut().zip(a_leftovers) {
+        *x_out += c * *x_a;
+    }
+}
+
+/// Fills `out` with `1, base, base^2, ...`.
+pub fn batch_powers_fill<F: Field>(out: &mut [F], base: F) {
+    type P<F> = <F as Packable>::Packing;
+
+    // `current` holds the next `WIDTH` powers, and advances by `base^WIDTH` at each step.
+    let mut current = P::<F>::ZEROS;
+    current
+        .as_slice_mut()
+        .iter_mut()
+        .zip(base.powers())
+        .for_each(|(x, power)| *x = power);
+    let step = base.exp_u64(P::<F>::WIDTH as u64);
+
+    let (out_packed, out_leftovers) = pack_slice_with_leftovers_mut::<P<F>>(out);
+    for x_out in out_packed.iter_mut() {
+        *x_out = current;
+        current *= step;
+    }
+    out_leftovers.copy_from_slice(&current.as_slice()[..out_leftovers.len()]);
+}
+
+/// Replaces every element of `values` with its multiplicative inverse, using Montgomery's trick
+/// with one dependency chain per lane of the packing.
+///
+/// Panics if any element is zero.
+pub fn batch_invert_inplace<F: Field>(values: &mut [F]) {
+    type P<F> = <F as Packable>::Packing;
+
+    let (packed, leftovers) = pack_slice_with_leftovers_mut::<P<F>>(values);
+    if !packed.is_empty() {
+        // `prefix_products[i]` holds the lanewise product of `packed[..i]`.
+        let mut prefix_products = Vec::with_capacity(packed.len());
+        let mut acc = P::<F>::ONES;
+        for &x in packed.iter() {
+            prefix_products.push(acc);
+            acc *= x;
+        }
+
+        // This is where the lane chains meet.
+        let lane_inverses = F::batch_multiplicative_inverse(acc.as_slice());
+        let mut acc_inv = *P::<F>::from_slice(&lane_inverses);
+        for (x, &prefix_product) in packed.iter_mut().zip(&prefix_products).rev() {
+            let x_inv = acc_inv * prefix_product;
+            acc_inv *= *x;
+            *x = x_inv;
+        }
+    }
+
+    let leftover_inverses = F::batch_multiplicative_inverse(leftovers);
+    leftovers.copy_from_slice(&leftover_inverses);
+}
+
+/// Computes `sum_i a[i] * b[i]`.
+pub fn inner_product<F: Field>(a: &[F], b: &[F]) -> F {
+    assert_eq!(a.len(), b.len(), "both arrays must have the same length");
+
+    let (a_packed, a_leftovers) = pack_slice_with_leftovers::<<F as Packable>::Packing>(a);
+    let (b_packed, b_leftovers) = pack_slice_with_leftovers::<<F as Packable>::Packing>(b);
+
+    let mut acc = <F as Packable>::Packing::ZEROS;
+    for (x_a, x_b) in a_packed.iter().zip(b_packed) {
+        acc += *x_a * *x_b;
+    }
+    let packed_sum: F = acc.as_slice().iter().copied().sum();
+    let leftovers_sum: F = a_leftovers
+        .iter()
+        .zip(b_leftovers)
+        .map(|(&x_a, &x_b)| x_a * x_b)
+        .sum();
+    packed_sum + leftovers_sum
+}
+
+/// Views a slice of quadratic extension elements as the slice of their coordinates.
+fn flatten_quadratic<F: Extendable<2>>(slice: &[QuadraticExtension<F>]) -> &[F] {
+    // Safety: `QuadraticExtension<F>` is `repr(transparent)` over `[F; 2]`.
+    unsafe { slice::from_raw_parts(slice.as_ptr().cast(), slice.len() * 2) }
+}
+
+fn flatten_quadratic_mut<F: Extendable<2>>(slice: &mut [QuadraticExtension<F>]) -> &mut [F] {
+    // Safety: `QuadraticExtension<F>` is `repr(transparent)` over `[F; 2]`.
+    unsafe { slice::from_raw_parts_mut(slice.as_mut_ptr().cast(), slice.len() * 2) }
+}
+
+/// Splits a slice of quadratic extension elements into chunks of `P::WIDTH` elements, leaving
+/// leftovers as scalars.
+fn chunk_quadratic_with_leftovers<P: PackedField>(
+    slice: &[QuadraticExtension<P::Scalar>],
+) -> (
+    ChunksExact<'_, QuadraticExtension<P::Scalar>>,
+    &[QuadraticExtension<P::Scalar>],
+)
+where
+    P::Scalar: Extendable<2>,
+{
+    let split_point = slice.len() - slice.len() % P::WIDTH;
+    let (slice_packable, slice_leftovers) = slice.split_at(split_point);
+    (slice_packable.chunks_exact(P::WIDTH), slice_leftovers)
+}
+
+fn chunk_quadratic_with_leftovers_mut<P: PackedField>(
+    slice: &mut [QuadraticExtension<P::Scalar>],
+) -> (
+    ChunksExactMut<'_, QuadraticExtension<P::Scalar>>,
+    &mut [QuadraticExtension<P::Scalar>],
+)
+where
+    P::Scalar: Extendable<2>,
+{
+    let split_point = slice.len() - slice.len() % P::WIDTH;
+    let (slice_packable, slice_leftovers) = slice.split_at_mut(split_point);
+    (slice_packable.chunks_exact_mut(P::WIDTH), slice_leftovers)
+}
+
+/// Gathers `P::WIDTH` quadratic extension elements into one packed vector per coordinate.
+fn unzip_quadratic<P: PackedField>(chunk: &[QuadraticExtension<P::Scalar>]) -> [P; 2]
+where
+    P::Scalar: Extendable<2>,
+{
+    let mut res = [P::ZEROS; 2];
+    for (i, x) in chunk.iter().enumerate() {
+        res[0].as_slice_mut()[i] = x.0[0];
+        res[1].as_slice_mut()[i] = x.0[1];
+    }
+    res
+}
+
+/// Inverse of `unzip_quadratic`.
+fn zip_quadratic<P: PackedField>(packed: [P; 2], chunk: &mut [QuadraticExtension<P::Scalar>])
+where
+    P::Scalar: Extendable<2>,
+{
+    for (i, x) in chunk.iter_mut().enumerate() {
+        *x = QuadraticExtension([packed[0].as_slice()[i], packed[1].as_slice()[i]]);
+    }
+}
+
+/// Lanewise product of quadratic extension elements given by their packed coordinates.
+fn mul_quadratic<P: PackedField>(a: [P; 2], b: [P; 2]) -> [P; 2]
+where
+    P::Scalar: Extendable<2>,
+{
+    let [a0, a1] = a;
+    let [b0, b1] = b;
+    [
+        a0 * b0 + a1 * b1 * <P::Scalar as Extendable<2>>::W,
+        a0 * b1 + a1 * b0,
+    ]
+}
+
+/// Like `batch_add_inplace`, for quadratic extension elements.
+pub fn batch_add_extension_inplace<F: Extendable<2>>(
+    out: &mut [QuadraticExtension<F>],
+    a: &[QuadraticExtension<F>],
+) {
+    assert_eq!(out.len(), a.len(), "both arrays must have the same length");
+    batch_add_inplace(flatten_quadratic_mut(out), flatten_quadratic(a));
+}
+
+/// Like `batch_sub_inplace`, for quadratic extension elements.
+pub fn batch_sub_extension_inplace<F: Extendable<2>>(
+    out: &mut [QuadraticExtension<F>],
+    a: &[QuadraticExtension<F>],
+) {
+    assert_eq!(out.len(), a.len(), "both arrays must have the same length");
+    batch_sub_inplace(flatten_quadratic_mut(out), flatten_quadratic(a));
+}
+
+/// Like `batch_multiply_inplace`, for quadratic extension elements.
+pub fn batch_multiply_extension_inplace<F: Extendable<2>>(
+    out: &mut [QuadraticExtension<F>],
+    a: &[QuadraticExtension<F>],
+) {
+    type P<F> = <F as Packable>::Packing;
+    assert_eq!(out.len(), a.len(), "both arrays must have the same length");
+
+    let (out_chunks, out_leftovers) = chunk_quadratic_with_leftovers_mut::<P<F>>(out);
+    let (a_chunks, a_leftovers) = chunk_quadratic_with_leftovers::<P<F>>(a);
+
+    for (chunk_out, chunk_a) in out_chunks.zip(a_chunks) {
+        let x_out = unzip_quadratic::<P<F>>(chunk_out);
+        let x_a = unzip_quadratic::<P<F>>(chunk_a);
+        zip_quadratic(mul_quadratic(x_out, x_a), chunk_out);
+    }
+    for (x_out, x_a) in out_leftovers.iter_mut().zip(a_leftovers) {
+        *x_out *= *x_a;
+    }
+}
+
+/// Like `batch_multiply_add_inplace`, for quadratic extension elements.
+pub fn batch_multiply_add_extension_inplace<F: Extendable<2>>(
+    out: &mut [QuadraticExtension<F>],
+    a: &[QuadraticExtension<F>],
+    b: &[QuadraticExtension<F>],
+) {
+    type P<F> = <F as Packable>::Packing;
+    let n = out.len();
+    assert_eq!(n, a.len(), "all arrays must have the same length");
+    assert_eq!(n, b.len(), "all arrays must have the same length");
+
+    let (out_chunks, out_leftovers) = chunk_quadratic_with_leftovers_mut::<P<F>>(out);
+    let (a_chunks, a_leftovers) = chunk_quadratic_with_leftovers::<P<F>>(a);
+    let (b_chunks, b_leftovers) = chunk_quadratic_with_leftovers::<P<F>>(b);
+
+    for ((chunk_out, chunk_a), chunk_b) in out_chunks.zip(a_chunks).zip(b_chunks) {
+        let [out0, out1] = unzip_quadratic::<P<F>>(chunk_out);
+        let [ab0, ab1] = mul_quadratic(
+            unzip_quadratic::<P<F>>(chunk_a),
+            unzip_quadratic::<P<F>>(chunk_b),
+        );
+        zip_quadratic([out0 + ab0, out1 + ab1], chunk_out);
+    }
+    for ((x_out, x_a), x_b) in out_leftovers.iter_mut().zip(a_leftovers).zip(b_leftovers) {
+        *x_out += *x_a * *x_b;
+    }
+}
+
+/// Like `batch_scalar_multiply_add_inplace`, for quadratic extension elements.
+pub fn batch_scalar_multiply_add_extension_inplace<F: Extendable<2>>(
+    out: &mut [QuadraticExtension<F>],
+    c: QuadraticExtension<F>,
+    a: &[QuadraticExtension<F>],
+) {
+    type P<F> = <F as Packable>::Packing;
+    assert_eq!(out.len(), a.len(), "both arrays must have the same length");
+
+    let c_packed = c.0.map(P::<F>::from);
+    let (out_chunks, out_leftovers) = chunk_quadratic_with_leftovers_mut::<P<F>>(out);
+    let (a_chunks, a_leftovers) = chunk_quadratic_with_leftovers::<P<F>>(a);
+
+    for (chunk_out, chunk_a) in out_chunks.zip(a_chunks) {
+        let [out0, out1] = unzip_quadratic::<P<F>>(chunk_out);
+        let [ca0, ca1] = mul_quadratic(c_packed, unzip_quadratic::<P<F>>(chunk_a));
+        zip_quadratic([out0 + ca0, out1 + ca1], chunk_out);
+    }
+    for (x_out, x_a) in out_leftovers.iter_mut().zip(a_leftovers) {
+        *x_out += c * *x_a;
+    }
+}
+
+/// Like `batch_powers_fill`, for quadratic extension elements.
+pub fn batch_powers_fill_extension<F: Extendable<2>>(
+    out: &mut [QuadraticExtension<F>],
+    base: QuadraticExtension<F>,
+) {
+    type P<F> = <F as Packable>::Packing;
+
+    let mut current = [P::<F>::ZEROS; 2];
+    for (i, power) in base.powers().take(P::<F>::WIDTH).enumerate() {
+        current[0].as_slice_mut()[i] = power.0[0];
+        current[1].as_slice_mut()[i] = power.0[1];
+    }
+    let step = base.exp_u64(P::<F>::WIDTH as u64).0.map(P::<F>::from);
+
+    let (out_chunks, out_leftovers) = chunk_quadratic_with_leftovers_mut::<P<F>>(out);
+    for chunk_out in out_chunks {
+        zip_quadratic(current, chunk_out);
+        current = mul_quadratic(current, step);
+    }
+    zip_quadratic(current, out_leftovers);
+}
+
+/// Like `batch_invert_inplace`, for quadratic extension elements.
+///
+/// Panics if any element is zero.
+pub fn batch_invert_extension_inplace<F: Extendable<2>>(values: &mut [QuadraticExtension<F>]) {
+    type P<F> = <F as Packable>::Packing;
+
+    // `prefix_products[i]` holds the lanewise product of the first `i` chunks.
+    let (chunks, _) = chunk_quadratic_with_leftovers::<P<F>>(values);
+    let mut prefix_products = Vec::with_capacity(chunks.len());
+    let mut acc = [P::<F>::ONES, P::<F>::ZEROS];
+    for chunk in chunks {
+        prefix_products.push(acc);
+        acc = mul_quadratic(acc, unzip_quadratic::<P<F>>(chunk));
+    }
+
+    let (chunks, leftovers) = chunk_quadratic_with_leftovers_mut::<P<F>>(values);
+    if !prefix_products.is_empty() {
+        // This is where the lane chains meet.
+        let mut lanes = vec![QuadraticExtension::<F>::ZERO; P::<F>::WIDTH];
+        zip_quadratic(acc, &mut lanes);
+        let lane_inverses = QuadraticExtension::<F>::batch_multiplicative_inverse(&lanes);
+        let mut acc_inv = unzip_quadratic::<P<F>>(&lane_inverses);
+        for (chunk, &prefix_product) in chunks.rev().zip(prefix_products.iter().rev()) {
+            let x = unzip_quadratic::<P<F>>(chunk);
+            zip_quadratic(mul_quadratic(acc_inv, prefix_product), chunk);
+            acc_inv = mul_quadratic(acc_inv, x);
+        }
+    }
+
+    let leftover_inverses = QuadraticExtension::<F>::batch_multiplicative_inverse(leftovers);
+    leftovers.copy_from_slice(&leftover_inverses);
+}
+
+/// Like `inner_product`, for quadratic extension elements.
+pub fn inner_product_extension<F: Extendable<2>>(
+    a: &[QuadraticExtension<F>],
+    b: &[QuadraticExtension<F>],
+) -> QuadraticExtension<F> {
+    type P<F> = <F as Packable>::Packing;
+    assert_eq!(a.len(), b.len(), "both arrays must have the same length");
+
+    let (a_chunks, a_leftovers) = chunk_quadratic_with_leftovers::<P<F>>(a);
+    let (b_chunks, b_leftovers) = chunk_quadratic_with_leftovers::<P<F>>(b);
+
+    let mut acc = [P::<F>::ZEROS; 2];
+    for (chunk_a, chunk_b) in a_chunks.zip(b_chunks) {
+        let [ab0, ab1] = mul_quadratic(
+            unzip_quadratic::<P<F>>(chunk_a),
+            unzip_quadratic::<P<F>>(chunk_b),
+        );
+        acc[0] += ab0;
+        acc[1] += ab1;
+    }
+    let packed_sum = QuadraticExtension(acc.map(|x| x.as_slice().iter().copied().sum()));
+    let leftovers_sum: QuadraticExtension<F> = a_leftovers
+        .iter()
+        .zip(b_leftovers)
+        .map(|(&x_a, &x_b)| x_a * x_b)
+        .sum();
+    packed_sum + leftovers_sum
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::goldilocks_field::GoldilocksField;
+    use crate::types::Sample;
+
+    type F = GoldilocksField;
+    type FF = QuadraticExtension<GoldilocksField>;
+
+    // Covers empty slices, slices shorter than a packing and slices with leftovers.
+    const LENGTHS: [usize; 5] = [0, 1, 3, 8, 21];
+
+    #[test]
+    fn test_batch_ops() {
+        for n in LENGTHS {
+            let a = F::rand_vec(n);
+            let b = F::rand_vec(n);
+            let c = F::rand();
+            let init = F::rand_vec(n);
+
+            let mut out = init.clone();
+            batch_sub_inplace(&mut out, &a);
+            assert!(out
+                .iter()
+                .zip(&init)
+                .zip(&a)
+                .all(|((&o, &i), &x)| o == i - x));
+
+            let mut out = init.clone();
+            batch_scalar_multiply_inplace(&mut out, c);
+            assert!(out.iter().zip(&init).all(|(&o, &i)| o == i * c));
+
+            let mut out = init.clone();
+            batch_multiply_add_inplace(&mut out, &a, &b);
+            assert!(out
+                .iter()
+                .zip(&init)
+                .zip(a.iter().zip(&b))
+                .all(|((&o, &i), (&x, &y))| o == i + x * y));
+
+            let mut out = init.clone();
+            batch_scalar_multiply_add_inplace(&mut out, c, &a);
+            assert!(out
+                .iter()
+                .zip(&init)
+                .zip(&a)
+                .all(|((&o, &i), &x)| o == i + c * x));
+
+            let mut out = vec![F::ZERO; n];
+            batch_powers_fill(&mut out, c);
+            assert_eq!(out, c.powers().take(n).collect::<Vec<_>>());
+
+            let mut out = init.clone();
+            batch_invert_inplace(&mut out);
+            assert_eq!(out, F::batch_multiplicative_inverse(&init));
+
+            let expected: F = a.iter().zip(&b).map(|(&x, &y)| x * y).sum();
+            assert_eq!(inner_product(&a, &b), expected);
+        }
+    }
+
+    #[test]
+    fn test_batch_ops_extension() {
+        for n in LENGTHS {
+            let a = FF::rand_vec(n);
+            let b = FF::rand_vec(n);
+            let c = FF::rand();
+            let init = FF::rand_vec(n);
+
+            let mut out = init.clone();
+            batch_add_extension_inplace(&mut out, &a);
+            assert!(out
+                .iter()
+                .zip(&init)
+                .zip(&a)
+                .all(|((&o, &i), &x)| o == i + x));
+
+            let mut out = init.clone();
+            batch_sub_extension_inplace(&mut out, &a);
+            assert!(out
+                .iter()
+                .zip(&init)
+                .zip(&a)
+                .all(|((&o, &i), &x)| o == i - x));
+
+            let mut out = init.clone();
+            batch_multiply_extension_inplace(&mut out, &a);
+            assert!(out
+                .iter()
+                .zip(&init)
+                .zip(&a)
+                .all(|((&o, &i), &x)| o == i * x));
+
+            let mut out = init.clone();
+            batch_multiply_add_extension_inplace(&mut out, &a, &b);
+            assert!(out
+                .iter()
+                .zip(&init)
+                .zip(a.iter().zip(&b))
+                .all(|((&o, &i), (&x, &y))| o == i + x * y));
+
+            let mut out = init.clone();
+            batch_scalar_multiply_add_extension_inplace(&mut out, c, &a);
+            assert!(out
+                .iter()
+                .zip(&init)
+                .zip(&a)
+                .all(|((&o, &i), &x)| o == i + c * x));
+
+            let mut out = vec![FF::ZERO; n];
+            batch_powers_fill_extension(&mut out, c);
+            assert_eq!(out, c.powers().take(n).collect::<Vec<_>>());
+
+            let mut out = init.clone();
+            batch_invert_extension_inplace(&mut out);
+            assert_eq!(out, FF::batch_multiplicative_inverse(&init));
+
+            let expected: FF = a.iter().zip(&b).map(|(&x, &y)| x * y).sum();
+            assert_eq!(inner_product_extension(&a, &b), expected);
+        }
+    }
+}
@@ -0,0 +1,410 @@
+//! Constant-time inversion, square roots and exponentiation, for use on secret values such as
+//! signing keys during witness generation.
+//!
+//! The `Field` methods of the same names branch on their inputs, and the arithmetic of the
+//! secp256k1 fields goes through `BigUint`, so they should only be used on public data. The
+//! methods of [`ConstantTimeField`] only branch on public quantities such as the field order or
+//! the number of limbs of an exponent. This is a source-level guarantee; it relies on the compiler
+//! lowering masked selections and carry arithmetic to branch-free instructions.
+
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq, CtOption};
+
+use crate::extension::quadratic::QuadraticExtension;
+use crate::extension::Extendable;
+use crate::goldilocks_field::{GoldilocksField, EPSILON};
+use crate::secp256k1_base::Secp256K1Base;
+use crate::secp256k1_scalar::Secp256K1Scalar;
+use crate::types::{Field, Field64};
+
+/// A field with constant-time arithmetic. Only `ct_add` and `ct_mul` are field specific; the
+/// remaining methods are built from them and from `subtle`'s selection and equality.
+pub trait ConstantTimeField: Field + ConditionallySelectable + ConstantTimeEq {
+    /// Returns `self + rhs` in constant time.
+    fn ct_add(&self, rhs: &Self) -> Self;
+
+    /// Returns `self * rhs` in constant time.
+    fn ct_mul(&self, rhs: &Self) -> Self;
+
+    fn ct_square(&self) -> Self {
+        self.ct_mul(self)
+    }
+
+    /// Returns `self^power`, where `power` is given as little-endian `u64` limbs. The running time
+    /// depends only on `power.len()`.
+    fn ct_exp_limbs(&self, power: &[u64]) -> Self {
+        let mut result = Self::ONE;
+        for &limb in power.iter().rev() {
+            for i in (0..64).rev() {
+                result = result.ct_square();
+                let product = result.ct_mul(self);
+                let bit = Choice::from(((limb >> i) & 1) as u8);
+                result = Self::conditional_select(&result, &product, bit);
+            }
+        }
+        result
+    }
+
+    /// Returns `self^power`, always performing 64 squarings and multiplications.
+    fn ct_exp_u64(&self, power: u64) -> Self {
+        self.ct_exp_limbs(&[power])
+    }
+
+    /// Returns the inverse of `self`, computed as `self^(|F| - 2)`, or none if `self` is zero.
+    fn ct_inverse(&self) -> CtOption<Self> {
+        let power = Self::order() - 2u32;
+        let inverse = self.ct_exp_limbs(&power.to_u64_digits());
+        CtOption::new(inverse, !self.ct_eq(&Self::ZERO))
+    }
+
+    /// Returns a square root of `self`, or none if `self` is not a square. This is the
+    /// constant-time Tonelli-Shanks variant of RFC 9380, appendix I.4.
+    fn ct_sqrt(&self) -> CtOption<Self> {
+        let s = Self::TWO_ADICITY;
+        let odd_part = (Self::order() - 1u32) >> s;
+        let power = (odd_part - 1u32) >> 1usize;
+
+        let mut z = self.ct_exp_limbs(&power.to_u64_digits());
+        let mut t = z.ct_square().ct_mul(self);
+        z = z.ct_mul(self);
+        let mut b = t;
+        let mut c = Self::POWER_OF_TWO_GENERATOR;
+        for i in (2..=s).rev() {
+            for _ in 2..i {
+                b = b.ct_square();
+            }
+            let is_one = b.ct_eq(&Self::ONE);
+            z = Self::conditional_select(&z.ct_mul(&c), &z, is_one);
+            c = c.ct_square();
+            t = Self::conditional_select(&t.ct_mul(&c), &t, is_one);
+            b = t;
+        }
+
+        let is_square = z.ct_square().ct_eq(self);
+        CtOption::new(z, is_square)
+    }
+}
+
+/// Reduces a Goldilocks element to canonical form without branching.
+fn goldilocks_canonical(x: u64) -> u64 {
+    let (reduced, borrow) = x.overflowing_sub(GoldilocksField::ORDER);
+    u64::conditional_select(&reduced, &x, Choice::from(borrow as u8))
+}
+
+impl ConditionallySelectable for GoldilocksField {
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        Self(u64::conditional_select(&a.0, &b.0, choice))
+    }
+}
+
+impl ConstantTimeEq for GoldilocksField {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        goldilocks_canonical(self.0).ct_eq(&goldilocks_canonical(other.0))
+    }
+}
+
+impl ConstantTimeField for GoldilocksField {
+    fn ct_add(&self, rhs: &Self) -> Self {
+        // Each overflow is corrected by adding `2^64 - P = EPSILON`. The second correction cannot
+        // overflow, since the sum it corrects has wrapped around to below `EPSILON`.
+        let (sum, over) = self.0.overflowing_add(rhs.0);
+        let (sum, over) = sum.overflowing_add(EPSILON * over as u64);
+        Self(sum.wrapping_add(EPSILON * over as u64))
+    }
+
+    fn ct_mul(&self, rhs: &Self) -> Self {
+        // Same reduction as `reduce128`, with the borrow and carry corrections done by
+        // multiplication instead of branches.
+        let x = (self.0 as u128) * (rhs.0 as u128);
+        let (x_lo, x_hi) = (x as u64, (x >> 64) as u64);
+        let x_hi_hi = x_hi >> 32;
+        let x_hi_lo = x_hi & EPSILON;
+
+        let (t0, borrow) = x_lo.overflowing_sub(x_hi_hi);
+        let t0 = t0.wrapping_sub(EPSILON * borrow as u64);
+        let t1 = x_hi_lo * EPSILON;
+        let (sum, carry) = t0.overflowing_add(t1);
+        Self(sum.wrapping_add(EPSILON * carry as u64))
+    }
+}
+
+impl<F: ConstantTimeField + Extendable<2>> ConditionallySelectable for QuadraticExtension<F> {
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        Self([
+            F::conditional_select(&a.0[0], &b.0[0], choice),
+            F::conditional_select(&a.0[1], &b.0[1], choice),
+        ])
+    }
+}
+
+impl<F: ConstantTimeField + Extendable<2>> ConstantTimeEq for QuadraticExtension<F> {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.0[0].ct_eq(&other.0[0]) & self.0[1].ct_eq(&other.0[1])
+    }
+}
+
+impl<F: ConstantTimeField + Extendable<2>> ConstantTimeField for QuadraticExtension<F> {
+    fn ct_add(&self, rhs: &Self) -> Self {
+        Self([self.0[0].ct_add(&rhs.0[0]), self.0[1].ct_add(&rhs.0[1])])
+    }
+
+    fn ct_mul(&self, rhs: &Self) -> Self {
+        let [a0, a1] = self.0;
+        let [b0, b1] = rhs.0;
+        let c0 = a0.ct_mul(&b0).ct_add(&F::W.ct_mul(&a1.ct_mul(&b1)));
+        let c1 = a0.ct_mul(&b1).ct_add(&a1.ct_mul(&b0));
+        Self([c0, c1])
+    }
+}
+
+/// A 256-bit odd modulus with the constants needed for Montgomery multiplication.
+struct Modulus256 {
+    limbs: [u64; 4],
+    /// `-limbs^-1 mod 2^64`.
+    inv: u64,
+    /// `2^512 mod limbs`.
+    r2: [u64; 4],
+}
+
+/// Returns `a + b * c + carry` as a low and a high limb.
+#[inline]
+const fn mac(a: u64, b: u64, c: u64, carry: u64) -> (u64, u64) {
+    let t = (a as u128) + (b as u128) * (c as u128) + (carry as u128);
+    (t as u64, (t >> 64) as u64)
+}
+
+fn add_256(a: &[u64; 4], b: &[u64; 4]) -> ([u64; 4], bool) {
+    let mut result = [0; 4];
+    let mut carry = false;
+    for i in 0..4 {
+        let (sum, c0) = a[i].overflowing_add(b[i]);
+        let (sum, c1) = sum.overflowing_add(carry as u64);
+        result[i] = sum;
+        carry = c0 | c1;
+    }
+    (result, carry)
+}
+
+fn sub_256(a: &[u64; 4], b: &[u64; 4]) -> ([u64; 4], bool) {
+    let mut result = [0; 4];
+    let mut borrow = false;
+    for i in 0..4 {
+        let (diff, b0) = a[i].overflowing_sub(b[i]);
+        let (diff, b1) = diff.overflowing_sub(borrow as u64);
+        result[i] = diff;
+        borrow = b0 | b1;
+    }
+    (result, borrow)
+}
+
+fn select_256(a: &[u64; 4], b: &[u64; 4], choice: Choice) -> [u64; 4] {
+    core::array::from_fn(|i| u64::conditional_select(&a[i], &b[i], choice))
+}
+
+impl Modulus256 {
+    /// Subtracts the modulus from `x`, or from `x + 2^256` if `overflow` is set, unless the result
+    /// would be negative. Returns a canonical value if `x + overflow * 2^256` is below twice the
+    /// modulus.
+    fn subtract_if_needed(&self, x: &[u64; 4], overflow: bool) -> [u64; 4] {
+        let (diff, borrow) = sub_256(x, &self.limbs);
+        let keep = Choice::from((borrow & !overflow) as u8);
+        select_256(&diff, x, keep)
+    }
+
+    /// Reduces `x` to canonical form. Both secp256k1 moduli exceed `2^255`, so one subtraction is
+    /// enough.
+    fn canonicalize(&self, x: &[u64; 4]) -> [u64; 4] {
+        self.subtract_if_needed(x, false)
+    }
+
+    fn add(&self, a: &[u64; 4], b: &[u64; 4]) -> [u64; 4] {
+        let (sum, carry) = add_256(&self.canonicalize(a), &self.canonicalize(b));
+        self.subtract_if_needed(&sum, carry)
+    }
+
+    /// Returns `a * b * 2^-256`, using the CIOS method with canonical inputs.
+    fn montgomery_mul(&self, a: &[u64; 4], b: &[u64; 4]) -> [u64; 4] {
+        let m = &self.limbs;
+        let mut t = [0u64; 4];
+        let mut t_hi = 0u64;
+        for &b_i in b {
+            let mut carry = 0;
+            for j in 0..4 {
+                (t[j], carry) = mac(t[j], a[j], b_i, carry);
+            }
+            let (t4, overflow) = t_hi.overflowing_add(carry);
+
+            let q = t[0].wrapping_mul(self.inv);
+            let (_, mut carry) = mac(t[0], q, m[0], 0);
+            for j in 1..4 {
+                (t[j - 1], carry) = mac(t[j], q, m[j], carry);
+            }
+            let (top, overflow_2) = t4.overflowing_add(carry);
+            t[3] = top;
+            t_hi = (overflow as u64) + (overflow_2 as u64);
+        }
+        self.subtract_if_needed(&t, t_hi != 0)
+    }
+
+    fn mul(&self, a: &[u64; 4], b: &[u64; 4]) -> [u64; 4] {
+        let product = self.montgomery_mul(&self.canonicalize(a), &self.canonicalize(b));
+        self.montgomery_mul(&product, &self.r2)
+    }
+
+    fn ct_eq(&self, a: &[u64; 4], b: &[u64; 4]) -> Choice {
+        let (a, b) = (self.canonicalize(a), self.canonicalize(b));
+        (0..4).fold(Choice::from(1), |acc, i| acc & a[i].ct_eq(&b[i]))
+    }
+}
+
+const SECP256K1_BASE_MODULUS: Modulus256 = Modulus256 {
+    limbs: [
+        0xFFFFFFFEFFFFFC2F,
+        0xFFFFFFFFFFFFFFFF,
+        0xFFFFFFFFFFFFFFFF,
+        0xFFFFFFFFFFFFFFFF,
+    ],
+    inv: 0xD838091DD2253531,
+    r2: [0x000007A2000E90A1, 0x1, 0x0, 0x0],
+};
+
+const SECP256K1_SCALAR_MODULUS: Modulus256 = Modulus256 {
+    limbs: [
+        0xBFD25E8CD0364141,
+        0xBAAEDCE6AF48A03B,
+        0xFFFFFFFFFFFFFFFE,
+        0xFFFFFFFFFFFFFFFF,
+    ],
+    inv: 0x4B0DFF665588B13F,
+    r2: [
+        0x896CF21467D7D140,
+        0x741496C20E7CF878,
+        0xE697F5E45BCD07C6,
+        0x9D671CD581C69BC5,
+    ],
+};
+
+macro_rules! impl_constant_time_256 {
+    ($field:ty, $modulus:expr) => {
+        impl ConditionallySelectable for $field {
+            fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+                Self(select_256(&a.0, &b.0, choice))
+            }
+        }
+
+        impl ConstantTimeEq for $field {
+            fn ct_eq(&self, other: &Self) -> Choice {
+                $modulus.ct_eq(&self.0, &other.0)
+            }
+        }
+
+        impl ConstantTimeField for $field {
+            fn ct_add(&self, rhs: &Self) -> Self {
+                Self($modulus.add(&self.0, &rhs.0))
+            }
+
+            fn ct_mul(&self, rhs: &Self) -> Self {
+                Self($modulus.mul(&self.0, &rhs.0))
+            }
+        }
+    };
+}
+
+impl_constant_time_256!(Secp256K1Base, SECP256K1_BASE_MODULUS);
+impl_constant_time_256!(Secp256K1Scalar, SECP256K1_SCALAR_MODULUS);
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::OsRng;
+    use rand::Rng;
+
+    use super::*;
+    use crate::types::{PrimeField, Sample};
+
+    fn check_arithmetic<F: ConstantTimeField + Sample>() {
+        for _ in 0..20 {
+            let (x, y) = (F::rand(), F::rand());
+            assert_eq!(x.ct_add(&y), x + y);
+            assert_eq!(x.ct_mul(&y), x * y);
+            assert_eq!(x.ct_square(), x.square());
+            assert!(bool::from(x.ct_eq(&x)));
+            assert_eq!(bool::from(x.ct_eq(&y)), x == y);
+
+            let k = OsRng.gen::<u64>();
+            assert_eq!(x.ct_exp_u64(k), x.exp_u64(k));
+            assert_eq!(x.ct_exp_u64(0), F::ONE);
+            assert_eq!(Option::<F>::from(x.ct_inverse()), x.try_inverse());
+        }
+        assert!(bool::from(F::ZERO.ct_inverse().is_none()));
+        assert_eq!(Option::<F>::from(F::ONE.ct_inverse()), Some(F::ONE));
+    }
+
+    fn check_sqrt<F: ConstantTimeField + Sample>() {
+        for _ in 0..20 {
+            let square = F::rand().square();
+            let root = Option::<F>::from(square.ct_sqrt()).unwrap();
+            assert_eq!(root.square(), square);
+        }
+        assert_eq!(Option::<F>::from(F::ZERO.ct_sqrt()), Some(F::ZERO));
+        // The multiplicative generator is never a square.
+        assert!(bool::from(
+            F::MULTIPLICATIVE_GROUP_GENERATOR.ct_sqrt().is_none()
+        ));
+    }
+
+    fn check_sqrt_matches<F: ConstantTimeField + PrimeField + Sample>() {
+        for _ in 0..20 {
+            let x = F::rand();
+            let ct_root = Option::<F>::from(x.ct_sqrt());
+            match x.sqrt() {
+                Some(root) => assert!(ct_root == Some(root) || ct_root == Some(-root)),
+                None => assert_eq!(ct_root, None),
+            }
+        }
+    }
+
+    #[test]
+    fn test_goldilocks() {
+        type F = GoldilocksField;
+        check_arithmetic::<F>();
+        check_sqrt::<F>();
+        check_sqrt_matches::<F>();
+
+        // Non-canonical representatives and the overflowing cases of addition.
+        let neg_one = F::NEG_ONE;
+        let noncanonical = GoldilocksField(F::ORDER + 5);
+        assert_eq!(neg_one.ct_add(&neg_one), neg_one + neg_one);
+        assert_eq!(noncanonical.ct_add(&neg_one), noncanonical + neg_one);
+        assert_eq!(
+            noncanonical.ct_mul(&noncanonical),
+            F::from_canonical_u64(25)
+        );
+        assert!(bool::from(noncanonical.ct_eq(&F::from_canonical_u64(5))));
+    }
+
+    #[test]
+    fn test_quadratic_extension() {
+        type F = QuadraticExtension<GoldilocksField>;
+        check_arithmetic::<F>();
+        check_sqrt::<F>();
+    }
+
+    #[test]
+    fn test_secp256k1_base() {
+        type F = Secp256K1Base;
+        check_arithmetic::<F>();
+        check_sqrt::<F>();
+        check_sqrt_matches::<F>();
+        assert_eq!(F::NEG_ONE.ct_add(&F::NEG_ONE), F::NEG_ONE + F::NEG_ONE);
+        assert_eq!(F::NEG_ONE.ct_mul(&F::NEG_ONE), F::ONE);
+    }
+
+    #[test]
+    fn test_secp256k1_scalar() {
+        type F = Secp256K1Scalar;
+        check_arithmetic::<F>();
+        check_sqrt::<F>();
+        check_sqrt_matches::<F>();
+        assert_eq!(F::NEG_ONE.ct_add(&F::NEG_ONE), F::NEG_ONE + F::NEG_ONE);
+        assert_eq!(F::NEG_ONE.ct_mul(&F::NEG_ONE), F::ONE);
+    }
+}
@@ -0,0 +1,113 @@
+use serde::{Deserialize, Serialize};
+
+use crate::bn254_base::Bn254Base;
+use crate::bn254_scalar::Bn254Scalar;
+use crate::curve::bn254_tower::Bn254Fp2;
+use crate::curve::curve_types::{AffinePoint, Curve};
+use crate::types::Field;
+
+/// The BN254 curve `y^2 = x^3 + 3` over `Bn254Base`, whose points form the group G1.
+#[derive(Debug, Copy, Clone, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct Bn254;
+
+impl Curve for Bn254 {
+    type BaseField = Bn254Base;
+    type ScalarField = Bn254Scalar;
+
+    const A: Bn254Base = Bn254Base::ZERO;
+    const B: Bn254Base = Bn254Base([3, 0, 0, 0]);
+    const GENERATOR_AFFINE: AffinePoint<Self> = AffinePoint {
+        x: Bn254Base::ONE,
+        y: Bn254Base::TWO,
+        zero: false,
+    };
+}
+
+/// The sextic twist `y^2 = x^3 + 3 / (9 + u)` of BN254 over `Bn254Fp2`, whose points of order
+/// `Bn254Scalar::order()` form the group G2.
+#[derive(Debug, Copy, Clone, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct Bn254G2;
+
+impl Curve for Bn254G2 {
+    type BaseField = Bn254Fp2;
+    type ScalarField = Bn254Scalar;
+
+    const A: Bn254Fp2 = Bn254Fp2::ZERO;
+    const B: Bn254Fp2 = Bn254Fp2([
+        Bn254Base([
+            0x3267E6DC24A138E5,
+            0xB5B4C5E559DBEFA3,
+            0x81BE18991BE06AC3,
+            0x2B149D40CEB8AAAE,
+        ]),
+        Bn254Base([
+            0xE4A2BD0685C315D2,
+            0xA74FA084E52D1852,
+            0xCD2CAFADEED8FDF4,
+            0x009713B03AF0FED4,
+        ]),
+    ]);
+    const GENERATOR_AFFINE: AffinePoint<Self> = AffinePoint {
+        x: BN254_G2_GENERATOR_X,
+        y: BN254_G2_GENERATOR_Y,
+        zero: false,
+    };
+}
+
+const BN254_G2_GENERATOR_X: Bn254Fp2 = Bn254Fp2([
+    Bn254Base([
+        0x46DEBD5CD992F6ED,
+        0x674322D4F75EDADD,
+        0x426A00665E5C4479,
+        0x1800DEEF121F1E76,
+    ]),
+    Bn254Base([
+        0x97E485B7AEF312C2,
+        0xF1AA493335A9E712,
+        0x7260BFB731FB5D25,
+        0x198E9393920D483A,
+    ]),
+]);
+
+const BN254_G2_GENERATOR_Y: Bn254Fp2 = Bn254Fp2([
+    Bn254Base([
+        0x4CE6CC0166FA7DAA,
+        0xE3D1E7690C43D37B,
+        0x4AAB71808DCB408F,
+        0x12C85EA5DB8C6DEB,
+    ]),
+    Bn254Base([
+        0x55ACDADCD122975B,
+        0xBC4B313370B38EF3,
+        0xEC9E99AD690C3395,
+        0x090689D0585FF075,
+    ]),
+]);
+
+#[cfg(test)]
+mod tests {
+    use crate::bn254_scalar::Bn254Scalar;
+    use crate::curve::bn254::{Bn254, Bn254G2};
+    use crate::curve::curve_types::{Curve, CurveScalar, ProjectivePoint};
+    use crate::types::Field;
+
+    #[test]
+    fn test_generators() {
+        assert!(Bn254::GENERATOR_AFFINE.is_valid());
+        assert!(Bn254G2::GENERATOR_AFFINE.is_valid());
+        assert!(Bn254::is_safe_curve());
+        assert!(Bn254G2::is_safe_curve());
+    }
+
+    #[test]
+    fn test_group_orders() {
+        let order_minus_one = Bn254Scalar::NEG_ONE;
+
+        let p = CurveScalar::<Bn254>(order_minus_one) * Bn254::GENERATOR_PROJECTIVE;
+        assert_eq!(p + Bn254::GENERATOR_PROJECTIVE, ProjectivePoint::ZERO);
+
+        let q = CurveScalar::<Bn254G2>(order_minus_one) * Bn254G2::GENERATOR_PROJECTIVE;
+        assert!(q.is_valid());
+        assert_eq!(q + Bn254G2::GENERATOR_PROJECTIVE, ProjectivePoint::ZERO);
+    }
+}
@@ -0,0 +1,338 @@
+//! The BN254 pairing, as a Tate pairing of a point of G1 with a point of the twist G2, with its
+//! output in `Bn254Fp12`.
+
+use crate::bn254_base::Bn254Base;
+use crate::curve::bn254::{Bn254, Bn254G2};
+use crate::curve::bn254_tower::{Bn254Fp12, Bn254Fp2, Bn254Fp6};
+use crate::curve::curve_types::AffinePoint;
+use crate::ops::Square;
+use crate::types::Field;
+
+/// The Tate pairing of a point of G1 with a point of the twist, which is bilinear and
+/// nondegenerate on the subgroups of order `Bn254Scalar::order()`.
+pub fn tate(p: AffinePoint<Bn254>, q: AffinePoint<Bn254G2>) -> Bn254Fp12 {
+    let miller_output = miller_loop(p, q);
+    final_exponentiation(miller_output)
+}
+
+/// Standard code for miller loop, can be found on page 99 at this url:
+/// <https://static1.squarespace.com/static/5fdbb09f31d71c1227082339/t/5ff394720493bd28278889c6/1609798774687/PairingsForBeginners.pdf#page=107>
+/// where BN_EXP is a hardcoding of the array of Booleans that the loop traverses
+pub fn miller_loop(p: AffinePoint<Bn254>, q: AffinePoint<Bn254G2>) -> Bn254Fp12 {
+    let mut r = p;
+    let mut acc = Bn254Fp12::ONE;
+    let mut line: Bn254Fp12;
+
+    for i in BN_EXP {
+        line = tangent(r, q);
+        r = r.double();
+        acc = line * acc * acc;
+        if i {
+            line = cord(p, r, q);
+            r = r + p;
+            acc = line * acc;
+        }
+    }
+    acc
+}
+
+/// The sloped line function for doubling a point
+fn tangent(p: AffinePoint<Bn254>, q: AffinePoint<Bn254G2>) -> Bn254Fp12 {
+    let cx = -p.x.square().triple();
+    let cy = p.y.double();
+    sparse_embed(
+        p.y.square() - Bn254Base::from_canonical_u64(9),
+        q.x.scalar_mul(cx),
+        q.y.scalar_mul(cy),
+    )
+}
+
+/// The sloped line function for adding two points
+fn cord(p1: AffinePoint<Bn254>, p2: AffinePoint<Bn254>, q: AffinePoint<Bn254G2>) -> Bn254Fp12 {
+    let cx = p2.y - p1.y;
+    let cy = p1.x - p2.x;
+    sparse_embed(
+        p1.y * p2.x - p2.y * p1.x,
+        q.x.scalar_mul(cx),
+        q.y.scalar_mul(cy),
+    )
+}
+
+/// The tangent and cord functions output sparse Fp12 elements.
+/// This map embeds the nonzero coefficients into an Fp12.
+pub const fn sparse_embed(g000: Bn254Base, g01: Bn254Fp2, g11: Bn254Fp2) -> Bn254Fp12 {
+    let g0 = Bn254Fp6([Bn254Fp2([g000, Bn254Base::ZERO]), g01, Bn254Fp2::ZERO]);
+    let g1 = Bn254Fp6([Bn254Fp2::ZERO, g11, Bn254Fp2::ZERO]);
+    Bn254Fp12([g0, g1])
+}
+
+/// The output y of the miller loop is not an invariant,
+/// but one gets an invariant by raising y to the power
+///     (p^12 - 1)/N = (p^6 - 1)(p^2 + 1)(p^4 - p^2 + 1)/N
+/// where N is the cyclic group order of the curve.
+/// To achieve this, we first exponentiate y by p^6 - 1 via
+///     y = y_6 / y
+/// and then exponentiate the result by p^2 + 1 via
+///     y = y_2 * y
+/// We then note that (p^4 - p^2 + 1)/N can be rewritten as
+///     (p^4 - p^2 + 1)/N = p^3 + (a2)p^2 - (a1)p - a0
+/// where 0 < a0, a1, a2 < p. Then the final power is given by
+///     y = y_3 * (y^a2)_2 * (y^-a1)_1 * (y^-a0)
+pub fn final_exponentiation(f: Bn254Fp12) -> Bn254Fp12 {
+    let mut y = f.frobenius(6) / f;
+    y = y.frobenius(2) * y;
+    let (y_a2, y_a1, y_a0) = get_custom_powers(y);
+    y.frobenius(3) * y_a2.frobenius(2) * y_a1.frobenius(1) * y_a0
+}
+
+/// We first together (so as to avoid repeated steps) compute
+///     y^a4, y^a2, y^a0
+/// where a1 is given by
+///     a1 = a4 + 2a2 - a0
+/// we then invert y^a0 and return
+///     y^a2, y^a1 = y^a4 * y^a2 * y^a2 * y^(-a0), y^(-a0)
+///
+/// Representing a4, a2, a0 in *little endian* binary, define
+///     BN_EXPS4 = [(a4[i], a2[i], a0[i]) for i in       0..len(a4)]
+///     BN_EXPS2 = [       (a2[i], a0[i]) for i in len(a4)..len(a2)]
+///     BN_EXPS0 = [               a0[i]  for i in len(a2)..len(a0)]
+fn get_custom_powers(f: Bn254Fp12) -> (Bn254Fp12, Bn254Fp12, Bn254Fp12) {
+    let mut sq = f;
+    let mut y0 = Bn254Fp12::ONE;
+    let mut y2 = Bn254Fp12::ONE;
+    let mut y4 = Bn254Fp12::ONE;
+
+    // proceed via standard squaring algorithm for exponentiation
+
+    // must keep multiplying all three values: a4, a2, a0
+    for (a, b, c) in BN_EXPS4 {
+        if a {
+            y4 = y4 * sq;
+        }
+        if b {
+            y2 = y2 * sq;
+        }
+        if c {
+            y0 = y0 * sq;
+        }
+        sq = sq * sq;
+    }
+    // leading term of a4 is always 1
+    y4 = y4 * sq;
+
+    // must keep multiplying remaining two values: a2, a0
+    for (a, b) in BN_EXPS2 {
+        if a {
+            y2 = y2 * sq;
+        }
+        if b {
+            y0 = y0 * sq;
+        }
+        sq = sq * sq;
+    }
+    // leading term of a2 is always 1
+    y2 = y2 * sq;
+
+    // must keep multiplying final remaining value: a0
+    for a in BN_EXPS0 {
+        if a {
+            y0 = y0 * sq;
+        }
+        sq = sq * sq;
+    }
+    // leading term of a0 is always 1
+    y0 = y0 * sq;
+
+    // invert y0 to compute y^(-a0)
+    let y0_inv = y0.inverse();
+
+    // return y^a2 = y2, y^a1 = y4 * y2^2 * y^(-a0), y^(-a0)
+    (y2, y4 * y2 * y2 * y0_inv, y0_inv)
+}
+
+const BN_EXP: [bool; 253] = [
+    true, false, false, false, false, false, true, true, false, false, true, false, false, false,
+    true, false, false, true, true, true, false, false, true, true, true, false, false, true,
+    false, true, true, true, false, false, false, false, true, false, false, true, true, false,
+    false, false, true, true, false, true, false, false, false, false, false, false, false, true,
+    false, true, false, false, true, true, false, true, true, true, false, false, false, false,
+    true, false, true, false, false, false, false, false, true, false, false, false, true, false,
+    true, true, false, true, true, false, true, true, false, true, false, false, false, false,
+    false, false, true, true, false, false, false, false, false, false, true, false, true, false,
+    true, true, false, false, false, false, true, false, true, true, true, false, true, false,
+    false, true, false, true, false, false, false, false, false, true, true, false, false, true,
+    true, true, true, true, false, true, false, false, false, false, true, false, false, true,
+    false, false, false, false, true, true, true, true, false, false, true, true, false, true,
+    true, true, false, false, true, false, true, true, true, false, false, false, false, true,
+    false, false, true, false, false, false, true, false, true, false, false, false, false, true,
+    true, true, true, true, false, false, false, false, true, true, true, true, true, false, true,
+    false, true, true, false, false, true, false, false, true, true, true, true, true, true, false,
+    false, false, false, false, false, false, false, false, false, false, false, false, false,
+    false, false, false, false, false, false, false, false, false, false, false, false, false,
+    false,
+];
+
+const BN_EXPS4: [(bool, bool, bool); 64] = [
+    (true, true, false),
+    (true, true, true),
+    (true, true, true),
+    (false, false, false),
+    (false, false, true),
+    (true, false, true),
+    (false, true, false),
+    (true, false, true),
+    (true, true, false),
+    (true, false, true),
+    (false, true, false),
+    (true, true, false),
+    (true, true, false),
+    (true, true, false),
+    (false, true, false),
+    (false, true, false),
+    (false, false, true),
+    (true, false, true),
+    (true, true, false),
+    (false, true, false),
+    (true, true, false),
+    (true, true, false),
+    (true, true, false),
+    (false, false, true),
+    (false, false, true),
+    (true, false, true),
+    (true, false, true),
+    (true, true, false),
+    (true, false, false),
+    (true, true, false),
+    (false, true, false),
+    (true, true, false),
+    (true, false, false),
+    (false, true, false),
+    (false, false, false),
+    (true, false, false),
+    (true, false, false),
+    (true, false, true),
+    (false, false, true),
+    (false, true, true),
+    (false, false, true),
+    (false, true, true),
+    (false, true, true),
+    (false, false, false),
+    (true, true, true),
+    (true, false, true),
+    (true, false, true),
+    (false, true, true),
+    (true, false, true),
+    (false, true, true),
+    (false, true, true),
+    (true, true, false),
+    (true, true, false),
+    (true, true, false),
+    (true, false, false),
+    (false, false, true),
+    (true, false, false),
+    (false, false, true),
+    (true, false, true),
+    (true, true, false),
+    (true, true, true),
+    (false, true, true),
+    (false, true, false),
+    (true, true, true),
+];
+
+const BN_EXPS2: [(bool, bool); 62] = [
+    (true, false),
+    (true, true),
+    (false, false),
+    (true, false),
+    (true, false),
+    (true, true),
+    (true, false),
+    (true, true),
+    (true, false),
+    (false, true),
+    (false, true),
+    (true, true),
+    (true, true),
+    (false, false),
+    (true, true),
+    (false, false),
+    (false, false),
+    (false, true),
+    (false, true),
+    (true, true),
+    (true, true),
+    (true, true),
+    (false, true),
+    (true, true),
+    (false, false),
+    (true, true),
+    (true, false),
+    (true, true),
+    (false, false),
+    (true, true),
+    (true, true),
+    (true, false),
+    (false, false),
+    (false, true),
+    (false, false),
+    (true, true),
+    (false, true),
+    (false, false),
+    (true, false),
+    (false, true),
+    (false, true),
+    (true, false),
+    (false, true),
+    (false, false),
+    (false, false),
+    (false, false),
+    (false, true),
+    (true, false),
+    (true, true),
+    (false, true),
+    (true, true),
+    (true, false),
+    (false, true),
+    (false, false),
+    (true, false),
+    (false, true),
+    (true, false),
+    (true, true),
+    (true, false),
+    (true, true),
+    (false, true),
+    (true, true),
+];
+
+const BN_EXPS0: [bool; 65] = [
+    false, false, true, false, false, true, true, false, true, false, true, true, true, false,
+    true, false, false, false, true, false, false, true, false, true, false, true, true, false,
+    false, false, false, false, true, false, true, false, true, true, true, false, false, true,
+    true, true, true, false, true, false, true, true, false, false, true, false, false, false,
+    true, true, true, true, false, false, true, true, false,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bn254_scalar::Bn254Scalar;
+    use crate::curve::bn254::{Bn254, Bn254G2};
+    use crate::curve::curve_types::{Curve, CurveScalar};
+    use crate::types::Sample;
+
+    #[test]
+    fn test_bilinearity() {
+        let p =
+            (CurveScalar::<Bn254>(Bn254Scalar::rand()) * Bn254::GENERATOR_PROJECTIVE).to_affine();
+        let q = (CurveScalar::<Bn254G2>(Bn254Scalar::rand()) * Bn254G2::GENERATOR_PROJECTIVE)
+            .to_affine();
+        let e = tate(p, q);
+        assert_ne!(e, Bn254Fp12::ONE);
+
+        let two_p = p.double();
+        let two_q = q.double();
+        assert_eq!(tate(two_p, q), e * e);
+        assert_eq!(tate(p, two_q), e * e);
+        assert_eq!(tate(-p, q), e.inverse());
+    }
+}
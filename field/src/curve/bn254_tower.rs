@@ -0,0 +1,759 @@
+//! The tower of extensions of `Bn254Base` used by the BN254 pairing:
+//! ```ignore
+//! Fp2  = Fp[u]  / (u^2 + 1)
+//! Fp6  = Fp2[v] / (v^3 - (9 + u))
+//! Fp12 = Fp6[w] / (w^2 - v)
+//! ```
+
+use core::fmt::{self, Debug, Display, Formatter};
+use core::iter::{Product, Sum};
+use core::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use num::bigint::BigUint;
+use num::Integer;
+use serde::{Deserialize, Serialize};
+
+use crate::bn254_base::Bn254Base;
+use crate::ops::Square;
+use crate::types::{Field, Sample};
+
+/// The quadratic extension `Fp[u] / (u^2 + 1)` of `Bn254Base`, over which the twist of BN254 is
+/// defined.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub struct Bn254Fp2(pub [Bn254Base; 2]);
+
+impl Bn254Fp2 {
+    /// The non-residue `9 + u`, whose cube root is adjoined to get `Bn254Fp6`.
+    pub const NON_RESIDUE: Self = Self([Bn254Base([9, 0, 0, 0]), Bn254Base::ONE]);
+
+    /// Returns the conjugate `a - bu` of `a + bu`. As `p = 3 mod 4`, this is also the Frobenius
+    /// map `x -> x^p`.
+    pub fn conj(&self) -> Self {
+        Self([self.0[0], -self.0[1]])
+    }
+
+    /// Returns the norm `a^2 + b^2` of `a + bu`.
+    pub fn norm(&self) -> Bn254Base {
+        self.0[0].square() + self.0[1].square()
+    }
+
+    pub fn scalar_mul(&self, scalar: Bn254Base) -> Self {
+        Self([self.0[0] * scalar, self.0[1] * scalar])
+    }
+
+    /// Multiplies by `NON_RESIDUE`.
+    pub fn mul_by_non_residue(&self) -> Self {
+        let [a, b] = self.0;
+        let nine = Bn254Base([9, 0, 0, 0]);
+        Self([nine * a - b, a + nine * b])
+    }
+
+    /// Returns `x^(p^n)`.
+    pub fn frobenius(&self, n: usize) -> Self {
+        if n % 2 == 0 {
+            *self
+        } else {
+            self.conj()
+        }
+    }
+}
+
+impl Default for Bn254Fp2 {
+    fn default() -> Self {
+        Self::ZERO
+    }
+}
+
+impl From<Bn254Base> for Bn254Fp2 {
+    fn from(x: Bn254Base) -> Self {
+        Self([x, Bn254Base::ZERO])
+    }
+}
+
+impl Sample for Bn254Fp2 {
+    #[inline]
+    fn sample<R>(rng: &mut R) -> Self
+    where
+        R: rand::RngCore + ?Sized,
+    {
+        Self([Bn254Base::sample(rng), Bn254Base::sample(rng)])
+    }
+}
+
+impl Field for Bn254Fp2 {
+    const ZERO: Self = Self([Bn254Base::ZERO; 2]);
+    const ONE: Self = Self([Bn254Base::ONE, Bn254Base::ZERO]);
+    const TWO: Self = Self([Bn254Base::TWO, Bn254Base::ZERO]);
+    const NEG_ONE: Self = Self([Bn254Base::NEG_ONE, Bn254Base::ZERO]);
+
+    // `p^2 - 1 = (p - 1)(p + 1)`, where `p - 1` has a two-adicity of 1 and `p + 1` has a
+    // two-adicity of 3.
+    const TWO_ADICITY: usize = 4;
+    const CHARACTERISTIC_TWO_ADICITY: usize = Bn254Base::TWO_ADICITY;
+
+    // Sage: `g = GF(p^2, 'u', modulus=x^2 + 1).multiplicative_generator()`, which is `5 + 4u`.
+    const MULTIPLICATIVE_GROUP_GENERATOR: Self =
+        Self([Bn254Base([5, 0, 0, 0]), Bn254Base([4, 0, 0, 0])]);
+
+    const POWER_OF_TWO_GENERATOR: Self = POW2;
+
+    const BITS: usize = Bn254Base::BITS * 2;
+
+    fn order() -> BigUint {
+        Bn254Base::order() * Bn254Base::order()
+    }
+    fn characteristic() -> BigUint {
+        Bn254Base::order()
+    }
+
+    /// The inverse of `z` is `conj(z) / norm(z)`, since `z * conj(z) = norm(z)`.
+    fn try_inverse(&self) -> Option<Self> {
+        if self.is_zero() {
+            return None;
+        }
+
+        Some(self.conj().scalar_mul(self.norm().inverse()))
+    }
+
+    fn from_noncanonical_biguint(n: BigUint) -> Self {
+        Bn254Base::from_noncanonical_biguint(n.mod_floor(&Bn254Base::order())).into()
+    }
+
+    fn from_canonical_u64(n: u64) -> Self {
+        Bn254Base::from_canonical_u64(n).into()
+    }
+
+    fn from_noncanonical_u128(n: u128) -> Self {
+        Bn254Base::from_noncanonical_u128(n).into()
+    }
+
+    fn from_noncanonical_i64(n: i64) -> Self {
+        Bn254Base::from_noncanonical_i64(n).into()
+    }
+
+    fn from_noncanonical_u64(n: u64) -> Self {
+        Bn254Base::from_noncanonical_u64(n).into()
+    }
+}
+
+impl Display for Bn254Fp2 {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{} + {}*u", self.0[0], self.0[1])
+    }
+}
+
+impl Debug for Bn254Fp2 {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+impl Neg for Bn254Fp2 {
+    type Output = Self;
+
+    #[inline]
+    fn neg(self) -> Self {
+        Self([-self.0[0], -self.0[1]])
+    }
+}
+
+impl Add for Bn254Fp2 {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, rhs: Self) -> Self {
+        Self([self.0[0] + rhs.0[0], self.0[1] + rhs.0[1]])
+    }
+}
+
+impl AddAssign for Bn254Fp2 {
+    #[inline]
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sum for Bn254Fp2 {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ZERO, |acc, x| acc + x)
+    }
+}
+
+impl Sub for Bn254Fp2 {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, rhs: Self) -> Self {
+        Self([self.0[0] - rhs.0[0], self.0[1] - rhs.0[1]])
+    }
+}
+
+impl SubAssign for Bn254Fp2 {
+    #[inline]
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl Mul for Bn254Fp2 {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, rhs: Self) -> Self {
+        let [a0, a1] = self.0;
+        let [b0, b1] = rhs.0;
+        Self([a0 * b0 - a1 * b1, a0 * b1 + a1 * b0])
+    }
+}
+
+impl MulAssign for Bn254Fp2 {
+    #[inline]
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+impl Product for Bn254Fp2 {
+    #[inline]
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ONE, |acc, x| acc * x)
+    }
+}
+
+impl Div for Bn254Fp2 {
+    type Output = Self;
+
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn div(self, rhs: Self) -> Self::Output {
+        self * rhs.inverse()
+    }
+}
+
+impl DivAssign for Bn254Fp2 {
+    fn div_assign(&mut self, rhs: Self) {
+        *self = *self / rhs;
+    }
+}
+
+/// The cubic extension `Fp2[v] / (v^3 - (9 + u))` of `Bn254Fp2`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub struct Bn254Fp6(pub [Bn254Fp2; 3]);
+
+impl Bn254Fp6 {
+    pub const ZERO: Self = Self([Bn254Fp2::ZERO; 3]);
+    pub const ONE: Self = Self([Bn254Fp2::ONE, Bn254Fp2::ZERO, Bn254Fp2::ZERO]);
+
+    pub fn scalar_mul(&self, scalar: Bn254Fp2) -> Self {
+        Self(self.0.map(|x| x * scalar))
+    }
+
+    /// Multiplies by `v`, which shifts the coefficients, the top one picking up a factor of
+    /// `9 + u`.
+    pub fn mul_by_v(&self) -> Self {
+        let [a0, a1, a2] = self.0;
+        Self([a2.mul_by_non_residue(), a0, a1])
+    }
+
+    /// Returns `x^(p^n)`. This sends `a0 + a1 v + a2 v^2` to
+    /// `a0^(p^n) + a1^(p^n) v^(p^n) + a2^(p^n) v^(2 p^n)`, where `v^(p^n) = v * FROB_V[n]` and
+    /// `v^(2 p^n) = v^2 * FROB_V2[n]`.
+    pub fn frobenius(&self, n: usize) -> Self {
+        let n = n % 6;
+        let [a0, a1, a2] = self.0.map(|x| x.frobenius(n));
+        Self([a0, a1 * FROB_V[n], a2 * FROB_V2[n]])
+    }
+
+    /// Algorithm 17 in <https://eprint.iacr.org/2010/354.pdf>.
+    pub fn inverse(&self) -> Self {
+        let [a0, a1, a2] = self.0;
+        let c0 = a0.square() - (a1 * a2).mul_by_non_residue();
+        let c1 = a2.square().mul_by_non_residue() - a0 * a1;
+        let c2 = a1.square() - a0 * a2;
+        let t = a0 * c0 + (a2 * c1 + a1 * c2).mul_by_non_residue();
+        Self([c0, c1, c2]).scalar_mul(t.inverse())
+    }
+}
+
+impl Default for Bn254Fp6 {
+    fn default() -> Self {
+        Self::ZERO
+    }
+}
+
+impl Sample for Bn254Fp6 {
+    fn sample<R>(rng: &mut R) -> Self
+    where
+        R: rand::RngCore + ?Sized,
+    {
+        Self([
+            Bn254Fp2::sample(rng),
+            Bn254Fp2::sample(rng),
+            Bn254Fp2::sample(rng),
+        ])
+    }
+}
+
+impl Neg for Bn254Fp6 {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self(self.0.map(|x| -x))
+    }
+}
+
+impl Add for Bn254Fp6 {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self([
+            self.0[0] + rhs.0[0],
+            self.0[1] + rhs.0[1],
+            self.0[2] + rhs.0[2],
+        ])
+    }
+}
+
+impl Sub for Bn254Fp6 {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self([
+            self.0[0] - rhs.0[0],
+            self.0[1] - rhs.0[1],
+            self.0[2] - rhs.0[2],
+        ])
+    }
+}
+
+impl Mul for Bn254Fp6 {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        let [a0, a1, a2] = self.0;
+        let [b0, b1, b2] = rhs.0;
+        Self([
+            a0 * b0 + (a1 * b2 + a2 * b1).mul_by_non_residue(),
+            a0 * b1 + a1 * b0 + (a2 * b2).mul_by_non_residue(),
+            a0 * b2 + a1 * b1 + a2 * b0,
+        ])
+    }
+}
+
+/// The quadratic extension `Fp6[w] / (w^2 - v)` of `Bn254Fp6`, which contains the target group of
+/// the BN254 pairing.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub struct Bn254Fp12(pub [Bn254Fp6; 2]);
+
+impl Bn254Fp12 {
+    pub const ZERO: Self = Self([Bn254Fp6::ZERO; 2]);
+    pub const ONE: Self = Self([Bn254Fp6::ONE, Bn254Fp6::ZERO]);
+
+    /// Returns the conjugate `a0 - a1 w` of `a0 + a1 w`, which is also `x^(p^6)`.
+    pub fn conj(&self) -> Self {
+        Self([self.0[0], -self.0[1]])
+    }
+
+    /// Returns `x^(p^n)`. This sends `a0 + a1 w` to `a0^(p^n) + a1^(p^n) w^(p^n)`, where
+    /// `w^(p^n) = w * FROB_W[n]`.
+    pub fn frobenius(&self, n: usize) -> Self {
+        let n = n % 12;
+        Self([
+            self.0[0].frobenius(n),
+            self.0[1].frobenius(n).scalar_mul(FROB_W[n]),
+        ])
+    }
+
+    /// The inverse of `a0 + a1 w` is `(a0 - a1 w) / (a0^2 - a1^2 v)`.
+    pub fn inverse(&self) -> Self {
+        let [a0, a1] = self.0;
+        let norm_inv = (a0 * a0 - (a1 * a1).mul_by_v()).inverse();
+        Self([a0 * norm_inv, -a1 * norm_inv])
+    }
+
+    pub fn exp_biguint(&self, power: &BigUint) -> Self {
+        let mut result = Self::ONE;
+        for &digit in power.to_u64_digits().iter().rev() {
+            for i in (0..64).rev() {
+                result = result * result;
+                if (digit >> i) & 1 == 1 {
+                    result = result * *self;
+                }
+            }
+        }
+        result
+    }
+}
+
+impl Default for Bn254Fp12 {
+    fn default() -> Self {
+        Self::ZERO
+    }
+}
+
+impl Sample for Bn254Fp12 {
+    fn sample<R>(rng: &mut R) -> Self
+    where
+        R: rand::RngCore + ?Sized,
+    {
+        Self([Bn254Fp6::sample(rng), Bn254Fp6::sample(rng)])
+    }
+}
+
+impl Neg for Bn254Fp12 {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self(self.0.map(|x| -x))
+    }
+}
+
+impl Add for Bn254Fp12 {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self([self.0[0] + rhs.0[0], self.0[1] + rhs.0[1]])
+    }
+}
+
+impl Sub for Bn254Fp12 {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self([self.0[0] - rhs.0[0], self.0[1] - rhs.0[1]])
+    }
+}
+
+impl Mul for Bn254Fp12 {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        let [a0, a1] = self.0;
+        let [b0, b1] = rhs.0;
+        let h0 = a0 * b0;
+        let h1 = a1 * b1;
+        let h01 = (a0 + a1) * (b0 + b1);
+        Self([h0 + h1.mul_by_v(), h01 - (h0 + h1)])
+    }
+}
+
+impl Div for Bn254Fp12 {
+    type Output = Self;
+
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn div(self, rhs: Self) -> Self::Output {
+        self * rhs.inverse()
+    }
+}
+
+// Sage: `g_2 = g^((p^2 - 1) / 2^4)` in `Bn254Fp2`.
+const POW2: Bn254Fp2 = Bn254Fp2([
+    Bn254Base([
+        0x911151AA341AC43B,
+        0xA7246E407F00771C,
+        0x95FDF38A582EDB43,
+        0x2FB24E8308A94437,
+    ]),
+    Bn254Base([
+        0xF450D028FE0B2DC5,
+        0x43E8C6771A5404B9,
+        0xAA7C58E18E7C4336,
+        0x254371BD70E36DBF,
+    ]),
+]);
+
+/// `FROB_V[n] = (9 + u)^((p^n - 1) / 3)`.
+const FROB_V: [Bn254Fp2; 6] = [
+    Bn254Fp2([Bn254Base::ONE, Bn254Base::ZERO]),
+    Bn254Fp2([
+        Bn254Base([
+            0x99E39557176F553D,
+            0xB78CC310C2C3330C,
+            0x4C0BEC3CF559B143,
+            0x2FB347984F7911F7,
+        ]),
+        Bn254Base([
+            0x1665D51C640FCBA2,
+            0x32AE2A1D0B7C9DCE,
+            0x4BA4CC8BD75A0794,
+            0x16C9E55061EBAE20,
+        ]),
+    ]),
+    Bn254Fp2([
+        Bn254Base([
+            0xE4BD44E5607CFD48,
+            0xC28F069FBB966E3D,
+            0x5E6DD9E7E0ACCCB0,
+            0x30644E72E131A029,
+        ]),
+        Bn254Base::ZERO,
+    ]),
+    Bn254Fp2([
+        Bn254Base([
+            0x7B746EE87BDCFB6D,
+            0x805FFD3D5D6942D3,
+            0xBAFF1C77959F25AC,
+            0x0856E078B755EF0A,
+        ]),
+        Bn254Base([
+            0x380CAB2BAAA586DE,
+            0x0FDF31BF98FF2631,
+            0xA9F30E6DEC26094F,
+            0x04F1DE41B3D1766F,
+        ]),
+    ]),
+    Bn254Fp2([
+        Bn254Base([
+            0x5763473177FFFFFE,
+            0xD4F263F1ACDB5C4F,
+            0x59E26BCEA0D48BAC,
+            0x0000000000000000,
+        ]),
+        Bn254Base::ZERO,
+    ]),
+    Bn254Fp2([
+        Bn254Base([
+            0x62E913EE1DADA9E4,
+            0xF71614D4B0B71F3A,
+            0x699582B87809D9CA,
+            0x28BE74D4BB943F51,
+        ]),
+        Bn254Base([
+            0xEDAE0BCEC9C7AAC7,
+            0x54F40EB4C3F6068D,
+            0xC2B86ABCBE01477A,
+            0x14A88AE0CB747B99,
+        ]),
+    ]),
+];
+
+/// `FROB_V2[n] = (9 + u)^(2 (p^n - 1) / 3)`.
+const FROB_V2: [Bn254Fp2; 6] = [
+    Bn254Fp2([Bn254Base::ONE, Bn254Base::ZERO]),
+    Bn254Fp2([
+        Bn254Base([
+            0x848A1F55921EA762,
+            0xD33365F7BE94EC72,
+            0x80F3C0B75A181E84,
+            0x05B54F5E64EEA801,
+        ]),
+        Bn254Base([
+            0xC13B4711CD2B8126,
+            0x3685D2EA1BDEC763,
+            0x9F3A80B03B0B1C92,
+            0x2C145EDBE7FD8AEE,
+        ]),
+    ]),
+    Bn254Fp2([
+        Bn254Base([
+            0x5763473177FFFFFE,
+            0xD4F263F1ACDB5C4F,
+            0x59E26BCEA0D48BAC,
+            0x0000000000000000,
+        ]),
+        Bn254Base::ZERO,
+    ]),
+    Bn254Fp2([
+        Bn254Base([
+            0x0E1A92BC3CCBF066,
+            0xE633094575B06BCB,
+            0x19BEE0F7B5B2444E,
+            0x0BC58C6611C08DAB,
+        ]),
+        Bn254Base([
+            0x5FE3ED9D730C239F,
+            0xA44A9E08737F96E5,
+            0xFEB0F6EF0CD21D04,
+            0x23D5E999E1910A12,
+        ]),
+    ]),
+    Bn254Fp2([
+        Bn254Base([
+            0xE4BD44E5607CFD48,
+            0xC28F069FBB966E3D,
+            0x5E6DD9E7E0ACCCB0,
+            0x30644E72E131A029,
+        ]),
+        Bn254Base::ZERO,
+    ]),
+    Bn254Fp2([
+        Bn254Base([
+            0xA97BDA050992657F,
+            0xDE1AFB54342C724F,
+            0x1D9DA40771B6F589,
+            0x1EE972AE6A826A7D,
+        ]),
+        Bn254Base([
+            0x5721E37E70C255C9,
+            0x54326430418536D1,
+            0xD2B513CDBB257724,
+            0x10DE546FF8D4AB51,
+        ]),
+    ]),
+];
+
+/// `FROB_W[n] = (9 + u)^((p^n - 1) / 6)`.
+const FROB_W: [Bn254Fp2; 12] = [
+    Bn254Fp2([Bn254Base::ONE, Bn254Base::ZERO]),
+    Bn254Fp2([
+        Bn254Base([
+            0xD60B35DADCC9E470,
+            0x5C521E08292F2176,
+            0xE8B99FDD76E68B60,
+            0x1284B71C2865A7DF,
+        ]),
+        Bn254Base([
+            0xCA5CF05F80F362AC,
+            0x747992778EEEC7E5,
+            0xA6327CFE12150B8E,
+            0x246996F3B4FAE7E6,
+        ]),
+    ]),
+    Bn254Fp2([
+        Bn254Base([
+            0xE4BD44E5607CFD49,
+            0xC28F069FBB966E3D,
+            0x5E6DD9E7E0ACCCB0,
+            0x30644E72E131A029,
+        ]),
+        Bn254Base::ZERO,
+    ]),
+    Bn254Fp2([
+        Bn254Base([
+            0xE86F7D391ED4A67F,
+            0x894CB38DBE55D24A,
+            0xEFE9608CD0ACAA90,
+            0x19DC81CFCC82E4BB,
+        ]),
+        Bn254Base([
+            0x7694AA2BF4C0C101,
+            0x7F03A5E397D439EC,
+            0x06CBEEE33576139D,
+            0x00ABF8B60BE77D73,
+        ]),
+    ]),
+    Bn254Fp2([
+        Bn254Base([
+            0xE4BD44E5607CFD48,
+            0xC28F069FBB966E3D,
+            0x5E6DD9E7E0ACCCB0,
+            0x30644E72E131A029,
+        ]),
+        Bn254Base::ZERO,
+    ]),
+    Bn254Fp2([
+        Bn254Base([
+            0x1264475E420AC20F,
+            0x2CFA95859526B0D4,
+            0x072FC0AF59C61F30,
+            0x0757CAB3A41D3CDC,
+        ]),
+        Bn254Base([
+            0xE85845E34C4A5B9C,
+            0xA20B7DFD71573C93,
+            0x18E9B79BA4E2606C,
+            0x0CA6B035381E35B6,
+        ]),
+    ]),
+    Bn254Fp2([
+        Bn254Base([
+            0x3C208C16D87CFD46,
+            0x97816A916871CA8D,
+            0xB85045B68181585D,
+            0x30644E72E131A029,
+        ]),
+        Bn254Base::ZERO,
+    ]),
+    Bn254Fp2([
+        Bn254Base([
+            0x6615563BFBB318D7,
+            0x3B2F4C893F42A916,
+            0xCF96A5D90A9ACCFD,
+            0x1DDF9756B8CBF849,
+        ]),
+        Bn254Base([
+            0x71C39BB757899A9B,
+            0x2307D819D98302A7,
+            0x121DC8B86F6C4CCF,
+            0x0BFAB77F2C36B843,
+        ]),
+    ]),
+    Bn254Fp2([
+        Bn254Base([
+            0x5763473177FFFFFE,
+            0xD4F263F1ACDB5C4F,
+            0x59E26BCEA0D48BAC,
+            0x0000000000000000,
+        ]),
+        Bn254Base::ZERO,
+    ]),
+    Bn254Fp2([
+        Bn254Base([
+            0x53B10EDDB9A856C8,
+            0x0E34B703AA1BF842,
+            0xC866E529B0D4ADCD,
+            0x1687CCA314AEBB6D,
+        ]),
+        Bn254Base([
+            0xC58BE1EAE3BC3C46,
+            0x187DC4ADD09D90A0,
+            0xB18456D34C0B44C0,
+            0x2FB855BCD54A22B6,
+        ]),
+    ]),
+    Bn254Fp2([
+        Bn254Base([
+            0x5763473177FFFFFF,
+            0xD4F263F1ACDB5C4F,
+            0x59E26BCEA0D48BAC,
+            0x0000000000000000,
+        ]),
+        Bn254Base::ZERO,
+    ]),
+    Bn254Fp2([
+        Bn254Base([
+            0x29BC44B896723B38,
+            0x6A86D50BD34B19B9,
+            0xB120850727BB392D,
+            0x290C83BF3D14634D,
+        ]),
+        Bn254Base([
+            0x53C846338C32A1AB,
+            0xF575EC93F71A8DF9,
+            0x9F668E1ADC9EF7F0,
+            0x23BD9E3DA9136A73,
+        ]),
+    ]),
+];
+
+#[cfg(test)]
+mod tests {
+    use num::BigUint;
+
+    use super::*;
+    use crate::test_field_arithmetic;
+
+    test_field_arithmetic!(crate::curve::bn254_tower::Bn254Fp2);
+
+    #[test]
+    fn test_fp6_inverse() {
+        let x = Bn254Fp6::rand();
+        assert_eq!(x * x.inverse(), Bn254Fp6::ONE);
+    }
+
+    #[test]
+    fn test_fp12_inverse() {
+        let x = Bn254Fp12::rand();
+        assert_eq!(x * x.inverse(), Bn254Fp12::ONE);
+    }
+
+    #[test]
+    fn test_fp12_frobenius() {
+        let x = Bn254Fp12::rand();
+        let p = Bn254Base::order();
+        assert_eq!(x.frobenius(1), x.exp_biguint(&p));
+        assert_eq!(x.frobenius(2), x.frobenius(1).frobenius(1));
+        assert_eq!(x.frobenius(6), x.conj());
+        assert_eq!(x.frobenius(12), x);
+        assert_eq!(x.exp_biguint(&BigUint::from(3u32)), x * x * x);
+    }
+}
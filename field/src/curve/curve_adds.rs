@@ -0,0 +1,193 @@
+use core::ops::{Add, AddAssign};
+
+use crate::curve::curve_types::{AffinePoint, Curve, ProjectivePoint};
+use crate::ops::Square;
+use crate::types::Field;
+
+impl<C: Curve> ProjectivePoint<C> {
+    /// Doubles a point, using the `dbl-2007-bl` formulas for homogeneous coordinates.
+    #[must_use]
+    pub fn double(&self) -> Self {
+        let Self { x, y, z } = *self;
+        if z.is_zero() || y.is_zero() {
+            return ProjectivePoint::ZERO;
+        }
+
+        let xx = x.square();
+        let zz = z.square();
+        let w = C::A * zz + xx.triple();
+        let s = (y * z).double();
+        let ss = s.square();
+        let sss = s * ss;
+        let r = y * s;
+        let rr = r.square();
+        let b = (x + r).square() - xx - rr;
+        let h = w.square() - b.double();
+        let x3 = h * s;
+        let y3 = w * (b - h) - rr.double();
+        let z3 = sss;
+        Self {
+            x: x3,
+            y: y3,
+            z: z3,
+        }
+    }
+}
+
+/// Adds two points, using the `add-1998-cmo-2` formulas for homogeneous coordinates.
+impl<C: Curve> Add<ProjectivePoint<C>> for ProjectivePoint<C> {
+    type Output = ProjectivePoint<C>;
+
+    fn add(self, rhs: ProjectivePoint<C>) -> Self::Output {
+        let ProjectivePoint {
+            x: x1,
+            y: y1,
+            z: z1,
+        } = self;
+        let ProjectivePoint {
+            x: x2,
+            y: y2,
+            z: z2,
+        } = rhs;
+
+        if z1.is_zero() {
+            return rhs;
+        }
+        if z2.is_zero() {
+            return self;
+        }
+
+        let x1z2 = x1 * z2;
+        let y1z2 = y1 * z2;
+        let x2z1 = x2 * z1;
+        let y2z1 = y2 * z1;
+
+        // Check if we're doubling or adding inverses.
+        if x1z2 == x2z1 {
+            if y1z2 == y2z1 {
+                return self.double();
+            }
+            return ProjectivePoint::ZERO;
+        }
+
+        let z1z2 = z1 * z2;
+        let u = y2z1 - y1z2;
+        let uu = u.square();
+        let v = x2z1 - x1z2;
+        let vv = v.square();
+        let vvv = v * vv;
+        let r = vv * x1z2;
+        let a = uu * z1z2 - vvv - r.double();
+        let x3 = v * a;
+        let y3 = u * (r - a) - vvv * y1z2;
+        let z3 = vvv * z1z2;
+        ProjectivePoint {
+            x: x3,
+            y: y3,
+            z: z3,
+        }
+    }
+}
+
+impl<C: Curve> AddAssign<ProjectivePoint<C>> for ProjectivePoint<C> {
+    fn add_assign(&mut self, rhs: ProjectivePoint<C>) {
+        *self = *self + rhs;
+    }
+}
+
+/// Adds an affine point to a projective one, using the `madd-1998-cmo` formulas.
+impl<C: Curve> Add<AffinePoint<C>> for ProjectivePoint<C> {
+    type Output = ProjectivePoint<C>;
+
+    fn add(self, rhs: AffinePoint<C>) -> Self::Output {
+        let ProjectivePoint {
+            x: x1,
+            y: y1,
+            z: z1,
+        } = self;
+        let AffinePoint {
+            x: x2,
+            y: y2,
+            zero: zero2,
+        } = rhs;
+
+        if z1.is_zero() {
+            return rhs.to_projective();
+        }
+        if zero2 {
+            return self;
+        }
+
+        let x2z1 = x2 * z1;
+        let y2z1 = y2 * z1;
+
+        // Check if we're doubling or adding inverses.
+        if x1 == x2z1 {
+            if y1 == y2z1 {
+                return self.double();
+            }
+            return ProjectivePoint::ZERO;
+        }
+
+        let u = y2z1 - y1;
+        let uu = u.square();
+        let v = x2z1 - x1;
+        let vv = v.square();
+        let vvv = v * vv;
+        let r = vv * x1;
+        let a = uu * z1 - vvv - r.double();
+        let x3 = v * a;
+        let y3 = u * (r - a) - vvv * y1;
+        let z3 = vvv * z1;
+        ProjectivePoint {
+            x: x3,
+            y: y3,
+            z: z3,
+        }
+    }
+}
+
+impl<C: Curve> AddAssign<AffinePoint<C>> for ProjectivePoint<C> {
+    fn add_assign(&mut self, rhs: AffinePoint<C>) {
+        *self = *self + rhs;
+    }
+}
+
+/// Adds two affine points, using the chord-and-tangent rule. This needs a field inversion, so
+/// projective arithmetic should be preferred for repeated additions.
+impl<C: Curve> Add<AffinePoint<C>> for AffinePoint<C> {
+    type Output = AffinePoint<C>;
+
+    fn add(self, rhs: AffinePoint<C>) -> Self::Output {
+        let AffinePoint {
+            x: x1,
+            y: y1,
+            zero: zero1,
+        } = self;
+        let AffinePoint {
+            x: x2,
+            y: y2,
+            zero: zero2,
+        } = rhs;
+
+        if zero1 {
+            return rhs;
+        }
+        if zero2 {
+            return self;
+        }
+
+        // Check if we're doubling or adding inverses.
+        if x1 == x2 {
+            if y1 == y2 {
+                return self.double();
+            }
+            return AffinePoint::ZERO;
+        }
+
+        let lambda = (y2 - y1) / (x2 - x1);
+        let x3 = lambda.square() - x1 - x2;
+        let y3 = lambda * (x1 - x3) - y1;
+        AffinePoint::nonzero(x3, y3)
+    }
+}
@@ -0,0 +1,125 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use num::BigUint;
+use plonky2_util::{ceil_div_usize, log2_ceil};
+
+use crate::curve::curve_types::{AffinePoint, Curve, ProjectivePoint};
+use crate::types::PrimeField;
+
+/// Picks the Pippenger window size for `n` terms. Each window costs `n` additions to fill the
+/// buckets and `2^w` additions to sum them, so `w` should grow like `log n`.
+fn msm_window_bits(n: usize) -> usize {
+    (log2_ceil(n) * 2 / 3).clamp(1, 16)
+}
+
+/// Splits `x` into `num_digits` little-endian digits of `window_bits` bits each.
+fn to_window_digits(x: &BigUint, window_bits: usize, num_digits: usize) -> Vec<usize> {
+    let limbs = x.to_u64_digits();
+    let bit = |i: usize| limbs.get(i / 64).map_or(0, |limb| (limb >> (i % 64)) & 1) as usize;
+    (0..num_digits)
+        .map(|d| (0..window_bits).fold(0, |acc, j| acc | (bit(d * window_bits + j) << j)))
+        .collect()
+}
+
+/// Computes `sum_i scalars[i] * points[i]` with Pippenger's bucket method.
+pub fn msm<C: Curve>(scalars: &[C::ScalarField], points: &[AffinePoint<C>]) -> ProjectivePoint<C> {
+    assert_eq!(
+        scalars.len(),
+        points.len(),
+        "Number of scalars and points must match"
+    );
+    if scalars.is_empty() {
+        return ProjectivePoint::ZERO;
+    }
+
+    // Only as many windows as the largest scalar needs, e.g. about half of them for the scalars of
+    // a GLV decomposition.
+    let scalars = scalars
+        .iter()
+        .map(|x| x.to_canonical_biguint())
+        .collect::<Vec<_>>();
+    let max_bits = scalars.iter().map(|x| x.bits()).max().unwrap() as usize;
+    let window_bits = msm_window_bits(scalars.len());
+    let num_windows = ceil_div_usize(max_bits, window_bits);
+    let digits = scalars
+        .iter()
+        .map(|x| to_window_digits(x, window_bits, num_windows))
+        .collect::<Vec<_>>();
+
+    let mut y = ProjectivePoint::ZERO;
+    let mut buckets = vec![ProjectivePoint::ZERO; (1 << window_bits) - 1];
+    for w in (0..num_windows).rev() {
+        for _ in 0..window_bits {
+            y = y.double();
+        }
+
+        // Bucket `i` accumulates the points whose digit is `i + 1`.
+        buckets.fill(ProjectivePoint::ZERO);
+        for (point_digits, &point) in digits.iter().zip(points) {
+            let digit = point_digits[w];
+            if digit != 0 {
+                buckets[digit - 1] += point;
+            }
+        }
+
+        // Compute `sum_i (i + 1) * buckets[i]` with running sums.
+        let mut running_sum = ProjectivePoint::ZERO;
+        let mut window_sum = ProjectivePoint::ZERO;
+        for &bucket in buckets.iter().rev() {
+            running_sum += bucket;
+            window_sum += running_sum;
+        }
+        y += window_sum;
+    }
+
+    y
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curve::curve_types::CurveScalar;
+    use crate::curve::secp256k1::Secp256K1;
+    use crate::secp256k1_scalar::Secp256K1Scalar;
+    use crate::types::{Field, Sample};
+
+    #[test]
+    fn test_msm() {
+        for n in [0, 1, 2, 7, 40] {
+            let scalars = Secp256K1Scalar::rand_vec(n);
+            let points = scalars
+                .iter()
+                .map(|_| {
+                    (CurveScalar(Secp256K1Scalar::rand()) * Secp256K1::GENERATOR_PROJECTIVE)
+                        .to_affine()
+                })
+                .collect::<Vec<_>>();
+
+            let expected = scalars
+                .iter()
+                .zip(&points)
+                .fold(ProjectivePoint::ZERO, |acc, (&x, &p)| {
+                    acc + CurveScalar::<Secp256K1>(x) * p
+                });
+            assert_eq!(msm(&scalars, &points), expected);
+        }
+    }
+
+    #[test]
+    fn test_msm_zero_scalars() {
+        let points = vec![Secp256K1::GENERATOR_AFFINE; 3];
+        let scalars = vec![Secp256K1Scalar::ZERO; 3];
+        assert_eq!(msm(&scalars, &points), ProjectivePoint::ZERO);
+    }
+
+    #[test]
+    fn test_msm_small_scalars() {
+        // These only need a few windows.
+        let scalars = [3, 1000, 0].map(Secp256K1Scalar::from_canonical_u64);
+        let points = [Secp256K1::GENERATOR_AFFINE; 3];
+        let expected = CurveScalar(Secp256K1Scalar::from_canonical_u64(1003))
+            * Secp256K1::GENERATOR_PROJECTIVE;
+        assert_eq!(msm(&scalars, &points), expected);
+    }
+}
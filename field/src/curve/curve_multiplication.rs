@@ -0,0 +1,63 @@
+use alloc::vec::Vec;
+use core::ops::Mul;
+
+use plonky2_util::ceil_div_usize;
+
+use crate::curve::curve_types::{AffinePoint, Curve, CurveScalar, ProjectivePoint};
+use crate::types::{Field, PrimeField};
+
+/// For simplicity, this must divide 64.
+const WINDOW_BITS: usize = 4;
+const BASE: usize = 1 << WINDOW_BITS;
+
+/// Splits `x` into little-endian digits of `WINDOW_BITS` bits each.
+pub(crate) fn to_digits<C: Curve>(x: &C::ScalarField) -> Vec<u64> {
+    let digits_per_u64 = 64 / WINDOW_BITS;
+    let mut digits = Vec::with_capacity(ceil_div_usize(C::ScalarField::BITS, WINDOW_BITS));
+    for limb in x.to_canonical_biguint().to_u64_digits() {
+        for j in 0..digits_per_u64 {
+            digits.push((limb >> (j * WINDOW_BITS)) & (BASE as u64 - 1));
+        }
+    }
+
+    digits
+}
+
+/// Multiplies a point by a scalar, using a fixed window of `WINDOW_BITS` bits.
+impl<C: Curve> Mul<ProjectivePoint<C>> for CurveScalar<C> {
+    type Output = ProjectivePoint<C>;
+
+    fn mul(self, rhs: ProjectivePoint<C>) -> Self::Output {
+        // Precompute `0 * rhs, 1 * rhs, ..., (BASE - 1) * rhs`.
+        let mut multiples = Vec::with_capacity(BASE);
+        multiples.push(ProjectivePoint::ZERO);
+        for i in 1..BASE {
+            multiples.push(multiples[i - 1] + rhs);
+        }
+
+        let mut y = ProjectivePoint::ZERO;
+        for &digit in to_digits::<C>(&self.0).iter().rev() {
+            for _ in 0..WINDOW_BITS {
+                y = y.double();
+            }
+            y += multiples[digit as usize];
+        }
+
+        y
+    }
+}
+
+impl<C: Curve> Mul<AffinePoint<C>> for CurveScalar<C> {
+    type Output = ProjectivePoint<C>;
+
+    fn mul(self, rhs: AffinePoint<C>) -> Self::Output {
+        self * rhs.to_projective()
+    }
+}
+
+impl<C: Curve> ProjectivePoint<C> {
+    /// Returns `x * self`, for a scalar given as a `u64`.
+    pub fn mul_u64(&self, x: u64) -> Self {
+        CurveScalar::<C>(C::ScalarField::from_noncanonical_u64(x)) * *self
+    }
+}
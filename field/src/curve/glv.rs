@@ -0,0 +1,135 @@
+use num::rational::Ratio;
+use num::{BigUint, Integer};
+
+use crate::curve::curve_msm::msm;
+use crate::curve::curve_types::{AffinePoint, ProjectivePoint};
+use crate::curve::secp256k1::Secp256K1;
+use crate::secp256k1_base::Secp256K1Base;
+use crate::secp256k1_scalar::Secp256K1Scalar;
+use crate::types::{Field, PrimeField};
+
+/// A primitive cube root of unity in `Secp256K1Base`, such that `(x, y) -> (GLV_BETA * x, y)` is
+/// the endomorphism acting as multiplication by `GLV_S` on the curve.
+// 55594575648329892869085402983802832744385952214688224221778511981742606582254
+pub const GLV_BETA: Secp256K1Base = Secp256K1Base([
+    0xC1396C28719501EE,
+    0x9CF0497512F58995,
+    0x6E64479EAC3434E9,
+    0x7AE96A2B657C0710,
+]);
+
+/// A primitive cube root of unity in `Secp256K1Scalar`, the eigenvalue of the GLV endomorphism.
+// 37718080363155996902926221483475020450927657555482586988616620542887997980018
+pub const GLV_S: Secp256K1Scalar = Secp256K1Scalar([
+    0xDF02967C1B23BD72,
+    0x122E22EA20816678,
+    0xA5261C028812645A,
+    0x5363AD4CC05C30E0,
+]);
+
+// A short basis `(A1, -B1), (A2, B2)` of the lattice `{(a, b) | a + b * GLV_S = 0}`.
+const A1: Secp256K1Scalar = Secp256K1Scalar([0xE86C90E49284EB15, 0x3086D221A7D46BCD, 0, 0]);
+const MINUS_B1: Secp256K1Scalar = Secp256K1Scalar([0x6F547FA90ABFE4C3, 0xE4437ED6010E8828, 0, 0]);
+const A2: Secp256K1Scalar = Secp256K1Scalar([0x57C1108D9D44CFD8, 0x14CA50F7A8E2F3F6, 1, 0]);
+const B2: Secp256K1Scalar = A1;
+
+/// Algorithm 15.41 in Handbook of Elliptic and Hyperelliptic Curve Cryptography.
+/// Decompose a scalar `k` into two small scalars `k1, k2` with `|k1|, |k2| < √p` that satisfy
+/// `k1 + s * k2 = k`.
+/// Returns a boolean indicating if `k1` (resp. `k2`) should be negated to get the correct
+/// decomposition.
+pub fn decompose_secp256k1_scalar(
+    k: Secp256K1Scalar,
+) -> (Secp256K1Scalar, Secp256K1Scalar, bool, bool) {
+    let p = Secp256K1Scalar::order();
+    let c1_biguint = Ratio::new(
+        B2.to_canonical_biguint() * k.to_canonical_biguint(),
+        p.clone(),
+    )
+    .round()
+    .to_integer();
+    let c1 = Secp256K1Scalar::from_noncanonical_biguint(c1_biguint);
+    let c2_biguint = Ratio::new(
+        MINUS_B1.to_canonical_biguint() * k.to_canonical_biguint(),
+        p.clone(),
+    )
+    .round()
+    .to_integer();
+    let c2 = Secp256K1Scalar::from_noncanonical_biguint(c2_biguint);
+
+    let k1_raw = k - c1 * A1 - c2 * A2;
+    let k2_raw = c1 * MINUS_B1 - c2 * B2;
+    debug_assert!(k1_raw + GLV_S * k2_raw == k);
+
+    let two = BigUint::from_slice(&[2]);
+    let k1_neg = k1_raw.to_canonical_biguint() > p.div_floor(&two);
+    let k1 = if k1_neg {
+        Secp256K1Scalar::from_noncanonical_biguint(p.clone() - k1_raw.to_canonical_biguint())
+    } else {
+        k1_raw
+    };
+    let k2_neg = k2_raw.to_canonical_biguint() > p.div_floor(&two);
+    let k2 = if k2_neg {
+        Secp256K1Scalar::from_noncanonical_biguint(p - k2_raw.to_canonical_biguint())
+    } else {
+        k2_raw
+    };
+
+    (k1, k2, k1_neg, k2_neg)
+}
+
+/// Returns `k * p`, computed as the two-term multi-scalar multiplication `k1 * p + k2 * phi(p)`
+/// where `phi` is the GLV endomorphism and `k1, k2` are about half the size of `k`.
+pub fn glv_mul(p: ProjectivePoint<Secp256K1>, k: Secp256K1Scalar) -> ProjectivePoint<Secp256K1> {
+    let (k1, k2, k1_neg, k2_neg) = decompose_secp256k1_scalar(k);
+
+    let p_affine = p.to_affine();
+    let sp = AffinePoint::<Secp256K1> {
+        x: p_affine.x * GLV_BETA,
+        y: p_affine.y,
+        zero: p_affine.zero,
+    };
+
+    let first = if k1_neg { -p_affine } else { p_affine };
+    let second = if k2_neg { -sp } else { sp };
+
+    msm(&[k1, k2], &[first, second])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curve::curve_types::{Curve, CurveScalar};
+    use crate::types::Sample;
+
+    #[test]
+    fn test_glv_decompose() {
+        for _ in 0..100 {
+            let k = Secp256K1Scalar::rand();
+            let (k1, k2, k1_neg, k2_neg) = decompose_secp256k1_scalar(k);
+            let one = Secp256K1Scalar::ONE;
+            let m1 = if k1_neg { -one } else { one };
+            let m2 = if k2_neg { -one } else { one };
+
+            assert_eq!(k1 * m1 + GLV_S * k2 * m2, k);
+            assert!(k1.to_canonical_biguint().bits() <= 129);
+            assert!(k2.to_canonical_biguint().bits() <= 129);
+        }
+    }
+
+    #[test]
+    fn test_glv_mul() {
+        let p = CurveScalar(Secp256K1Scalar::rand()) * Secp256K1::GENERATOR_PROJECTIVE;
+        let k = Secp256K1Scalar::rand();
+
+        assert_eq!(glv_mul(p, k), CurveScalar(k) * p);
+    }
+
+    #[test]
+    fn test_glv_endomorphism() {
+        let g = Secp256K1::GENERATOR_AFFINE;
+        let sg = AffinePoint::<Secp256K1>::nonzero(g.x * GLV_BETA, g.y);
+
+        assert_eq!(sg.to_projective(), CurveScalar(GLV_S) * g);
+    }
+}
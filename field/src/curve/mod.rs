@@ -0,0 +1,12 @@
+//! Short Weierstrass curves over the fields of this crate, for native witness generation and as a
+//! reference for in-circuit elliptic curve arithmetic.
+
+pub mod bn254;
+pub mod bn254_pairing;
+pub mod bn254_tower;
+pub mod curve_adds;
+pub mod curve_msm;
+pub mod curve_multiplication;
+pub mod curve_types;
+pub mod glv;
+pub mod secp256k1;
@@ -11,6 +11,8 @@ use crate::types::{Field, Sample};
 
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
 #[serde(bound = "")]
+// `batch_util` relies on this layout to view slices of extension elements as slices of `F`.
+#[repr(transparent)]
 pub struct QuadraticExtension<F: Extendable<2>>(pub [F; 2]);
 
 impl<F: Extendable<2>> Default for QuadraticExtension<F> {
@@ -247,4 +249,15 @@ mod tests {
             >
         );
     }
+
+    mod mersenne31 {
+        use crate::{test_field_arithmetic, test_field_extension};
+
+        test_field_extension!(crate::mersenne31_field::Mersenne31Field, 2);
+        test_field_arithmetic!(
+            crate::extension::quadratic::QuadraticExtension<
+                crate::mersenne31_field::Mersenne31Field,
+            >
+        );
+    }
 }
@@ -273,4 +273,13 @@ mod tests {
             >
         );
     }
+
+    mod babybear {
+        use crate::{test_field_arithmetic, test_field_extension};
+
+        test_field_extension!(crate::babybear_field::BabyBearField, 4);
+        test_field_arithmetic!(
+            crate::extension::quartic::QuarticExtension<crate::babybear_field::BabyBearField>
+        );
+    }
 }
@@ -288,4 +288,13 @@ mod tests {
             >
         );
     }
+
+    mod babybear {
+        use crate::{test_field_arithmetic, test_field_extension};
+
+        test_field_extension!(crate::babybear_field::BabyBearField, 5);
+        test_field_arithmetic!(
+            crate::extension::quintic::QuinticExtension<crate::babybear_field::BabyBearField>
+        );
+    }
 }
@@ -1,7 +1,9 @@
+use alloc::vec;
 use alloc::vec::Vec;
 use core::cmp::{max, min};
+use core::slice;
 
-use plonky2_util::{log2_strict, reverse_index_bits_in_place};
+use plonky2_util::{log2_strict, reverse_index_bits_in_place, transpose_square_in_place};
 use unroll::unroll_for_loops;
 
 use crate::packable::Packable;
@@ -64,6 +66,40 @@ pub fn fft_with_options<F: Field>(
     PolynomialValues::new(buffer)
 }
 
+/// The smallest LDE, as a log of its length, computed with `fft_six_step` rather than
+/// `fft_classic`. Measured with the `ffts` benchmark of `plonky2` on Goldilocks and a rate of 8,
+/// six-step was 25-45% slower up to `2^20` points, and on par with classic from `2^22` points
+/// (by best times, medians still trailing by up to 20%), where the LDE no longer fits in cache.
+const SIX_STEP_MIN_LG_N: usize = 22;
+
+/// The size, as a log, of the sub-transforms which `fft_six_step` computes with `fft_classic` when
+/// used for LDEs.
+const SIX_STEP_LG_LEAF: usize = 16;
+
+/// Low-degree extends `poly` by a factor of `2^rate_bits` and evaluates it on the coset `shift*H`.
+/// This is equivalent to `poly.lde(rate_bits).coset_fft_with_options(shift, Some(rate_bits), _)`,
+/// but fills the padded buffer directly instead of allocating intermediate polynomials. LDEs of at
+/// least `2^SIX_STEP_MIN_LG_N` points use the cache-friendly `fft_six_step`.
+pub fn coset_lde<F: Field>(
+    poly: &PolynomialCoeffs<F>,
+    rate_bits: usize,
+    shift: F,
+    root_table: Option<&FftRootTable<F>>,
+) -> PolynomialValues<F> {
+    let lde_len = poly.len() << rate_bits;
+    let mut buffer = Vec::with_capacity(lde_len);
+    buffer.extend(poly.coeffs.iter().zip(shift.powers()).map(|(&c, r)| c * r));
+    buffer.resize(lde_len, F::ZERO);
+    if lde_len >= 1 << SIX_STEP_MIN_LG_N {
+        let computed_root_table = root_table.is_none().then(|| fft_root_table(lde_len));
+        let used_root_table = root_table.or(computed_root_table.as_ref()).unwrap();
+        fft_six_step(&mut buffer, rate_bits, used_root_table, SIX_STEP_LG_LEAF);
+    } else {
+        fft_dispatch(&mut buffer, Some(rate_bits), root_table);
+    }
+    PolynomialValues::new(buffer)
+}
+
 #[inline]
 pub fn ifft<F: Field>(poly: PolynomialValues<F>) -> PolynomialCoeffs<F> {
     ifft_with_options(poly, None, None)
@@ -94,13 +130,123 @@ pub fn ifft_with_options<F: Field>(
     PolynomialCoeffs { coeffs: buffer }
 }
 
+/// Splits `n = 2^k 3^j` into `(k, j)`, or returns `None` if `n` has any other prime factor.
+pub fn smooth_factors(n: usize) -> Option<(usize, usize)> {
+    if n == 0 {
+        return None;
+    }
+    let k = n.trailing_zeros() as usize;
+    let mut odd = n >> k;
+    let mut j = 0;
+    while odd % 3 == 0 {
+        odd /= 3;
+        j += 1;
+    }
+    (odd == 1).then_some((k, j))
+}
+
+/// Returns the generator `w` of the subgroup of order `n = 2^k 3^j` of `F^*` which satisfies
+/// `w^(3^j) = F::primitive_root_of_unity(k)`. For `j = 0` this is the usual two-adic generator, so
+/// smooth transforms of power-of-two length agree with `fft` and `ifft`. The generators are
+/// compatible across sizes: the generator for `n 2^r` raised to `2^r` is the generator for `n`, so
+/// an LDE of a smooth domain contains it as in the two-adic case. Its 3-adic part is derived from
+/// `F::POWER_OF_THREE_GENERATOR`, so `j` is limited by `F::THREE_ADICITY`.
+pub fn smooth_root_of_unity<F: Field>(n: usize) -> F {
+    let (k, j) = smooth_factors(n).expect("Domain size is not of the form 2^k 3^j");
+    let two_adic_root = F::primitive_root_of_unity(k);
+    if j == 0 {
+        return two_adic_root;
+    }
+
+    assert!(
+        j <= F::THREE_ADICITY,
+        "The multiplicative group has no subgroup of order 3^{j}"
+    );
+    let three_pow = 3u64.pow(j as u32);
+    let three_adic_root =
+        F::POWER_OF_THREE_GENERATOR.exp_u64(3u64.pow((F::THREE_ADICITY - j) as u32));
+
+    // By the CRT, `w = w_2^a w_3^b` with `a = 3^-j mod 2^k` and `b = 2^-k mod 3^j`, the inverses
+    // being computed with Euler's theorem.
+    let two_pow = 1u64 << k;
+    let a = pow_mod(
+        three_pow % two_pow,
+        (two_pow >> 1).saturating_sub(1),
+        two_pow,
+    );
+    let b = pow_mod(two_pow % three_pow, three_pow / 3 * 2 - 1, three_pow);
+    two_adic_root.exp_u64(a) * three_adic_root.exp_u64(b)
+}
+
+/// Computes `base^exp mod modulus`.
+fn pow_mod(base: u64, mut exp: u64, modulus: u64) -> u64 {
+    let modulus = modulus as u128;
+    let mut base = base as u128 % modulus;
+    let mut result = 1 % modulus;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = result * base % modulus;
+        }
+        base = base * base % modulus;
+        exp >>= 1;
+    }
+    result as u64
+}
+
+/// Evaluates `poly`, of length `n = 2^k 3^j`, on the subgroup generated by
+/// `smooth_root_of_unity(n)`. Radix-3 steps reduce the length to `2^k`, after which `fft` is used.
+pub fn fft_smooth<F: Field>(poly: PolynomialCoeffs<F>) -> PolynomialValues<F> {
+    let n = poly.len();
+    let (_, j) = smooth_factors(n).expect("Domain size is not of the form 2^k 3^j");
+    PolynomialValues::new(fft_radix_3(poly.coeffs, j, smooth_root_of_unity(n)))
+}
+
+/// The inverse of `fft_smooth`.
+pub fn ifft_smooth<F: Field>(poly: PolynomialValues<F>) -> PolynomialCoeffs<F> {
+    let n = poly.len();
+    let (_, j) = smooth_factors(n).expect("Domain size is not of the form 2^k 3^j");
+    let n_inv = F::from_canonical_usize(n).inverse();
+
+    // As in `ifft`, a forward transform followed by reversing all values but the first.
+    let mut buffer = fft_radix_3(poly.values, j, smooth_root_of_unity(n));
+    buffer[1..].reverse();
+    buffer.iter_mut().for_each(|c| *c *= n_inv);
+    PolynomialCoeffs { coeffs: buffer }
+}
+
+/// Decimation-in-time radix-3 step: with `A_r` the transform of the coefficients at indices
+/// `r mod 3`, the value at `root^t` is `A_0[t] + root^t A_1[t] + root^(2t) A_2[t]`, indices of the
+/// `A_r` being taken modulo `n / 3`.
+fn fft_radix_3<F: Field>(coeffs: Vec<F>, j: usize, root: F) -> Vec<F> {
+    if j == 0 {
+        return fft(PolynomialCoeffs::new(coeffs)).values;
+    }
+
+    let n = coeffs.len();
+    let m = n / 3;
+    let root_cubed = root.cube();
+    let [a0, a1, a2] = [0, 1, 2].map(|r| {
+        let sub = coeffs[r..].iter().step_by(3).copied().collect();
+        fft_radix_3(sub, j - 1, root_cubed)
+    });
+
+    root.powers()
+        .take(n)
+        .enumerate()
+        .map(|(t, x)| {
+            let i = t % m;
+            a0[i] + x * (a1[i] + x * a2[i])
+        })
+        .collect()
+}
+
 /// Generic FFT implementation that works with both scalar and packed inputs.
 #[unroll_for_loops]
 fn fft_classic_simd<P: PackedField>(
     values: &mut [P::Scalar],
     r: usize,
     lg_n: usize,
-    root_table: &FftRootTable<P::Scalar>,
+    root_table: &[Vec<P::Scalar>],
 ) {
     let lg_packed_width = log2_strict(P::WIDTH); // 0 when P is a scalar.
     let packed_values = P::pack_slice_mut(values);
@@ -166,7 +312,7 @@ fn fft_classic_simd<P: PackedField>(
 /// The parameter r signifies that the first 1/2^r of the entries of
 /// input may be non-zero, but the last 1 - 1/2^r entries are
 /// definitely zero.
-pub(crate) fn fft_classic<F: Field>(values: &mut [F], r: usize, root_table: &FftRootTable<F>) {
+pub(crate) fn fft_classic<F: Field>(values: &mut [F], r: usize, root_table: &[Vec<F>]) {
     reverse_index_bits_in_place(values);
 
     let n = values.len();
@@ -205,20 +351,152 @@ pub(crate) fn fft_classic<F: Field>(values: &mut [F], r: usize, root_table: &Fft
     }
 }
 
+/// Cache-friendly FFT, following the six-step algorithm of Bailey, "FFTs in External or
+/// Hierarchical Memory". The input of length `n = n1 * n2` is viewed as an `n1` by `n2` row-major
+/// matrix. We transpose it in place, do `n2` FFTs of length `n1` on its (now contiguous) columns,
+/// multiply by twiddle factors, transpose back, do `n1` FFTs of length `n2` on its rows, and
+/// transpose once more to get the output in natural order. Sub-transforms of length at most
+/// `2^lg_leaf` use `fft_classic`; longer ones recurse.
+///
+/// The parameter r has the same meaning as in `fft_classic`.
+///
+/// `coset_lde` uses it for large LDEs, see `SIX_STEP_MIN_LG_N`; the `ffts` benchmark of `plonky2`
+/// compares it with `fft_classic` on a given machine.
+pub fn fft_six_step<F: Field>(values: &mut [F], r: usize, root_table: &[Vec<F>], lg_leaf: usize) {
+    let lg_n = log2_strict(values.len());
+    if root_table.len() != lg_n {
+        panic!(
+            "Expected root table of length {}, but it was {}.",
+            lg_n,
+            root_table.len()
+        );
+    }
+
+    // Half the length of the longest rows (de)interleaved by `transpose_rect`.
+    let mut scratch = vec![F::ZERO; 1 << (lg_n / 2)];
+    fft_six_step_rec(values, r, root_table, max(lg_leaf, 1), &mut scratch);
+}
+
+fn fft_six_step_rec<F: Field>(
+    values: &mut [F],
+    r: usize,
+    root_table: &[Vec<F>],
+    lg_leaf: usize,
+    scratch: &mut [F],
+) {
+    let n = values.len();
+    let lg_n = log2_strict(n);
+    if lg_n <= lg_leaf {
+        fft_classic(values, r, &root_table[..lg_n]);
+        return;
+    }
+
+    // There are either as many columns as rows, or twice as many.
+    let lg_rows = lg_n / 2;
+    let lg_cols = lg_n - lg_rows;
+    let rows = 1 << lg_rows;
+    let cols = 1 << lg_cols;
+
+    // `root_table[lg_n - 1]` holds `g^k` for `k < n / 2`, where `g` is a primitive `n`-th root of
+    // unity. There are fewer than `n / 2` columns.
+    let powers = &root_table[lg_n - 1];
+
+    // Only the first `rows >> r` rows can be non-zero, so each column inherits the zero tail.
+    let col_r = min(r, lg_rows);
+    transpose_rect(values, lg_rows, lg_cols, scratch);
+    for (j, col) in values.chunks_exact_mut(rows).enumerate() {
+        fft_six_step_rec(col, col_r, root_table, lg_leaf, scratch);
+        // The twiddle factors of column `j` are the powers of `g^j`. Computing them as such avoids
+        // strided reads of the root table, which miss the cache for large `n`.
+        let twiddle_j = powers[j];
+        let mut twiddle_jk = F::ONE;
+        for x in col.iter_mut() {
+            *x *= twiddle_jk;
+            twiddle_jk *= twiddle_j;
+        }
+    }
+    transpose_rect(values, lg_cols, lg_rows, scratch);
+
+    for row in values.chunks_exact_mut(cols) {
+        fft_six_step_rec(row, 0, root_table, lg_leaf, scratch);
+    }
+    transpose_rect(values, lg_rows, lg_cols, scratch);
+}
+
+/// Transposes in place a `2^lg_rows` by `2^lg_cols` row-major matrix, whose dimensions differ by
+/// at most a factor of two. A non-square matrix is treated as a square matrix of pairs, as in
+/// `projects/cache-friendly-fft`; `scratch` must hold at least `2^min(lg_rows, lg_cols)` elements.
+fn transpose_rect<T: Copy>(values: &mut [T], lg_rows: usize, lg_cols: usize, scratch: &mut [T]) {
+    debug_assert_eq!(values.len(), 1 << (lg_rows + lg_cols));
+    if lg_rows == lg_cols {
+        transpose_square_in_place(values);
+    } else if lg_cols == lg_rows + 1 {
+        // Pair up adjacent columns. Transposing the square matrix of pairs leaves row `i` holding
+        // columns `2i` and `2i + 1` interleaved.
+        transpose_square_in_place(as_pairs_mut(values));
+        for row_pair in values.chunks_exact_mut(2 << lg_rows) {
+            deinterleave(row_pair, scratch);
+        }
+    } else {
+        assert_eq!(lg_rows, lg_cols + 1, "Matrix is too far from square");
+        // The inverse of the above: interleave adjacent rows, then transpose the pairs.
+        for row_pair in values.chunks_exact_mut(2 << lg_cols) {
+            interleave(row_pair, scratch);
+        }
+        transpose_square_in_place(as_pairs_mut(values));
+    }
+}
+
+fn as_pairs_mut<T>(values: &mut [T]) -> &mut [[T; 2]] {
+    debug_assert!(values.len() % 2 == 0);
+    // SAFETY: `[T; 2]` has the same alignment as `T` and twice its size.
+    unsafe { slice::from_raw_parts_mut(values.as_mut_ptr().cast(), values.len() / 2) }
+}
+
+/// Rearranges `[x0, ..., x(n-1), y0, ..., y(n-1)]` into `[x0, y0, ..., x(n-1), y(n-1)]`.
+fn interleave<T: Copy>(values: &mut [T], scratch: &mut [T]) {
+    let half_n = values.len() / 2;
+    let scratch = &mut scratch[..half_n];
+    scratch.copy_from_slice(&values[..half_n]);
+    for i in 0..half_n {
+        values[2 * i] = scratch[i];
+        values[2 * i + 1] = values[half_n + i];
+    }
+}
+
+/// Rearranges `[x0, y0, ..., x(n-1), y(n-1)]` into `[x0, ..., x(n-1), y0, ..., y(n-1)]`.
+fn deinterleave<T: Copy>(values: &mut [T], scratch: &mut [T]) {
+    let half_n = values.len() / 2;
+    let scratch = &mut scratch[..half_n];
+    for i in 0..half_n {
+        values[i] = values[2 * i];
+        scratch[i] = values[2 * i + 1];
+    }
+    values[half_n..].copy_from_slice(scratch);
+}
+
 #[cfg(test)]
 mod tests {
     use alloc::vec::Vec;
 
     use plonky2_util::{log2_ceil, log2_strict};
 
-    use crate::fft::{fft, fft_with_options, ifft};
+    use crate::babybear_field::BabyBearField;
+    use crate::fft::{
+        coset_lde, fft, fft_classic, fft_root_table, fft_six_step, fft_smooth, fft_with_options,
+        ifft, ifft_smooth, smooth_factors, smooth_root_of_unity, SIX_STEP_MIN_LG_N,
+    };
     use crate::goldilocks_field::GoldilocksField;
     use crate::polynomial::{PolynomialCoeffs, PolynomialValues};
-    use crate::types::Field;
+    use crate::types::{Field, Sample};
 
     #[test]
     fn fft_and_ifft() {
-        type F = GoldilocksField;
+        check_fft_and_ifft::<GoldilocksField>();
+        check_fft_and_ifft::<BabyBearField>();
+    }
+
+    fn check_fft_and_ifft<F: Field>() {
         let degree = 200usize;
         let degree_padded = degree.next_power_of_two();
 
@@ -252,6 +530,120 @@ mod tests {
         }
     }
 
+    #[test]
+    fn six_step_matches_classic() {
+        check_six_step_matches_classic::<GoldilocksField>();
+        check_six_step_matches_classic::<BabyBearField>();
+    }
+
+    fn check_six_step_matches_classic<F: Field + Sample>() {
+        for lg_n in 0..=11 {
+            let n = 1 << lg_n;
+            let root_table = fft_root_table::<F>(n);
+            for r in 0..=lg_n.min(3) {
+                let mut values = F::rand_vec(n >> r);
+                values.resize(n, F::ZERO);
+
+                let mut expected = values.clone();
+                fft_classic(&mut expected, r, &root_table);
+                for lg_leaf in 1..=3 {
+                    let mut six_step = values.clone();
+                    fft_six_step(&mut six_step, r, &root_table, lg_leaf);
+                    assert_eq!(six_step, expected, "lg_n = {lg_n}, r = {r}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn large_fft_and_ifft() {
+        // Long enough for the six-step algorithm to split the input with 2^16-long leaves.
+        type F = GoldilocksField;
+        let lg_leaf = 16;
+        let lg_n = lg_leaf + 1;
+        let coeffs = PolynomialCoeffs::new(F::rand_vec(1 << lg_n));
+        let root_table = fft_root_table(1 << lg_n);
+
+        let mut six_step = coeffs.coeffs.clone();
+        fft_six_step(&mut six_step, 0, &root_table, lg_leaf);
+        let values = fft(coeffs.clone());
+        assert_eq!(values.values, six_step);
+        assert_eq!(ifft(values), coeffs);
+    }
+
+    #[test]
+    fn coset_lde_matches_coset_fft() {
+        type F = BabyBearField;
+        let poly = PolynomialCoeffs::new(F::rand_vec(64));
+        let shift = F::coset_shift();
+        for rate_bits in 0..4 {
+            assert_eq!(
+                coset_lde(&poly, rate_bits, shift, None),
+                poly.lde(rate_bits)
+                    .coset_fft_with_options(shift, Some(rate_bits), None)
+            );
+        }
+    }
+
+    #[test]
+    fn coset_lde_six_step() {
+        // Long enough for `coset_lde` to use the six-step algorithm.
+        type F = GoldilocksField;
+        let rate_bits = 3;
+        let poly = PolynomialCoeffs::new(F::rand_vec(1 << (SIX_STEP_MIN_LG_N - rate_bits)));
+        let shift = F::coset_shift();
+        assert_eq!(
+            coset_lde(&poly, rate_bits, shift, None),
+            poly.lde(rate_bits)
+                .coset_fft_with_options(shift, Some(rate_bits), None)
+        );
+    }
+
+    #[test]
+    fn smooth_fft_and_ifft() {
+        check_smooth_fft_and_ifft::<GoldilocksField>(&[1, 3, 6, 12, 48, 96]);
+        check_smooth_fft_and_ifft::<BabyBearField>(&[3, 24, 96]);
+    }
+
+    fn check_smooth_fft_and_ifft<F: Field + Sample>(sizes: &[usize]) {
+        for &n in sizes {
+            let root = smooth_root_of_unity::<F>(n);
+            assert_eq!(root.exp_u64(n as u64), F::ONE);
+            let (k, j) = smooth_factors(n).unwrap();
+            assert_eq!(
+                root.exp_u64(3u64.pow(j as u32)),
+                F::primitive_root_of_unity(k)
+            );
+            if j > 0 {
+                assert_ne!(root.exp_u64((n / 3) as u64), F::ONE);
+            }
+            // The generator of an LDE of the domain is compatible with the domain's.
+            assert_eq!(smooth_root_of_unity::<F>(n << 2).exp_u64(4), root);
+
+            let coeffs = PolynomialCoeffs::new(F::rand_vec(n));
+            let values = fft_smooth(coeffs.clone());
+            let expected = F::cyclic_subgroup_known_order(root, n)
+                .into_iter()
+                .map(|x| evaluate_at_naive(&coeffs, x))
+                .collect::<Vec<_>>();
+            assert_eq!(values.values, expected, "n = {n}");
+            assert_eq!(ifft_smooth(values), coeffs, "n = {n}");
+        }
+
+        // On powers of two, the smooth transforms agree with the two-adic ones.
+        let coeffs = PolynomialCoeffs::new(F::rand_vec(32));
+        assert_eq!(fft_smooth(coeffs.clone()), fft(coeffs));
+    }
+
+    #[test]
+    fn test_smooth_factors() {
+        assert_eq!(smooth_factors(1), Some((0, 0)));
+        assert_eq!(smooth_factors(24), Some((3, 1)));
+        assert_eq!(smooth_factors(9 << 5), Some((5, 2)));
+        assert_eq!(smooth_factors(0), None);
+        assert_eq!(smooth_factors(20), None);
+    }
+
     fn evaluate_naive<F: Field>(coefficients: &PolynomialCoeffs<F>) -> PolynomialValues<F> {
         let degree = coefficients.len();
         let degree_padded = 1 << log2_ceil(degree);
@@ -50,6 +50,17 @@ macro_rules! test_field_arithmetic {
                 }
             }
 
+            #[test]
+            fn power_of_three_generator() {
+                type F = $field;
+                let three_pow = BigUint::from(3u32).pow(F::THREE_ADICITY as u32);
+                let exponent = (F::order() - 1u32) / three_pow;
+                assert_eq!(
+                    F::MULTIPLICATIVE_GROUP_GENERATOR.exp_biguint(&exponent),
+                    F::POWER_OF_THREE_GENERATOR
+                );
+            }
+
             #[test]
             fn negation() {
                 type F = $field;
@@ -10,7 +10,7 @@ use serde::{Deserialize, Serialize};
 use crate::ops::Square;
 use crate::types::{Field, Field64, PrimeField, PrimeField64, Sample};
 
-const EPSILON: u64 = (1 << 32) - 1;
+pub(crate) const EPSILON: u64 = (1 << 32) - 1;
 
 /// A field selected to have fast reduction.
 ///
@@ -86,6 +86,11 @@ impl Field for GoldilocksField {
     // ```
     const POWER_OF_TWO_GENERATOR: Self = Self(1753635133440165772);
 
+    const THREE_ADICITY: usize = 1;
+
+    // Sage: `g^((p - 1) / 3)`
+    const POWER_OF_THREE_GENERATOR: Self = Self(18446744065119617025);
+
     const BITS: usize = 64;
 
     fn order() -> BigUint {
@@ -3,7 +3,7 @@ use alloc::vec::Vec;
 use plonky2_util::log2_ceil;
 
 use crate::fft::ifft;
-use crate::polynomial::{PolynomialCoeffs, PolynomialValues};
+use crate::polynomial::{PolynomialCoeffs, PolynomialValues, SubproductTree};
 use crate::types::Field;
 
 /// Computes the unique degree < n interpolant of an arbitrary list of n (point, value) pairs.
@@ -26,6 +26,14 @@ pub fn interpolant<F: Field>(points: &[(F, F)]) -> PolynomialCoeffs<F> {
     coeffs
 }
 
+/// Computes the unique degree < n interpolant of n (point, value) pairs with distinct points, in
+/// quasi-linear time using a subproduct tree. Unlike `interpolant`, this works on any point set
+/// and does not require `F` to be two-adic.
+pub fn fast_interpolant<F: Field>(points: &[(F, F)]) -> PolynomialCoeffs<F> {
+    let (xs, ys): (Vec<F>, Vec<F>) = points.iter().copied().unzip();
+    SubproductTree::new(&xs).interpolate(&ys)
+}
+
 /// Interpolate the polynomial defined by an arbitrary set of (point, value) pairs at the given
 /// point `x`.
 pub fn interpolate<F: Field>(points: &[(F, F)], x: F, barycentric_weights: &[F]) -> F {
@@ -127,6 +135,23 @@ mod tests {
         }
     }
 
+    #[test]
+    fn fast_interpolant_random() {
+        type F = GoldilocksField;
+
+        for deg in [0, 1, 9, 100] {
+            let domain = F::rand_vec(deg);
+            let coeffs = F::rand_vec(deg);
+            let coeffs = PolynomialCoeffs { coeffs };
+
+            let points = eval_naive(&coeffs, &domain);
+            assert_eq!(fast_interpolant(&points), coeffs);
+            if deg < 10 {
+                assert_eq!(fast_interpolant(&points), interpolant(&points));
+            }
+        }
+    }
+
     fn eval_naive<F: Field>(coeffs: &PolynomialCoeffs<F>, domain: &[F]) -> Vec<(F, F)> {
         domain.iter().map(|&x| (x, coeffs.eval(x))).collect()
     }
@@ -11,13 +11,20 @@ extern crate alloc;
 
 pub(crate) mod arch;
 
+pub mod babybear_field;
 pub mod batch_util;
+pub mod bn254_base;
+pub mod bn254_scalar;
+#[cfg(feature = "constant-time")]
+pub mod constant_time;
 pub mod cosets;
+pub mod curve;
 pub mod extension;
 pub mod fft;
 pub mod goldilocks_extensions;
 pub mod goldilocks_field;
 pub mod interpolation;
+pub mod mersenne31_field;
 pub mod ops;
 pub mod packable;
 pub mod packed;
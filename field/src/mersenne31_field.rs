@@ -0,0 +1,275 @@
+use core::fmt::{self, Debug, Display, Formatter};
+use core::iter::{Product, Sum};
+use core::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use num::{BigUint, Integer, ToPrimitive};
+use serde::{Deserialize, Serialize};
+
+use crate::extension::quadratic::QuadraticExtension;
+use crate::extension::{Extendable, Frobenius};
+use crate::types::{Field, Field64, PrimeField, PrimeField64, Sample};
+
+/// The Mersenne31 field, whose order is the Mersenne prime 2^31 - 1.
+///
+/// Reduction modulo this prime only needs shifts and additions, but `p - 1 = 2 * 3^2 * 7 * 11 * 31
+/// * 151 * 331` has a two-adicity of 1, so the field has no large multiplicative subgroup of
+/// power-of-two order. Since `p = 3 mod 4` and `gcd(5, p - 1) = 1`, it has no binomial extension
+/// of degree 4 or 5 either; only the quadratic extension `F[i] / (i^2 + 1)` is provided.
+///
+/// Elements are always stored in canonical form.
+#[derive(Copy, Clone, Default, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[repr(transparent)]
+pub struct Mersenne31Field(pub u32);
+
+const P: u32 = (1 << 31) - 1;
+
+impl Mersenne31Field {
+    /// Reduces a `u64` to its canonical representative.
+    #[inline]
+    fn reduce_u64(n: u64) -> Self {
+        // Since 2^31 = 1 mod P, the bits above the 31st can be folded onto the lower ones.
+        let folded = (n & P as u64) + (n >> 31);
+        let folded = ((folded & P as u64) + (folded >> 31)) as u32;
+        Self(if folded >= P { folded - P } else { folded })
+    }
+}
+
+impl Display for Mersenne31Field {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+impl Debug for Mersenne31Field {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&self.0, f)
+    }
+}
+
+impl Sample for Mersenne31Field {
+    #[inline]
+    fn sample<R>(rng: &mut R) -> Self
+    where
+        R: rand::RngCore + ?Sized,
+    {
+        use rand::Rng;
+        Self(rng.gen_range(0..P))
+    }
+}
+
+impl Field for Mersenne31Field {
+    const ZERO: Self = Self(0);
+    const ONE: Self = Self(1);
+    const TWO: Self = Self(2);
+    const NEG_ONE: Self = Self(P - 1);
+
+    const TWO_ADICITY: usize = 1;
+    const CHARACTERISTIC_TWO_ADICITY: usize = Self::TWO_ADICITY;
+
+    // Sage: `g = GF(p).multiplicative_generator()`
+    const MULTIPLICATIVE_GROUP_GENERATOR: Self = Self(7);
+
+    // The only non-trivial square root of unity.
+    const POWER_OF_TWO_GENERATOR: Self = Self::NEG_ONE;
+
+    const THREE_ADICITY: usize = 2;
+
+    // Sage: `g^((p - 1) / 3^2)`
+    const POWER_OF_THREE_GENERATOR: Self = Self(309107220);
+
+    const BITS: usize = 31;
+
+    fn order() -> BigUint {
+        P.into()
+    }
+    fn characteristic() -> BigUint {
+        Self::order()
+    }
+
+    /// Returns the inverse of the field element, using Fermat's little theorem.
+    fn try_inverse(&self) -> Option<Self> {
+        if self.is_zero() {
+            return None;
+        }
+        Some(self.exp_u64(Self::ORDER - 2))
+    }
+
+    fn from_noncanonical_biguint(n: BigUint) -> Self {
+        Self(n.mod_floor(&Self::order()).to_u32().unwrap())
+    }
+
+    #[inline(always)]
+    fn from_canonical_u64(n: u64) -> Self {
+        debug_assert!(n < Self::ORDER);
+        Self(n as u32)
+    }
+
+    fn from_noncanonical_u128(n: u128) -> Self {
+        Self((n % P as u128) as u32)
+    }
+
+    #[inline]
+    fn from_noncanonical_u64(n: u64) -> Self {
+        Self::reduce_u64(n)
+    }
+
+    #[inline]
+    fn from_noncanonical_i64(n: i64) -> Self {
+        Self(n.rem_euclid(P as i64) as u32)
+    }
+
+    #[inline]
+    fn multiply_accumulate(&self, x: Self, y: Self) -> Self {
+        // u32 + u32 * u32 cannot overflow a u64.
+        Self::reduce_u64(self.0 as u64 + x.0 as u64 * y.0 as u64)
+    }
+}
+
+impl PrimeField for Mersenne31Field {
+    fn to_canonical_biguint(&self) -> BigUint {
+        self.0.into()
+    }
+}
+
+impl Field64 for Mersenne31Field {
+    const ORDER: u64 = P as u64;
+
+    #[inline]
+    unsafe fn add_canonical_u64(&self, rhs: u64) -> Self {
+        *self + Self(rhs as u32)
+    }
+
+    #[inline]
+    unsafe fn sub_canonical_u64(&self, rhs: u64) -> Self {
+        *self - Self(rhs as u32)
+    }
+}
+
+impl PrimeField64 for Mersenne31Field {
+    #[inline]
+    fn to_canonical_u64(&self) -> u64 {
+        self.0 as u64
+    }
+
+    #[inline(always)]
+    fn to_noncanonical_u64(&self) -> u64 {
+        self.0 as u64
+    }
+}
+
+impl Neg for Mersenne31Field {
+    type Output = Self;
+
+    #[inline]
+    fn neg(self) -> Self {
+        if self.is_zero() {
+            Self::ZERO
+        } else {
+            Self(P - self.0)
+        }
+    }
+}
+
+impl Add for Mersenne31Field {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, rhs: Self) -> Self {
+        // Both operands are below 2^31, so their sum fits in a u32.
+        let sum = self.0 + rhs.0;
+        Self(if sum >= P { sum - P } else { sum })
+    }
+}
+
+impl AddAssign for Mersenne31Field {
+    #[inline]
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sum for Mersenne31Field {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ZERO, |acc, x| acc + x)
+    }
+}
+
+impl Sub for Mersenne31Field {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, rhs: Self) -> Self {
+        let (diff, under) = self.0.overflowing_sub(rhs.0);
+        Self(if under { diff.wrapping_add(P) } else { diff })
+    }
+}
+
+impl SubAssign for Mersenne31Field {
+    #[inline]
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl Mul for Mersenne31Field {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, rhs: Self) -> Self {
+        Self::reduce_u64(self.0 as u64 * rhs.0 as u64)
+    }
+}
+
+impl MulAssign for Mersenne31Field {
+    #[inline]
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+impl Product for Mersenne31Field {
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ONE, |acc, x| acc * x)
+    }
+}
+
+impl Div for Mersenne31Field {
+    type Output = Self;
+
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn div(self, rhs: Self) -> Self::Output {
+        self * rhs.inverse()
+    }
+}
+
+impl DivAssign for Mersenne31Field {
+    fn div_assign(&mut self, rhs: Self) {
+        *self = *self / rhs;
+    }
+}
+
+impl Frobenius<1> for Mersenne31Field {}
+
+impl Extendable<2> for Mersenne31Field {
+    type Extension = QuadraticExtension<Self>;
+
+    // `-1` is not a square since `p = 3 mod 4`.
+    const W: Self = Self::NEG_ONE;
+
+    // DTH_ROOT = W^((ORDER - 1)/2)
+    const DTH_ROOT: Self = Self::NEG_ONE;
+
+    const EXT_MULTIPLICATIVE_GROUP_GENERATOR: [Self; 2] = [Self(2), Self(7)];
+
+    // The extension has a two-adicity of 32, but `QuadraticExtension` only exposes a subgroup of
+    // order `2^(F::TWO_ADICITY + 1)`, generated by `-i`.
+    const EXT_POWER_OF_TWO_GENERATOR: [Self; 2] = [Self(0), Self::NEG_ONE];
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{test_field_arithmetic, test_small_prime_field_arithmetic};
+
+    test_small_prime_field_arithmetic!(crate::mersenne31_field::Mersenne31Field);
+    test_field_arithmetic!(crate::mersenne31_field::Mersenne31Field);
+}
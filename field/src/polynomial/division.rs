@@ -87,6 +87,36 @@ impl<F: Field> PolynomialCoeffs<F> {
         Self { coeffs: bs }
     }
 
+    /// Returns the monic greatest common divisor of `self` and `other`, or the zero polynomial if
+    /// both are zero.
+    pub fn gcd(&self, other: &Self) -> Self {
+        self.xgcd(other).0
+    }
+
+    /// Extended Euclidean algorithm. Returns `(g, s, t)` such that `g = s * self + t * other`,
+    /// where `g` is the monic greatest common divisor of `self` and `other`.
+    pub fn xgcd(&self, other: &Self) -> (Self, Self, Self) {
+        let (mut r0, mut r1) = (self.trimmed(), other.trimmed());
+        let (mut s0, mut s1) = (Self::new(vec![F::ONE]), Self::empty());
+        let (mut t0, mut t1) = (Self::empty(), Self::new(vec![F::ONE]));
+        while !r1.is_zero() {
+            let (q, r) = r0.div_rem(&r1);
+            let mut s = &s0 - &(&q * &s1);
+            let mut t = &t0 - &(&q * &t1);
+            s.trim();
+            t.trim();
+            (r0, r1) = (r1, r);
+            (s0, s1) = (s1, s);
+            (t0, t1) = (t1, t);
+        }
+
+        if r0.is_zero() {
+            return (r0, s0, t0);
+        }
+        let lead_inv = r0.lead().inverse();
+        (&r0 * lead_inv, &s0 * lead_inv, &t0 * lead_inv)
+    }
+
     /// Computes the inverse of `self` modulo `x^n`.
     pub fn inv_mod_xn(&self, n: usize) -> Self {
         assert!(n > 0, "`n` needs to be nonzero");
@@ -142,6 +172,26 @@ mod tests {
     use crate::polynomial::PolynomialCoeffs;
     use crate::types::{Field, Sample};
 
+    #[test]
+    fn test_gcd() {
+        type F = GoldilocksField;
+        let mut rng = OsRng;
+        let common = PolynomialCoeffs::new(F::rand_vec(rng.gen_range(2..50)));
+        // Linear factors with distinct roots are coprime.
+        let a = &PolynomialCoeffs::new(vec![F::ONE, F::ONE]) * &common;
+        let b = &PolynomialCoeffs::new(vec![F::TWO, F::ONE]) * &common;
+
+        let (g, s, t) = a.xgcd(&b);
+        assert_eq!(g, &common * common.lead().inverse());
+        assert_eq!(g.lead(), F::ONE);
+        assert_eq!(&(&s * &a) + &(&t * &b), g);
+        assert_eq!(b.gcd(&a), g);
+
+        let zero = PolynomialCoeffs::<F>::empty();
+        assert_eq!(a.gcd(&zero), &a * a.lead().inverse());
+        assert!(zero.gcd(&zero).is_zero());
+    }
+
     #[test]
     fn test_division_by_linear() {
         type F = QuarticExtension<GoldilocksField>;
@@ -1,4 +1,6 @@
 pub(crate) mod division;
+mod multilinear;
+mod subproduct_tree;
 
 use alloc::vec;
 use alloc::vec::Vec;
@@ -11,10 +13,18 @@ use itertools::Itertools;
 use plonky2_util::log2_strict;
 use serde::{Deserialize, Serialize};
 
+pub use self::multilinear::MultilinearPolynomial;
+pub use self::subproduct_tree::SubproductTree;
 use crate::extension::{Extendable, FieldExtension};
-use crate::fft::{fft, fft_with_options, ifft, FftRootTable};
+use crate::fft::{
+    coset_lde, fft, fft_smooth, fft_with_options, ifft, ifft_smooth, smooth_factors, FftRootTable,
+};
 use crate::types::Field;
 
+/// Products whose smaller factor has at most this many coefficients are computed with schoolbook
+/// multiplication, which beats the three FFTs for small inputs.
+const MUL_FFT_THRESHOLD: usize = 32;
+
 /// A polynomial in point-value form.
 ///
 /// The points are implicitly `g^i`, where `g` generates the subgroup whose size equals the number
@@ -26,8 +36,8 @@ pub struct PolynomialValues<F: Field> {
 
 impl<F: Field> PolynomialValues<F> {
     pub fn new(values: Vec<F>) -> Self {
-        // Check that a subgroup exists of this size, which should be a power of two.
-        debug_assert!(log2_strict(values.len()) <= F::TWO_ADICITY);
+        // Check that a subgroup exists of this size, which should be of the form `2^k 3^j`.
+        debug_assert!(smooth_factors(values.len()).is_some_and(|(k, _)| k <= F::TWO_ADICITY));
         PolynomialValues { values }
     }
 
@@ -72,6 +82,24 @@ impl<F: Field> PolynomialValues<F> {
         shifted_coeffs
     }
 
+    /// Interpolates values given on the subgroup generated by `smooth_root_of_unity(self.len())`,
+    /// whose order need not be a power of two.
+    pub fn ifft_smooth(self) -> PolynomialCoeffs<F> {
+        ifft_smooth(self)
+    }
+
+    pub fn coset_ifft_smooth(self, shift: F) -> PolynomialCoeffs<F> {
+        let mut shifted_coeffs = self.ifft_smooth();
+        shifted_coeffs
+            .coeffs
+            .iter_mut()
+            .zip(shift.inverse().powers())
+            .for_each(|(c, r)| {
+                *c *= r;
+            });
+        shifted_coeffs
+    }
+
     pub fn lde_multiple(polys: Vec<Self>, rate_bits: usize) -> Vec<Self> {
         polys.into_iter().map(|p| p.lde(rate_bits)).collect()
     }
@@ -83,8 +111,7 @@ impl<F: Field> PolynomialValues<F> {
 
     /// Low-degree extend `Self` (seen as evaluations over the subgroup) onto a coset.
     pub fn lde_onto_coset(self, rate_bits: usize) -> Self {
-        let coeffs = ifft(self).lde(rate_bits);
-        coeffs.coset_fft_with_options(F::coset_shift(), Some(rate_bits), None)
+        ifft(self).coset_lde(rate_bits, F::coset_shift(), None)
     }
 
     pub fn degree(&self) -> usize {
@@ -273,6 +300,24 @@ impl<F: Field> PolynomialCoeffs<F> {
         fft_with_options(self, zero_factor, root_table)
     }
 
+    /// Evaluates the polynomial on the subgroup generated by `smooth_root_of_unity(self.len())`,
+    /// whose order need not be a power of two.
+    pub fn fft_smooth(self) -> PolynomialValues<F> {
+        fft_smooth(self)
+    }
+
+    /// Returns the evaluation of the polynomial on the coset `shift*H`, where `H` is the smooth
+    /// subgroup of order `self.len()`.
+    pub fn coset_fft_smooth(&self, shift: F) -> PolynomialValues<F> {
+        let modified_poly: Self = shift
+            .powers()
+            .zip(&self.coeffs)
+            .map(|(r, &c)| r * c)
+            .collect::<Vec<_>>()
+            .into();
+        modified_poly.fft_smooth()
+    }
+
     /// Returns the evaluation of the polynomial on the coset `shift*H`.
     pub fn coset_fft(&self, shift: F) -> PolynomialValues<F> {
         self.coset_fft_with_options(shift, None, None)
@@ -294,6 +339,48 @@ impl<F: Field> PolynomialCoeffs<F> {
         modified_poly.fft_with_options(zero_factor, root_table)
     }
 
+    /// Returns the evaluation of the low-degree extension of the polynomial by a factor of
+    /// `2^rate_bits` on the coset `shift*H`.
+    pub fn coset_lde(
+        &self,
+        rate_bits: usize,
+        shift: F,
+        root_table: Option<&FftRootTable<F>>,
+    ) -> PolynomialValues<F> {
+        coset_lde(self, rate_bits, shift, root_table)
+    }
+
+    /// Evaluates the polynomial at each of `points`, using a subproduct tree.
+    pub fn eval_many(&self, points: &[F]) -> Vec<F> {
+        SubproductTree::new(points).evaluate(self)
+    }
+
+    /// The formal derivative of the polynomial.
+    pub fn derivative(&self) -> Self {
+        Self::new(
+            self.coeffs
+                .iter()
+                .enumerate()
+                .skip(1)
+                .map(|(i, &c)| F::from_canonical_usize(i) * c)
+                .collect(),
+        )
+    }
+
+    /// Schoolbook multiplication, in `O(self.len() * rhs.len())` operations.
+    pub(crate) fn mul_naive(&self, rhs: &Self) -> Self {
+        if self.coeffs.is_empty() || rhs.coeffs.is_empty() {
+            return Self::empty();
+        }
+        let mut coeffs = vec![F::ZERO; self.len() + rhs.len() - 1];
+        for (i, &a) in self.coeffs.iter().enumerate() {
+            for (j, &b) in rhs.coeffs.iter().enumerate() {
+                coeffs[i + j] += a * b;
+            }
+        }
+        Self::new(coeffs)
+    }
+
     pub fn to_extension<const D: usize>(&self) -> PolynomialCoeffs<F::Extension>
     where
         F: Extendable<D>,
@@ -422,6 +509,10 @@ impl<F: Field> Mul for &PolynomialCoeffs<F> {
 
     #[allow(clippy::suspicious_arithmetic_impl)]
     fn mul(self, rhs: Self) -> Self::Output {
+        if self.len().min(rhs.len()) <= MUL_FFT_THRESHOLD {
+            return self.mul_naive(rhs);
+        }
+
         let new_len = (self.len() + rhs.len()).next_power_of_two();
         let a = self.padded(new_len);
         let b = rhs.padded(new_len);
@@ -525,7 +616,7 @@ mod tests {
         let a = PolynomialCoeffs::new(F::rand_vec(a_deg));
         let b = PolynomialCoeffs::new(F::rand_vec(b_deg));
         let m1 = &a * &b;
-        let m2 = &a * &b;
+        let m2 = a.mul_naive(&b);
         for _ in 0..1000 {
             let x = F::rand();
             assert_eq!(m1.eval(x), a.eval(x) * b.eval(x));
@@ -533,6 +624,30 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_polynomial_multiplication_threshold() {
+        type F = GoldilocksField;
+        for a_len in [0, 1, MUL_FFT_THRESHOLD, MUL_FFT_THRESHOLD + 1, 100] {
+            for b_len in [1, MUL_FFT_THRESHOLD, MUL_FFT_THRESHOLD + 1, 100] {
+                let a = PolynomialCoeffs::new(F::rand_vec(a_len));
+                let b = PolynomialCoeffs::new(F::rand_vec(b_len));
+                assert_eq!(&a * &b, a.mul_naive(&b));
+            }
+        }
+    }
+
+    #[test]
+    fn test_derivative() {
+        type F = GoldilocksField;
+        // d/dX (1 + 2X + 3X^2) = 2 + 6X
+        let poly = PolynomialCoeffs::new(vec![F::ONE, F::TWO, F::from_canonical_u64(3)]);
+        assert_eq!(
+            poly.derivative(),
+            PolynomialCoeffs::new(vec![F::TWO, F::from_canonical_u64(6)])
+        );
+        assert!(PolynomialCoeffs::new(vec![F::ONE]).derivative().is_zero());
+    }
+
     #[test]
     fn test_inv_mod_xn() {
         type F = GoldilocksField;
@@ -0,0 +1,147 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use plonky2_util::log2_strict;
+use serde::{Deserialize, Serialize};
+
+use crate::extension::{Extendable, FieldExtension};
+use crate::types::Field;
+
+/// A multilinear polynomial in `n` variables, given by its evaluations over the boolean hypercube
+/// `{0, 1}^n`.
+///
+/// The evaluation at `(x_0, ..., x_{n-1})` is stored at index `sum_i x_i 2^(n - 1 - i)`, i.e. the
+/// first variable is the most significant bit of the index. Fixing the first variable thus folds
+/// the two halves of the table together.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct MultilinearPolynomial<F: Field> {
+    pub evals: Vec<F>,
+}
+
+impl<F: Field> MultilinearPolynomial<F> {
+    pub fn new(evals: Vec<F>) -> Self {
+        // Panics if the table is not indexed by a hypercube.
+        log2_strict(evals.len());
+        Self { evals }
+    }
+
+    pub fn num_vars(&self) -> usize {
+        log2_strict(self.evals.len())
+    }
+
+    /// The multilinear extension of `eq(x, point) = prod_i (x_i point_i + (1 - x_i)(1 - point_i))`,
+    /// which is `1` at `x = point` and `0` elsewhere on the hypercube when `point` is boolean.
+    pub fn eq(point: &[F]) -> Self {
+        let mut evals = vec![F::ONE];
+        for &r in point {
+            evals = evals
+                .into_iter()
+                .flat_map(|v| {
+                    let v_r = v * r;
+                    [v - v_r, v_r]
+                })
+                .collect();
+        }
+        Self { evals }
+    }
+
+    /// Returns the sum of the evaluations over the hypercube.
+    pub fn sum_over_hypercube(&self) -> F {
+        self.evals.iter().copied().sum()
+    }
+
+    /// Evaluates the polynomial at an arbitrary point of `F^n`.
+    pub fn evaluate(&self, point: &[F]) -> F {
+        assert_eq!(point.len(), self.num_vars(), "Wrong number of variables");
+        point
+            .iter()
+            .fold(self.clone(), |poly, &r| poly.fix_first_variable(r))
+            .evals[0]
+    }
+
+    /// Returns the polynomial in `n - 1` variables obtained by setting the first variable to `r`.
+    pub fn fix_first_variable(&self, r: F) -> Self {
+        assert!(self.num_vars() > 0, "No variable left to fix");
+        let (lo, hi) = self.evals.split_at(self.evals.len() / 2);
+        let evals = lo.iter().zip(hi).map(|(&l, &h)| l + r * (h - l)).collect();
+        Self { evals }
+    }
+
+    /// Returns the polynomial obtained by setting the first `point.len()` variables to `point`.
+    pub fn partial_evaluate(&self, point: &[F]) -> Self {
+        assert!(point.len() <= self.num_vars(), "Too many variables");
+        point
+            .iter()
+            .fold(self.clone(), |poly, &r| poly.fix_first_variable(r))
+    }
+
+    pub fn to_extension<const D: usize>(&self) -> MultilinearPolynomial<F::Extension>
+    where
+        F: Extendable<D>,
+    {
+        MultilinearPolynomial {
+            evals: self
+                .evals
+                .iter()
+                .map(|&e| F::Extension::from_basefield(e))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use crate::goldilocks_field::GoldilocksField;
+    use crate::polynomial::MultilinearPolynomial;
+    use crate::types::{Field, Sample};
+
+    type F = GoldilocksField;
+
+    fn to_bits(index: usize, num_vars: usize) -> Vec<F> {
+        (0..num_vars)
+            .map(|i| F::from_bool((index >> (num_vars - 1 - i)) & 1 == 1))
+            .collect()
+    }
+
+    #[test]
+    fn test_evaluate_on_hypercube() {
+        let num_vars = 4;
+        let poly = MultilinearPolynomial::new(F::rand_vec(1 << num_vars));
+        for (i, &e) in poly.evals.iter().enumerate() {
+            assert_eq!(poly.evaluate(&to_bits(i, num_vars)), e);
+        }
+    }
+
+    #[test]
+    fn test_eq() {
+        let num_vars = 5;
+        let point = F::rand_vec(num_vars);
+        let eq = MultilinearPolynomial::eq(&point);
+        for (i, &e) in eq.evals.iter().enumerate() {
+            let expected = to_bits(i, num_vars)
+                .iter()
+                .zip(&point)
+                .map(|(&x, &r)| x * r + (F::ONE - x) * (F::ONE - r))
+                .product::<F>();
+            assert_eq!(e, expected);
+        }
+        assert_eq!(eq.sum_over_hypercube(), F::ONE);
+
+        // `sum_x eq(x, r) p(x) = p(r)` for multilinear `p`.
+        let poly = MultilinearPolynomial::new(F::rand_vec(1 << num_vars));
+        let inner_product = poly.evals.iter().zip(&eq.evals).map(|(&a, &b)| a * b).sum();
+        assert_eq!(poly.evaluate(&point), inner_product);
+    }
+
+    #[test]
+    fn test_partial_evaluate() {
+        let poly = MultilinearPolynomial::new(F::rand_vec(1 << 6));
+        let point = F::rand_vec(6);
+        let partial = poly.partial_evaluate(&point[..2]);
+        assert_eq!(partial.num_vars(), 4);
+        assert_eq!(partial.evaluate(&point[2..]), poly.evaluate(&point));
+    }
+}
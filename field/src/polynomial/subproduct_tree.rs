@@ -0,0 +1,171 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::polynomial::PolynomialCoeffs;
+use crate::types::Field;
+
+/// Nodes spanning at most `2^DIRECT_EVAL_LOG` points evaluate their remainder directly with
+/// Horner's method rather than dividing further down the tree.
+const DIRECT_EVAL_LOG: usize = 4;
+
+/// The subproduct tree of a list of points `x_0, ..., x_{n-1}`: leaf `i` holds `X - x_i`, and each
+/// internal node holds the product of its two children. When a layer has an odd number of nodes,
+/// the last one is carried up unchanged. This gives quasi-linear multipoint evaluation and
+/// interpolation, see Modern Computer Algebra (von zur Gathen and Gerhard), Chapter 10.
+#[derive(Clone, Debug)]
+pub struct SubproductTree<F: Field> {
+    points: Vec<F>,
+    /// `layers[0]` holds the leaves and the last layer holds the root.
+    layers: Vec<Vec<PolynomialCoeffs<F>>>,
+}
+
+impl<F: Field> SubproductTree<F> {
+    pub fn new(points: &[F]) -> Self {
+        let mut layers = vec![points
+            .iter()
+            .map(|&x| PolynomialCoeffs::new(vec![-x, F::ONE]))
+            .collect::<Vec<_>>()];
+        while layers.last().unwrap().len() > 1 {
+            let next = layers
+                .last()
+                .unwrap()
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => left * right,
+                    [single] => single.clone(),
+                    _ => unreachable!(),
+                })
+                .collect();
+            layers.push(next);
+        }
+        Self {
+            points: points.to_vec(),
+            layers,
+        }
+    }
+
+    pub fn points(&self) -> &[F] {
+        &self.points
+    }
+
+    /// The vanishing polynomial of the points, `prod_i (X - x_i)`.
+    pub fn root(&self) -> PolynomialCoeffs<F> {
+        self.layers
+            .last()
+            .unwrap()
+            .first()
+            .cloned()
+            .unwrap_or_else(|| PolynomialCoeffs::new(vec![F::ONE]))
+    }
+
+    /// Evaluates `poly` at every point, by reducing it modulo each node on the way down the tree.
+    pub fn evaluate(&self, poly: &PolynomialCoeffs<F>) -> Vec<F> {
+        if self.points.is_empty() {
+            return Vec::new();
+        }
+
+        let top = self.layers.len() - 1;
+        let stop = DIRECT_EVAL_LOG.min(top);
+        let mut remainders = vec![poly.div_rem(&self.layers[top][0]).1];
+        for layer in self.layers[stop..top].iter().rev() {
+            remainders = layer
+                .iter()
+                .enumerate()
+                .map(|(i, node)| remainders[i / 2].div_rem(node).1)
+                .collect();
+        }
+
+        self.points
+            .iter()
+            .enumerate()
+            .map(|(i, &x)| remainders[i >> stop].eval(x))
+            .collect()
+    }
+
+    /// Returns the unique polynomial of degree less than `n` taking the given values at the points,
+    /// which must be distinct.
+    pub fn interpolate(&self, values: &[F]) -> PolynomialCoeffs<F> {
+        assert_eq!(
+            values.len(),
+            self.points.len(),
+            "Expected one value per point"
+        );
+        if values.is_empty() {
+            return PolynomialCoeffs::empty();
+        }
+
+        // The Lagrange basis polynomial of `x_i` is `m(X) / ((X - x_i) m'(x_i))`, where `m` is the
+        // root of the tree.
+        let denominators = self.evaluate(&self.root().derivative());
+        let mut polys = F::batch_multiplicative_inverse(&denominators)
+            .into_iter()
+            .zip(values)
+            .map(|(w, &y)| PolynomialCoeffs::new(vec![w * y]))
+            .collect::<Vec<_>>();
+
+        // Going up the tree, the combination for a node is `left * m_right + right * m_left`.
+        for layer in &self.layers[..self.layers.len() - 1] {
+            polys = polys
+                .chunks(2)
+                .zip(layer.chunks(2))
+                .map(|(pair, nodes)| match (pair, nodes) {
+                    ([left, right], [left_node, right_node]) => {
+                        &(left * right_node) + &(right * left_node)
+                    }
+                    ([single], [_]) => single.clone(),
+                    _ => unreachable!(),
+                })
+                .collect();
+        }
+
+        let mut poly = polys.pop().unwrap();
+        poly.trim();
+        poly
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::goldilocks_field::GoldilocksField;
+    use crate::polynomial::{PolynomialCoeffs, SubproductTree};
+    use crate::types::{Field, Sample};
+
+    type F = GoldilocksField;
+
+    #[test]
+    fn test_evaluate() {
+        for n in [1, 2, 3, 17, 100] {
+            let points = F::rand_vec(n);
+            let tree = SubproductTree::new(&points);
+            for degree in [0, n / 2, n, 3 * n] {
+                let poly = PolynomialCoeffs::new(F::rand_vec(degree));
+                let expected = points.iter().map(|&x| poly.eval(x)).collect::<Vec<_>>();
+                assert_eq!(tree.evaluate(&poly), expected);
+                assert_eq!(poly.eval_many(&points), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_root_vanishes() {
+        let points = F::rand_vec(37);
+        let root = SubproductTree::new(&points).root();
+        assert_eq!(root.degree_plus_one(), points.len() + 1);
+        for x in points {
+            assert_eq!(root.eval(x), F::ZERO);
+        }
+        assert_eq!(SubproductTree::<F>::new(&[]).root(), vec![F::ONE].into());
+    }
+
+    #[test]
+    fn test_interpolate() {
+        for n in [1, 2, 5, 64, 101] {
+            let points = F::rand_vec(n);
+            let values = F::rand_vec(n);
+            let tree = SubproductTree::new(&points);
+            let poly = tree.interpolate(&values);
+            assert!(poly.len() <= n);
+            assert_eq!(tree.evaluate(&poly), values);
+        }
+    }
+}
@@ -181,3 +181,135 @@ macro_rules! test_prime_field_arithmetic {
         }
     };
 }
+
+/// Like `test_prime_field_arithmetic`, but for fields whose order is much smaller than `2^64`,
+/// and whose two-adicity may be as small as 1.
+#[macro_export]
+macro_rules! test_small_prime_field_arithmetic {
+    ($field:ty) => {
+        mod small_prime_field_arithmetic {
+            use core::ops::{Add, Mul, Neg, Sub};
+
+            use $crate::ops::Square;
+            use $crate::types::{Field, Field64};
+
+            #[test]
+            fn arithmetic_addition() {
+                let modulus = <$field>::ORDER;
+                $crate::prime_field_testing::run_binaryop_test_cases(<$field>::add, |x, y| {
+                    ((x as u128 + y as u128) % (modulus as u128)) as u64
+                })
+            }
+
+            #[test]
+            fn arithmetic_subtraction() {
+                let modulus = <$field>::ORDER;
+                $crate::prime_field_testing::run_binaryop_test_cases(<$field>::sub, |x, y| {
+                    if x >= y {
+                        x - y
+                    } else {
+                        modulus - y + x
+                    }
+                })
+            }
+
+            #[test]
+            fn arithmetic_negation() {
+                let modulus = <$field>::ORDER;
+                $crate::prime_field_testing::run_unaryop_test_cases(<$field>::neg, |x| {
+                    if x == 0 {
+                        0
+                    } else {
+                        modulus - x
+                    }
+                })
+            }
+
+            #[test]
+            fn arithmetic_multiplication() {
+                let modulus = <$field>::ORDER;
+                $crate::prime_field_testing::run_binaryop_test_cases(<$field>::mul, |x, y| {
+                    ((x as u128) * (y as u128) % (modulus as u128)) as u64
+                })
+            }
+
+            #[test]
+            fn arithmetic_square() {
+                let modulus = <$field>::ORDER;
+                $crate::prime_field_testing::run_unaryop_test_cases(
+                    |x: $field| x.square(),
+                    |x| ((x as u128 * x as u128) % (modulus as u128)) as u64,
+                )
+            }
+
+            #[test]
+            fn inversion() {
+                let zero = <$field>::ZERO;
+                let one = <$field>::ONE;
+                let modulus = <$field>::ORDER;
+
+                assert_eq!(zero.try_inverse(), None);
+
+                let inputs = $crate::prime_field_testing::test_inputs(modulus);
+
+                for x in inputs {
+                    if x != 0 {
+                        let x = <$field>::from_canonical_u64(x);
+                        let inv = x.inverse();
+                        assert_eq!(x * inv, one);
+                    }
+                }
+            }
+
+            #[test]
+            fn inverse_2exp() {
+                type F = $field;
+
+                let v = <F as Field>::TWO_ADICITY;
+
+                // The two-adicity may be as small as 1.
+                for e in [
+                    0,
+                    1,
+                    2,
+                    3,
+                    4,
+                    v.saturating_sub(2),
+                    v - 1,
+                    v,
+                    v + 1,
+                    v + 2,
+                    123 * v,
+                ] {
+                    let x = F::TWO.exp_u64(e as u64);
+                    let y = F::inverse_2exp(e);
+                    assert_eq!(x * y, F::ONE);
+                }
+            }
+
+            #[test]
+            fn subtraction_double_wraparound() {
+                type F = $field;
+
+                let (a, b) = (F::from_canonical_u64((F::ORDER + 1u64) / 2u64), F::TWO);
+                let x = a * b;
+                assert_eq!(x, F::ONE);
+                assert_eq!(F::ZERO - x, F::NEG_ONE);
+            }
+
+            #[test]
+            fn addition_double_wraparound() {
+                type F = $field;
+
+                // Unlike for 64-bit fields, this isn't canonical.
+                let a = F::from_noncanonical_u64(u64::MAX - F::ORDER);
+                let b = F::NEG_ONE;
+
+                let c = (a + a) + (b + b);
+                let d = (a + b) + (a + b);
+
+                assert_eq!(c, d);
+            }
+        }
+    };
+}
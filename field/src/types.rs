@@ -88,6 +88,12 @@ pub trait Field:
     /// Generator of a multiplicative subgroup of order `2^TWO_ADICITY`.
     const POWER_OF_TWO_GENERATOR: Self;
 
+    /// The 3-adicity of this field's multiplicative group, used for domains of size `2^k 3^j`.
+    /// Fields which don't support such domains keep the default of 0.
+    const THREE_ADICITY: usize = 0;
+    /// Generator of a multiplicative subgroup of order `3^THREE_ADICITY`.
+    const POWER_OF_THREE_GENERATOR: Self = Self::ONE;
+
     /// The bit length of the field order.
     const BITS: usize;
 
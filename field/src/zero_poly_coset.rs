@@ -18,14 +18,21 @@ pub struct ZeroPolyOnCoset<F: Field> {
 
 impl<F: Field> ZeroPolyOnCoset<F> {
     pub fn new(n_log: usize, rate_bits: usize) -> Self {
-        let g_pow_n = F::coset_shift().exp_power_of_2(n_log);
+        Self::new_smooth(1 << n_log, rate_bits)
+    }
+
+    /// Like `new`, for a subgroup `H` of any order `n = 2^k 3^j`, with `K` generated by
+    /// `smooth_root_of_unity(n << rate_bits)`. Its `n`-th power is then
+    /// `primitive_root_of_unity(rate_bits)`, so the evaluations are periodic as in the two-adic case.
+    pub fn new_smooth(n: usize, rate_bits: usize) -> Self {
+        let g_pow_n = F::coset_shift().exp_u64(n as u64);
         let evals = F::two_adic_subgroup(rate_bits)
             .into_iter()
             .map(|x| g_pow_n * x - F::ONE)
             .collect::<Vec<_>>();
         let inverses = F::batch_multiplicative_inverse(&evals);
         Self {
-            n: F::from_canonical_usize(1 << n_log),
+            n: F::from_canonical_usize(n),
             rate: 1 << rate_bits,
             evals,
             inverses,
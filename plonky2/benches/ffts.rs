@@ -1,6 +1,7 @@
 mod allocator;
 
 use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use plonky2::field::fft::{fft_root_table, fft_six_step};
 use plonky2::field::goldilocks_field::GoldilocksField;
 use plonky2::field::polynomial::PolynomialCoeffs;
 use plonky2::field::types::Field;
@@ -18,6 +19,31 @@ pub(crate) fn bench_ffts<F: Field>(c: &mut Criterion) {
     }
 }
 
+/// Compares the default FFT with the six-step one on sizes that no longer fit in cache.
+pub(crate) fn bench_fft_six_step<F: Field>(c: &mut Criterion) {
+    const LG_LEAF: usize = 16;
+
+    let mut group = c.benchmark_group(&format!("fft-six-step<{}>", type_name::<F>()));
+    group.sample_size(10);
+
+    for size_log in [16, 18, 20, 22] {
+        let size = 1 << size_log;
+        let root_table = fft_root_table::<F>(size);
+        let coeffs = PolynomialCoeffs::new(F::rand_vec(size));
+
+        group.bench_with_input(BenchmarkId::new("classic", size), &size, |b, _| {
+            b.iter(|| coeffs.clone().fft_with_options(None, Some(&root_table)));
+        });
+        group.bench_with_input(BenchmarkId::new("six-step", size), &size, |b, _| {
+            b.iter(|| {
+                let mut values = coeffs.coeffs.clone();
+                fft_six_step(&mut values, 0, &root_table, LG_LEAF);
+                values
+            });
+        });
+    }
+}
+
 pub(crate) fn bench_ldes<F: Field>(c: &mut Criterion) {
     const RATE_BITS: usize = 3;
 
@@ -39,6 +65,7 @@ pub(crate) fn bench_ldes<F: Field>(c: &mut Criterion) {
 
 fn criterion_benchmark(c: &mut Criterion) {
     bench_ffts::<GoldilocksField>(c);
+    bench_fft_six_step::<GoldilocksField>(c);
     bench_ldes::<GoldilocksField>(c);
 }
 
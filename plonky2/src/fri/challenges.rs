@@ -1,6 +1,10 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
 use crate::field::extension::Extendable;
 use crate::field::polynomial::PolynomialCoeffs;
 use crate::fri::proof::{FriChallenges, FriChallengesTarget};
+use crate::fri::recursive_verifier::FriDegreeRangeTarget;
 use crate::fri::structure::{FriOpenings, FriOpeningsTarget};
 use crate::fri::FriConfig;
 use crate::gadgets::polynomial::PolynomialCoeffsExtTarget;
@@ -110,4 +114,57 @@ impl<F: RichField + Extendable<D>, H: AlgebraicHasher<F>, const D: usize>
             fri_query_indices,
         }
     }
+
+    /// Like `fri_challenges`, but for a proof whose degree is only known to lie in a range, see
+    /// `FriDegreeRangeTarget`. The transcript of each supported degree is derived, and the
+    /// challenges of the proof's degree are selected. Since the reduction rounds of a smaller degree
+    /// are a prefix of those of a larger one, the betas are shared.
+    pub fn fri_challenges_with_degree_range(
+        mut self,
+        builder: &mut CircuitBuilder<F, D>,
+        commit_phase_merkle_caps: &[MerkleCapTarget],
+        final_poly: &PolynomialCoeffsExtTarget<D>,
+        pow_witness: Target,
+        degree: &FriDegreeRangeTarget,
+    ) -> FriChallengesTarget<D> {
+        let num_fri_queries = degree.max_params().config.num_query_rounds;
+        let fri_alpha = self.get_extension_challenge(builder);
+
+        // `challengers[i]` holds the transcript after the first `i` reduction rounds.
+        let mut challengers = vec![self.clone()];
+        let fri_betas = commit_phase_merkle_caps
+            .iter()
+            .map(|cap| {
+                self.observe_cap(cap);
+                let beta = self.get_extension_challenge(builder);
+                challengers.push(self.clone());
+                beta
+            })
+            .collect();
+
+        let (pow_responses, query_indices): (Vec<_>, Vec<_>) = degree
+            .params
+            .iter()
+            .map(|params| {
+                let mut challenger = challengers[params.reduction_arity_bits.len()].clone();
+                challenger.observe_extension_elements(&final_poly.0[..params.final_poly_len()]);
+                challenger.observe_element(pow_witness);
+                let pow_response = challenger.get_challenge(builder);
+                let query_indices = challenger.get_n_challenges(builder, num_fri_queries);
+                (pow_response, query_indices)
+            })
+            .unzip();
+
+        let fri_pow_response = degree.select(builder, pow_responses);
+        let fri_query_indices = (0..num_fri_queries)
+            .map(|i| degree.select(builder, query_indices.iter().map(|q| q[i]).collect()))
+            .collect();
+
+        FriChallengesTarget {
+            fri_alpha,
+            fri_betas,
+            fri_pow_response,
+            fri_query_indices,
+        }
+    }
 }
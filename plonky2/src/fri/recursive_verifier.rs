@@ -1,5 +1,6 @@
 use alloc::format;
 use alloc::vec::Vec;
+use core::ops::Range;
 
 use itertools::Itertools;
 
@@ -13,7 +14,8 @@ use crate::fri::{FriConfig, FriParams};
 use crate::gates::coset_interpolation::CosetInterpolationGate;
 use crate::gates::gate::Gate;
 use crate::gates::random_access::RandomAccessGate;
-use crate::hash::hash_types::{MerkleCapTarget, RichField};
+use crate::hash::hash_types::{HashOutTarget, MerkleCapTarget, RichField, NUM_HASH_OUT_ELTS};
+use crate::hash::merkle_proofs::MerkleProofTarget;
 use crate::iop::ext_target::{flatten_target, ExtensionTarget};
 use crate::iop::target::{BoolTarget, Target};
 use crate::plonk::circuit_builder::CircuitBuilder;
@@ -22,6 +24,83 @@ use crate::util::reducing::ReducingFactorTarget;
 use crate::util::{log2_strict, reverse_index_bits_in_place};
 use crate::with_context;
 
+/// The degree of a FRI proof verified by a circuit which supports a range of consecutive degrees.
+///
+/// Proof targets are created by `add_virtual_fri_proof_with_degree_range`, and proofs of smaller
+/// degrees are padded with zeros. The reduction arities of the supported degrees must be prefixes of each
+/// other, as is the case with `FriReductionStrategy::ConstantArityBits`, so that a proof of a
+/// smaller degree simply skips the last reduction rounds.
+#[derive(Clone, Debug)]
+pub struct FriDegreeRangeTarget {
+    /// The FRI parameters of each supported degree, by increasing `degree_bits`.
+    pub params: Vec<FriParams>,
+    /// The index in `params` of the proof's degree.
+    pub index: Target,
+    /// `flags[i]` is set iff `index == i`.
+    pub flags: Vec<BoolTarget>,
+}
+
+impl FriDegreeRangeTarget {
+    /// The FRI parameters of the largest supported degree.
+    pub fn max_params(&self) -> &FriParams {
+        self.params.last().expect("The degree range is empty")
+    }
+
+    /// Selects the element of `v` corresponding to the proof's degree.
+    pub fn select<F: RichField + Extendable<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        mut v: Vec<Target>,
+    ) -> Target {
+        assert_eq!(v.len(), self.params.len());
+        // `random_access` needs a power-of-two length. `index` is already constrained to be in
+        // range, so the padding is never accessed.
+        v.resize(v.len().next_power_of_two(), v[v.len() - 1]);
+        builder.random_access(self.index, v)
+    }
+
+    /// Like `select`, but with `ExtensionTarget`s rather than simple `Target`s.
+    pub fn select_extension<F: RichField + Extendable<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        v: Vec<ExtensionTarget<D>>,
+    ) -> ExtensionTarget<D> {
+        ExtensionTarget(core::array::from_fn(|i| {
+            self.select(builder, v.iter().map(|et| et.0[i]).collect())
+        }))
+    }
+
+    /// Like `select`, but with `HashOutTarget`s rather than simple `Target`s.
+    pub fn select_hash<F: RichField + Extendable<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        v: Vec<HashOutTarget>,
+    ) -> HashOutTarget {
+        HashOutTarget {
+            elements: core::array::from_fn(|i| {
+                self.select(builder, v.iter().map(|h| h.elements[i]).collect())
+            }),
+        }
+    }
+
+    /// Returns a flag which is set iff the parameters of the proof's degree satisfy `predicate`.
+    pub fn flag_where<F: RichField + Extendable<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        predicate: impl Fn(&FriParams) -> bool,
+    ) -> BoolTarget {
+        let flags = self
+            .flags
+            .iter()
+            .zip(&self.params)
+            .filter(|(_, params)| predicate(params))
+            .map(|(flag, _)| flag.target)
+            .collect_vec();
+        // At most one of the flags is set.
+        BoolTarget::new_unsafe(builder.add_many(flags))
+    }
+}
+
 impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
     /// Computes P'(x^arity) from {P(x*g^i)}_(i=0..arity), where g is a `arity`-th root of unity
     /// and P' is the FRI reduced polynomial.
@@ -98,6 +177,136 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         );
     }
 
+    /// Creates a `FriDegreeRangeTarget` for proofs of degree `2^degree_bits`, and constrains
+    /// `degree_bits` to lie in `degree_bits_range`.
+    pub fn fri_degree_range(
+        &mut self,
+        degree_bits: Target,
+        degree_bits_range: Range<usize>,
+        config: &FriConfig,
+        hiding: bool,
+    ) -> FriDegreeRangeTarget {
+        assert!(!degree_bits_range.is_empty(), "The degree range is empty");
+        let params = degree_bits_range
+            .clone()
+            .map(|degree_bits| config.fri_params(degree_bits, hiding))
+            .collect_vec();
+        let max_arity_bits = &params[params.len() - 1].reduction_arity_bits;
+        assert!(
+            params
+                .iter()
+                .all(|p| max_arity_bits.starts_with(&p.reduction_arity_bits)),
+            "The reduction arities of the supported degrees must be prefixes of each other."
+        );
+        assert!(params[0].lde_bits() >= config.cap_height);
+
+        let flags = degree_bits_range
+            .clone()
+            .map(|d| {
+                let d = self.constant(F::from_canonical_usize(d));
+                self.is_equal(degree_bits, d)
+            })
+            .collect_vec();
+        let num_flags_set = self.add_many(flags.iter().map(|f| f.target));
+        self.assert_one(num_flags_set);
+        let min_degree_bits = self.constant(F::from_canonical_usize(degree_bits_range.start));
+        let index = self.sub(degree_bits, min_degree_bits);
+
+        FriDegreeRangeTarget {
+            params,
+            index,
+            flags,
+        }
+    }
+
+    /// Like `verify_fri_proof`, but for a proof whose degree is only known to lie in a range, see
+    /// `FriDegreeRangeTarget`. The challenges must come from `fri_challenges_with_degree_range`, and
+    /// `instance` must use the domain generator of the proof's degree.
+    pub fn verify_fri_proof_with_degree_range<C: GenericConfig<D, F = F>>(
+        &mut self,
+        instance: &FriInstanceInfoTarget<D>,
+        openings: &FriOpeningsTarget<D>,
+        challenges: &FriChallengesTarget<D>,
+        initial_merkle_caps: &[MerkleCapTarget],
+        proof: &FriProofTarget<D>,
+        degree: &FriDegreeRangeTarget,
+    ) where
+        C::Hasher: AlgebraicHasher<F>,
+    {
+        let params = degree.max_params();
+        if let Some(max_arity_bits) = params.max_arity_bits() {
+            self.check_recursion_config(max_arity_bits);
+        }
+
+        debug_assert_eq!(
+            degree.params.iter().map(|p| p.final_poly_len()).max(),
+            Some(proof.final_poly.len()),
+            "Final polynomial has wrong degree."
+        );
+
+        with_context!(
+            self,
+            "check PoW",
+            self.fri_verify_proof_of_work(challenges.fri_pow_response, &params.config)
+        );
+
+        // The challenger only observes the actual coefficients of the final polynomial, so its
+        // padding must be checked to be zero.
+        with_context!(self, "check final polynomial padding", {
+            for (i, coeff) in proof.final_poly.0.iter().enumerate() {
+                if degree.params.iter().all(|p| p.final_poly_len() > i) {
+                    continue;
+                }
+                let is_padding = degree.flag_where(self, |p| p.final_poly_len() <= i);
+                for &c in &coeff.0 {
+                    let padding = self.mul(is_padding.target, c);
+                    self.assert_zero(padding);
+                }
+            }
+        });
+
+        debug_assert_eq!(
+            params.config.num_query_rounds,
+            proof.query_round_proofs.len(),
+            "Number of query rounds does not match config."
+        );
+
+        let precomputed_reduced_evals = with_context!(
+            self,
+            "precompute reduced evaluations",
+            PrecomputedReducedOpeningsTarget::from_os_and_alpha(
+                openings,
+                challenges.fri_alpha,
+                self
+            )
+        );
+
+        for (i, round_proof) in proof.query_round_proofs.iter().enumerate() {
+            let level = if i == 1 {
+                log::Level::Debug
+            } else {
+                log::Level::Trace
+            };
+
+            let num_queries = proof.query_round_proofs.len();
+            with_context!(
+                self,
+                level,
+                &format!("verify one (of {num_queries}) query rounds"),
+                self.fri_verifier_query_round_with_degree_range::<C>(
+                    instance,
+                    challenges,
+                    &precomputed_reduced_evals,
+                    initial_merkle_caps,
+                    proof,
+                    challenges.fri_query_indices[i],
+                    round_proof,
+                    degree,
+                )
+            );
+        }
+    }
+
     pub fn verify_fri_proof<C: GenericConfig<D, F = F>>(
         &mut self,
         instance: &FriInstanceInfoTarget<D>,
@@ -360,6 +569,192 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         self.connect_extension(eval, old_eval);
     }
 
+    fn fri_verifier_query_round_with_degree_range<C: GenericConfig<D, F = F>>(
+        &mut self,
+        instance: &FriInstanceInfoTarget<D>,
+        challenges: &FriChallengesTarget<D>,
+        precomputed_reduced_evals: &PrecomputedReducedOpeningsTarget<D>,
+        initial_merkle_caps: &[MerkleCapTarget],
+        proof: &FriProofTarget<D>,
+        x_index: Target,
+        round_proof: &FriQueryRoundTarget<D>,
+        degree: &FriDegreeRangeTarget,
+    ) where
+        C::Hasher: AlgebraicHasher<F>,
+    {
+        let params = degree.max_params();
+        let cap_height = params.config.cap_height;
+
+        // The query index of a smaller degree is given by the low bits of the same challenge.
+        Self::assert_noncanonical_indices_ok(&params.config);
+        let mut x_index_bits = self.low_bits(x_index, params.lde_bits(), F::BITS);
+
+        let cap_indices = degree
+            .params
+            .iter()
+            .map(|p| self.le_sum(x_index_bits[p.lde_bits() - cap_height..p.lde_bits()].iter()))
+            .collect();
+        let cap_index = degree.select(self, cap_indices);
+        with_context!(self, "check FRI initial proof", {
+            let proof_lens = degree
+                .params
+                .iter()
+                .map(|p| p.lde_bits() - cap_height)
+                .collect_vec();
+            for (i, ((evals, merkle_proof), cap)) in round_proof
+                .initial_trees_proof
+                .evals_proofs
+                .iter()
+                .zip(initial_merkle_caps)
+                .enumerate()
+            {
+                with_context!(
+                    self,
+                    &format!("verify {i}'th initial Merkle proof"),
+                    self.verify_merkle_proof_with_degree_range::<C::Hasher>(
+                        evals.clone(),
+                        &x_index_bits,
+                        cap_index,
+                        cap,
+                        merkle_proof,
+                        &proof_lens,
+                        degree,
+                        None,
+                    )
+                );
+            }
+        });
+
+        let mut subgroup_x = with_context!(self, "compute x from its index", {
+            let g = self.constant(F::coset_shift());
+            let xs = degree
+                .params
+                .iter()
+                .map(|p| {
+                    let phi = F::primitive_root_of_unity(p.lde_bits());
+                    let phi = self
+                        .exp_from_bits_const_base(phi, x_index_bits[..p.lde_bits()].iter().rev());
+                    self.mul(g, phi)
+                })
+                .collect();
+            degree.select(self, xs)
+        });
+
+        let mut old_eval = with_context!(
+            self,
+            "combine initial oracles",
+            self.fri_combine_initial(
+                instance,
+                &round_proof.initial_trees_proof,
+                challenges.fri_alpha,
+                subgroup_x,
+                precomputed_reduced_evals,
+                params,
+            )
+        );
+
+        let mut reduced_bits = 0;
+        for (i, &arity_bits) in params.reduction_arity_bits.iter().enumerate() {
+            // Rounds past the last one of the proof's degree are padding, and are skipped.
+            let is_active = degree.flag_where(self, |p| p.reduction_arity_bits.len() > i);
+            let evals = &round_proof.steps[i].evals;
+
+            let coset_index_bits = x_index_bits[arity_bits..].to_vec();
+            let x_index_within_coset_bits = &x_index_bits[..arity_bits];
+            let x_index_within_coset = self.le_sum(x_index_within_coset_bits.iter());
+
+            let new_eval = self.random_access_extension(x_index_within_coset, evals.clone());
+            let diff = self.sub_extension(new_eval, old_eval);
+            let diff = self.scalar_mul_ext(is_active.target, diff);
+            for &d in &diff.0 {
+                self.assert_zero(d);
+            }
+
+            let eval = with_context!(
+                self,
+                "infer evaluation using interpolation",
+                self.compute_evaluation(
+                    subgroup_x,
+                    x_index_within_coset_bits,
+                    arity_bits,
+                    evals,
+                    challenges.fri_betas[i],
+                )
+            );
+            old_eval = self.select_ext(is_active, eval, old_eval);
+
+            reduced_bits += arity_bits;
+            let proof_lens = degree
+                .params
+                .iter()
+                .map(|p| p.lde_bits().saturating_sub(reduced_bits + cap_height))
+                .collect_vec();
+            with_context!(
+                self,
+                "verify FRI round Merkle proof.",
+                self.verify_merkle_proof_with_degree_range::<C::Hasher>(
+                    flatten_target(evals),
+                    &coset_index_bits,
+                    cap_index,
+                    &proof.commit_phase_merkle_caps[i],
+                    &round_proof.steps[i].merkle_proof,
+                    &proof_lens,
+                    degree,
+                    Some(is_active),
+                )
+            );
+
+            let next_subgroup_x = self.exp_power_of_2(subgroup_x, arity_bits);
+            subgroup_x = self.select(is_active, next_subgroup_x, subgroup_x);
+
+            x_index_bits = coset_index_bits;
+        }
+
+        let eval = with_context!(
+            self,
+            &format!(
+                "evaluate final polynomial of length {}",
+                proof.final_poly.len()
+            ),
+            proof.final_poly.eval_scalar(self, subgroup_x)
+        );
+        self.connect_extension(eval, old_eval);
+    }
+
+    /// Verifies a Merkle proof whose length depends on the proof's degree, `proof_lens` holding its
+    /// length for each supported degree. `proof` is padded to the largest length. If a `condition`
+    /// is given, the proof is only checked when it is set.
+    fn verify_merkle_proof_with_degree_range<H: AlgebraicHasher<F>>(
+        &mut self,
+        leaf_data: Vec<Target>,
+        leaf_index_bits: &[BoolTarget],
+        cap_index: Target,
+        merkle_cap: &MerkleCapTarget,
+        proof: &MerkleProofTarget,
+        proof_lens: &[usize],
+        degree: &FriDegreeRangeTarget,
+        condition: Option<BoolTarget>,
+    ) {
+        let digests = self.merkle_proof_digests::<H>(leaf_data, leaf_index_bits, proof);
+        let roots = proof_lens.iter().map(|&len| digests[len]).collect();
+        let root = degree.select_hash(self, roots);
+
+        for i in 0..NUM_HASH_OUT_ELTS {
+            let result = self.random_access(
+                cap_index,
+                merkle_cap.0.iter().map(|h| h.elements[i]).collect(),
+            );
+            match condition {
+                None => self.connect(result, root.elements[i]),
+                Some(condition) => {
+                    let diff = self.sub(result, root.elements[i]);
+                    let diff = self.mul(condition.target, diff);
+                    self.assert_zero(diff);
+                }
+            }
+        }
+    }
+
     /// We decompose FRI query indices into bits without verifying that the decomposition given by
     /// the prover is the canonical one. In particular, if `x_index < 2^field_bits - p`, then the
     /// prover could supply the binary encoding of either `x_index` or `x_index + p`, since the are
@@ -384,6 +779,43 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         &mut self,
         num_leaves_per_oracle: &[usize],
         params: &FriParams,
+    ) -> FriProofTarget<D> {
+        self.add_virtual_fri_proof_with_final_poly_len(
+            num_leaves_per_oracle,
+            params,
+            params.final_poly_len(),
+        )
+    }
+
+    /// Like `add_virtual_fri_proof`, but shaped for proofs of any of the degrees in
+    /// `degree_bits_range`, as expected by `verify_fri_proof_with_degree_range`.
+    pub fn add_virtual_fri_proof_with_degree_range(
+        &mut self,
+        num_leaves_per_oracle: &[usize],
+        degree_bits_range: Range<usize>,
+        config: &FriConfig,
+        hiding: bool,
+    ) -> FriProofTarget<D> {
+        assert!(!degree_bits_range.is_empty(), "The degree range is empty");
+        // The final polynomial of a smaller degree may be longer, as it may have fewer reductions.
+        let final_poly_len = degree_bits_range
+            .clone()
+            .map(|degree_bits| config.fri_params(degree_bits, hiding).final_poly_len())
+            .max()
+            .unwrap();
+        let params = config.fri_params(degree_bits_range.end - 1, hiding);
+        self.add_virtual_fri_proof_with_final_poly_len(
+            num_leaves_per_oracle,
+            &params,
+            final_poly_len,
+        )
+    }
+
+    fn add_virtual_fri_proof_with_final_poly_len(
+        &mut self,
+        num_leaves_per_oracle: &[usize],
+        params: &FriParams,
+        final_poly_len: usize,
     ) -> FriProofTarget<D> {
         let cap_height = params.config.cap_height;
         let num_queries = params.config.num_query_rounds;
@@ -393,7 +825,7 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         let query_round_proofs = (0..num_queries)
             .map(|_| self.add_virtual_fri_query(num_leaves_per_oracle, params))
             .collect();
-        let final_poly = self.add_virtual_poly_coeff_ext(params.final_poly_len());
+        let final_poly = self.add_virtual_poly_coeff_ext(final_poly_len);
         let pow_witness = self.add_virtual_target();
         FriProofTarget {
             commit_phase_merkle_caps,
@@ -2,12 +2,12 @@ use alloc::vec;
 use alloc::vec::Vec;
 
 use log::debug;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 #[cfg(feature = "timing")]
 use web_time::Instant;
 
 /// A method for deciding what arity to use at each reduction layer.
-#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub enum FriReductionStrategy {
     /// Specifies the exact sequence of arities (expressed in bits) to use.
     Fixed(Vec<usize>),
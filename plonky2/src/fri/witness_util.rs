@@ -1,8 +1,12 @@
+use alloc::vec;
+
 use itertools::Itertools;
 
 use crate::field::extension::Extendable;
+use crate::field::types::Field;
 use crate::fri::proof::{FriProof, FriProofTarget};
-use crate::hash::hash_types::RichField;
+use crate::hash::hash_types::{HashOut, RichField};
+use crate::hash::merkle_tree::MerkleCap;
 use crate::iop::witness::WitnessWrite;
 use crate::plonk::config::AlgebraicHasher;
 
@@ -69,3 +73,88 @@ pub fn set_fri_proof_target<F, W, H, const D: usize>(
         }
     }
 }
+
+/// Like `set_fri_proof_target`, but `fri_proof` may have a smaller degree than the one
+/// `fri_proof_target` was built for, as supported by `verify_fri_proof_with_degree_range`. The
+/// targets without a counterpart in `fri_proof` are set to zero.
+pub fn set_fri_proof_target_with_padding<F, W, H, const D: usize>(
+    witness: &mut W,
+    fri_proof_target: &FriProofTarget<D>,
+    fri_proof: &FriProof<F, H, D>,
+) where
+    F: RichField + Extendable<D>,
+    W: WitnessWrite<F> + ?Sized,
+    H: AlgebraicHasher<F>,
+{
+    fn padded<T: Clone>(values: &[T], len: usize, padding: T) -> impl Iterator<Item = T> + '_ {
+        assert!(values.len() <= len, "The proof is larger than its target");
+        values
+            .iter()
+            .cloned()
+            .chain(core::iter::repeat(padding).take(len - values.len()))
+    }
+
+    let zero_hash = HashOut::<F>::ZERO;
+    witness.set_target(fri_proof_target.pow_witness, fri_proof.pow_witness);
+
+    let final_poly_len = fri_proof_target.final_poly.len();
+    for (&t, x) in fri_proof_target.final_poly.0.iter().zip(padded(
+        &fri_proof.final_poly.coeffs,
+        final_poly_len,
+        F::Extension::ZERO,
+    )) {
+        witness.set_extension_target(t, x);
+    }
+
+    let num_rounds = fri_proof_target.commit_phase_merkle_caps.len();
+    let cap_len = fri_proof_target
+        .commit_phase_merkle_caps
+        .first()
+        .map_or(0, |cap| cap.0.len());
+    let zero_cap = MerkleCap::<F, H>(vec![zero_hash; cap_len]);
+    for (t, x) in fri_proof_target.commit_phase_merkle_caps.iter().zip(padded(
+        &fri_proof.commit_phase_merkle_caps,
+        num_rounds,
+        zero_cap,
+    )) {
+        witness.set_cap_target(t, &x);
+    }
+
+    for (qt, q) in fri_proof_target
+        .query_round_proofs
+        .iter()
+        .zip_eq(&fri_proof.query_round_proofs)
+    {
+        for (at, a) in qt
+            .initial_trees_proof
+            .evals_proofs
+            .iter()
+            .zip_eq(&q.initial_trees_proof.evals_proofs)
+        {
+            for (&t, &x) in at.0.iter().zip_eq(&a.0) {
+                witness.set_target(t, x);
+            }
+            let siblings = padded(&a.1.siblings, at.1.siblings.len(), zero_hash);
+            for (&t, x) in at.1.siblings.iter().zip(siblings) {
+                witness.set_hash_target(t, x);
+            }
+        }
+
+        for (i, st) in qt.steps.iter().enumerate() {
+            let (evals, siblings) = q.steps.get(i).map_or((&[][..], &[][..]), |s| {
+                (&s.evals[..], &s.merkle_proof.siblings[..])
+            });
+            for (&t, x) in st
+                .evals
+                .iter()
+                .zip(padded(evals, st.evals.len(), F::Extension::ZERO))
+            {
+                witness.set_extension_target(t, x);
+            }
+            let siblings = padded(siblings, st.merkle_proof.siblings.len(), zero_hash);
+            for (&t, x) in st.merkle_proof.siblings.iter().zip(siblings) {
+                witness.set_hash_target(t, x);
+            }
+        }
+    }
+}
@@ -77,13 +77,23 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
             return result;
         }
 
-        // Otherwise, we must actually perform the operation using an ArithmeticExtensionGate slot.
-        let result = self.add_base_arithmetic_operation(operation);
+        // Otherwise, we must actually perform the operation using an ArithmeticGate slot. When
+        // optimizing, this is deferred to `build`.
+        let result = if self.optimize {
+            let output = self.add_virtual_target();
+            self.pending_arithmetic_operations.push((operation, output));
+            output
+        } else {
+            self.add_base_arithmetic_operation(operation)
+        };
         self.base_arithmetic_results.insert(operation, result);
         result
     }
 
-    fn add_base_arithmetic_operation(&mut self, operation: BaseArithmeticOperation<F>) -> Target {
+    pub(crate) fn add_base_arithmetic_operation(
+        &mut self,
+        operation: BaseArithmeticOperation<F>,
+    ) -> Target {
         let gate = ArithmeticGate::new_from_config(&self.config);
         let constants = vec![operation.const_0, operation.const_1];
         let (gate, i) = self.find_slot(gate, &constants, &constants);
@@ -101,7 +111,7 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
     /// Checks for special cases where the value of
     /// `const_0 * multiplicand_0 * multiplicand_1 + const_1 * addend`
     /// can be determined without adding an `ArithmeticGate`.
-    fn arithmetic_special_cases(
+    pub(crate) fn arithmetic_special_cases(
         &mut self,
         const_0: F,
         const_1: F,
@@ -423,9 +433,9 @@ impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F, D> for Equ
 /// Represents a base arithmetic operation in the circuit. Used to memoize results.
 #[derive(Copy, Clone, Eq, PartialEq, Hash)]
 pub(crate) struct BaseArithmeticOperation<F: Field64> {
-    const_0: F,
-    const_1: F,
-    multiplicand_0: Target,
-    multiplicand_1: Target,
-    addend: Target,
+    pub(crate) const_0: F,
+    pub(crate) const_1: F,
+    pub(crate) multiplicand_0: Target,
+    pub(crate) multiplicand_1: Target,
+    pub(crate) addend: Target,
 }
@@ -0,0 +1,65 @@
+use alloc::vec::Vec;
+
+use crate::field::extension::Extendable;
+use crate::gates::expression::ExpressionGate;
+use crate::hash::hash_types::RichField;
+use crate::iop::target::Target;
+use crate::plonk::circuit_builder::CircuitBuilder;
+
+impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
+    /// Adds an operation of `gate` with the given constants, and returns the targets of its wires.
+    /// Operations with the same constants share a row.
+    pub fn add_expression_operation(
+        &mut self,
+        gate: &ExpressionGate<F, D>,
+        constants: &[F],
+    ) -> Vec<Target> {
+        assert_eq!(
+            constants.len(),
+            gate.num_consts,
+            "Expected {} constants",
+            gate.num_consts
+        );
+
+        // Unused operations in a row have zero wires, so only pack operations if that is allowed.
+        let gate = if gate.is_satisfied_by_zero_wires(constants) {
+            gate.clone()
+        } else {
+            gate.with_single_op()
+        };
+        let (row, op) = self.find_slot(gate.clone(), constants, constants);
+
+        (0..gate.wires_per_op)
+            .map(|w| Target::wire(row, gate.wire_ith_op(op, w)))
+            .collect()
+    }
+
+    /// Adds an operation of `gate`, which must have an output wire, connects its other wires to
+    /// `inputs` in order, and returns the output.
+    pub fn expression(
+        &mut self,
+        gate: &ExpressionGate<F, D>,
+        inputs: &[Target],
+        constants: &[F],
+    ) -> Target {
+        let output = gate.output.expect("Expression gate has no output wire");
+        assert_eq!(
+            inputs.len(),
+            gate.wires_per_op - 1,
+            "Expected {} inputs",
+            gate.wires_per_op - 1
+        );
+
+        let wires = self.add_expression_operation(gate, constants);
+        let input_wires = wires
+            .iter()
+            .enumerate()
+            .filter(|&(w, _)| w != output)
+            .map(|(_, &t)| t);
+        for (input, wire) in inputs.iter().zip(input_wires) {
+            self.connect(*input, wire);
+        }
+
+        wires[output]
+    }
+}
@@ -1,9 +1,11 @@
 use alloc::borrow::ToOwned;
+use alloc::sync::Arc;
 use alloc::vec;
+use alloc::vec::Vec;
 
 use crate::field::extension::Extendable;
 use crate::gates::lookup::LookupGate;
-use crate::gates::lookup_table::{LookupTable, LookupTableGate};
+use crate::gates::lookup_table::{LookupTable, LookupTableData, LookupTableGate};
 use crate::gates::noop::NoopGate;
 use crate::hash::hash_types::RichField;
 use crate::iop::target::Target;
@@ -62,22 +64,63 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         self.update_luts_from_fn(f, inputs)
     }
 
+    /// Adds a lookup table to the list of stored lookup tables `self.luts`, given as rows of `num_inputs` inputs followed by `num_outputs` outputs. It returns the index of the LUT within `self.luts`.
+    pub fn add_lookup_table_from_rows(
+        &mut self,
+        num_inputs: usize,
+        num_outputs: usize,
+        rows: &[Vec<F>],
+    ) -> usize {
+        let table = LookupTableData::from_rows(num_inputs, num_outputs, rows);
+        self.update_luts(Arc::new(table))
+    }
+
+    /// Adds a lookup table with a single input and no output to the list of stored lookup tables `self.luts`. Looking up a value in it checks that the value is one of `values`, e.g. to range check it. It returns the index of the LUT within `self.luts`.
+    pub fn add_lookup_table_from_values(&mut self, values: &[F]) -> usize {
+        self.update_luts(Arc::new(LookupTableData::from_values(values)))
+    }
+
     /// Adds a lookup (input, output) pair to the stored lookups. Takes a `Target` input and returns a `Target` output.
     pub fn add_lookup_from_index(&mut self, looking_in: Target, lut_index: usize) -> Target {
+        self.check_lut_index(lut_index);
+        let lut = self.get_lut(lut_index);
+        assert!(
+            lut.num_inputs() == 1 && lut.num_outputs() == 1,
+            "lut number {} does not map one input to one output",
+            lut_index
+        );
+        self.add_lookup(&[looking_in], lut_index)[0]
+    }
+
+    /// Adds a lookup to the stored lookups. Takes the input `Target`s of a row of the LUT and returns its output `Target`s, of which there are none if the LUT has no outputs.
+    pub fn add_lookup(&mut self, looking_inputs: &[Target], lut_index: usize) -> Vec<Target> {
+        self.check_lut_index(lut_index);
+        let lut = self.get_lut(lut_index);
+        assert_eq!(
+            looking_inputs.len(),
+            lut.num_inputs(),
+            "lut number {} takes {} inputs",
+            lut_index,
+            lut.num_inputs()
+        );
+        let looking_outputs = self.add_virtual_targets(lut.num_outputs());
+        self.update_lookups([looking_inputs, &looking_outputs].concat(), lut_index);
+        looking_outputs
+    }
+
+    fn check_lut_index(&self, lut_index: usize) {
         assert!(
             lut_index < self.get_luts_length(),
             "lut number {} not in luts (length = {})",
             lut_index,
             self.get_luts_length()
         );
-        let looking_out = self.add_virtual_target();
-        self.update_lookups(looking_in, looking_out, lut_index);
-        looking_out
     }
 
     /// We call this function at the end of circuit building right before the PI gate to add all `LookupTableGate` and `LookupGate`.
     /// It also updates `self.lookup_rows` accordingly.
     pub fn add_all_lookups(&mut self) {
+        let width = self.lookup_width();
         for lut_index in 0..self.num_luts() {
             assert!(
                 !self.get_lut_lookups(lut_index).is_empty(),
@@ -92,8 +135,8 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
 
                 let lookups = self.get_lut_lookups(lut_index).to_owned();
 
-                let gate = LookupGate::new_from_table(&self.config, lut.clone());
-                let num_slots = LookupGate::num_slots(&self.config);
+                let gate = LookupGate::new_from_table(&self.config, width, lut.clone());
+                let num_slots = LookupGate::num_slots(&self.config, width);
 
                 // Given the number of lookups and the number of slots for each gate, it is possible
                 // to compute the number of gates that will employ all their slots; such gates can
@@ -109,29 +152,27 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
                 // handle chunks that can fill all the slots of a `LookupGate`
                 lookup_iter.for_each(|chunk| {
                     let row = self.add_gate(gate.clone(), vec![]);
-                    for (i, (looking_in, looking_out)) in chunk.iter().enumerate() {
-                        let gate_in = Target::wire(row, LookupGate::wire_ith_looking_inp(i));
-                        let gate_out = Target::wire(row, LookupGate::wire_ith_looking_out(i));
-                        self.connect(gate_in, *looking_in);
-                        self.connect(gate_out, *looking_out);
+                    for (i, lookup) in chunk.iter().enumerate() {
+                        self.connect_lookup(row, width, i, lookup);
                     }
                 });
                 // deal with the last chunk
-                for (looking_in, looking_out) in last_chunk.iter() {
+                for lookup in last_chunk.iter() {
                     let (gate, i) =
                         self.find_slot(gate.clone(), &[F::from_canonical_usize(lut_index)], &[]);
-                    let gate_in = Target::wire(gate, LookupGate::wire_ith_looking_inp(i));
-                    let gate_out = Target::wire(gate, LookupGate::wire_ith_looking_out(i));
-                    self.connect(gate_in, *looking_in);
-                    self.connect(gate_out, *looking_out);
+                    self.connect_lookup(gate, width, i, lookup);
                 }
 
                 // Create LUT gates. Nothing is connected to them.
                 let last_lut_gate = self.num_gates();
-                let num_lut_entries = LookupTableGate::num_slots(&self.config);
+                let num_lut_entries = LookupTableGate::num_slots(&self.config, width);
                 let num_lut_rows = (self.get_luts_idx_length(lut_index) - 1) / num_lut_entries + 1;
-                let gate =
-                    LookupTableGate::new_from_table(&self.config, lut.clone(), last_lut_gate);
+                let gate = LookupTableGate::new_from_table(
+                    &self.config,
+                    width,
+                    lut.clone(),
+                    last_lut_gate,
+                );
                 // Also instances of `LookupTableGate` can be placed with the `add_gate` function
                 // rather than being instantiated slot by slot; note that in this case there is no
                 // need to separately handle the last chunk of LUT entries that cannot fill all the
@@ -153,4 +194,14 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
             }
         }
     }
+
+    /// Connects the targets of a lookup to the given slot of a `LookupGate`. The remaining wires of
+    /// the slot are left to zero.
+    fn connect_lookup(&mut self, row: usize, width: usize, slot: usize, lookup: &[Target]) {
+        for (col, &target) in lookup.iter().enumerate() {
+            let gate_target =
+                Target::wire(row, LookupGate::wire_ith_looking_value(width, slot, col));
+            self.connect(gate_target, target);
+        }
+    }
 }
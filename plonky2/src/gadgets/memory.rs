@@ -0,0 +1,579 @@
+use alloc::boxed::Box;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+
+use crate::field::extension::Extendable;
+use crate::hash::hash_types::RichField;
+use crate::hash::poseidon::PoseidonHash;
+use crate::iop::generator::{
+    GeneratedValues, GeneratorState, WitnessGenerator, WitnessGeneratorRef,
+};
+use crate::iop::target::{BoolTarget, Target};
+use crate::iop::witness::{PartitionWitness, Witness, WitnessWrite};
+use crate::plonk::circuit_builder::CircuitBuilder;
+use crate::plonk::circuit_data::CommonCircuitData;
+use crate::util::log2_ceil;
+use crate::util::serialization::{Buffer, IoResult, Read, Write};
+
+/// A handle to a read/write memory added with [`CircuitBuilder::add_memory`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct MemoryTarget {
+    index: usize,
+}
+
+/// A memory's initial contents, and the operations performed on it so far, in order.
+#[derive(Clone, Debug)]
+pub(crate) struct Memory {
+    initial_values: Vec<Target>,
+    operations: Vec<MemoryOperation>,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct MemoryOperation {
+    address: Target,
+    value: Target,
+    is_write: bool,
+}
+
+/// An access to a memory, either one of its operations or the write of an initial value at
+/// timestamp zero.
+#[derive(Copy, Clone, Debug)]
+struct MemoryEntryTarget {
+    address: Target,
+    timestamp: Target,
+    is_write: Target,
+    value: Target,
+}
+
+impl MemoryEntryTarget {
+    const fn targets(&self) -> [Target; 4] {
+        [self.address, self.timestamp, self.is_write, self.value]
+    }
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
+    /// Adds a memory with the given initial contents, which can then be read from and written to
+    /// at addresses computed in the circuit with `memory_read` and `memory_write`.
+    ///
+    /// Memory consistency is checked when the circuit is built, with offline memory checking:
+    /// the accesses are sorted by address and timestamp, each read is checked against the
+    /// previous access to the same address, and the sorted accesses are checked to be a
+    /// permutation of the original ones. This costs a constant number of gates per access, rather
+    /// than a number proportional to the memory size as with `random_access`.
+    pub fn add_memory(&mut self, initial_values: Vec<Target>) -> MemoryTarget {
+        assert!(!initial_values.is_empty(), "Memory must not be empty");
+        self.memories.push(Memory {
+            initial_values,
+            operations: Vec::new(),
+        });
+        MemoryTarget {
+            index: self.memories.len() - 1,
+        }
+    }
+
+    /// Reads the value of `memory` at `address`, which must be less than its size.
+    pub fn memory_read(&mut self, memory: MemoryTarget, address: Target) -> Target {
+        let value = self.add_virtual_target();
+        self.memories[memory.index]
+            .operations
+            .push(MemoryOperation {
+                address,
+                value,
+                is_write: false,
+            });
+        value
+    }
+
+    /// Writes `value` to `memory` at `address`, which must be less than its size.
+    pub fn memory_write(&mut self, memory: MemoryTarget, address: Target, value: Target) {
+        self.memories[memory.index]
+            .operations
+            .push(MemoryOperation {
+                address,
+                value,
+                is_write: true,
+            });
+    }
+
+    /// Adds the consistency checks of all memories. Called when the circuit is built, once all
+    /// operations are known.
+    pub(crate) fn add_all_memory_checks(&mut self) {
+        for memory in core::mem::take(&mut self.memories) {
+            self.add_memory_checks(memory);
+        }
+    }
+
+    fn add_memory_checks(&mut self, memory: Memory) {
+        let Memory {
+            initial_values,
+            operations,
+        } = memory;
+        let size = initial_values.len();
+
+        // Initial values are written at timestamp 0, and operation `i` happens at timestamp
+        // `i + 1`.
+        let mut entries = Vec::with_capacity(size + operations.len());
+        let mut timestamp = self.zero();
+        for (address, &value) in initial_values.iter().enumerate() {
+            entries.push(MemoryEntryTarget {
+                address: self.constant(F::from_canonical_usize(address)),
+                timestamp,
+                is_write: self._true().target,
+                value,
+            });
+        }
+        for operation in &operations {
+            timestamp = self.add_const(timestamp, F::ONE);
+            entries.push(MemoryEntryTarget {
+                address: operation.address,
+                timestamp,
+                is_write: self.constant_bool(operation.is_write).target,
+                value: operation.value,
+            });
+        }
+
+        let sorted = (0..entries.len())
+            .map(|_| MemoryEntryTarget {
+                address: self.add_virtual_target(),
+                timestamp: self.add_virtual_target(),
+                is_write: self.add_virtual_target(),
+                value: self.add_virtual_target(),
+            })
+            .collect::<Vec<_>>();
+        self.add_generators(vec![WitnessGeneratorRef::new(MemoryGenerator::<F, D> {
+            initial_values,
+            operations,
+            sorted: sorted.clone(),
+            _phantom: PhantomData,
+        })]);
+
+        self.check_sorted_memory_entries(&sorted, size);
+        self.check_memory_permutation(&entries, &sorted);
+    }
+
+    /// Checks that `sorted` lists the accesses to a memory of the given size by address, then
+    /// timestamp, and that each read returns the value of the previous access to its address.
+    fn check_sorted_memory_entries(&mut self, sorted: &[MemoryEntryTarget], size: usize) {
+        let zero = self.zero();
+        let one = self.one();
+        let last_address = self.constant(F::from_canonical_usize(size - 1));
+        self.connect(sorted[0].address, zero);
+        self.connect(sorted[sorted.len() - 1].address, last_address);
+
+        // Timestamps range from 0 to the number of operations.
+        let timestamp_bits = log2_ceil(sorted.len() - size + 1).max(1);
+        for pair in sorted.windows(2) {
+            let (current, next) = (pair[0], pair[1]);
+
+            // Addresses start at 0, end at `size - 1` and increase by 0 or 1 at each step, so they
+            // are all in range.
+            let address_step = self.sub(next.address, current.address);
+            self.assert_bool(BoolTarget::new_unsafe(address_step));
+            let same_address = self.sub(one, address_step);
+
+            // Timestamps increase for each address.
+            let timestamp_step = self.sub(next.timestamp, current.timestamp);
+            let timestamp_gap = self.arithmetic(
+                F::ONE,
+                F::NEG_ONE,
+                same_address,
+                timestamp_step,
+                same_address,
+            );
+            self.range_check(timestamp_gap, timestamp_bits);
+
+            // Since each address starts with its initial value at timestamp 0, every read follows
+            // an access to the same address, whose value it must return.
+            let is_read = self.sub(one, next.is_write);
+            let value_change = self.sub(next.value, current.value);
+            let stale_read = self.mul_many([same_address, is_read, value_change]);
+            self.assert_zero(stale_read);
+        }
+    }
+
+    /// Checks that `sorted` is a permutation of `entries`, by comparing the products of
+    /// `gamma - (address + alpha * timestamp + alpha^2 * is_write + alpha^3 * value)` over both
+    /// lists, for challenges `alpha` and `gamma` derived from a hash of both lists.
+    fn check_memory_permutation(
+        &mut self,
+        entries: &[MemoryEntryTarget],
+        sorted: &[MemoryEntryTarget],
+    ) {
+        let inputs = entries
+            .iter()
+            .chain(sorted)
+            .flat_map(MemoryEntryTarget::targets)
+            .collect();
+        let challenges =
+            self.hash_n_to_m_no_pad::<PoseidonHash>(inputs, 2 * self.config.num_challenges);
+
+        for pair in challenges.chunks_exact(2) {
+            let (alpha, gamma) = (pair[0], pair[1]);
+            let entries_product = self.memory_entries_product(entries, alpha, gamma);
+            let sorted_product = self.memory_entries_product(sorted, alpha, gamma);
+            self.connect(entries_product, sorted_product);
+        }
+    }
+
+    fn memory_entries_product(
+        &mut self,
+        entries: &[MemoryEntryTarget],
+        alpha: Target,
+        gamma: Target,
+    ) -> Target {
+        let mut product = self.one();
+        for entry in entries {
+            let mut combination = entry.value;
+            for target in [entry.is_write, entry.timestamp, entry.address] {
+                combination = self.mul_add(combination, alpha, target);
+            }
+            let factor = self.sub(gamma, combination);
+            product = self.mul(product, factor);
+        }
+        product
+    }
+}
+
+/// Simulates a memory's operations in order, filling in the values read, and then the sorted
+/// accesses used by its consistency checks. Addresses and written values may depend on earlier
+/// reads, so this runs as far as it can each time one of them becomes known, and saves its
+/// progress in its state so that the next run resumes where this one stopped.
+#[derive(Debug)]
+pub struct MemoryGenerator<F: RichField + Extendable<D>, const D: usize> {
+    initial_values: Vec<Target>,
+    operations: Vec<MemoryOperation>,
+    sorted: Vec<MemoryEntryTarget>,
+    _phantom: PhantomData<F>,
+}
+
+/// The state of a memory's simulation after its first `next_operation` operations.
+#[derive(Clone, Debug)]
+struct MemoryProgress<F> {
+    next_operation: usize,
+    /// The value of each address, or `None` if it hasn't been written and its initial value was
+    /// not known yet.
+    values: Vec<Option<F>>,
+    /// The accesses performed so far, as `(address, timestamp, is_write, value)`.
+    accesses: Vec<(usize, usize, bool, F)>,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> WitnessGenerator<F, D>
+    for MemoryGenerator<F, D>
+{
+    fn id(&self) -> String {
+        MEMORY_GENERATOR_ID.to_string()
+    }
+
+    fn watch_list(&self) -> Vec<Target> {
+        let mut watch_list = self.initial_values.clone();
+        for operation in &self.operations {
+            watch_list.push(operation.address);
+            if operation.is_write {
+                watch_list.push(operation.value);
+            }
+        }
+        watch_list
+    }
+
+    fn run(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) -> bool {
+        self.run_with_state(witness, out_buffer, &mut None)
+    }
+
+    fn run_with_state(
+        &self,
+        witness: &PartitionWitness<F>,
+        out_buffer: &mut GeneratedValues<F>,
+        state: &mut GeneratorState,
+    ) -> bool {
+        let size = self.initial_values.len();
+        let progress = state
+            .get_or_insert_with(|| {
+                Box::new(MemoryProgress::<F> {
+                    next_operation: 0,
+                    values: vec![None; size],
+                    accesses: Vec::with_capacity(size + self.operations.len()),
+                })
+            })
+            .downcast_mut::<MemoryProgress<F>>()
+            .expect("A memory generator's state is its progress");
+
+        while let Some(operation) = self.operations.get(progress.next_operation) {
+            let Some(address) = witness.try_get_target(operation.address) else {
+                return false;
+            };
+            let address = address.to_canonical_u64() as usize;
+            assert!(
+                address < size,
+                "Memory address {} is out of bounds for a memory of size {}",
+                address,
+                size
+            );
+
+            let value = if operation.is_write {
+                witness.try_get_target(operation.value)
+            } else {
+                progress.values[address]
+                    .or_else(|| witness.try_get_target(self.initial_values[address]))
+            };
+            let Some(value) = value else {
+                return false;
+            };
+            if !operation.is_write {
+                out_buffer.set_target(operation.value, value);
+            }
+            progress.values[address] = Some(value);
+            progress.next_operation += 1;
+            progress
+                .accesses
+                .push((address, progress.next_operation, operation.is_write, value));
+        }
+
+        // The initial values of addresses which were written before being read may not be known
+        // yet.
+        let mut accesses = progress.accesses.clone();
+        for (address, &initial_value) in self.initial_values.iter().enumerate() {
+            let Some(initial_value) = witness.try_get_target(initial_value) else {
+                return false;
+            };
+            accesses.push((address, 0, true, initial_value));
+        }
+
+        accesses.sort_unstable_by_key(|&(address, timestamp, _, _)| (address, timestamp));
+        for (entry, (address, timestamp, is_write, value)) in self.sorted.iter().zip(accesses) {
+            out_buffer.set_target(entry.address, F::from_canonical_usize(address));
+            out_buffer.set_target(entry.timestamp, F::from_canonical_usize(timestamp));
+            out_buffer.set_target(entry.is_write, F::from_bool(is_write));
+            out_buffer.set_target(entry.value, value);
+        }
+        true
+    }
+
+    fn serialize(&self, dst: &mut Vec<u8>, _common_data: &CommonCircuitData<F, D>) -> IoResult<()> {
+        dst.write_target_vec(&self.initial_values)?;
+        dst.write_usize(self.operations.len())?;
+        for operation in &self.operations {
+            dst.write_target(operation.address)?;
+            dst.write_target(operation.value)?;
+            dst.write_bool(operation.is_write)?;
+        }
+        dst.write_usize(self.sorted.len())?;
+        for entry in &self.sorted {
+            for target in entry.targets() {
+                dst.write_target(target)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn deserialize(src: &mut Buffer, _common_data: &CommonCircuitData<F, D>) -> IoResult<Self> {
+        let initial_values = src.read_target_vec()?;
+        let num_operations = src.read_usize()?;
+        let operations = (0..num_operations)
+            .map(|_| {
+                Ok(MemoryOperation {
+                    address: src.read_target()?,
+                    value: src.read_target()?,
+                    is_write: src.read_bool()?,
+                })
+            })
+            .collect::<IoResult<_>>()?;
+        let num_entries = src.read_usize()?;
+        let sorted = (0..num_entries)
+            .map(|_| {
+                Ok(MemoryEntryTarget {
+                    address: src.read_target()?,
+                    timestamp: src.read_target()?,
+                    is_write: src.read_target()?,
+                    value: src.read_target()?,
+                })
+            })
+            .collect::<IoResult<_>>()?;
+        Ok(Self {
+            initial_values,
+            operations,
+            sorted,
+            _phantom: PhantomData,
+        })
+    }
+}
+
+/// The id of [`MemoryGenerator`]s, to register [`read_memory_generator`] with serializers.
+pub const MEMORY_GENERATOR_ID: &str = "MemoryGenerator";
+
+/// Reads a [`MemoryGenerator`]. Since it isn't a [`SimpleGenerator`], serializers register this
+/// function with [`GeneratorRegistry::with_reader`] rather than listing the generator's type.
+///
+/// [`SimpleGenerator`]: crate::iop::generator::SimpleGenerator
+/// [`GeneratorRegistry::with_reader`]: crate::util::serialization::GeneratorRegistry::with_reader
+pub fn read_memory_generator<F: RichField + Extendable<D>, const D: usize>(
+    buf: &mut Buffer,
+    common_data: &CommonCircuitData<F, D>,
+) -> IoResult<WitnessGeneratorRef<F, D>> {
+    let generator = MemoryGenerator::<F, D>::deserialize(buf, common_data)?;
+    Ok(WitnessGeneratorRef::new(generator))
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use super::*;
+    use crate::field::types::Field;
+    use crate::iop::witness::PartialWitness;
+    use crate::plonk::circuit_data::{CircuitConfig, CircuitData};
+    use crate::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+    use crate::util::serialization::{
+        DefaultGateSerializer, DefaultGeneratorSerializer, GateRegistry, GeneratorRegistry,
+    };
+
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+
+    const INITIAL_VALUES: [usize; 5] = [3, 0, 4, 1, 2];
+
+    /// A circuit following pointers through a memory, overwriting each cell it visits, and then
+    /// reading back the whole memory. Returns the initial values to set, and the expected public
+    /// inputs.
+    fn pointer_chasing_circuit(builder: &mut CircuitBuilder<F, D>) -> (Vec<Target>, Vec<F>) {
+        let size = INITIAL_VALUES.len();
+        let initial_values = builder.add_virtual_targets(size);
+        let memory = builder.add_memory(initial_values.clone());
+        let mut values = INITIAL_VALUES.to_vec();
+        let mut expected = Vec::new();
+
+        let mut pointer = builder.zero();
+        let mut pointer_value = 0;
+        for step in 0..8 {
+            let next = builder.memory_read(memory, pointer);
+            builder.register_public_input(next);
+            let next_value = values[pointer_value];
+            expected.push(next_value);
+
+            let overwrite = (2 * step + 1) % size;
+            let overwrite_target = builder.constant(F::from_canonical_usize(overwrite));
+            builder.memory_write(memory, pointer, overwrite_target);
+            values[pointer_value] = overwrite;
+
+            pointer = next;
+            pointer_value = next_value;
+        }
+
+        for (address, &value) in values.iter().enumerate() {
+            let address = builder.constant(F::from_canonical_usize(address));
+            let read = builder.memory_read(memory, address);
+            builder.register_public_input(read);
+            expected.push(value);
+        }
+
+        let expected = expected.into_iter().map(F::from_canonical_usize).collect();
+        (initial_values, expected)
+    }
+
+    fn witness(initial_values: &[Target]) -> PartialWitness<F> {
+        let mut pw = PartialWitness::new();
+        for (&target, &value) in initial_values.iter().zip(&INITIAL_VALUES) {
+            pw.set_target(target, F::from_canonical_usize(value));
+        }
+        pw
+    }
+
+    #[test]
+    fn test_memory() -> Result<()> {
+        let mut builder = CircuitBuilder::<F, D>::new(CircuitConfig::standard_recursion_config());
+        let (initial_values, expected) = pointer_chasing_circuit(&mut builder);
+        let data = builder.build::<C>();
+
+        // The memory's simulation starts afresh in each witness generation.
+        for _ in 0..2 {
+            let proof = data.prove(witness(&initial_values))?;
+            assert_eq!(proof.public_inputs, expected);
+            data.verify(proof)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn test_out_of_bounds() {
+        let mut builder = CircuitBuilder::<F, D>::new(CircuitConfig::standard_recursion_config());
+        let initial_values = builder.add_virtual_targets(4);
+        let memory = builder.add_memory(initial_values.clone());
+        let address = builder.constant(F::from_canonical_usize(4));
+        builder.memory_read(memory, address);
+        let data = builder.build::<C>();
+
+        let mut pw = PartialWitness::new();
+        for target in initial_values {
+            pw.set_target(target, F::ZERO);
+        }
+        data.prove(pw).unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "set twice with different values")]
+    fn test_stale_read() {
+        // The accesses are sorted, and trivially a permutation of themselves, but the read doesn't
+        // return the initial value, so witness generation fails to set the stale read check to
+        // zero.
+        let mut builder = CircuitBuilder::<F, D>::new(CircuitConfig::standard_recursion_config());
+        let (zero, one) = (builder.zero(), builder.one());
+        let initial_value = builder.add_virtual_target();
+        let read_value = builder.add_virtual_target();
+        let entries = [
+            MemoryEntryTarget {
+                address: zero,
+                timestamp: zero,
+                is_write: one,
+                value: initial_value,
+            },
+            MemoryEntryTarget {
+                address: zero,
+                timestamp: one,
+                is_write: zero,
+                value: read_value,
+            },
+        ];
+        builder.check_sorted_memory_entries(&entries, 1);
+        builder.check_memory_permutation(&entries, &entries);
+        let data = builder.build::<C>();
+
+        let mut pw = PartialWitness::new();
+        pw.set_target(initial_value, F::from_canonical_u64(5));
+        pw.set_target(read_value, F::from_canonical_u64(7));
+        let proof = data.prove(pw).unwrap();
+        data.verify(proof).unwrap()
+    }
+
+    #[test]
+    fn test_memory_serialization() -> Result<()> {
+        let mut builder = CircuitBuilder::<F, D>::new(CircuitConfig::standard_recursion_config());
+        let (initial_values, expected) = pointer_chasing_circuit(&mut builder);
+        let data = builder.build::<C>();
+
+        let gate_serializer = DefaultGateSerializer;
+        let generator_serializer = DefaultGeneratorSerializer::<C, D> {
+            _phantom: PhantomData,
+        };
+        let bytes = data
+            .to_bytes(&gate_serializer, &generator_serializer)
+            .unwrap();
+        let data_from_bytes =
+            CircuitData::<F, C, D>::from_bytes(&bytes, &gate_serializer, &generator_serializer)
+                .unwrap();
+        assert_eq!(data, data_from_bytes);
+
+        let gate_registry = GateRegistry::default();
+        let generator_registry = GeneratorRegistry::<C, D>::default();
+        let bytes = data.to_bytes(&gate_registry, &generator_registry).unwrap();
+        let data_from_bytes =
+            CircuitData::<F, C, D>::from_bytes(&bytes, &gate_registry, &generator_registry)
+                .unwrap();
+
+        let proof = data_from_bytes.prove(witness(&initial_values))?;
+        assert_eq!(proof.public_inputs, expected);
+        data_from_bytes.verify(proof)
+    }
+}
@@ -4,9 +4,11 @@
 
 pub mod arithmetic;
 pub mod arithmetic_extension;
+pub mod expression;
 pub mod hash;
 pub mod interpolation;
 pub mod lookup;
+pub mod memory;
 pub mod polynomial;
 pub mod random_access;
 pub mod range_check;
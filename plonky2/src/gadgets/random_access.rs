@@ -1,41 +1,63 @@
+use alloc::vec;
 use alloc::vec::Vec;
 
 use crate::field::extension::Extendable;
 use crate::gates::random_access::RandomAccessGate;
-use crate::hash::hash_types::{HashOutTarget, MerkleCapTarget, RichField};
+use crate::hash::hash_types::{HashOutTarget, MerkleCapTarget, RichField, NUM_HASH_OUT_ELTS};
 use crate::iop::ext_target::ExtensionTarget;
 use crate::iop::target::Target;
 use crate::plonk::circuit_builder::CircuitBuilder;
 use crate::plonk::circuit_data::VerifierCircuitTarget;
-use crate::util::log2_strict;
+use crate::util::log2_ceil;
 
 impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
-    /// Checks that a `Target` matches a vector at a particular index.
+    /// Checks that a `Target` matches a vector at a particular index. If the length of the vector
+    /// is not a power of two, the index is also checked to be less than it.
     pub fn random_access(&mut self, access_index: Target, v: Vec<Target>) -> Target {
-        let vec_size = v.len();
-        let bits = log2_strict(vec_size);
+        self.random_access_many(access_index, vec![v])[0]
+    }
+
+    /// Reads the element at `access_index` of each of the given vectors, which must have the same
+    /// length. The index is only range-checked once, as in `random_access`.
+    fn random_access_many(&mut self, access_index: Target, vs: Vec<Vec<Target>>) -> Vec<Target> {
+        let vec_size = vs[0].len();
         debug_assert!(vec_size > 0);
+        assert!(vs.iter().all(|v| v.len() == vec_size));
         if vec_size == 1 {
-            return v[0];
+            return vs.into_iter().map(|v| v[0]).collect();
         }
-        let claimed_element = self.add_virtual_target();
-
+        let bits = log2_ceil(vec_size);
+        if !vec_size.is_power_of_two() {
+            // `RandomAccessGate` reads from vectors of length `2^bits`, so the vectors are padded,
+            // and the indices of the padding are ruled out.
+            let last_index = self.constant(F::from_canonical_usize(vec_size - 1));
+            let index_gap = self.sub(last_index, access_index);
+            self.range_check(index_gap, bits);
+        }
+        let zero = self.zero();
         let dummy_gate = RandomAccessGate::<F, D>::new_from_config(&self.config, bits);
-        let (row, copy) = self.find_slot(dummy_gate, &[], &[]);
-
-        v.iter().enumerate().for_each(|(i, &val)| {
-            self.connect(val, Target::wire(row, dummy_gate.wire_list_item(i, copy)));
-        });
-        self.connect(
-            access_index,
-            Target::wire(row, dummy_gate.wire_access_index(copy)),
-        );
-        self.connect(
-            claimed_element,
-            Target::wire(row, dummy_gate.wire_claimed_element(copy)),
-        );
-
-        claimed_element
+
+        vs.into_iter()
+            .map(|mut v| {
+                v.resize(1 << bits, zero);
+                let claimed_element = self.add_virtual_target();
+                let (row, copy) = self.find_slot(dummy_gate, &[], &[]);
+
+                v.iter().enumerate().for_each(|(i, &val)| {
+                    self.connect(val, Target::wire(row, dummy_gate.wire_list_item(i, copy)));
+                });
+                self.connect(
+                    access_index,
+                    Target::wire(row, dummy_gate.wire_access_index(copy)),
+                );
+                self.connect(
+                    claimed_element,
+                    Target::wire(row, dummy_gate.wire_claimed_element(copy)),
+                );
+
+                claimed_element
+            })
+            .collect()
     }
 
     /// Like `random_access`, but with `ExtensionTarget`s rather than simple `Target`s.
@@ -44,9 +66,10 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         access_index: Target,
         v: Vec<ExtensionTarget<D>>,
     ) -> ExtensionTarget<D> {
-        let selected: Vec<_> = (0..D)
-            .map(|i| self.random_access(access_index, v.iter().map(|et| et.0[i]).collect()))
+        let vs = (0..D)
+            .map(|i| v.iter().map(|et| et.0[i]).collect())
             .collect();
+        let selected = self.random_access_many(access_index, vs);
 
         ExtensionTarget(selected.try_into().unwrap())
     }
@@ -57,13 +80,11 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         access_index: Target,
         v: Vec<HashOutTarget>,
     ) -> HashOutTarget {
-        let selected = core::array::from_fn(|i| {
-            self.random_access(
-                access_index,
-                v.iter().map(|hash| hash.elements[i]).collect(),
-            )
-        });
-        selected.into()
+        let vs = (0..NUM_HASH_OUT_ELTS)
+            .map(|i| v.iter().map(|hash| hash.elements[i]).collect())
+            .collect();
+        let selected = self.random_access_many(access_index, vs);
+        HashOutTarget::from_vec(selected)
     }
 
     /// Like `random_access`, but with `MerkleCapTarget`s rather than simple `Target`s.
@@ -75,10 +96,20 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         let cap_size = v[0].0.len();
         assert!(v.iter().all(|cap| cap.0.len() == cap_size));
 
-        let selected = (0..cap_size)
-            .map(|i| self.random_access_hash(access_index, v.iter().map(|cap| cap.0[i]).collect()))
+        let vs = (0..cap_size)
+            .flat_map(|i| {
+                let v = &v;
+                (0..NUM_HASH_OUT_ELTS)
+                    .map(move |j| v.iter().map(|cap| cap.0[i].elements[j]).collect())
+            })
             .collect();
-        MerkleCapTarget(selected)
+        let selected = self.random_access_many(access_index, vs);
+        MerkleCapTarget(
+            selected
+                .chunks_exact(NUM_HASH_OUT_ELTS)
+                .map(|elements| HashOutTarget::from_vec(elements.to_vec()))
+                .collect(),
+        )
     }
 
     /// Like `random_access`, but with `VerifierCircuitTarget`s rather than simple `Target`s.
@@ -87,11 +118,18 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         access_index: Target,
         v: Vec<VerifierCircuitTarget>,
     ) -> VerifierCircuitTarget {
-        let constants_sigmas_caps = v.iter().map(|vk| vk.constants_sigmas_cap.clone()).collect();
-        let circuit_digests = v.iter().map(|vk| vk.circuit_digest).collect();
-        let constants_sigmas_cap =
-            self.random_access_merkle_cap(access_index, constants_sigmas_caps);
-        let circuit_digest = self.random_access_hash(access_index, circuit_digests);
+        // The circuit digest is read along with the cap, as one more of its hashes.
+        let caps_with_digests = v
+            .iter()
+            .map(|vk| {
+                let mut cap = vk.constants_sigmas_cap.clone();
+                cap.0.push(vk.circuit_digest);
+                cap
+            })
+            .collect();
+        let mut constants_sigmas_cap =
+            self.random_access_merkle_cap(access_index, caps_with_digests);
+        let circuit_digest = constants_sigmas_cap.0.pop().unwrap();
         VerifierCircuitTarget {
             constants_sigmas_cap,
             circuit_digest,
@@ -105,11 +143,16 @@ mod tests {
 
     use super::*;
     use crate::field::types::{Field, Sample};
-    use crate::iop::witness::PartialWitness;
-    use crate::plonk::circuit_data::CircuitConfig;
+    use crate::hash::hash_types::HashOut;
+    use crate::iop::witness::{PartialWitness, WitnessWrite};
+    use crate::plonk::circuit_data::{CircuitConfig, CircuitData};
     use crate::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
     use crate::plonk::verifier::verify;
 
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+
     fn test_random_access_given_len(len_log: usize) -> Result<()> {
         const D: usize = 2;
         type C = PoseidonGoldilocksConfig;
@@ -142,4 +185,82 @@ mod tests {
         }
         Ok(())
     }
+
+    fn random_access_circuit(
+        len: usize,
+        index: usize,
+    ) -> (CircuitData<F, C, D>, PartialWitness<F>) {
+        let mut builder = CircuitBuilder::<F, D>::new(CircuitConfig::standard_recursion_config());
+        let values = F::rand_vec(len);
+        let v = builder.add_virtual_targets(len);
+        let access_index = builder.add_virtual_target();
+        let element = builder.random_access(access_index, v.clone());
+        builder.register_public_input(element);
+        let data = builder.build::<C>();
+
+        let mut pw = PartialWitness::new();
+        pw.set_target_arr(&v, &values);
+        pw.set_target(access_index, F::from_canonical_usize(index));
+        (data, pw)
+    }
+
+    #[test]
+    fn test_random_access_non_power_of_two() -> Result<()> {
+        for len in [3, 5, 6, 7] {
+            for index in 0..len {
+                let (data, pw) = random_access_circuit(len, index);
+                let proof = data.prove(pw)?;
+                data.verify(proof)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn random_access_hash_circuit(
+        len: usize,
+        index: usize,
+    ) -> (CircuitData<F, C, D>, PartialWitness<F>) {
+        let mut builder = CircuitBuilder::<F, D>::new(CircuitConfig::standard_recursion_config());
+        let hashes = (0..len)
+            .map(|_| builder.add_virtual_hash())
+            .collect::<Vec<_>>();
+        let access_index = builder.add_virtual_target();
+        let hash = builder.random_access_hash(access_index, hashes.clone());
+        builder.register_public_inputs(&hash.elements);
+        let data = builder.build::<C>();
+
+        let mut pw = PartialWitness::new();
+        for &hash in &hashes {
+            pw.set_hash_target(hash, HashOut::rand());
+        }
+        pw.set_target(access_index, F::from_canonical_usize(index));
+        (data, pw)
+    }
+
+    #[test]
+    fn test_random_access_hash_non_power_of_two() -> Result<()> {
+        for index in 0..5 {
+            let (data, pw) = random_access_hash_circuit(5, index);
+            let proof = data.prove(pw)?;
+            data.verify(proof)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    #[should_panic(expected = "set twice with different values")]
+    fn test_random_access_hash_out_of_range() {
+        let (data, pw) = random_access_hash_circuit(5, 7);
+        let proof = data.prove(pw).unwrap();
+        data.verify(proof).unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "set twice with different values")]
+    fn test_random_access_out_of_range() {
+        // Reads from the padding to a length of 8.
+        let (data, pw) = random_access_circuit(5, 6);
+        let proof = data.prove(pw).unwrap();
+        data.verify(proof).unwrap();
+    }
 }
@@ -56,6 +56,27 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
 
         bits
     }
+
+    /// Like `split_le` with `F::BITS` bits, but also checks that the bits encode a value below the
+    /// field order, so that they are the canonical representation of `integer`.
+    pub fn split_le_canonical(&mut self, integer: Target) -> Vec<BoolTarget> {
+        let bits = self.split_le(integer, F::BITS);
+
+        // Walk down from the most significant bit, tracking whether the bits so far match those of
+        // `p - 1`. While they do, a bit may only be set where `p - 1` has a one.
+        let max = F::NEG_ONE.to_canonical_u64();
+        let mut prefix_is_max = self._true();
+        for i in (0..F::BITS).rev() {
+            if (max >> i) & 1 == 1 {
+                prefix_is_max = self.and(prefix_is_max, bits[i]);
+            } else {
+                let exceeds_max = self.and(prefix_is_max, bits[i]);
+                self.assert_zero(exceeds_max.target);
+            }
+        }
+
+        bits
+    }
 }
 
 #[derive(Debug, Default)]
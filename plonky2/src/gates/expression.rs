@@ -0,0 +1,612 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::field::extension::{Extendable, FieldExtension};
+use crate::field::packed::PackedField;
+use crate::field::types::Field;
+use crate::gates::gate::Gate;
+use crate::gates::packed_util::PackedEvaluableBase;
+use crate::gates::util::StridedConstraintConsumer;
+use crate::hash::hash_types::RichField;
+use crate::iop::ext_target::ExtensionTarget;
+use crate::iop::generator::{GeneratedValues, SimpleGenerator, WitnessGeneratorRef};
+use crate::iop::target::Target;
+use crate::iop::witness::{PartitionWitness, Witness, WitnessWrite};
+use crate::plonk::circuit_builder::CircuitBuilder;
+use crate::plonk::circuit_data::{CircuitConfig, CommonCircuitData};
+use crate::plonk::vars::{
+    EvaluationTargets, EvaluationVars, EvaluationVarsBase, EvaluationVarsBaseBatch,
+    EvaluationVarsBasePacked,
+};
+use crate::util::expression::Expression;
+use crate::util::serialization::{Buffer, IoError, IoResult, Read, Write};
+
+/// A variable of the constraints of an [`ExpressionGate`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum GateVar {
+    /// The wire with the given index within the operation.
+    Wire(usize),
+    /// The gate constant with the given index.
+    Constant(usize),
+}
+
+/// A polynomial in the wires of one operation of an [`ExpressionGate`] and the constants of the
+/// gate.
+pub type GateExpression<F> = Expression<F, GateVar>;
+
+/// The degree of an expression, where wires and constants both have degree one, since they are
+/// interpolated by polynomials.
+fn degree<F: Field>(expression: &GateExpression<F>) -> usize {
+    expression.degree(&|_| 1)
+}
+
+/// The degree of an expression in the given wire alone.
+fn degree_in_wire<F: Field>(expression: &GateExpression<F>, wire: usize) -> usize {
+    expression.degree(&|&var| usize::from(var == GateVar::Wire(wire)))
+}
+
+/// The indices of the wires referenced by an expression, without duplicates.
+fn wires<F: Field>(expression: &GateExpression<F>) -> Vec<usize> {
+    let mut wires = Vec::new();
+    expression.for_each_var(&mut |&var| {
+        if let GateVar::Wire(w) = var {
+            if !wires.contains(&w) {
+                wires.push(w);
+            }
+        }
+    });
+    wires
+}
+
+/// The number of wires referenced by an expression, i.e. one more than the largest wire index, or
+/// zero.
+fn num_wires<F: Field>(expression: &GateExpression<F>) -> usize {
+    wires(expression).into_iter().max().map_or(0, |w| w + 1)
+}
+
+/// The number of constants referenced by an expression, i.e. one more than the largest constant
+/// index, or zero.
+fn num_constants<F: Field>(expression: &GateExpression<F>) -> usize {
+    let mut num_constants = 0;
+    expression.for_each_var(&mut |&var| {
+        if let GateVar::Constant(c) = var {
+            num_constants = num_constants.max(c + 1);
+        }
+    });
+    num_constants
+}
+
+/// Evaluates an expression in the base field.
+fn eval_base<F: Field>(
+    expression: &GateExpression<F>,
+    wires: &impl Fn(usize) -> F,
+    constants: &[F],
+) -> F {
+    expression.eval(
+        &|&var| match var {
+            GateVar::Wire(w) => wires(w),
+            GateVar::Constant(c) => constants[c],
+        },
+        &|x| x,
+    )
+}
+
+fn write_expression<F: RichField>(
+    dst: &mut Vec<u8>,
+    expression: &GateExpression<F>,
+) -> IoResult<()> {
+    match expression {
+        Expression::Var(GateVar::Wire(w)) => {
+            dst.write_u8(0)?;
+            dst.write_usize(*w)
+        }
+        Expression::Var(GateVar::Constant(c)) => {
+            dst.write_u8(1)?;
+            dst.write_usize(*c)
+        }
+        Expression::Literal(x) => {
+            dst.write_u8(2)?;
+            dst.write_field(*x)
+        }
+        Expression::Add(a, b) | Expression::Sub(a, b) | Expression::Mul(a, b) => {
+            let tag = match expression {
+                Expression::Add(..) => 3,
+                Expression::Sub(..) => 4,
+                _ => 5,
+            };
+            dst.write_u8(tag)?;
+            write_expression(dst, a)?;
+            write_expression(dst, b)
+        }
+        Expression::Neg(a) => {
+            dst.write_u8(6)?;
+            write_expression(dst, a)
+        }
+    }
+}
+
+fn read_expression<F: RichField>(src: &mut Buffer) -> IoResult<GateExpression<F>> {
+    Ok(match src.read_u8()? {
+        0 => Expression::Var(GateVar::Wire(src.read_usize()?)),
+        1 => Expression::Var(GateVar::Constant(src.read_usize()?)),
+        2 => Expression::Literal(src.read_field()?),
+        3 => read_expression(src)? + read_expression(src)?,
+        4 => read_expression(src)? - read_expression(src)?,
+        5 => read_expression(src)? * read_expression(src)?,
+        6 => -read_expression(src)?,
+        _ => return Err(IoError),
+    })
+}
+
+/// A gate enforcing a set of polynomial constraints over the wires of an operation and the
+/// constants of the gate. If the config has enough routed wires, it can support several such
+/// operations in one gate, which share the gate's constants.
+///
+/// If an output wire is given, the gate's generators compute it from the operation's other wires,
+/// using the first constraint, which must be linear in the output.
+#[derive(Debug, Clone)]
+pub struct ExpressionGate<F: RichField + Extendable<D>, const D: usize> {
+    /// The constraints of each operation.
+    pub constraints: Vec<GateExpression<F>>,
+    /// The index of the wire of each operation computed by the generators, if any.
+    pub output: Option<usize>,
+    /// The number of wires of each operation.
+    pub wires_per_op: usize,
+    /// The number of constants of the gate.
+    pub num_consts: usize,
+    /// The number of operations performed by the gate.
+    pub num_ops: usize,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> ExpressionGate<F, D> {
+    /// Creates a gate enforcing `constraints`, with as many operations as fit in the routed wires
+    /// of the config. The wires of each operation are those referenced by the constraints.
+    pub fn new_from_config(
+        config: &CircuitConfig,
+        constraints: Vec<GateExpression<F>>,
+        output: Option<usize>,
+    ) -> Self {
+        assert!(
+            !constraints.is_empty(),
+            "An ExpressionGate needs constraints"
+        );
+        let wires_per_op = constraints.iter().map(num_wires).max().unwrap();
+        let num_consts = constraints.iter().map(num_constants).max().unwrap();
+        assert!(
+            wires_per_op > 0 && wires_per_op <= config.num_routed_wires,
+            "Constraints must use between 1 and {} wires, but use {}",
+            config.num_routed_wires,
+            wires_per_op
+        );
+
+        let gate = Self {
+            constraints,
+            output,
+            wires_per_op,
+            num_consts,
+            num_ops: config.num_routed_wires / wires_per_op,
+        };
+        assert!(
+            gate.degree() <= config.max_quotient_degree_factor,
+            "Constraints have degree {}, but the config allows at most {}",
+            gate.degree(),
+            config.max_quotient_degree_factor
+        );
+        if let Some(output) = output {
+            assert!(
+                gate.solving_constraint().is_some(),
+                "No constraint is linear in output wire {}",
+                output
+            );
+        }
+        gate
+    }
+
+    /// The same gate, but with a single operation per row.
+    pub fn with_single_op(&self) -> Self {
+        Self {
+            num_ops: 1,
+            ..self.clone()
+        }
+    }
+
+    pub const fn wire_ith_op(&self, i: usize, wire: usize) -> usize {
+        i * self.wires_per_op + wire
+    }
+
+    /// The constraint used to compute the output, i.e. the first one which is linear in it.
+    fn solving_constraint(&self) -> Option<&GateExpression<F>> {
+        let output = self.output?;
+        if output >= self.wires_per_op {
+            return None;
+        }
+        self.constraints
+            .iter()
+            .find(|constraint| degree_in_wire(constraint, output) == 1)
+    }
+
+    /// Whether an operation whose wires are all zero satisfies the constraints, given the gate's
+    /// constants. Unused operations of a gate have zero wires, so they must satisfy this.
+    pub fn is_satisfied_by_zero_wires(&self, constants: &[F]) -> bool {
+        self.constraints
+            .iter()
+            .all(|constraint| eval_base(constraint, &|_| F::ZERO, constants) == F::ZERO)
+    }
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> Gate<F, D> for ExpressionGate<F, D> {
+    fn id(&self) -> String {
+        format!("{self:?}")
+    }
+
+    fn serialization_id() -> String {
+        "ExpressionGate".into()
+    }
+
+    fn serialize(&self, dst: &mut Vec<u8>, _common_data: &CommonCircuitData<F, D>) -> IoResult<()> {
+        dst.write_usize(self.constraints.len())?;
+        for constraint in &self.constraints {
+            write_expression(dst, constraint)?;
+        }
+        dst.write_bool(self.output.is_some())?;
+        dst.write_usize(self.output.unwrap_or_default())?;
+        dst.write_usize(self.wires_per_op)?;
+        dst.write_usize(self.num_consts)?;
+        dst.write_usize(self.num_ops)
+    }
+
+    fn deserialize(src: &mut Buffer, _common_data: &CommonCircuitData<F, D>) -> IoResult<Self> {
+        let num_constraints = src.read_usize()?;
+        let constraints = (0..num_constraints)
+            .map(|_| read_expression(src))
+            .collect::<IoResult<_>>()?;
+        let has_output = src.read_bool()?;
+        let output = src.read_usize()?;
+        let wires_per_op = src.read_usize()?;
+        let num_consts = src.read_usize()?;
+        let num_ops = src.read_usize()?;
+        Ok(Self {
+            constraints,
+            output: has_output.then_some(output),
+            wires_per_op,
+            num_consts,
+            num_ops,
+        })
+    }
+
+    fn eval_unfiltered(&self, vars: EvaluationVars<F, D>) -> Vec<F::Extension> {
+        let mut constraints = Vec::with_capacity(self.num_constraints());
+        for i in 0..self.num_ops {
+            let var = |&var: &GateVar| match var {
+                GateVar::Wire(w) => vars.local_wires[self.wire_ith_op(i, w)],
+                GateVar::Constant(c) => vars.local_constants[c],
+            };
+            constraints.extend(
+                self.constraints
+                    .iter()
+                    .map(|constraint| constraint.eval(&var, &F::Extension::from_basefield)),
+            );
+        }
+
+        constraints
+    }
+
+    fn eval_unfiltered_base_one(
+        &self,
+        _vars: EvaluationVarsBase<F>,
+        _yield_constr: StridedConstraintConsumer<F>,
+    ) {
+        panic!("use eval_unfiltered_base_packed instead");
+    }
+
+    fn eval_unfiltered_base_batch(&self, vars_base: EvaluationVarsBaseBatch<F>) -> Vec<F> {
+        self.eval_unfiltered_base_batch_packed(vars_base)
+    }
+
+    fn eval_unfiltered_circuit(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        vars: EvaluationTargets<D>,
+    ) -> Vec<ExtensionTarget<D>> {
+        let mut constraints = Vec::with_capacity(self.num_constraints());
+        for i in 0..self.num_ops {
+            let var = |&var: &GateVar| match var {
+                GateVar::Wire(w) => vars.local_wires[self.wire_ith_op(i, w)],
+                GateVar::Constant(c) => vars.local_constants[c],
+            };
+            for constraint in &self.constraints {
+                constraints.push(constraint.eval_circuit(builder, &var));
+            }
+        }
+
+        constraints
+    }
+
+    fn generators(&self, row: usize, local_constants: &[F]) -> Vec<WitnessGeneratorRef<F, D>> {
+        let Some(constraint) = self.solving_constraint() else {
+            return Vec::new();
+        };
+        (0..self.num_ops)
+            .map(|i| {
+                WitnessGeneratorRef::new(
+                    ExpressionGenerator {
+                        row,
+                        i,
+                        wires_per_op: self.wires_per_op,
+                        output: self.output.unwrap(),
+                        constraint: constraint.clone(),
+                        constants: local_constants.to_vec(),
+                    }
+                    .adapter(),
+                )
+            })
+            .collect()
+    }
+
+    fn num_wires(&self) -> usize {
+        self.num_ops * self.wires_per_op
+    }
+
+    fn num_constants(&self) -> usize {
+        self.num_consts
+    }
+
+    fn degree(&self) -> usize {
+        self.constraints.iter().map(degree).max().unwrap_or(0)
+    }
+
+    fn num_constraints(&self) -> usize {
+        self.num_ops * self.constraints.len()
+    }
+
+    fn num_ops(&self) -> usize {
+        self.num_ops
+    }
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> PackedEvaluableBase<F, D>
+    for ExpressionGate<F, D>
+{
+    fn eval_unfiltered_base_packed<P: PackedField<Scalar = F>>(
+        &self,
+        vars: EvaluationVarsBasePacked<P>,
+        mut yield_constr: StridedConstraintConsumer<P>,
+    ) {
+        for i in 0..self.num_ops {
+            let var = |&var: &GateVar| match var {
+                GateVar::Wire(w) => vars.local_wires[self.wire_ith_op(i, w)],
+                GateVar::Constant(c) => vars.local_constants[c],
+            };
+            yield_constr.many(
+                self.constraints
+                    .iter()
+                    .map(|constraint| constraint.eval(&var, &P::from)),
+            );
+        }
+    }
+}
+
+/// Computes the output wire of an operation of an [`ExpressionGate`] from a constraint `c` which
+/// is linear in it, as `-c(0) / (c(1) - c(0))`, where `c(x)` is the constraint evaluated with the
+/// output set to `x`.
+#[derive(Clone, Debug, Default)]
+pub struct ExpressionGenerator<F: RichField + Extendable<D>, const D: usize> {
+    row: usize,
+    i: usize,
+    wires_per_op: usize,
+    output: usize,
+    constraint: GateExpression<F>,
+    constants: Vec<F>,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> ExpressionGenerator<F, D> {
+    fn target(&self, wire: usize) -> Target {
+        Target::wire(self.row, self.i * self.wires_per_op + wire)
+    }
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F, D>
+    for ExpressionGenerator<F, D>
+{
+    fn id(&self) -> String {
+        "ExpressionGenerator".to_string()
+    }
+
+    fn dependencies(&self) -> Vec<Target> {
+        wires(&self.constraint)
+            .into_iter()
+            .filter(|&w| w != self.output)
+            .map(|w| self.target(w))
+            .collect()
+    }
+
+    fn run_once(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
+        let eval_with_output = |output_value: F| {
+            let wire = |w| {
+                if w == self.output {
+                    output_value
+                } else {
+                    witness.get_target(self.target(w))
+                }
+            };
+            eval_base(&self.constraint, &wire, &self.constants)
+        };
+
+        let at_zero = eval_with_output(F::ZERO);
+        let slope = eval_with_output(F::ONE) - at_zero;
+        let output = slope
+            .try_inverse()
+            .map(|inverse| -at_zero * inverse)
+            .expect("The output of an ExpressionGate operation is not determined by its inputs");
+
+        out_buffer.set_target(self.target(self.output), output)
+    }
+
+    fn serialize(&self, dst: &mut Vec<u8>, _common_data: &CommonCircuitData<F, D>) -> IoResult<()> {
+        dst.write_usize(self.row)?;
+        dst.write_usize(self.i)?;
+        dst.write_usize(self.wires_per_op)?;
+        dst.write_usize(self.output)?;
+        write_expression(dst, &self.constraint)?;
+        dst.write_usize(self.constants.len())?;
+        dst.write_field_vec(&self.constants)
+    }
+
+    fn deserialize(src: &mut Buffer, _common_data: &CommonCircuitData<F, D>) -> IoResult<Self> {
+        let row = src.read_usize()?;
+        let i = src.read_usize()?;
+        let wires_per_op = src.read_usize()?;
+        let output = src.read_usize()?;
+        let constraint = read_expression(src)?;
+        let num_constants = src.read_usize()?;
+        let constants = src.read_field_vec(num_constants)?;
+        Ok(Self {
+            row,
+            i,
+            wires_per_op,
+            output,
+            constraint,
+            constants,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(not(feature = "std"))]
+    use alloc::vec;
+
+    use anyhow::Result;
+
+    use super::*;
+    use crate::field::types::Sample;
+    use crate::gates::gate_testing::{test_eval_fns, test_low_degree};
+    use crate::iop::witness::PartialWitness;
+    use crate::plonk::circuit_data::CircuitData;
+    use crate::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+    use crate::util::serialization::{GateRegistry, GeneratorRegistry};
+
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+
+    /// The gate enforcing `out = c0 * x * y + x^2 - c1`, with wires `x`, `y`, `out`, and that `b`
+    /// is a bit, with wire `b`.
+    fn gate() -> ExpressionGate<F, D> {
+        let [x, y, out, b] = [0, 1, 2, 3].map(|w| Expression::var(GateVar::Wire(w)));
+        let [c0, c1] = [0, 1].map(|c| Expression::var(GateVar::Constant(c)));
+        ExpressionGate::new_from_config(
+            &CircuitConfig::standard_recursion_config(),
+            vec![
+                c0 * x.clone() * y + x.clone() * x - c1 - out,
+                b.clone() * (b - F::ONE),
+            ],
+            Some(2),
+        )
+    }
+
+    fn expected(x: F, y: F, constants: [F; 2]) -> F {
+        constants[0] * x * y + x * x - constants[1]
+    }
+
+    #[test]
+    fn low_degree() {
+        test_low_degree::<F, _, D>(gate());
+    }
+
+    #[test]
+    fn eval_fns() -> Result<()> {
+        test_eval_fns::<F, C, _, D>(gate())
+    }
+
+    #[test]
+    #[should_panic(expected = "degree")]
+    fn too_high_degree() {
+        let x = GateExpression::<F>::var(GateVar::Wire(0));
+        let mut config = CircuitConfig::standard_recursion_config();
+        config.max_quotient_degree_factor = 3;
+        ExpressionGate::<F, D>::new_from_config(
+            &config,
+            vec![x.clone() * x.clone() * x.clone() * x],
+            None,
+        );
+    }
+
+    #[test]
+    fn test_expression() -> Result<()> {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let gate = gate();
+
+        // With `c1 = 0`, zero wires satisfy the constraints, so these operations share a row.
+        let packed_constants = [F::rand(), F::ZERO];
+        let offset_constants = [F::rand(), F::rand()];
+        for constants in [packed_constants, packed_constants, offset_constants] {
+            let (x, y) = (F::rand(), F::rand());
+            let inputs = [
+                builder.constant(x),
+                builder.constant(y),
+                builder.constant(F::from_bool(true)),
+            ];
+            let out = builder.expression(&gate, &inputs, &constants);
+            let expected_out = builder.constant(expected(x, y, constants));
+            builder.connect(out, expected_out);
+        }
+        let expression_rows = builder
+            .gate_instances
+            .iter()
+            .filter(|instance| instance.gate_ref.0.as_any().is::<ExpressionGate<F, D>>())
+            .count();
+        assert_eq!(expression_rows, 2);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(PartialWitness::new())?;
+        data.verify(proof)
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_wrong_witness() {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let inputs = builder.add_virtual_targets(3);
+        builder.expression(&gate(), &inputs, &[F::ONE, F::ZERO]);
+
+        // The output is computed correctly, but `b` is not a bit.
+        let mut pw = PartialWitness::new();
+        pw.set_target(inputs[0], F::ONE);
+        pw.set_target(inputs[1], F::ONE);
+        pw.set_target(inputs[2], F::TWO);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw).unwrap();
+        data.verify(proof).unwrap()
+    }
+
+    #[test]
+    fn test_serialization() -> Result<()> {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let inputs = builder.add_virtual_targets(3);
+        let out = builder.expression(&gate(), &inputs, &[F::TWO, F::ONE]);
+        builder.register_public_input(out);
+        let data = builder.build::<C>();
+
+        let gate_registry = GateRegistry::default();
+        let generator_registry = GeneratorRegistry::<C, D>::default();
+        let bytes = data.to_bytes(&gate_registry, &generator_registry).unwrap();
+        let data_from_bytes =
+            CircuitData::<F, C, D>::from_bytes(&bytes, &gate_registry, &generator_registry)
+                .unwrap();
+        assert_eq!(data, data_from_bytes);
+
+        let mut pw = PartialWitness::new();
+        pw.set_target(inputs[0], F::TWO);
+        pw.set_target(inputs[1], F::ONE);
+        pw.set_target(inputs[2], F::ONE);
+        let proof = data_from_bytes.prove(pw)?;
+        assert_eq!(proof.public_inputs, vec![F::from_canonical_u64(7)]);
+        data_from_bytes.verify(proof)
+    }
+}
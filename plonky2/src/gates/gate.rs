@@ -24,6 +24,7 @@ use crate::plonk::vars::{
     EvaluationTargets, EvaluationVars, EvaluationVarsBase, EvaluationVarsBaseBatch,
 };
 use crate::util::serialization::{Buffer, IoResult};
+use crate::util::short_type_name;
 
 /// A custom gate.
 ///
@@ -64,6 +65,17 @@ pub trait Gate<F: RichField + Extendable<D>, const D: usize>: 'static + Send + S
     where
         Self: Sized;
 
+    /// The id under which [`GateRegistry::with`](crate::util::serialization::GateRegistry::with) registers this gate type. It must differ from the
+    /// ids of the other gates of a registry and stay the same across compilers, since it is written
+    /// in serialized circuits. The default, derived from the type name, is only a fallback which
+    /// gates should override.
+    fn serialization_id() -> String
+    where
+        Self: Sized,
+    {
+        short_type_name::<Self>()
+    }
+
     /// Defines and evaluates the constraints that enforce the statement represented by this gate.
     /// Constraints must be defined in the extension of this custom gate base field.
     fn eval_unfiltered(&self, vars: EvaluationVars<F, D>) -> Vec<F::Extension>;
@@ -328,7 +340,7 @@ fn compute_filter<K: Field>(row: usize, group_range: Range<usize>, s: K, many_se
     group_range
         .filter(|&i| i != row)
         .chain(many_selector.then_some(UNUSED_SELECTOR))
-        .map(|i| K::from_canonical_usize(i) - s)
+        .map(|i| K::from_noncanonical_u64(i as u64) - s)
         .product()
 }
 
@@ -344,7 +356,7 @@ fn compute_filter_circuit<F: RichField + Extendable<D>, const D: usize>(
         .filter(|&i| i != row)
         .chain(many_selectors.then_some(UNUSED_SELECTOR))
         .map(|i| {
-            let c = builder.constant_extension(F::Extension::from_canonical_usize(i));
+            let c = builder.constant_extension(F::Extension::from_noncanonical_u64(i as u64));
             builder.sub_extension(c, s)
         })
         .collect::<Vec<_>>();
@@ -3,9 +3,6 @@ use alloc::vec::Vec;
 use alloc::{format, vec};
 use core::usize;
 
-use itertools::Itertools;
-use keccak_hash::keccak;
-
 use super::lookup_table::LookupTable;
 use crate::field::extension::Extendable;
 use crate::field::packed::PackedField;
@@ -25,13 +22,17 @@ use crate::plonk::vars::{
 };
 use crate::util::serialization::{Buffer, IoResult, Read, Write};
 
-pub type Lookup = Vec<(Target, Target)>;
+/// The lookups made in a LUT, each given as its input targets followed by its output targets.
+pub type Lookup = Vec<Vec<Target>>;
 
-/// A gate which stores (input, output) lookup pairs made elsewhere in the trace. It doesn't check any constraints itself.
+/// A gate which stores lookups made elsewhere in the trace, i.e. rows of inputs followed by
+/// outputs, padded with zeros to `width` values. It doesn't check any constraints itself.
 #[derive(Debug, Clone)]
 pub struct LookupGate {
     /// Number of lookups per gate.
     pub num_slots: usize,
+    /// Number of values in each lookup, i.e. the width of the widest table in the circuit.
+    pub width: usize,
     /// LUT associated to the gate.
     lut: LookupTable,
     /// The Keccak hash of the lookup table.
@@ -39,29 +40,22 @@ pub struct LookupGate {
 }
 
 impl LookupGate {
-    pub fn new_from_table(config: &CircuitConfig, lut: LookupTable) -> Self {
-        let table_bytes = lut
-            .iter()
-            .flat_map(|(input, output)| [input.to_le_bytes(), output.to_le_bytes()].concat())
-            .collect_vec();
-
+    pub fn new_from_table(config: &CircuitConfig, width: usize, lut: LookupTable) -> Self {
         Self {
-            num_slots: Self::num_slots(config),
+            num_slots: Self::num_slots(config, width),
+            width,
+            lut_hash: lut.keccak_hash(),
             lut,
-            lut_hash: keccak(table_bytes).0,
         }
     }
-    pub(crate) const fn num_slots(config: &CircuitConfig) -> usize {
-        let wires_per_lookup = 2;
-        config.num_routed_wires / wires_per_lookup
-    }
 
-    pub const fn wire_ith_looking_inp(i: usize) -> usize {
-        2 * i
+    pub(crate) const fn num_slots(config: &CircuitConfig, width: usize) -> usize {
+        config.num_routed_wires / width
     }
 
-    pub const fn wire_ith_looking_out(i: usize) -> usize {
-        2 * i + 1
+    /// Wire for the `col`-th looking value.
+    pub const fn wire_ith_looking_value(width: usize, i: usize, col: usize) -> usize {
+        width * i + col
     }
 }
 
@@ -69,13 +63,18 @@ impl<F: RichField + Extendable<D>, const D: usize> Gate<F, D> for LookupGate {
     fn id(&self) -> String {
         // Custom implementation to not have the entire lookup table
         format!(
-            "LookupGate {{num_slots: {}, lut_hash: {:?}}}",
-            self.num_slots, self.lut_hash
+            "LookupGate {{num_slots: {}, width: {}, lut_hash: {:?}}}",
+            self.num_slots, self.width, self.lut_hash
         )
     }
 
+    fn serialization_id() -> String {
+        "LookupGate".into()
+    }
+
     fn serialize(&self, dst: &mut Vec<u8>, common_data: &CommonCircuitData<F, D>) -> IoResult<()> {
         dst.write_usize(self.num_slots)?;
+        dst.write_usize(self.width)?;
         for (i, lut) in common_data.luts.iter().enumerate() {
             if lut == &self.lut {
                 dst.write_usize(i)?;
@@ -88,13 +87,24 @@ impl<F: RichField + Extendable<D>, const D: usize> Gate<F, D> for LookupGate {
 
     fn deserialize(src: &mut Buffer, common_data: &CommonCircuitData<F, D>) -> IoResult<Self> {
         let num_slots = src.read_usize()?;
+        let width = if src.legacy_lookups() {
+            2
+        } else {
+            src.read_usize()?
+        };
         let lut_index = src.read_usize()?;
         let mut lut_hash = [0u8; 32];
         src.read_exact(&mut lut_hash)?;
+        let lut = common_data.luts[lut_index].clone();
+        if src.legacy_lookups() {
+            // The hash of legacy tables didn't cover their number of inputs and outputs.
+            lut_hash = lut.keccak_hash();
+        }
 
         Ok(Self {
             num_slots,
-            lut: common_data.luts[lut_index].clone(),
+            width,
+            lut,
             lut_hash,
         })
     }
@@ -133,6 +143,7 @@ impl<F: RichField + Extendable<D>, const D: usize> Gate<F, D> for LookupGate {
                         row,
                         lut: self.lut.clone(),
                         slot_nb: i,
+                        width: self.width,
                     }
                     .adapter(),
                 )
@@ -141,7 +152,7 @@ impl<F: RichField + Extendable<D>, const D: usize> Gate<F, D> for LookupGate {
     }
 
     fn num_wires(&self) -> usize {
-        self.num_slots * 2
+        self.num_slots * self.width
     }
 
     fn num_constants(&self) -> usize {
@@ -171,6 +182,16 @@ pub struct LookupGenerator {
     row: usize,
     lut: LookupTable,
     slot_nb: usize,
+    width: usize,
+}
+
+impl LookupGenerator {
+    fn wire(&self, col: usize) -> Target {
+        Target::wire(
+            self.row,
+            LookupGate::wire_ith_looking_value(self.width, self.slot_nb, col),
+        )
+    }
 }
 
 impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F, D> for LookupGenerator {
@@ -179,40 +200,30 @@ impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F, D> for Loo
     }
 
     fn dependencies(&self) -> Vec<Target> {
-        vec![Target::wire(
-            self.row,
-            LookupGate::wire_ith_looking_inp(self.slot_nb),
-        )]
+        (0..self.lut.num_inputs())
+            .map(|col| self.wire(col))
+            .collect()
     }
 
     fn run_once(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
-        let get_wire = |wire: usize| -> F { witness.get_target(Target::wire(self.row, wire)) };
-
-        let input_val = get_wire(LookupGate::wire_ith_looking_inp(self.slot_nb));
-        let (input, output) = self.lut[input_val.to_canonical_u64() as usize];
-        if input_val == F::from_canonical_u16(input) {
-            let output_val = F::from_canonical_u16(output);
-
-            let out_wire = Target::wire(self.row, LookupGate::wire_ith_looking_out(self.slot_nb));
-            out_buffer.set_target(out_wire, output_val);
-        } else {
-            for (input, output) in self.lut.iter() {
-                if input_val == F::from_canonical_u16(*input) {
-                    let output_val = F::from_canonical_u16(*output);
-
-                    let out_wire =
-                        Target::wire(self.row, LookupGate::wire_ith_looking_out(self.slot_nb));
-                    out_buffer.set_target(out_wire, output_val);
-                    return;
-                }
-            }
-            panic!("Incorrect input value provided");
-        };
+        let inputs = (0..self.lut.num_inputs())
+            .map(|col| witness.get_target(self.wire(col)).to_canonical_u64())
+            .collect::<Vec<_>>();
+        let row = self
+            .lut
+            .find_row(&inputs)
+            .expect("Incorrect input value provided");
+
+        let num_inputs = self.lut.num_inputs();
+        for (col, &output) in self.lut.row(row).iter().enumerate().skip(num_inputs) {
+            out_buffer.set_target(self.wire(col), F::from_canonical_u64(output));
+        }
     }
 
     fn serialize(&self, dst: &mut Vec<u8>, common_data: &CommonCircuitData<F, D>) -> IoResult<()> {
         dst.write_usize(self.row)?;
         dst.write_usize(self.slot_nb)?;
+        dst.write_usize(self.width)?;
         for (i, lut) in common_data.luts.iter().enumerate() {
             if lut == &self.lut {
                 return dst.write_usize(i);
@@ -225,12 +236,18 @@ impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F, D> for Loo
     fn deserialize(src: &mut Buffer, common_data: &CommonCircuitData<F, D>) -> IoResult<Self> {
         let row = src.read_usize()?;
         let slot_nb = src.read_usize()?;
+        let width = if src.legacy_lookups() {
+            2
+        } else {
+            src.read_usize()?
+        };
         let lut_index = src.read_usize()?;
 
         Ok(Self {
             row,
             lut: common_data.luts[lut_index].clone(),
             slot_nb,
+            width,
         })
     }
 }
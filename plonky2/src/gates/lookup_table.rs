@@ -2,14 +2,18 @@ use alloc::string::{String, ToString};
 use alloc::sync::Arc;
 use alloc::vec::Vec;
 use alloc::{format, vec};
+use core::hash::{Hash, Hasher};
 use core::usize;
 
+use hashbrown::HashMap;
 use itertools::Itertools;
 use keccak_hash::keccak;
 use plonky2_util::ceil_div_usize;
+use serde::{Deserialize, Serialize};
 
 use crate::field::extension::Extendable;
 use crate::field::packed::PackedField;
+use crate::field::types::PrimeField64;
 use crate::gates::gate::Gate;
 use crate::gates::packed_util::PackedEvaluableBase;
 use crate::gates::util::StridedConstraintConsumer;
@@ -26,13 +30,178 @@ use crate::plonk::vars::{
 };
 use crate::util::serialization::{Buffer, IoResult, Read, Write};
 
-pub type LookupTable = Arc<Vec<(u16, u16)>>;
+/// The rows of a lookup table. Each row holds `num_inputs` input values followed by `num_outputs`
+/// output values, which are field elements stored in canonical form. A table without outputs
+/// only constrains its inputs to appear in it, which is useful for range checks.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(from = "LookupTableRows")]
+pub struct LookupTableData {
+    num_inputs: usize,
+    num_outputs: usize,
+    /// The values of all rows, concatenated.
+    values: Vec<u64>,
+    /// Maps the inputs of each row to the index of the first row holding them.
+    #[serde(skip_serializing)]
+    row_index: HashMap<Vec<u64>, usize>,
+}
+
+/// The serialized form of a `LookupTableData`, from which the row index is rebuilt.
+#[derive(Deserialize)]
+struct LookupTableRows {
+    num_inputs: usize,
+    num_outputs: usize,
+    values: Vec<u64>,
+}
+
+impl From<LookupTableRows> for LookupTableData {
+    fn from(rows: LookupTableRows) -> Self {
+        Self::from_canonical_values(rows.num_inputs, rows.num_outputs, rows.values)
+    }
+}
+
+impl PartialEq for LookupTableData {
+    fn eq(&self, other: &Self) -> bool {
+        self.num_inputs == other.num_inputs
+            && self.num_outputs == other.num_outputs
+            && self.values == other.values
+    }
+}
+
+impl Eq for LookupTableData {}
+
+impl Hash for LookupTableData {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.num_inputs.hash(state);
+        self.num_outputs.hash(state);
+        self.values.hash(state);
+    }
+}
+
+pub type LookupTable = Arc<LookupTableData>;
+
+impl LookupTableData {
+    /// Creates a table from rows of `num_inputs` inputs followed by `num_outputs` outputs.
+    pub fn from_rows<F: PrimeField64>(
+        num_inputs: usize,
+        num_outputs: usize,
+        rows: &[Vec<F>],
+    ) -> Self {
+        let values = rows
+            .iter()
+            .flat_map(|row| {
+                assert_eq!(
+                    row.len(),
+                    num_inputs + num_outputs,
+                    "Lookup table rows must have {} inputs and {} outputs",
+                    num_inputs,
+                    num_outputs
+                );
+                row.iter().map(|x| x.to_canonical_u64())
+            })
+            .collect();
+        Self::from_canonical_values(num_inputs, num_outputs, values)
+    }
+
+    /// Creates a table with a single input column and no outputs, whose rows are `values`.
+    pub fn from_values<F: PrimeField64>(values: &[F]) -> Self {
+        Self::from_canonical_values(1, 0, values.iter().map(|x| x.to_canonical_u64()).collect())
+    }
 
-/// A gate which stores the set of (input, output) value pairs of a lookup table, and their multiplicities.
+    /// Creates a table from the concatenation of its rows, where all values are canonical.
+    pub fn from_canonical_values(num_inputs: usize, num_outputs: usize, values: Vec<u64>) -> Self {
+        assert!(num_inputs > 0, "Lookup tables must have at least one input");
+        assert_eq!(
+            values.len() % (num_inputs + num_outputs),
+            0,
+            "Lookup table values do not form complete rows"
+        );
+        let mut row_index = HashMap::new();
+        for (i, row) in values.chunks_exact(num_inputs + num_outputs).enumerate() {
+            row_index.entry(row[..num_inputs].to_vec()).or_insert(i);
+        }
+        Self {
+            num_inputs,
+            num_outputs,
+            values,
+            row_index,
+        }
+    }
+
+    pub fn num_inputs(&self) -> usize {
+        self.num_inputs
+    }
+
+    pub fn num_outputs(&self) -> usize {
+        self.num_outputs
+    }
+
+    /// The number of values in each row.
+    pub fn width(&self) -> usize {
+        self.num_inputs + self.num_outputs
+    }
+
+    /// The number of rows.
+    pub fn len(&self) -> usize {
+        if self.values.is_empty() {
+            0
+        } else {
+            self.values.len() / self.width()
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn values(&self) -> &[u64] {
+        &self.values
+    }
+
+    pub fn row(&self, i: usize) -> &[u64] {
+        let width = self.width();
+        &self.values[i * width..(i + 1) * width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[u64]> {
+        self.values.chunks_exact(self.width().max(1))
+    }
+
+    /// Returns the index of the first row whose inputs are `inputs`, if any.
+    pub fn find_row(&self, inputs: &[u64]) -> Option<usize> {
+        self.row_index.get(inputs).copied()
+    }
+
+    /// The Keccak hash of the table, used to identify the gates associated to it.
+    pub(crate) fn keccak_hash(&self) -> [u8; 32] {
+        let table_bytes = [self.num_inputs as u64, self.num_outputs as u64]
+            .iter()
+            .chain(&self.values)
+            .flat_map(|x| x.to_le_bytes())
+            .collect_vec();
+        keccak(table_bytes).0
+    }
+}
+
+impl FromIterator<(u16, u16)> for LookupTableData {
+    /// Collects `(input, output)` pairs into a table with one input and one output.
+    fn from_iter<T: IntoIterator<Item = (u16, u16)>>(iter: T) -> Self {
+        let values = iter
+            .into_iter()
+            .flat_map(|(input, output)| [input as u64, output as u64])
+            .collect();
+        Self::from_canonical_values(1, 1, values)
+    }
+}
+
+/// A gate which stores the rows of a lookup table, and their multiplicities. Each slot holds
+/// `width` values, the first ones being a row of the table padded with zeros, followed by the
+/// multiplicity of the row.
 #[derive(Debug, Clone)]
 pub struct LookupTableGate {
     /// Number of lookup entries per gate.
     pub num_slots: usize,
+    /// Number of values in each entry, i.e. the width of the widest table in the circuit.
+    pub width: usize,
     /// Lookup table associated to the gate.
     pub lut: LookupTable,
     /// The Keccak hash of the lookup table.
@@ -42,38 +211,34 @@ pub struct LookupTableGate {
 }
 
 impl LookupTableGate {
-    pub fn new_from_table(config: &CircuitConfig, lut: LookupTable, last_lut_row: usize) -> Self {
-        let table_bytes = lut
-            .iter()
-            .flat_map(|(input, output)| [input.to_le_bytes(), output.to_le_bytes()].concat())
-            .collect_vec();
-
+    pub fn new_from_table(
+        config: &CircuitConfig,
+        width: usize,
+        lut: LookupTable,
+        last_lut_row: usize,
+    ) -> Self {
         Self {
-            num_slots: Self::num_slots(config),
+            num_slots: Self::num_slots(config, width),
+            width,
+            lut_hash: lut.keccak_hash(),
             lut,
-            lut_hash: keccak(table_bytes).0,
             last_lut_row,
         }
     }
 
-    pub(crate) const fn num_slots(config: &CircuitConfig) -> usize {
-        let wires_per_entry = 3;
+    pub(crate) const fn num_slots(config: &CircuitConfig, width: usize) -> usize {
+        let wires_per_entry = width + 1;
         config.num_routed_wires / wires_per_entry
     }
 
-    /// Wire for the looked input.
-    pub const fn wire_ith_looked_inp(i: usize) -> usize {
-        3 * i
-    }
-
-    // Wire for the looked output.
-    pub const fn wire_ith_looked_out(i: usize) -> usize {
-        3 * i + 1
+    /// Wire for the `col`-th looked value.
+    pub const fn wire_ith_looked_value(width: usize, i: usize, col: usize) -> usize {
+        (width + 1) * i + col
     }
 
     /// Wire for the multiplicity. Set after the trace has been generated.
-    pub const fn wire_ith_multiplicity(i: usize) -> usize {
-        3 * i + 2
+    pub const fn wire_ith_multiplicity(width: usize, i: usize) -> usize {
+        (width + 1) * i + width
     }
 }
 
@@ -81,13 +246,18 @@ impl<F: RichField + Extendable<D>, const D: usize> Gate<F, D> for LookupTableGat
     fn id(&self) -> String {
         // Custom implementation to not have the entire lookup table
         format!(
-            "LookupTableGate {{num_slots: {}, lut_hash: {:?}, last_lut_row: {}}}",
-            self.num_slots, self.lut_hash, self.last_lut_row
+            "LookupTableGate {{num_slots: {}, width: {}, lut_hash: {:?}, last_lut_row: {}}}",
+            self.num_slots, self.width, self.lut_hash, self.last_lut_row
         )
     }
 
+    fn serialization_id() -> String {
+        "LookupTableGate".into()
+    }
+
     fn serialize(&self, dst: &mut Vec<u8>, common_data: &CommonCircuitData<F, D>) -> IoResult<()> {
         dst.write_usize(self.num_slots)?;
+        dst.write_usize(self.width)?;
         dst.write_usize(self.last_lut_row)?;
         for (i, lut) in common_data.luts.iter().enumerate() {
             if lut == &self.lut {
@@ -101,14 +271,25 @@ impl<F: RichField + Extendable<D>, const D: usize> Gate<F, D> for LookupTableGat
 
     fn deserialize(src: &mut Buffer, common_data: &CommonCircuitData<F, D>) -> IoResult<Self> {
         let num_slots = src.read_usize()?;
+        let width = if src.legacy_lookups() {
+            2
+        } else {
+            src.read_usize()?
+        };
         let last_lut_row = src.read_usize()?;
         let lut_index = src.read_usize()?;
         let mut lut_hash = [0u8; 32];
         src.read_exact(&mut lut_hash)?;
+        let lut = common_data.luts[lut_index].clone();
+        if src.legacy_lookups() {
+            // The hash of legacy tables didn't cover their number of inputs and outputs.
+            lut_hash = lut.keccak_hash();
+        }
 
         Ok(Self {
             num_slots,
-            lut: common_data.luts[lut_index].clone(),
+            width,
+            lut,
             lut_hash,
             last_lut_row,
         })
@@ -149,6 +330,7 @@ impl<F: RichField + Extendable<D>, const D: usize> Gate<F, D> for LookupTableGat
                         lut: self.lut.clone(),
                         slot_nb: i,
                         num_slots: self.num_slots,
+                        width: self.width,
                         last_lut_row: self.last_lut_row,
                     }
                     .adapter(),
@@ -158,7 +340,7 @@ impl<F: RichField + Extendable<D>, const D: usize> Gate<F, D> for LookupTableGat
     }
 
     fn num_wires(&self) -> usize {
-        self.num_slots * 3
+        self.num_slots * (self.width + 1)
     }
 
     fn num_constants(&self) -> usize {
@@ -189,6 +371,7 @@ pub struct LookupTableGenerator {
     lut: LookupTable,
     slot_nb: usize,
     num_slots: usize,
+    width: usize,
     last_lut_row: usize,
 }
 
@@ -205,19 +388,16 @@ impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F, D> for Loo
         let first_row = self.last_lut_row + ceil_div_usize(self.lut.len(), self.num_slots) - 1;
         let slot = (first_row - self.row) * self.num_slots + self.slot_nb;
 
-        let slot_input_target =
-            Target::wire(self.row, LookupTableGate::wire_ith_looked_inp(self.slot_nb));
-        let slot_output_target =
-            Target::wire(self.row, LookupTableGate::wire_ith_looked_out(self.slot_nb));
-
-        if slot < self.lut.len() {
-            let (input, output) = self.lut[slot];
-            out_buffer.set_target(slot_input_target, F::from_canonical_usize(input as usize));
-            out_buffer.set_target(slot_output_target, F::from_canonical_usize(output as usize));
-        } else {
-            // Pad with zeros.
-            out_buffer.set_target(slot_input_target, F::ZERO);
-            out_buffer.set_target(slot_output_target, F::ZERO);
+        // Rows past the end of the table, and columns past the width of the table, are padded
+        // with zeros.
+        let row = (slot < self.lut.len()).then(|| self.lut.row(slot));
+        for col in 0..self.width {
+            let value = row.and_then(|row| row.get(col)).copied().unwrap_or(0);
+            let target = Target::wire(
+                self.row,
+                LookupTableGate::wire_ith_looked_value(self.width, self.slot_nb, col),
+            );
+            out_buffer.set_target(target, F::from_canonical_u64(value));
         }
     }
 
@@ -225,6 +405,7 @@ impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F, D> for Loo
         dst.write_usize(self.row)?;
         dst.write_usize(self.slot_nb)?;
         dst.write_usize(self.num_slots)?;
+        dst.write_usize(self.width)?;
         dst.write_usize(self.last_lut_row)?;
         for (i, lut) in common_data.luts.iter().enumerate() {
             if lut == &self.lut {
@@ -239,6 +420,11 @@ impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F, D> for Loo
         let row = src.read_usize()?;
         let slot_nb = src.read_usize()?;
         let num_slots = src.read_usize()?;
+        let width = if src.legacy_lookups() {
+            2
+        } else {
+            src.read_usize()?
+        };
         let last_lut_row = src.read_usize()?;
         let lut_index = src.read_usize()?;
 
@@ -247,6 +433,7 @@ impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F, D> for Loo
             lut: common_data.luts[lut_index].clone(),
             slot_nb,
             num_slots,
+            width,
             last_lut_row,
         })
     }
@@ -19,6 +19,10 @@ impl<F: RichField + Extendable<D>, const D: usize> Gate<F, D> for NoopGate {
         "NoopGate".into()
     }
 
+    fn serialization_id() -> String {
+        "NoopGate".into()
+    }
+
     fn serialize(
         &self,
         _dst: &mut Vec<u8>,
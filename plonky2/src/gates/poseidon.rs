@@ -100,6 +100,10 @@ impl<F: RichField + Extendable<D>, const D: usize> Gate<F, D> for PoseidonGate<F
         format!("{self:?}<WIDTH={SPONGE_WIDTH}>")
     }
 
+    fn serialization_id() -> String {
+        "PoseidonGate".into()
+    }
+
     fn serialize(
         &self,
         _dst: &mut Vec<u8>,
@@ -541,13 +545,15 @@ mod tests {
     use crate::field::types::Field;
     use crate::gates::gate_testing::{test_eval_fns, test_low_degree};
     use crate::gates::poseidon::PoseidonGate;
-    use crate::hash::poseidon::{Poseidon, SPONGE_WIDTH};
+    use crate::hash::poseidon::{Poseidon, PoseidonHash, SPONGE_WIDTH};
     use crate::iop::generator::generate_partial_witness;
     use crate::iop::wire::Wire;
     use crate::iop::witness::{PartialWitness, Witness, WitnessWrite};
     use crate::plonk::circuit_builder::CircuitBuilder;
     use crate::plonk::circuit_data::CircuitConfig;
-    use crate::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+    use crate::plonk::config::{
+        GenericConfig, Hasher, KeccakBabyBearConfig, PoseidonGoldilocksConfig,
+    };
 
     #[test]
     fn wire_indices() {
@@ -637,4 +643,38 @@ mod tests {
         let gate = PoseidonGate::<F, 2>::new();
         test_eval_fns::<F, C, _, D>(gate)
     }
+
+    #[test]
+    fn eval_fns_babybear() -> Result<()> {
+        const D: usize = 4;
+        type C = KeccakBabyBearConfig;
+        type F = <C as GenericConfig<D>>::F;
+        let gate = PoseidonGate::<F, D>::new();
+        test_eval_fns::<F, C, _, D>(gate)
+    }
+
+    #[test]
+    fn prove_babybear() -> Result<()> {
+        const D: usize = 4;
+        type C = KeccakBabyBearConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        // Besides the Poseidon gates, this circuit has public input and constant gates, so some
+        // gates have a filter involving the unused selector value.
+        let mut builder = CircuitBuilder::<F, D>::new(CircuitConfig::standard_recursion_config());
+        let inputs = builder.add_virtual_targets(10);
+        let hash = builder.hash_n_to_hash_no_pad::<PoseidonHash>(inputs.clone());
+        builder.register_public_inputs(&hash.elements);
+        let data = builder.build::<C>();
+
+        let values = (0..10).map(F::from_canonical_usize).collect::<Vec<_>>();
+        let mut pw = PartialWitness::new();
+        pw.set_target_arr(&inputs, &values);
+        let proof = data.prove(pw)?;
+        assert_eq!(
+            proof.public_inputs,
+            PoseidonHash::hash_no_pad(&values).elements
+        );
+        data.verify(proof)
+    }
 }
@@ -119,6 +119,10 @@ impl<F: RichField + Extendable<D> + Poseidon, const D: usize> Gate<F, D> for Pos
         format!("{self:?}<WIDTH={SPONGE_WIDTH}>")
     }
 
+    fn serialization_id() -> String {
+        "PoseidonMdsGate".into()
+    }
+
     fn serialize(
         &self,
         _dst: &mut Vec<u8>,
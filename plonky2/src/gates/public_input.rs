@@ -32,6 +32,10 @@ impl<F: RichField + Extendable<D>, const D: usize> Gate<F, D> for PublicInputGat
         "PublicInputGate".into()
     }
 
+    fn serialization_id() -> String {
+        "PublicInputGate".into()
+    }
+
     fn serialize(
         &self,
         _dst: &mut Vec<u8>,
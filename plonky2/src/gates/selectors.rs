@@ -2,7 +2,7 @@ use alloc::vec;
 use alloc::vec::Vec;
 use core::ops::Range;
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::field::extension::Extendable;
 use crate::field::polynomial::PolynomialValues;
@@ -10,10 +10,11 @@ use crate::gates::gate::{GateInstance, GateRef};
 use crate::hash::hash_types::RichField;
 use crate::plonk::circuit_builder::LookupWire;
 
-/// Placeholder value to indicate that a gate doesn't use a selector polynomial.
+/// Placeholder value to indicate that a gate doesn't use a selector polynomial. It exceeds the
+/// order of 31-bit fields, so it must be converted with `from_noncanonical_u64`.
 pub(crate) const UNUSED_SELECTOR: usize = u32::MAX as usize;
 
-#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub struct SelectorsInfo {
     pub(crate) selector_indices: Vec<usize>,
     pub(crate) groups: Vec<Range<usize>>,
@@ -163,7 +164,7 @@ pub(crate) fn selector_polynomials<F: RichField + Extendable<D>, const D: usize>
     let selector_indices = (0..num_gates).map(group).collect();
 
     // Placeholder value to indicate that a gate doesn't use a selector polynomial.
-    let unused = F::from_canonical_usize(UNUSED_SELECTOR);
+    let unused = F::from_noncanonical_u64(UNUSED_SELECTOR as u64);
 
     let mut polynomials = vec![PolynomialValues::zero(n); groups.len()];
     for (j, g) in instances.iter().enumerate() {
@@ -3,6 +3,7 @@ use alloc::vec::Vec;
 use anyhow::ensure;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
+use crate::field::babybear_field::BabyBearField;
 use crate::field::goldilocks_field::GoldilocksField;
 use crate::field::types::{Field, PrimeField64, Sample};
 use crate::hash::poseidon::Poseidon;
@@ -14,6 +15,8 @@ pub trait RichField: PrimeField64 + Poseidon {}
 
 impl RichField for GoldilocksField {}
 
+impl RichField for BabyBearField {}
+
 pub const NUM_HASH_OUT_ELTS: usize = 4;
 
 /// Represents a ~256 bit hash output.
@@ -111,6 +114,81 @@ impl<F: Field> Default for HashOut<F> {
     }
 }
 
+pub const NUM_WIDE_HASH_OUT_ELTS: usize = 8;
+
+/// Represents a hash output of 8 field elements, i.e. ~248 bits for a 31-bit field such as
+/// BabyBear, where a `HashOut` would only have ~124 bits.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct WideHashOut<F: Field> {
+    pub elements: [F; NUM_WIDE_HASH_OUT_ELTS],
+}
+
+impl<F: Field> WideHashOut<F> {
+    pub const ZERO: Self = Self {
+        elements: [F::ZERO; NUM_WIDE_HASH_OUT_ELTS],
+    };
+
+    pub fn from_vec(elements: Vec<F>) -> Self {
+        debug_assert!(elements.len() == NUM_WIDE_HASH_OUT_ELTS);
+        Self {
+            elements: elements.try_into().unwrap(),
+        }
+    }
+}
+
+impl<F: Field> From<[F; NUM_WIDE_HASH_OUT_ELTS]> for WideHashOut<F> {
+    fn from(elements: [F; NUM_WIDE_HASH_OUT_ELTS]) -> Self {
+        Self { elements }
+    }
+}
+
+impl<F> Sample for WideHashOut<F>
+where
+    F: Field,
+{
+    #[inline]
+    fn sample<R>(rng: &mut R) -> Self
+    where
+        R: rand::RngCore + ?Sized,
+    {
+        Self {
+            elements: core::array::from_fn(|_| F::sample(rng)),
+        }
+    }
+}
+
+impl<F: RichField> GenericHashOut<F> for WideHashOut<F> {
+    fn to_bytes(&self) -> Vec<u8> {
+        self.elements
+            .into_iter()
+            .flat_map(|x| x.to_canonical_u64().to_le_bytes())
+            .collect()
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Self {
+        WideHashOut {
+            elements: bytes
+                .chunks(8)
+                .take(NUM_WIDE_HASH_OUT_ELTS)
+                .map(|x| F::from_canonical_u64(u64::from_le_bytes(x.try_into().unwrap())))
+                .collect::<Vec<_>>()
+                .try_into()
+                .unwrap(),
+        }
+    }
+
+    fn to_vec(&self) -> Vec<F> {
+        self.elements.to_vec()
+    }
+}
+
+impl<F: Field> Default for WideHashOut<F> {
+    fn default() -> Self {
+        Self::ZERO
+    }
+}
+
 /// Represents a ~256 bit hash output.
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub struct HashOutTarget {
@@ -179,9 +257,11 @@ impl<F: RichField, const N: usize> GenericHashOut<F> for BytesHash<N> {
     }
 
     fn to_vec(&self) -> Vec<F> {
+        // Pack as many bytes as fit in a field element without wrapping around, e.g. 7 bytes for
+        // Goldilocks and 3 bytes for BabyBear, since more would allow collisions.
+        let bytes_per_elt = (F::BITS - 1) / 8;
         self.0
-            // Chunks of 7 bytes since 8 bytes would allow collisions.
-            .chunks(7)
+            .chunks(bytes_per_elt)
             .map(|bytes| {
                 let mut arr = [0; 8];
                 arr[..bytes.len()].copy_from_slice(bytes);
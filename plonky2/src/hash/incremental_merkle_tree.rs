@@ -0,0 +1,144 @@
+use alloc::vec::Vec;
+
+use anyhow::{ensure, Result};
+
+use crate::hash::hash_types::RichField;
+use crate::hash::merkle_proofs::MerkleProof;
+use crate::plonk::config::Hasher;
+
+/// An append-only Merkle tree with `2^depth` leaves, of which the first `len` have been pushed and
+/// the rest are empty. Its root is that of a `MerkleTree` with cap height zero whose remaining
+/// leaves are empty vectors.
+///
+/// Only the frontier is stored: for each layer, the digest of the rightmost node which is a left
+/// child. This is enough to update the root, and to prove the most recently pushed leaf, in
+/// `depth` hashes.
+#[derive(Clone, Debug)]
+pub struct IncrementalMerkleTree<F: RichField, H: Hasher<F>> {
+    depth: usize,
+    len: usize,
+
+    /// The digest of the rightmost left child in each layer, starting from the leaves.
+    frontier: Vec<H::Hash>,
+
+    /// The digest of an empty subtree of each height, from `0` to `depth - 1`.
+    empty_digests: Vec<H::Hash>,
+
+    root: H::Hash,
+}
+
+impl<F: RichField, H: Hasher<F>> IncrementalMerkleTree<F, H> {
+    pub fn new(depth: usize) -> Self {
+        assert!(depth < usize::BITS as usize, "Depth {} is too large", depth);
+
+        let mut empty_digests = Vec::with_capacity(depth);
+        let mut digest = H::hash_or_noop(&[]);
+        for _ in 0..depth {
+            empty_digests.push(digest);
+            digest = H::two_to_one(digest, digest);
+        }
+
+        Self {
+            depth,
+            len: 0,
+            frontier: empty_digests.clone(),
+            empty_digests,
+            root: digest,
+        }
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len == 1 << self.depth
+    }
+
+    pub fn root(&self) -> H::Hash {
+        self.root
+    }
+
+    /// Appends a leaf and returns its proof against the new root. The leaf's index is the length
+    /// of the tree before the call.
+    pub fn push(&mut self, leaf: Vec<F>) -> Result<MerkleProof<F, H>> {
+        ensure!(
+            !self.is_full(),
+            "Merkle tree of depth {} is full",
+            self.depth
+        );
+
+        let mut index = self.len;
+        let mut digest = H::hash_or_noop(&leaf);
+        let mut siblings = Vec::with_capacity(self.depth);
+        for height in 0..self.depth {
+            digest = if index & 1 == 1 {
+                // The left sibling is complete, since all leaves before this one have been pushed.
+                siblings.push(self.frontier[height]);
+                H::two_to_one(self.frontier[height], digest)
+            } else {
+                // The right sibling is empty, since this is the last leaf pushed.
+                self.frontier[height] = digest;
+                siblings.push(self.empty_digests[height]);
+                H::two_to_one(digest, self.empty_digests[height])
+            };
+            index >>= 1;
+        }
+
+        self.len += 1;
+        self.root = digest;
+        Ok(MerkleProof { siblings })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(not(feature = "std"))]
+    use alloc::vec;
+
+    use super::*;
+    use crate::field::types::Sample;
+    use crate::hash::merkle_proofs::verify_merkle_proof;
+    use crate::hash::merkle_tree::MerkleTree;
+    use crate::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+    type H = <C as GenericConfig<D>>::Hasher;
+
+    #[test]
+    fn test_incremental_merkle_tree() -> Result<()> {
+        let depth = 4;
+        let mut tree = IncrementalMerkleTree::<F, H>::new(depth);
+        let mut leaves = vec![Vec::new(); 1 << depth];
+        assert_eq!(
+            tree.root(),
+            MerkleTree::<F, H>::new(leaves.clone(), 0).cap.0[0]
+        );
+
+        for i in 0..1 << depth {
+            let leaf = F::rand_vec(7);
+            let proof = tree.push(leaf.clone())?;
+            verify_merkle_proof(leaf.clone(), i, tree.root(), &proof)?;
+
+            leaves[i] = leaf;
+            let full_tree = MerkleTree::<F, H>::new(leaves.clone(), 0);
+            assert_eq!(tree.root(), full_tree.cap.0[0]);
+            assert_eq!(proof, full_tree.prove(i));
+        }
+
+        assert!(tree.is_full());
+        assert!(tree.push(F::rand_vec(7)).is_err());
+
+        Ok(())
+    }
+}
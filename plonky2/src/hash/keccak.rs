@@ -81,8 +81,11 @@ impl<F: RichField> PlonkyPermutation<F> for KeccakPermutation<F> {
         });
 
         // Parse field elements from u64 stream, using rejection sampling such that words that don't
-        // fit in F are ignored.
+        // fit in F are ignored. Words are first truncated to the bit length of the field order, so
+        // that few are rejected in small fields.
+        let word_mask = u64::MAX >> (64 - F::BITS);
         let hash_onion_elems = hash_onion_u64s
+            .map(|word| word & word_mask)
             .filter(|&word| word < F::ORDER)
             .map(F::from_canonical_u64);
 
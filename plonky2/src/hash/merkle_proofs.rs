@@ -2,6 +2,7 @@ use alloc::vec;
 use alloc::vec::Vec;
 
 use anyhow::{ensure, Result};
+use hashbrown::HashMap;
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 
@@ -9,10 +10,12 @@ use crate::field::extension::Extendable;
 use crate::hash::hash_types::{HashOutTarget, MerkleCapTarget, RichField, NUM_HASH_OUT_ELTS};
 use crate::hash::hashing::PlonkyPermutation;
 use crate::hash::merkle_tree::MerkleCap;
+use crate::hash::path_compression::{compressed_sibling_layers, decompress_merkle_proofs};
 use crate::iop::target::{BoolTarget, Target};
 use crate::plonk::circuit_builder::CircuitBuilder;
 use crate::plonk::circuit_data::VerifierCircuitTarget;
 use crate::plonk::config::{AlgebraicHasher, Hasher};
+use crate::util::log2_strict;
 
 #[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
 #[serde(bound = "")]
@@ -76,6 +79,34 @@ pub fn verify_merkle_proof_to_cap<F: RichField, H: Hasher<F>>(
     Ok(())
 }
 
+/// Verifies compressed Merkle proofs, as created by `MerkleTree::prove_batch`, that the given
+/// leaves are present at the given indices in the tree with `2^height` leaves and the given cap.
+pub fn verify_batch_merkle_proof_to_cap<F: RichField, H: Hasher<F>>(
+    leaves_data: &[Vec<F>],
+    leaf_indices: &[usize],
+    height: usize,
+    merkle_cap: &MerkleCap<F, H>,
+    proofs: &[MerkleProof<F, H>],
+) -> Result<()> {
+    ensure!(
+        leaves_data.len() == leaf_indices.len() && proofs.len() == leaf_indices.len(),
+        "Mismatched number of leaves, indices and proofs."
+    );
+    let cap_height = merkle_cap.height();
+    let layers = compressed_sibling_layers(height, cap_height, leaf_indices);
+    ensure!(
+        layers.iter().zip(proofs).all(|(l, p)| l.len() == p.len()),
+        "Invalid compressed Merkle proof lengths."
+    );
+
+    let proofs = decompress_merkle_proofs(leaves_data, leaf_indices, proofs, height, cap_height);
+    for ((leaf_data, &leaf_index), proof) in leaves_data.iter().zip(leaf_indices).zip(&proofs) {
+        verify_merkle_proof_to_cap(leaf_data.clone(), leaf_index, merkle_cap, proof)?;
+    }
+
+    Ok(())
+}
+
 impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
     /// Verifies that the given leaf data is present at the given index in the Merkle tree with the
     /// given root. The index is given by its little-endian bits.
@@ -119,11 +150,36 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         merkle_cap: &MerkleCapTarget,
         proof: &MerkleProofTarget,
     ) {
+        let state = *self
+            .merkle_proof_digests::<H>(leaf_data, leaf_index_bits, proof)
+            .last()
+            .unwrap();
+
+        for i in 0..NUM_HASH_OUT_ELTS {
+            let result = self.random_access(
+                cap_index,
+                merkle_cap.0.iter().map(|h| h.elements[i]).collect(),
+            );
+            self.connect(result, state.elements[i]);
+        }
+    }
+
+    /// Returns the digests computed while walking up a Merkle proof, starting with the leaf hash and
+    /// ending with the root of the subtree whose siblings are all in `proof`. This lets a proof be
+    /// checked against roots of different heights.
+    pub(crate) fn merkle_proof_digests<H: AlgebraicHasher<F>>(
+        &mut self,
+        leaf_data: Vec<Target>,
+        leaf_index_bits: &[BoolTarget],
+        proof: &MerkleProofTarget,
+    ) -> Vec<HashOutTarget> {
         debug_assert!(H::AlgebraicPermutation::RATE >= NUM_HASH_OUT_ELTS);
 
         let zero = self.zero();
         let mut state: HashOutTarget = self.hash_or_noop::<H>(leaf_data);
         debug_assert_eq!(state.elements.len(), NUM_HASH_OUT_ELTS);
+        let mut digests = Vec::with_capacity(proof.siblings.len() + 1);
+        digests.push(state);
 
         for (&bit, &sibling) in leaf_index_bits.iter().zip(&proof.siblings) {
             debug_assert_eq!(sibling.elements.len(), NUM_HASH_OUT_ELTS);
@@ -140,14 +196,114 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
             state = HashOutTarget {
                 elements: hash_outs,
             };
+            digests.push(state);
         }
 
-        for i in 0..NUM_HASH_OUT_ELTS {
-            let result = self.random_access(
-                cap_index,
-                merkle_cap.0.iter().map(|h| h.elements[i]).collect(),
-            );
-            self.connect(result, state.elements[i]);
+        digests
+    }
+
+    /// Verifies that the leaf at the given index changes from `old_leaf_data` to `new_leaf_data`
+    /// between the Merkle trees with roots `old_root` and `new_root`. The siblings of the leaf are
+    /// unchanged, so both trees share `proof`. Appending to a tree padded with empty leaves is the
+    /// special case where `old_leaf_data` is empty.
+    pub fn verify_merkle_proof_update<H: AlgebraicHasher<F>>(
+        &mut self,
+        old_leaf_data: Vec<Target>,
+        new_leaf_data: Vec<Target>,
+        leaf_index_bits: &[BoolTarget],
+        old_root: HashOutTarget,
+        new_root: HashOutTarget,
+        proof: &MerkleProofTarget,
+    ) {
+        self.verify_merkle_proof::<H>(old_leaf_data, leaf_index_bits, old_root, proof);
+        self.verify_merkle_proof::<H>(new_leaf_data, leaf_index_bits, new_root, proof);
+    }
+
+    /// Adds the targets of compressed Merkle proofs for the given leaves of a tree with
+    /// `2^height` leaves and a cap of height `cap_height`, as created by `MerkleTree::prove_batch`.
+    pub fn add_virtual_batch_merkle_proof(
+        &mut self,
+        height: usize,
+        cap_height: usize,
+        leaf_indices: &[usize],
+    ) -> Vec<MerkleProofTarget> {
+        compressed_sibling_layers(height, cap_height, leaf_indices)
+            .into_iter()
+            .map(|layers| MerkleProofTarget {
+                siblings: self.add_virtual_hashes(layers.len()),
+            })
+            .collect()
+    }
+
+    /// Verifies compressed Merkle proofs that the given leaves are present at the given indices in
+    /// the tree with `2^height` leaves and the given cap. The indices are fixed when building the
+    /// circuit, which lets each node shared by several paths be hashed only once.
+    pub fn verify_batch_merkle_proof_to_cap<H: AlgebraicHasher<F>>(
+        &mut self,
+        leaves_data: Vec<Vec<Target>>,
+        leaf_indices: &[usize],
+        height: usize,
+        merkle_cap: &MerkleCapTarget,
+        proofs: &[MerkleProofTarget],
+    ) {
+        assert_eq!(leaves_data.len(), leaf_indices.len());
+        assert_eq!(proofs.len(), leaf_indices.len());
+        let cap_height = log2_strict(merkle_cap.0.len());
+        let num_leaves = 1 << height;
+
+        // The digests of the nodes computed so far, indexed as in `decompress_merkle_proofs`.
+        let mut known = HashMap::new();
+        for (leaf_data, &i) in leaves_data.into_iter().zip(leaf_indices) {
+            let digest = self.hash_or_noop::<H>(leaf_data);
+            if let Some(&other) = known.get(&(i + num_leaves)) {
+                self.connect_hashes(digest, other);
+            } else {
+                known.insert(i + num_leaves, digest);
+            }
+        }
+
+        let mut siblings = proofs.iter().map(|p| p.siblings.iter()).collect::<Vec<_>>();
+        for layer in 0..height - cap_height {
+            for (&i, p) in leaf_indices.iter().zip(siblings.iter_mut()) {
+                let index = (i + num_leaves) >> layer;
+                if known.contains_key(&(index >> 1)) {
+                    continue;
+                }
+                let current = known[&index];
+                let sibling = *known.entry(index ^ 1).or_insert_with(|| *p.next().unwrap());
+                let parent = if index & 1 == 1 {
+                    self.two_to_one::<H>(sibling, current)
+                } else {
+                    self.two_to_one::<H>(current, sibling)
+                };
+                known.insert(index >> 1, parent);
+            }
+        }
+        debug_assert!(siblings.iter_mut().all(|s| s.next().is_none()));
+
+        for &i in leaf_indices {
+            let root_index = (i + num_leaves) >> (height - cap_height);
+            let cap_index = root_index - (1 << cap_height);
+            self.connect_hashes(known[&root_index], merkle_cap.0[cap_index]);
+        }
+    }
+
+    /// The in-circuit version of `Hasher::two_to_one`.
+    fn two_to_one<H: AlgebraicHasher<F>>(
+        &mut self,
+        left: HashOutTarget,
+        right: HashOutTarget,
+    ) -> HashOutTarget {
+        let zero = self.zero();
+        let mut perm_inputs = H::AlgebraicPermutation::default();
+        perm_inputs.set_from_slice(&left.elements, 0);
+        perm_inputs.set_from_slice(&right.elements, NUM_HASH_OUT_ELTS);
+        perm_inputs.set_from_iter(core::iter::repeat(zero), 2 * NUM_HASH_OUT_ELTS);
+        let perm_outs = self.permute::<H>(perm_inputs);
+        HashOutTarget {
+            elements: perm_outs.squeeze()[0..NUM_HASH_OUT_ELTS]
+                .try_into()
+                .unwrap(),
         }
     }
 
@@ -176,7 +332,8 @@ mod tests {
     use rand::Rng;
 
     use super::*;
-    use crate::field::types::Field;
+    use crate::field::types::{Field, Sample};
+    use crate::hash::incremental_merkle_tree::IncrementalMerkleTree;
     use crate::hash::merkle_tree::MerkleTree;
     use crate::iop::witness::{PartialWitness, WitnessWrite};
     use crate::plonk::circuit_builder::CircuitBuilder;
@@ -232,4 +389,99 @@ mod tests {
 
         verify(proof, &data.verifier_only, &data.common)
     }
+
+    #[test]
+    fn test_recursive_batch_merkle_proof() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        type H = <C as GenericConfig<D>>::InnerHasher;
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let log_n = 8;
+        let n = 1 << log_n;
+        let cap_height = 1;
+        let leaves = random_data::<F>(n, 7);
+        let tree = MerkleTree::<F, H>::new(leaves, cap_height);
+        let indices = (0..10).map(|_| OsRng.gen_range(0..n)).collect::<Vec<_>>();
+        let proofs = tree.prove_batch(&indices);
+
+        let proofs_t = builder.add_virtual_batch_merkle_proof(log_n, cap_height, &indices);
+        for (proof_t, proof) in proofs_t.iter().zip(&proofs) {
+            for (&t, &h) in proof_t.siblings.iter().zip(&proof.siblings) {
+                pw.set_hash_target(t, h);
+            }
+        }
+
+        let cap_t = builder.add_virtual_cap(cap_height);
+        pw.set_cap_target(&cap_t, &tree.cap);
+
+        let data_t = indices
+            .iter()
+            .map(|&i| {
+                let data = builder.add_virtual_targets(tree.leaves[i].len());
+                pw.set_target_arr(&data, &tree.leaves[i]);
+                data
+            })
+            .collect();
+
+        builder.verify_batch_merkle_proof_to_cap::<H>(data_t, &indices, log_n, &cap_t, &proofs_t);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+
+        verify(proof, &data.verifier_only, &data.common)
+    }
+
+    #[test]
+    fn test_recursive_merkle_proof_update() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        type H = <C as GenericConfig<D>>::InnerHasher;
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let log_n = 8;
+        let mut tree = IncrementalMerkleTree::<F, H>::new(log_n);
+        for _ in 0..100 {
+            tree.push(F::rand_vec(7))?;
+        }
+        let old_root = tree.root();
+        let leaf = F::rand_vec(7);
+        let proof = tree.push(leaf.clone())?;
+
+        let proof_t = MerkleProofTarget {
+            siblings: builder.add_virtual_hashes(proof.siblings.len()),
+        };
+        for (&t, &h) in proof_t.siblings.iter().zip(&proof.siblings) {
+            pw.set_hash_target(t, h);
+        }
+        let old_root_t = builder.add_virtual_hash();
+        pw.set_hash_target(old_root_t, old_root);
+        let new_root_t = builder.add_virtual_hash();
+        pw.set_hash_target(new_root_t, tree.root());
+
+        let i_c = builder.constant(F::from_canonical_usize(tree.len() - 1));
+        let i_bits = builder.split_le(i_c, log_n);
+        let data = builder.add_virtual_targets(leaf.len());
+        pw.set_target_arr(&data, &leaf);
+
+        builder.verify_merkle_proof_update::<H>(
+            Vec::new(),
+            data,
+            &i_bits,
+            old_root_t,
+            new_root_t,
+            &proof_t,
+        );
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+
+        verify(proof, &data.verifier_only, &data.common)
+    }
 }
@@ -7,6 +7,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::hash::hash_types::RichField;
 use crate::hash::merkle_proofs::MerkleProof;
+use crate::hash::path_compression::compressed_sibling_layers;
 use crate::plonk::config::{GenericHashOut, Hasher};
 use crate::util::log2_strict;
 
@@ -221,6 +222,53 @@ impl<F: RichField, H: Hasher<F>> MerkleTree<F, H> {
 
         MerkleProof { siblings }
     }
+
+    /// Create compressed Merkle proofs for several leaves at once, to be checked with
+    /// `verify_batch_merkle_proof_to_cap`. Siblings which are on the path of another leaf, or
+    /// which are already in an earlier proof, are omitted.
+    pub fn prove_batch(&self, leaf_indices: &[usize]) -> Vec<MerkleProof<F, H>> {
+        let height = log2_strict(self.leaves.len());
+        compressed_sibling_layers(height, self.cap.height(), leaf_indices)
+            .into_iter()
+            .zip(leaf_indices)
+            .map(|(layers, &i)| {
+                let proof = self.prove(i);
+                MerkleProof {
+                    siblings: layers.into_iter().map(|l| proof.siblings[l]).collect(),
+                }
+            })
+            .collect()
+    }
+
+    /// Replace the data of a leaf, recomputing only the digests on its path and its cap element.
+    pub fn update_leaf(&mut self, leaf_index: usize, leaf: Vec<F>) {
+        let cap_height = log2_strict(self.cap.len());
+        let num_layers = log2_strict(self.leaves.len()) - cap_height;
+
+        let tree_index = leaf_index >> num_layers;
+        let tree_len = self.digests.len() >> cap_height;
+        let digest_tree = &mut self.digests[tree_len * tree_index..tree_len * (tree_index + 1)];
+
+        // Walk up the same interleaved layout as `prove`, writing the new digest of each node on
+        // the path next to its sibling.
+        let mut digest = H::hash_or_noop(&leaf);
+        let mut pair_index = leaf_index & ((1 << num_layers) - 1);
+        for i in 0..num_layers {
+            let parity = pair_index & 1;
+            pair_index >>= 1;
+            let siblings_index = (pair_index << (i + 1)) + (1 << i) - 1;
+            digest_tree[2 * siblings_index + parity] = digest;
+            let sibling = digest_tree[2 * siblings_index + 1 - parity];
+            digest = if parity == 1 {
+                H::two_to_one(sibling, digest)
+            } else {
+                H::two_to_one(digest, sibling)
+            };
+        }
+
+        self.cap.0[tree_index] = digest;
+        self.leaves[leaf_index] = leaf;
+    }
 }
 
 #[cfg(test)]
@@ -229,7 +277,10 @@ mod tests {
 
     use super::*;
     use crate::field::extension::Extendable;
-    use crate::hash::merkle_proofs::verify_merkle_proof_to_cap;
+    use crate::field::types::Sample;
+    use crate::hash::merkle_proofs::{
+        verify_batch_merkle_proof_to_cap, verify_merkle_proof_to_cap,
+    };
     use crate::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
 
     fn random_data<F: RichField>(n: usize, k: usize) -> Vec<Vec<F>> {
@@ -295,4 +346,63 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_update_leaf() {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        type H = <C as GenericConfig<D>>::Hasher;
+
+        let log_n = 5;
+        let n = 1 << log_n;
+        for cap_height in [0, 2, log_n] {
+            let mut leaves = random_data::<F>(n, 7);
+            let mut tree = MerkleTree::<F, H>::new(leaves.clone(), cap_height);
+            for i in [0, 7, n - 1, 7] {
+                let leaf = F::rand_vec(7);
+                tree.update_leaf(i, leaf.clone());
+                leaves[i] = leaf;
+                assert_eq!(tree, MerkleTree::new(leaves.clone(), cap_height));
+            }
+        }
+    }
+
+    #[test]
+    fn test_prove_batch() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        type H = <C as GenericConfig<D>>::Hasher;
+
+        let log_n = 8;
+        let n = 1 << log_n;
+        let cap_height = 2;
+        let leaves = random_data::<F>(n, 7);
+        let tree = MerkleTree::<F, H>::new(leaves.clone(), cap_height);
+
+        let indices = [3, 200, 4, 3, 255, 0];
+        let proofs = tree.prove_batch(&indices);
+        let num_siblings = proofs.iter().map(|p| p.len()).sum::<usize>();
+        assert!(num_siblings < indices.len() * (log_n - cap_height));
+
+        let leaves_data = indices
+            .iter()
+            .map(|&i| leaves[i].clone())
+            .collect::<Vec<_>>();
+        verify_batch_merkle_proof_to_cap(&leaves_data, &indices, log_n, &tree.cap, &proofs)?;
+
+        let mut wrong_leaves_data = leaves_data;
+        wrong_leaves_data[1] = F::rand_vec(7);
+        assert!(verify_batch_merkle_proof_to_cap(
+            &wrong_leaves_data,
+            &indices,
+            log_n,
+            &tree.cap,
+            &proofs
+        )
+        .is_err());
+
+        Ok(())
+    }
 }
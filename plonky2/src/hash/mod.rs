@@ -4,9 +4,13 @@
 mod arch;
 pub mod hash_types;
 pub mod hashing;
+pub mod incremental_merkle_tree;
 pub mod keccak;
 pub mod merkle_proofs;
 pub mod merkle_tree;
 pub mod path_compression;
 pub mod poseidon;
+pub mod poseidon_babybear;
 pub mod poseidon_goldilocks;
+pub mod sparse_merkle_proofs;
+pub mod sparse_merkle_tree;
@@ -8,16 +8,15 @@ use crate::hash::hash_types::RichField;
 use crate::hash::merkle_proofs::MerkleProof;
 use crate::plonk::config::Hasher;
 
-/// Compress multiple Merkle proofs on the same tree by removing redundancy in the Merkle paths.
-pub(crate) fn compress_merkle_proofs<F: RichField, H: Hasher<F>>(
+/// For each of the given leaves, the layers whose sibling is kept when compressing their Merkle
+/// proofs with `compress_merkle_proofs`. The other siblings are either on the path of one of the
+/// leaves, or kept by an earlier proof.
+pub(crate) fn compressed_sibling_layers(
+    height: usize,
     cap_height: usize,
     indices: &[usize],
-    proofs: &[MerkleProof<F, H>],
-) -> Vec<MerkleProof<F, H>> {
-    assert!(!proofs.is_empty());
-    let height = cap_height + proofs[0].siblings.len();
+) -> Vec<Vec<usize>> {
     let num_leaves = 1 << height;
-    let mut compressed_proofs = Vec::with_capacity(proofs.len());
     // Holds the known nodes in the tree at a given time. The root is at index 1.
     // Valid indices are 1 through n, and each element at index `i` has
     // children at indices `2i` and `2i +1` its parent at index `floor(i ∕ 2)`.
@@ -29,26 +28,42 @@ pub(crate) fn compress_merkle_proofs<F: RichField, H: Hasher<F>>(
         }
     }
     // For each proof collect all the unknown proof elements.
-    for (&i, p) in indices.iter().zip(proofs) {
-        let mut compressed_proof = MerkleProof {
-            siblings: Vec::new(),
-        };
-        let mut index = i + num_leaves;
-        for &sibling in &p.siblings {
-            let sibling_index = index ^ 1;
-            if !known[sibling_index] {
-                // If the sibling is not yet known, add it to the proof and set it to known.
-                compressed_proof.siblings.push(sibling);
-                known[sibling_index] = true;
+    indices
+        .iter()
+        .map(|&i| {
+            let mut layers = Vec::new();
+            let mut index = i + num_leaves;
+            for layer in 0..(height - cap_height) {
+                let sibling_index = index ^ 1;
+                if !known[sibling_index] {
+                    // If the sibling is not yet known, add it to the proof and set it to known.
+                    layers.push(layer);
+                    known[sibling_index] = true;
+                }
+                // Go up the tree and set the parent to known.
+                index >>= 1;
+                known[index] = true;
             }
-            // Go up the tree and set the parent to known.
-            index >>= 1;
-            known[index] = true;
-        }
-        compressed_proofs.push(compressed_proof);
-    }
+            layers
+        })
+        .collect()
+}
 
-    compressed_proofs
+/// Compress multiple Merkle proofs on the same tree by removing redundancy in the Merkle paths.
+pub(crate) fn compress_merkle_proofs<F: RichField, H: Hasher<F>>(
+    cap_height: usize,
+    indices: &[usize],
+    proofs: &[MerkleProof<F, H>],
+) -> Vec<MerkleProof<F, H>> {
+    assert!(!proofs.is_empty());
+    let height = cap_height + proofs[0].siblings.len();
+    compressed_sibling_layers(height, cap_height, indices)
+        .into_iter()
+        .zip(proofs)
+        .map(|(layers, p)| MerkleProof {
+            siblings: layers.into_iter().map(|layer| p.siblings[layer]).collect(),
+        })
+        .collect()
 }
 
 /// Decompress compressed Merkle proofs.
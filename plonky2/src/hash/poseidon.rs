@@ -12,8 +12,10 @@ use crate::field::types::{Field, PrimeField64};
 use crate::gates::gate::Gate;
 use crate::gates::poseidon::PoseidonGate;
 use crate::gates::poseidon_mds::PoseidonMdsGate;
-use crate::hash::hash_types::{HashOut, RichField};
-use crate::hash::hashing::{compress, hash_n_to_hash_no_pad, PlonkyPermutation};
+use crate::hash::hash_types::{HashOut, RichField, WideHashOut, NUM_WIDE_HASH_OUT_ELTS};
+use crate::hash::hashing::{
+    compress, hash_n_to_hash_no_pad, hash_n_to_m_no_pad, PlonkyPermutation,
+};
 use crate::iop::ext_target::ExtensionTarget;
 use crate::iop::target::{BoolTarget, Target};
 use crate::plonk::circuit_builder::CircuitBuilder;
@@ -165,6 +167,10 @@ pub trait Poseidon: PrimeField64 {
     const MDS_MATRIX_CIRC: [u64; SPONGE_WIDTH];
     const MDS_MATRIX_DIAG: [u64; SPONGE_WIDTH];
 
+    // The round constants, which must be in canonical form. The default ones are only canonical
+    // for fields of (nearly) 64 bits such as Goldilocks.
+    const ROUND_CONSTANTS: [u64; SPONGE_WIDTH * N_ROUNDS] = ALL_ROUND_CONSTANTS;
+
     // Precomputed constants for the fast Poseidon calculation. See
     // the paper.
     const FAST_PARTIAL_FIRST_ROUND_CONSTANT: [u64; SPONGE_WIDTH];
@@ -484,7 +490,7 @@ pub trait Poseidon: PrimeField64 {
     fn constant_layer(state: &mut [Self; SPONGE_WIDTH], round_ctr: usize) {
         for i in 0..12 {
             if i < SPONGE_WIDTH {
-                let round_constant = ALL_ROUND_CONSTANTS[i + SPONGE_WIDTH * round_ctr];
+                let round_constant = Self::ROUND_CONSTANTS[i + SPONGE_WIDTH * round_ctr];
                 unsafe {
                     state[i] = state[i].add_canonical_u64(round_constant);
                 }
@@ -498,7 +504,7 @@ pub trait Poseidon: PrimeField64 {
         round_ctr: usize,
     ) {
         for i in 0..SPONGE_WIDTH {
-            state[i] += F::from_canonical_u64(ALL_ROUND_CONSTANTS[i + SPONGE_WIDTH * round_ctr]);
+            state[i] += F::from_canonical_u64(Self::ROUND_CONSTANTS[i + SPONGE_WIDTH * round_ctr]);
         }
     }
 
@@ -511,7 +517,7 @@ pub trait Poseidon: PrimeField64 {
         Self: RichField + Extendable<D>,
     {
         for i in 0..SPONGE_WIDTH {
-            let c = ALL_ROUND_CONSTANTS[i + SPONGE_WIDTH * round_ctr];
+            let c = Self::ROUND_CONSTANTS[i + SPONGE_WIDTH * round_ctr];
             let c = Self::Extension::from_canonical_u64(c);
             let c = builder.constant_extension(c);
             state[i] = builder.add_extension(state[i], c);
@@ -751,6 +757,71 @@ impl<F: RichField> AlgebraicHasher<F> for PoseidonHash {
     }
 }
 
+/// Poseidon permutation used as a sponge of rate 4 and capacity 8, see `PoseidonWideHash`.
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq)]
+pub struct PoseidonWidePermutation<F>(PoseidonPermutation<F>);
+
+impl<F> AsRef<[F]> for PoseidonWidePermutation<F> {
+    fn as_ref(&self) -> &[F] {
+        self.0.as_ref()
+    }
+}
+
+impl<F: RichField> PlonkyPermutation<F> for PoseidonWidePermutation<F> {
+    const RATE: usize = SPONGE_WIDTH - NUM_WIDE_HASH_OUT_ELTS;
+    const WIDTH: usize = SPONGE_WIDTH;
+
+    fn new<I: IntoIterator<Item = F>>(elts: I) -> Self {
+        Self(PoseidonPermutation::new(elts))
+    }
+
+    fn set_elt(&mut self, elt: F, idx: usize) {
+        self.0.set_elt(elt, idx);
+    }
+
+    fn set_from_slice(&mut self, elts: &[F], start_idx: usize) {
+        self.0.set_from_slice(elts, start_idx);
+    }
+
+    fn set_from_iter<I: IntoIterator<Item = F>>(&mut self, elts: I, start_idx: usize) {
+        self.0.set_from_iter(elts, start_idx);
+    }
+
+    fn permute(&mut self) {
+        self.0.permute();
+    }
+
+    fn squeeze(&self) -> &[F] {
+        &self.0.as_ref()[..Self::RATE]
+    }
+}
+
+/// Poseidon hash function with 8-element digests, for fields too small for the 4-element digests of
+/// `PoseidonHash` to be collision resistant, such as BabyBear. To match the digest size, the sponge
+/// has a capacity of 8 elements, and thus absorbs and squeezes 4 elements per permutation.
+///
+/// This hasher has no circuit implementation, so it can only be used natively.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct PoseidonWideHash;
+impl<F: RichField> Hasher<F> for PoseidonWideHash {
+    const HASH_SIZE: usize = NUM_WIDE_HASH_OUT_ELTS * 8;
+    type Hash = WideHashOut<F>;
+    type Permutation = PoseidonWidePermutation<F>;
+
+    fn hash_no_pad(input: &[F]) -> Self::Hash {
+        WideHashOut::from_vec(hash_n_to_m_no_pad::<F, Self::Permutation>(
+            input,
+            NUM_WIDE_HASH_OUT_ELTS,
+        ))
+    }
+
+    fn two_to_one(left: Self::Hash, right: Self::Hash) -> Self::Hash {
+        let mut input = left.elements.to_vec();
+        input.extend_from_slice(&right.elements);
+        Self::hash_no_pad(&input)
+    }
+}
+
 #[cfg(test)]
 pub(crate) mod test_helpers {
     #[cfg(not(feature = "std"))]
@@ -0,0 +1,364 @@
+//! Implementation of Poseidon over the BabyBear field, with width 12.
+//!
+//! The S-box is `x^7`, which is a permutation since `gcd(7, p - 1) = 1`.
+//!
+//! The round numbers are derived as in Plonky3, with the round number procedure of the Poseidon2
+//! reference implementation (`calc_round_numbers.py`, see
+//! <https://github.com/HorizenLabs/poseidon2>): the numbers of full and partial rounds minimizing
+//! the number of S-boxes while resisting the statistical, interpolation and Gröbner basis attacks
+//! of the Poseidon paper (Grassi et al., <https://eprint.iacr.org/2019/458>) and the attack of
+//! <https://eprint.iacr.org/2023/537> at 128-bit security, plus two full rounds and 7.5% more
+//! partial rounds as a security margin. For BabyBear and `x^7`, this gives Plonky3's published
+//! `R_F = 8` and `R_P = 13` for width 16, and `R_P = 21` for width 24. For width 12 it gives
+//! `R_F = 8` and `R_P = 10`: we use `R_F = 8`, and the 22 partial rounds fixed by
+//! `N_PARTIAL_ROUNDS`, which the Poseidon gate also relies on, so we have 12 partial rounds more
+//! than required. The `round_numbers` test below checks this derivation.
+//!
+//! The round constants are generated with the Grain LFSR of the Poseidon reference implementation
+//! (`generate_parameters_grain.sage`, see <https://extgit.iaik.tugraz.at/krypto/hadeshash>), for
+//! a prime field with `n = 31`, `t = 12`, `R_F = 8` and `R_P = 22`.
+//!
+//! The MDS matrix has the same circulant-plus-diagonal shape as over Goldilocks, whose matrix is
+//! not MDS over BabyBear. Ours was checked to have no singular square submatrix over BabyBear, and
+//! the characteristic polynomials of `M^i` for `1 <= i <= 2t` to be irreducible, which rules out
+//! infinitely long subspace trails in the partial rounds (see Grassi et al., "Proving Resistance
+//! Against Infinitely Long Subspace Trails: How to Choose the Linear Layer"). The constants of
+//! the fast partial rounds are derived from those as in the `poseidon_constants.sage` script, and
+//! checked against the naive permutation by the tests below.
+
+use crate::field::babybear_field::BabyBearField;
+use crate::hash::poseidon::{Poseidon, N_PARTIAL_ROUNDS, N_ROUNDS, SPONGE_WIDTH};
+
+#[rustfmt::skip]
+impl Poseidon for BabyBearField {
+    // The MDS matrix we use is C + D, where C is the circulant matrix whose first row is given by
+    // `MDS_MATRIX_CIRC`, and D is the diagonal matrix whose diagonal is given by `MDS_MATRIX_DIAG`.
+    //
+    // WARNING: If the MDS matrix or the round constants are changed, then the following
+    // constants need to be updated accordingly:
+    //  - FAST_PARTIAL_FIRST_ROUND_CONSTANT
+    //  - FAST_PARTIAL_ROUND_CONSTANTS
+    //  - FAST_PARTIAL_ROUND_VS
+    //  - FAST_PARTIAL_ROUND_W_HATS
+    //  - FAST_PARTIAL_ROUND_INITIAL_MATRIX
+    const MDS_MATRIX_CIRC: [u64; 12] = [46, 49, 9, 10, 63, 48, 3, 43, 4, 9, 15, 35];
+    const MDS_MATRIX_DIAG: [u64; 12] = [12, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+
+    const ROUND_CONSTANTS: [u64; SPONGE_WIDTH * N_ROUNDS] = [
+        0x2aca0f60, 0x3ba734d7, 0x06ec532c, 0x38b9494f, 0x417e70fe, 0x252eacf5,
+        0x426bf2c6, 0x2d17ffc7, 0x1cff027c, 0x22e50af9, 0x0b016838, 0x6a594419,
+        0x2c7426e5, 0x6f1b5206, 0x566c7d29, 0x766ebf1b, 0x490d199d, 0x1406ac9d,
+        0x4d0751fe, 0x3985f66d, 0x25d068db, 0x48cc6aa1, 0x109dfbf5, 0x4bbbbad6,
+        0x4cf3d862, 0x2ff7b94e, 0x1eaaa905, 0x751d68df, 0x317f9b22, 0x19bd9e63,
+        0x062fe825, 0x332ed44c, 0x5972352f, 0x762d42b4, 0x0b3ca65b, 0x315ac7aa,
+        0x1d7aab14, 0x4c8ee3c4, 0x6713de36, 0x5be4d21a, 0x1a05aacc, 0x178f9941,
+        0x61e3ae9f, 0x07ed872a, 0x33beea2b, 0x542dbaf5, 0x7283d36c, 0x3f5a8437,
+        0x593ba3fa, 0x5605ad06, 0x408afe78, 0x198bd589, 0x35a7efad, 0x216a3c9d,
+        0x03449f97, 0x46d8d754, 0x27009f67, 0x54d561d7, 0x37b4414c, 0x5bc8e541,
+        0x45681efd, 0x01ca65c4, 0x4f52fd74, 0x2f4d0440, 0x11cf3809, 0x685e3453,
+        0x592f845c, 0x03b66d8a, 0x62cfd7d3, 0x72c47885, 0x58e7ff30, 0x5f4b82e9,
+        0x3a763615, 0x3af73ae0, 0x00f479a7, 0x2bd5d15d, 0x26641d49, 0x5f130785,
+        0x1cc10d09, 0x54077647, 0x41c02700, 0x3710b983, 0x0646510c, 0x6aff023e,
+        0x22d1b1eb, 0x61d591c3, 0x19f2c712, 0x40a60bdf, 0x1f7483e5, 0x3ffcfa08,
+        0x4539fce1, 0x2ef5f162, 0x46fee714, 0x3d03c26f, 0x514bea0c, 0x1d95e123,
+        0x37de4ee0, 0x50621a17, 0x72a58af9, 0x046f082c, 0x31d4c91f, 0x63b70752,
+        0x02feb00b, 0x0338e721, 0x1141ba94, 0x12fa780f, 0x18c02e12, 0x5602f382,
+        0x00d48230, 0x6f9609ec, 0x54845d57, 0x2bcf97e7, 0x6ea9ae65, 0x3d4d4c81,
+        0x0a79b3ea, 0x4e486271, 0x385f5210, 0x57a892e1, 0x132f1646, 0x170e4499,
+        0x462993ab, 0x4170d71f, 0x50eabd12, 0x18241b92, 0x43d71eab, 0x008b148d,
+        0x5b1e6ca3, 0x58210512, 0x220c0084, 0x31e6c8a9, 0x6ea4fe0a, 0x02260f62,
+        0x44f07cfc, 0x5cb95add, 0x16d31902, 0x35dab942, 0x1fdc3ef1, 0x60004542,
+        0x593669ef, 0x385abc3e, 0x395eede7, 0x52b67d61, 0x6b72587d, 0x1a779dc9,
+        0x0178db91, 0x2d3fc017, 0x180e9d38, 0x212a8e97, 0x2546000a, 0x20f7eb01,
+        0x024b3991, 0x5a3ed69d, 0x508e8924, 0x2b5ad2d0, 0x55948437, 0x048089e4,
+        0x77a20d63, 0x3615693f, 0x3342d7f5, 0x25b6715f, 0x5dba02bb, 0x0b247e42,
+        0x4a9549a6, 0x3fbffefa, 0x4c56b2e4, 0x1259c687, 0x66a565c6, 0x274ce13c,
+        0x05baf5ee, 0x70de5238, 0x4701106f, 0x47fc4cee, 0x3aa8e9c7, 0x45b55c00,
+        0x0c621060, 0x296bd032, 0x5ebe831d, 0x3d118c81, 0x3e8a9237, 0x15aff485,
+        0x0a45966a, 0x6b72590a, 0x29a0bcda, 0x20b56348, 0x4016109b, 0x37ea7aae,
+        0x4f47c3c6, 0x66333e62, 0x1ec62f27, 0x4b07a7dc, 0x24a8b57b, 0x6526b857,
+        0x1169391c, 0x20fa625b, 0x6d67fc83, 0x295c1a49, 0x6c90ae12, 0x7137b224,
+        0x74b6adde, 0x4831a90b, 0x71b7cdd6, 0x3c38b85c, 0x1457a016, 0x532bd8f1,
+        0x16eb5ec3, 0x3843c556, 0x5931f8d0, 0x1aefde66, 0x6e61a73b, 0x6d91de95,
+        0x10a392ae, 0x690ba270, 0x200a3f3a, 0x1e9c51a7, 0x0f80430a, 0x0a076e31,
+        0x019f39b2, 0x3a56438b, 0x2d4b5759, 0x588dc54b, 0x3325db17, 0x08a55fd4,
+        0x043c4a7a, 0x6523c274, 0x5802aa9f, 0x6482af11, 0x4079029a, 0x5422936d,
+        0x0f0f4feb, 0x166bcd32, 0x3bfb0abb, 0x16f759f6, 0x6d81799e, 0x5bb91744,
+        0x25f99d00, 0x47e0b365, 0x04efeb08, 0x71cee707, 0x3857b009, 0x5df90ff6,
+        0x38eb2c16, 0x6a6eda0a, 0x0a804d2a, 0x25b597ed, 0x12836b54, 0x54bac7e7,
+        0x3bade01d, 0x1194e16f, 0x0fdc933f, 0x125460ee, 0x35780cef, 0x353bcf5c,
+        0x284d13e4, 0x6c7bf643, 0x5f3de1d0, 0x5c7786df, 0x4e643e15, 0x29167be7,
+        0x6d169a67, 0x67fee7cc, 0x298a9dbd, 0x485a9f7c, 0x7416f32c, 0x3f2ccb55,
+        0x1aa5a604, 0x09668ab6, 0x0ba9fbfb, 0x4092f7cd, 0x42ab286f, 0x286587f3,
+        0x1bf7588a, 0x22c21b38, 0x2ceb9090, 0x1e3ad8e9, 0x3b5b0bef, 0x1b8adf66,
+        0x5eba8f8d, 0x1b14be5a, 0x522b2e76, 0x1a1f99fd, 0x57c4d1d8, 0x1be03507,
+        0x4c5446b2, 0x4bd224f8, 0x0b645a45, 0x512f5ac5, 0x37301c0c, 0x3b40dfcf,
+        0x609ab28b, 0x189e103b, 0x31e1d2b7, 0x4a82bc33, 0x0827cfe2, 0x39931b4f,
+        0x6ce82553, 0x282bafbe, 0x570f6e4a, 0x0a62458a, 0x3200269c, 0x3f575bd7,
+        0x0d7618de, 0x0f392ac7, 0x1cc975f2, 0x18741cfd, 0x3c223983, 0x1931571f,
+        0x26d6bc81, 0x6893827c, 0x3736b6d2, 0x1d3d8583, 0x26fd0fd4, 0x1882e166,
+        0x48be6c61, 0x5080539d, 0x292a0da8, 0x6765a684, 0x123c977f, 0x1b1451c3,
+        0x5ef995dc, 0x14c017a3, 0x3b8e290b, 0x01a78c84, 0x46ed9af4, 0x19a77953,
+        0x6a28b0e1, 0x2e6da936, 0x3626f911, 0x4b1f79ee, 0x2431e31a, 0x41e6f6fa,
+        0x162e8389, 0x24906cf0, 0x51a93db2, 0x095591b6, 0x701291ea, 0x34e19a0f,
+        0x36d146c2, 0x66ceecb3, 0x4088627b, 0x50a8abd3, 0x70f33725, 0x06503514,
+        0x6becdd7d, 0x0281f5f0, 0x50cd0f28, 0x20e9b6ca, 0x6de65db5, 0x241ed36e,
+        0x3ce1a4dd, 0x6d48f2ef, 0x17cef120, 0x43867380, 0x4d7fc688, 0x0c921543,
+        0x499022fe, 0x157d3ac7, 0x028a40ae, 0x38db0a2c, 0x7054033e, 0x6825f3d1,
+    ];
+
+    const FAST_PARTIAL_FIRST_ROUND_CONSTANT: [u64; 12] = [
+        0x593ba3fa, 0x32e87dd7, 0x215416fc, 0x207e9f5b, 0x009487ff, 0x0712150a,
+        0x51df5ec5, 0x736aeabc, 0x1d024a17, 0x3a6a4a64, 0x3aa5b5f8, 0x63eac104,
+    ];
+
+    const FAST_PARTIAL_ROUND_CONSTANTS: [u64; N_PARTIAL_ROUNDS] = [
+        0x3324e19f, 0x0b95620e, 0x745107ab, 0x483f9165, 0x02f6ec48, 0x1983961f,
+        0x00c43961, 0x2fe8ad22, 0x1d547ea8, 0x72f02578, 0x37220a1e, 0x6fec496d,
+        0x0d031041, 0x077b617b, 0x72434b21, 0x31b78433, 0x084632c0, 0x1daa50ad,
+        0x645fc7be, 0x70a712da, 0x2c171ed4, 0x00000000,
+    ];
+
+    const FAST_PARTIAL_ROUND_VS: [[u64; 12 - 1]; N_PARTIAL_ROUNDS] = [
+        [0x119e772b, 0x65c84710, 0x3d66ed30, 0x1537cb9c, 0x692d2f67, 0x0582560d,
+         0x57804563, 0x71fef558, 0x1e1af5d3, 0x04898e81, 0x4f58d728],
+        [0x01d96de4, 0x65f01814, 0x5210a526, 0x3f9b2664, 0x02a443d6, 0x151aead5,
+         0x6a255dc8, 0x4e778ede, 0x3081c070, 0x36605082, 0x2880be44],
+        [0x4dc4eb47, 0x420d661a, 0x708996f0, 0x137dbede, 0x0dbdd525, 0x1bdb7033,
+         0x012a54c5, 0x30b956fa, 0x107f88f8, 0x557fe39f, 0x02e0d745],
+        [0x381d14ef, 0x37bda1c0, 0x3c58040c, 0x65282683, 0x24a4d0de, 0x6841ddb9,
+         0x016d0dfe, 0x6d9a75d1, 0x6007372c, 0x3c205cad, 0x43ae19b4],
+        [0x703c30d6, 0x4ac55f9d, 0x5b15bdfa, 0x277799a0, 0x39b9cb31, 0x2eacc574,
+         0x682b7600, 0x0551196b, 0x4a475e9e, 0x5fb98610, 0x0d369058],
+        [0x02161a21, 0x4f92b6a2, 0x076260cb, 0x27a7192c, 0x1dc8c73b, 0x25b55308,
+         0x6cc6a4c3, 0x037b3e85, 0x577abf4c, 0x456ce9f7, 0x27ccfe75],
+        [0x594d02cd, 0x646364dc, 0x12890073, 0x08adbb1e, 0x5fbabde8, 0x10e11076,
+         0x2827e98f, 0x6788475c, 0x3e995963, 0x19358f8b, 0x44b8cc87],
+        [0x064876a6, 0x3c6aaf93, 0x0d30df43, 0x51725bc2, 0x2755814a, 0x47d3e864,
+         0x5865c4fa, 0x302c3348, 0x299e6387, 0x6fbe7f72, 0x52240538],
+        [0x1f26434a, 0x0a495c23, 0x19c8655a, 0x0162e638, 0x00739a2b, 0x6c56a0c5,
+         0x746f6561, 0x73f29142, 0x724e7b5b, 0x5fc3ce39, 0x1eb505b5],
+        [0x247764cb, 0x2646bd15, 0x57813691, 0x4ac764b6, 0x1aeb065a, 0x6cec2096,
+         0x71aec027, 0x248c6344, 0x50b72ff1, 0x127ed1c1, 0x291e17b1],
+        [0x1dd79d3f, 0x42d4a6cb, 0x34cb1d0c, 0x168380d3, 0x45fad16c, 0x74e882dd,
+         0x5b5b7e05, 0x169e7fa1, 0x6c32ae65, 0x07031004, 0x55b8cad9],
+        [0x6304ba35, 0x58d2f0d2, 0x08de28e3, 0x7302ca39, 0x26682f9d, 0x603f605c,
+         0x1c2bc11b, 0x60d74849, 0x63a94734, 0x20a109e0, 0x5f7ace21],
+        [0x2c799c99, 0x018bb8cd, 0x096f2a7c, 0x5709d45f, 0x2179504a, 0x38bdbef1,
+         0x5991ccb4, 0x56e2d9f3, 0x6b60fcae, 0x1456f1fb, 0x1b7cd3bb],
+        [0x6afe9e5f, 0x39c928b4, 0x42a0a721, 0x3897de42, 0x744f8e43, 0x63fde8b3,
+         0x6bb1a7db, 0x5f2fc959, 0x114665a4, 0x55867687, 0x49916006],
+        [0x06d79943, 0x1a74bb68, 0x66fe3498, 0x36e7d463, 0x28fb75ee, 0x51c5278f,
+         0x0d4acd0b, 0x3f52acd1, 0x56ad981a, 0x62a89bed, 0x650b5b17],
+        [0x3868e1fe, 0x03704e8e, 0x6d2556a3, 0x17399a49, 0x4a466c96, 0x12331b67,
+         0x52cc6ccf, 0x661636f5, 0x649ad011, 0x5e9a4da8, 0x14452b4b],
+        [0x3e229cc6, 0x092d7488, 0x5a28ee25, 0x6946fd61, 0x5e9b8704, 0x0b7d23b5,
+         0x5f25cfdc, 0x63ee9cee, 0x2ea28469, 0x60617ca7, 0x6111ee76],
+        [0x0530891f, 0x0c194237, 0x573689c8, 0x27701007, 0x139b26ca, 0x420f83f0,
+         0x47e8d00f, 0x47adbd5f, 0x151299b1, 0x65547ea7, 0x72116e05],
+        [0x2b6c013e, 0x3027a80b, 0x2efebd3a, 0x3077473c, 0x2b592d60, 0x2ea103e3,
+         0x290d6400, 0x2781435b, 0x2ee6861e, 0x306a67a2, 0x2b14f916],
+        [0x00220240, 0x0028e600, 0x002b1cc3, 0x00262567, 0x0023715e, 0x002856a9,
+         0x0020f259, 0x0022c91a, 0x0028dcae, 0x0022f758, 0x0023bf0e],
+        [0x00002344, 0x0000190b, 0x00002222, 0x000027fc, 0x00001352, 0x00002447,
+         0x00002796, 0x00002007, 0x00001da6, 0x00001fce, 0x00001360],
+        [0x00000023, 0x0000000f, 0x00000009, 0x00000004, 0x0000002b, 0x00000003,
+         0x00000030, 0x0000003f, 0x0000000a, 0x00000009, 0x00000031],
+    ];
+
+    const FAST_PARTIAL_ROUND_W_HATS: [[u64; 12 - 1]; N_PARTIAL_ROUNDS] = [
+        [0x4ab28b20, 0x2d4de877, 0x2b8d490c, 0x3addb707, 0x2d1797c6, 0x6730e841,
+         0x63a52d25, 0x2abc1439, 0x27fecbd7, 0x6f8507a1, 0x623a64e1],
+        [0x338b34a3, 0x1ed0ee62, 0x12765667, 0x27bcfab2, 0x0a9cf245, 0x77b6f315,
+         0x453bcbe8, 0x5bb10f22, 0x5304da9f, 0x4d0133bc, 0x5a012f36],
+        [0x35853f61, 0x1c2fb805, 0x55dca03a, 0x2b7cf86b, 0x2bfc30dd, 0x1ca2f59c,
+         0x58247c8f, 0x2b1fdd9e, 0x63f52ee6, 0x6d915ce7, 0x6e7b2e38],
+        [0x60d97e44, 0x5c093119, 0x3a6b36ab, 0x3d5bcb57, 0x37e04b11, 0x2fba8613,
+         0x080e865a, 0x3fb8a9a9, 0x19129b65, 0x06c72ce1, 0x1f3c1496],
+        [0x47f28c4d, 0x7692a6a8, 0x40837800, 0x768ca372, 0x0ed1b1da, 0x1e916e26,
+         0x06b7b929, 0x04f084d6, 0x4cd8282e, 0x4874acb2, 0x471f97e2],
+        [0x331919dd, 0x63ca60c9, 0x3c4d76f2, 0x221247ce, 0x01167d51, 0x24db4e3a,
+         0x778d5f68, 0x5ae85225, 0x1dc1f887, 0x1bf2732b, 0x4ffca871],
+        [0x27da9220, 0x4b6bf1ea, 0x0134f64d, 0x54871caf, 0x3d7ef653, 0x04e090a2,
+         0x4dc98518, 0x3c9cc39a, 0x6a10c2dc, 0x2d61e560, 0x3cbdb4ee],
+        [0x5cf66bcb, 0x45b168c8, 0x7362e311, 0x5554bcf1, 0x2f7df5fa, 0x36bd2dac,
+         0x4b971ce1, 0x51736343, 0x731e8a78, 0x4f09b9f6, 0x0dd9654c],
+        [0x3dff0151, 0x1842a165, 0x076e0963, 0x4d719b5c, 0x2955f9db, 0x4af858ff,
+         0x25e48ce0, 0x4e7b5e69, 0x3309f0fd, 0x1f7c8618, 0x05186026],
+        [0x73687101, 0x74ae858a, 0x558c51ec, 0x54bf5128, 0x1775beee, 0x11731ddb,
+         0x4dd507d3, 0x21b81b0a, 0x11839fea, 0x2eeee6e6, 0x4f4960c7],
+        [0x6512262b, 0x56f5f96e, 0x440a3b08, 0x7053a1f6, 0x06f7a705, 0x2d8f2699,
+         0x34d53862, 0x690e88cb, 0x2e46f281, 0x11908044, 0x3cb43fe0],
+        [0x1e02727b, 0x69cdc2f1, 0x5197719a, 0x21b81675, 0x0902b16a, 0x646e6c61,
+         0x4093e05a, 0x50106d09, 0x6f482dd5, 0x76df1315, 0x393e18d7],
+        [0x4cfa9ce8, 0x51a3efdd, 0x35ea1233, 0x0e11e107, 0x0258f739, 0x56793d47,
+         0x1d28ab94, 0x52e2dec1, 0x435105e9, 0x21518b05, 0x22d3b415],
+        [0x5f201c80, 0x0d843cea, 0x4b3fa675, 0x2111c4c9, 0x0517ee92, 0x6174898c,
+         0x748ed524, 0x29e3eaef, 0x1124aae7, 0x4415a7d5, 0x364031c4],
+        [0x5fafdc84, 0x50b0113d, 0x0f9bcc55, 0x5369518a, 0x4b48c63c, 0x437d0704,
+         0x156a3a92, 0x661d4065, 0x7530a387, 0x2a8a0e80, 0x70a9c0e1],
+        [0x01b37176, 0x543173b5, 0x24bd9b87, 0x6be35c32, 0x04274146, 0x2f1acab8,
+         0x48760795, 0x3d44607a, 0x569521a1, 0x424104d7, 0x2fd629be],
+        [0x5f31bd69, 0x723b2a30, 0x1f24889a, 0x1903f0fa, 0x3f12a99a, 0x32eaf11b,
+         0x455a5707, 0x0f4e12d6, 0x2a242e01, 0x3f11d3ac, 0x2ec6a5de],
+        [0x505765a3, 0x2fb9646e, 0x2ab53518, 0x4ff84dd1, 0x07fd9a37, 0x73154665,
+         0x4c34360b, 0x3c26e3df, 0x0b5992af, 0x4dbbcc79, 0x71287981],
+        [0x35a3c99d, 0x456e2777, 0x563e8fd2, 0x096e212b, 0x58f8445e, 0x677b3f25,
+         0x423f9141, 0x043e7259, 0x07d9510d, 0x63eec298, 0x6edfb792],
+        [0x37bc083f, 0x4f74ab61, 0x14df5487, 0x13a800b8, 0x77641ca0, 0x241902bc,
+         0x6d2c2180, 0x2a9ea173, 0x4d57e3fd, 0x09ebe082, 0x6195f37b],
+        [0x07dee53e, 0x2de60bc3, 0x04477a8b, 0x4835017d, 0x16291cfd, 0x098b79d8,
+         0x314fbc73, 0x2604f04d, 0x5802325a, 0x4dd976dc, 0x19ee158b],
+        [0x46e4019c, 0x6e2ec1aa, 0x58bcffd0, 0x003b763e, 0x4026d3c4, 0x29a63f89,
+         0x090ba0ad, 0x18a74178, 0x044a5821, 0x4c6d0335, 0x1819593e],
+    ];
+
+    // NB: This is in ROW-major order to support cache-friendly pre-multiplication.
+    const FAST_PARTIAL_ROUND_INITIAL_MATRIX: [[u64; 12 - 1]; 12 - 1] = [
+        [0x1f258b7f, 0x21bd621d, 0x07a6787a, 0x24940404, 0x4496fa9d, 0x40be5abf,
+         0x29cf6132, 0x178399f0, 0x0e2a096b, 0x00ba8148, 0x4b8d4aee],
+        [0x64d91ccc, 0x054e254b, 0x29df6780, 0x0007c0ae, 0x1e884380, 0x08e9ff0d,
+         0x1091b357, 0x10a4c2eb, 0x688aefbe, 0x4c488df6, 0x00ba8148],
+        [0x6c49b884, 0x1e8c8c26, 0x056064a1, 0x5ed2b6c6, 0x10dff1f4, 0x11332ca2,
+         0x62b7df46, 0x4aafcefb, 0x0796e0be, 0x688aefbe, 0x0e2a096b],
+        [0x5c904846, 0x29c8ac78, 0x5f26f0e8, 0x758c31c7, 0x1b441206, 0x515132a1,
+         0x0f8217bf, 0x340b2bb8, 0x4aafcefb, 0x10a4c2eb, 0x178399f0],
+        [0x167d0258, 0x453d7003, 0x621a0c4e, 0x1f5a8676, 0x35699a7a, 0x3d31b1af,
+         0x1267ae74, 0x0f8217bf, 0x62b7df46, 0x1091b357, 0x29cf6132],
+        [0x46a089e8, 0x0483cbe5, 0x49726d38, 0x65bde902, 0x1a94f233, 0x0f8e0a0b,
+         0x3d31b1af, 0x515132a1, 0x11332ca2, 0x08e9ff0d, 0x40be5abf],
+        [0x67b41d7d, 0x6f57a7f5, 0x102d55cc, 0x52c96f6f, 0x348693e3, 0x1a94f233,
+         0x35699a7a, 0x1b441206, 0x10dff1f4, 0x1e884380, 0x4496fa9d],
+        [0x625d02ed, 0x175a3ed3, 0x6e6e81c4, 0x5e359ff6, 0x52c96f6f, 0x65bde902,
+         0x1f5a8676, 0x758c31c7, 0x5ed2b6c6, 0x0007c0ae, 0x24940404],
+        [0x3a3ba96e, 0x72ef6fa2, 0x4975e6be, 0x6e6e81c4, 0x102d55cc, 0x49726d38,
+         0x621a0c4e, 0x5f26f0e8, 0x056064a1, 0x29df6780, 0x07a6787a],
+        [0x3214ff78, 0x61101bcc, 0x72ef6fa2, 0x175a3ed3, 0x6f57a7f5, 0x0483cbe5,
+         0x453d7003, 0x29c8ac78, 0x1e8c8c26, 0x054e254b, 0x21bd621d],
+        [0x2985fca0, 0x3214ff78, 0x3a3ba96e, 0x625d02ed, 0x67b41d7d, 0x46a089e8,
+         0x167d0258, 0x5c904846, 0x6c49b884, 0x64d91ccc, 0x1f258b7f],
+    ];
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(not(feature = "std"))]
+    use alloc::{vec, vec::Vec};
+
+    use crate::field::babybear_field::BabyBearField as F;
+    #[cfg(feature = "std")]
+    use crate::field::types::Field64;
+    use crate::field::types::{Field, PrimeField64};
+    use crate::hash::poseidon::test_helpers::{check_consistency, check_test_vectors};
+    #[cfg(feature = "std")]
+    use crate::hash::poseidon::{N_FULL_ROUNDS_TOTAL, N_PARTIAL_ROUNDS, SPONGE_WIDTH};
+
+    #[test]
+    fn test_vectors() {
+        // Test inputs are:
+        // 1. all zeros
+        // 2. range 0..WIDTH
+        // 3. all -1's
+        // 4. random elements of BabyBearField.
+        // expected output calculated with the naive (unoptimized) permutation.
+
+        let neg_one: u64 = F::NEG_ONE.to_canonical_u64();
+
+        #[rustfmt::skip]
+        let test_vectors12: Vec<([u64; 12], [u64; 12])> = vec![
+            ([0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, ],
+             [0x062d5b17, 0x18e757a9, 0x211f1503, 0x671a8b9f, 0x19c48fa9, 0x48ebcd40,
+              0x17e86fca, 0x5b6b9bc4, 0x614a90aa, 0x1c3b0668, 0x20d861c3, 0x4f894317, ]),
+            ([0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, ],
+             [0x38b533a4, 0x6b62684c, 0x48f3103a, 0x66825e9c, 0x66fe6994, 0x38e139a7,
+              0x0e83fa17, 0x365bbe60, 0x016d3961, 0x59ebda5b, 0x2947c186, 0x02253f33, ]),
+            ([neg_one, neg_one, neg_one, neg_one,
+              neg_one, neg_one, neg_one, neg_one,
+              neg_one, neg_one, neg_one, neg_one, ],
+             [0x6370e807, 0x1602a895, 0x6d854b37, 0x4a48933d, 0x4ee43d6c, 0x1ac2a61c,
+              0x21d16fa6, 0x28533984, 0x30b83035, 0x1794f88e, 0x30aae725, 0x39ea75be, ]),
+            ([0x1132d8fa, 0x48dbac25, 0x6c78b56f, 0x66b09f18, 0x61c35de2, 0x0813e268,
+              0x20a61a1e, 0x0f17f5c4, 0x3f6a6abd, 0x616737a2, 0x3988ec51, 0x3c728830, ],
+             [0x3940f223, 0x5cb0e856, 0x4888398d, 0x35d7d3b9, 0x25bdb1fb, 0x3f2563da,
+              0x5bb99ae7, 0x2f6451d4, 0x3644fdb4, 0x318b4f2a, 0x3809b8f9, 0x12945a94, ]),
+        ];
+
+        check_test_vectors::<F>(test_vectors12);
+    }
+
+    #[test]
+    fn consistency() {
+        check_consistency::<F>();
+    }
+
+    /// The smallest numbers of full and partial rounds of Poseidon over a prime field of order
+    /// `p`, with width `t` and S-box `x^alpha`, resisting the attacks considered by the round
+    /// number script of the Poseidon2 reference implementation at 128-bit security, with its
+    /// security margin added.
+    #[cfg(feature = "std")]
+    fn calc_round_numbers(p: u64, t: usize, alpha: u64) -> (usize, usize) {
+        const M: f64 = 128.0;
+        let log_p = (p as f64).log2();
+        let p_bits = (64 - p.leading_zeros()) as f64;
+        let (t_f, alpha_f) = (t as f64, alpha as f64);
+        let log_alpha = |x: f64| x.ln() / alpha_f.ln();
+        let log2_binomial = |n: usize, k: usize| {
+            (1..=k)
+                .map(|i| ((n - k + i) as f64 / i as f64).log2())
+                .sum::<f64>()
+        };
+
+        let secure = |r_f: usize, r_p: usize| {
+            let r_p_f = r_p as f64;
+            let statistical = if M <= (log_p - (alpha_f - 1.0) / 2.0).floor() * (t_f + 1.0) {
+                6.0
+            } else {
+                10.0
+            };
+            let interpolation =
+                1.0 + (log_alpha(2.0) * M.min(p_bits)).ceil() + log_alpha(t_f).ceil() - r_p_f;
+            let groebner_1 = log_alpha(2.0) * M.min(log_p) - r_p_f;
+            let groebner_2 =
+                t_f - 1.0 + log_alpha(2.0) * (M / (t_f + 1.0)).min(log_p / 2.0) - r_p_f;
+            let groebner_3 = (t_f - 2.0 + M / (2.0 * alpha_f.log2()) - r_p_f) / (t_f - 1.0);
+            let r_f_min = [
+                statistical,
+                interpolation,
+                groebner_1,
+                groebner_2,
+                groebner_3,
+            ]
+            .into_iter()
+            .map(f64::ceil)
+            .fold(f64::MIN, f64::max);
+
+            // The attack of https://eprint.iacr.org/2023/537.
+            let r = t / 3;
+            let over = (r_f - 1) * t + r_p + r + r * (r_f / 2) + r_p + alpha as usize;
+            let under = r * (r_f / 2) + r_p + alpha as usize;
+            let groebner_4 = (2.0 * log2_binomial(over, under)).ceil();
+
+            r_f as f64 >= r_f_min && groebner_4 >= M
+        };
+
+        (1..500)
+            .filter_map(|r_p| {
+                let r_f = (4..100).step_by(2).find(|&r_f| secure(r_f, r_p))?;
+                Some((r_f + 2, (r_p as f64 * 1.075).ceil() as usize))
+            })
+            .min_by_key(|&(r_f, r_p)| t * r_f + r_p)
+            .unwrap()
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn round_numbers() {
+        // Plonky3's round numbers for BabyBear.
+        assert_eq!(calc_round_numbers(F::ORDER, 16, 7), (8, 13));
+        assert_eq!(calc_round_numbers(F::ORDER, 24, 7), (8, 21));
+
+        let (r_f, r_p) = calc_round_numbers(F::ORDER, SPONGE_WIDTH, 7);
+        assert_eq!((r_f, r_p), (N_FULL_ROUNDS_TOTAL, 10));
+        assert!(r_p <= N_PARTIAL_ROUNDS);
+    }
+}
@@ -0,0 +1,228 @@
+use crate::field::extension::Extendable;
+use crate::hash::hash_types::{HashOut, HashOutTarget, RichField};
+use crate::hash::merkle_proofs::MerkleProofTarget;
+use crate::iop::target::BoolTarget;
+use crate::plonk::circuit_builder::CircuitBuilder;
+use crate::plonk::config::AlgebraicHasher;
+
+/// A change of the value stored under `key` in a sparse Merkle tree. The value before and after
+/// the change is `old_value` or `new_value` if the corresponding flag is set, and absent otherwise.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SparseMerkleUpdateTarget {
+    pub key: HashOutTarget,
+    pub old_is_present: BoolTarget,
+    pub old_value: HashOutTarget,
+    pub new_is_present: BoolTarget,
+    pub new_value: HashOutTarget,
+    pub proof: MerkleProofTarget,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
+    /// Adds the targets of an update to a sparse Merkle tree of the given depth.
+    pub fn add_virtual_sparse_merkle_update_target(
+        &mut self,
+        depth: usize,
+    ) -> SparseMerkleUpdateTarget {
+        SparseMerkleUpdateTarget {
+            key: self.add_virtual_hash(),
+            old_is_present: self.add_virtual_bool_target_safe(),
+            old_value: self.add_virtual_hash(),
+            new_is_present: self.add_virtual_bool_target_safe(),
+            new_value: self.add_virtual_hash(),
+            proof: MerkleProofTarget {
+                siblings: self.add_virtual_hashes(depth),
+            },
+        }
+    }
+
+    /// Returns the root of the sparse Merkle tree with `proof.siblings.len()` layers whose leaf for
+    /// `key` stores `value` if `is_present` is set, or is empty otherwise. This is the in-circuit
+    /// version of `sparse_merkle_root`.
+    pub fn sparse_merkle_root<H: AlgebraicHasher<F>>(
+        &mut self,
+        key: HashOutTarget,
+        is_present: BoolTarget,
+        value: HashOutTarget,
+        proof: &MerkleProofTarget,
+    ) -> HashOutTarget {
+        let depth = proof.siblings.len();
+        assert!(depth <= F::BITS, "Depth must be at most {}", F::BITS);
+        let index_bits = self.split_le_canonical(key.elements[0]);
+
+        let leaf_inputs = [key.elements, value.elements].concat();
+        let occupied_digest = self.hash_n_to_hash_no_pad::<H>(leaf_inputs);
+        let empty_digest = self.constant_hash(HashOut::ZERO);
+        let leaf_digest = self.select_hash(is_present, occupied_digest, empty_digest);
+
+        // A digest is four elements, so `hash_or_noop` leaves it unchanged.
+        *self
+            .merkle_proof_digests::<H>(leaf_digest.elements.to_vec(), &index_bits[..depth], proof)
+            .last()
+            .unwrap()
+    }
+
+    /// Verifies that `key` is stored with `value` in the sparse Merkle tree with the given root.
+    pub fn verify_sparse_merkle_membership<H: AlgebraicHasher<F>>(
+        &mut self,
+        key: HashOutTarget,
+        value: HashOutTarget,
+        root: HashOutTarget,
+        proof: &MerkleProofTarget,
+    ) {
+        let is_present = self._true();
+        let computed_root = self.sparse_merkle_root::<H>(key, is_present, value, proof);
+        self.connect_hashes(computed_root, root);
+    }
+
+    /// Verifies that `key` is absent from the sparse Merkle tree with the given root.
+    pub fn verify_sparse_merkle_non_membership<H: AlgebraicHasher<F>>(
+        &mut self,
+        key: HashOutTarget,
+        root: HashOutTarget,
+        proof: &MerkleProofTarget,
+    ) {
+        let is_present = self._false();
+        let value = self.constant_hash(HashOut::ZERO);
+        let computed_root = self.sparse_merkle_root::<H>(key, is_present, value, proof);
+        self.connect_hashes(computed_root, root);
+    }
+
+    /// Verifies that applying `updates` in order to the sparse Merkle tree with root `old_root`
+    /// yields the tree with root `new_root`.
+    pub fn verify_sparse_merkle_updates<H: AlgebraicHasher<F>>(
+        &mut self,
+        old_root: HashOutTarget,
+        new_root: HashOutTarget,
+        updates: &[SparseMerkleUpdateTarget],
+    ) {
+        let mut root = old_root;
+        for update in updates {
+            let before = self.sparse_merkle_root::<H>(
+                update.key,
+                update.old_is_present,
+                update.old_value,
+                &update.proof,
+            );
+            self.connect_hashes(before, root);
+            root = self.sparse_merkle_root::<H>(
+                update.key,
+                update.new_is_present,
+                update.new_value,
+                &update.proof,
+            );
+        }
+        self.connect_hashes(root, new_root);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use anyhow::Result;
+
+    use super::*;
+    use crate::field::types::Sample;
+    use crate::hash::sparse_merkle_tree::SparseMerkleTree;
+    use crate::iop::witness::{PartialWitness, WitnessWrite};
+    use crate::plonk::circuit_data::CircuitConfig;
+    use crate::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+    use crate::plonk::verifier::verify;
+
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+    type H = <C as GenericConfig<D>>::InnerHasher;
+
+    const DEPTH: usize = 32;
+
+    /// Proves that an insertion, a modification and a removal turn a tree with two keys into one
+    /// with root `new_root`, or the actual new root if `new_root` is `None`.
+    fn prove_updates(new_root: Option<HashOut<F>>) -> Result<()> {
+        let mut tree = SparseMerkleTree::<F, H>::new(DEPTH);
+        let keys = (0..3).map(|_| HashOut::rand()).collect::<Vec<_>>();
+        tree.insert(keys[0], HashOut::rand())?;
+        tree.insert(keys[1], HashOut::rand())?;
+        let old_root = tree.root();
+
+        let updates = [
+            tree.insert(keys[2], HashOut::rand())?,
+            tree.insert(keys[0], HashOut::rand())?,
+            tree.remove(keys[1])?,
+        ];
+        let new_root = new_root.unwrap_or(tree.root());
+
+        let mut builder = CircuitBuilder::<F, D>::new(CircuitConfig::standard_recursion_config());
+        let old_root_t = builder.add_virtual_hash();
+        let new_root_t = builder.add_virtual_hash();
+        let update_ts = (0..updates.len())
+            .map(|_| builder.add_virtual_sparse_merkle_update_target(DEPTH))
+            .collect::<Vec<_>>();
+        builder.verify_sparse_merkle_updates::<H>(old_root_t, new_root_t, &update_ts);
+        let data = builder.build::<C>();
+
+        let mut pw = PartialWitness::new();
+        pw.set_hash_target(old_root_t, old_root);
+        pw.set_hash_target(new_root_t, new_root);
+        for (update_t, update) in update_ts.iter().zip(&updates) {
+            pw.set_sparse_merkle_update_target(update_t, update);
+        }
+        let proof = data.prove(pw)?;
+
+        verify(proof, &data.verifier_only, &data.common)
+    }
+
+    #[test]
+    fn test_sparse_merkle_updates() -> Result<()> {
+        prove_updates(None)
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_sparse_merkle_updates_wrong_root() {
+        prove_updates(Some(HashOut::rand())).unwrap()
+    }
+
+    #[test]
+    fn test_sparse_merkle_membership() -> Result<()> {
+        let mut tree = SparseMerkleTree::<F, H>::new(DEPTH);
+        let (key, value) = (HashOut::rand(), HashOut::rand());
+        let absent_key = HashOut::rand();
+        tree.insert(key, value)?;
+        tree.insert(HashOut::rand(), HashOut::rand())?;
+
+        let mut builder = CircuitBuilder::<F, D>::new(CircuitConfig::standard_recursion_config());
+        let root_t = builder.add_virtual_hash();
+        let key_t = builder.add_virtual_hash();
+        let value_t = builder.add_virtual_hash();
+        let absent_key_t = builder.add_virtual_hash();
+        let proof_t = MerkleProofTarget {
+            siblings: builder.add_virtual_hashes(DEPTH),
+        };
+        let absent_proof_t = MerkleProofTarget {
+            siblings: builder.add_virtual_hashes(DEPTH),
+        };
+        builder.verify_sparse_merkle_membership::<H>(key_t, value_t, root_t, &proof_t);
+        builder.verify_sparse_merkle_non_membership::<H>(absent_key_t, root_t, &absent_proof_t);
+        let data = builder.build::<C>();
+
+        let mut pw = PartialWitness::new();
+        pw.set_hash_target(root_t, tree.root());
+        pw.set_hash_target(key_t, key);
+        pw.set_hash_target(value_t, value);
+        pw.set_hash_target(absent_key_t, absent_key);
+        for (&t, &h) in proof_t.siblings.iter().zip(&tree.prove(key).siblings) {
+            pw.set_hash_target(t, h);
+        }
+        for (&t, &h) in absent_proof_t
+            .siblings
+            .iter()
+            .zip(&tree.prove(absent_key).siblings)
+        {
+            pw.set_hash_target(t, h);
+        }
+        let proof = data.prove(pw)?;
+
+        verify(proof, &data.verifier_only, &data.common)
+    }
+}
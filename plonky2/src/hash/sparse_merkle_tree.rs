@@ -0,0 +1,338 @@
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+
+use anyhow::{ensure, Result};
+use hashbrown::HashMap;
+use serde::{Deserialize, Serialize};
+
+use crate::hash::hash_types::{HashOut, RichField, NUM_HASH_OUT_ELTS};
+use crate::hash::merkle_proofs::MerkleProof;
+use crate::plonk::config::Hasher;
+
+/// An updatable Merkle tree with `2^depth` leaves, most of which are empty, keyed by `HashOut`s.
+///
+/// A key is stored at the leaf indexed by the low `depth` bits of its first element. An occupied
+/// leaf's digest is the hash of its key and value, while an empty leaf's digest is zero. Only
+/// the digests of nonempty subtrees are stored; the digest of an empty subtree of each height is
+/// precomputed. Two keys whose indices collide cannot both be stored, so `depth` should be large
+/// enough to make collisions unlikely.
+#[derive(Clone, Debug)]
+pub struct SparseMerkleTree<F: RichField, H: Hasher<F, Hash = HashOut<F>>> {
+    depth: usize,
+
+    /// The key and value stored at each occupied leaf index.
+    leaves: HashMap<u64, (HashOut<F>, HashOut<F>)>,
+
+    /// The digests of nonempty subtrees, keyed by their height and index within their layer.
+    nodes: HashMap<(usize, u64), HashOut<F>>,
+
+    /// The digest of an empty subtree of each height, from `0` to `depth`.
+    empty_digests: Vec<HashOut<F>>,
+
+    _phantom: PhantomData<H>,
+}
+
+/// A change of the value stored under `key`, where `None` means the key is absent. The siblings of
+/// the key's leaf are the same before and after the change, so a single `MerkleProof` relates the
+/// old root to the new one.
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(bound = "")]
+pub struct SparseMerkleUpdate<F: RichField, H: Hasher<F>> {
+    pub key: HashOut<F>,
+    pub old_value: Option<HashOut<F>>,
+    pub new_value: Option<HashOut<F>>,
+    pub proof: MerkleProof<F, H>,
+}
+
+/// The digest of the leaf for `key`, given the value stored there, if any.
+pub fn sparse_merkle_leaf_digest<F: RichField, H: Hasher<F, Hash = HashOut<F>>>(
+    key: HashOut<F>,
+    value: Option<HashOut<F>>,
+) -> HashOut<F> {
+    match value {
+        Some(value) => {
+            let mut inputs = [F::ZERO; 2 * NUM_HASH_OUT_ELTS];
+            inputs[..NUM_HASH_OUT_ELTS].copy_from_slice(&key.elements);
+            inputs[NUM_HASH_OUT_ELTS..].copy_from_slice(&value.elements);
+            H::hash_no_pad(&inputs)
+        }
+        None => HashOut::ZERO,
+    }
+}
+
+/// The root of a sparse Merkle tree with `proof.len()` layers whose leaf for `key` stores `value`,
+/// or is empty if `value` is `None`.
+pub fn sparse_merkle_root<F: RichField, H: Hasher<F, Hash = HashOut<F>>>(
+    key: HashOut<F>,
+    value: Option<HashOut<F>>,
+    proof: &MerkleProof<F, H>,
+) -> HashOut<F> {
+    let mut index = key.elements[0].to_canonical_u64();
+    let mut digest = sparse_merkle_leaf_digest::<F, H>(key, value);
+    for &sibling in &proof.siblings {
+        digest = if index & 1 == 1 {
+            H::two_to_one(sibling, digest)
+        } else {
+            H::two_to_one(digest, sibling)
+        };
+        index >>= 1;
+    }
+    digest
+}
+
+/// Verifies that `key` is stored with `value` in the sparse Merkle tree with the given root, or
+/// that it is absent if `value` is `None`.
+pub fn verify_sparse_merkle_proof<F: RichField, H: Hasher<F, Hash = HashOut<F>>>(
+    key: HashOut<F>,
+    value: Option<HashOut<F>>,
+    root: HashOut<F>,
+    proof: &MerkleProof<F, H>,
+) -> Result<()> {
+    ensure!(
+        sparse_merkle_root(key, value, proof) == root,
+        "Invalid sparse Merkle proof."
+    );
+    Ok(())
+}
+
+impl<F: RichField, H: Hasher<F, Hash = HashOut<F>>> SparseMerkleUpdate<F, H> {
+    /// Verifies that this update turns the tree with root `old_root` into the one with root
+    /// `new_root`.
+    pub fn verify(&self, old_root: HashOut<F>, new_root: HashOut<F>) -> Result<()> {
+        verify_sparse_merkle_proof(self.key, self.old_value, old_root, &self.proof)?;
+        verify_sparse_merkle_proof(self.key, self.new_value, new_root, &self.proof)
+    }
+}
+
+impl<F: RichField, H: Hasher<F, Hash = HashOut<F>>> SparseMerkleTree<F, H> {
+    /// Creates an empty tree with `2^depth` leaves, where `depth` is at most `F::BITS`.
+    pub fn new(depth: usize) -> Self {
+        assert!(
+            (1..=F::BITS).contains(&depth),
+            "Depth must be between 1 and {}",
+            F::BITS
+        );
+
+        let mut empty_digests = Vec::with_capacity(depth + 1);
+        empty_digests.push(HashOut::ZERO);
+        for height in 0..depth {
+            let child = empty_digests[height];
+            empty_digests.push(H::two_to_one(child, child));
+        }
+
+        Self {
+            depth,
+            leaves: HashMap::new(),
+            nodes: HashMap::new(),
+            empty_digests,
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    pub fn len(&self) -> usize {
+        self.leaves.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.leaves.is_empty()
+    }
+
+    pub fn root(&self) -> HashOut<F> {
+        self.node(self.depth, 0)
+    }
+
+    /// The index of the leaf that `key` is stored at.
+    pub fn leaf_index(&self, key: HashOut<F>) -> u64 {
+        key.elements[0].to_canonical_u64() & (u64::MAX >> (64 - self.depth))
+    }
+
+    pub fn get(&self, key: HashOut<F>) -> Option<HashOut<F>> {
+        self.leaves
+            .get(&self.leaf_index(key))
+            .filter(|(k, _)| *k == key)
+            .map(|&(_, v)| v)
+    }
+
+    /// Creates a proof of the value stored under `key`, or of its absence, to be checked with
+    /// `verify_sparse_merkle_proof`.
+    pub fn prove(&self, key: HashOut<F>) -> MerkleProof<F, H> {
+        let index = self.leaf_index(key);
+        MerkleProof {
+            siblings: (0..self.depth)
+                .map(|height| self.node(height, (index >> height) ^ 1))
+                .collect(),
+        }
+    }
+
+    /// Stores `value` under `key`, replacing any previous value.
+    pub fn insert(
+        &mut self,
+        key: HashOut<F>,
+        value: HashOut<F>,
+    ) -> Result<SparseMerkleUpdate<F, H>> {
+        self.set(key, Some(value))
+    }
+
+    /// Removes `key` from the tree. Removing an absent key leaves the tree unchanged.
+    pub fn remove(&mut self, key: HashOut<F>) -> Result<SparseMerkleUpdate<F, H>> {
+        self.set(key, None)
+    }
+
+    fn node(&self, height: usize, index: u64) -> HashOut<F> {
+        self.nodes
+            .get(&(height, index))
+            .copied()
+            .unwrap_or(self.empty_digests[height])
+    }
+
+    fn set(
+        &mut self,
+        key: HashOut<F>,
+        value: Option<HashOut<F>>,
+    ) -> Result<SparseMerkleUpdate<F, H>> {
+        let mut index = self.leaf_index(key);
+        if let Some((existing_key, _)) = self.leaves.get(&index) {
+            ensure!(
+                *existing_key == key,
+                "Leaf {} is already occupied by another key",
+                index
+            );
+        }
+
+        let proof = self.prove(key);
+        let old_value = match value {
+            Some(value) => self.leaves.insert(index, (key, value)),
+            None => self.leaves.remove(&index),
+        }
+        .map(|(_, v)| v);
+
+        let mut digest = sparse_merkle_leaf_digest::<F, H>(key, value);
+        for (height, &sibling) in proof.siblings.iter().enumerate() {
+            self.set_node(height, index, digest);
+            digest = if index & 1 == 1 {
+                H::two_to_one(sibling, digest)
+            } else {
+                H::two_to_one(digest, sibling)
+            };
+            index >>= 1;
+        }
+        self.set_node(self.depth, 0, digest);
+
+        Ok(SparseMerkleUpdate {
+            key,
+            old_value,
+            new_value: value,
+            proof,
+        })
+    }
+
+    fn set_node(&mut self, height: usize, index: u64, digest: HashOut<F>) {
+        if digest == self.empty_digests[height] {
+            self.nodes.remove(&(height, index));
+        } else {
+            self.nodes.insert((height, index), digest);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::goldilocks_field::GoldilocksField;
+    use crate::field::types::{Field, Sample};
+    use crate::hash::poseidon::PoseidonHash;
+
+    type F = GoldilocksField;
+    type H = PoseidonHash;
+
+    #[test]
+    fn test_insert_update_remove() -> Result<()> {
+        let mut tree = SparseMerkleTree::<F, H>::new(32);
+        let empty_root = tree.root();
+        let keys = (0..10).map(|_| HashOut::<F>::rand()).collect::<Vec<_>>();
+
+        for &key in &keys {
+            verify_sparse_merkle_proof(key, None, tree.root(), &tree.prove(key))?;
+
+            let old_root = tree.root();
+            let value = HashOut::rand();
+            let update = tree.insert(key, value)?;
+            assert_eq!(update.old_value, None);
+            update.verify(old_root, tree.root())?;
+            assert_eq!(tree.get(key), Some(value));
+            verify_sparse_merkle_proof(key, Some(value), tree.root(), &tree.prove(key))?;
+        }
+
+        let old_root = tree.root();
+        let old_value = tree.get(keys[3]);
+        let new_value = HashOut::rand();
+        let update = tree.insert(keys[3], new_value)?;
+        assert_eq!(update.old_value, old_value);
+        update.verify(old_root, tree.root())?;
+        assert!(update.verify(tree.root(), old_root).is_err());
+
+        for &key in &keys {
+            let old_root = tree.root();
+            let update = tree.remove(key)?;
+            assert!(update.old_value.is_some());
+            update.verify(old_root, tree.root())?;
+            assert_eq!(tree.get(key), None);
+        }
+        assert!(tree.is_empty());
+        assert_eq!(tree.root(), empty_root);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_root_independent_of_order() -> Result<()> {
+        let entries = (0..8)
+            .map(|_| (HashOut::<F>::rand(), HashOut::<F>::rand()))
+            .collect::<Vec<_>>();
+
+        let mut forward = SparseMerkleTree::<F, H>::new(40);
+        let mut backward = SparseMerkleTree::<F, H>::new(40);
+        for &(key, value) in &entries {
+            forward.insert(key, value)?;
+        }
+        for &(key, value) in entries.iter().rev() {
+            backward.insert(key, value)?;
+        }
+        assert_eq!(forward.root(), backward.root());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_wrong_proofs() -> Result<()> {
+        let mut tree = SparseMerkleTree::<F, H>::new(16);
+        let (key, value) = (HashOut::<F>::rand(), HashOut::<F>::rand());
+        tree.insert(key, value)?;
+        let proof = tree.prove(key);
+
+        assert!(verify_sparse_merkle_proof(key, None, tree.root(), &proof).is_err());
+        assert!(
+            verify_sparse_merkle_proof(key, Some(HashOut::rand()), tree.root(), &proof).is_err()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_colliding_keys() -> Result<()> {
+        let mut tree = SparseMerkleTree::<F, H>::new(8);
+        let key = HashOut::from_partial(&[F::from_canonical_u64(0x1234)]);
+        let colliding_key = HashOut::from_partial(&[F::from_canonical_u64(0x5634)]);
+        tree.insert(key, HashOut::rand())?;
+
+        assert!(tree.insert(colliding_key, HashOut::rand()).is_err());
+        assert!(tree.remove(colliding_key).is_err());
+        assert_eq!(tree.get(colliding_key), None);
+
+        Ok(())
+    }
+}
@@ -2,8 +2,18 @@ use alloc::boxed::Box;
 use alloc::string::{String, ToString};
 use alloc::vec;
 use alloc::vec::Vec;
+use core::any::Any;
 use core::fmt::Debug;
 use core::marker::PhantomData;
+use core::time::Duration;
+
+#[cfg(feature = "timing")]
+use hashbrown::HashMap;
+#[cfg(feature = "timing")]
+use log::Level;
+use plonky2_maybe_rayon::*;
+#[cfg(feature = "timing")]
+use web_time::Instant;
 
 use crate::field::extension::Extendable;
 use crate::field::types::Field;
@@ -15,6 +25,7 @@ use crate::iop::witness::{PartialWitness, PartitionWitness, Witness, WitnessWrit
 use crate::plonk::circuit_data::{CommonCircuitData, ProverOnlyCircuitData};
 use crate::plonk::config::GenericConfig;
 use crate::util::serialization::{Buffer, IoResult, Read, Write};
+use crate::util::timing::TimingTree;
 
 /// Given a `PartitionWitness` that has only inputs set, populates the rest of the witness using the
 /// given set of generators.
@@ -27,6 +38,65 @@ pub fn generate_partial_witness<
     inputs: PartialWitness<F>,
     prover_data: &'a ProverOnlyCircuitData<F, C, D>,
     common_data: &'a CommonCircuitData<F, D>,
+) -> PartitionWitness<'a, F> {
+    generate_partial_witness_with_timing(
+        inputs,
+        prover_data,
+        common_data,
+        &mut TimingTree::default(),
+    )
+}
+
+/// The number of generators run in parallel before their outputs are merged into the witness.
+const GENERATOR_CHUNK_SIZE: usize = 1 << 12;
+
+/// Like `generate_partial_witness`, but also adds the time spent running each kind of generator,
+/// keyed by `WitnessGenerator::id`, to `timing`. Since generators run in parallel, these times
+/// can add up to more than the wall-clock time.
+///
+/// Generators are run in wavefronts, which are discovered while the witness is being populated
+/// rather than computed ahead of time from the generators' watch lists: a watch list only gives
+/// a generator's inputs, not the targets it populates, and a generator may need several runs, so
+/// the watch lists alone don't determine which level a generator belongs to. The first wavefront
+/// contains every generator, and each following one contains the unfinished generators watching
+/// a target populated by the previous one, which is the level a precomputed schedule would
+/// assign them. A wavefront is run in chunks of
+/// `GENERATOR_CHUNK_SIZE` generators: the generators of a chunk run in parallel against the same
+/// witness, and their outputs are then merged in the order of the generators before the next
+/// chunk runs, so the resulting witness doesn't depend on scheduling, and at most one chunk's
+/// outputs are buffered at a time.
+pub fn generate_partial_witness_with_timing<
+    'a,
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    const D: usize,
+>(
+    inputs: PartialWitness<F>,
+    prover_data: &'a ProverOnlyCircuitData<F, C, D>,
+    common_data: &'a CommonCircuitData<F, D>,
+    timing: &mut TimingTree,
+) -> PartitionWitness<'a, F> {
+    generate_partial_witness_in_chunks(
+        inputs,
+        prover_data,
+        common_data,
+        timing,
+        GENERATOR_CHUNK_SIZE,
+    )
+}
+
+/// Like `generate_partial_witness_with_timing`, but runs `chunk_size` generators at a time.
+fn generate_partial_witness_in_chunks<
+    'a,
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    const D: usize,
+>(
+    inputs: PartialWitness<F>,
+    prover_data: &'a ProverOnlyCircuitData<F, C, D>,
+    common_data: &'a CommonCircuitData<F, D>,
+    timing: &mut TimingTree,
+    chunk_size: usize,
 ) -> PartitionWitness<'a, F> {
     let config = &common_data.config;
     let generators = &prover_data.generators;
@@ -42,51 +112,86 @@ pub fn generate_partial_witness<
         witness.set_target(t, v);
     }
 
-    // Build a list of "pending" generators which are queued to be run. Initially, all generators
-    // are queued.
+    // The generators of the current wavefront. Initially, all generators are queued.
     let mut pending_generator_indices: Vec<_> = (0..generators.len()).collect();
 
-    // We also track a list of "expired" generators which have already returned false.
+    // We also track a list of "expired" generators which have already returned true.
     let mut generator_is_expired = vec![false; generators.len()];
     let mut remaining_generators = generators.len();
 
-    let mut buffer = GeneratedValues::empty();
+    // The state of each unfinished generator, see `WitnessGenerator::run_with_state`.
+    let mut generator_states = (0..generators.len())
+        .map(|_| None)
+        .collect::<Vec<GeneratorState>>();
+
+    // The total time spent running each generator.
+    #[cfg(feature = "timing")]
+    let mut generator_durations = vec![Duration::ZERO; generators.len()];
 
     // Keep running generators until we fail to make progress.
     while !pending_generator_indices.is_empty() {
         let mut next_pending_generator_indices = Vec::new();
+        for chunk in pending_generator_indices.chunks(chunk_size) {
+            // A wavefront has no duplicates, so each state is taken by a single generator.
+            let outputs = chunk
+                .iter()
+                .map(|&generator_idx| core::mem::take(&mut generator_states[generator_idx]))
+                .collect::<Vec<_>>()
+                .into_par_iter()
+                .zip(chunk.par_iter())
+                .map(|(mut state, &generator_idx)| {
+                    let mut buffer = GeneratedValues::empty();
+                    let (finished, duration) = run_generator(
+                        &*generators[generator_idx].0,
+                        &witness,
+                        &mut buffer,
+                        &mut state,
+                    );
+                    (finished, duration, buffer, state)
+                })
+                .collect::<Vec<_>>();
+
+            for (&generator_idx, (finished, duration, buffer, state)) in chunk.iter().zip(outputs) {
+                #[cfg(feature = "timing")]
+                {
+                    generator_durations[generator_idx] += duration;
+                }
+                #[cfg(not(feature = "timing"))]
+                let _ = duration;
+                if finished {
+                    generator_is_expired[generator_idx] = true;
+                    remaining_generators -= 1;
+                } else {
+                    generator_states[generator_idx] = state;
+                }
 
-        for &generator_idx in &pending_generator_indices {
-            if generator_is_expired[generator_idx] {
-                continue;
-            }
-
-            let finished = generators[generator_idx].0.run(&witness, &mut buffer);
-            if finished {
-                generator_is_expired[generator_idx] = true;
-                remaining_generators -= 1;
-            }
-
-            // Merge any generated values into our witness, and get a list of newly-populated
-            // targets' representatives.
-            let new_target_reps = buffer
-                .target_values
-                .drain(..)
-                .flat_map(|(t, v)| witness.set_target_returning_rep(t, v));
-
-            // Enqueue unfinished generators that were watching one of the newly populated targets.
-            for watch in new_target_reps {
-                let opt_watchers = generator_indices_by_watches.get(&watch);
-                if let Some(watchers) = opt_watchers {
-                    for &watching_generator_idx in watchers {
-                        if !generator_is_expired[watching_generator_idx] {
-                            next_pending_generator_indices.push(watching_generator_idx);
+                // Merge any generated values into our witness, and get a list of newly-populated
+                // targets' representatives.
+                let new_target_reps = buffer
+                    .target_values
+                    .into_iter()
+                    .flat_map(|(t, v)| witness.set_target_returning_rep(t, v));
+
+                // Enqueue unfinished generators that were watching one of the newly populated
+                // targets.
+                for watch in new_target_reps {
+                    let opt_watchers = generator_indices_by_watches.get(&watch);
+                    if let Some(watchers) = opt_watchers {
+                        for &watching_generator_idx in watchers {
+                            if !generator_is_expired[watching_generator_idx] {
+                                next_pending_generator_indices.push(watching_generator_idx);
+                            }
                         }
                     }
                 }
             }
         }
 
+        // A generator which was finished by this wavefront may have been enqueued before finishing.
+        next_pending_generator_indices
+            .retain(|&generator_idx| !generator_is_expired[generator_idx]);
+        next_pending_generator_indices.sort_unstable();
+        next_pending_generator_indices.dedup();
         pending_generator_indices = next_pending_generator_indices;
     }
 
@@ -96,9 +201,46 @@ pub fn generate_partial_witness<
         remaining_generators,
     );
 
+    #[cfg(feature = "timing")]
+    {
+        let mut durations_by_id = HashMap::<String, (usize, Duration)>::new();
+        for (generator, &duration) in generators.iter().zip(&generator_durations) {
+            let (count, total) = durations_by_id.entry(generator.0.id()).or_default();
+            *count += 1;
+            *total += duration;
+        }
+        let mut durations_by_id = durations_by_id.into_iter().collect::<Vec<_>>();
+        durations_by_id.sort_unstable_by(|(_, (_, a)), (_, (_, b))| b.cmp(a));
+        for (id, (count, duration)) in durations_by_id {
+            timing.add_duration(&format!("{count} {id}s"), Level::Debug, duration);
+        }
+    }
+    #[cfg(not(feature = "timing"))]
+    let _ = timing;
+
     witness
 }
 
+/// Runs `generator`, returning whether it is finished and how long it took.
+fn run_generator<F: RichField + Extendable<D>, const D: usize>(
+    generator: &dyn WitnessGenerator<F, D>,
+    witness: &PartitionWitness<F>,
+    out_buffer: &mut GeneratedValues<F>,
+    state: &mut GeneratorState,
+) -> (bool, Duration) {
+    #[cfg(feature = "timing")]
+    {
+        let start = Instant::now();
+        let finished = generator.run_with_state(witness, out_buffer, state);
+        (finished, start.elapsed())
+    }
+    #[cfg(not(feature = "timing"))]
+    (
+        generator.run_with_state(witness, out_buffer, state),
+        Duration::ZERO,
+    )
+}
+
 /// A generator participates in the generation of the witness.
 pub trait WitnessGenerator<F: RichField + Extendable<D>, const D: usize>:
     'static + Send + Sync + Debug
@@ -114,6 +256,19 @@ pub trait WitnessGenerator<F: RichField + Extendable<D>, const D: usize>:
     /// run next time a target in its watch list is populated.
     fn run(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) -> bool;
 
+    /// Like `run`, but with a state which is kept from one run of this generator to the next
+    /// during a witness generation, and dropped at its end. The state is `None` before the first
+    /// run. Witness generation calls this rather than `run`, so generators which would otherwise
+    /// redo the work of their previous runs can save their progress here.
+    fn run_with_state(
+        &self,
+        witness: &PartitionWitness<F>,
+        out_buffer: &mut GeneratedValues<F>,
+        _state: &mut GeneratorState,
+    ) -> bool {
+        self.run(witness, out_buffer)
+    }
+
     fn serialize(&self, dst: &mut Vec<u8>, common_data: &CommonCircuitData<F, D>) -> IoResult<()>;
 
     fn deserialize(src: &mut Buffer, common_data: &CommonCircuitData<F, D>) -> IoResult<Self>
@@ -121,6 +276,9 @@ pub trait WitnessGenerator<F: RichField + Extendable<D>, const D: usize>:
         Self: Sized;
 }
 
+/// The state of a generator during a witness generation, see `WitnessGenerator::run_with_state`.
+pub type GeneratorState = Option<Box<dyn Any + Send>>;
+
 /// A wrapper around an `Box<WitnessGenerator>` which implements `PartialEq`
 /// and `Eq` based on generator IDs.
 pub struct WitnessGeneratorRef<F: RichField + Extendable<D>, const D: usize>(
@@ -415,3 +573,117 @@ impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F, D> for Con
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::types::Sample;
+    use crate::hash::poseidon::PoseidonHash;
+    use crate::plonk::circuit_builder::CircuitBuilder;
+    use crate::plonk::circuit_data::CircuitConfig;
+    use crate::plonk::config::PoseidonGoldilocksConfig;
+
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+
+    /// Runs generators one at a time, merging each one's outputs before running the next.
+    fn generate_partial_witness_sequentially<'a>(
+        inputs: PartialWitness<F>,
+        prover_data: &'a ProverOnlyCircuitData<F, C, D>,
+        common_data: &'a CommonCircuitData<F, D>,
+    ) -> PartitionWitness<'a, F> {
+        let mut witness = PartitionWitness::new(
+            common_data.config.num_wires,
+            common_data.degree(),
+            &prover_data.representative_map,
+        );
+        for (t, v) in inputs.target_values {
+            witness.set_target(t, v);
+        }
+
+        let generators = &prover_data.generators;
+        let mut pending = (0..generators.len()).collect::<Vec<_>>();
+        let mut expired = vec![false; generators.len()];
+        let mut states = (0..generators.len())
+            .map(|_| None)
+            .collect::<Vec<GeneratorState>>();
+        while let Some(generator_idx) = pending.pop() {
+            if expired[generator_idx] {
+                continue;
+            }
+            let mut buffer = GeneratedValues::empty();
+            expired[generator_idx] = generators[generator_idx].0.run_with_state(
+                &witness,
+                &mut buffer,
+                &mut states[generator_idx],
+            );
+            for (t, v) in buffer.target_values {
+                if let Some(rep) = witness.set_target_returning_rep(t, v) {
+                    let watchers = prover_data.generator_indices_by_watches.get(&rep);
+                    pending.extend(watchers.into_iter().flatten());
+                }
+            }
+        }
+        assert!(expired.into_iter().all(|e| e));
+
+        witness
+    }
+
+    #[test]
+    fn test_parallel_witness_generation() {
+        let mut builder = CircuitBuilder::<F, D>::new(CircuitConfig::standard_recursion_config());
+        let inputs = builder.add_virtual_targets(4);
+
+        // A long chain of dependent operations, next to independent ones.
+        let mut acc = inputs[0];
+        for &input in inputs.iter().cycle().take(200) {
+            acc = builder.mul_add(acc, input, inputs[1]);
+            let hash = builder.hash_n_to_hash_no_pad::<PoseidonHash>(vec![input, acc]);
+            let bits = builder.split_le(hash.elements[0], 64);
+            let index = builder.le_sum(bits[..2].iter());
+            let element = builder.random_access(index, inputs.clone());
+            acc = builder.add(acc, element);
+        }
+        builder.register_public_input(acc);
+        let data = builder.build::<C>();
+
+        let mut pw = PartialWitness::new();
+        for &input in &inputs {
+            pw.set_target(input, F::rand());
+        }
+        let mut sequential =
+            generate_partial_witness_sequentially(pw.clone(), &data.prover_only, &data.common);
+        // Random values differ between runs, so ignore them.
+        let random_reps = data
+            .prover_only
+            .generators
+            .iter()
+            .filter(|generator| generator.0.id() == "RandomValueGenerator")
+            .map(|generator| {
+                let mut bytes = Vec::new();
+                generator.0.serialize(&mut bytes, &data.common).unwrap();
+                let target = Buffer::new(&bytes).read_target().unwrap();
+                sequential.representative_map[sequential.target_index(target)]
+            })
+            .collect::<Vec<_>>();
+        for &rep in &random_reps {
+            sequential.values[rep] = None;
+        }
+
+        // Small chunks make later chunks of a wavefront depend on earlier ones.
+        for chunk_size in [1, 7, GENERATOR_CHUNK_SIZE] {
+            let mut parallel = generate_partial_witness_in_chunks(
+                pw.clone(),
+                &data.prover_only,
+                &data.common,
+                &mut TimingTree::default(),
+                chunk_size,
+            );
+            for &rep in &random_reps {
+                parallel.values[rep] = None;
+            }
+            assert_eq!(parallel.values, sequential.values);
+        }
+    }
+}
@@ -10,6 +10,8 @@ use crate::fri::structure::{FriOpenings, FriOpeningsTarget};
 use crate::fri::witness_util::set_fri_proof_target;
 use crate::hash::hash_types::{HashOut, HashOutTarget, MerkleCapTarget, RichField};
 use crate::hash::merkle_tree::MerkleCap;
+use crate::hash::sparse_merkle_proofs::SparseMerkleUpdateTarget;
+use crate::hash::sparse_merkle_tree::SparseMerkleUpdate;
 use crate::iop::ext_target::ExtensionTarget;
 use crate::iop::target::{BoolTarget, Target};
 use crate::iop::wire::Wire;
@@ -39,6 +41,23 @@ pub trait WitnessWrite<F: Field> {
         }
     }
 
+    fn set_sparse_merkle_update_target<H: Hasher<F, Hash = HashOut<F>>>(
+        &mut self,
+        target: &SparseMerkleUpdateTarget,
+        update: &SparseMerkleUpdate<F, H>,
+    ) where
+        F: RichField,
+    {
+        self.set_hash_target(target.key, update.key);
+        self.set_bool_target(target.old_is_present, update.old_value.is_some());
+        self.set_hash_target(target.old_value, update.old_value.unwrap_or(HashOut::ZERO));
+        self.set_bool_target(target.new_is_present, update.new_value.is_some());
+        self.set_hash_target(target.new_value, update.new_value.unwrap_or(HashOut::ZERO));
+        for (&t, &h) in target.proof.siblings.iter().zip(&update.proof.siblings) {
+            self.set_hash_target(t, h);
+        }
+    }
+
     fn set_extension_target<const D: usize>(&mut self, et: ExtensionTarget<D>, value: F::Extension)
     where
         F: RichField + Extendable<D>,
@@ -15,6 +15,7 @@ pub mod hash;
 pub mod iop;
 pub mod plonk;
 pub mod recursion;
+pub mod sumcheck;
 pub mod util;
 
 #[cfg(test)]
@@ -12,9 +12,11 @@ use crate::gates::lookup_table::LookupTable;
 use crate::gates::noop::NoopGate;
 use crate::iop::witness::{PartialWitness, WitnessWrite};
 use crate::plonk::circuit_builder::CircuitBuilder;
-use crate::plonk::circuit_data::CircuitConfig;
+use crate::plonk::circuit_data::{CircuitConfig, CircuitData};
 use crate::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
 use crate::plonk::prover::prove;
+use crate::util::serialization::envelope::EnvelopeHeader;
+use crate::util::serialization::{DefaultGateSerializer, DefaultGeneratorSerializer};
 use crate::util::timing::TimingTree;
 
 const D: usize = 2;
@@ -89,8 +91,8 @@ fn test_one_lookup() -> anyhow::Result<()> {
     let look_val_a = 1;
     let look_val_b = 2;
 
-    let out_a = table[look_val_a].1;
-    let out_b = table[look_val_b].1;
+    let out_a = table.row(look_val_a)[1];
+    let out_b = table.row(look_val_b)[1];
     let table_index = builder.add_lookup_table_from_pairs(table);
     let output_a = builder.add_lookup_from_index(initial_a, table_index);
 
@@ -113,12 +115,12 @@ fn test_one_lookup() -> anyhow::Result<()> {
     data.verify(proof.clone())?;
 
     assert!(
-        proof.public_inputs[2] == F::from_canonical_u16(out_a),
+        proof.public_inputs[2] == F::from_canonical_u64(out_a),
         "First lookup, at index {} in the Tip5 table gives an incorrect output.",
         proof.public_inputs[0]
     );
     assert!(
-        proof.public_inputs[3] == F::from_canonical_u16(out_b),
+        proof.public_inputs[3] == F::from_canonical_u64(out_b),
         "Second lookup, at index {} in the Tip5 table gives an incorrect output.",
         proof.public_inputs[1]
     );
@@ -239,10 +241,10 @@ fn test_different_inputs() -> anyhow::Result<()> {
 
     let mut pw = PartialWitness::new();
 
-    let look_val_a = table[init_a].0;
-    let look_val_b = table[init_b].0;
-    pw.set_target(initial_a, F::from_canonical_u16(look_val_a));
-    pw.set_target(initial_b, F::from_canonical_u16(look_val_b));
+    let look_val_a = table.row(init_a)[0];
+    let look_val_b = table.row(init_b)[0];
+    pw.set_target(initial_a, F::from_canonical_u64(look_val_a));
+    pw.set_target(initial_b, F::from_canonical_u64(look_val_b));
 
     let data = builder.build::<C>();
     let mut timing = TimingTree::new("prove different lookups", Level::Debug);
@@ -250,27 +252,27 @@ fn test_different_inputs() -> anyhow::Result<()> {
     data.verify(proof.clone())?;
     timing.print();
 
-    let out_a = table[init_a].1;
-    let out_b = table[init_b].1;
+    let out_a = table.row(init_a)[1];
+    let out_b = table.row(init_b)[1];
     let s = out_a + out_b;
-    let out_final = table2[s as usize].1;
+    let out_final = table2.row(s as usize)[1];
 
     assert!(
-        proof.public_inputs[3] == F::from_canonical_u16(out_a),
+        proof.public_inputs[3] == F::from_canonical_u64(out_a),
         "First lookup, at index {} in the smaller LUT gives an incorrect output.",
         proof.public_inputs[0]
     );
     assert!(
-        proof.public_inputs[4] == F::from_canonical_u16(out_b),
+        proof.public_inputs[4] == F::from_canonical_u64(out_b),
         "Second lookup, at index {} in the smaller LUT gives an incorrect output.",
         proof.public_inputs[1]
     );
     assert!(
-        proof.public_inputs[2] == F::from_canonical_u16(s),
+        proof.public_inputs[2] == F::from_canonical_u64(s),
         "Sum between the first two LUT outputs is incorrect."
     );
     assert!(
-        proof.public_inputs[5] == F::from_canonical_u16(out_final),
+        proof.public_inputs[5] == F::from_canonical_u64(out_final),
         "Output of the second LUT at index {} is incorrect.",
         s
     );
@@ -295,8 +297,8 @@ fn test_many_lookups() -> anyhow::Result<()> {
     let tip5_table = TIP5_TABLE.to_vec();
     let table: LookupTable = Arc::new((0..256).zip_eq(tip5_table).collect());
 
-    let out_a = table[look_val_a].1;
-    let out_b = table[look_val_b].1;
+    let out_a = table.row(look_val_a)[1];
+    let out_b = table.row(look_val_b)[1];
 
     let tip5_index = builder.add_lookup_table_from_pairs(table);
     let output_a = builder.add_lookup_from_index(initial_a, tip5_index);
@@ -313,7 +315,7 @@ fn test_many_lookups() -> anyhow::Result<()> {
     let table2: LookupTable = Arc::new((0..256).zip_eq(other_table).collect());
 
     let s = out_a + out_b;
-    let out_final = table2[s as usize].1;
+    let out_final = table2.row(s as usize)[1];
 
     let other_index = builder.add_lookup_table_from_pairs(table2);
     let output_final = builder.add_lookup_from_index(sum, other_index);
@@ -338,21 +340,21 @@ fn test_many_lookups() -> anyhow::Result<()> {
     timing.print();
 
     assert!(
-        proof.public_inputs[3] == F::from_canonical_u16(out_a),
+        proof.public_inputs[3] == F::from_canonical_u64(out_a),
         "First lookup, at index {} in the Tip5 table gives an incorrect output.",
         proof.public_inputs[0]
     );
     assert!(
-        proof.public_inputs[4] == F::from_canonical_u16(out_b),
+        proof.public_inputs[4] == F::from_canonical_u64(out_b),
         "Second lookup, at index {} in the Tip5 table gives an incorrect output.",
         proof.public_inputs[1]
     );
     assert!(
-        proof.public_inputs[2] == F::from_canonical_u16(s),
+        proof.public_inputs[2] == F::from_canonical_u64(s),
         "Sum between the first two LUT outputs is incorrect."
     );
     assert!(
-        proof.public_inputs[5] == F::from_canonical_u16(out_final),
+        proof.public_inputs[5] == F::from_canonical_u64(out_final),
         "Output of the second LUT at index {} is incorrect.",
         s
     );
@@ -510,6 +512,157 @@ fn test_many_lookups_on_big_lut() -> anyhow::Result<()> {
     data.verify(proof)
 }
 
+// Tests lookups in a table with two inputs, mixed with lookups in narrower tables.
+#[test]
+fn test_multi_input_lut() -> anyhow::Result<()> {
+    init_logger();
+
+    let config = CircuitConfig::standard_recursion_config();
+    let mut builder = CircuitBuilder::<F, D>::new(config);
+
+    // (a, b) -> a xor b over 8-bit limbs.
+    let xor_rows = (0..256u64)
+        .cartesian_product(0..256u64)
+        .map(|(a, b)| {
+            vec![a, b, a ^ b]
+                .into_iter()
+                .map(F::from_canonical_u64)
+                .collect()
+        })
+        .collect::<Vec<_>>();
+    let xor_index = builder.add_lookup_table_from_rows(2, 1, &xor_rows);
+    let tip5_table = TIP5_TABLE.to_vec();
+    let tip5_index =
+        builder.add_lookup_table_from_pairs(Arc::new((0..256).zip_eq(tip5_table).collect()));
+
+    let a = builder.add_virtual_target();
+    let b = builder.add_virtual_target();
+    let a_xor_b = builder.add_lookup(&[a, b], xor_index)[0];
+    let tip5_out = builder.add_lookup_from_index(a_xor_b, tip5_index);
+    let b_xor_b = builder.add_lookup(&[b, b], xor_index)[0];
+
+    builder.register_public_input(a_xor_b);
+    builder.register_public_input(tip5_out);
+    builder.register_public_input(b_xor_b);
+
+    let data = builder.build::<C>();
+
+    let mut pw = PartialWitness::new();
+    let (a_val, b_val) = (0xa5, 0x3c);
+    pw.set_target(a, F::from_canonical_u64(a_val));
+    pw.set_target(b, F::from_canonical_u64(b_val));
+
+    let proof = data.prove(pw)?;
+    assert_eq!(proof.public_inputs[0], F::from_canonical_u64(a_val ^ b_val));
+    assert_eq!(
+        proof.public_inputs[1],
+        F::from_canonical_u16(TIP5_TABLE[(a_val ^ b_val) as usize])
+    );
+    assert_eq!(proof.public_inputs[2], F::ZERO);
+
+    data.verify(proof)
+}
+
+// Tests a table over arbitrary field elements, together with a range check table without outputs.
+#[test]
+fn test_field_element_and_range_check_luts() -> anyhow::Result<()> {
+    init_logger();
+
+    let config = CircuitConfig::standard_recursion_config();
+    let mut builder = CircuitBuilder::<F, D>::new(config);
+
+    let inputs = [F::NEG_ONE, F::from_canonical_u64(1 << 40), F::TWO];
+    let cube_rows = inputs
+        .iter()
+        .map(|&x| vec![x, x.cube()])
+        .collect::<Vec<_>>();
+    let cube_index = builder.add_lookup_table_from_rows(1, 1, &cube_rows);
+    let byte_values = (0..256).map(F::from_canonical_u64).collect::<Vec<_>>();
+    let byte_index = builder.add_lookup_table_from_values(&byte_values);
+
+    let x = builder.add_virtual_target();
+    let x_cubed = builder.add_lookup_from_index(x, cube_index);
+    let y = builder.add_virtual_target();
+    assert!(builder.add_lookup(&[y], byte_index).is_empty());
+    builder.register_public_input(x_cubed);
+
+    let data = builder.build::<C>();
+
+    let mut pw = PartialWitness::new();
+    pw.set_target(x, inputs[1]);
+    pw.set_target(y, F::from_canonical_u64(255));
+
+    let proof = data.prove(pw)?;
+    assert_eq!(proof.public_inputs[0], inputs[1].cube());
+
+    data.verify(proof)
+}
+
+#[should_panic]
+#[test]
+fn test_range_check_lut_out_of_range() {
+    init_logger();
+
+    let config = CircuitConfig::standard_recursion_config();
+    let mut builder = CircuitBuilder::<F, D>::new(config);
+
+    let byte_values = (0..256).map(F::from_canonical_u64).collect::<Vec<_>>();
+    let byte_index = builder.add_lookup_table_from_values(&byte_values);
+    let y = builder.add_virtual_target();
+    builder.add_lookup(&[y], byte_index);
+
+    let data = builder.build::<C>();
+
+    let mut pw = PartialWitness::new();
+    pw.set_target(y, F::from_canonical_u64(256));
+
+    data.prove(pw).unwrap();
+}
+
+// Tests a circuit with a lookup serialized before lookup tables could have any width.
+#[test]
+fn test_legacy_lookup_circuit() -> anyhow::Result<()> {
+    init_logger();
+
+    // The circuit looks up its public input in the table of squares of `0..16`, and makes the
+    // result public too.
+    let bytes = include_bytes!("util/serialization/fixtures/legacy_lookup_circuit.bin");
+    let generator_serializer = DefaultGeneratorSerializer::<C, D> {
+        _phantom: Default::default(),
+    };
+    assert_eq!(EnvelopeHeader::read(bytes).unwrap(), None);
+    let data =
+        CircuitData::<F, C, D>::from_bytes(bytes, &DefaultGateSerializer, &generator_serializer)
+            .unwrap();
+    assert_eq!(data.common.luts.len(), 1);
+    assert_eq!(data.common.luts[0].num_inputs(), 1);
+    assert_eq!(data.common.luts[0].num_outputs(), 1);
+    assert_eq!(data.common.luts[0].row(3), &[3, 9]);
+
+    let mut pw = PartialWitness::new();
+    pw.set_target(
+        data.prover_only.public_inputs[0],
+        F::from_canonical_usize(3),
+    );
+    let proof = data.prove(pw)?;
+    assert_eq!(
+        proof.public_inputs,
+        vec![F::from_canonical_usize(3), F::from_canonical_usize(9)]
+    );
+    data.verify(proof)?;
+
+    // Once written again, it is in the current layout.
+    let bytes = data
+        .to_bytes(&DefaultGateSerializer, &generator_serializer)
+        .unwrap();
+    assert_eq!(
+        CircuitData::from_bytes(&bytes, &DefaultGateSerializer, &generator_serializer).unwrap(),
+        data
+    );
+
+    Ok(())
+}
+
 fn init_logger() {
     #[cfg(feature = "std")]
     {
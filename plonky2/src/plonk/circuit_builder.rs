@@ -22,6 +22,7 @@ use crate::fri::oracle::PolynomialBatch;
 use crate::fri::{FriConfig, FriParams};
 use crate::gadgets::arithmetic::BaseArithmeticOperation;
 use crate::gadgets::arithmetic_extension::ExtensionArithmeticOperation;
+use crate::gadgets::memory::Memory;
 use crate::gadgets::polynomial::PolynomialCoeffsExtTarget;
 use crate::gates::arithmetic_base::ArithmeticGate;
 use crate::gates::arithmetic_extension::ArithmeticExtensionGate;
@@ -47,6 +48,7 @@ use crate::plonk::circuit_data::{
 };
 use crate::plonk::config::{AlgebraicHasher, GenericConfig, GenericHashOut, Hasher};
 use crate::plonk::copy_constraint::CopyConstraint;
+use crate::plonk::optimizer::OptimizationReport;
 use crate::plonk::permutation_argument::Forest;
 use crate::plonk::plonk_common::PlonkOracle;
 use crate::timed;
@@ -175,6 +177,14 @@ pub struct CircuitBuilder<F: RichField + Extendable<D>, const D: usize> {
     /// Memoized results of `arithmetic_extension` calls.
     pub(crate) arithmetic_results: HashMap<ExtensionArithmeticOperation<F, D>, ExtensionTarget<D>>,
 
+    /// Whether to run the optimization pass over base arithmetic operations in `build`. See
+    /// [`CircuitBuilder::set_optimize`].
+    pub(crate) optimize: bool,
+
+    /// Base arithmetic operations, with their output targets, whose placement is deferred to the
+    /// optimization pass in `build`. Only used if `optimize` is set.
+    pub(crate) pending_arithmetic_operations: Vec<(BaseArithmeticOperation<F>, Target)>,
+
     /// Map between gate type and the current gate of this type with available slots.
     current_slots: HashMap<GateRef<F, D>, CurrentSlot<F, D>>,
 
@@ -185,12 +195,15 @@ pub struct CircuitBuilder<F: RichField + Extendable<D>, const D: usize> {
     /// [LookupTableGate](crate::gates::lookup_table::LookupTableGate).
     lookup_rows: Vec<LookupWire>,
 
-    /// For each LUT index, vector of `(looking_in, looking_out)` pairs.
+    /// For each LUT index, the lookups made in it, as inputs followed by outputs.
     lut_to_lookups: Vec<Lookup>,
 
-    // Lookup tables in the form of `Vec<(input_value, output_value)>`.
+    /// Lookup tables, as rows of inputs followed by outputs.
     luts: Vec<LookupTable>,
 
+    /// Read/write memories, whose consistency is checked in `build`.
+    pub(crate) memories: Vec<Memory>,
+
     /// Optional common data. When it is `Some(goal_data)`, the `build` function panics if the resulting
     /// common data doesn't equal `goal_data`.
     /// This is used in cyclic recursion.
@@ -221,11 +234,14 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
             targets_to_constants: HashMap::new(),
             base_arithmetic_results: HashMap::new(),
             arithmetic_results: HashMap::new(),
+            optimize: false,
+            pending_arithmetic_operations: Vec::new(),
             current_slots: HashMap::new(),
             constant_generators: Vec::new(),
             lookup_rows: Vec::new(),
             lut_to_lookups: Vec::new(),
             luts: Vec::new(),
+            memories: Vec::new(),
             goal_common_data: None,
             verifier_data_public_input: None,
         };
@@ -263,7 +279,25 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         self.domain_separator = Some(separator);
     }
 
+    /// Sets whether to run an optimization pass over base arithmetic operations in `build`, which
+    /// folds constants, merges duplicate operations and removes unused ones. See
+    /// [`optimizer`](crate::plonk::optimizer) for details. Defaults to `false`.
+    ///
+    /// Only the operations added while it is enabled are optimized, so it can't be disabled once
+    /// some were.
+    pub fn set_optimize(&mut self, optimize: bool) {
+        assert!(
+            optimize || self.pending_arithmetic_operations.is_empty(),
+            "Can't disable the optimization pass once operations were deferred to it"
+        );
+        self.optimize = optimize;
+    }
+
     /// Outputs the number of gates in this circuit.
+    ///
+    /// **Note:** with optimization enabled by [`CircuitBuilder::set_optimize`], base arithmetic operations are only placed
+    /// by the optimization pass in `build`, so their `ArithmeticGate`s are not counted here until
+    /// then. The same goes for the gate counts of contexts.
     pub fn num_gates(&self) -> usize {
         self.gate_instances.len()
     }
@@ -297,15 +331,22 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         });
     }
 
-    /// Adds a looking (input, output) pair to the corresponding LUT.
-    pub fn update_lookups(&mut self, looking_in: Target, looking_out: Target, lut_index: usize) {
+    /// Adds a lookup, given as its input targets followed by its output targets, to the
+    /// corresponding LUT.
+    pub fn update_lookups(&mut self, lookup: Vec<Target>, lut_index: usize) {
         assert!(
             lut_index < self.lut_to_lookups.len(),
             "The LUT with index {} has not been created. The last LUT is at index {}",
             lut_index,
             self.lut_to_lookups.len() - 1
         );
-        self.lut_to_lookups[lut_index].push((looking_in, looking_out));
+        assert_eq!(
+            lookup.len(),
+            self.luts[lut_index].width(),
+            "Lookup has the wrong number of targets for LUT {}",
+            lut_index
+        );
+        self.lut_to_lookups[lut_index].push(lookup);
     }
 
     /// Outputs the number of lookup tables in this circuit.
@@ -314,8 +355,8 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
     }
 
     /// Given an index, outputs the corresponding looking table in the set of tables
-    /// used in this circuit, as a sequence of target tuples `(input, output)`.
-    pub fn get_lut_lookups(&self, lut_index: usize) -> &[(Target, Target)] {
+    /// used in this circuit, as a sequence of lookups made of inputs followed by outputs.
+    pub fn get_lut_lookups(&self, lut_index: usize) -> &[Vec<Target>] {
         &self.lut_to_lookups[lut_index]
     }
 
@@ -665,6 +706,50 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         }
     }
 
+    /// The pairs of targets constrained to be copies of each other.
+    pub(crate) fn copied_targets(&self) -> impl Iterator<Item = (Target, Target)> + '_ {
+        self.copy_constraints
+            .iter()
+            .map(|constraint| constraint.pair)
+    }
+
+    /// The targets created by the `constant(F)` method, with their values.
+    pub(crate) fn constant_targets(&self) -> impl Iterator<Item = (Target, F)> + '_ {
+        self.targets_to_constants
+            .iter()
+            .map(|(&target, &constant)| (target, constant))
+    }
+
+    /// The targets which are copied, public or read by a generator.
+    pub(crate) fn used_targets(&self) -> HashSet<Target> {
+        self.copied_targets()
+            .flat_map(|(a, b)| [a, b])
+            .chain(self.public_inputs.iter().copied())
+            .chain(
+                self.generators
+                    .iter()
+                    .flat_map(|generator| generator.0.watch_list()),
+            )
+            .collect()
+    }
+
+    /// Drops the constants which are not used, so that no constant generator fills them, and
+    /// returns how many were dropped.
+    pub(crate) fn remove_unused_constants(&mut self) -> usize {
+        let used = self.used_targets();
+        let unused_constants = self
+            .constants_to_targets
+            .iter()
+            .filter(|(_, &target)| !used.contains(&target))
+            .map(|(&constant, &target)| (constant, target))
+            .collect::<Vec<_>>();
+        for (constant, target) in &unused_constants {
+            self.constants_to_targets.remove(constant);
+            self.targets_to_constants.remove(target);
+        }
+        unused_constants.len()
+    }
+
     pub fn push_context(&mut self, level: log::Level, ctx: &str) {
         self.context_log.push(ctx, level, self.num_gates());
     }
@@ -713,19 +798,20 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         inputs.iter().map(|&input| (input, f(input))).collect()
     }
 
+    /// The number of values in the lookups of the widest LUT, which is the number of wires used by
+    /// each slot of the lookup gates.
+    pub fn lookup_width(&self) -> usize {
+        self.luts.iter().map(|lut| lut.width()).max().unwrap_or(0)
+    }
+
     /// Given a function `f: fn(u16) -> u16`, adds a LUT to the circuit builder.
     pub fn update_luts_from_fn(&mut self, f: fn(u16) -> u16, inputs: &[u16]) -> usize {
-        let lut = Arc::new(Self::get_lut_from_fn::<u16>(f, inputs));
-
-        // If the LUT `lut` is already stored in `self.luts`, return its index. Otherwise, append `table` to `self.luts` and return its index.
-        if let Some(idx) = self.is_stored(lut.clone()) {
-            idx
-        } else {
-            self.luts.push(lut);
-            self.lut_to_lookups.push(vec![]);
-            assert!(self.luts.len() == self.lut_to_lookups.len());
-            self.luts.len() - 1
-        }
+        let lut = Arc::new(
+            Self::get_lut_from_fn::<u16>(f, inputs)
+                .into_iter()
+                .collect(),
+        );
+        self.update_luts(lut)
     }
 
     /// Adds a table to the vector of LUTs in the circuit builder, given a list of inputs and table values.
@@ -741,21 +827,23 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
             .copied()
             .zip_eq(table.iter().copied())
             .collect();
-        let lut: LookupTable = Arc::new(pairs);
-
-        // If the LUT `lut` is already stored in `self.luts`, return its index. Otherwise, append `table` to `self.luts` and return its index.
-        if let Some(idx) = self.is_stored(lut.clone()) {
-            idx
-        } else {
-            self.luts.push(lut);
-            self.lut_to_lookups.push(vec![]);
-            assert!(self.luts.len() == self.lut_to_lookups.len());
-            self.luts.len() - 1
-        }
+        self.update_luts(Arc::new(pairs))
     }
 
     /// Adds a table to the vector of LUTs in the circuit builder.
     pub fn update_luts_from_pairs(&mut self, table: LookupTable) -> usize {
+        self.update_luts(table)
+    }
+
+    /// Adds a table to the vector of LUTs in the circuit builder, and returns its index.
+    pub fn update_luts(&mut self, table: LookupTable) -> usize {
+        assert!(!table.is_empty(), "Lookup tables must not be empty");
+        assert!(
+            table.width() < self.config.num_routed_wires,
+            "Lookup tables must have fewer than {} columns",
+            self.config.num_routed_wires
+        );
+
         // If the LUT `table` is already stored in `self.luts`, return its index. Otherwise, append `table` to `self.luts` and return its index.
         if let Some(idx) = self.is_stored(table.clone()) {
             idx
@@ -1021,7 +1109,10 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
     pub fn build_with_options<C: GenericConfig<D, F = F>>(
         self,
         commit_to_sigma: bool,
-    ) -> CircuitData<F, C, D> {
+    ) -> CircuitData<F, C, D>
+    where
+        C::InnerHasher: AlgebraicHasher<F>,
+    {
         let (circuit_data, success) = self.try_build_with_options(commit_to_sigma);
         if !success {
             panic!("Failed to build circuit");
@@ -1030,14 +1121,46 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
     }
 
     pub fn try_build_with_options<C: GenericConfig<D, F = F>>(
+        self,
+        commit_to_sigma: bool,
+    ) -> (CircuitData<F, C, D>, bool)
+    where
+        C::InnerHasher: AlgebraicHasher<F>,
+    {
+        let (circuit_data, success, _) = self.try_build_with_report(commit_to_sigma);
+        (circuit_data, success)
+    }
+
+    /// Builds a "full circuit", along with the report of the optimization pass if it was enabled
+    /// with [`CircuitBuilder::set_optimize`].
+    pub fn build_with_optimization_report<C: GenericConfig<D, F = F>>(
+        self,
+    ) -> (CircuitData<F, C, D>, Option<OptimizationReport>)
+    where
+        C::InnerHasher: AlgebraicHasher<F>,
+    {
+        let (circuit_data, success, report) = self.try_build_with_report(true);
+        if !success {
+            panic!("Failed to build circuit");
+        }
+        (circuit_data, report)
+    }
+
+    fn try_build_with_report<C: GenericConfig<D, F = F>>(
         mut self,
         commit_to_sigma: bool,
-    ) -> (CircuitData<F, C, D>, bool) {
+    ) -> (CircuitData<F, C, D>, bool, Option<OptimizationReport>)
+    where
+        C::InnerHasher: AlgebraicHasher<F>,
+    {
         let mut timing = TimingTree::new("preprocess", Level::Trace);
 
         #[cfg(feature = "std")]
         let start = Instant::now();
 
+        // Check the consistency of memories, now that all their operations are known.
+        self.add_all_memory_checks();
+
         let rate_bits = self.config.fri_config.rate_bits;
         let cap_height = self.config.fri_config.cap_height;
         // Total number of LUTs.
@@ -1060,6 +1183,13 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         // Place LUT-related gates.
         self.add_all_lookups();
 
+        // Optimize and place the deferred arithmetic operations, now that all their uses are known.
+        let optimization_report = self.optimize.then(|| {
+            let report = self.optimize_arithmetic();
+            info!("Arithmetic optimization: {:?}", report);
+            report
+        });
+
         // Make sure we have enough constant generators. If not, add a `ConstantGate`.
         while self.constants_to_targets.len() > self.constant_generators.len() {
             self.add_gate(
@@ -1207,7 +1337,10 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
             0
         } else {
             // There is 1 RE polynomial and multiple Sum/LDC polynomials.
-            ceil_div_usize(LookupGate::num_slots(&self.config), lookup_degree) + 1
+            ceil_div_usize(
+                LookupGate::num_slots(&self.config, self.lookup_width()),
+                lookup_degree,
+            ) + 1
         };
         let constants_sigmas_cap = constants_sigmas_commitment.merkle_tree.cap.clone();
         let domain_separator = self.domain_separator.unwrap_or_default();
@@ -1277,15 +1410,22 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
                 common,
             },
             success,
+            optimization_report,
         )
     }
 
     /// Builds a "full circuit", with both prover and verifier data.
-    pub fn build<C: GenericConfig<D, F = F>>(self) -> CircuitData<F, C, D> {
+    pub fn build<C: GenericConfig<D, F = F>>(self) -> CircuitData<F, C, D>
+    where
+        C::InnerHasher: AlgebraicHasher<F>,
+    {
         self.build_with_options(true)
     }
 
-    pub fn mock_build<C: GenericConfig<D, F = F>>(self) -> MockCircuitData<F, C, D> {
+    pub fn mock_build<C: GenericConfig<D, F = F>>(self) -> MockCircuitData<F, C, D>
+    where
+        C::InnerHasher: AlgebraicHasher<F>,
+    {
         let circuit_data = self.build_with_options(false);
         MockCircuitData {
             prover_only: circuit_data.prover_only,
@@ -1293,14 +1433,20 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         }
     }
     /// Builds a "prover circuit", with data needed to generate proofs but not verify them.
-    pub fn build_prover<C: GenericConfig<D, F = F>>(self) -> ProverCircuitData<F, C, D> {
+    pub fn build_prover<C: GenericConfig<D, F = F>>(self) -> ProverCircuitData<F, C, D>
+    where
+        C::InnerHasher: AlgebraicHasher<F>,
+    {
         // TODO: Can skip parts of this.
         let circuit_data = self.build::<C>();
         circuit_data.prover_data()
     }
 
     /// Builds a "verifier circuit", with data needed to verify proofs but not generate them.
-    pub fn build_verifier<C: GenericConfig<D, F = F>>(self) -> VerifierCircuitData<F, C, D> {
+    pub fn build_verifier<C: GenericConfig<D, F = F>>(self) -> VerifierCircuitData<F, C, D>
+    where
+        C::InnerHasher: AlgebraicHasher<F>,
+    {
         // TODO: Can skip parts of this.
         let circuit_data = self.build::<C>();
         circuit_data.verifier_data()
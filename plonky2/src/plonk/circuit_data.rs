@@ -13,12 +13,16 @@
 //! This is useful to allow even small devices to verify plonky2 proofs.
 
 use alloc::collections::BTreeMap;
-use alloc::vec;
+use alloc::string::String;
 use alloc::vec::Vec;
+use alloc::{format, vec};
 use core::ops::{Range, RangeFrom};
 
 use anyhow::Result;
-use serde::Serialize;
+use keccak_hash::keccak;
+use serde::de::Error as _;
+use serde::ser::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use super::circuit_builder::LookupWire;
 use crate::field::extension::Extendable;
@@ -42,13 +46,15 @@ use crate::iop::generator::{generate_partial_witness, WitnessGeneratorRef};
 use crate::iop::target::Target;
 use crate::iop::witness::{PartialWitness, PartitionWitness};
 use crate::plonk::circuit_builder::CircuitBuilder;
-use crate::plonk::config::{GenericConfig, Hasher};
+use crate::plonk::config::{AlgebraicHasher, GenericConfig, GenericHashOut, Hasher};
 use crate::plonk::plonk_common::PlonkOracle;
 use crate::plonk::proof::{CompressedProofWithPublicInputs, ProofWithPublicInputs};
 use crate::plonk::prover::prove;
 use crate::plonk::verifier::verify;
+use crate::util::serialization::envelope::{self, PayloadKind};
 use crate::util::serialization::{
-    Buffer, GateSerializer, IoResult, Read, WitnessGeneratorSerializer, Write,
+    Buffer, GateRegistry, GateSerializer, IoResult, Read, SerializedGate,
+    WitnessGeneratorSerializer, Write,
 };
 use crate::util::timing::TimingTree;
 
@@ -57,7 +63,7 @@ use crate::util::timing::TimingTree;
 ///
 /// It supports a [`Default`] implementation tailored for recursion with Poseidon hash (of width 12)
 /// as internal hash function and FRI rate of 1/8.
-#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct CircuitConfig {
     /// The number of wires available at each row. This corresponds to the "width" of the circuit,
     /// and consists in the sum of routed wires and advice wires.
@@ -171,9 +177,12 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
         gate_serializer: &dyn GateSerializer<F, D>,
         generator_serializer: &dyn WitnessGeneratorSerializer<F, D>,
     ) -> IoResult<Vec<u8>> {
-        let mut buffer = Vec::new();
-        buffer.write_circuit_data(self, gate_serializer, generator_serializer)?;
-        Ok(buffer)
+        envelope::seal(
+            PayloadKind::CircuitData,
+            &C::id(),
+            &self.verifier_only.circuit_digest.to_bytes(),
+            |buffer| buffer.write_circuit_data(self, gate_serializer, generator_serializer),
+        )
     }
 
     pub fn from_bytes(
@@ -181,11 +190,19 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
         gate_serializer: &dyn GateSerializer<F, D>,
         generator_serializer: &dyn WitnessGeneratorSerializer<F, D>,
     ) -> IoResult<Self> {
-        let mut buffer = Buffer::new(bytes);
-        buffer.read_circuit_data(gate_serializer, generator_serializer)
+        envelope::unseal(
+            bytes,
+            PayloadKind::CircuitData,
+            &C::id(),
+            |buffer| buffer.read_circuit_data(gate_serializer, generator_serializer),
+            |data: &Self| data.verifier_only.circuit_digest.to_bytes(),
+        )
     }
 
-    pub fn prove(&self, inputs: PartialWitness<F>) -> Result<ProofWithPublicInputs<F, C, D>> {
+    pub fn prove(&self, inputs: PartialWitness<F>) -> Result<ProofWithPublicInputs<F, C, D>>
+    where
+        C::InnerHasher: AlgebraicHasher<F>,
+    {
         prove::<F, C, D>(
             &self.prover_only,
             &self.common,
@@ -194,14 +211,20 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
         )
     }
 
-    pub fn verify(&self, proof_with_pis: ProofWithPublicInputs<F, C, D>) -> Result<()> {
+    pub fn verify(&self, proof_with_pis: ProofWithPublicInputs<F, C, D>) -> Result<()>
+    where
+        C::InnerHasher: AlgebraicHasher<F>,
+    {
         verify::<F, C, D>(proof_with_pis, &self.verifier_only, &self.common)
     }
 
     pub fn verify_compressed(
         &self,
         compressed_proof_with_pis: CompressedProofWithPublicInputs<F, C, D>,
-    ) -> Result<()> {
+    ) -> Result<()>
+    where
+        C::InnerHasher: AlgebraicHasher<F>,
+    {
         compressed_proof_with_pis.verify(&self.verifier_only, &self.common)
     }
 
@@ -268,9 +291,12 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
         gate_serializer: &dyn GateSerializer<F, D>,
         generator_serializer: &dyn WitnessGeneratorSerializer<F, D>,
     ) -> IoResult<Vec<u8>> {
-        let mut buffer = Vec::new();
-        buffer.write_prover_circuit_data(self, gate_serializer, generator_serializer)?;
-        Ok(buffer)
+        envelope::seal(
+            PayloadKind::ProverCircuitData,
+            &C::id(),
+            &self.prover_only.circuit_digest.to_bytes(),
+            |buffer| buffer.write_prover_circuit_data(self, gate_serializer, generator_serializer),
+        )
     }
 
     pub fn from_bytes(
@@ -278,11 +304,19 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
         gate_serializer: &dyn GateSerializer<F, D>,
         generator_serializer: &dyn WitnessGeneratorSerializer<F, D>,
     ) -> IoResult<Self> {
-        let mut buffer = Buffer::new(bytes);
-        buffer.read_prover_circuit_data(gate_serializer, generator_serializer)
+        envelope::unseal(
+            bytes,
+            PayloadKind::ProverCircuitData,
+            &C::id(),
+            |buffer| buffer.read_prover_circuit_data(gate_serializer, generator_serializer),
+            |data: &Self| data.prover_only.circuit_digest.to_bytes(),
+        )
     }
 
-    pub fn prove(&self, inputs: PartialWitness<F>) -> Result<ProofWithPublicInputs<F, C, D>> {
+    pub fn prove(&self, inputs: PartialWitness<F>) -> Result<ProofWithPublicInputs<F, C, D>>
+    where
+        C::InnerHasher: AlgebraicHasher<F>,
+    {
         prove::<F, C, D>(
             &self.prover_only,
             &self.common,
@@ -293,7 +327,8 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
 }
 
 /// Circuit data required by the prover.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound = "")]
 pub struct VerifierCircuitData<
     F: RichField + Extendable<D>,
     C: GenericConfig<D, F = F>,
@@ -307,27 +342,41 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
     VerifierCircuitData<F, C, D>
 {
     pub fn to_bytes(&self, gate_serializer: &dyn GateSerializer<F, D>) -> IoResult<Vec<u8>> {
-        let mut buffer = Vec::new();
-        buffer.write_verifier_circuit_data(self, gate_serializer)?;
-        Ok(buffer)
+        envelope::seal(
+            PayloadKind::VerifierCircuitData,
+            &C::id(),
+            &self.verifier_only.circuit_digest.to_bytes(),
+            |buffer| buffer.write_verifier_circuit_data(self, gate_serializer),
+        )
     }
 
     pub fn from_bytes(
         bytes: Vec<u8>,
         gate_serializer: &dyn GateSerializer<F, D>,
     ) -> IoResult<Self> {
-        let mut buffer = Buffer::new(&bytes);
-        buffer.read_verifier_circuit_data(gate_serializer)
+        envelope::unseal(
+            &bytes,
+            PayloadKind::VerifierCircuitData,
+            &C::id(),
+            |buffer| buffer.read_verifier_circuit_data(gate_serializer),
+            |data: &Self| data.verifier_only.circuit_digest.to_bytes(),
+        )
     }
 
-    pub fn verify(&self, proof_with_pis: ProofWithPublicInputs<F, C, D>) -> Result<()> {
+    pub fn verify(&self, proof_with_pis: ProofWithPublicInputs<F, C, D>) -> Result<()>
+    where
+        C::InnerHasher: AlgebraicHasher<F>,
+    {
         verify::<F, C, D>(proof_with_pis, &self.verifier_only, &self.common)
     }
 
     pub fn verify_compressed(
         &self,
         compressed_proof_with_pis: CompressedProofWithPublicInputs<F, C, D>,
-    ) -> Result<()> {
+    ) -> Result<()>
+    where
+        C::InnerHasher: AlgebraicHasher<F>,
+    {
         compressed_proof_with_pis.verify(&self.verifier_only, &self.common)
     }
 }
@@ -373,9 +422,12 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
         generator_serializer: &dyn WitnessGeneratorSerializer<F, D>,
         common_data: &CommonCircuitData<F, D>,
     ) -> IoResult<Vec<u8>> {
-        let mut buffer = Vec::new();
-        buffer.write_prover_only_circuit_data(self, generator_serializer, common_data)?;
-        Ok(buffer)
+        envelope::seal(
+            PayloadKind::ProverOnlyCircuitData,
+            &C::id(),
+            &self.circuit_digest.to_bytes(),
+            |buffer| buffer.write_prover_only_circuit_data(self, generator_serializer, common_data),
+        )
     }
 
     pub fn from_bytes(
@@ -383,13 +435,19 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
         generator_serializer: &dyn WitnessGeneratorSerializer<F, D>,
         common_data: &CommonCircuitData<F, D>,
     ) -> IoResult<Self> {
-        let mut buffer = Buffer::new(bytes);
-        buffer.read_prover_only_circuit_data(generator_serializer, common_data)
+        envelope::unseal(
+            bytes,
+            PayloadKind::ProverOnlyCircuitData,
+            &C::id(),
+            |buffer| buffer.read_prover_only_circuit_data(generator_serializer, common_data),
+            |data: &Self| data.circuit_digest.to_bytes(),
+        )
     }
 }
 
 /// Circuit data required by the verifier, but not the prover.
-#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(bound = "")]
 pub struct VerifierOnlyCircuitData<C: GenericConfig<D>, const D: usize> {
     /// A commitment to each constant polynomial and each permutation polynomial.
     pub constants_sigmas_cap: MerkleCap<C::F, C::Hasher>,
@@ -412,7 +470,10 @@ impl<C: GenericConfig<D>, const D: usize> VerifierOnlyCircuitData<C, D> {
 }
 
 /// Circuit data required by both the prover and the verifier.
-#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+///
+/// With serde, it is represented as a [`SerializableCommonCircuitData`] whose gates are those of
+/// `GateRegistry::default()`.
+#[derive(Debug, Clone, Eq, PartialEq)]
 pub struct CommonCircuitData<F: RichField + Extendable<D>, const D: usize> {
     pub config: CircuitConfig,
 
@@ -451,19 +512,165 @@ pub struct CommonCircuitData<F: RichField + Extendable<D>, const D: usize> {
     pub luts: Vec<LookupTable>,
 }
 
+/// A form of [`CommonCircuitData`] implementing `Serialize` and `Deserialize`, in which each gate
+/// is given by its id in a [`GateRegistry`] and its parameters. Circuits with custom gates can be
+/// converted to and from it with a registry of those gates.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct SerializableCommonCircuitData<F: RichField + Extendable<D>, const D: usize> {
+    pub config: CircuitConfig,
+    pub fri_params: FriParams,
+    pub gates: Vec<SerializedGate>,
+    pub selectors_info: SelectorsInfo,
+    pub quotient_degree_factor: usize,
+    pub num_gate_constraints: usize,
+    pub num_constants: usize,
+    pub num_public_inputs: usize,
+    pub k_is: Vec<F>,
+    pub num_partial_products: usize,
+    pub num_lookup_polys: usize,
+    pub num_lookup_selectors: usize,
+    pub luts: Vec<LookupTable>,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> Serialize for CommonCircuitData<F, D> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_serializable(&GateRegistry::default())
+            .map_err(|_| S::Error::custom("circuit uses gates missing from the default registry"))?
+            .serialize(serializer)
+    }
+}
+
+impl<'de, F: RichField + Extendable<D>, const D: usize> Deserialize<'de>
+    for CommonCircuitData<F, D>
+{
+    fn deserialize<De: Deserializer<'de>>(deserializer: De) -> Result<Self, De::Error> {
+        let data = SerializableCommonCircuitData::deserialize(deserializer)?;
+        Self::from_serializable(data, &GateRegistry::default())
+            .map_err(|_| De::Error::custom("invalid or unregistered gates"))
+    }
+}
+
 impl<F: RichField + Extendable<D>, const D: usize> CommonCircuitData<F, D> {
+    /// Converts this data to a form implementing `Serialize`, with gates from `gate_registry`.
+    pub fn to_serializable(
+        &self,
+        gate_registry: &GateRegistry<F, D>,
+    ) -> IoResult<SerializableCommonCircuitData<F, D>> {
+        let gates = self
+            .gates
+            .iter()
+            .map(|gate| gate_registry.to_serialized_gate(gate, self))
+            .collect::<IoResult<_>>()?;
+
+        Ok(SerializableCommonCircuitData {
+            config: self.config.clone(),
+            fri_params: self.fri_params.clone(),
+            gates,
+            selectors_info: self.selectors_info.clone(),
+            quotient_degree_factor: self.quotient_degree_factor,
+            num_gate_constraints: self.num_gate_constraints,
+            num_constants: self.num_constants,
+            num_public_inputs: self.num_public_inputs,
+            k_is: self.k_is.clone(),
+            num_partial_products: self.num_partial_products,
+            num_lookup_polys: self.num_lookup_polys,
+            num_lookup_selectors: self.num_lookup_selectors,
+            luts: self.luts.clone(),
+        })
+    }
+
+    /// Converts data from a form implementing `Deserialize`, with gates from `gate_registry`.
+    pub fn from_serializable(
+        data: SerializableCommonCircuitData<F, D>,
+        gate_registry: &GateRegistry<F, D>,
+    ) -> IoResult<Self> {
+        // Like when reading bytes, the gates are read after the rest of the data, which they may
+        // refer to.
+        let mut common_data = CommonCircuitData {
+            config: data.config,
+            fri_params: data.fri_params,
+            gates: vec![],
+            selectors_info: data.selectors_info,
+            quotient_degree_factor: data.quotient_degree_factor,
+            num_gate_constraints: data.num_gate_constraints,
+            num_constants: data.num_constants,
+            num_public_inputs: data.num_public_inputs,
+            k_is: data.k_is,
+            num_partial_products: data.num_partial_products,
+            num_lookup_polys: data.num_lookup_polys,
+            num_lookup_selectors: data.num_lookup_selectors,
+            luts: data.luts,
+        };
+        common_data.gates = data
+            .gates
+            .iter()
+            .map(|gate| gate_registry.from_serialized_gate(gate, &common_data))
+            .collect::<IoResult<_>>()?;
+
+        Ok(common_data)
+    }
+
     pub fn to_bytes(&self, gate_serializer: &dyn GateSerializer<F, D>) -> IoResult<Vec<u8>> {
-        let mut buffer = Vec::new();
-        buffer.write_common_circuit_data(self, gate_serializer)?;
-        Ok(buffer)
+        envelope::seal(
+            PayloadKind::CommonCircuitData,
+            &Self::config_id(),
+            &self.fingerprint(),
+            |buffer| buffer.write_common_circuit_data(self, gate_serializer),
+        )
     }
 
     pub fn from_bytes(
         bytes: Vec<u8>,
         gate_serializer: &dyn GateSerializer<F, D>,
     ) -> IoResult<Self> {
-        let mut buffer = Buffer::new(&bytes);
-        buffer.read_common_circuit_data(gate_serializer)
+        envelope::unseal(
+            &bytes,
+            PayloadKind::CommonCircuitData,
+            &Self::config_id(),
+            |buffer| buffer.read_common_circuit_data(gate_serializer),
+            Self::fingerprint,
+        )
+    }
+
+    /// A Keccak-256 hash of this data, recorded when serializing it and proofs of the circuit so
+    /// that they are not read back for another circuit. Unlike the serialized data, it does not
+    /// depend on the `GateSerializer` used, as gates are identified by their ids.
+    pub fn fingerprint(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        self.write_fingerprint_preimage(&mut bytes)
+            .expect("Writing to a byte-vector cannot fail.");
+        keccak(bytes).0.to_vec()
+    }
+
+    fn write_fingerprint_preimage(&self, bytes: &mut Vec<u8>) -> IoResult<()> {
+        bytes.write_circuit_config(&self.config)?;
+        bytes.write_fri_params(&self.fri_params)?;
+        bytes.write_selectors_info(&self.selectors_info)?;
+        bytes.write_usize(self.quotient_degree_factor)?;
+        bytes.write_usize(self.num_gate_constraints)?;
+        bytes.write_usize(self.num_constants)?;
+        bytes.write_usize(self.num_public_inputs)?;
+        bytes.write_usize(self.k_is.len())?;
+        bytes.write_field_vec(&self.k_is)?;
+        bytes.write_usize(self.num_partial_products)?;
+        bytes.write_usize(self.num_lookup_polys)?;
+        bytes.write_usize(self.num_lookup_selectors)?;
+        bytes.write_usize(self.luts.len())?;
+        for lut in &self.luts {
+            bytes.write_lut(lut)?;
+        }
+        bytes.write_usize(self.gates.len())?;
+        for gate in &self.gates {
+            bytes.write_str(&gate.0.id())?;
+        }
+        Ok(())
+    }
+
+    /// The id recorded when serializing this data. Since it does not depend on the hashers of a
+    /// `GenericConfig`, this identifies the extension field by its order, e.g. `GF(p^2)`.
+    fn config_id() -> String {
+        format!("GF({}^{})", F::order(), D)
     }
 
     pub const fn degree_bits(&self) -> usize {
@@ -490,6 +697,12 @@ impl<F: RichField + Extendable<D>, const D: usize> CommonCircuitData<F, D> {
             .expect("No gates?")
     }
 
+    /// The number of values in the lookups of the widest LUT, which is the number of wires used by
+    /// each slot of the lookup gates.
+    pub fn lookup_width(&self) -> usize {
+        self.luts.iter().map(|lut| lut.width()).max().unwrap_or(0)
+    }
+
     pub const fn quotient_degree(&self) -> usize {
         self.quotient_degree_factor * self.degree()
     }
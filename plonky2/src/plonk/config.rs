@@ -6,6 +6,7 @@
 //! the Poseidon hash function both internally and natively, and one
 //! mixing Poseidon internally and truncated Keccak externally.
 
+use alloc::string::String;
 use alloc::vec;
 use alloc::vec::Vec;
 use core::fmt::Debug;
@@ -13,13 +14,15 @@ use core::fmt::Debug;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 
+use crate::field::babybear_field::BabyBearField;
 use crate::field::extension::quadratic::QuadraticExtension;
+use crate::field::extension::quartic::QuarticExtension;
 use crate::field::extension::{Extendable, FieldExtension};
 use crate::field::goldilocks_field::GoldilocksField;
 use crate::hash::hash_types::{HashOut, RichField};
 use crate::hash::hashing::PlonkyPermutation;
 use crate::hash::keccak::KeccakHash;
-use crate::hash::poseidon::PoseidonHash;
+use crate::hash::poseidon::{PoseidonHash, PoseidonWideHash};
 use crate::iop::target::{BoolTarget, Target};
 use crate::plonk::circuit_builder::CircuitBuilder;
 
@@ -101,8 +104,15 @@ pub trait GenericConfig<const D: usize>:
     type FE: FieldExtension<D, BaseField = Self::F>;
     /// Hash function used for building Merkle trees.
     type Hasher: Hasher<Self::F>;
-    /// Algebraic hash function used for the challenger and hashing public inputs.
-    type InnerHasher: AlgebraicHasher<Self::F>;
+    /// Hash function used for the challenger and hashing public inputs. It must be algebraic for
+    /// plonky2 circuits, which hash their public inputs in-circuit, and for recursion.
+    type InnerHasher: Hasher<Self::F>;
+
+    /// An identifier of this configuration, recorded when serializing circuit data and proofs so
+    /// that they are not read back with another configuration. It must stay the same across
+    /// compilers and crate versions, so it is spelled out by each configuration rather than
+    /// derived from its type name.
+    fn id() -> String;
 }
 
 /// Configuration using Poseidon over the Goldilocks field.
@@ -113,6 +123,10 @@ impl GenericConfig<2> for PoseidonGoldilocksConfig {
     type FE = QuadraticExtension<Self::F>;
     type Hasher = PoseidonHash;
     type InnerHasher = PoseidonHash;
+
+    fn id() -> String {
+        "PoseidonGoldilocksConfig".into()
+    }
 }
 
 /// Configuration using truncated Keccak over the Goldilocks field.
@@ -123,4 +137,42 @@ impl GenericConfig<2> for KeccakGoldilocksConfig {
     type FE = QuadraticExtension<Self::F>;
     type Hasher = KeccakHash<25>;
     type InnerHasher = PoseidonHash;
+
+    fn id() -> String {
+        "KeccakGoldilocksConfig".into()
+    }
+}
+
+/// Configuration using Poseidon over the BabyBear field, with challenges drawn from its quartic
+/// extension. Merkle trees, Fiat-Shamir and public inputs all use `PoseidonWideHash`, since the
+/// 4-element (~124 bit) digests of `PoseidonHash` only give ~62 bits of collision resistance.
+/// Wide digests have no circuit implementation, so this configuration cannot be verified
+/// recursively, and plonky2 circuits, which hash their public inputs in-circuit, can't be built
+/// with it; it is meant for STARKs.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize)]
+pub struct PoseidonBabyBearConfig;
+impl GenericConfig<4> for PoseidonBabyBearConfig {
+    type F = BabyBearField;
+    type FE = QuarticExtension<Self::F>;
+    type Hasher = PoseidonWideHash;
+    type InnerHasher = PoseidonWideHash;
+
+    fn id() -> String {
+        "PoseidonBabyBearConfig".into()
+    }
+}
+
+/// Configuration using Keccak over the BabyBear field, with challenges drawn from its quartic
+/// extension.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct KeccakBabyBearConfig;
+impl GenericConfig<4> for KeccakBabyBearConfig {
+    type F = BabyBearField;
+    type FE = QuarticExtension<Self::F>;
+    type Hasher = KeccakHash<32>;
+    type InnerHasher = PoseidonHash;
+
+    fn id() -> String {
+        "KeccakBabyBearConfig".into()
+    }
 }
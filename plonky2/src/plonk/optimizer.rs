@@ -0,0 +1,411 @@
+//! An optimization pass over the base arithmetic operations of a circuit, run by
+//! [`CircuitBuilder::build`] when enabled with [`CircuitBuilder::set_optimize`].
+//!
+//! While optimizing, `arithmetic` returns a virtual target for each new operation instead of
+//! placing it right away. Once the whole circuit is known, the deferred operations are
+//! - folded, if copy constraints make their result a constant or one of their inputs,
+//! - merged, if they are identical to another operation up to copy constraints,
+//! - removed, if their result is not used by anything in the circuit,
+//!
+//! and the remaining ones are packed into `ArithmeticGate`s. Constants which end up unused, e.g.
+//! because only removed operations read them, are then dropped along with the constant generators
+//! which would fill them.
+//!
+//! Note that until then, [`CircuitBuilder::num_gates`] does not count the deferred operations.
+//! The changes made are summarized by an [`OptimizationReport`], which is returned by
+//! [`CircuitBuilder::build_with_optimization_report`].
+
+use alloc::vec::Vec;
+
+use hashbrown::{HashMap, HashSet};
+
+use crate::field::extension::Extendable;
+use crate::gadgets::arithmetic::BaseArithmeticOperation;
+use crate::gates::arithmetic_base::ArithmeticGate;
+use crate::hash::hash_types::RichField;
+use crate::iop::target::Target;
+use crate::plonk::circuit_builder::CircuitBuilder;
+
+/// A summary of the changes made by the optimization pass.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct OptimizationReport {
+    /// The number of operations whose result was found to be a constant or one of their inputs.
+    pub folded: usize,
+    /// The number of operations identical to an earlier one, up to copy constraints.
+    pub merged: usize,
+    /// The number of operations whose result is unused.
+    pub removed: usize,
+    /// The number of `ArithmeticGate` rows saved by the above.
+    pub rows_saved: usize,
+    /// The number of unused constants dropped, each of which would have taken a constant
+    /// generator and a constant wire.
+    pub unused_constants: usize,
+}
+
+/// The partition of targets into classes of targets which are copies of each other, along with the
+/// constant each class is equal to, if any.
+#[derive(Default)]
+struct TargetPartition<F> {
+    parents: HashMap<Target, Target>,
+    sizes: HashMap<Target, usize>,
+    constants: HashMap<Target, F>,
+}
+
+impl<F: Copy> TargetPartition<F> {
+    /// The representative of the class of `target`.
+    fn find(&mut self, target: Target) -> Target {
+        let mut root = target;
+        while let Some(&parent) = self.parents.get(&root) {
+            root = parent;
+        }
+
+        // Compress the path to the root.
+        let mut current = target;
+        while current != root {
+            let parent = self.parents[&current];
+            self.parents.insert(current, root);
+            current = parent;
+        }
+
+        root
+    }
+
+    fn union(&mut self, a: Target, b: Target) {
+        let (a, b) = (self.find(a), self.find(b));
+        if a == b {
+            return;
+        }
+
+        let size_a = self.sizes.get(&a).copied().unwrap_or(1);
+        let size_b = self.sizes.get(&b).copied().unwrap_or(1);
+        let (root, child) = if size_a >= size_b { (a, b) } else { (b, a) };
+        self.parents.insert(child, root);
+        self.sizes.remove(&child);
+        self.sizes.insert(root, size_a + size_b);
+        if let Some(constant) = self.constants.remove(&child) {
+            self.constants.entry(root).or_insert(constant);
+        }
+    }
+
+    fn set_constant(&mut self, target: Target, constant: F) {
+        let root = self.find(target);
+        self.constants.entry(root).or_insert(constant);
+    }
+
+    fn constant(&mut self, target: Target) -> Option<F> {
+        let root = self.find(target);
+        self.constants.get(&root).copied()
+    }
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
+    /// Folds, merges and removes the deferred base arithmetic operations where possible, and
+    /// places the remaining ones. This must only be called once the rest of the circuit is known,
+    /// since an operation whose result is not used yet is removed.
+    pub(crate) fn optimize_arithmetic(&mut self) -> OptimizationReport {
+        let operations = core::mem::take(&mut self.pending_arithmetic_operations);
+        let mut report = OptimizationReport::default();
+
+        // Results which are copied, public or read by a generator. Results can also be used by
+        // other operations, which is handled once operations have been folded and merged.
+        let outputs = operations
+            .iter()
+            .map(|&(_, output)| output)
+            .collect::<HashSet<_>>();
+        let used = self
+            .used_targets()
+            .into_iter()
+            .filter(|target| outputs.contains(target))
+            .collect::<HashSet<_>>();
+
+        let mut partition = TargetPartition::default();
+        for (a, b) in self.copied_targets() {
+            partition.union(a, b);
+        }
+        for (target, constant) in self.constant_targets() {
+            partition.set_constant(target, constant);
+        }
+
+        // Folding or merging an operation can make others foldable or mergeable, so repeat until
+        // nothing changes.
+        let mut remaining = (0..operations.len()).collect::<Vec<_>>();
+        loop {
+            let mut seen = HashMap::new();
+            let mut kept = Vec::with_capacity(remaining.len());
+            for &i in &remaining {
+                let (operation, output) = operations[i];
+                let [multiplicand_0, multiplicand_1, addend] = [
+                    operation.multiplicand_0,
+                    operation.multiplicand_1,
+                    operation.addend,
+                ]
+                .map(|target| match partition.constant(target) {
+                    Some(constant) => self.constant(constant),
+                    None => partition.find(target),
+                });
+
+                if let Some(result) = self.arithmetic_special_cases(
+                    operation.const_0,
+                    operation.const_1,
+                    multiplicand_0,
+                    multiplicand_1,
+                    addend,
+                ) {
+                    if let Some(constant) = self.target_as_constant(result) {
+                        partition.set_constant(result, constant);
+                    }
+                    partition.union(output, result);
+                    self.connect(output, result);
+                    report.folded += 1;
+                    continue;
+                }
+
+                // The addend doesn't matter if its coefficient is zero, as in `mul`.
+                let addend = if operation.const_1.is_zero() {
+                    self.zero()
+                } else {
+                    addend
+                };
+                let canonical = BaseArithmeticOperation {
+                    multiplicand_0,
+                    multiplicand_1,
+                    addend,
+                    ..operation
+                };
+                let swapped = BaseArithmeticOperation {
+                    multiplicand_0: multiplicand_1,
+                    multiplicand_1: multiplicand_0,
+                    ..canonical
+                };
+                if let Some(&existing) = seen.get(&canonical).or_else(|| seen.get(&swapped)) {
+                    partition.union(output, existing);
+                    self.connect(output, existing);
+                    report.merged += 1;
+                    continue;
+                }
+
+                seen.insert(canonical, output);
+                kept.push(i);
+            }
+
+            let done = kept.len() == remaining.len();
+            remaining = kept;
+            if done {
+                break;
+            }
+        }
+
+        // Keep the operations whose result is used, either directly or by another kept operation.
+        let mut producers = HashMap::<Target, Vec<usize>>::new();
+        for &i in &remaining {
+            let class = partition.find(operations[i].1);
+            producers.entry(class).or_default().push(i);
+        }
+        let mut worklist = used
+            .iter()
+            .map(|&target| partition.find(target))
+            .collect::<Vec<_>>();
+        let mut live_classes = HashSet::new();
+        let mut live = Vec::new();
+        while let Some(class) = worklist.pop() {
+            if !live_classes.insert(class) {
+                continue;
+            }
+            for &i in producers.get(&class).into_iter().flatten() {
+                let operation = operations[i].0;
+                live.push(i);
+                worklist.extend(
+                    [
+                        operation.multiplicand_0,
+                        operation.multiplicand_1,
+                        operation.addend,
+                    ]
+                    .map(|target| partition.find(target)),
+                );
+            }
+        }
+        // Place operations in the order they were added, so that building is deterministic.
+        live.sort_unstable();
+        report.removed = remaining.len() - live.len();
+
+        for &i in &live {
+            let (operation, output) = operations[i];
+            let result = self.add_base_arithmetic_operation(operation);
+            self.connect(output, result);
+        }
+
+        let all_rows = self.num_arithmetic_rows(operations.iter().map(|(operation, _)| operation));
+        let live_rows = self.num_arithmetic_rows(live.iter().map(|&i| &operations[i].0));
+        report.rows_saved = all_rows - live_rows;
+
+        // Drop the constants nothing refers to anymore, so that no constant generator fills them.
+        report.unused_constants = self.remove_unused_constants();
+
+        report
+    }
+
+    /// The number of `ArithmeticGate` rows needed for `operations`, since only operations with the
+    /// same constants can share a row.
+    fn num_arithmetic_rows<'a>(
+        &self,
+        operations: impl Iterator<Item = &'a BaseArithmeticOperation<F>>,
+    ) -> usize {
+        let num_ops = ArithmeticGate::new_from_config(&self.config).num_ops;
+        let mut counts = HashMap::new();
+        for operation in operations {
+            *counts
+                .entry((operation.const_0, operation.const_1))
+                .or_insert(0) += 1;
+        }
+        counts
+            .values()
+            .map(|&count: &usize| count.div_ceil(num_ops))
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(not(feature = "std"))]
+    use alloc::vec;
+
+    use anyhow::Result;
+
+    use super::*;
+    use crate::field::types::{Field, Sample};
+    use crate::iop::witness::{PartialWitness, WitnessWrite};
+    use crate::plonk::circuit_data::CircuitConfig;
+    use crate::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+
+    fn optimizing_builder() -> CircuitBuilder<F, D> {
+        let mut builder = CircuitBuilder::new(CircuitConfig::standard_recursion_config());
+        builder.set_optimize(true);
+        builder
+    }
+
+    #[test]
+    fn test_optimize_arithmetic() -> Result<()> {
+        let mut builder = optimizing_builder();
+        let x = builder.add_virtual_target();
+        let y = builder.add_virtual_target();
+        let x_copy = builder.add_virtual_target();
+        let one = builder.add_virtual_target();
+        let two = builder.add_virtual_target();
+
+        // Identical up to copy constraints and commutativity.
+        let product = builder.mul(x, y);
+        let product_copy = builder.mul(y, x_copy);
+        // Foldable once `one` and `two` are known to be constants.
+        let y_times_one = builder.mul(y, one);
+        let three = builder.add(one, two);
+        // Unused, along with the operation it depends on.
+        let square = builder.square(x);
+        builder.add(square, y);
+        // Unused, as is the constant it reads.
+        let seven = builder.constant(F::from_canonical_u64(7));
+        builder.mul(x, seven);
+
+        builder.connect(x, x_copy);
+        let one_constant = builder.one();
+        builder.connect(one, one_constant);
+        let two_constant = builder.two();
+        builder.connect(two, two_constant);
+        builder.register_public_inputs(&[product, product_copy, y_times_one, three]);
+
+        let report = builder.optimize_arithmetic();
+        assert_eq!(
+            report,
+            OptimizationReport {
+                folded: 2,
+                merged: 1,
+                removed: 3,
+                // The multiplications and the additions would use one row each.
+                rows_saved: 1,
+                // Seven, and zero, which is only created to check for special cases.
+                unused_constants: 2,
+            }
+        );
+
+        let data = builder.build::<C>();
+        let (x_value, y_value) = (F::rand(), F::rand());
+        let mut pw = PartialWitness::new();
+        pw.set_target(x, x_value);
+        pw.set_target(y, y_value);
+        let proof = data.prove(pw)?;
+        assert_eq!(
+            proof.public_inputs,
+            vec![
+                x_value * y_value,
+                x_value * y_value,
+                y_value,
+                F::from_canonical_u64(3)
+            ]
+        );
+        data.verify(proof)
+    }
+
+    #[test]
+    fn test_rows_saved() -> Result<()> {
+        let num_ops = 100;
+        let circuit = |optimize: bool| {
+            let mut builder =
+                CircuitBuilder::<F, D>::new(CircuitConfig::standard_recursion_config());
+            builder.set_optimize(optimize);
+            let x = builder.add_virtual_target();
+            let copies = builder.add_virtual_targets(num_ops);
+            let mut sum = builder.zero();
+            for &copy in &copies {
+                builder.connect(x, copy);
+                let cube = builder.cube(copy);
+                sum = builder.add(sum, cube);
+            }
+            builder.register_public_input(sum);
+            (builder, x)
+        };
+
+        let (builder, _) = circuit(false);
+        let unoptimized_rows = builder.num_gates();
+
+        let (mut builder, x) = circuit(true);
+        let report = builder.optimize_arithmetic();
+        assert_eq!(report.merged, 2 * (num_ops - 1));
+        assert_eq!(builder.num_gates(), unoptimized_rows - report.rows_saved);
+        assert!(report.rows_saved > 0);
+
+        let data = builder.build::<C>();
+        let x_value = F::rand();
+        let mut pw = PartialWitness::new();
+        pw.set_target(x, x_value);
+        let proof = data.prove(pw)?;
+        assert_eq!(
+            proof.public_inputs,
+            vec![x_value.cube() * F::from_canonical_usize(num_ops)]
+        );
+        data.verify(proof)
+    }
+
+    #[test]
+    fn test_build_with_optimization_report() {
+        let circuit = |optimize: bool| {
+            let mut builder =
+                CircuitBuilder::<F, D>::new(CircuitConfig::standard_recursion_config());
+            builder.set_optimize(optimize);
+            let x = builder.add_virtual_target();
+            let x_copy = builder.add_virtual_target();
+            builder.connect(x, x_copy);
+            let square = builder.square(x);
+            let square_copy = builder.square(x_copy);
+            builder.register_public_inputs(&[square, square_copy]);
+            builder.build_with_optimization_report::<C>()
+        };
+
+        let (_, report) = circuit(false);
+        assert_eq!(report, None);
+        let (_, report) = circuit(true);
+        assert_eq!(report.map(|report| report.merged), Some(1));
+    }
+}
@@ -25,9 +25,10 @@ use crate::hash::merkle_tree::MerkleCap;
 use crate::iop::ext_target::ExtensionTarget;
 use crate::iop::target::Target;
 use crate::plonk::circuit_data::{CommonCircuitData, VerifierOnlyCircuitData};
-use crate::plonk::config::{GenericConfig, Hasher};
+use crate::plonk::config::{AlgebraicHasher, GenericConfig, Hasher};
 use crate::plonk::verifier::verify_with_challenges;
-use crate::util::serialization::{Buffer, Read, Write};
+use crate::util::serialization::envelope::{self, PayloadKind};
+use crate::util::serialization::{Read, Write};
 
 #[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
 #[serde(bound = "")]
@@ -107,22 +108,41 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
         C::InnerHasher::hash_no_pad(&self.public_inputs)
     }
 
+    /// Serializes this proof of the circuit described by `common_data`, recording the
+    /// fingerprint of `common_data` so that the proof is not read back for another circuit.
+    pub fn to_bytes_with_common_data(&self, common_data: &CommonCircuitData<F, D>) -> Vec<u8> {
+        self.seal(&common_data.fingerprint())
+    }
+
+    #[deprecated(
+        note = "the circuit of the proof is not recorded; use `to_bytes_with_common_data` instead"
+    )]
     pub fn to_bytes(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        buffer
-            .write_proof_with_public_inputs(self)
-            .expect("Writing to a byte-vector cannot fail.");
-        buffer
+        self.seal(&[])
+    }
+
+    fn seal(&self, fingerprint: &[u8]) -> Vec<u8> {
+        envelope::seal(
+            PayloadKind::ProofWithPublicInputs,
+            &C::id(),
+            fingerprint,
+            |buffer| buffer.write_proof_with_public_inputs(self),
+        )
+        .expect("Writing to a byte-vector cannot fail.")
     }
 
     pub fn from_bytes(
         bytes: Vec<u8>,
         common_data: &CommonCircuitData<F, D>,
     ) -> anyhow::Result<Self> {
-        let mut buffer = Buffer::new(&bytes);
-        let proof = buffer
-            .read_proof_with_public_inputs(common_data)
-            .map_err(anyhow::Error::msg)?;
+        let proof = envelope::unseal(
+            &bytes,
+            PayloadKind::ProofWithPublicInputs,
+            &C::id(),
+            |buffer| buffer.read_proof_with_public_inputs(common_data),
+            |_| common_data.fingerprint(),
+        )
+        .map_err(anyhow::Error::msg)?;
         Ok(proof)
     }
 }
@@ -206,7 +226,10 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
         self,
         verifier_data: &VerifierOnlyCircuitData<C, D>,
         common_data: &CommonCircuitData<F, D>,
-    ) -> anyhow::Result<()> {
+    ) -> anyhow::Result<()>
+    where
+        C::InnerHasher: AlgebraicHasher<F>,
+    {
         ensure!(
             self.public_inputs.len() == common_data.num_public_inputs,
             "Number of public inputs doesn't match circuit data."
@@ -236,22 +259,41 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
         C::InnerHasher::hash_no_pad(&self.public_inputs)
     }
 
+    /// Serializes this proof of the circuit described by `common_data`, recording the
+    /// fingerprint of `common_data` so that the proof is not read back for another circuit.
+    pub fn to_bytes_with_common_data(&self, common_data: &CommonCircuitData<F, D>) -> Vec<u8> {
+        self.seal(&common_data.fingerprint())
+    }
+
+    #[deprecated(
+        note = "the circuit of the proof is not recorded; use `to_bytes_with_common_data` instead"
+    )]
     pub fn to_bytes(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        buffer
-            .write_compressed_proof_with_public_inputs(self)
-            .expect("Writing to a byte-vector cannot fail.");
-        buffer
+        self.seal(&[])
+    }
+
+    fn seal(&self, fingerprint: &[u8]) -> Vec<u8> {
+        envelope::seal(
+            PayloadKind::CompressedProofWithPublicInputs,
+            &C::id(),
+            fingerprint,
+            |buffer| buffer.write_compressed_proof_with_public_inputs(self),
+        )
+        .expect("Writing to a byte-vector cannot fail.")
     }
 
     pub fn from_bytes(
         bytes: Vec<u8>,
         common_data: &CommonCircuitData<F, D>,
     ) -> anyhow::Result<Self> {
-        let mut buffer = Buffer::new(&bytes);
-        let proof = buffer
-            .read_compressed_proof_with_public_inputs(common_data)
-            .map_err(anyhow::Error::msg)?;
+        let proof = envelope::unseal(
+            &bytes,
+            PayloadKind::CompressedProofWithPublicInputs,
+            &C::id(),
+            |buffer| buffer.read_compressed_proof_with_public_inputs(common_data),
+            |_| common_data.fingerprint(),
+        )
+        .map_err(anyhow::Error::msg)?;
         Ok(proof)
     }
 }
@@ -563,4 +605,89 @@ mod tests {
         verify(proof, &data.verifier_only, &data.common)?;
         data.verify_compressed(compressed_proof)
     }
+
+    // The errors of `serde_json` and `serde_cbor` only convert to `anyhow::Error` with `std`.
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_serde_round_trip() -> Result<()> {
+        use super::{CompressedProofWithPublicInputs, ProofWithPublicInputs};
+        use crate::field::types::Field;
+        use crate::iop::witness::WitnessWrite;
+        use crate::plonk::circuit_data::VerifierCircuitData;
+        use crate::util::serialization::{DefaultGateSerializer, GateRegistry};
+
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let mut builder = CircuitBuilder::<F, D>::new(CircuitConfig::standard_recursion_config());
+        let lut_index = builder.add_lookup_table_from_fn(|x| x.wrapping_mul(3), &[1, 2, 3]);
+        let x = builder.add_virtual_target();
+        let y = builder.add_lookup_from_index(x, lut_index);
+        let z = builder.mul(x, y);
+        builder.register_public_input(z);
+        let data = builder.build::<C>();
+
+        let mut pw = PartialWitness::new();
+        pw.set_target(x, F::TWO);
+        let proof = data.prove(pw)?;
+        let compressed_proof = data.compress(proof.clone())?;
+        let verifier_data = data.verifier_data();
+
+        let json = serde_json::to_string(&proof)?;
+        let proof_from_json: ProofWithPublicInputs<F, C, D> = serde_json::from_str(&json)?;
+        let cbor = serde_cbor::to_vec(&proof)?;
+        let proof_from_cbor: ProofWithPublicInputs<F, C, D> = serde_cbor::from_slice(&cbor)?;
+        let proof_bytes = proof.to_bytes_with_common_data(&data.common);
+        assert_eq!(
+            proof_from_json.to_bytes_with_common_data(&data.common),
+            proof_bytes
+        );
+        assert_eq!(
+            proof_from_cbor.to_bytes_with_common_data(&data.common),
+            proof_bytes
+        );
+
+        let json = serde_json::to_string(&compressed_proof)?;
+        let compressed_proof_from_json: CompressedProofWithPublicInputs<F, C, D> =
+            serde_json::from_str(&json)?;
+        let cbor = serde_cbor::to_vec(&compressed_proof)?;
+        let compressed_proof_from_cbor: CompressedProofWithPublicInputs<F, C, D> =
+            serde_cbor::from_slice(&cbor)?;
+        let compressed_proof_bytes = compressed_proof.to_bytes_with_common_data(&data.common);
+        assert_eq!(
+            compressed_proof_from_json.to_bytes_with_common_data(&data.common),
+            compressed_proof_bytes
+        );
+        assert_eq!(
+            compressed_proof_from_cbor.to_bytes_with_common_data(&data.common),
+            compressed_proof_bytes
+        );
+
+        let json = serde_json::to_string(&verifier_data)?;
+        let verifier_data_from_json: VerifierCircuitData<F, C, D> = serde_json::from_str(&json)?;
+        let cbor = serde_cbor::to_vec(&verifier_data)?;
+        let verifier_data_from_cbor: VerifierCircuitData<F, C, D> = serde_cbor::from_slice(&cbor)?;
+        let bytes = verifier_data.to_bytes(&DefaultGateSerializer).unwrap();
+        assert_eq!(
+            verifier_data_from_json
+                .to_bytes(&DefaultGateSerializer)
+                .unwrap(),
+            bytes
+        );
+        assert_eq!(
+            verifier_data_from_cbor
+                .to_bytes(&DefaultGateSerializer)
+                .unwrap(),
+            bytes
+        );
+        verifier_data_from_json.verify(proof_from_json)?;
+        verifier_data_from_cbor.verify_compressed(compressed_proof_from_cbor)?;
+
+        // Gates missing from the registry can't be serialized.
+        assert!(serde_json::to_string(&data.common).is_ok());
+        assert!(data.common.to_serializable(&GateRegistry::new()).is_err());
+
+        Ok(())
+    }
 }
@@ -20,13 +20,13 @@ use crate::gates::lookup_table::LookupTableGate;
 use crate::gates::selectors::LookupSelectors;
 use crate::hash::hash_types::RichField;
 use crate::iop::challenger::Challenger;
-use crate::iop::generator::generate_partial_witness;
+use crate::iop::generator::generate_partial_witness_with_timing;
 use crate::iop::target::Target;
 use crate::iop::witness::{MatrixWitness, PartialWitness, PartitionWitness, Witness, WitnessWrite};
 use crate::plonk::circuit_builder::NUM_COINS_LOOKUP;
 use crate::plonk::circuit_data::{CommonCircuitData, ProverOnlyCircuitData};
-use crate::plonk::config::{GenericConfig, Hasher};
-use crate::plonk::plonk_common::PlonkOracle;
+use crate::plonk::config::{AlgebraicHasher, GenericConfig, Hasher};
+use crate::plonk::plonk_common::{reduce_with_powers, PlonkOracle};
 use crate::plonk::proof::{OpeningSet, Proof, ProofWithPublicInputs};
 use crate::plonk::vanishing_poly::{eval_vanishing_poly_base_batch, get_lut_poly};
 use crate::plonk::vars::EvaluationVarsBaseBatch;
@@ -56,24 +56,26 @@ pub fn set_lookup_wires<
         },
     ) in prover_data.lookup_rows.iter().enumerate()
     {
-        let lut_len = common_data.luts[lut_index].len();
-        let num_entries = LookupGate::num_slots(&common_data.config);
-        let num_lut_entries = LookupTableGate::num_slots(&common_data.config);
+        let lut = &common_data.luts[lut_index];
+        let lut_len = lut.len();
+        let width = common_data.lookup_width();
+        let num_entries = LookupGate::num_slots(&common_data.config, width);
+        let num_lut_entries = LookupTableGate::num_slots(&common_data.config, width);
 
         // Compute multiplicities.
         let mut multiplicities = vec![0; lut_len];
 
-        let table_value_to_idx: HashMap<u16, usize> = common_data.luts[lut_index]
-            .iter()
-            .enumerate()
-            .map(|(i, (inp_target, _))| (*inp_target, i))
-            .collect();
+        let table_row_to_idx: HashMap<&[u64], usize> =
+            lut.rows().enumerate().map(|(i, row)| (row, i)).collect();
 
-        for (inp_target, _) in prover_data.lut_to_lookups[lut_index].iter() {
-            let inp_value = pw.get_target(*inp_target);
-            let idx = table_value_to_idx
-                .get(&u16::try_from(inp_value.to_canonical_u64()).unwrap())
-                .unwrap();
+        for lookup in prover_data.lut_to_lookups[lut_index].iter() {
+            let row = lookup
+                .iter()
+                .map(|&t| pw.get_target(t).to_canonical_u64())
+                .collect::<Vec<_>>();
+            let idx = table_row_to_idx
+                .get(row.as_slice())
+                .expect("Lookup not found in the lookup table");
 
             multiplicities[*idx] += 1;
         }
@@ -82,14 +84,14 @@ pub fn set_lookup_wires<
         let remaining_slots = (num_entries
             - (prover_data.lut_to_lookups[lut_index].len() % num_entries))
             % num_entries;
-        let (first_inp_value, first_out_value) = common_data.luts[lut_index][0];
         for slot in (num_entries - remaining_slots)..num_entries {
-            let inp_target =
-                Target::wire(last_lut_gate - 1, LookupGate::wire_ith_looking_inp(slot));
-            let out_target =
-                Target::wire(last_lut_gate - 1, LookupGate::wire_ith_looking_out(slot));
-            pw.set_target(inp_target, F::from_canonical_u16(first_inp_value));
-            pw.set_target(out_target, F::from_canonical_u16(first_out_value));
+            for (col, &value) in lut.row(0).iter().enumerate() {
+                let target = Target::wire(
+                    last_lut_gate - 1,
+                    LookupGate::wire_ith_looking_value(width, slot, col),
+                );
+                pw.set_target(target, F::from_canonical_u64(value));
+            }
 
             multiplicities[0] += 1;
         }
@@ -99,7 +101,7 @@ pub fn set_lookup_wires<
             let row = first_lut_gate - lut_entry / num_lut_entries;
             let col = lut_entry % num_lut_entries;
 
-            let mul_target = Target::wire(row, LookupTableGate::wire_ith_multiplicity(col));
+            let mul_target = Target::wire(row, LookupTableGate::wire_ith_multiplicity(width, col));
 
             pw.set_target(
                 mul_target,
@@ -117,12 +119,12 @@ pub fn prove<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D:
 ) -> Result<ProofWithPublicInputs<F, C, D>>
 where
     C::Hasher: Hasher<F>,
-    C::InnerHasher: Hasher<F>,
+    C::InnerHasher: AlgebraicHasher<F>,
 {
     let partition_witness = timed!(
         timing,
         &format!("run {} generators", prover_data.generators.len()),
-        generate_partial_witness(inputs, prover_data, common_data)
+        generate_partial_witness_with_timing(inputs, prover_data, common_data, timing)
     );
 
     prove_with_partition_witness(prover_data, common_data, partition_witness, timing)
@@ -140,7 +142,7 @@ pub fn prove_with_partition_witness<
 ) -> Result<ProofWithPublicInputs<F, C, D>>
 where
     C::Hasher: Hasher<F>,
-    C::InnerHasher: Hasher<F>,
+    C::InnerHasher: AlgebraicHasher<F>,
 {
     let has_lookup = !common_data.luts.is_empty();
     let config = &common_data.config;
@@ -460,10 +462,11 @@ fn compute_lookup_polys<
     common_data: &CommonCircuitData<F, D>,
 ) -> Vec<PolynomialValues<F>> {
     let degree = common_data.degree();
-    let num_lu_slots = LookupGate::num_slots(&common_data.config);
+    let width = common_data.lookup_width();
+    let num_lu_slots = LookupGate::num_slots(&common_data.config, width);
     let max_lookup_degree = common_data.config.max_quotient_degree_factor - 1;
     let num_partial_lookups = ceil_div_usize(num_lu_slots, max_lookup_degree);
-    let num_lut_slots = LookupTableGate::num_slots(&common_data.config);
+    let num_lut_slots = LookupTableGate::num_slots(&common_data.config, width);
     let max_lookup_table_degree = ceil_div_usize(num_lut_slots, num_partial_lookups);
 
     // First poly is RE, the rest are partial SLDCs.
@@ -483,10 +486,14 @@ fn compute_lookup_polys<
             // Get combos for Sum.
             let looked_combos: Vec<F> = (0..num_lut_slots)
                 .map(|s| {
-                    let looked_inp = witness.get_wire(row, LookupTableGate::wire_ith_looked_inp(s));
-                    let looked_out = witness.get_wire(row, LookupTableGate::wire_ith_looked_out(s));
+                    let looked_values = (0..width).map(|col| {
+                        witness.get_wire(row, LookupTableGate::wire_ith_looked_value(width, s, col))
+                    });
 
-                    looked_inp + deltas[LookupChallenges::ChallengeA as usize] * looked_out
+                    reduce_with_powers(
+                        &looked_values.collect::<Vec<_>>(),
+                        deltas[LookupChallenges::ChallengeA as usize],
+                    )
                 })
                 .collect();
             // Get (alpha - combo).
@@ -499,10 +506,14 @@ fn compute_lookup_polys<
             // Get lookup combos, used to check the well formation of the LUT.
             let lookup_combos: Vec<F> = (0..num_lut_slots)
                 .map(|s| {
-                    let looked_inp = witness.get_wire(row, LookupTableGate::wire_ith_looked_inp(s));
-                    let looked_out = witness.get_wire(row, LookupTableGate::wire_ith_looked_out(s));
+                    let looked_values = (0..width).map(|col| {
+                        witness.get_wire(row, LookupTableGate::wire_ith_looked_value(width, s, col))
+                    });
 
-                    looked_inp + deltas[LookupChallenges::ChallengeB as usize] * looked_out
+                    reduce_with_powers(
+                        &looked_values.collect::<Vec<_>>(),
+                        deltas[LookupChallenges::ChallengeB as usize],
+                    )
                 })
                 .collect();
 
@@ -524,7 +535,8 @@ fn compute_lookup_polys<
                 let sum = (slot * max_lookup_table_degree
                     ..min((slot + 1) * max_lookup_table_degree, num_lut_slots))
                     .fold(prev, |acc, s| {
-                        acc + witness.get_wire(row, LookupTableGate::wire_ith_multiplicity(s))
+                        acc + witness
+                            .get_wire(row, LookupTableGate::wire_ith_multiplicity(width, s))
                             * looked_combo_inverses[s]
                     });
                 final_poly_vecs[slot + 1].values[row] = sum;
@@ -536,10 +548,14 @@ fn compute_lookup_polys<
             // Get looking combos.
             let looking_combos: Vec<F> = (0..num_lu_slots)
                 .map(|s| {
-                    let looking_in = witness.get_wire(row, LookupGate::wire_ith_looking_inp(s));
-                    let looking_out = witness.get_wire(row, LookupGate::wire_ith_looking_out(s));
+                    let looking_values = (0..width).map(|col| {
+                        witness.get_wire(row, LookupGate::wire_ith_looking_value(width, s, col))
+                    });
 
-                    looking_in + deltas[LookupChallenges::ChallengeA as usize] * looking_out
+                    reduce_with_powers(
+                        &looking_values.collect::<Vec<_>>(),
+                        deltas[LookupChallenges::ChallengeA as usize],
+                    )
                 })
                 .collect();
             // Get (alpha - combo).
@@ -615,7 +631,10 @@ fn compute_quotient_polys<
     gammas: &[F],
     deltas: &[F],
     alphas: &[F],
-) -> Vec<PolynomialCoeffs<F>> {
+) -> Vec<PolynomialCoeffs<F>>
+where
+    C::InnerHasher: AlgebraicHasher<F>,
+{
     let num_challenges = common_data.config.num_challenges;
 
     let has_lookup = common_data.num_lookup_polys != 0;
@@ -644,7 +663,8 @@ fn compute_quotient_polys<
     // and are the same each time in check_lookup_constraints_batched.
     // lut_poly_evals[i][j] gives the eval for the i'th challenge and the j'th lookup table
     let lut_re_poly_evals: Vec<Vec<F>> = if has_lookup {
-        let num_lut_slots = LookupTableGate::num_slots(&common_data.config);
+        let num_lut_slots =
+            LookupTableGate::num_slots(&common_data.config, common_data.lookup_width());
         (0..num_challenges)
             .map(move |i| {
                 let cur_deltas = &deltas[NUM_COINS_LOOKUP * i..NUM_COINS_LOOKUP * (i + 1)];
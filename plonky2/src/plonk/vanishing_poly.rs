@@ -20,7 +20,9 @@ use crate::iop::target::Target;
 use crate::plonk::circuit_builder::CircuitBuilder;
 use crate::plonk::circuit_data::CommonCircuitData;
 use crate::plonk::plonk_common;
-use crate::plonk::plonk_common::eval_l_0_circuit;
+use crate::plonk::plonk_common::{
+    eval_l_0_circuit, reduce_with_powers, reduce_with_powers_ext_circuit,
+};
 use crate::plonk::vars::{EvaluationTargets, EvaluationVars, EvaluationVarsBaseBatch};
 use crate::util::partial_products::{check_partial_products, check_partial_products_circuit};
 use crate::util::reducing::ReducingFactorTarget;
@@ -37,8 +39,12 @@ pub(crate) fn get_lut_poly<F: RichField + Extendable<D>, const D: usize>(
     let b = deltas[LookupChallenges::ChallengeB as usize];
     let mut coeffs = Vec::with_capacity(common_data.luts[lut_index].len());
     let n = common_data.luts[lut_index].len();
-    for (input, output) in common_data.luts[lut_index].iter() {
-        coeffs.push(F::from_canonical_u16(*input) + b * F::from_canonical_u16(*output));
+    for row in common_data.luts[lut_index].rows() {
+        let row = row
+            .iter()
+            .map(|&x| F::from_canonical_u64(x))
+            .collect::<Vec<_>>();
+        coeffs.push(reduce_with_powers(&row, b));
     }
     coeffs.append(&mut vec![F::ZERO; degree - n]);
     coeffs.reverse();
@@ -328,8 +334,9 @@ pub(crate) fn eval_vanishing_poly_base_batch<F: RichField + Extendable<D>, const
 ///
 /// There are three polynomials to check:
 /// - RE ensures the well formation of lookup tables;
-/// - Sum is a running sum of m_i/(X - (input_i + a * output_i)) where (input_i, output_i) are input pairs in the lookup table (LUT);
-/// - LDC is a running sum of 1/(X - (input_i + a * output_i)) where (input_i, output_i) are input pairs that look in the LUT.
+/// - Sum is a running sum of m_i/(X - combo_i) where combo_i = sum_j a^j * v_{i, j} compresses the i-th row of the lookup table (LUT),
+///   made of its inputs followed by its outputs and padded with zeros;
+/// - LDC is a running sum of 1/(X - combo_i) where combo_i compresses the i-th row that looks in the LUT in the same way.
 /// Sum and LDC are broken down in partial polynomials to lower the constraint degree, similarly to the permutation argument.
 /// They also share the same partial SLDC polynomials, so that the last SLDC value is Sum(end) - LDC(end). The final constraint
 /// Sum(end) = LDC(end) becomes simply SLDC(end) = 0, and we can remove the LDC initial constraint.
@@ -341,8 +348,9 @@ pub fn check_lookup_constraints<F: RichField + Extendable<D>, const D: usize>(
     lookup_selectors: &[F::Extension],
     deltas: &[F; 4],
 ) -> Vec<F::Extension> {
-    let num_lu_slots = LookupGate::num_slots(&common_data.config);
-    let num_lut_slots = LookupTableGate::num_slots(&common_data.config);
+    let width = common_data.lookup_width();
+    let num_lu_slots = LookupGate::num_slots(&common_data.config, width);
+    let num_lut_slots = LookupTableGate::num_slots(&common_data.config, width);
     let lu_degree = common_data.quotient_degree_factor - 1;
     let num_sldc_polys = local_lookup_zs.len() - 1;
     let lut_degree = ceil_div_usize(num_lut_slots, num_sldc_polys);
@@ -361,29 +369,27 @@ pub fn check_lookup_constraints<F: RichField + Extendable<D>, const D: usize>(
     let delta_challenge_b = F::Extension::from(deltas[LookupChallenges::ChallengeB as usize]);
 
     // Compute all current looked and looking combos, i.e. the combos we need for the SLDC polynomials.
+    let looked_values = |s: usize| {
+        (0..width)
+            .map(|col| vars.local_wires[LookupTableGate::wire_ith_looked_value(width, s, col)])
+            .collect::<Vec<_>>()
+    };
     let current_looked_combos: Vec<F::Extension> = (0..num_lut_slots)
-        .map(|s| {
-            let input_wire = vars.local_wires[LookupTableGate::wire_ith_looked_inp(s)];
-            let output_wire = vars.local_wires[LookupTableGate::wire_ith_looked_out(s)];
-            input_wire + delta_challenge_a * output_wire
-        })
+        .map(|s| reduce_with_powers(&looked_values(s), delta_challenge_a))
         .collect();
 
     let current_looking_combos: Vec<F::Extension> = (0..num_lu_slots)
         .map(|s| {
-            let input_wire = vars.local_wires[LookupGate::wire_ith_looking_inp(s)];
-            let output_wire = vars.local_wires[LookupGate::wire_ith_looking_out(s)];
-            input_wire + delta_challenge_a * output_wire
+            let looking_values = (0..width)
+                .map(|col| vars.local_wires[LookupGate::wire_ith_looking_value(width, s, col)])
+                .collect::<Vec<_>>();
+            reduce_with_powers(&looking_values, delta_challenge_a)
         })
         .collect();
 
     // Compute all current lookup combos, i.e. the combos used to check that the LUT is correct.
     let current_lookup_combos: Vec<F::Extension> = (0..num_lut_slots)
-        .map(|s| {
-            let input_wire = vars.local_wires[LookupTableGate::wire_ith_looked_inp(s)];
-            let output_wire = vars.local_wires[LookupTableGate::wire_ith_looked_out(s)];
-            input_wire + delta_challenge_b * output_wire
-        })
+        .map(|s| reduce_with_powers(&looked_values(s), delta_challenge_b))
         .collect();
 
     // Check last LDC constraint.
@@ -480,7 +486,8 @@ pub fn check_lookup_constraints<F: RichField + Extendable<D>, const D: usize>(
         let lut_sum_prods_with_mul = (poly * lut_degree
             ..min((poly + 1) * lut_degree, num_lut_slots))
             .fold(F::Extension::ZERO, |acc, i| {
-                acc + vars.local_wires[LookupTableGate::wire_ith_multiplicity(i)] * lut_prod_i(i)
+                acc + vars.local_wires[LookupTableGate::wire_ith_multiplicity(width, i)]
+                    * lut_prod_i(i)
             });
 
         // The previous element is the previous poly of the current row or the last poly of the next row.
@@ -515,8 +522,9 @@ pub fn check_lookup_constraints_batch<F: RichField + Extendable<D>, const D: usi
     deltas: &[F; 4],
     lut_re_poly_evals: &[F],
 ) -> Vec<F> {
-    let num_lu_slots = LookupGate::num_slots(&common_data.config);
-    let num_lut_slots = LookupTableGate::num_slots(&common_data.config);
+    let width = common_data.lookup_width();
+    let num_lu_slots = LookupGate::num_slots(&common_data.config, width);
+    let num_lut_slots = LookupTableGate::num_slots(&common_data.config, width);
     let lu_degree = common_data.quotient_degree_factor - 1;
     let num_sldc_polys = local_lookup_zs.len() - 1;
     let lut_degree = ceil_div_usize(num_lut_slots, num_sldc_polys);
@@ -532,28 +540,39 @@ pub fn check_lookup_constraints_batch<F: RichField + Extendable<D>, const D: usi
     let z_gx_lookup_sldcs = &next_lookup_zs[1..num_sldc_polys + 1];
 
     // Compute all current looked and looking combos, i.e. the combos we need for the SLDC polynomials.
+    let looked_values = |s: usize| {
+        (0..width)
+            .map(|col| vars.local_wires[LookupTableGate::wire_ith_looked_value(width, s, col)])
+            .collect::<Vec<_>>()
+    };
     let current_looked_combos: Vec<F> = (0..num_lut_slots)
         .map(|s| {
-            let input_wire = vars.local_wires[LookupTableGate::wire_ith_looked_inp(s)];
-            let output_wire = vars.local_wires[LookupTableGate::wire_ith_looked_out(s)];
-            input_wire + deltas[LookupChallenges::ChallengeA as usize] * output_wire
+            reduce_with_powers(
+                &looked_values(s),
+                deltas[LookupChallenges::ChallengeA as usize],
+            )
         })
         .collect();
 
     let current_looking_combos: Vec<F> = (0..num_lu_slots)
         .map(|s| {
-            let input_wire = vars.local_wires[LookupGate::wire_ith_looking_inp(s)];
-            let output_wire = vars.local_wires[LookupGate::wire_ith_looking_out(s)];
-            input_wire + deltas[LookupChallenges::ChallengeA as usize] * output_wire
+            let looking_values = (0..width)
+                .map(|col| vars.local_wires[LookupGate::wire_ith_looking_value(width, s, col)])
+                .collect::<Vec<_>>();
+            reduce_with_powers(
+                &looking_values,
+                deltas[LookupChallenges::ChallengeA as usize],
+            )
         })
         .collect();
 
     // Compute all current lookup combos, i.e. the combos used to check that the LUT is correct.
     let current_lookup_combos: Vec<F> = (0..num_lut_slots)
         .map(|s| {
-            let input_wire = vars.local_wires[LookupTableGate::wire_ith_looked_inp(s)];
-            let output_wire = vars.local_wires[LookupTableGate::wire_ith_looked_out(s)];
-            input_wire + deltas[LookupChallenges::ChallengeB as usize] * output_wire
+            reduce_with_powers(
+                &looked_values(s),
+                deltas[LookupChallenges::ChallengeB as usize],
+            )
         })
         .collect();
 
@@ -633,7 +652,8 @@ pub fn check_lookup_constraints_batch<F: RichField + Extendable<D>, const D: usi
         let lut_sum_prods_with_mul = (poly * lut_degree
             ..min((poly + 1) * lut_degree, num_lut_slots))
             .fold(F::ZERO, |acc, i| {
-                acc + vars.local_wires[LookupTableGate::wire_ith_multiplicity(i)] * lut_prod_i(i)
+                acc + vars.local_wires[LookupTableGate::wire_ith_multiplicity(width, i)]
+                    * lut_prod_i(i)
             });
 
         // The previous element is the previous poly of the current row or the last poly of the next row.
@@ -757,11 +777,21 @@ pub(crate) fn get_lut_poly_circuit<F: RichField + Extendable<D>, const D: usize>
     let b = deltas[LookupChallenges::ChallengeB as usize];
     let delta = deltas[LookupChallenges::ChallengeDelta as usize];
     let n = common_data.luts[lut_index].len();
+    let b_powers = (0..common_data.luts[lut_index].width())
+        .scan(builder.one(), |power, _| {
+            let current = *power;
+            *power = builder.mul(*power, b);
+            Some(current)
+        })
+        .collect::<Vec<_>>();
     let mut coeffs: Vec<Target> = common_data.luts[lut_index]
-        .iter()
-        .map(|(input, output)| {
-            let temp = builder.mul_const(F::from_canonical_u16(*output), b);
-            builder.add_const(temp, F::from_canonical_u16(*input))
+        .rows()
+        .map(|row| {
+            row.iter()
+                .zip(&b_powers)
+                .fold(builder.zero(), |acc, (&x, &b_power)| {
+                    builder.mul_const_add(F::from_canonical_u64(x), b_power, acc)
+                })
         })
         .collect();
     for _ in n..degree {
@@ -927,8 +957,9 @@ pub fn check_lookup_constraints_circuit<F: RichField + Extendable<D>, const D: u
     lookup_selectors: &[ExtensionTarget<D>],
     deltas: &[Target],
 ) -> Vec<ExtensionTarget<D>> {
-    let num_lu_slots = LookupGate::num_slots(&common_data.config);
-    let num_lut_slots = LookupTableGate::num_slots(&common_data.config);
+    let width = common_data.lookup_width();
+    let num_lu_slots = LookupGate::num_slots(&common_data.config, width);
+    let num_lut_slots = LookupTableGate::num_slots(&common_data.config, width);
     let lu_degree = common_data.quotient_degree_factor - 1;
     let num_sldc_polys = local_lookup_zs.len() - 1;
     let lut_degree = ceil_div_usize(num_lut_slots, num_sldc_polys);
@@ -950,25 +981,29 @@ pub fn check_lookup_constraints_circuit<F: RichField + Extendable<D>, const D: u
         .collect::<Vec<_>>();
 
     // Computing all current looked and looking combos, i.e. the combos we need for the SLDC polynomials.
+    let looked_values = |s: usize| {
+        (0..width)
+            .map(|col| vars.local_wires[LookupTableGate::wire_ith_looked_value(width, s, col)])
+            .collect::<Vec<_>>()
+    };
     let current_looked_combos = (0..num_lut_slots)
         .map(|s| {
-            let input_wire = vars.local_wires[LookupTableGate::wire_ith_looked_inp(s)];
-            let output_wire = vars.local_wires[LookupTableGate::wire_ith_looked_out(s)];
-            builder.mul_add_extension(
-                ext_deltas[LookupChallenges::ChallengeA as usize],
-                output_wire,
-                input_wire,
+            reduce_with_powers_ext_circuit(
+                builder,
+                &looked_values(s),
+                deltas[LookupChallenges::ChallengeA as usize],
             )
         })
         .collect::<Vec<_>>();
     let current_looking_combos = (0..num_lu_slots)
         .map(|s| {
-            let input_wire = vars.local_wires[LookupGate::wire_ith_looking_inp(s)];
-            let output_wire = vars.local_wires[LookupGate::wire_ith_looking_out(s)];
-            builder.mul_add_extension(
-                ext_deltas[LookupChallenges::ChallengeA as usize],
-                output_wire,
-                input_wire,
+            let looking_values = (0..width)
+                .map(|col| vars.local_wires[LookupGate::wire_ith_looking_value(width, s, col)])
+                .collect::<Vec<_>>();
+            reduce_with_powers_ext_circuit(
+                builder,
+                &looking_values,
+                deltas[LookupChallenges::ChallengeA as usize],
             )
         })
         .collect::<Vec<_>>();
@@ -994,12 +1029,10 @@ pub fn check_lookup_constraints_circuit<F: RichField + Extendable<D>, const D: u
     // Computing all current lookup combos, i.e. the combos used to check that the LUT is correct.
     let current_lookup_combos = (0..num_lut_slots)
         .map(|s| {
-            let input_wire = vars.local_wires[LookupTableGate::wire_ith_looked_inp(s)];
-            let output_wire = vars.local_wires[LookupTableGate::wire_ith_looked_out(s)];
-            builder.mul_add_extension(
-                ext_deltas[LookupChallenges::ChallengeB as usize],
-                output_wire,
-                input_wire,
+            reduce_with_powers_ext_circuit(
+                builder,
+                &looked_values(s),
+                deltas[LookupChallenges::ChallengeB as usize],
             )
         })
         .collect::<Vec<_>>();
@@ -1097,7 +1130,7 @@ pub fn check_lookup_constraints_circuit<F: RichField + Extendable<D>, const D: u
                 }
                 builder.mul_add_extension(
                     prod_i,
-                    vars.local_wires[LookupTableGate::wire_ith_multiplicity(i)],
+                    vars.local_wires[LookupTableGate::wire_ith_multiplicity(width, i)],
                     acc,
                 )
             });
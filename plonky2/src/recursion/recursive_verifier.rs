@@ -21,6 +21,7 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         inner_common_data: &CommonCircuitData<F, D>,
     ) where
         C::Hasher: AlgebraicHasher<F>,
+        C::InnerHasher: AlgebraicHasher<F>,
     {
         assert_eq!(
             proof_with_pis.public_inputs.len(),
@@ -192,7 +193,7 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
 #[cfg(test)]
 mod tests {
     #[cfg(not(feature = "std"))]
-    use alloc::{sync::Arc, vec};
+    use alloc::{sync::Arc, vec, vec::Vec};
     #[cfg(feature = "std")]
     use std::sync::Arc;
 
@@ -277,6 +278,22 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_recursive_verifier_multi_column_luts() -> Result<()> {
+        init_logger();
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        let config = CircuitConfig::standard_recursion_config();
+
+        let (proof, vd, common_data) = dummy_multi_column_luts_proof::<F, C, D>(&config)?;
+        let (proof, vd, common_data) =
+            recursive_proof::<F, C, C, D>(proof, vd, common_data, &config, None, true, true)?;
+        test_serialization(&proof, &vd, &common_data)?;
+
+        Ok(())
+    }
+
     #[test]
     fn test_recursive_recursive_verifier() -> Result<()> {
         init_logger();
@@ -415,7 +432,10 @@ mod tests {
     fn dummy_proof<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>(
         config: &CircuitConfig,
         num_dummy_gates: u64,
-    ) -> Result<Proof<F, C, D>> {
+    ) -> Result<Proof<F, C, D>>
+    where
+        C::InnerHasher: AlgebraicHasher<F>,
+    {
         let mut builder = CircuitBuilder::<F, D>::new(config.clone());
         for _ in 0..num_dummy_gates {
             builder.add_gate(NoopGate, vec![]);
@@ -437,7 +457,10 @@ mod tests {
     >(
         config: &CircuitConfig,
         num_dummy_gates: u64,
-    ) -> Result<Proof<F, C, D>> {
+    ) -> Result<Proof<F, C, D>>
+    where
+        C::InnerHasher: AlgebraicHasher<F>,
+    {
         let mut builder = CircuitBuilder::<F, D>::new(config.clone());
         let initial_a = builder.add_virtual_target();
         let initial_b = builder.add_virtual_target();
@@ -448,8 +471,8 @@ mod tests {
         let tip5_table = TIP5_TABLE.to_vec();
         let table: LookupTable = Arc::new((0..256).zip_eq(tip5_table).collect());
 
-        let out_a = table[look_val_a].1;
-        let out_b = table[look_val_b].1;
+        let out_a = table.row(look_val_a)[1];
+        let out_b = table.row(look_val_b)[1];
 
         let tip5_index = builder.add_lookup_table_from_pairs(table);
 
@@ -474,12 +497,12 @@ mod tests {
         data.verify(proof.clone())?;
 
         assert!(
-            proof.public_inputs[2] == F::from_canonical_u16(out_a),
+            proof.public_inputs[2] == F::from_canonical_u64(out_a),
             "First lookup, at index {} in the Tip5 table gives an incorrect output.",
             proof.public_inputs[0]
         );
         assert!(
-            proof.public_inputs[3] == F::from_canonical_u16(out_b),
+            proof.public_inputs[3] == F::from_canonical_u64(out_b),
             "Second lookup, at index {} in the Tip5 table gives an incorrect output.",
             proof.public_inputs[1]
         );
@@ -494,7 +517,10 @@ mod tests {
         const D: usize,
     >(
         config: &CircuitConfig,
-    ) -> Result<Proof<F, C, D>> {
+    ) -> Result<Proof<F, C, D>>
+    where
+        C::InnerHasher: AlgebraicHasher<F>,
+    {
         let mut builder = CircuitBuilder::<F, D>::new(config.clone());
         let initial_a = builder.add_virtual_target();
         let initial_b = builder.add_virtual_target();
@@ -564,6 +590,58 @@ mod tests {
         Ok((proof, data.verifier_only, data.common))
     }
 
+    /// Creates a dummy lookup proof which uses a LUT with two inputs, a LUT with one input and one
+    /// output, and a range check LUT without outputs.
+    fn dummy_multi_column_luts_proof<
+        F: RichField + Extendable<D>,
+        C: GenericConfig<D, F = F>,
+        const D: usize,
+    >(
+        config: &CircuitConfig,
+    ) -> Result<Proof<F, C, D>>
+    where
+        C::InnerHasher: AlgebraicHasher<F>,
+    {
+        let mut builder = CircuitBuilder::<F, D>::new(config.clone());
+
+        // (a, b) -> a xor b over 4-bit limbs.
+        let xor_rows = (0..16u64)
+            .cartesian_product(0..16u64)
+            .map(|(a, b)| {
+                vec![a, b, a ^ b]
+                    .into_iter()
+                    .map(F::from_canonical_u64)
+                    .collect()
+            })
+            .collect::<Vec<_>>();
+        let xor_index = builder.add_lookup_table_from_rows(2, 1, &xor_rows);
+        let table: LookupTable = Arc::new((0..256).zip_eq(TIP5_TABLE).collect());
+        let tip5_index = builder.add_lookup_table_from_pairs(table);
+        let nibble_values = (0..16).map(F::from_canonical_u64).collect::<Vec<_>>();
+        let nibble_index = builder.add_lookup_table_from_values(&nibble_values);
+
+        let a = builder.add_virtual_target();
+        let b = builder.add_virtual_target();
+        builder.add_lookup(&[a], nibble_index);
+        let a_xor_b = builder.add_lookup(&[a, b], xor_index)[0];
+        let output = builder.add_lookup_from_index(a_xor_b, tip5_index);
+        builder.register_public_input(output);
+
+        let mut pw = PartialWitness::new();
+        pw.set_target(a, F::from_canonical_u64(9));
+        pw.set_target(b, F::from_canonical_u64(5));
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+        assert_eq!(
+            proof.public_inputs[0],
+            F::from_canonical_u16(TIP5_TABLE[9 ^ 5])
+        );
+        data.verify(proof.clone())?;
+
+        Ok((proof, data.verifier_only, data.common))
+    }
+
     /// Creates a dummy proof which has more than 256 lookups to one LUT.
     fn dummy_too_many_rows_proof<
         F: RichField + Extendable<D>,
@@ -571,7 +649,10 @@ mod tests {
         const D: usize,
     >(
         config: &CircuitConfig,
-    ) -> Result<Proof<F, C, D>> {
+    ) -> Result<Proof<F, C, D>>
+    where
+        C::InnerHasher: AlgebraicHasher<F>,
+    {
         let mut builder = CircuitBuilder::<F, D>::new(config.clone());
 
         let initial_a = builder.add_virtual_target();
@@ -583,8 +664,8 @@ mod tests {
         let tip5_table = TIP5_TABLE.to_vec();
         let table: LookupTable = Arc::new((0..256).zip_eq(tip5_table).collect());
 
-        let out_a = table[look_val_a].1;
-        let out_b = table[look_val_b].1;
+        let out_a = table.row(look_val_a)[1];
+        let out_b = table.row(look_val_b)[1];
 
         let tip5_index = builder.add_lookup_table_from_pairs(table);
         let output_b = builder.add_lookup_from_index(initial_b, tip5_index);
@@ -606,12 +687,12 @@ mod tests {
         let data = builder.build::<C>();
         let proof = data.prove(pw)?;
         assert!(
-            proof.public_inputs[2] == F::from_canonical_u16(out_b),
+            proof.public_inputs[2] == F::from_canonical_u64(out_b),
             "First lookup, at index {} in the Tip5 table gives an incorrect output.",
             proof.public_inputs[1]
         );
         assert!(
-            proof.public_inputs[3] == F::from_canonical_u16(out_a),
+            proof.public_inputs[3] == F::from_canonical_u64(out_a),
             "Lookups at index {} in the Tip5 table gives an incorrect output.",
             proof.public_inputs[0]
         );
@@ -636,6 +717,8 @@ mod tests {
     ) -> Result<Proof<F, C, D>>
     where
         InnerC::Hasher: AlgebraicHasher<F>,
+        InnerC::InnerHasher: AlgebraicHasher<F>,
+        C::InnerHasher: AlgebraicHasher<F>,
     {
         let mut builder = CircuitBuilder::<F, D>::new(config.clone());
         let mut pw = PartialWitness::new();
@@ -688,7 +771,7 @@ mod tests {
         vd: &VerifierOnlyCircuitData<C, D>,
         common_data: &CommonCircuitData<F, D>,
     ) -> Result<()> {
-        let proof_bytes = proof.to_bytes();
+        let proof_bytes = proof.to_bytes_with_common_data(common_data);
         info!("Proof length: {} bytes", proof_bytes.len());
         let proof_from_bytes = ProofWithPublicInputs::from_bytes(proof_bytes, common_data)?;
         assert_eq!(proof, &proof_from_bytes);
@@ -706,7 +789,7 @@ mod tests {
 
         assert_eq!(proof, &decompressed_compressed_proof);
 
-        let compressed_proof_bytes = compressed_proof.to_bytes();
+        let compressed_proof_bytes = compressed_proof.to_bytes_with_common_data(common_data);
         info!(
             "Compressed proof length: {} bytes",
             compressed_proof_bytes.len()
@@ -0,0 +1,15 @@
+//! Sumcheck protocol over multilinear polynomials.
+//!
+//! The prover convinces the verifier that `sum_{x in {0, 1}^n} g(p_1(x), ..., p_k(x))` equals a
+//! claimed value, where the `p_j` are multilinear and `g` is a polynomial of bounded total degree.
+//! Each round fixes the first remaining variable to a challenge drawn from the `Challenger`, so
+//! the protocol reduces the claim to a single evaluation of `g(p_1(r), ..., p_k(r))`, which the
+//! caller is responsible for checking, e.g. with a polynomial commitment opening or another
+//! sumcheck as in GKR.
+//!
+//! It provides both a native verifier and an in-circuit version for recursive proof composition.
+
+pub mod proof;
+pub mod prover;
+pub mod recursive_verifier;
+pub mod verifier;
@@ -0,0 +1,64 @@
+use alloc::vec::Vec;
+
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+use crate::field::extension::Extendable;
+use crate::hash::hash_types::RichField;
+use crate::iop::ext_target::ExtensionTarget;
+use crate::iop::witness::WitnessWrite;
+
+/// A sumcheck proof. Round `i` sends the univariate polynomial obtained by summing over all but the
+/// `i`-th variable, given by its evaluations at `0, 1, ..., degree`.
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
+#[serde(bound = "")]
+pub struct SumcheckProof<F: RichField + Extendable<D>, const D: usize> {
+    pub round_polys: Vec<Vec<F::Extension>>,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> SumcheckProof<F, D> {
+    pub fn num_vars(&self) -> usize {
+        self.round_polys.len()
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SumcheckProofTarget<const D: usize> {
+    pub round_polys: Vec<Vec<ExtensionTarget<D>>>,
+}
+
+impl<const D: usize> SumcheckProofTarget<D> {
+    pub fn num_vars(&self) -> usize {
+        self.round_polys.len()
+    }
+}
+
+/// What remains to be checked once the verifier has accepted every round: the summand
+/// `g(p_1(point), ..., p_k(point))` must equal `expected_evaluation`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SumcheckSubClaim<F: RichField + Extendable<D>, const D: usize> {
+    pub point: Vec<F::Extension>,
+    pub expected_evaluation: F::Extension,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SumcheckSubClaimTarget<const D: usize> {
+    pub point: Vec<ExtensionTarget<D>>,
+    pub expected_evaluation: ExtensionTarget<D>,
+}
+
+/// Set the targets in a `SumcheckProofTarget` to their corresponding values in a `SumcheckProof`.
+pub fn set_sumcheck_proof_target<F, W, const D: usize>(
+    witness: &mut W,
+    proof_target: &SumcheckProofTarget<D>,
+    proof: &SumcheckProof<F, D>,
+) where
+    F: RichField + Extendable<D>,
+    W: WitnessWrite<F> + ?Sized,
+{
+    for (targets, values) in proof_target.round_polys.iter().zip_eq(&proof.round_polys) {
+        for (&t, &x) in targets.iter().zip_eq(values) {
+            witness.set_extension_target(t, x);
+        }
+    }
+}
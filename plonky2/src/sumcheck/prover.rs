@@ -0,0 +1,88 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use itertools::Itertools;
+
+use crate::field::extension::Extendable;
+use crate::field::polynomial::MultilinearPolynomial;
+use crate::field::types::Field;
+use crate::hash::hash_types::RichField;
+use crate::iop::challenger::Challenger;
+use crate::plonk::config::Hasher;
+use crate::sumcheck::proof::SumcheckProof;
+
+/// The output of `prove_sumcheck`.
+#[derive(Clone, Debug)]
+pub struct SumcheckProverOutput<F: RichField + Extendable<D>, const D: usize> {
+    pub proof: SumcheckProof<F, D>,
+    /// The challenges of each round, which form the point the claim is reduced to.
+    pub point: Vec<F::Extension>,
+    /// The evaluations of the input polynomials at `point`.
+    pub final_evals: Vec<F::Extension>,
+}
+
+/// Proves that `sum_{x in {0, 1}^n} combine(p_1(x), ..., p_k(x))` equals the sum claimed by the
+/// caller, where `degree` bounds the total degree of `combine`.
+///
+/// The claimed sum is not observed here; the caller should observe it, along with any commitments
+/// to the polynomials, before invoking the prover.
+pub fn prove_sumcheck<F, H, const D: usize>(
+    polys: Vec<MultilinearPolynomial<F::Extension>>,
+    combine: impl Fn(&[F::Extension]) -> F::Extension,
+    degree: usize,
+    challenger: &mut Challenger<F, H>,
+) -> SumcheckProverOutput<F, D>
+where
+    F: RichField + Extendable<D>,
+    H: Hasher<F>,
+{
+    assert!(!polys.is_empty(), "No polynomials to sum over");
+    let num_vars = polys[0].num_vars();
+    assert!(
+        polys.iter().all(|p| p.num_vars() == num_vars),
+        "Polynomials have different numbers of variables"
+    );
+
+    let mut polys = polys;
+    let mut round_polys = Vec::with_capacity(num_vars);
+    let mut point = Vec::with_capacity(num_vars);
+    for _ in 0..num_vars {
+        let round_poly = round_polynomial(&polys, &combine, degree);
+        challenger.observe_extension_elements(&round_poly);
+        round_polys.push(round_poly);
+
+        let r = challenger.get_extension_challenge::<D>();
+        polys = polys.iter().map(|p| p.fix_first_variable(r)).collect();
+        point.push(r);
+    }
+
+    SumcheckProverOutput {
+        proof: SumcheckProof { round_polys },
+        point,
+        final_evals: polys.iter().map(|p| p.evals[0]).collect(),
+    }
+}
+
+/// Evaluates `t -> sum_{x in {0, 1}^(m - 1)} combine(p_1(t, x), ..., p_k(t, x))` at
+/// `t = 0, 1, ..., degree`.
+fn round_polynomial<F: Field>(
+    polys: &[MultilinearPolynomial<F>],
+    combine: &impl Fn(&[F]) -> F,
+    degree: usize,
+) -> Vec<F> {
+    let half = polys[0].evals.len() / 2;
+    let mut round_poly = vec![F::ZERO; degree + 1];
+    let mut values = vec![F::ZERO; polys.len()];
+    let mut steps = vec![F::ZERO; polys.len()];
+    for i in 0..half {
+        for (j, p) in polys.iter().enumerate() {
+            values[j] = p.evals[i];
+            steps[j] = p.evals[half + i] - p.evals[i];
+        }
+        for eval in round_poly.iter_mut() {
+            *eval += combine(&values);
+            values.iter_mut().zip_eq(&steps).for_each(|(v, &s)| *v += s);
+        }
+    }
+    round_poly
+}
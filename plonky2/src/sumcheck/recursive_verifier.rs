@@ -0,0 +1,180 @@
+use alloc::format;
+use alloc::vec::Vec;
+
+use crate::field::extension::Extendable;
+use crate::field::interpolation::barycentric_weights;
+use crate::hash::hash_types::RichField;
+use crate::iop::challenger::RecursiveChallenger;
+use crate::iop::ext_target::ExtensionTarget;
+use crate::plonk::circuit_builder::CircuitBuilder;
+use crate::plonk::config::AlgebraicHasher;
+use crate::sumcheck::proof::{SumcheckProofTarget, SumcheckSubClaimTarget};
+use crate::with_context;
+
+impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
+    pub fn add_virtual_sumcheck_proof(
+        &mut self,
+        num_vars: usize,
+        degree: usize,
+    ) -> SumcheckProofTarget<D> {
+        SumcheckProofTarget {
+            round_polys: (0..num_vars)
+                .map(|_| self.add_virtual_extension_targets(degree + 1))
+                .collect(),
+        }
+    }
+
+    /// In-circuit version of `verify_sumcheck`. The caller must check the returned
+    /// `SumcheckSubClaimTarget` against the summand itself.
+    pub fn verify_sumcheck<H: AlgebraicHasher<F>>(
+        &mut self,
+        claimed_sum: ExtensionTarget<D>,
+        proof: &SumcheckProofTarget<D>,
+        degree: usize,
+        challenger: &mut RecursiveChallenger<F, H, D>,
+    ) -> SumcheckSubClaimTarget<D> {
+        let domain = (0..=degree as u64)
+            .map(|t| (F::from_canonical_u64(t), F::ZERO))
+            .collect::<Vec<_>>();
+        let weights = barycentric_weights(&domain);
+
+        let mut expected = claimed_sum;
+        let mut point = Vec::with_capacity(proof.num_vars());
+        for (i, round_poly) in proof.round_polys.iter().enumerate() {
+            assert_eq!(
+                round_poly.len(),
+                degree + 1,
+                "Sumcheck round polynomial has the wrong degree."
+            );
+            with_context!(self, &format!("check sumcheck round {i}"), {
+                let sum = self.add_extension(round_poly[0], round_poly[1]);
+                self.connect_extension(sum, expected);
+            });
+            challenger.observe_extension_elements(round_poly);
+
+            let r = challenger.get_extension_challenge(self);
+            expected = with_context!(
+                self,
+                "evaluate sumcheck round polynomial",
+                self.interpolate_round_polynomial(round_poly, &domain, &weights, r)
+            );
+            point.push(r);
+        }
+
+        SumcheckSubClaimTarget {
+            point,
+            expected_evaluation: expected,
+        }
+    }
+
+    /// Evaluates at `r` the polynomial taking the values `evals` on the points of `domain`, as
+    /// `sum_i w_i y_i prod_{j != i} (r - x_j)`. Unlike the barycentric formula, this needs no
+    /// division, so it holds even when `r` lies in the domain.
+    fn interpolate_round_polynomial(
+        &mut self,
+        evals: &[ExtensionTarget<D>],
+        domain: &[(F, F)],
+        weights: &[F],
+        r: ExtensionTarget<D>,
+    ) -> ExtensionTarget<D> {
+        let diffs = domain
+            .iter()
+            .map(|&(x, _)| self.add_const_extension(r, -x))
+            .collect::<Vec<_>>();
+
+        let one = self.one_extension();
+        let mut suffix_products = Vec::with_capacity(diffs.len());
+        let mut acc = one;
+        for &d in diffs.iter().rev() {
+            suffix_products.push(acc);
+            acc = self.mul_extension(acc, d);
+        }
+        suffix_products.reverse();
+
+        let mut prefix_product = one;
+        let mut result = self.zero_extension();
+        for ((&y, &w), (&d, &suffix_product)) in evals
+            .iter()
+            .zip(weights)
+            .zip(diffs.iter().zip(&suffix_products))
+        {
+            let others = self.mul_extension(prefix_product, suffix_product);
+            result = self.arithmetic_extension(w, F::ONE, others, y, result);
+            prefix_product = self.mul_extension(prefix_product, d);
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(not(feature = "std"))]
+    use alloc::vec::Vec;
+
+    use anyhow::Result;
+
+    use crate::field::polynomial::MultilinearPolynomial;
+    use crate::field::types::Sample;
+    use crate::iop::challenger::{Challenger, RecursiveChallenger};
+    use crate::iop::witness::{PartialWitness, WitnessWrite};
+    use crate::plonk::circuit_builder::CircuitBuilder;
+    use crate::plonk::circuit_data::CircuitConfig;
+    use crate::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+    use crate::sumcheck::proof::set_sumcheck_proof_target;
+    use crate::sumcheck::prover::prove_sumcheck;
+
+    #[test]
+    fn test_recursive_sumcheck() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        type FF = <C as GenericConfig<D>>::FE;
+        type H = <C as GenericConfig<D>>::Hasher;
+
+        let num_vars = 5;
+        let degree = 2;
+        let polys = (0..2)
+            .map(|_| MultilinearPolynomial::new(FF::rand_vec(1 << num_vars)))
+            .collect::<Vec<_>>();
+        let claimed_sum: FF = polys[0]
+            .evals
+            .iter()
+            .zip(&polys[1].evals)
+            .map(|(&a, &b)| a * b)
+            .sum();
+        let output = prove_sumcheck::<F, H, D>(
+            polys,
+            |v: &[FF]| v[0] * v[1],
+            degree,
+            &mut Challenger::<F, H>::new(),
+        );
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let claimed_sum_target = builder.add_virtual_extension_target();
+        let proof_target = builder.add_virtual_sumcheck_proof(num_vars, degree);
+        let mut challenger = RecursiveChallenger::<F, H, D>::new(&mut builder);
+        let sub_claim =
+            builder.verify_sumcheck(claimed_sum_target, &proof_target, degree, &mut challenger);
+
+        for (&t, &r) in sub_claim.point.iter().zip(&output.point) {
+            let r = builder.constant_extension(r);
+            builder.connect_extension(t, r);
+        }
+        let final_evals = output
+            .final_evals
+            .iter()
+            .map(|&e| builder.constant_extension(e))
+            .collect::<Vec<_>>();
+        let expected = builder.mul_extension(final_evals[0], final_evals[1]);
+        builder.connect_extension(sub_claim.expected_evaluation, expected);
+
+        let mut pw = PartialWitness::new();
+        pw.set_extension_target(claimed_sum_target, claimed_sum);
+        set_sumcheck_proof_target(&mut pw, &proof_target, &output.proof);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+        data.verify(proof)
+    }
+}
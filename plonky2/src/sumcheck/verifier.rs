@@ -0,0 +1,136 @@
+use alloc::vec::Vec;
+
+use anyhow::{ensure, Result};
+
+use crate::field::extension::Extendable;
+use crate::field::interpolation::{barycentric_weights, interpolate};
+use crate::field::types::Field;
+use crate::hash::hash_types::RichField;
+use crate::iop::challenger::Challenger;
+use crate::plonk::config::Hasher;
+use crate::sumcheck::proof::{SumcheckProof, SumcheckSubClaim};
+
+/// Checks every round of a sumcheck proof for `claimed_sum`, and returns the claim it reduces to.
+/// The caller must check the returned `SumcheckSubClaim` against the summand itself.
+pub fn verify_sumcheck<F, H, const D: usize>(
+    claimed_sum: F::Extension,
+    proof: &SumcheckProof<F, D>,
+    num_vars: usize,
+    degree: usize,
+    challenger: &mut Challenger<F, H>,
+) -> Result<SumcheckSubClaim<F, D>>
+where
+    F: RichField + Extendable<D>,
+    H: Hasher<F>,
+{
+    ensure!(
+        proof.num_vars() == num_vars,
+        "Sumcheck proof has the wrong number of rounds."
+    );
+
+    let domain = (0..=degree as u64)
+        .map(|t| (F::Extension::from_canonical_u64(t), F::Extension::ZERO))
+        .collect::<Vec<_>>();
+    let weights = barycentric_weights(&domain);
+
+    let mut expected = claimed_sum;
+    let mut point = Vec::with_capacity(num_vars);
+    for round_poly in &proof.round_polys {
+        ensure!(
+            round_poly.len() == degree + 1,
+            "Sumcheck round polynomial has the wrong degree."
+        );
+        ensure!(
+            round_poly[0] + round_poly[1] == expected,
+            "Sumcheck round polynomial is inconsistent with the claimed sum."
+        );
+        challenger.observe_extension_elements(round_poly);
+
+        let r = challenger.get_extension_challenge::<D>();
+        let points = domain
+            .iter()
+            .zip(round_poly)
+            .map(|(&(t, _), &y)| (t, y))
+            .collect::<Vec<_>>();
+        expected = interpolate(&points, r, &weights);
+        point.push(r);
+    }
+
+    Ok(SumcheckSubClaim {
+        point,
+        expected_evaluation: expected,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(not(feature = "std"))]
+    use alloc::vec;
+
+    use anyhow::Result;
+
+    use super::*;
+    use crate::field::polynomial::MultilinearPolynomial;
+    use crate::field::types::Sample;
+    use crate::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+    use crate::sumcheck::prover::prove_sumcheck;
+
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+    type FF = <C as GenericConfig<D>>::FE;
+    type H = <C as GenericConfig<D>>::Hasher;
+
+    #[test]
+    fn test_sumcheck_product() -> Result<()> {
+        let num_vars = 6;
+        let polys = (0..3)
+            .map(|_| MultilinearPolynomial::new(FF::rand_vec(1 << num_vars)))
+            .collect::<Vec<_>>();
+        let combine = |v: &[FF]| v[0] * v[1] * v[2];
+        let claimed_sum: FF = (0..1 << num_vars)
+            .map(|i| combine(&polys.iter().map(|p| p.evals[i]).collect::<Vec<_>>()))
+            .sum();
+
+        let output =
+            prove_sumcheck::<F, H, D>(polys.clone(), combine, 3, &mut Challenger::<F, H>::new());
+        let sub_claim = verify_sumcheck(
+            claimed_sum,
+            &output.proof,
+            num_vars,
+            3,
+            &mut Challenger::<F, H>::new(),
+        )?;
+
+        assert_eq!(sub_claim.point, output.point);
+        let final_evals = polys
+            .iter()
+            .map(|p| p.evaluate(&sub_claim.point))
+            .collect::<Vec<_>>();
+        assert_eq!(final_evals, output.final_evals);
+        assert_eq!(sub_claim.expected_evaluation, combine(&final_evals));
+        Ok(())
+    }
+
+    #[test]
+    fn test_sumcheck_wrong_sum() {
+        let num_vars = 4;
+        let poly = MultilinearPolynomial::new(FF::rand_vec(1 << num_vars));
+        let claimed_sum: FF = poly.sum_over_hypercube();
+
+        let output = prove_sumcheck::<F, H, D>(
+            vec![poly],
+            |v: &[FF]| v[0],
+            1,
+            &mut Challenger::<F, H>::new(),
+        );
+        assert!(verify_sumcheck(
+            claimed_sum + FF::ONE,
+            &output.proof,
+            num_vars,
+            1,
+            &mut Challenger::<F, H>::new(),
+        )
+        .is_err());
+    }
+}
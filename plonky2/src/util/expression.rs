@@ -0,0 +1,190 @@
+//! Symbolic polynomial expressions over variables and fixed field elements, which can be evaluated
+//! natively on any values supporting field arithmetic, and in a circuit.
+//!
+//! What variables stand for depends on where expressions are used. For instance, they are the
+//! wires of an operation and the constants of the gate in the constraints of an
+//! [`ExpressionGate`](crate::gates::expression::ExpressionGate), and the columns of a trace in the
+//! constraints of a STARK.
+
+use alloc::boxed::Box;
+use core::ops::{Add, Mul, Neg, Sub};
+
+use crate::field::extension::{Extendable, FieldExtension};
+use crate::field::types::Field;
+use crate::hash::hash_types::RichField;
+use crate::iop::ext_target::ExtensionTarget;
+use crate::plonk::circuit_builder::CircuitBuilder;
+
+/// A polynomial expression in variables of type `V`, with coefficients in `F`. Expressions can be
+/// combined with `+`, `-`, `*` and unary `-`.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Expression<F: Field, V> {
+    Var(V),
+    /// A fixed field element.
+    Literal(F),
+    Add(Box<Self>, Box<Self>),
+    Sub(Box<Self>, Box<Self>),
+    Mul(Box<Self>, Box<Self>),
+    Neg(Box<Self>),
+}
+
+impl<F: Field, V> Expression<F, V> {
+    pub const fn var(var: V) -> Self {
+        Self::Var(var)
+    }
+
+    pub const fn literal(value: F) -> Self {
+        Self::Literal(value)
+    }
+
+    pub fn square(self) -> Self
+    where
+        V: Clone,
+    {
+        self.clone() * self
+    }
+
+    /// The degree of this expression, given the degree of each variable. This is a syntactic
+    /// bound, which may be larger than the actual degree of the polynomial.
+    pub fn degree(&self, var_degree: &impl Fn(&V) -> usize) -> usize {
+        match self {
+            Self::Var(v) => var_degree(v),
+            Self::Literal(_) => 0,
+            Self::Add(a, b) | Self::Sub(a, b) => a.degree(var_degree).max(b.degree(var_degree)),
+            Self::Mul(a, b) => a.degree(var_degree) + b.degree(var_degree),
+            Self::Neg(a) => a.degree(var_degree),
+        }
+    }
+
+    /// Calls `f` on every occurrence of a variable, from left to right.
+    pub fn for_each_var(&self, f: &mut impl FnMut(&V)) {
+        match self {
+            Self::Var(v) => f(v),
+            Self::Literal(_) => {}
+            Self::Add(a, b) | Self::Sub(a, b) | Self::Mul(a, b) => {
+                a.for_each_var(f);
+                b.for_each_var(f);
+            }
+            Self::Neg(a) => a.for_each_var(f),
+        }
+    }
+
+    /// Evaluates this expression, given the values of variables and literals.
+    pub fn eval<T>(&self, var: &impl Fn(&V) -> T, literal: &impl Fn(F) -> T) -> T
+    where
+        T: Copy + Add<Output = T> + Sub<Output = T> + Mul<Output = T> + Neg<Output = T>,
+    {
+        match self {
+            Self::Var(v) => var(v),
+            Self::Literal(x) => literal(*x),
+            Self::Add(a, b) => a.eval(var, literal) + b.eval(var, literal),
+            Self::Sub(a, b) => a.eval(var, literal) - b.eval(var, literal),
+            Self::Mul(a, b) => a.eval(var, literal) * b.eval(var, literal),
+            Self::Neg(a) => -a.eval(var, literal),
+        }
+    }
+}
+
+impl<F: RichField, V> Expression<F, V> {
+    /// Evaluates this expression in a circuit, given the targets of variables.
+    pub fn eval_circuit<const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        var: &impl Fn(&V) -> ExtensionTarget<D>,
+    ) -> ExtensionTarget<D>
+    where
+        F: Extendable<D>,
+    {
+        match self {
+            Self::Var(v) => var(v),
+            Self::Literal(x) => builder.constant_extension(F::Extension::from_basefield(*x)),
+            Self::Add(a, b) => {
+                let a = a.eval_circuit(builder, var);
+                let b = b.eval_circuit(builder, var);
+                builder.add_extension(a, b)
+            }
+            Self::Sub(a, b) => {
+                let a = a.eval_circuit(builder, var);
+                let b = b.eval_circuit(builder, var);
+                builder.sub_extension(a, b)
+            }
+            Self::Mul(a, b) => {
+                let a = a.eval_circuit(builder, var);
+                let b = b.eval_circuit(builder, var);
+                builder.mul_extension(a, b)
+            }
+            Self::Neg(a) => {
+                let a = a.eval_circuit(builder, var);
+                builder.mul_const_extension(F::NEG_ONE, a)
+            }
+        }
+    }
+}
+
+impl<F: Field, V> Default for Expression<F, V> {
+    fn default() -> Self {
+        Self::Literal(F::ZERO)
+    }
+}
+
+impl<F: Field, V> From<F> for Expression<F, V> {
+    fn from(value: F) -> Self {
+        Self::Literal(value)
+    }
+}
+
+impl<F: Field, V> Add for Expression<F, V> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self::Add(Box::new(self), Box::new(rhs))
+    }
+}
+
+impl<F: Field, V> Add<F> for Expression<F, V> {
+    type Output = Self;
+
+    fn add(self, rhs: F) -> Self {
+        self + Self::Literal(rhs)
+    }
+}
+
+impl<F: Field, V> Sub for Expression<F, V> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self::Sub(Box::new(self), Box::new(rhs))
+    }
+}
+
+impl<F: Field, V> Sub<F> for Expression<F, V> {
+    type Output = Self;
+
+    fn sub(self, rhs: F) -> Self {
+        self - Self::Literal(rhs)
+    }
+}
+
+impl<F: Field, V> Mul for Expression<F, V> {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        Self::Mul(Box::new(self), Box::new(rhs))
+    }
+}
+
+impl<F: Field, V> Mul<F> for Expression<F, V> {
+    type Output = Self;
+
+    fn mul(self, rhs: F) -> Self {
+        self * Self::Literal(rhs)
+    }
+}
+
+impl<F: Field, V> Neg for Expression<F, V> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::Neg(Box::new(self))
+    }
+}
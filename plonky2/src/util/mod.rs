@@ -1,7 +1,9 @@
 //! Utility module for helper methods and plonky2 serialization logic.
 
+use alloc::string::String;
 #[cfg(not(feature = "std"))]
 use alloc::vec::Vec;
+use core::any::type_name;
 
 use plonky2_maybe_rayon::*;
 #[doc(inline)]
@@ -12,6 +14,7 @@ use crate::field::types::Field;
 
 pub(crate) mod context_tree;
 pub(crate) mod partial_products;
+pub mod expression;
 pub mod reducing;
 pub mod serialization;
 pub mod strided_view;
@@ -40,6 +43,25 @@ pub(crate) const fn reverse_bits(n: usize, num_bits: usize) -> usize {
         .0
 }
 
+/// The name of `T` with the module paths of it and its type parameters removed.
+pub(crate) fn short_type_name<T: ?Sized>() -> String {
+    let full_name = type_name::<T>();
+    let mut name = String::with_capacity(full_name.len());
+    // The start of the path currently being read, which is dropped when a `::` follows it.
+    let mut path_start = 0;
+    for c in full_name.chars() {
+        if c == ':' {
+            name.truncate(path_start);
+        } else {
+            name.push(c);
+            if !(c.is_alphanumeric() || c == '_') {
+                path_start = name.len();
+            }
+        }
+    }
+    name
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -47,6 +69,22 @@ mod tests {
     use alloc::vec;
 
     use super::*;
+    use crate::field::extension::quadratic::QuadraticExtension;
+    use crate::field::goldilocks_field::GoldilocksField;
+    use crate::iop::target::Target;
+
+    #[test]
+    fn test_short_type_name() {
+        assert_eq!(short_type_name::<Target>(), "Target");
+        assert_eq!(
+            short_type_name::<QuadraticExtension<GoldilocksField>>(),
+            "QuadraticExtension<GoldilocksField>"
+        );
+        assert_eq!(
+            short_type_name::<(Vec<Target>, [u8; 4])>(),
+            "(Vec<Target>, [u8; 4])"
+        );
+    }
 
     #[test]
     fn test_reverse_bits() {
@@ -0,0 +1,402 @@
+//! A versioned, self-describing envelope around serialized circuit data and proofs.
+//!
+//! An envelope consists of
+//! - the magic bytes [`MAGIC`],
+//! - the format version, as a `u32`,
+//! - the [`PayloadKind`], as a `u8`,
+//! - the id of the configuration, as a length-prefixed string,
+//! - a digest of the circuit the payload belongs to, as length-prefixed bytes,
+//! - the payload, as length-prefixed bytes,
+//! - a checksum of all of the above, made of the first 8 bytes of its Keccak-256 hash.
+//!
+//! Bytes which do not start with [`MAGIC`] were written before envelopes were introduced, and are
+//! read as a bare payload in the layout of that time, which differs in how lookups are stored.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use keccak_hash::keccak;
+
+use crate::util::serialization::{Buffer, IoError, IoResult, Read, Remaining, Write};
+
+/// The bytes every envelope starts with.
+pub const MAGIC: [u8; 8] = *b"PLONKY2\0";
+
+/// The version of the format written by this crate. Envelopes of this or any earlier version can
+/// be read.
+pub const FORMAT_VERSION: u32 = 1;
+
+const CHECKSUM_LEN: usize = 8;
+
+/// The type of value held by an envelope.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum PayloadKind {
+    CircuitData,
+    ProverCircuitData,
+    VerifierCircuitData,
+    CommonCircuitData,
+    ProofWithPublicInputs,
+    CompressedProofWithPublicInputs,
+    ProverOnlyCircuitData,
+}
+
+impl PayloadKind {
+    const ALL: [Self; 7] = [
+        Self::CircuitData,
+        Self::ProverCircuitData,
+        Self::VerifierCircuitData,
+        Self::CommonCircuitData,
+        Self::ProofWithPublicInputs,
+        Self::CompressedProofWithPublicInputs,
+        Self::ProverOnlyCircuitData,
+    ];
+
+    const fn tag(self) -> u8 {
+        self as u8
+    }
+
+    fn from_tag(tag: u8) -> Option<Self> {
+        Self::ALL.get(tag as usize).copied()
+    }
+}
+
+/// The metadata stored in an envelope ahead of its payload.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EnvelopeHeader {
+    pub version: u32,
+    pub kind: PayloadKind,
+    /// The `GenericConfig::id` of the configuration used, or for `CommonCircuitData`, which does
+    /// not depend on the hashers, the extension field written as `GF(p^D)`.
+    pub config_id: String,
+    /// The bytes of the circuit digest for payloads which include it, or of
+    /// `CommonCircuitData::fingerprint` for `CommonCircuitData` and proofs. It is empty for
+    /// proofs written without their common data.
+    pub digest: Vec<u8>,
+}
+
+impl EnvelopeHeader {
+    /// Reads the header of the envelope in `bytes`, checking its checksum, or returns `None` if
+    /// `bytes` were written without an envelope.
+    pub fn read(bytes: &[u8]) -> IoResult<Option<Self>> {
+        Ok(open(bytes)?.map(|(header, _)| header))
+    }
+}
+
+/// Writes an envelope around the payload written by `write_payload`.
+pub(crate) fn seal(
+    kind: PayloadKind,
+    config_id: &str,
+    digest: &[u8],
+    write_payload: impl FnOnce(&mut Vec<u8>) -> IoResult<()>,
+) -> IoResult<Vec<u8>> {
+    let mut payload = Vec::new();
+    write_payload(&mut payload)?;
+
+    let mut bytes = Vec::with_capacity(payload.len() + 64);
+    bytes.write_all(&MAGIC)?;
+    bytes.write_u32(FORMAT_VERSION)?;
+    bytes.write_u8(kind.tag())?;
+    bytes.write_str(config_id)?;
+    write_bytes(&mut bytes, digest)?;
+    write_bytes(&mut bytes, &payload)?;
+    let checksum = checksum(&bytes);
+    bytes.write_all(&checksum)?;
+    Ok(bytes)
+}
+
+/// Reads the value of the given kind from `bytes`, which may or may not have an envelope, with
+/// `read_payload`. If there is an envelope, it must match `kind` and `config_id`, the payload
+/// must be read entirely, and the digest it records, if any, must equal the one returned by
+/// `digest` for the value read.
+pub(crate) fn unseal<T>(
+    bytes: &[u8],
+    kind: PayloadKind,
+    config_id: &str,
+    read_payload: impl FnOnce(&mut Buffer) -> IoResult<T>,
+    digest: impl FnOnce(&T) -> Vec<u8>,
+) -> IoResult<T> {
+    let Some((header, payload)) = open(bytes)? else {
+        return read_payload(&mut Buffer::with_legacy_lookups(bytes));
+    };
+
+    if header.kind != kind {
+        log::error!(
+            "attempted to read {:?} from serialized {:?}",
+            kind,
+            header.kind
+        );
+        return Err(IoError);
+    }
+    if header.config_id != config_id {
+        log::error!(
+            "attempted to read {:?} serialized with configuration `{}` using configuration `{}`",
+            kind,
+            header.config_id,
+            config_id
+        );
+        return Err(IoError);
+    }
+
+    let mut buffer = Buffer::new(payload);
+    let value = read_payload(&mut buffer)?;
+    if !buffer.is_empty() {
+        log::error!(
+            "{} bytes of serialized {:?} were left unread, so it was likely written for another circuit",
+            buffer.remaining(),
+            kind
+        );
+        return Err(IoError);
+    }
+    if !header.digest.is_empty() && header.digest != digest(&value) {
+        log::error!("serialized {:?} does not match its digest", kind);
+        return Err(IoError);
+    }
+
+    Ok(value)
+}
+
+/// Splits the envelope in `bytes` into its header and payload, or returns `None` if `bytes` do
+/// not start with [`MAGIC`].
+fn open(bytes: &[u8]) -> IoResult<Option<(EnvelopeHeader, &[u8])>> {
+    if !bytes.starts_with(&MAGIC) {
+        return Ok(None);
+    }
+    let Some(checked_len) = bytes.len().checked_sub(CHECKSUM_LEN) else {
+        log::error!("serialized envelope is too short");
+        return Err(IoError);
+    };
+    let (checked, expected_checksum) = bytes.split_at(checked_len);
+    if checksum(checked) != expected_checksum {
+        log::error!("serialized envelope has an invalid checksum");
+        return Err(IoError);
+    }
+
+    let mut buffer = Buffer::new(&checked[MAGIC.len()..]);
+    let version = buffer.read_u32()?;
+    if version == 0 || version > FORMAT_VERSION {
+        log::error!(
+            "serialized envelope has version {}, but only versions up to {} are supported",
+            version,
+            FORMAT_VERSION
+        );
+        return Err(IoError);
+    }
+    let tag = buffer.read_u8()?;
+    let Some(kind) = PayloadKind::from_tag(tag) else {
+        log::error!("serialized envelope has unknown payload kind {}", tag);
+        return Err(IoError);
+    };
+    let config_id = buffer.read_string()?;
+    let digest = read_bytes(&mut buffer)?.to_vec();
+    let payload = read_bytes(&mut buffer)?;
+    if !buffer.is_empty() {
+        return Err(IoError);
+    }
+
+    let header = EnvelopeHeader {
+        version,
+        kind,
+        config_id,
+        digest,
+    };
+    Ok(Some((header, payload)))
+}
+
+fn checksum(bytes: &[u8]) -> [u8; CHECKSUM_LEN] {
+    let mut checksum = [0; CHECKSUM_LEN];
+    checksum.copy_from_slice(&keccak(bytes).0[..CHECKSUM_LEN]);
+    checksum
+}
+
+fn write_bytes(buffer: &mut Vec<u8>, bytes: &[u8]) -> IoResult<()> {
+    buffer.write_usize(bytes.len())?;
+    buffer.write_all(bytes)
+}
+
+fn read_bytes<'a>(buffer: &mut Buffer<'a>) -> IoResult<&'a [u8]> {
+    let len = buffer.read_usize()?;
+    let bytes = buffer.unread_bytes();
+    if bytes.len() < len {
+        return Err(IoError);
+    }
+    buffer.pos += len;
+    Ok(&bytes[..len])
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(not(feature = "std"))]
+    use alloc::vec;
+
+    use anyhow::Result;
+
+    use super::*;
+    use crate::field::types::{Field, Sample};
+    use crate::iop::witness::{PartialWitness, WitnessWrite};
+    use crate::plonk::circuit_builder::CircuitBuilder;
+    use crate::plonk::circuit_data::{
+        CircuitConfig, CircuitData, CommonCircuitData, VerifierCircuitData,
+    };
+    use crate::plonk::config::{
+        GenericConfig, GenericHashOut, KeccakGoldilocksConfig, PoseidonGoldilocksConfig,
+    };
+    use crate::plonk::proof::ProofWithPublicInputs;
+    use crate::util::serialization::{DefaultGateSerializer, DefaultGeneratorSerializer};
+
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+
+    /// Proves knowledge of `num_factors` numbers whose product is a public input.
+    fn product_proof(
+        num_factors: usize,
+    ) -> Result<(CircuitData<F, C, D>, ProofWithPublicInputs<F, C, D>)> {
+        let mut builder = CircuitBuilder::<F, D>::new(CircuitConfig::standard_recursion_config());
+        let factors = builder.add_virtual_targets(num_factors);
+        let product = builder.mul_many(&factors);
+        builder.register_public_input(product);
+        let data = builder.build::<C>();
+
+        let mut pw = PartialWitness::new();
+        for &factor in &factors {
+            pw.set_target(factor, F::rand());
+        }
+        let proof = data.prove(pw)?;
+        Ok((data, proof))
+    }
+
+    fn generator_serializer() -> DefaultGeneratorSerializer<C, D> {
+        DefaultGeneratorSerializer {
+            _phantom: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_envelope_round_trip() -> Result<()> {
+        let (data, proof) = product_proof(4)?;
+
+        let bytes = data
+            .to_bytes(&DefaultGateSerializer, &generator_serializer())
+            .unwrap();
+        let header = EnvelopeHeader::read(&bytes).unwrap().unwrap();
+        assert_eq!(header.version, FORMAT_VERSION);
+        assert_eq!(header.kind, PayloadKind::CircuitData);
+        assert_eq!(header.config_id, "PoseidonGoldilocksConfig");
+        assert_eq!(header.digest, data.verifier_only.circuit_digest.to_bytes());
+        let data_from_bytes =
+            CircuitData::from_bytes(&bytes, &DefaultGateSerializer, &generator_serializer())
+                .unwrap();
+        assert_eq!(data, data_from_bytes);
+
+        let common_bytes = data.common.to_bytes(&DefaultGateSerializer).unwrap();
+        let header = EnvelopeHeader::read(&common_bytes).unwrap().unwrap();
+        assert_eq!(header.config_id, "GF(18446744069414584321^2)");
+        assert_eq!(header.digest, data.common.fingerprint());
+        assert_eq!(
+            CommonCircuitData::from_bytes(common_bytes, &DefaultGateSerializer).unwrap(),
+            data.common
+        );
+
+        let proof_bytes = proof.to_bytes_with_common_data(&data.common);
+        let header = EnvelopeHeader::read(&proof_bytes).unwrap().unwrap();
+        assert_eq!(header.kind, PayloadKind::ProofWithPublicInputs);
+        assert_eq!(header.digest, data.common.fingerprint());
+        let proof_from_bytes = ProofWithPublicInputs::from_bytes(proof_bytes, &data.common)?;
+        assert_eq!(proof, proof_from_bytes);
+
+        // Proofs written without their common data record no digest, but can still be read.
+        #[allow(deprecated)]
+        let proof_bytes = proof.to_bytes();
+        assert!(EnvelopeHeader::read(&proof_bytes)
+            .unwrap()
+            .unwrap()
+            .digest
+            .is_empty());
+        assert_eq!(
+            ProofWithPublicInputs::from_bytes(proof_bytes, &data.common)?,
+            proof
+        );
+
+        data.verify(proof_from_bytes)
+    }
+
+    #[test]
+    fn test_legacy_bytes() -> Result<()> {
+        // Written before envelopes were introduced, for a circuit proving knowledge of 4 numbers
+        // whose product is a public input, with a proof for the factors 2, 3, 5 and 7.
+        let common_bytes = include_bytes!("fixtures/legacy_product_common.bin").to_vec();
+        let verifier_bytes = include_bytes!("fixtures/legacy_product_verifier.bin").to_vec();
+        let proof_bytes = include_bytes!("fixtures/legacy_product_proof.bin").to_vec();
+        assert_eq!(EnvelopeHeader::read(&common_bytes).unwrap(), None);
+        assert_eq!(EnvelopeHeader::read(&verifier_bytes).unwrap(), None);
+        assert_eq!(EnvelopeHeader::read(&proof_bytes).unwrap(), None);
+
+        let common_data = CommonCircuitData::from_bytes(common_bytes, &DefaultGateSerializer)
+            .map_err(anyhow::Error::msg)?;
+        let verifier_data =
+            VerifierCircuitData::<F, C, D>::from_bytes(verifier_bytes, &DefaultGateSerializer)
+                .map_err(anyhow::Error::msg)?;
+        assert_eq!(common_data, verifier_data.common);
+        let proof = ProofWithPublicInputs::from_bytes(proof_bytes, &common_data)?;
+        assert_eq!(proof.public_inputs, vec![F::from_canonical_u64(210)]);
+        verifier_data.verify(proof.clone())?;
+
+        // Once written again, they have an envelope.
+        let proof_bytes = proof.to_bytes_with_common_data(&common_data);
+        assert_eq!(
+            EnvelopeHeader::read(&proof_bytes).unwrap().unwrap().digest,
+            common_data.fingerprint()
+        );
+        assert_eq!(
+            ProofWithPublicInputs::from_bytes(proof_bytes, &common_data)?,
+            proof
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_mismatched_envelopes() -> Result<()> {
+        let (data, proof) = product_proof(4)?;
+        let (other_data, _) = product_proof(300)?;
+        let proof_bytes = proof.to_bytes_with_common_data(&data.common);
+
+        // A proof of another circuit.
+        assert!(ProofWithPublicInputs::<F, C, D>::from_bytes(
+            proof_bytes.clone(),
+            &other_data.common
+        )
+        .is_err());
+
+        // Another configuration.
+        assert!(
+            ProofWithPublicInputs::<F, KeccakGoldilocksConfig, D>::from_bytes(
+                proof_bytes.clone(),
+                &data.common
+            )
+            .is_err()
+        );
+
+        // Another kind of payload.
+        let common_bytes = data.common.to_bytes(&DefaultGateSerializer).unwrap();
+        assert!(ProofWithPublicInputs::<F, C, D>::from_bytes(common_bytes, &data.common).is_err());
+
+        // A corrupted payload.
+        let mut corrupted_bytes = proof_bytes.clone();
+        corrupted_bytes[100] ^= 1;
+        assert!(EnvelopeHeader::read(&corrupted_bytes).is_err());
+        assert!(
+            ProofWithPublicInputs::<F, C, D>::from_bytes(corrupted_bytes, &data.common).is_err()
+        );
+
+        // A newer version of the format.
+        let mut newer_bytes = proof_bytes;
+        newer_bytes[MAGIC.len()..MAGIC.len() + 4].copy_from_slice(&2u32.to_le_bytes());
+        let checksum_start = newer_bytes.len() - CHECKSUM_LEN;
+        let checksum = checksum(&newer_bytes[..checksum_start]);
+        newer_bytes[checksum_start..].copy_from_slice(&checksum);
+        assert!(EnvelopeHeader::read(&newer_bytes).is_err());
+
+        Ok(())
+    }
+}
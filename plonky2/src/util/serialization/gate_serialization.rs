@@ -1,13 +1,17 @@
 //! A module to help with GateRef serialization
 
+use alloc::string::{String, ToString};
 use alloc::vec::Vec;
+use core::any::{type_name, TypeId};
 
+use hashbrown::HashMap;
 use plonky2_field::extension::Extendable;
+use serde::{Deserialize, Serialize};
 
-use crate::gates::gate::GateRef;
+use crate::gates::gate::{Gate, GateRef};
 use crate::hash::hash_types::RichField;
 use crate::plonk::circuit_data::CommonCircuitData;
-use crate::util::serialization::{Buffer, IoResult};
+use crate::util::serialization::{Buffer, IoError, IoResult, Read, Remaining, Write};
 
 pub trait GateSerializer<F: RichField + Extendable<D>, const D: usize> {
     fn read_gate(
@@ -89,6 +93,153 @@ macro_rules! impl_gate_serializer {
     };
 }
 
+/// A gate as represented by serde: its id in a [`GateRegistry`], and its parameters as written by
+/// [`Gate::serialize`].
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct SerializedGate {
+    pub id: String,
+    pub parameters: Vec<u8>,
+}
+
+type ReadGateFn<F, const D: usize> =
+    fn(&mut Buffer, &CommonCircuitData<F, D>) -> IoResult<GateRef<F, D>>;
+
+/// A [`GateSerializer`] for the gates registered with [`GateRegistry::with`], which lets custom
+/// gates be added to the default ones without listing those again.
+///
+/// Each gate is written after an id fixed by its type, rather than its position in the registry,
+/// so the encoding does not depend on the order in which gates are registered.
+#[derive(Clone, Debug)]
+pub struct GateRegistry<F: RichField + Extendable<D>, const D: usize> {
+    readers: HashMap<String, ReadGateFn<F, D>>,
+    ids: HashMap<TypeId, String>,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> GateRegistry<F, D> {
+    /// Creates a registry without any gates. Use `GateRegistry::default()` for one supporting
+    /// all the gates of this crate.
+    pub fn new() -> Self {
+        Self {
+            readers: HashMap::new(),
+            ids: HashMap::new(),
+        }
+    }
+
+    /// Registers the gate `G`, identified by its [`Gate::serialization_id`].
+    pub fn with<G: Gate<F, D>>(self) -> Self {
+        self.with_id::<G>(G::serialization_id())
+    }
+
+    /// Registers the gate `G` under the given id, which must not be used by another gate.
+    pub fn with_id<G: Gate<F, D>>(mut self, id: impl Into<String>) -> Self {
+        let id = id.into();
+        if let Some(existing_id) = self.ids.get(&TypeId::of::<G>()) {
+            assert_eq!(
+                existing_id,
+                &id,
+                "Gate {} is already registered with id `{}`",
+                type_name::<G>(),
+                existing_id
+            );
+            return self;
+        }
+        assert!(
+            !self.readers.contains_key(&id),
+            "Gate id `{}` is already registered for another gate",
+            id
+        );
+
+        self.readers
+            .insert(id.clone(), read_registered_gate::<F, D, G>);
+        self.ids.insert(TypeId::of::<G>(), id);
+        self
+    }
+
+    /// Returns the id under which `gate` is written, if its type is registered.
+    pub fn gate_id(&self, gate: &GateRef<F, D>) -> Option<&str> {
+        self.ids.get(&gate.0.as_any().type_id()).map(String::as_str)
+    }
+
+    /// Converts `gate` to its serde representation.
+    pub fn to_serialized_gate(
+        &self,
+        gate: &GateRef<F, D>,
+        common_data: &CommonCircuitData<F, D>,
+    ) -> IoResult<SerializedGate> {
+        let id = self.registered_id(gate)?;
+        let mut parameters = Vec::new();
+        gate.0.serialize(&mut parameters, common_data)?;
+        Ok(SerializedGate {
+            id: id.to_string(),
+            parameters,
+        })
+    }
+
+    /// Converts a gate from its serde representation.
+    pub fn from_serialized_gate(
+        &self,
+        gate: &SerializedGate,
+        common_data: &CommonCircuitData<F, D>,
+    ) -> IoResult<GateRef<F, D>> {
+        let read = self.reader(&gate.id)?;
+        let mut buffer = Buffer::new(&gate.parameters);
+        let gate_ref = read(&mut buffer, common_data)?;
+        if !buffer.is_empty() {
+            log::error!(
+                "parameters of gate with id `{}` were not read entirely",
+                gate.id
+            );
+            return Err(IoError);
+        }
+        Ok(gate_ref)
+    }
+
+    fn registered_id(&self, gate: &GateRef<F, D>) -> IoResult<&str> {
+        self.gate_id(gate).ok_or_else(|| {
+            log::error!(
+                "attempted to serialize gate `{}` which is not registered",
+                gate.0.id()
+            );
+            IoError
+        })
+    }
+
+    fn reader(&self, id: &str) -> IoResult<ReadGateFn<F, D>> {
+        self.readers.get(id).copied().ok_or_else(|| {
+            log::error!("attempted to deserialize gate with unregistered id `{id}`");
+            IoError
+        })
+    }
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> GateSerializer<F, D> for GateRegistry<F, D> {
+    fn read_gate(
+        &self,
+        buf: &mut Buffer,
+        common_data: &CommonCircuitData<F, D>,
+    ) -> IoResult<GateRef<F, D>> {
+        let id = buf.read_string()?;
+        self.reader(&id)?(buf, common_data)
+    }
+
+    fn write_gate(
+        &self,
+        buf: &mut Vec<u8>,
+        gate: &GateRef<F, D>,
+        common_data: &CommonCircuitData<F, D>,
+    ) -> IoResult<()> {
+        buf.write_str(self.registered_id(gate)?)?;
+        gate.0.serialize(buf, common_data)
+    }
+}
+
+fn read_registered_gate<F: RichField + Extendable<D>, const D: usize, G: Gate<F, D>>(
+    buf: &mut Buffer,
+    common_data: &CommonCircuitData<F, D>,
+) -> IoResult<GateRef<F, D>> {
+    Ok(GateRef::new(G::deserialize(buf, common_data)?))
+}
+
 pub mod default {
     use plonky2_field::extension::Extendable;
 
@@ -98,6 +249,7 @@ pub mod default {
     use crate::gates::constant::ConstantGate;
     use crate::gates::coset_interpolation::CosetInterpolationGate;
     use crate::gates::exponentiation::ExponentiationGate;
+    use crate::gates::expression::ExpressionGate;
     use crate::gates::lookup::LookupGate;
     use crate::gates::lookup_table::LookupTableGate;
     use crate::gates::multiplication_extension::MulExtensionGate;
@@ -109,7 +261,7 @@ pub mod default {
     use crate::gates::reducing::ReducingGate;
     use crate::gates::reducing_extension::ReducingExtensionGate;
     use crate::hash::hash_types::RichField;
-    use crate::util::serialization::GateSerializer;
+    use crate::util::serialization::{GateRegistry, GateSerializer};
 
     pub struct DefaultGateSerializer;
     impl<F: RichField + Extendable<D>, const D: usize> GateSerializer<F, D> for DefaultGateSerializer {
@@ -130,7 +282,152 @@ pub mod default {
             PublicInputGate,
             RandomAccessGate<F, D>,
             ReducingExtensionGate<D>,
-            ReducingGate<D>
+            ReducingGate<D>,
+            ExpressionGate<F, D>
         }
     }
+
+    impl<F: RichField + Extendable<D>, const D: usize> Default for GateRegistry<F, D> {
+        fn default() -> Self {
+            Self::new()
+                .with::<ArithmeticGate>()
+                .with::<ArithmeticExtensionGate<D>>()
+                .with::<BaseSumGate<2>>()
+                .with::<ConstantGate>()
+                .with::<CosetInterpolationGate<F, D>>()
+                .with::<ExponentiationGate<F, D>>()
+                .with::<LookupGate>()
+                .with::<LookupTableGate>()
+                .with::<MulExtensionGate<D>>()
+                .with::<NoopGate>()
+                .with::<PoseidonMdsGate<F, D>>()
+                .with::<PoseidonGate<F, D>>()
+                .with::<PublicInputGate>()
+                .with::<RandomAccessGate<F, D>>()
+                .with::<ReducingExtensionGate<D>>()
+                .with::<ReducingGate<D>>()
+                .with::<ExpressionGate<F, D>>()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use super::*;
+    use crate::gates::arithmetic_base::ArithmeticGate;
+    use crate::gates::base_sum::BaseSumGate;
+    use crate::gates::noop::NoopGate;
+    use crate::gates::poseidon::PoseidonGate;
+    use crate::hash::poseidon::PoseidonHash;
+    use crate::plonk::circuit_builder::CircuitBuilder;
+    use crate::plonk::circuit_data::{CircuitConfig, CircuitData};
+    use crate::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+    use crate::util::serialization::GeneratorRegistry;
+
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+
+    fn circuit() -> CircuitData<F, C, D> {
+        let mut builder = CircuitBuilder::<F, D>::new(CircuitConfig::standard_recursion_config());
+        let inputs = builder.add_virtual_targets(5);
+        let hash = builder.hash_n_to_hash_no_pad::<PoseidonHash>(inputs.clone());
+        builder.register_public_inputs(&hash.elements);
+        let index = builder.add_virtual_target();
+        let element = builder.random_access(index, inputs[..4].to_vec());
+        let product = builder.mul(element, inputs[4]);
+        builder.register_public_input(product);
+        builder.build::<C>()
+    }
+
+    #[test]
+    fn test_registry_round_trip() -> Result<()> {
+        let data = circuit();
+        let gate_registry = GateRegistry::default();
+        let generator_registry = GeneratorRegistry::<C, D>::default();
+
+        let bytes = data
+            .to_bytes(&gate_registry, &generator_registry)
+            .map_err(|_| anyhow::Error::msg("CircuitData serialization failed."))?;
+        let data_from_bytes =
+            CircuitData::<F, C, D>::from_bytes(&bytes, &gate_registry, &generator_registry)
+                .map_err(|_| anyhow::Error::msg("CircuitData deserialization failed."))?;
+        assert_eq!(data, data_from_bytes);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_registry_ids() {
+        let registry = GateRegistry::<F, D>::default();
+        let poseidon = GateRef::new(PoseidonGate::<F, D>::new());
+        assert_eq!(registry.gate_id(&poseidon), Some("PoseidonGate"));
+        let base_sum = GateRef::new(BaseSumGate::<2>::new(4));
+        assert_eq!(registry.gate_id(&base_sum), Some("BaseSumGate<B=2>"));
+    }
+
+    #[test]
+    fn test_registry_order_independent() {
+        let data = circuit();
+        let gate = GateRef::new(PoseidonGate::<F, D>::new());
+        let first = GateRegistry::new()
+            .with::<NoopGate>()
+            .with::<PoseidonGate<F, D>>();
+        let second = GateRegistry::new()
+            .with::<PoseidonGate<F, D>>()
+            .with::<ArithmeticGate>()
+            .with::<NoopGate>();
+
+        let mut first_bytes = Vec::new();
+        first
+            .write_gate(&mut first_bytes, &gate, &data.common)
+            .unwrap();
+        let mut second_bytes = Vec::new();
+        second
+            .write_gate(&mut second_bytes, &gate, &data.common)
+            .unwrap();
+        assert_eq!(first_bytes, second_bytes);
+
+        let mut buf = Buffer::new(&first_bytes);
+        assert_eq!(second.read_gate(&mut buf, &data.common).unwrap(), gate);
+    }
+
+    #[test]
+    fn test_registry_unknown_ids() {
+        let data = circuit();
+        let generator_registry = GeneratorRegistry::<C, D>::default();
+        let bytes = data
+            .to_bytes(&GateRegistry::default(), &generator_registry)
+            .unwrap();
+
+        let partial_gates = GateRegistry::new()
+            .with::<ArithmeticGate>()
+            .with::<NoopGate>();
+        assert!(data.to_bytes(&partial_gates, &generator_registry).is_err());
+        assert!(
+            CircuitData::<F, C, D>::from_bytes(&bytes, &partial_gates, &generator_registry)
+                .is_err()
+        );
+
+        let no_generators = GeneratorRegistry::<C, D>::new();
+        assert!(data
+            .to_bytes(&GateRegistry::default(), &no_generators)
+            .is_err());
+        assert!(CircuitData::<F, C, D>::from_bytes(
+            &bytes,
+            &GateRegistry::default(),
+            &no_generators
+        )
+        .is_err());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_registry_duplicate_id() {
+        let _ = GateRegistry::<F, D>::new()
+            .with_id::<ArithmeticGate>("Gate")
+            .with_id::<NoopGate>("Gate");
+    }
 }
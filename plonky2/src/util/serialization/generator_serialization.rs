@@ -1,13 +1,16 @@
 //! A module to help with WitnessGeneratorRef serialization
 
+use alloc::string::{String, ToString};
 use alloc::vec::Vec;
 
+use hashbrown::HashMap;
 use plonky2_field::extension::Extendable;
 
 use crate::hash::hash_types::RichField;
-use crate::iop::generator::WitnessGeneratorRef;
+use crate::iop::generator::{SimpleGenerator, WitnessGeneratorRef};
 use crate::plonk::circuit_data::CommonCircuitData;
-use crate::util::serialization::{Buffer, IoResult};
+use crate::plonk::config::GenericConfig;
+use crate::util::serialization::{Buffer, IoError, IoResult, Read, Write};
 
 pub trait WitnessGeneratorSerializer<F: RichField + Extendable<D>, const D: usize> {
     fn read_generator(
@@ -26,7 +29,7 @@ pub trait WitnessGeneratorSerializer<F: RichField + Extendable<D>, const D: usiz
 
 #[macro_export]
 macro_rules! read_generator_impl {
-    ($buf:expr, $tag:expr, $common:expr, $($generator_types:ty),+) => {{
+    ($buf:expr, $tag:expr, $common:expr, $($generator_types:ty),+ $(; $($reader_ids:expr => $readers:expr),+)?) => {{
         let tag = $tag;
         let buf = $buf;
         let mut i = 0..;
@@ -38,6 +41,9 @@ macro_rules! read_generator_impl {
             $crate::iop::generator::SimpleGenerator::<F, D>::adapter(generator),
         ))
         } else)*
+        $($(if tag == i.next().unwrap() {
+            $readers(buf, $common)
+        } else)*)?
         {
             Err($crate::util::serialization::IoError)
         }
@@ -46,11 +52,14 @@ macro_rules! read_generator_impl {
 
 #[macro_export]
 macro_rules! get_generator_tag_impl {
-    ($generator:expr, $($generator_types:ty),+) => {{
+    ($generator:expr, $($generator_types:ty),+ $(; $($reader_ids:expr),+)?) => {{
         let mut i = 0..;
         $(if let (tag, true) = (i.next().unwrap(), $generator.0.id() == $crate::iop::generator::SimpleGenerator::<F, D>::id(&<$generator_types>::default())) {
             Ok(tag)
         } else)*
+        $($(if let (tag, true) = (i.next().unwrap(), $generator.0.id() == $reader_ids) {
+            Ok(tag)
+        } else)*)?
         {
             log::log!(
                 log::Level::Error,
@@ -67,15 +76,17 @@ macro_rules! get_generator_tag_impl {
 /// To serialize a list of generators used for a circuit,
 /// this macro should be called with a struct on which to implement
 /// this as first argument, followed by all the targeted generators.
+/// Generators which are not [`SimpleGenerator`]s can be listed after a
+/// semicolon, as `id => read_fn` pairs, see [`GeneratorRegistry::with_reader`].
 macro_rules! impl_generator_serializer {
-    ($target:ty, $($generator_types:ty),+) => {
+    ($target:ty, $($generator_types:ty),+ $(; $($reader_ids:expr => $readers:expr),+ $(,)?)?) => {
         fn read_generator(
             &self,
             buf: &mut $crate::util::serialization::Buffer,
             common: &$crate::plonk::circuit_data::CommonCircuitData<F, D>,
         ) -> $crate::util::serialization::IoResult<$crate::iop::generator::WitnessGeneratorRef<F, D>> {
             let tag = $crate::util::serialization::Read::read_u32(buf)?;
-            read_generator_impl!(buf, tag, common, $($generator_types),+)
+            read_generator_impl!(buf, tag, common, $($generator_types),+ $(; $($reader_ids => $readers),+)?)
         }
 
         fn write_generator(
@@ -84,7 +95,7 @@ macro_rules! impl_generator_serializer {
             generator: &$crate::iop::generator::WitnessGeneratorRef<F, D>,
             common: &$crate::plonk::circuit_data::CommonCircuitData<F, D>,
         ) -> $crate::util::serialization::IoResult<()> {
-            let tag = get_generator_tag_impl!(generator, $($generator_types),+)?;
+            let tag = get_generator_tag_impl!(generator, $($generator_types),+ $(; $($reader_ids),+)?)?;
 
             $crate::util::serialization::Write::write_u32(buf, tag)?;
             generator.0.serialize(buf, common)?;
@@ -93,6 +104,104 @@ macro_rules! impl_generator_serializer {
     };
 }
 
+/// A function reading a generator, as registered with [`GeneratorRegistry::with_reader`].
+pub type ReadGeneratorFn<F, const D: usize> =
+    fn(&mut Buffer, &CommonCircuitData<F, D>) -> IoResult<WitnessGeneratorRef<F, D>>;
+
+/// A [`WitnessGeneratorSerializer`] for the generators registered with
+/// [`GeneratorRegistry::with`], which lets custom generators be added to the default ones without
+/// listing those again.
+///
+/// Each generator is written after its `id`, rather than its position in the registry, so the
+/// encoding does not depend on the order in which generators are registered.
+#[derive(Clone, Debug)]
+pub struct GeneratorRegistry<C: GenericConfig<D>, const D: usize> {
+    readers: HashMap<String, ReadGeneratorFn<C::F, D>>,
+}
+
+impl<C: GenericConfig<D>, const D: usize> GeneratorRegistry<C, D> {
+    /// Creates a registry without any generators. Use `GeneratorRegistry::default()` for one
+    /// supporting all the generators of this crate.
+    pub fn new() -> Self {
+        Self {
+            readers: HashMap::new(),
+        }
+    }
+
+    /// Registers the generator `G`, identified by the `id` of its default value, which must not
+    /// be registered yet.
+    pub fn with<G: SimpleGenerator<C::F, D> + Default>(self) -> Self {
+        self.with_reader(&G::default().id(), read_simple_generator::<C::F, D, G>)
+    }
+
+    /// Registers `read` for the generators with the given `id`, which must not be registered yet.
+    /// This allows registering generators which are not [`SimpleGenerator`]s.
+    pub fn with_reader(mut self, id: &str, read: ReadGeneratorFn<C::F, D>) -> Self {
+        assert!(
+            !self.readers.contains_key(id),
+            "Generator id `{}` is already registered",
+            id
+        );
+
+        self.readers.insert(id.to_string(), read);
+        self
+    }
+
+    /// Returns whether a generator with the given id is registered.
+    pub fn contains(&self, id: &str) -> bool {
+        self.readers.contains_key(id)
+    }
+}
+
+impl<F, C, const D: usize> WitnessGeneratorSerializer<F, D> for GeneratorRegistry<C, D>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+{
+    fn read_generator(
+        &self,
+        buf: &mut Buffer,
+        common_data: &CommonCircuitData<F, D>,
+    ) -> IoResult<WitnessGeneratorRef<F, D>> {
+        let id = buf.read_string()?;
+        match self.readers.get(&id) {
+            Some(read) => read(buf, common_data),
+            None => {
+                log::error!("attempted to deserialize generator with unregistered id `{id}`");
+                Err(IoError)
+            }
+        }
+    }
+
+    fn write_generator(
+        &self,
+        buf: &mut Vec<u8>,
+        generator: &WitnessGeneratorRef<F, D>,
+        common_data: &CommonCircuitData<F, D>,
+    ) -> IoResult<()> {
+        let id = generator.0.id();
+        if !self.contains(&id) {
+            log::error!("attempted to serialize generator with unregistered id `{id}`");
+            return Err(IoError);
+        }
+
+        buf.write_str(&id)?;
+        generator.0.serialize(buf, common_data)
+    }
+}
+
+fn read_simple_generator<F, const D: usize, G>(
+    buf: &mut Buffer,
+    common_data: &CommonCircuitData<F, D>,
+) -> IoResult<WitnessGeneratorRef<F, D>>
+where
+    F: RichField + Extendable<D>,
+    G: SimpleGenerator<F, D>,
+{
+    let generator = G::deserialize(buf, common_data)?;
+    Ok(WitnessGeneratorRef::new(generator.adapter()))
+}
+
 pub mod default {
     use core::marker::PhantomData;
 
@@ -100,6 +209,7 @@ pub mod default {
 
     use crate::gadgets::arithmetic::EqualityGenerator;
     use crate::gadgets::arithmetic_extension::QuotientGeneratorExtension;
+    use crate::gadgets::memory::{read_memory_generator, MEMORY_GENERATOR_ID};
     use crate::gadgets::range_check::LowHighGenerator;
     use crate::gadgets::split_base::BaseSumGenerator;
     use crate::gadgets::split_join::{SplitGenerator, WireSplitGenerator};
@@ -108,6 +218,7 @@ pub mod default {
     use crate::gates::base_sum::BaseSplitGenerator;
     use crate::gates::coset_interpolation::InterpolationGenerator;
     use crate::gates::exponentiation::ExponentiationGenerator;
+    use crate::gates::expression::ExpressionGenerator;
     use crate::gates::lookup::LookupGenerator;
     use crate::gates::lookup_table::LookupTableGenerator;
     use crate::gates::multiplication_extension::MulExtensionGenerator;
@@ -122,7 +233,7 @@ pub mod default {
     };
     use crate::plonk::config::{AlgebraicHasher, GenericConfig};
     use crate::recursion::dummy_circuit::DummyProofGenerator;
-    use crate::util::serialization::WitnessGeneratorSerializer;
+    use crate::util::serialization::{GeneratorRegistry, WitnessGeneratorSerializer};
 
     pub struct DefaultGeneratorSerializer<C: GenericConfig<D>, const D: usize> {
         pub _phantom: PhantomData<C>,
@@ -159,7 +270,46 @@ pub mod default {
             ReducingGenerator<D>,
             ReducingExtensionGenerator<D>,
             SplitGenerator,
-            WireSplitGenerator
+            WireSplitGenerator,
+            ExpressionGenerator<F, D>;
+            MEMORY_GENERATOR_ID => read_memory_generator
+        }
+    }
+
+    impl<F, C, const D: usize> Default for GeneratorRegistry<C, D>
+    where
+        F: RichField + Extendable<D>,
+        C: GenericConfig<D, F = F> + 'static,
+        C::Hasher: AlgebraicHasher<F>,
+    {
+        fn default() -> Self {
+            Self::new()
+                .with::<ArithmeticBaseGenerator<F, D>>()
+                .with::<ArithmeticExtensionGenerator<F, D>>()
+                .with::<BaseSplitGenerator<2>>()
+                .with::<BaseSumGenerator<2>>()
+                .with::<ConstantGenerator<F>>()
+                .with::<CopyGenerator>()
+                .with::<DummyProofGenerator<F, C, D>>()
+                .with::<EqualityGenerator>()
+                .with::<ExponentiationGenerator<F, D>>()
+                .with::<InterpolationGenerator<F, D>>()
+                .with::<LookupGenerator>()
+                .with::<LookupTableGenerator>()
+                .with::<LowHighGenerator>()
+                .with::<MulExtensionGenerator<F, D>>()
+                .with::<NonzeroTestGenerator>()
+                .with::<PoseidonGenerator<F, D>>()
+                .with::<PoseidonMdsGenerator<D>>()
+                .with::<QuotientGeneratorExtension<D>>()
+                .with::<RandomAccessGenerator<F, D>>()
+                .with::<RandomValueGenerator>()
+                .with::<ReducingGenerator<D>>()
+                .with::<ReducingExtensionGenerator<D>>()
+                .with::<SplitGenerator>()
+                .with::<WireSplitGenerator>()
+                .with::<ExpressionGenerator<F, D>>()
+                .with_reader(MEMORY_GENERATOR_ID, read_memory_generator)
         }
     }
 }
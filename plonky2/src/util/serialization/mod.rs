@@ -4,7 +4,10 @@ pub mod generator_serialization;
 #[macro_use]
 pub mod gate_serialization;
 
+pub mod envelope;
+
 use alloc::collections::BTreeMap;
+use alloc::string::String;
 use alloc::sync::Arc;
 use alloc::vec;
 use alloc::vec::Vec;
@@ -14,9 +17,9 @@ use core::mem::size_of;
 use core::ops::Range;
 
 pub use gate_serialization::default::DefaultGateSerializer;
-pub use gate_serialization::GateSerializer;
+pub use gate_serialization::{GateRegistry, GateSerializer, SerializedGate};
 pub use generator_serialization::default::DefaultGeneratorSerializer;
-pub use generator_serialization::WitnessGeneratorSerializer;
+pub use generator_serialization::{GeneratorRegistry, ReadGeneratorFn, WitnessGeneratorSerializer};
 use hashbrown::HashMap;
 
 use crate::field::extension::{Extendable, FieldExtension};
@@ -32,6 +35,7 @@ use crate::fri::{FriConfig, FriParams};
 use crate::gadgets::polynomial::PolynomialCoeffsExtTarget;
 use crate::gates::gate::GateRef;
 use crate::gates::lookup::Lookup;
+use crate::gates::lookup_table::LookupTableData;
 use crate::gates::selectors::SelectorsInfo;
 use crate::hash::hash_types::{HashOutTarget, MerkleCapTarget, RichField};
 use crate::hash::merkle_proofs::{MerkleProof, MerkleProofTarget};
@@ -81,6 +85,14 @@ pub trait Read {
     /// Reads exactly the length of `bytes` from `self` and writes it to `bytes`.
     fn read_exact(&mut self, bytes: &mut [u8]) -> IoResult<()>;
 
+    /// Returns whether lookups are read in the layout written before lookup tables could have
+    /// any number of inputs and outputs, where tables, lookups and the lookup gates all had one
+    /// input and one output.
+    #[inline]
+    fn legacy_lookups(&self) -> bool {
+        false
+    }
+
     /// Reads a `bool` value from `self`.
     #[inline]
     fn read_bool(&mut self) -> IoResult<bool> {
@@ -131,6 +143,14 @@ pub trait Read {
         Ok(u32::from_le_bytes(buf))
     }
 
+    /// Reads a `u64` value from `self`.
+    #[inline]
+    fn read_u64(&mut self) -> IoResult<u64> {
+        let mut buf = [0; size_of::<u64>()];
+        self.read_exact(&mut buf)?;
+        Ok(u64::from_le_bytes(buf))
+    }
+
     /// Reads a `usize` value from `self`.
     #[inline]
     fn read_usize(&mut self) -> IoResult<usize> {
@@ -151,6 +171,15 @@ pub trait Read {
         Ok(res)
     }
 
+    /// Reads a UTF-8 string, prefixed by its length in bytes, from `self`.
+    #[inline]
+    fn read_string(&mut self) -> IoResult<String> {
+        let len = self.read_usize()?;
+        let mut bytes = vec![0; len];
+        self.read_exact(&mut bytes)?;
+        String::from_utf8(bytes).map_err(|_| IoError)
+    }
+
     /// Reads a element from the field `F` with size less than `2^64` from `self.`
     #[inline]
     fn read_field<F>(&mut self) -> IoResult<F>
@@ -1158,16 +1187,35 @@ pub trait Read {
         })
     }
 
-    /// Reads a lookup table stored as `Vec<(u16, u16)>` from `self`.
+    /// Reads a lookup table stored as `LookupTableData` from `self`.
     #[inline]
-    fn read_lut(&mut self) -> IoResult<Vec<(u16, u16)>> {
+    fn read_lut(&mut self) -> IoResult<LookupTableData> {
+        if self.legacy_lookups() {
+            let length = self.read_usize()?;
+            let mut values = Vec::with_capacity(2 * length);
+            for _ in 0..length {
+                values.push(self.read_u16()? as u64);
+                values.push(self.read_u16()? as u64);
+            }
+            return Ok(LookupTableData::from_canonical_values(1, 1, values));
+        }
+
+        let num_inputs = self.read_usize()?;
+        let num_outputs = self.read_usize()?;
         let length = self.read_usize()?;
-        let mut lut = Vec::with_capacity(length);
+        if num_inputs == 0 || length % (num_inputs + num_outputs) != 0 {
+            return Err(IoError);
+        }
+        let mut values = Vec::with_capacity(length);
         for _ in 0..length {
-            lut.push((self.read_u16()?, self.read_u16()?));
+            values.push(self.read_u64()?);
         }
 
-        Ok(lut)
+        Ok(LookupTableData::from_canonical_values(
+            num_inputs,
+            num_outputs,
+            values,
+        ))
     }
 
     /// Reads a target lookup table stored as `Lookup` from `self`.
@@ -1176,7 +1224,11 @@ pub trait Read {
         let length = self.read_usize()?;
         let mut lut = Vec::with_capacity(length);
         for _ in 0..length {
-            lut.push((self.read_target()?, self.read_target()?));
+            if self.legacy_lookups() {
+                lut.push(vec![self.read_target()?, self.read_target()?]);
+            } else {
+                lut.push(self.read_target_vec()?);
+            }
         }
 
         Ok(lut)
@@ -1232,6 +1284,12 @@ pub trait Write {
         self.write_all(&x.to_le_bytes())
     }
 
+    /// Writes a word `x` to `self.`
+    #[inline]
+    fn write_u64(&mut self, x: u64) -> IoResult<()> {
+        self.write_all(&x.to_le_bytes())
+    }
+
     /// Writes a word `x` to `self.`
     #[inline]
     fn write_usize(&mut self, x: usize) -> IoResult<()> {
@@ -1249,6 +1307,13 @@ pub trait Write {
         Ok(())
     }
 
+    /// Writes the string `s`, prefixed by its length in bytes, to `self`.
+    #[inline]
+    fn write_str(&mut self, s: &str) -> IoResult<()> {
+        self.write_usize(s.len())?;
+        self.write_all(s.as_bytes())
+    }
+
     /// Writes an element `x` from the field `F` to `self`.
     #[inline]
     fn write_field<F>(&mut self, x: F) -> IoResult<()>
@@ -2112,11 +2177,12 @@ pub trait Write {
 
     /// Writes a lookup table to `self`.
     #[inline]
-    fn write_lut(&mut self, lut: &[(u16, u16)]) -> IoResult<()> {
-        self.write_usize(lut.len())?;
-        for (a, b) in lut.iter() {
-            self.write_u16(*a)?;
-            self.write_u16(*b)?;
+    fn write_lut(&mut self, lut: &LookupTableData) -> IoResult<()> {
+        self.write_usize(lut.num_inputs())?;
+        self.write_usize(lut.num_outputs())?;
+        self.write_usize(lut.values().len())?;
+        for &x in lut.values() {
+            self.write_u64(x)?;
         }
 
         Ok(())
@@ -2124,11 +2190,10 @@ pub trait Write {
 
     /// Writes a target lookup table to `self`.
     #[inline]
-    fn write_target_lut(&mut self, lut: &[(Target, Target)]) -> IoResult<()> {
+    fn write_target_lut(&mut self, lut: &[Vec<Target>]) -> IoResult<()> {
         self.write_usize(lut.len())?;
-        for (a, b) in lut.iter() {
-            self.write_target(*a)?;
-            self.write_target(*b)?;
+        for lookup in lut.iter() {
+            self.write_target_vec(lookup)?;
         }
 
         Ok(())
@@ -2168,13 +2233,29 @@ impl Write for Vec<u8> {
 pub struct Buffer<'a> {
     bytes: &'a [u8],
     pos: usize,
+    legacy_lookups: bool,
 }
 
 impl<'a> Buffer<'a> {
     /// Builds a new [`Buffer`] over `buffer`.
     #[inline]
     pub const fn new(bytes: &'a [u8]) -> Self {
-        Self { bytes, pos: 0 }
+        Self {
+            bytes,
+            pos: 0,
+            legacy_lookups: false,
+        }
+    }
+
+    /// Builds a new [`Buffer`] over `buffer`, which reads lookups in their legacy layout. See
+    /// [`Read::legacy_lookups`].
+    #[inline]
+    pub const fn with_legacy_lookups(bytes: &'a [u8]) -> Self {
+        Self {
+            bytes,
+            pos: 0,
+            legacy_lookups: true,
+        }
     }
 
     /// Returns the inner position.
@@ -2203,6 +2284,11 @@ impl<'a> Remaining for Buffer<'a> {
 }
 
 impl<'a> Read for Buffer<'a> {
+    #[inline]
+    fn legacy_lookups(&self) -> bool {
+        self.legacy_lookups
+    }
+
     #[inline]
     fn read_exact(&mut self, bytes: &mut [u8]) -> IoResult<()> {
         let n = bytes.len();
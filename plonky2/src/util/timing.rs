@@ -1,6 +1,8 @@
+use core::time::Duration;
+
 use log::{log, Level};
 #[cfg(feature = "timing")]
-use web_time::{Duration, Instant};
+use web_time::Instant;
 
 /// The hierarchy of scopes, and the time consumed by each one. Useful for profiling.
 #[cfg(feature = "timing")]
@@ -119,6 +121,34 @@ impl TimingTree {
     #[cfg(not(feature = "timing"))]
     pub fn pop(&mut self) {}
 
+    /// Add a closed scope which took `duration`, in the deepest open scope of this tree. This is
+    /// useful for time which was measured separately, e.g. summed over several threads.
+    #[cfg(feature = "timing")]
+    pub fn add_duration(&mut self, ctx: &str, mut level: log::Level, duration: Duration) {
+        assert!(self.is_open());
+
+        level = level.max(self.level);
+
+        if let Some(last_child) = self.children.last_mut() {
+            if last_child.is_open() {
+                last_child.add_duration(ctx, level, duration);
+                return;
+            }
+        }
+
+        let now = Instant::now();
+        self.children.push(TimingTree {
+            name: ctx.to_string(),
+            level,
+            enter_time: now,
+            exit_time: Some(now + duration),
+            children: vec![],
+        })
+    }
+
+    #[cfg(not(feature = "timing"))]
+    pub fn add_duration(&mut self, _ctx: &str, _level: log::Level, _duration: Duration) {}
+
     #[cfg(feature = "timing")]
     fn duration(&self) -> Duration {
         self.exit_time
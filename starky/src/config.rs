@@ -1,3 +1,4 @@
+use plonky2::field::types::Field;
 use plonky2::fri::reduction_strategies::FriReductionStrategy;
 use plonky2::fri::{FriConfig, FriParams};
 
@@ -28,6 +29,30 @@ impl StarkConfig {
         }
     }
 
+    /// Like `standard_fast_config`, for base fields of about 31 bits such as BabyBear, used with
+    /// a degree 4 or 5 extension. Two base field challenges would only give about 62 bits, so the
+    /// constraint combination and lookup challenges are drawn from that extension.
+    pub const fn standard_fast_small_field_config() -> Self {
+        Self::standard_fast_config()
+    }
+
+    /// Whether the constraint combination and lookup challenges are drawn from the extension of
+    /// `F` rather than from `F` itself. This is needed when `F` is too small for `num_challenges`
+    /// base field challenges to reach `security_bits`.
+    pub(crate) const fn extension_challenges<F: Field>(&self) -> bool {
+        self.num_challenges * F::BITS < self.security_bits
+    }
+
+    /// The number of base field elements making up each constraint combination or lookup
+    /// challenge, for challenges drawn from the degree `d` extension of `F` when needed.
+    pub(crate) const fn challenge_degree<F: Field>(&self, d: usize) -> usize {
+        if self.extension_challenges::<F>() {
+            d
+        } else {
+            1
+        }
+    }
+
     pub(crate) fn fri_params(&self, degree_bits: usize) -> FriParams {
         self.fri_config.fri_params(degree_bits, false)
     }
@@ -4,15 +4,30 @@ use core::marker::PhantomData;
 
 use plonky2::field::extension::Extendable;
 use plonky2::field::packed::PackedField;
+use plonky2::field::types::Field;
 use plonky2::hash::hash_types::RichField;
 use plonky2::iop::ext_target::ExtensionTarget;
 use plonky2::iop::target::Target;
 use plonky2::plonk::circuit_builder::CircuitBuilder;
 
 pub struct ConstraintConsumer<P: PackedField> {
-    /// Random values used to combine multiple constraints into one.
+    /// Random values used to combine multiple constraints into one. For alphas drawn from an
+    /// extension, these are the coefficients of each alpha, `challenge_degree` at a time.
     alphas: Vec<P::Scalar>,
 
+    /// The degree of the extension from which the alphas are drawn, 1 for base field alphas.
+    challenge_degree: usize,
+
+    /// The constant `w` such that `x^challenge_degree = w` in the extension of the alphas.
+    w: P::Scalar,
+
+    /// For alphas drawn from an extension, the coefficients of the power of each alpha by which
+    /// the next constraint is scaled.
+    alpha_powers: Vec<P::Scalar>,
+
+    /// Scratch space for updating `alpha_powers`.
+    next_alpha_power: Vec<P::Scalar>,
+
     /// Running sums of constraints that have been emitted so far, scaled by powers of alpha.
     // TODO(JN): This is pub so it can be used in a test. Once we have an API for accessing this
     // result, it should be made private.
@@ -40,6 +55,45 @@ impl<P: PackedField> ConstraintConsumer<P> {
         Self {
             constraint_accs: vec![P::ZEROS; alphas.len()],
             alphas,
+            challenge_degree: 1,
+            w: P::Scalar::ZERO,
+            alpha_powers: vec![],
+            next_alpha_power: vec![],
+            z_last,
+            lagrange_basis_first,
+            lagrange_basis_last,
+        }
+    }
+
+    /// Like `new`, but for alphas drawn from the extension of degree `challenge_degree` in which
+    /// `x^challenge_degree = w`, each given by its `challenge_degree` coefficients. There is then one
+    /// accumulator per coefficient of each combination `sum alpha^i C_i`, where the `C_i`s are the
+    /// constraints in the order they are added.
+    pub fn new_with_extension_alphas(
+        alphas: Vec<P::Scalar>,
+        challenge_degree: usize,
+        w: P::Scalar,
+        z_last: P,
+        lagrange_basis_first: P,
+        lagrange_basis_last: P,
+    ) -> Self {
+        assert_eq!(alphas.len() % challenge_degree, 0);
+        let alpha_powers = (0..alphas.len())
+            .map(|i| {
+                if i % challenge_degree == 0 {
+                    P::Scalar::ONE
+                } else {
+                    P::Scalar::ZERO
+                }
+            })
+            .collect();
+        Self {
+            constraint_accs: vec![P::ZEROS; alphas.len()],
+            alphas,
+            challenge_degree,
+            w,
+            alpha_powers,
+            next_alpha_power: vec![P::Scalar::ZERO; challenge_degree],
             z_last,
             lagrange_basis_first,
             lagrange_basis_last,
@@ -57,9 +111,39 @@ impl<P: PackedField> ConstraintConsumer<P> {
 
     /// Add one constraint on all rows.
     pub fn constraint(&mut self, constraint: P) {
-        for (&alpha, acc) in self.alphas.iter().zip(&mut self.constraint_accs) {
-            *acc *= alpha;
-            *acc += constraint;
+        if self.challenge_degree == 1 {
+            for (&alpha, acc) in self.alphas.iter().zip(&mut self.constraint_accs) {
+                *acc *= alpha;
+                *acc += constraint;
+            }
+            return;
+        }
+
+        let d = self.challenge_degree;
+        let w = self.w;
+        for ((alpha, power), accs) in self
+            .alphas
+            .chunks(d)
+            .zip(self.alpha_powers.chunks_mut(d))
+            .zip(self.constraint_accs.chunks_mut(d))
+        {
+            for (acc, &coeff) in accs.iter_mut().zip(power.iter()) {
+                *acc += constraint * coeff;
+            }
+
+            // Multiply the power by alpha, reducing with `x^d = w`.
+            let next_power = &mut self.next_alpha_power;
+            next_power.fill(P::Scalar::ZERO);
+            for (i, &p) in power.iter().enumerate() {
+                for (j, &a) in alpha.iter().enumerate() {
+                    if i + j < d {
+                        next_power[i + j] += p * a;
+                    } else {
+                        next_power[i + j - d] += w * p * a;
+                    }
+                }
+            }
+            power.copy_from_slice(next_power);
         }
     }
 
@@ -80,6 +164,12 @@ pub struct RecursiveConstraintConsumer<F: RichField + Extendable<D>, const D: us
     /// A random value used to combine multiple constraints into one.
     alphas: Vec<Target>,
 
+    /// Random values drawn from the extension, used instead of `alphas` when not empty.
+    extension_alphas: Vec<ExtensionTarget<D>>,
+
+    /// The power of each of `extension_alphas` by which the next constraint is scaled.
+    extension_alpha_powers: Vec<ExtensionTarget<D>>,
+
     /// A running sum of constraints that have been emitted so far, scaled by powers of alpha.
     constraint_accs: Vec<ExtensionTarget<D>>,
 
@@ -108,6 +198,30 @@ impl<F: RichField + Extendable<D>, const D: usize> RecursiveConstraintConsumer<F
         Self {
             constraint_accs: vec![zero; alphas.len()],
             alphas,
+            extension_alphas: vec![],
+            extension_alpha_powers: vec![],
+            z_last,
+            lagrange_basis_first,
+            lagrange_basis_last,
+            _phantom: Default::default(),
+        }
+    }
+
+    /// Circuit version of `ConstraintConsumer::new_with_extension_alphas`, for alphas drawn from
+    /// the degree `D` extension.
+    pub fn new_with_extension_alphas(
+        zero: ExtensionTarget<D>,
+        one: ExtensionTarget<D>,
+        alphas: Vec<ExtensionTarget<D>>,
+        z_last: ExtensionTarget<D>,
+        lagrange_basis_first: ExtensionTarget<D>,
+        lagrange_basis_last: ExtensionTarget<D>,
+    ) -> Self {
+        Self {
+            constraint_accs: vec![zero; alphas.len() * D],
+            alphas: vec![],
+            extension_alpha_powers: vec![one; alphas.len()],
+            extension_alphas: alphas,
             z_last,
             lagrange_basis_first,
             lagrange_basis_last,
@@ -138,6 +252,18 @@ impl<F: RichField + Extendable<D>, const D: usize> RecursiveConstraintConsumer<F
         for (&alpha, acc) in self.alphas.iter().zip(&mut self.constraint_accs) {
             *acc = builder.scalar_mul_add_extension(alpha, *acc, constraint);
         }
+
+        for ((&alpha, power), accs) in self
+            .extension_alphas
+            .iter()
+            .zip(&mut self.extension_alpha_powers)
+            .zip(self.constraint_accs.chunks_mut(D))
+        {
+            for (acc, &coeff) in accs.iter_mut().zip(power.0.iter()) {
+                *acc = builder.scalar_mul_add_extension(coeff, constraint, *acc);
+            }
+            *power = builder.mul_extension(*power, alpha);
+        }
     }
 
     /// Add one constraint, but first multiply it by a filter such that it will only apply to the
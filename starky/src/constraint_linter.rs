@@ -0,0 +1,423 @@
+//! Tools to analyze the constraints of a [`Stark`].
+//!
+//! Constraints are evaluated symbolically with [`symbolic_constraints`], which gives their exact
+//! degrees. The circuit constraints are then compared one by one with the native ones, by
+//! recovering them from fewer than twice as many linear combinations as there are constraints.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use anyhow::{bail, ensure, Result};
+use itertools::Itertools;
+use plonky2::field::extension::{Extendable, FieldExtension};
+use plonky2::field::packed::PackedField;
+use plonky2::field::polynomial::PolynomialValues;
+use plonky2::field::types::{Field, Sample};
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::witness::{PartialWitness, Witness, WitnessWrite};
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use plonky2::plonk::circuit_data::CircuitConfig;
+use plonky2::plonk::config::{AlgebraicHasher, GenericConfig};
+use plonky2::util::log2_ceil;
+
+use crate::constraint_consumer::RecursiveConstraintConsumer;
+use crate::evaluation_frame::StarkEvaluationFrame;
+use crate::stark::Stark;
+use crate::symbolic::{symbolic_constraints, SymbolicPolynomial, SymbolicValue};
+
+/// The result of the analysis of the constraints emitted by `eval_packed_generic`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ConstraintReport {
+    /// The degree of each constraint, in the order in which they are emitted, including the
+    /// degree of their row filter: the filters `z_last`, `lagrange_basis_first` and
+    /// `lagrange_basis_last` each count as degree 1, which makes these degrees directly comparable
+    /// with `Stark::constraint_degree`. `None` means that the constraint is identically zero.
+    pub degrees: Vec<Option<usize>>,
+}
+
+impl ConstraintReport {
+    fn new<F: RichField>(constraints: &[SymbolicPolynomial<F>]) -> Self {
+        Self {
+            degrees: constraints.iter().map(SymbolicPolynomial::degree).collect(),
+        }
+    }
+
+    /// The number of constraints emitted by the STARK.
+    pub fn num_constraints(&self) -> usize {
+        self.degrees.len()
+    }
+
+    /// The maximum degree over all constraints, or 0 if all constraints are identically zero.
+    pub fn max_degree(&self) -> usize {
+        self.degrees.iter().flatten().copied().max().unwrap_or(0)
+    }
+
+    /// The indices of the constraints which are identically zero, and hence constrain nothing.
+    pub fn zero_constraints(&self) -> Vec<usize> {
+        self.degrees
+            .iter()
+            .positions(|degree| degree.is_none())
+            .collect()
+    }
+}
+
+/// Computes the degree of every constraint emitted by `eval_packed_generic`, by evaluating them
+/// symbolically.
+///
+/// Public inputs are constants of the constraints, so they are set to random values. A constraint
+/// which is only zero for some values of the public inputs is thus reported as nonzero.
+pub fn analyze_constraints<F: RichField + Extendable<D>, S: Stark<F, D>, const D: usize>(
+    stark: &S,
+) -> Result<ConstraintReport>
+where
+    SymbolicValue<F>: PackedField<Scalar = F>,
+{
+    let public_inputs = F::rand_vec(S::PUBLIC_INPUTS);
+    Ok(ConstraintReport::new(&symbolic_constraints(
+        stark,
+        &public_inputs,
+    )?))
+}
+
+/// Checks that `eval_ext_circuit` emits the given constraints of `eval_packed_generic`, in the
+/// same order, and reports the first one which differs.
+///
+/// Both are evaluated at a random point, with `2^k` alphas for the smallest `k` such that there
+/// are more alphas than constraints, and with accumulators starting at one. The accumulators
+/// then hold the evaluations of `X^n + c_0 X^(n-1) + ... + c_(n-1)`, whose coefficients are
+/// recovered by interpolation. One more random alpha detects circuits emitting more constraints
+/// than the interpolation can recover.
+fn check_circuit_constraints<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    S: Stark<F, D>,
+    const D: usize,
+>(
+    stark: &S,
+    public_inputs: &[F],
+    constraints: &[SymbolicPolynomial<F>],
+) -> Result<()>
+where
+    C::InnerHasher: AlgebraicHasher<F>,
+{
+    let num_constraints = constraints.len();
+    let mut alphas = F::two_adic_subgroup(log2_ceil(num_constraints + 1));
+    alphas.push(F::rand());
+
+    // The local values, the next values and the three row filters, as in `symbolic_constraints`.
+    let variables = F::Extension::rand_vec(2 * S::COLUMNS + 3);
+    let native_constraints = constraints
+        .iter()
+        .map(|constraint| constraint.eval(&variables))
+        .collect::<Vec<_>>();
+    let native_accumulators = alphas
+        .iter()
+        .map(|&alpha| {
+            native_constraints
+                .iter()
+                .fold(F::Extension::ONE, |acc, &c| acc.scalar_mul(alpha) + c)
+        })
+        .collect::<Vec<_>>();
+
+    let mut builder = CircuitBuilder::<F, D>::new(CircuitConfig::standard_recursion_config());
+    let mut pw = PartialWitness::<F>::new();
+    let variables_t = builder.add_virtual_extension_targets(variables.len());
+    pw.set_extension_targets(&variables_t, &variables);
+    let public_inputs_t = public_inputs
+        .iter()
+        .map(|&pi| builder.constant_extension(F::Extension::from_basefield(pi)))
+        .collect::<Vec<_>>();
+    let alphas_t = alphas
+        .iter()
+        .map(|&alpha| builder.constant(alpha))
+        .collect();
+
+    let (locals_t, rest) = variables_t.split_at(S::COLUMNS);
+    let (nexts_t, filters_t) = rest.split_at(S::COLUMNS);
+    let vars = S::EvaluationFrameTarget::from_values(locals_t, nexts_t, &public_inputs_t);
+    let one = builder.one_extension();
+    let mut consumer = RecursiveConstraintConsumer::<F, D>::new(
+        one,
+        alphas_t,
+        filters_t[0],
+        filters_t[1],
+        filters_t[2],
+    );
+    stark.eval_ext_circuit(&mut builder, &vars, &mut consumer);
+    let accumulators_t = consumer.accumulators();
+
+    let data = builder.mock_build::<C>();
+    let witness = data.generate_witness(pw);
+    let circuit_accumulators = accumulators_t
+        .into_iter()
+        .map(|t| witness.get_extension_target(t))
+        .collect::<Vec<_>>();
+    if circuit_accumulators == native_accumulators {
+        return Ok(());
+    }
+
+    // Find out how the constraints differ, from the coefficients of the circuit polynomial.
+    let (_, subgroup_accumulators) = circuit_accumulators.split_last().unwrap();
+    let coeffs = interpolate(subgroup_accumulators);
+    let same_count = coeffs[num_constraints].is_one()
+        && coeffs[num_constraints + 1..].iter().all(|c| c.is_zero());
+    let mismatch = (0..num_constraints)
+        .find(|&i| coeffs[num_constraints - 1 - i] != native_constraints[i])
+        .filter(|_| same_count);
+    match mismatch {
+        Some(i) => bail!(
+            "Constraint {} differs between eval_packed_generic and eval_ext_circuit",
+            i
+        ),
+        None => bail!(
+            "eval_packed_generic emits {} constraints, but eval_ext_circuit emits another number",
+            num_constraints
+        ),
+    }
+}
+
+/// Returns the coefficients of the polynomial with the given evaluations over a subgroup.
+fn interpolate<F: Field, FE: FieldExtension<D2, BaseField = F>, const D2: usize>(
+    evals: &[FE],
+) -> Vec<FE> {
+    // The subgroup is in the base field, so we can interpolate each component separately.
+    let components = (0..D2)
+        .map(|i| {
+            PolynomialValues::new(evals.iter().map(|e| e.to_basefield_array()[i]).collect())
+                .ifft()
+                .coeffs
+        })
+        .collect::<Vec<_>>();
+    (0..evals.len())
+        .map(|j| FE::from_basefield_array(core::array::from_fn(|i| components[i][j])))
+        .collect()
+}
+
+/// Runs all the checks of this module on the given STARK:
+/// - no constraint is identically zero,
+/// - no constraint has a degree larger than `constraint_degree`,
+/// - `eval_ext_circuit` emits the same constraints as `eval_packed_generic`, in the same order.
+///
+/// A `constraint_degree` larger than needed is not an error, but is reported as a warning since it
+/// needlessly increases the size of the quotient polynomials.
+pub fn lint_stark<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    S: Stark<F, D>,
+    const D: usize,
+>(
+    stark: S,
+) -> Result<ConstraintReport>
+where
+    SymbolicValue<F>: PackedField<Scalar = F>,
+    C::InnerHasher: AlgebraicHasher<F>,
+{
+    let public_inputs = F::rand_vec(S::PUBLIC_INPUTS);
+    let constraints = symbolic_constraints(&stark, &public_inputs)?;
+    let report = ConstraintReport::new(&constraints);
+
+    let zero_constraints = report.zero_constraints();
+    ensure!(
+        zero_constraints.is_empty(),
+        "Constraints {:?} are identically zero",
+        zero_constraints
+    );
+
+    let declared_degree = stark.constraint_degree();
+    let max_degree = report.max_degree();
+    ensure!(
+        max_degree <= declared_degree,
+        "Constraints {:?} have degree {}, but `constraint_degree` is {}",
+        report
+            .degrees
+            .iter()
+            .positions(|&degree| degree == Some(max_degree))
+            .collect::<Vec<_>>(),
+        max_degree,
+        declared_degree
+    );
+    if max_degree < declared_degree {
+        log::warn!(
+            "`constraint_degree` is {}, but the maximum constraint degree is {}",
+            declared_degree,
+            max_degree
+        );
+    }
+
+    check_circuit_constraints::<F, C, S, D>(&stark, &public_inputs, &constraints)?;
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use core::marker::PhantomData;
+
+    use anyhow::Result;
+    use plonky2::field::extension::{Extendable, FieldExtension};
+    use plonky2::field::packed::PackedField;
+    use plonky2::hash::hash_types::RichField;
+    use plonky2::iop::ext_target::ExtensionTarget;
+    use plonky2::plonk::circuit_builder::CircuitBuilder;
+    use plonky2::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+
+    use crate::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
+    use crate::constraint_linter::{analyze_constraints, check_circuit_constraints, lint_stark};
+    use crate::evaluation_frame::{StarkEvaluationFrame, StarkFrame};
+    use crate::stark::Stark;
+    use crate::symbolic::symbolic_constraints;
+
+    /// A STARK whose constraints have several defects: its degree is under-declared, one of its
+    /// constraints is identically zero, and its circuit constraints are emitted in another order.
+    #[derive(Copy, Clone)]
+    struct FaultyStark<F: RichField + Extendable<D>, const D: usize> {
+        _phantom: PhantomData<F>,
+    }
+
+    impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for FaultyStark<F, D> {
+        type EvaluationFrame<FE, P, const D2: usize> = StarkFrame<P, P::Scalar, 2, 0>
+        where
+            FE: FieldExtension<D2, BaseField = F>,
+            P: PackedField<Scalar = FE>;
+
+        type EvaluationFrameTarget = StarkFrame<ExtensionTarget<D>, ExtensionTarget<D>, 2, 0>;
+
+        fn eval_packed_generic<FE, P, const D2: usize>(
+            &self,
+            vars: &Self::EvaluationFrame<FE, P, D2>,
+            yield_constr: &mut ConstraintConsumer<P>,
+        ) where
+            FE: FieldExtension<D2, BaseField = F>,
+            P: PackedField<Scalar = FE>,
+        {
+            let local_values = vars.get_local_values();
+            let next_values = vars.get_next_values();
+
+            yield_constr.constraint_transition(next_values[0] - local_values[0] * local_values[1]);
+            let sum = local_values[0] + local_values[1];
+            yield_constr.constraint(sum - local_values[0] - local_values[1]);
+            yield_constr.constraint_first_row(local_values[1]);
+        }
+
+        fn eval_ext_circuit(
+            &self,
+            builder: &mut CircuitBuilder<F, D>,
+            vars: &Self::EvaluationFrameTarget,
+            yield_constr: &mut RecursiveConstraintConsumer<F, D>,
+        ) {
+            let local_values = vars.get_local_values();
+            let next_values = vars.get_next_values();
+
+            let constraint = builder.arithmetic_extension(
+                F::NEG_ONE,
+                F::ONE,
+                local_values[0],
+                local_values[1],
+                next_values[0],
+            );
+            yield_constr.constraint_transition(builder, constraint);
+            yield_constr.constraint_first_row(builder, local_values[1]);
+            let zero = builder.zero_extension();
+            yield_constr.constraint(builder, zero);
+        }
+
+        fn constraint_degree(&self) -> usize {
+            2
+        }
+    }
+
+    #[test]
+    fn test_faulty_stark_lint() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        type S = FaultyStark<F, D>;
+
+        let stark = S {
+            _phantom: PhantomData,
+        };
+        let report = analyze_constraints(&stark)?;
+        assert_eq!(report.degrees, vec![Some(3), None, Some(2)]);
+        assert_eq!(report.max_degree(), 3);
+        assert_eq!(report.zero_constraints(), vec![1]);
+
+        let constraints = symbolic_constraints(&stark, &[])?;
+        let error = check_circuit_constraints::<F, C, S, D>(&stark, &[], &constraints).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Constraint 1 differs between eval_packed_generic and eval_ext_circuit"
+        );
+        assert!(lint_stark::<F, C, S, D>(stark).is_err());
+
+        Ok(())
+    }
+
+    /// A STARK whose constraints view packed values as slices, which symbolic evaluation doesn't
+    /// support.
+    #[derive(Copy, Clone)]
+    struct SlicingStark<F: RichField + Extendable<D>, const D: usize> {
+        _phantom: PhantomData<F>,
+    }
+
+    impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for SlicingStark<F, D> {
+        type EvaluationFrame<FE, P, const D2: usize> = StarkFrame<P, P::Scalar, 1, 0>
+        where
+            FE: FieldExtension<D2, BaseField = F>,
+            P: PackedField<Scalar = FE>;
+
+        type EvaluationFrameTarget = StarkFrame<ExtensionTarget<D>, ExtensionTarget<D>, 1, 0>;
+
+        fn eval_packed_generic<FE, P, const D2: usize>(
+            &self,
+            vars: &Self::EvaluationFrame<FE, P, D2>,
+            yield_constr: &mut ConstraintConsumer<P>,
+        ) where
+            FE: FieldExtension<D2, BaseField = F>,
+            P: PackedField<Scalar = FE>,
+        {
+            let local_value = vars.get_local_values()[0];
+            let square = local_value
+                .as_slice()
+                .iter()
+                .map(|&x| x.square())
+                .collect::<Vec<_>>();
+            yield_constr.constraint(*P::from_slice(&square) - local_value);
+        }
+
+        fn eval_ext_circuit(
+            &self,
+            builder: &mut CircuitBuilder<F, D>,
+            vars: &Self::EvaluationFrameTarget,
+            yield_constr: &mut RecursiveConstraintConsumer<F, D>,
+        ) {
+            let local_value = vars.get_local_values()[0];
+            let constraint = builder.arithmetic_extension(
+                F::ONE,
+                F::NEG_ONE,
+                local_value,
+                local_value,
+                local_value,
+            );
+            yield_constr.constraint(builder, constraint);
+        }
+
+        fn constraint_degree(&self) -> usize {
+            2
+        }
+    }
+
+    #[test]
+    fn test_unsupported_symbolic_operations() {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        type S = SlicingStark<F, D>;
+
+        let stark = S {
+            _phantom: PhantomData,
+        };
+        let error = analyze_constraints(&stark).unwrap_err();
+        assert!(error.to_string().contains("PackedField::as_slice"));
+        assert!(lint_stark::<F, C, S, D>(stark).is_err());
+    }
+}
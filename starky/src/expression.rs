@@ -0,0 +1,336 @@
+//! A small expression language to write the constraints of a STARK once, and evaluate them both
+//! natively through a [`ConstraintConsumer`] and recursively through a
+//! [`RecursiveConstraintConsumer`].
+//!
+//! A table author builds its constraints once, typically when constructing the STARK, and then
+//! implements `eval_packed_generic` with [`eval_expr_constraints`] and `eval_ext_circuit` with
+//! [`eval_expr_constraints_circuit`]. Since both are derived from the same expressions, they are
+//! guaranteed to emit the same constraints, in the same order.
+//!
+//! Expressions are those of [`plonky2::util::expression`], whose variables are the values of an
+//! evaluation frame, so they share their operators with the constraints of plonky2's
+//! `ExpressionGate`.
+
+use plonky2::field::extension::{Extendable, FieldExtension};
+use plonky2::field::packed::PackedField;
+use plonky2::field::types::Field;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::ext_target::ExtensionTarget;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use plonky2::util::expression::Expression;
+
+use crate::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
+use crate::evaluation_frame::StarkEvaluationFrame;
+
+/// A variable of an [`Expr`], which is a value of an evaluation frame.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum FrameVar {
+    /// The value of the given column in the current row.
+    Local(usize),
+    /// The value of the given column in the next row.
+    Next(usize),
+    /// The given public input.
+    PublicInput(usize),
+}
+
+/// A polynomial expression over the values of an evaluation frame.
+pub type Expr<F> = Expression<F, FrameVar>;
+
+/// The degree of `expr` in the trace values. Public inputs and constants have degree 0. This is a
+/// syntactic bound, which may be larger than the actual degree of the polynomial.
+pub fn expr_degree<F: Field>(expr: &Expr<F>) -> usize {
+    expr.degree(&|var| match var {
+        FrameVar::Local(_) | FrameVar::Next(_) => 1,
+        FrameVar::PublicInput(_) => 0,
+    })
+}
+
+/// Evaluates `expr` natively on the given evaluation frame.
+pub fn eval_expr<F, FE, P, const D2: usize>(
+    expr: &Expr<F>,
+    vars: &impl StarkEvaluationFrame<P, FE>,
+) -> P
+where
+    F: Field,
+    FE: FieldExtension<D2, BaseField = F>,
+    P: PackedField<Scalar = FE>,
+{
+    expr.eval(
+        &|&var| match var {
+            FrameVar::Local(i) => vars.get_local_values()[i],
+            FrameVar::Next(i) => vars.get_next_values()[i],
+            FrameVar::PublicInput(i) => P::from(vars.get_public_inputs()[i]),
+        },
+        &|c| P::from(FE::from_basefield(c)),
+    )
+}
+
+/// Evaluates `expr` in a circuit on the given evaluation frame.
+pub fn eval_expr_circuit<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    expr: &Expr<F>,
+    vars: &impl StarkEvaluationFrame<ExtensionTarget<D>, ExtensionTarget<D>>,
+) -> ExtensionTarget<D> {
+    expr.eval_circuit(builder, &|&var| match var {
+        FrameVar::Local(i) => vars.get_local_values()[i],
+        FrameVar::Next(i) => vars.get_next_values()[i],
+        FrameVar::PublicInput(i) => vars.get_public_inputs()[i],
+    })
+}
+
+/// The rows on which a constraint applies.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ConstraintKind {
+    /// The constraint applies to all rows.
+    EveryRow,
+    /// The constraint applies to all rows except the last.
+    Transition,
+    /// The constraint only applies to the first row.
+    FirstRow,
+    /// The constraint only applies to the last row.
+    LastRow,
+}
+
+/// A constraint written as an expression, which must vanish on the rows given by its kind.
+#[derive(Clone, Debug)]
+pub struct ExprConstraint<F: Field> {
+    pub kind: ConstraintKind,
+    pub expr: Expr<F>,
+}
+
+impl<F: Field> ExprConstraint<F> {
+    pub const fn every_row(expr: Expr<F>) -> Self {
+        Self {
+            kind: ConstraintKind::EveryRow,
+            expr,
+        }
+    }
+
+    pub const fn transition(expr: Expr<F>) -> Self {
+        Self {
+            kind: ConstraintKind::Transition,
+            expr,
+        }
+    }
+
+    pub const fn first_row(expr: Expr<F>) -> Self {
+        Self {
+            kind: ConstraintKind::FirstRow,
+            expr,
+        }
+    }
+
+    pub const fn last_row(expr: Expr<F>) -> Self {
+        Self {
+            kind: ConstraintKind::LastRow,
+            expr,
+        }
+    }
+
+    /// The degree of this constraint, including the degree of its row filter, as expected by
+    /// `Stark::constraint_degree`.
+    pub fn degree(&self) -> usize {
+        match self.kind {
+            ConstraintKind::EveryRow => expr_degree(&self.expr),
+            _ => expr_degree(&self.expr) + 1,
+        }
+    }
+}
+
+/// The maximum degree of the given constraints, which can be used to implement
+/// `Stark::constraint_degree`.
+pub fn expr_constraints_degree<F: Field>(constraints: &[ExprConstraint<F>]) -> usize {
+    constraints
+        .iter()
+        .map(ExprConstraint::degree)
+        .max()
+        .unwrap_or(0)
+}
+
+/// Evaluates the given constraints natively, and feeds them to `yield_constr`. This can be used to
+/// implement `Stark::eval_packed_generic`.
+pub fn eval_expr_constraints<F, FE, P, const D2: usize>(
+    constraints: &[ExprConstraint<F>],
+    vars: &impl StarkEvaluationFrame<P, FE>,
+    yield_constr: &mut ConstraintConsumer<P>,
+) where
+    F: Field,
+    FE: FieldExtension<D2, BaseField = F>,
+    P: PackedField<Scalar = FE>,
+{
+    for constraint in constraints {
+        let value = eval_expr(&constraint.expr, vars);
+        match constraint.kind {
+            ConstraintKind::EveryRow => yield_constr.constraint(value),
+            ConstraintKind::Transition => yield_constr.constraint_transition(value),
+            ConstraintKind::FirstRow => yield_constr.constraint_first_row(value),
+            ConstraintKind::LastRow => yield_constr.constraint_last_row(value),
+        }
+    }
+}
+
+/// Evaluates the given constraints in a circuit, and feeds them to `yield_constr`. This can be
+/// used to implement `Stark::eval_ext_circuit`.
+pub fn eval_expr_constraints_circuit<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    constraints: &[ExprConstraint<F>],
+    vars: &impl StarkEvaluationFrame<ExtensionTarget<D>, ExtensionTarget<D>>,
+    yield_constr: &mut RecursiveConstraintConsumer<F, D>,
+) {
+    for constraint in constraints {
+        let value = eval_expr_circuit(builder, &constraint.expr, vars);
+        match constraint.kind {
+            ConstraintKind::EveryRow => yield_constr.constraint(builder, value),
+            ConstraintKind::Transition => yield_constr.constraint_transition(builder, value),
+            ConstraintKind::FirstRow => yield_constr.constraint_first_row(builder, value),
+            ConstraintKind::LastRow => yield_constr.constraint_last_row(builder, value),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use core::marker::PhantomData;
+
+    use anyhow::Result;
+    use plonky2::field::extension::{Extendable, FieldExtension};
+    use plonky2::field::packed::PackedField;
+    use plonky2::field::polynomial::PolynomialValues;
+    use plonky2::field::types::Field;
+    use plonky2::hash::hash_types::RichField;
+    use plonky2::iop::ext_target::ExtensionTarget;
+    use plonky2::plonk::circuit_builder::CircuitBuilder;
+    use plonky2::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+    use plonky2::util::timing::TimingTree;
+
+    use crate::config::StarkConfig;
+    use crate::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
+    #[cfg(feature = "std")]
+    use crate::constraint_linter::lint_stark;
+    use crate::evaluation_frame::StarkFrame;
+    use crate::expression::{
+        eval_expr_constraints, eval_expr_constraints_circuit, expr_constraints_degree, Expr,
+        ExprConstraint, FrameVar,
+    };
+    use crate::prover::prove;
+    use crate::stark::Stark;
+    use crate::stark_testing::{test_stark_circuit_constraints, test_stark_low_degree};
+    use crate::util::trace_rows_to_poly_values;
+    use crate::verifier::verify_stark_proof;
+
+    /// Computes the sequence `x' <- x^2 + y, y' <- y + 1`, with the first value of `x` given as a
+    /// public input, and its last value as another one.
+    #[derive(Clone)]
+    struct SquaringStark<F: RichField + Extendable<D>, const D: usize> {
+        num_rows: usize,
+        constraints: Vec<ExprConstraint<F>>,
+        _phantom: PhantomData<F>,
+    }
+
+    impl<F: RichField + Extendable<D>, const D: usize> SquaringStark<F, D> {
+        fn new(num_rows: usize) -> Self {
+            let [x, y] = [0, 1].map(|i| Expr::var(FrameVar::Local(i)));
+            let [x_next, y_next] = [0, 1].map(|i| Expr::var(FrameVar::Next(i)));
+            let [x0, x_last] = [0, 1].map(|i| Expr::var(FrameVar::PublicInput(i)));
+            let constraints = vec![
+                ExprConstraint::first_row(x.clone() - x0),
+                ExprConstraint::first_row(y.clone()),
+                ExprConstraint::last_row(x.clone() - x_last),
+                ExprConstraint::transition(x_next - (x.square() + y.clone())),
+                ExprConstraint::transition(y_next - y - F::ONE),
+            ];
+            Self {
+                num_rows,
+                constraints,
+                _phantom: PhantomData,
+            }
+        }
+
+        fn generate_trace(&self, x0: F) -> (Vec<PolynomialValues<F>>, F) {
+            let rows = (0..self.num_rows)
+                .scan([x0, F::ZERO], |acc, _| {
+                    let row = *acc;
+                    *acc = [row[0].square() + row[1], row[1] + F::ONE];
+                    Some(row)
+                })
+                .collect::<Vec<_>>();
+            let last = rows[self.num_rows - 1][0];
+            (trace_rows_to_poly_values(rows), last)
+        }
+    }
+
+    impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for SquaringStark<F, D> {
+        type EvaluationFrame<FE, P, const D2: usize> = StarkFrame<P, P::Scalar, 2, 2>
+        where
+            FE: FieldExtension<D2, BaseField = F>,
+            P: PackedField<Scalar = FE>;
+
+        type EvaluationFrameTarget = StarkFrame<ExtensionTarget<D>, ExtensionTarget<D>, 2, 2>;
+
+        fn eval_packed_generic<FE, P, const D2: usize>(
+            &self,
+            vars: &Self::EvaluationFrame<FE, P, D2>,
+            yield_constr: &mut ConstraintConsumer<P>,
+        ) where
+            FE: FieldExtension<D2, BaseField = F>,
+            P: PackedField<Scalar = FE>,
+        {
+            eval_expr_constraints(&self.constraints, vars, yield_constr);
+        }
+
+        fn eval_ext_circuit(
+            &self,
+            builder: &mut CircuitBuilder<F, D>,
+            vars: &Self::EvaluationFrameTarget,
+            yield_constr: &mut RecursiveConstraintConsumer<F, D>,
+        ) {
+            eval_expr_constraints_circuit(builder, &self.constraints, vars, yield_constr);
+        }
+
+        fn constraint_degree(&self) -> usize {
+            expr_constraints_degree(&self.constraints)
+        }
+    }
+
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+    type S = SquaringStark<F, D>;
+
+    #[test]
+    fn test_expr_stark() -> Result<()> {
+        let config = StarkConfig::standard_fast_config();
+        let stark = S::new(1 << 5);
+        let x0 = F::TWO;
+        let (trace, last) = stark.generate_trace(x0);
+        let proof = prove::<F, C, S, D>(
+            stark.clone(),
+            &config,
+            trace,
+            &[x0, last],
+            &mut TimingTree::default(),
+        )?;
+
+        verify_stark_proof(stark, proof, &config)
+    }
+
+    #[test]
+    fn test_expr_stark_constraints() -> Result<()> {
+        let stark = S::new(1 << 5);
+        assert_eq!(stark.constraint_degree(), 3);
+
+        test_stark_low_degree(stark.clone())?;
+        test_stark_circuit_constraints::<F, C, S, D>(stark.clone())?;
+        #[cfg(feature = "std")]
+        {
+            let report = lint_stark::<F, C, S, D>(stark)?;
+            assert_eq!(
+                report.degrees,
+                vec![Some(2), Some(2), Some(2), Some(3), Some(2)]
+            );
+        }
+        Ok(())
+    }
+}
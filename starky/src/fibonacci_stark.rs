@@ -129,34 +129,48 @@ impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for FibonacciStar
     }
 
     fn lookups(&self) -> Vec<Lookup<F>> {
-        vec![Lookup {
-            columns: vec![Column::single(2)],
-            table_column: Column::single(3),
-            frequencies_column: Column::single(4),
-            filter_columns: vec![None; 1],
-        }]
+        vec![Lookup::new_single_column(
+            vec![Column::single(2)],
+            Column::single(3),
+            Column::single(4),
+            vec![None; 1],
+        )]
     }
 }
 
 #[cfg(test)]
 mod tests {
+    #[cfg(not(feature = "std"))]
+    use alloc::{string::String, vec};
+
     use anyhow::Result;
-    use plonky2::field::extension::Extendable;
+    use plonky2::field::babybear_field::BabyBearField;
+    use plonky2::field::extension::quintic::QuinticExtension;
+    use plonky2::field::extension::{Extendable, FieldExtension};
+    use plonky2::field::packed::PackedField;
     use plonky2::field::types::Field;
     use plonky2::hash::hash_types::RichField;
-    use plonky2::iop::witness::PartialWitness;
+    use plonky2::hash::poseidon::PoseidonHash;
+    use plonky2::iop::witness::{PartialWitness, WitnessWrite};
     use plonky2::plonk::circuit_builder::CircuitBuilder;
     use plonky2::plonk::circuit_data::CircuitConfig;
-    use plonky2::plonk::config::{AlgebraicHasher, GenericConfig, PoseidonGoldilocksConfig};
+    use plonky2::plonk::config::{
+        AlgebraicHasher, GenericConfig, KeccakBabyBearConfig, PoseidonBabyBearConfig,
+        PoseidonGoldilocksConfig,
+    };
     use plonky2::util::timing::TimingTree;
 
     use crate::config::StarkConfig;
+    use crate::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
+    #[cfg(feature = "std")]
+    use crate::constraint_linter::lint_stark;
     use crate::fibonacci_stark::FibonacciStark;
     use crate::proof::StarkProofWithPublicInputs;
     use crate::prover::prove;
     use crate::recursive_verifier::{
-        add_virtual_stark_proof_with_pis, set_stark_proof_with_pis_target,
-        verify_stark_proof_circuit,
+        add_virtual_stark_proof_with_pis, add_virtual_stark_proof_with_pis_and_degree_range,
+        set_stark_proof_with_pis_target, try_set_stark_proof_with_pis_target,
+        verify_stark_proof_circuit, verify_stark_proof_with_degree_range_circuit,
     };
     use crate::stark::Stark;
     use crate::stark_testing::{test_stark_circuit_constraints, test_stark_low_degree};
@@ -166,6 +180,48 @@ mod tests {
         (0..n).fold((x0, x1), |x, _| (x.1, x.0 + x.1)).1
     }
 
+    /// `FibonacciStark` without its lookup, to test STARKs which don't use lookups.
+    #[derive(Copy, Clone)]
+    struct FibonacciStarkWithoutLookups<F: RichField + Extendable<D>, const D: usize>(
+        FibonacciStark<F, D>,
+    );
+
+    impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D>
+        for FibonacciStarkWithoutLookups<F, D>
+    {
+        type EvaluationFrame<FE, P, const D2: usize> =
+            <FibonacciStark<F, D> as Stark<F, D>>::EvaluationFrame<FE, P, D2>
+        where
+            FE: FieldExtension<D2, BaseField = F>,
+            P: PackedField<Scalar = FE>;
+
+        type EvaluationFrameTarget = <FibonacciStark<F, D> as Stark<F, D>>::EvaluationFrameTarget;
+
+        fn eval_packed_generic<FE, P, const D2: usize>(
+            &self,
+            vars: &Self::EvaluationFrame<FE, P, D2>,
+            yield_constr: &mut ConstraintConsumer<P>,
+        ) where
+            FE: FieldExtension<D2, BaseField = F>,
+            P: PackedField<Scalar = FE>,
+        {
+            self.0.eval_packed_generic(vars, yield_constr)
+        }
+
+        fn eval_ext_circuit(
+            &self,
+            builder: &mut CircuitBuilder<F, D>,
+            vars: &Self::EvaluationFrameTarget,
+            yield_constr: &mut RecursiveConstraintConsumer<F, D>,
+        ) {
+            self.0.eval_ext_circuit(builder, vars, yield_constr)
+        }
+
+        fn constraint_degree(&self) -> usize {
+            self.0.constraint_degree()
+        }
+    }
+
     #[test]
     fn test_fibonacci_stark() -> Result<()> {
         const D: usize = 2;
@@ -189,6 +245,145 @@ mod tests {
         verify_stark_proof(stark, proof, &config)
     }
 
+    #[test]
+    fn test_fibonacci_stark_without_lookups() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        type S = FibonacciStarkWithoutLookups<F, D>;
+
+        let config = StarkConfig::standard_fast_config();
+        let num_rows = 1 << 5;
+        let public_inputs = [F::ZERO, F::ONE, fibonacci(num_rows - 1, F::ZERO, F::ONE)];
+        let stark = FibonacciStarkWithoutLookups(FibonacciStark::new(num_rows));
+        let trace = stark.0.generate_trace(public_inputs[0], public_inputs[1]);
+        let proof = prove::<F, C, S, D>(
+            stark,
+            &config,
+            trace,
+            &public_inputs,
+            &mut TimingTree::default(),
+        )?;
+        assert!(proof.proof.auxiliary_polys_cap.is_none());
+        verify_stark_proof(stark, proof.clone(), &config)?;
+
+        recursive_proof::<F, C, S, C, D>(stark, proof, &config, false)
+    }
+
+    #[test]
+    fn test_fibonacci_stark_smooth_length() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        type S = FibonacciStark<F, D>;
+
+        let config = StarkConfig::standard_fast_config();
+        // Not a power of two: FRI works with 128 as degree bound, but the trace and the quotient
+        // live on the subgroup of order 96.
+        let num_rows = 3 << 5;
+        let public_inputs = [F::ZERO, F::ONE, fibonacci(num_rows - 1, F::ZERO, F::ONE)];
+        let stark = S::new(num_rows);
+        let trace = stark.generate_trace(public_inputs[0], public_inputs[1]);
+        let proof = prove::<F, C, S, D>(
+            stark,
+            &config,
+            trace,
+            &public_inputs,
+            &mut TimingTree::default(),
+        )?;
+        assert_eq!(proof.proof.trace_len, num_rows);
+        assert_eq!(proof.proof.recover_degree_bits(&config), 7);
+
+        verify_stark_proof(stark, proof.clone(), &config)?;
+
+        // Such proofs can't be verified recursively.
+        let mut builder = CircuitBuilder::<F, D>::new(CircuitConfig::standard_recursion_config());
+        let pt = add_virtual_stark_proof_with_pis(&mut builder, stark, &config, 7);
+        assert!(
+            try_set_stark_proof_with_pis_target(&mut PartialWitness::new(), &pt, &proof).is_err()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_fibonacci_stark_wrong_trace_len() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        type S = FibonacciStark<F, D>;
+
+        let config = StarkConfig::standard_fast_config();
+        let num_rows = 3 << 5;
+        let public_inputs = [F::ZERO, F::ONE, fibonacci(num_rows - 1, F::ZERO, F::ONE)];
+        let stark = S::new(num_rows);
+        let trace = stark.generate_trace(public_inputs[0], public_inputs[1]);
+        let proof = prove::<F, C, S, D>(
+            stark,
+            &config,
+            trace,
+            &public_inputs,
+            &mut TimingTree::default(),
+        )?;
+        verify_stark_proof(stark, proof.clone(), &config)?;
+
+        // All these lengths have the FRI degree bound 128 of the proof. 128 is another valid length,
+        // 9 * 8 would need a subgroup of order 9, which Goldilocks doesn't have, and 100 isn't of the
+        // form 2^k 3^j.
+        for trace_len in [1 << 7, 9 << 3, 100] {
+            let mut wrong_proof = proof.clone();
+            wrong_proof.proof.trace_len = trace_len;
+            assert!(verify_stark_proof(stark, wrong_proof, &config).is_err());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_fibonacci_stark_babybear() -> Result<()> {
+        type F = BabyBearField;
+
+        /// A configuration drawing challenges from the quintic extension of BabyBear.
+        #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+        struct PoseidonBabyBearQuinticConfig;
+        impl GenericConfig<5> for PoseidonBabyBearQuinticConfig {
+            type F = F;
+            type FE = QuinticExtension<F>;
+            type Hasher = PoseidonHash;
+            type InnerHasher = PoseidonHash;
+
+            fn id() -> String {
+                "PoseidonBabyBearQuinticConfig".into()
+            }
+        }
+
+        fn prove_and_verify<C: GenericConfig<D, F = F>, const D: usize>() -> Result<()>
+        where
+            F: Extendable<D>,
+        {
+            type S<const D: usize> = FibonacciStark<F, D>;
+
+            let config = StarkConfig::standard_fast_small_field_config();
+            let num_rows = 1 << 5;
+            let public_inputs = [F::ZERO, F::ONE, fibonacci(num_rows - 1, F::ZERO, F::ONE)];
+            let stark = S::<D>::new(num_rows);
+            let trace = stark.generate_trace(public_inputs[0], public_inputs[1]);
+            let proof = prove::<F, C, S<D>, D>(
+                stark,
+                &config,
+                trace,
+                &public_inputs,
+                &mut TimingTree::default(),
+            )?;
+
+            verify_stark_proof(stark, proof, &config)?;
+            test_stark_low_degree(stark)
+        }
+
+        prove_and_verify::<PoseidonBabyBearConfig, 4>()?;
+        prove_and_verify::<KeccakBabyBearConfig, 4>()?;
+        prove_and_verify::<PoseidonBabyBearQuinticConfig, 5>()
+    }
+
     #[test]
     fn test_fibonacci_stark_degree() -> Result<()> {
         const D: usize = 2;
@@ -213,6 +408,21 @@ mod tests {
         test_stark_circuit_constraints::<F, C, S, D>(stark)
     }
 
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_fibonacci_stark_lint() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        type S = FibonacciStark<F, D>;
+
+        let num_rows = 1 << 5;
+        let stark = S::new(num_rows);
+        let report = lint_stark::<F, C, S, D>(stark)?;
+        assert_eq!(report.degrees, vec![Some(2); 5]);
+        Ok(())
+    }
+
     #[test]
     fn test_recursive_stark_verifier() -> Result<()> {
         init_logger();
@@ -238,6 +448,112 @@ mod tests {
         recursive_proof::<F, C, S, C, D>(stark, proof, &config, true)
     }
 
+    #[test]
+    fn test_recursive_stark_verifier_with_degree_range() -> Result<()> {
+        init_logger();
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        type S = FibonacciStark<F, D>;
+
+        let config = StarkConfig::standard_fast_config();
+        let degree_bits_range = 5..8;
+
+        // A single circuit, verifying proofs of traces with 2^5 to 2^7 rows.
+        let mut builder = CircuitBuilder::<F, D>::new(CircuitConfig::standard_recursion_config());
+        let pt = add_virtual_stark_proof_with_pis_and_degree_range(
+            &mut builder,
+            S::new(1 << 7),
+            &config,
+            degree_bits_range.clone(),
+        );
+        let degree_bits = builder.add_virtual_target();
+        verify_stark_proof_with_degree_range_circuit::<F, C, S, D>(
+            &mut builder,
+            S::new(1 << 7),
+            pt.clone(),
+            degree_bits,
+            degree_bits_range.clone(),
+            &config,
+        );
+        let data = builder.build::<C>();
+
+        for log_num_rows in degree_bits_range {
+            let num_rows = 1 << log_num_rows;
+            let public_inputs = [F::ZERO, F::ONE, fibonacci(num_rows - 1, F::ZERO, F::ONE)];
+            let stark = S::new(num_rows);
+            let trace = stark.generate_trace(public_inputs[0], public_inputs[1]);
+            let proof = prove::<F, C, S, D>(
+                stark,
+                &config,
+                trace,
+                &public_inputs,
+                &mut TimingTree::default(),
+            )?;
+
+            let mut pw = PartialWitness::new();
+            set_stark_proof_with_pis_target(&mut pw, &pt, &proof);
+            pw.set_target(degree_bits, F::from_canonical_usize(log_num_rows));
+            let proof = data.prove(pw)?;
+            assert_eq!(
+                proof.public_inputs,
+                vec![F::from_canonical_usize(log_num_rows)]
+            );
+            data.verify(proof)?;
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_recursive_stark_verifier_with_wrong_degree() {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        type S = FibonacciStark<F, D>;
+
+        let config = StarkConfig::standard_fast_config();
+        let degree_bits_range = 5..7;
+
+        let mut builder = CircuitBuilder::<F, D>::new(CircuitConfig::standard_recursion_config());
+        let pt = add_virtual_stark_proof_with_pis_and_degree_range(
+            &mut builder,
+            S::new(1 << 6),
+            &config,
+            degree_bits_range.clone(),
+        );
+        let degree_bits = builder.add_virtual_target();
+        verify_stark_proof_with_degree_range_circuit::<F, C, S, D>(
+            &mut builder,
+            S::new(1 << 6),
+            pt.clone(),
+            degree_bits,
+            degree_bits_range,
+            &config,
+        );
+        let data = builder.build::<C>();
+
+        // A proof of 2^5 rows, claimed to have 2^6 rows.
+        let num_rows = 1 << 5;
+        let public_inputs = [F::ZERO, F::ONE, fibonacci(num_rows - 1, F::ZERO, F::ONE)];
+        let stark = S::new(num_rows);
+        let trace = stark.generate_trace(public_inputs[0], public_inputs[1]);
+        let proof = prove::<F, C, S, D>(
+            stark,
+            &config,
+            trace,
+            &public_inputs,
+            &mut TimingTree::default(),
+        )
+        .unwrap();
+
+        let mut pw = PartialWitness::new();
+        set_stark_proof_with_pis_target(&mut pw, &pt, &proof);
+        pw.set_target(degree_bits, F::from_canonical_usize(6));
+        let _ = data.prove(pw);
+    }
+
     fn recursive_proof<
         F: RichField + Extendable<D>,
         C: GenericConfig<D, F = F>,
@@ -252,6 +568,7 @@ mod tests {
     ) -> Result<()>
     where
         InnerC::Hasher: AlgebraicHasher<F>,
+        C::InnerHasher: AlgebraicHasher<F>,
     {
         let circuit_config = CircuitConfig::standard_recursion_config();
         let mut builder = CircuitBuilder::<F, D>::new(circuit_config);
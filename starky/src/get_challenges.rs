@@ -2,7 +2,9 @@ use alloc::vec::Vec;
 
 use plonky2::field::extension::Extendable;
 use plonky2::field::polynomial::PolynomialCoeffs;
+use plonky2::field::types::Field;
 use plonky2::fri::proof::{FriProof, FriProofTarget};
+use plonky2::fri::recursive_verifier::FriDegreeRangeTarget;
 use plonky2::gadgets::polynomial::PolynomialCoeffsExtTarget;
 use plonky2::hash::hash_types::{MerkleCapTarget, RichField};
 use plonky2::hash::merkle_tree::MerkleCap;
@@ -17,6 +19,7 @@ use crate::proof::*;
 use crate::stark::Stark;
 
 fn get_challenges<F, C, const D: usize>(
+    trace_len: usize,
     trace_cap: &MerkleCap<F, C::Hasher>,
     auxiliary_polys_cap: Option<&MerkleCap<F, C::Hasher>>,
     quotient_polys_cap: &MerkleCap<F, C::Hasher>,
@@ -32,18 +35,22 @@ where
     C: GenericConfig<D, F = F>,
 {
     let num_challenges = config.num_challenges;
+    let challenge_degree = config.challenge_degree::<F>(D);
 
     let mut challenger = Challenger::<F, C::Hasher>::new();
 
+    challenger.observe_element(F::from_canonical_usize(trace_len));
     challenger.observe_cap(trace_cap);
 
     let lookup_challenge_set = auxiliary_polys_cap.map(|auxiliary_polys_cap| {
-        let tmp = get_grand_product_challenge_set(&mut challenger, num_challenges);
+        let tmp =
+            get_grand_product_challenge_set(&mut challenger, num_challenges, challenge_degree);
         challenger.observe_cap(auxiliary_polys_cap);
         tmp
     });
 
-    let stark_alphas = challenger.get_n_challenges(num_challenges);
+    // Alphas drawn from the extension are sampled coefficient by coefficient.
+    let stark_alphas = challenger.get_n_challenges(num_challenges * challenge_degree);
 
     challenger.observe_cap(quotient_polys_cap);
     let stark_zeta = challenger.get_extension_challenge::<D>();
@@ -84,6 +91,7 @@ where
         degree_bits: usize,
     ) -> StarkProofChallenges<F, D> {
         let StarkProof {
+            trace_len,
             trace_cap,
             auxiliary_polys_cap,
             quotient_polys_cap,
@@ -98,6 +106,7 @@ where
         } = &self.proof;
 
         get_challenges::<F, C, D>(
+            *trace_len,
             trace_cap,
             auxiliary_polys_cap.as_ref(),
             quotient_polys_cap,
@@ -118,6 +127,7 @@ pub(crate) fn get_challenges_target<
     const D: usize,
 >(
     builder: &mut CircuitBuilder<F, D>,
+    trace_len: Target,
     trace_cap: &MerkleCapTarget,
     auxiliary_polys_cap: Option<&MerkleCapTarget>,
     quotient_polys_cap: &MerkleCapTarget,
@@ -126,40 +136,59 @@ pub(crate) fn get_challenges_target<
     final_poly: &PolynomialCoeffsExtTarget<D>,
     pow_witness: Target,
     config: &StarkConfig,
+    degree: Option<&FriDegreeRangeTarget>,
 ) -> StarkProofChallengesTarget<D>
 where
     C::Hasher: AlgebraicHasher<F>,
 {
     let num_challenges = config.num_challenges;
+    let challenge_degree = config.challenge_degree::<F>(D);
 
     let mut challenger = RecursiveChallenger::<F, C::Hasher, D>::new(builder);
 
+    challenger.observe_element(trace_len);
     challenger.observe_cap(trace_cap);
 
     let lookup_challenge_set = auxiliary_polys_cap.map(|permutation_zs_cap| {
-        let tmp = get_grand_product_challenge_set_target(builder, &mut challenger, num_challenges);
+        let tmp = get_grand_product_challenge_set_target(
+            builder,
+            &mut challenger,
+            num_challenges,
+            challenge_degree,
+        );
         challenger.observe_cap(permutation_zs_cap);
         tmp
     });
 
-    let stark_alphas = challenger.get_n_challenges(builder, num_challenges);
+    let stark_alphas = challenger.get_n_challenges(builder, num_challenges * challenge_degree);
 
     challenger.observe_cap(quotient_polys_cap);
     let stark_zeta = challenger.get_extension_challenge(builder);
 
     challenger.observe_openings(&openings.to_fri_openings());
 
-    StarkProofChallengesTarget {
-        lookup_challenge_set,
-        stark_alphas,
-        stark_zeta,
-        fri_challenges: challenger.fri_challenges(
+    let fri_challenges = match degree {
+        Some(degree) => challenger.fri_challenges_with_degree_range(
+            builder,
+            commit_phase_merkle_caps,
+            final_poly,
+            pow_witness,
+            degree,
+        ),
+        None => challenger.fri_challenges(
             builder,
             commit_phase_merkle_caps,
             final_poly,
             pow_witness,
             &config.fri_config,
         ),
+    };
+
+    StarkProofChallengesTarget {
+        lookup_challenge_set,
+        stark_alphas,
+        stark_zeta,
+        fri_challenges,
     }
 }
 
@@ -168,6 +197,7 @@ impl<const D: usize> StarkProofWithPublicInputsTarget<D> {
         &self,
         builder: &mut CircuitBuilder<F, D>,
         config: &StarkConfig,
+        degree: Option<&FriDegreeRangeTarget>,
     ) -> StarkProofChallengesTarget<D>
     where
         F: RichField + Extendable<D>,
@@ -188,8 +218,27 @@ impl<const D: usize> StarkProofWithPublicInputsTarget<D> {
                 },
         } = &self.proof;
 
+        // The recursive verifier only handles power-of-two trace lengths.
+        let trace_len = match degree {
+            Some(degree) => {
+                let trace_lens = degree
+                    .params
+                    .iter()
+                    .map(|params| {
+                        builder.constant(F::from_canonical_usize(1 << params.degree_bits))
+                    })
+                    .collect();
+                degree.select(builder, trace_lens)
+            }
+            None => {
+                let degree_bits = self.proof.recover_degree_bits(config);
+                builder.constant(F::from_canonical_usize(1 << degree_bits))
+            }
+        };
+
         get_challenges_target::<F, C, D>(
             builder,
+            trace_len,
             trace_cap,
             auxiliary_polys_cap.as_ref(),
             quotient_polys_cap,
@@ -198,6 +247,7 @@ impl<const D: usize> StarkProofWithPublicInputsTarget<D> {
             final_poly,
             *pow_witness,
             config,
+            degree,
         )
     }
 }
@@ -9,13 +9,19 @@ mod get_challenges;
 
 pub mod config;
 pub mod constraint_consumer;
+#[cfg(feature = "std")]
+pub mod constraint_linter;
 pub mod evaluation_frame;
+pub mod expression;
 pub mod lookup;
 pub mod proof;
 pub mod prover;
 pub mod recursive_verifier;
 pub mod stark;
 pub mod stark_testing;
+#[cfg(feature = "std")]
+pub mod symbolic;
+pub mod trace;
 pub mod util;
 pub mod vanishing_poly;
 pub mod verifier;
@@ -1,3 +1,4 @@
+use alloc::collections::BTreeMap;
 use alloc::vec;
 use alloc::vec::Vec;
 use core::borrow::Borrow;
@@ -10,16 +11,14 @@ use plonky2::field::batch_util::batch_add_inplace;
 use plonky2::field::extension::{Extendable, FieldExtension};
 use plonky2::field::packed::PackedField;
 use plonky2::field::polynomial::PolynomialValues;
-use plonky2::field::types::Field;
+use plonky2::field::types::{Field, PrimeField64};
 use plonky2::hash::hash_types::RichField;
 use plonky2::iop::challenger::{Challenger, RecursiveChallenger};
 use plonky2::iop::ext_target::ExtensionTarget;
 use plonky2::iop::target::Target;
 use plonky2::plonk::circuit_builder::CircuitBuilder;
 use plonky2::plonk::config::{AlgebraicHasher, Hasher};
-use plonky2::plonk::plonk_common::{
-    reduce_with_powers, reduce_with_powers_circuit, reduce_with_powers_ext_circuit,
-};
+use plonky2::plonk::plonk_common::reduce_with_powers_ext_circuit;
 use plonky2::util::serialization::{Buffer, IoResult, Read, Write};
 use plonky2_util::ceil_div_usize;
 
@@ -403,13 +402,24 @@ impl<F: Field> Column<F> {
 pub(crate) type ColumnFilter<'a, F> = (&'a [Column<F>], &'a Option<Filter<F>>);
 
 pub struct Lookup<F: Field> {
-    /// Columns whose values should be contained in the lookup table.
-    /// These are the f_i(x) polynomials in the logUp paper.
-    pub columns: Vec<Column<F>>,
-    /// Column containing the lookup table.
+    /// Tuples of columns whose values should be contained in the lookup table. Each tuple has as
+    /// many columns as the table. These are the f_i(x) polynomials in the logUp paper.
+    pub columns: Vec<Vec<Column<F>>>,
+    /// Columns containing the lookup table. The columns of the table, like those of each looking
+    /// tuple, are combined into a single value with the `GrandProductChallenge` randomness.
     /// This is the t(x) polynomial in the paper.
-    pub table_column: Column<F>,
-    /// Column containing the frequencies of `columns` in `table_column`.
+    ///
+    /// These are ordinary trace columns, committed along with the rest of the trace, so the lookup
+    /// argument alone lets the prover choose them. A table meant to be fixed must either be pinned
+    /// down by the constraints of the STARK, e.g. by constraining a counter column to start at zero
+    /// and increase by one on each row, or be given by `preprocessed_table_columns`.
+    pub table_columns: Vec<Column<F>>,
+    /// Fixed columns of the table, given by their values on each row of the trace, which follow
+    /// `table_columns` in each row of the table. They are not committed: the verifier evaluates
+    /// them itself, so they can't be chosen by the prover. Evaluating them costs the verifier a
+    /// number of operations linear in the length of the trace.
+    pub preprocessed_table_columns: Vec<PolynomialValues<F>>,
+    /// Column containing the frequencies of `columns` in the table, see `compute_frequencies`.
     /// This is the m(x) polynomial in the paper.
     pub frequencies_column: Column<F>,
 
@@ -419,6 +429,56 @@ pub struct Lookup<F: Field> {
 }
 
 impl<F: Field> Lookup<F> {
+    /// Creates a lookup of single columns into a single-column table.
+    pub fn new_single_column(
+        columns: Vec<Column<F>>,
+        table_column: Column<F>,
+        frequencies_column: Column<F>,
+        filter_columns: Vec<Option<Filter<F>>>,
+    ) -> Self {
+        Self {
+            columns: columns.into_iter().map(|col| vec![col]).collect(),
+            table_columns: vec![table_column],
+            preprocessed_table_columns: vec![],
+            frequencies_column,
+            filter_columns,
+        }
+    }
+
+    /// Creates a lookup of tuples of columns into a fixed table, given by the values of its columns
+    /// on each row of the trace.
+    pub fn new_preprocessed(
+        columns: Vec<Vec<Column<F>>>,
+        preprocessed_table_columns: Vec<PolynomialValues<F>>,
+        frequencies_column: Column<F>,
+        filter_columns: Vec<Option<Filter<F>>>,
+    ) -> Self {
+        Self {
+            columns,
+            table_columns: vec![],
+            preprocessed_table_columns,
+            frequencies_column,
+            filter_columns,
+        }
+    }
+
+    /// The column of a single-column table given by the trace.
+    ///
+    /// Panics if the table has another shape.
+    #[deprecated(note = "tables may have several columns, use `table_columns` instead")]
+    pub fn table_column(&self) -> &Column<F> {
+        assert!(
+            self.table_columns.len() == 1 && self.preprocessed_table_columns.is_empty(),
+            "The table doesn't consist of a single trace column."
+        );
+        &self.table_columns[0]
+    }
+
+    /// The number of columns of the table, including preprocessed ones.
+    pub fn table_width(&self) -> usize {
+        self.table_columns.len() + self.preprocessed_table_columns.len()
+    }
+
     pub fn num_helper_columns(&self, constraint_degree: usize) -> usize {
         // One helper column for each column batch of size `constraint_degree-1`,
         // then one column for the inverse of `table + challenge` and one for the `Z` polynomial.
@@ -426,6 +486,56 @@ impl<F: Field> Lookup<F> {
     }
 }
 
+impl<F: PrimeField64> Lookup<F> {
+    /// Computes the values of the frequencies column, given a trace in column-major form.
+    ///
+    /// This lets the frequencies be filled in after the rest of the trace has been generated,
+    /// which is convenient when the table doesn't depend on the values being looked up, and in
+    /// particular for preprocessed tables. When a tuple appears several times in the table, its
+    /// whole frequency is assigned to its first occurrence.
+    ///
+    /// Panics if a looking tuple isn't in the table.
+    pub fn compute_frequencies(&self, trace: &[PolynomialValues<F>]) -> Vec<F> {
+        let num_rows = trace[0].len();
+        let row_key = |cols: &[Column<F>], row: usize| {
+            cols.iter()
+                .map(|col| col.eval_table(trace, row).to_canonical_u64())
+                .collect::<Vec<_>>()
+        };
+
+        let mut table_rows = BTreeMap::new();
+        for row in 0..num_rows {
+            let mut key = row_key(&self.table_columns, row);
+            key.extend(
+                self.preprocessed_table_columns
+                    .iter()
+                    .map(|col| col.values[row].to_canonical_u64()),
+            );
+            table_rows.entry(key).or_insert(row);
+        }
+
+        let mut frequencies = vec![F::ZERO; num_rows];
+        for (cols, filter) in self.columns.iter().zip(&self.filter_columns) {
+            for row in 0..num_rows {
+                if let Some(filter) = filter {
+                    let f = filter.eval_table(trace, row);
+                    if f.is_zero() {
+                        continue;
+                    }
+                    assert!(f.is_one(), "Non-binary filter?");
+                }
+                let key = row_key(cols, row);
+                let &table_row = table_rows
+                    .get(&key)
+                    .unwrap_or_else(|| panic!("Value {key:?} of row {row} is not in the table"));
+                frequencies[table_row] += F::ONE;
+            }
+        }
+
+        frequencies
+    }
+}
+
 /// Randomness for a single instance of a permutation check protocol.
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
 pub(crate) struct GrandProductChallenge<T: Copy + Eq + PartialEq + Debug> {
@@ -435,41 +545,136 @@ pub(crate) struct GrandProductChallenge<T: Copy + Eq + PartialEq + Debug> {
     pub(crate) gamma: T,
 }
 
-impl<F: Field> GrandProductChallenge<F> {
-    pub(crate) fn combine<'a, FE, P, T: IntoIterator<Item = &'a P>, const D2: usize>(
+impl<E: Field> GrandProductChallenge<E> {
+    /// Combines base field values into `sum beta^i terms_i + gamma`, in the field of the challenge.
+    pub(crate) fn combine<'a, F, T, const DE: usize>(&self, terms: T) -> E
+    where
+        F: Field,
+        E: FieldExtension<DE, BaseField = F>,
+        T: IntoIterator<Item = &'a F>,
+        T::IntoIter: DoubleEndedIterator,
+    {
+        terms.into_iter().rev().fold(E::ZERO, |acc, &term| {
+            acc * self.beta + E::from_basefield(term)
+        }) + self.gamma
+    }
+
+    /// Like `combine`, for packed values of a field `FE` containing `F`, and a challenge in the
+    /// degree `D` extension of `F`. The result is given by its coefficients, of which only the first
+    /// `challenge_degree` are computed: all of them for challenges drawn from the extension, and
+    /// the first one for challenges drawn from `F`.
+    pub(crate) fn combine_packed<'a, F, FE, P, T, const D: usize, const D2: usize>(
         &self,
         terms: T,
-    ) -> P
+        challenge_degree: usize,
+    ) -> [P; D]
     where
+        F: Field,
+        E: FieldExtension<D, BaseField = F>,
         FE: FieldExtension<D2, BaseField = F>,
         P: PackedField<Scalar = FE>,
-        T::IntoIter: DoubleEndedIterator,
+        T: IntoIterator<Item = &'a P>,
     {
-        reduce_with_powers(terms, FE::from_basefield(self.beta)) + FE::from_basefield(self.gamma)
+        let mut res = [P::ZEROS; D];
+        for (r, g) in res
+            .iter_mut()
+            .zip(self.gamma.to_basefield_array())
+            .take(challenge_degree)
+        {
+            *r = FE::from_basefield(g).into();
+        }
+        let mut power = E::ONE;
+        for &term in terms {
+            for (r, p) in res
+                .iter_mut()
+                .zip(power.to_basefield_array())
+                .take(challenge_degree)
+            {
+                *r += term * FE::from_basefield(p);
+            }
+            power *= self.beta;
+        }
+        res
     }
 }
 
-impl GrandProductChallenge<Target> {
-    pub(crate) fn combine_circuit<F: RichField + Extendable<D>, const D: usize>(
+impl<const D: usize> GrandProductChallenge<ExtensionTarget<D>> {
+    /// Circuit version of `combine_packed`.
+    pub(crate) fn combine_circuit<F: RichField + Extendable<D>>(
         &self,
         builder: &mut CircuitBuilder<F, D>,
         terms: &[ExtensionTarget<D>],
-    ) -> ExtensionTarget<D> {
-        let reduced = reduce_with_powers_ext_circuit(builder, terms, self.beta);
-        let gamma = builder.convert_to_ext(self.gamma);
-        builder.add_extension(reduced, gamma)
+        challenge_degree: usize,
+    ) -> [ExtensionTarget<D>; D] {
+        let mut res = [builder.zero_extension(); D];
+        if challenge_degree == 1 {
+            let reduced = reduce_with_powers_ext_circuit(builder, terms, self.beta.0[0]);
+            let gamma = builder.convert_to_ext(self.gamma.0[0]);
+            res[0] = builder.add_extension(reduced, gamma);
+            return res;
+        }
+
+        for (r, &g) in res.iter_mut().zip(self.gamma.0.iter()) {
+            *r = builder.convert_to_ext(g);
+        }
+        let mut power = builder.one_extension();
+        for &term in terms {
+            for (r, &p) in res.iter_mut().zip(power.0.iter()) {
+                *r = builder.scalar_mul_add_extension(p, term, *r);
+            }
+            power = builder.mul_extension(power, self.beta);
+        }
+        res
     }
 }
 
-impl GrandProductChallenge<Target> {
-    pub(crate) fn combine_base_circuit<F: RichField + Extendable<D>, const D: usize>(
-        &self,
-        builder: &mut CircuitBuilder<F, D>,
-        terms: &[Target],
-    ) -> Target {
-        let reduced = reduce_with_powers_circuit(builder, terms, self.beta);
-        builder.add(reduced, self.gamma)
+/// Multiplies two elements of the extension of `F` from which the lookup challenges are drawn,
+/// given by their coefficients, which are packed values of a field `FE` containing `F`. Only the
+/// first `challenge_degree` coefficients are used, `challenge_degree` being either `D` or 1 for
+/// challenges drawn from `F`.
+fn mul_packed<F, FE, P, const D: usize, const D2: usize>(
+    a: &[P; D],
+    b: &[P; D],
+    challenge_degree: usize,
+) -> [P; D]
+where
+    F: RichField + Extendable<D>,
+    FE: FieldExtension<D2, BaseField = F>,
+    P: PackedField<Scalar = FE>,
+{
+    let w = FE::from_basefield(F::W);
+    let mut res = [P::ZEROS; D];
+    for (i, &x) in a.iter().enumerate().take(challenge_degree) {
+        for (j, &y) in b.iter().enumerate().take(challenge_degree) {
+            if i + j < challenge_degree {
+                res[i + j] += x * y;
+            } else {
+                res[i + j - challenge_degree] += x * y * w;
+            }
+        }
     }
+    res
+}
+
+/// Circuit version of `mul_packed`.
+fn mul_circuit<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    a: &[ExtensionTarget<D>; D],
+    b: &[ExtensionTarget<D>; D],
+    challenge_degree: usize,
+) -> [ExtensionTarget<D>; D] {
+    let mut res = [builder.zero_extension(); D];
+    for (i, &x) in a.iter().enumerate().take(challenge_degree) {
+        for (j, &y) in b.iter().enumerate().take(challenge_degree) {
+            if i + j < challenge_degree {
+                res[i + j] = builder.mul_add_extension(x, y, res[i + j]);
+            } else {
+                let k = i + j - challenge_degree;
+                res[k] = builder.arithmetic_extension(F::W, F::ONE, x, y, res[k]);
+            }
+        }
+    }
+    res
 }
 
 /// Like `GrandProductChallenge`, but with `num_challenges` copies to boost soundness.
@@ -478,12 +683,12 @@ pub struct GrandProductChallengeSet<T: Copy + Eq + PartialEq + Debug> {
     pub(crate) challenges: Vec<GrandProductChallenge<T>>,
 }
 
-impl GrandProductChallengeSet<Target> {
+impl<const D: usize> GrandProductChallengeSet<ExtensionTarget<D>> {
     pub(crate) fn to_buffer(&self, buffer: &mut Vec<u8>) -> IoResult<()> {
         buffer.write_usize(self.challenges.len())?;
         for challenge in &self.challenges {
-            buffer.write_target(challenge.beta)?;
-            buffer.write_target(challenge.gamma)?;
+            buffer.write_target_ext(challenge.beta)?;
+            buffer.write_target_ext(challenge.gamma)?;
         }
         Ok(())
     }
@@ -493,8 +698,8 @@ impl GrandProductChallengeSet<Target> {
         let mut challenges = Vec::with_capacity(length);
         for _ in 0..length {
             challenges.push(GrandProductChallenge {
-                beta: buffer.read_target()?,
-                gamma: buffer.read_target()?,
+                beta: buffer.read_target_ext()?,
+                gamma: buffer.read_target_ext()?,
             });
         }
 
@@ -502,24 +707,57 @@ impl GrandProductChallengeSet<Target> {
     }
 }
 
-fn get_grand_product_challenge<F: RichField, H: Hasher<F>>(
+/// Draws a challenge from the extension of `F` of degree `challenge_degree`, which is either `D`
+/// or 1 for a challenge in `F`.
+fn get_challenge<F: RichField + Extendable<D>, H: Hasher<F>, const D: usize>(
+    challenger: &mut Challenger<F, H>,
+    challenge_degree: usize,
+) -> F::Extension {
+    if challenge_degree == 1 {
+        challenger.get_challenge().into()
+    } else {
+        challenger.get_extension_challenge::<D>()
+    }
+}
+
+fn get_grand_product_challenge<F: RichField + Extendable<D>, H: Hasher<F>, const D: usize>(
     challenger: &mut Challenger<F, H>,
-) -> GrandProductChallenge<F> {
-    let beta = challenger.get_challenge();
-    let gamma = challenger.get_challenge();
+    challenge_degree: usize,
+) -> GrandProductChallenge<F::Extension> {
+    let beta = get_challenge(challenger, challenge_degree);
+    let gamma = get_challenge(challenger, challenge_degree);
     GrandProductChallenge { beta, gamma }
 }
 
-pub(crate) fn get_grand_product_challenge_set<F: RichField, H: Hasher<F>>(
+pub(crate) fn get_grand_product_challenge_set<
+    F: RichField + Extendable<D>,
+    H: Hasher<F>,
+    const D: usize,
+>(
     challenger: &mut Challenger<F, H>,
     num_challenges: usize,
-) -> GrandProductChallengeSet<F> {
+    challenge_degree: usize,
+) -> GrandProductChallengeSet<F::Extension> {
     let challenges = (0..num_challenges)
-        .map(|_| get_grand_product_challenge(challenger))
+        .map(|_| get_grand_product_challenge(challenger, challenge_degree))
         .collect();
     GrandProductChallengeSet { challenges }
 }
 
+/// Circuit version of `get_challenge`.
+fn get_challenge_target<F: RichField + Extendable<D>, H: AlgebraicHasher<F>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    challenger: &mut RecursiveChallenger<F, H, D>,
+    challenge_degree: usize,
+) -> ExtensionTarget<D> {
+    if challenge_degree == 1 {
+        let challenge = challenger.get_challenge(builder);
+        builder.convert_to_ext(challenge)
+    } else {
+        challenger.get_extension_challenge(builder)
+    }
+}
+
 fn get_grand_product_challenge_target<
     F: RichField + Extendable<D>,
     H: AlgebraicHasher<F>,
@@ -527,9 +765,10 @@ fn get_grand_product_challenge_target<
 >(
     builder: &mut CircuitBuilder<F, D>,
     challenger: &mut RecursiveChallenger<F, H, D>,
-) -> GrandProductChallenge<Target> {
-    let beta = challenger.get_challenge(builder);
-    let gamma = challenger.get_challenge(builder);
+    challenge_degree: usize,
+) -> GrandProductChallenge<ExtensionTarget<D>> {
+    let beta = get_challenge_target(builder, challenger, challenge_degree);
+    let gamma = get_challenge_target(builder, challenger, challenge_degree);
     GrandProductChallenge { beta, gamma }
 }
 
@@ -541,54 +780,66 @@ pub(crate) fn get_grand_product_challenge_set_target<
     builder: &mut CircuitBuilder<F, D>,
     challenger: &mut RecursiveChallenger<F, H, D>,
     num_challenges: usize,
-) -> GrandProductChallengeSet<Target> {
+    challenge_degree: usize,
+) -> GrandProductChallengeSet<ExtensionTarget<D>> {
     let challenges = (0..num_challenges)
-        .map(|_| get_grand_product_challenge_target(builder, challenger))
+        .map(|_| get_grand_product_challenge_target(builder, challenger, challenge_degree))
         .collect();
     GrandProductChallengeSet { challenges }
 }
 
 /// logUp protocol from <https://ia.cr/2022/1530>
 /// Compute the helper columns for the lookup argument.
-/// Given columns `f0,...,fk` and a column `t`, such that `∪fi ⊆ t`, and challenges `x`,
+/// Given column tuples `f0,...,fk` and a table `t`, such that `∪fi ⊆ t`, and challenges `x`,
 /// this computes the helper columns `h_i = 1/(x+f_2i) + 1/(x+f_2i+1)`, `g = 1/(x+t)`,
 /// and `Z(gx) = Z(x) + sum h_i(x) - m(x)g(x)` where `m` is the frequencies column.
-pub(crate) fn lookup_helper_columns<F: Field>(
+/// Tuples and table rows are reduced to single values with the `beta` challenge, and `x` is the
+/// `gamma` challenge.
+/// The challenges are in a field `E`, either `F` or an extension of it, and each helper column is
+/// returned as the `DE` columns of the coefficients of its values.
+pub(crate) fn lookup_helper_columns<F, E, const DE: usize>(
     lookup: &Lookup<F>,
     trace_poly_values: &[PolynomialValues<F>],
-    challenge: F,
+    challenge: GrandProductChallenge<E>,
     constraint_degree: usize,
-) -> Vec<PolynomialValues<F>> {
+) -> Vec<PolynomialValues<F>>
+where
+    F: Field,
+    E: FieldExtension<DE, BaseField = F>,
+{
     assert!(
         constraint_degree == 2 || constraint_degree == 3,
         "TODO: Allow other constraint degrees."
     );
 
     assert_eq!(lookup.columns.len(), lookup.filter_columns.len());
+    assert!(
+        lookup
+            .columns
+            .iter()
+            .all(|cols| cols.len() == lookup.table_width()),
+        "Looking tuples must have as many columns as the table."
+    );
+    assert!(
+        lookup
+            .preprocessed_table_columns
+            .iter()
+            .all(|col| col.len() == trace_poly_values[0].len()),
+        "Preprocessed table columns must be as long as the trace."
+    );
 
     let num_total_logup_entries = trace_poly_values[0].values.len() * lookup.columns.len();
     assert!(BigUint::from(num_total_logup_entries) < F::characteristic());
 
     let num_helper_columns = lookup.num_helper_columns(constraint_degree);
-    let mut helper_columns: Vec<PolynomialValues<F>> = Vec::with_capacity(num_helper_columns);
 
-    let looking_cols = lookup
+    let columns_filters = lookup
         .columns
-        .iter()
-        .map(|col| vec![col.clone()])
-        .collect::<Vec<Vec<Column<F>>>>();
-
-    let grand_challenge = GrandProductChallenge {
-        beta: F::ONE,
-        gamma: challenge,
-    };
-
-    let columns_filters = looking_cols
         .iter()
         .zip(lookup.filter_columns.iter())
         .map(|(col, filter)| (&col[..], filter))
         .collect::<Vec<_>>();
-    // For each batch of `constraint_degree-1` columns `fi`, compute `sum 1/(f_i+challenge)` and
+    // For each batch of `constraint_degree-1` tuples `fi`, compute `sum 1/(combine(f_i)+challenge)` and
     // add it to the helper columns.
     // Note: these are the h_k(x) polynomials in the paper, with a few differences:
     //       * Here, the first ratio m_0(x)/phi_0(x) is not included with the columns batched up to create the
@@ -601,18 +852,28 @@ pub(crate) fn lookup_helper_columns<F: Field>(
         trace_poly_values,
         trace_poly_values[0].len(),
         &columns_filters,
-        grand_challenge,
+        challenge,
         constraint_degree,
     );
 
     // Add `1/(table+challenge)` to the helper columns.
-    // This is 1/phi_0(x) = 1/(x + t(x)) from the paper.
+    // This is 1/phi_0(x) = 1/(x + t(x)) from the paper, where the table columns are combined into t(x).
     // Here, we don't include m(x) in the numerator, instead multiplying it with this column later.
-    let mut table = lookup.table_column.eval_all_rows(trace_poly_values);
-    for x in table.iter_mut() {
-        *x = challenge + *x;
-    }
-    let table_inverse: Vec<F> = F::batch_multiplicative_inverse(&table);
+    let table_columns = lookup
+        .table_columns
+        .iter()
+        .map(|col| col.eval_all_rows(trace_poly_values))
+        .chain(
+            lookup
+                .preprocessed_table_columns
+                .iter()
+                .map(|col| col.values.clone()),
+        )
+        .collect::<Vec<_>>();
+    let table = (0..trace_poly_values[0].len())
+        .map(|i| challenge.combine(table_columns.iter().map(|col| &col[i])))
+        .collect::<Vec<E>>();
+    let table_inverse: Vec<E> = E::batch_multiplicative_inverse(&table);
 
     // Compute the `Z` polynomial with `Z(1)=0` and `Z(gx) = Z(x) + sum h_i(x) - frequencies(x)g(x)`.
     // This enforces the check from the paper, that the sum of the h_k(x) polynomials is 0 over H.
@@ -620,29 +881,41 @@ pub(crate) fn lookup_helper_columns<F: Field>(
     // into the h_k(x) polynomials.
     let frequencies = &lookup.frequencies_column.eval_all_rows(trace_poly_values);
     let mut z = Vec::with_capacity(frequencies.len());
-    z.push(F::ZERO);
+    z.push(E::ZERO);
     for i in 0..frequencies.len() - 1 {
         let x = helper_columns[..num_helper_columns - 1]
             .iter()
             .map(|col| col.values[i])
-            .sum::<F>()
-            - frequencies[i] * table_inverse[i];
+            .sum::<E>()
+            - table_inverse[i].scalar_mul(frequencies[i]);
         z.push(z[i] + x);
     }
     helper_columns.push(z.into());
 
     helper_columns
+        .into_iter()
+        .flat_map(|column| {
+            let coeffs = column
+                .values
+                .iter()
+                .map(|v| v.to_basefield_array())
+                .collect::<Vec<_>>();
+            (0..DE).map(move |k| coeffs.iter().map(|c| c[k]).collect::<Vec<_>>().into())
+        })
+        .collect()
 }
 
-/// Given data associated to a lookup, check the associated helper polynomials.
+/// Given data associated to a lookup, check the associated helper polynomials, given by their
+/// first `challenge_degree` coefficients.
 pub(crate) fn eval_helper_columns<F, FE, P, const D: usize, const D2: usize>(
     filter: &[Option<Filter<F>>],
     columns: &[Vec<P>],
     local_values: &[P],
     next_values: &[P],
-    helper_columns: &[P],
+    helper_columns: &[[P; D]],
     constraint_degree: usize,
-    challenges: &GrandProductChallenge<F>,
+    challenges: &GrandProductChallenge<F::Extension>,
+    challenge_degree: usize,
     consumer: &mut ConstraintConsumer<P>,
 ) where
     F: RichField + Extendable<D>,
@@ -653,12 +926,12 @@ pub(crate) fn eval_helper_columns<F, FE, P, const D: usize, const D2: usize>(
         for (j, chunk) in columns.chunks(constraint_degree - 1).enumerate() {
             let fs =
                 &filter[(constraint_degree - 1) * j..(constraint_degree - 1) * j + chunk.len()];
-            let h = helper_columns[j];
+            let h = &helper_columns[j];
 
             match chunk.len() {
                 2 => {
-                    let combin0 = challenges.combine(&chunk[0]);
-                    let combin1 = challenges.combine(chunk[1].iter());
+                    let combin0 = challenges.combine_packed(&chunk[0], challenge_degree);
+                    let combin1 = challenges.combine_packed(&chunk[1], challenge_degree);
 
                     let f0 = if let Some(filter0) = &fs[0] {
                         filter0.eval_filter(local_values, next_values)
@@ -671,16 +944,29 @@ pub(crate) fn eval_helper_columns<F, FE, P, const D: usize, const D2: usize>(
                         P::ONES
                     };
 
-                    consumer.constraint(combin1 * combin0 * h - f0 * combin1 - f1 * combin0);
+                    let combin = mul_packed(&combin1, &combin0, challenge_degree);
+                    let constr = mul_packed(&combin, h, challenge_degree);
+                    for ((&c, &c0), &c1) in constr
+                        .iter()
+                        .zip(&combin0)
+                        .zip(&combin1)
+                        .take(challenge_degree)
+                    {
+                        consumer.constraint(c - f0 * c1 - f1 * c0);
+                    }
                 }
                 1 => {
-                    let combin = challenges.combine(&chunk[0]);
+                    let combin = challenges.combine_packed(&chunk[0], challenge_degree);
                     let f0 = if let Some(filter1) = &fs[0] {
                         filter1.eval_filter(local_values, next_values)
                     } else {
                         P::ONES
                     };
-                    consumer.constraint(combin * h - f0);
+                    let mut constr = mul_packed(&combin, h, challenge_degree);
+                    constr[0] -= f0;
+                    for &c in constr.iter().take(challenge_degree) {
+                        consumer.constraint(c);
+                    }
                 }
 
                 _ => todo!("Allow other constraint degrees"),
@@ -697,22 +983,23 @@ pub(crate) fn eval_helper_columns_circuit<F: RichField + Extendable<D>, const D:
     columns: &[Vec<ExtensionTarget<D>>],
     local_values: &[ExtensionTarget<D>],
     next_values: &[ExtensionTarget<D>],
-    helper_columns: &[ExtensionTarget<D>],
+    helper_columns: &[[ExtensionTarget<D>; D]],
     constraint_degree: usize,
-    challenges: &GrandProductChallenge<Target>,
+    challenges: &GrandProductChallenge<ExtensionTarget<D>>,
+    challenge_degree: usize,
     consumer: &mut RecursiveConstraintConsumer<F, D>,
 ) {
     if !helper_columns.is_empty() {
         for (j, chunk) in columns.chunks(constraint_degree - 1).enumerate() {
             let fs =
                 &filter[(constraint_degree - 1) * j..(constraint_degree - 1) * j + chunk.len()];
-            let h = helper_columns[j];
+            let h = &helper_columns[j];
 
             let one = builder.one_extension();
             match chunk.len() {
                 2 => {
-                    let combin0 = challenges.combine_circuit(builder, &chunk[0]);
-                    let combin1 = challenges.combine_circuit(builder, &chunk[1]);
+                    let combin0 = challenges.combine_circuit(builder, &chunk[0], challenge_degree);
+                    let combin1 = challenges.combine_circuit(builder, &chunk[1], challenge_degree);
 
                     let f0 = if let Some(filter0) = &fs[0] {
                         filter0.eval_filter_circuit(builder, local_values, next_values)
@@ -725,22 +1012,27 @@ pub(crate) fn eval_helper_columns_circuit<F: RichField + Extendable<D>, const D:
                         one
                     };
 
-                    let constr = builder.mul_sub_extension(combin0, h, f0);
-                    let constr = builder.mul_extension(constr, combin1);
-                    let f1_constr = builder.mul_extension(f1, combin0);
-                    let constr = builder.sub_extension(constr, f1_constr);
-
-                    consumer.constraint(builder, constr);
+                    let mut constr = mul_circuit(builder, &combin0, h, challenge_degree);
+                    constr[0] = builder.sub_extension(constr[0], f0);
+                    let constr = mul_circuit(builder, &constr, &combin1, challenge_degree);
+                    for (&c, &c0) in constr.iter().zip(&combin0).take(challenge_degree) {
+                        let f1_constr = builder.mul_extension(f1, c0);
+                        let c = builder.sub_extension(c, f1_constr);
+                        consumer.constraint(builder, c);
+                    }
                 }
                 1 => {
-                    let combin = challenges.combine_circuit(builder, &chunk[0]);
+                    let combin = challenges.combine_circuit(builder, &chunk[0], challenge_degree);
                     let f0 = if let Some(filter1) = &fs[0] {
                         filter1.eval_filter_circuit(builder, local_values, next_values)
                     } else {
                         one
                     };
-                    let constr = builder.mul_sub_extension(combin, h, f0);
-                    consumer.constraint(builder, constr);
+                    let mut constr = mul_circuit(builder, &combin, h, challenge_degree);
+                    constr[0] = builder.sub_extension(constr[0], f0);
+                    for &c in constr.iter().take(challenge_degree) {
+                        consumer.constraint(builder, c);
+                    }
                 }
 
                 _ => todo!("Allow other constraint degrees"),
@@ -750,14 +1042,18 @@ pub(crate) fn eval_helper_columns_circuit<F: RichField + Extendable<D>, const D:
 }
 
 /// Given a STARK's trace, and the data associated to one lookup (either CTL or range check),
-/// returns the associated helper polynomials.
-pub(crate) fn get_helper_cols<F: Field>(
+/// returns the associated helper polynomials, with values in the field `E` of the challenge.
+pub(crate) fn get_helper_cols<F, E, const DE: usize>(
     trace: &[PolynomialValues<F>],
     degree: usize,
     columns_filters: &[ColumnFilter<F>],
-    challenge: GrandProductChallenge<F>,
+    challenge: GrandProductChallenge<E>,
     constraint_degree: usize,
-) -> Vec<PolynomialValues<F>> {
+) -> Vec<PolynomialValues<E>>
+where
+    F: Field,
+    E: FieldExtension<DE, BaseField = F>,
+{
     let num_helper_columns = ceil_div_usize(columns_filters.len(), constraint_degree - 1);
 
     let mut helper_columns = Vec::with_capacity(num_helper_columns);
@@ -786,15 +1082,15 @@ pub(crate) fn get_helper_cols<F: Field>(
                 } else {
                     assert_eq!(f, F::ZERO, "Non-binary filter?");
                     // Dummy value. Cannot be zero since it will be batch-inverted.
-                    F::ONE
+                    E::ONE
                 }
             })
-            .collect::<Vec<F>>();
+            .collect::<Vec<E>>();
 
-        let mut acc = F::batch_multiplicative_inverse(&first_combined);
+        let mut acc = E::batch_multiplicative_inverse(&first_combined);
         for d in 0..degree {
             if filter_col[d].is_zero() {
-                acc[d] = F::ZERO;
+                acc[d] = E::ZERO;
             }
         }
 
@@ -819,16 +1115,16 @@ pub(crate) fn get_helper_cols<F: Field>(
                     } else {
                         assert_eq!(f, F::ZERO, "Non-binary filter?");
                         // Dummy value. Cannot be zero since it will be batch-inverted.
-                        F::ONE
+                        E::ONE
                     }
                 })
-                .collect::<Vec<F>>();
+                .collect::<Vec<E>>();
 
-            combined = F::batch_multiplicative_inverse(&combined);
+            combined = E::batch_multiplicative_inverse(&combined);
 
             for d in 0..degree {
                 if filter_col[d].is_zero() {
-                    combined[d] = F::ZERO;
+                    combined[d] = E::ZERO;
                 }
             }
 
@@ -842,15 +1138,70 @@ pub(crate) fn get_helper_cols<F: Field>(
     helper_columns
 }
 
-pub(crate) struct LookupCheckVars<F, FE, P, const D2: usize>
+pub(crate) struct LookupCheckVars<F, FE, P, const D: usize, const D2: usize>
 where
-    F: Field,
+    F: Extendable<D>,
     FE: FieldExtension<D2, BaseField = F>,
     P: PackedField<Scalar = FE>,
 {
     pub(crate) local_values: Vec<P>,
     pub(crate) next_values: Vec<P>,
-    pub(crate) challenges: Vec<F>,
+    pub(crate) challenges: Vec<GrandProductChallenge<F::Extension>>,
+    /// The values of the preprocessed table columns of all lookups, in order.
+    pub(crate) preprocessed_table_values: Vec<P>,
+    /// The number of columns holding the coefficients of each helper column, `D` for challenges
+    /// drawn from the extension and 1 for challenges drawn from `F`.
+    pub(crate) challenge_degree: usize,
+}
+
+/// Evaluates the preprocessed table columns of all lookups, in order, at `x`.
+pub(crate) fn eval_preprocessed_table_columns<F: RichField + Extendable<D>, const D: usize>(
+    lookups: &[Lookup<F>],
+    x: F::Extension,
+) -> Vec<F::Extension> {
+    lookups
+        .iter()
+        .flat_map(|lookup| &lookup.preprocessed_table_columns)
+        .map(|col| col.clone().ifft_smooth().to_extension().eval(x))
+        .collect()
+}
+
+/// Circuit version of `eval_preprocessed_table_columns`.
+pub(crate) fn eval_preprocessed_table_columns_circuit<
+    F: RichField + Extendable<D>,
+    const D: usize,
+>(
+    builder: &mut CircuitBuilder<F, D>,
+    lookups: &[Lookup<F>],
+    x: ExtensionTarget<D>,
+) -> Vec<ExtensionTarget<D>> {
+    lookups
+        .iter()
+        .flat_map(|lookup| &lookup.preprocessed_table_columns)
+        .map(|col| {
+            let coeffs = col.clone().ifft_smooth().coeffs;
+            let zero = builder.zero_extension();
+            coeffs.into_iter().rev().fold(zero, |acc, c| {
+                let c = builder.constant_extension(F::Extension::from_basefield(c));
+                builder.mul_add_extension(acc, x, c)
+            })
+        })
+        .collect()
+}
+
+/// Returns the value of the `j`-th helper column of a lookup whose helper columns start at
+/// `start`, given by the columns of its first `challenge_degree` coefficients.
+fn helper_column<T: Copy, const D: usize>(
+    values: &[T],
+    start: usize,
+    j: usize,
+    challenge_degree: usize,
+    zero: T,
+) -> [T; D] {
+    let mut res = [zero; D];
+    let start = start + j * challenge_degree;
+    res[..challenge_degree].copy_from_slice(&values[start..start + challenge_degree]);
+    res
 }
 
 /// Constraints for the logUp lookup argument.
@@ -858,7 +1209,7 @@ pub(crate) fn eval_packed_lookups_generic<F, FE, P, S, const D: usize, const D2:
     stark: &S,
     lookups: &[Lookup<F>],
     vars: &S::EvaluationFrame<FE, P, D2>,
-    lookup_vars: LookupCheckVars<F, FE, P, D2>,
+    lookup_vars: LookupCheckVars<F, FE, P, D, D2>,
     yield_constr: &mut ConstraintConsumer<P>,
 ) where
     F: RichField + Extendable<D>,
@@ -873,20 +1224,43 @@ pub(crate) fn eval_packed_lookups_generic<F, FE, P, S, const D: usize, const D2:
         degree == 2 || degree == 3,
         "TODO: Allow other constraint degrees."
     );
+    let challenge_degree = lookup_vars.challenge_degree;
     let mut start = 0;
+    let mut preprocessed_table_values = lookup_vars.preprocessed_table_values.iter();
     for lookup in lookups {
         let num_helper_columns = lookup.num_helper_columns(degree);
-        for &challenge in &lookup_vars.challenges {
-            let grand_challenge = GrandProductChallenge {
-                beta: F::ONE,
-                gamma: challenge,
-            };
-            let lookup_columns = lookup
-                .columns
-                .iter()
-                .map(|col| vec![col.eval_with_next(local_values, next_values)])
-                .collect::<Vec<Vec<P>>>();
-
+        let lookup_columns = lookup
+            .columns
+            .iter()
+            .map(|cols| {
+                cols.iter()
+                    .map(|col| col.eval_with_next(local_values, next_values))
+                    .collect()
+            })
+            .collect::<Vec<Vec<P>>>();
+        let table_columns = lookup
+            .table_columns
+            .iter()
+            .map(|col| col.eval(local_values))
+            .chain(
+                preprocessed_table_values
+                    .by_ref()
+                    .take(lookup.preprocessed_table_columns.len())
+                    .copied(),
+            )
+            .collect::<Vec<P>>();
+        for grand_challenge in &lookup_vars.challenges {
+            let helper_columns = (0..num_helper_columns - 1)
+                .map(|j| {
+                    helper_column(
+                        &lookup_vars.local_values,
+                        start,
+                        j,
+                        challenge_degree,
+                        P::ZEROS,
+                    )
+                })
+                .collect::<Vec<[P; D]>>();
             // For each chunk, check that `h_i (x+f_2i) (x+f_{2i+1}) = (x+f_2i) * filter_{2i+1} + (x+f_{2i+1}) * filter_2i`
             // if the chunk has length 2 or if it has length 1, check that `h_i * (x+f_2i) = filter_2i`, where x is the challenge
             eval_helper_columns(
@@ -894,27 +1268,45 @@ pub(crate) fn eval_packed_lookups_generic<F, FE, P, S, const D: usize, const D2:
                 &lookup_columns,
                 local_values,
                 next_values,
-                &lookup_vars.local_values[start..start + num_helper_columns - 1],
+                &helper_columns,
                 degree,
-                &grand_challenge,
+                grand_challenge,
+                challenge_degree,
                 yield_constr,
             );
 
-            let challenge = FE::from_basefield(challenge);
-
             // Check the `Z` polynomial.
-            let z = lookup_vars.local_values[start + num_helper_columns - 1];
-            let next_z = lookup_vars.next_values[start + num_helper_columns - 1];
-            let table_with_challenge = lookup.table_column.eval(local_values) + challenge;
-            let y = lookup_vars.local_values[start..start + num_helper_columns - 1]
-                .iter()
-                .fold(P::ZEROS, |acc, x| acc + *x)
-                * table_with_challenge
-                - lookup.frequencies_column.eval(local_values);
+            let z: [P; D] = helper_column(
+                &lookup_vars.local_values,
+                start,
+                num_helper_columns - 1,
+                challenge_degree,
+                P::ZEROS,
+            );
+            let next_z: [P; D] = helper_column(
+                &lookup_vars.next_values,
+                start,
+                num_helper_columns - 1,
+                challenge_degree,
+                P::ZEROS,
+            );
+            let table_with_challenge =
+                grand_challenge.combine_packed(&table_columns, challenge_degree);
+            let helper_sum = helper_columns.iter().fold([P::ZEROS; D], |acc, h| {
+                core::array::from_fn(|k| acc[k] + h[k])
+            });
+            let mut y = mul_packed(&helper_sum, &table_with_challenge, challenge_degree);
+            y[0] -= lookup.frequencies_column.eval(local_values);
+            let z_diff = core::array::from_fn(|k| next_z[k] - z[k]);
+            let constr = mul_packed(&z_diff, &table_with_challenge, challenge_degree);
             // Check that in the first row, z = 0;
-            yield_constr.constraint_first_row(z);
-            yield_constr.constraint((next_z - z) * table_with_challenge - y);
-            start += num_helper_columns;
+            for &z_k in z.iter().take(challenge_degree) {
+                yield_constr.constraint_first_row(z_k);
+            }
+            for (&c, &y_k) in constr.iter().zip(&y).take(challenge_degree) {
+                yield_constr.constraint(c - y_k);
+            }
+            start += num_helper_columns * challenge_degree;
         }
     }
 }
@@ -922,7 +1314,9 @@ pub(crate) fn eval_packed_lookups_generic<F, FE, P, S, const D: usize, const D2:
 pub(crate) struct LookupCheckVarsTarget<const D: usize> {
     pub(crate) local_values: Vec<ExtensionTarget<D>>,
     pub(crate) next_values: Vec<ExtensionTarget<D>>,
-    pub(crate) challenges: Vec<Target>,
+    pub(crate) challenges: Vec<GrandProductChallenge<ExtensionTarget<D>>>,
+    pub(crate) preprocessed_table_values: Vec<ExtensionTarget<D>>,
+    pub(crate) challenge_degree: usize,
 }
 
 pub(crate) fn eval_ext_lookups_circuit<
@@ -936,7 +1330,7 @@ pub(crate) fn eval_ext_lookups_circuit<
     lookup_vars: LookupCheckVarsTarget<D>,
     yield_constr: &mut RecursiveConstraintConsumer<F, D>,
 ) {
-    let one = builder.one_extension();
+    let zero = builder.zero_extension();
     let degree = stark.constraint_degree();
     let lookups = stark.lookups();
 
@@ -946,57 +1340,367 @@ pub(crate) fn eval_ext_lookups_circuit<
         degree == 2 || degree == 3,
         "TODO: Allow other constraint degrees."
     );
+    let challenge_degree = lookup_vars.challenge_degree;
     let mut start = 0;
+    let mut preprocessed_table_values = lookup_vars.preprocessed_table_values.iter();
     for lookup in lookups {
         let num_helper_columns = lookup.num_helper_columns(degree);
         let col_values = lookup
             .columns
             .iter()
-            .map(|col| vec![col.eval_with_next_circuit(builder, local_values, next_values)])
+            .map(|cols| {
+                cols.iter()
+                    .map(|col| col.eval_with_next_circuit(builder, local_values, next_values))
+                    .collect()
+            })
+            .collect::<Vec<_>>();
+        let table_columns = lookup
+            .table_columns
+            .iter()
+            .map(|col| col.eval_circuit(builder, local_values))
+            .chain(
+                preprocessed_table_values
+                    .by_ref()
+                    .take(lookup.preprocessed_table_columns.len())
+                    .copied(),
+            )
             .collect::<Vec<_>>();
 
-        for &challenge in &lookup_vars.challenges {
-            let grand_challenge = GrandProductChallenge {
-                beta: builder.one(),
-                gamma: challenge,
-            };
-
+        for grand_challenge in &lookup_vars.challenges {
+            let helper_columns = (0..num_helper_columns - 1)
+                .map(|j| helper_column(&lookup_vars.local_values, start, j, challenge_degree, zero))
+                .collect::<Vec<[ExtensionTarget<D>; D]>>();
             eval_helper_columns_circuit(
                 builder,
                 &lookup.filter_columns,
                 &col_values,
                 local_values,
                 next_values,
-                &lookup_vars.local_values[start..start + num_helper_columns - 1],
+                &helper_columns,
                 degree,
-                &grand_challenge,
+                grand_challenge,
+                challenge_degree,
                 yield_constr,
             );
-            let challenge = builder.convert_to_ext(challenge);
 
-            let z = lookup_vars.local_values[start + num_helper_columns - 1];
-            let next_z = lookup_vars.next_values[start + num_helper_columns - 1];
-            let table_column = lookup
-                .table_column
-                .eval_circuit(builder, vars.get_local_values());
-            let table_with_challenge = builder.add_extension(table_column, challenge);
-            let mut y = builder.add_many_extension(
-                &lookup_vars.local_values[start..start + num_helper_columns - 1],
+            let z: [ExtensionTarget<D>; D] = helper_column(
+                &lookup_vars.local_values,
+                start,
+                num_helper_columns - 1,
+                challenge_degree,
+                zero,
             );
+            let next_z: [ExtensionTarget<D>; D] = helper_column(
+                &lookup_vars.next_values,
+                start,
+                num_helper_columns - 1,
+                challenge_degree,
+                zero,
+            );
+            let table_with_challenge =
+                grand_challenge.combine_circuit(builder, &table_columns, challenge_degree);
+            let mut y = [zero; D];
+            for (k, y_k) in y.iter_mut().enumerate().take(challenge_degree) {
+                let terms = helper_columns.iter().map(|h| h[k]).collect::<Vec<_>>();
+                *y_k = builder.add_many_extension(terms);
+            }
 
             let frequencies_column = lookup
                 .frequencies_column
                 .eval_circuit(builder, vars.get_local_values());
-            y = builder.mul_extension(y, table_with_challenge);
-            y = builder.sub_extension(y, frequencies_column);
+            let mut y = mul_circuit(builder, &y, &table_with_challenge, challenge_degree);
+            y[0] = builder.sub_extension(y[0], frequencies_column);
 
             // Check that in the first row, z = 0;
-            yield_constr.constraint_first_row(builder, z);
-            let mut constraint = builder.sub_extension(next_z, z);
-            constraint = builder.mul_extension(constraint, table_with_challenge);
-            constraint = builder.sub_extension(constraint, y);
-            yield_constr.constraint(builder, constraint);
-            start += num_helper_columns;
+            for &z_k in z.iter().take(challenge_degree) {
+                yield_constr.constraint_first_row(builder, z_k);
+            }
+            let mut z_diff = [zero; D];
+            for ((d, &z_k), &next_z_k) in z_diff
+                .iter_mut()
+                .zip(&z)
+                .zip(&next_z)
+                .take(challenge_degree)
+            {
+                *d = builder.sub_extension(next_z_k, z_k);
+            }
+            let constr = mul_circuit(builder, &z_diff, &table_with_challenge, challenge_degree);
+            for (&c, &y_k) in constr.iter().zip(&y).take(challenge_degree) {
+                let c = builder.sub_extension(c, y_k);
+                yield_constr.constraint(builder, c);
+            }
+            start += num_helper_columns * challenge_degree;
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use core::marker::PhantomData;
+
+    use anyhow::Result;
+    use plonky2::field::babybear_field::BabyBearField;
+    use plonky2::field::extension::{Extendable, FieldExtension};
+    use plonky2::field::packed::PackedField;
+    use plonky2::field::polynomial::PolynomialValues;
+    use plonky2::field::types::Field;
+    use plonky2::hash::hash_types::RichField;
+    use plonky2::iop::ext_target::ExtensionTarget;
+    use plonky2::iop::witness::PartialWitness;
+    use plonky2::plonk::circuit_builder::CircuitBuilder;
+    use plonky2::plonk::circuit_data::CircuitConfig;
+    use plonky2::plonk::config::{GenericConfig, PoseidonBabyBearConfig, PoseidonGoldilocksConfig};
+    use plonky2::util::timing::TimingTree;
+
+    use crate::config::StarkConfig;
+    use crate::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
+    use crate::evaluation_frame::StarkFrame;
+    use crate::lookup::{Column, Lookup};
+    use crate::prover::prove;
+    use crate::recursive_verifier::{
+        add_virtual_stark_proof_with_pis, set_stark_proof_with_pis_target,
+        verify_stark_proof_circuit,
+    };
+    use crate::stark::Stark;
+    use crate::util::trace_rows_to_poly_values;
+    use crate::verifier::verify_stark_proof;
+
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+
+    /// Looks up `(a, b, a ^ b)` in a table of all 2-bit XORs, and `a` and `b` in a range table.
+    /// The tables are given by the trace, which is enough to test the lookup argument, except for
+    /// the XOR table when `preprocessed` is set.
+    #[derive(Copy, Clone)]
+    struct XorStark<F: RichField + Extendable<D>, const D: usize> {
+        preprocessed: bool,
+        _phantom: PhantomData<F>,
+    }
+
+    const A: usize = 0;
+    const B: usize = 1;
+    const A_XOR_B: usize = 2;
+    const XOR_TABLE: [usize; 3] = [3, 4, 5];
+    const XOR_FREQUENCIES: usize = 6;
+    const RANGE_TABLE: usize = 7;
+    const RANGE_FREQUENCIES: usize = 8;
+    const COLUMNS: usize = 9;
+    const NUM_ROWS: usize = 16;
+
+    impl<F: RichField + Extendable<D>, const D: usize> XorStark<F, D> {
+        fn new(preprocessed: bool) -> Self {
+            Self {
+                preprocessed,
+                _phantom: PhantomData,
+            }
+        }
+
+        fn xor_table() -> Vec<PolynomialValues<F>> {
+            let column = |f: fn(u64) -> u64| {
+                PolynomialValues::new(
+                    (0..NUM_ROWS as u64)
+                        .map(f)
+                        .map(F::from_canonical_u64)
+                        .collect(),
+                )
+            };
+            vec![
+                column(|i| i >> 2),
+                column(|i| i & 3),
+                column(|i| (i >> 2) ^ (i & 3)),
+            ]
+        }
+
+        fn generate_trace(&self, values: &[(u64, u64)]) -> Vec<PolynomialValues<F>> {
+            let rows = (0..NUM_ROWS as u64)
+                .map(|i| {
+                    let (a, b) = values[i as usize % values.len()];
+                    let mut row = [F::ZERO; COLUMNS];
+                    row[A] = F::from_canonical_u64(a);
+                    row[B] = F::from_canonical_u64(b);
+                    row[A_XOR_B] = F::from_canonical_u64(a ^ b);
+                    row[XOR_TABLE[0]] = F::from_canonical_u64(i >> 2);
+                    row[XOR_TABLE[1]] = F::from_canonical_u64(i & 3);
+                    row[XOR_TABLE[2]] = F::from_canonical_u64((i >> 2) ^ (i & 3));
+                    row[RANGE_TABLE] = F::from_canonical_u64(i);
+                    row
+                })
+                .collect();
+            let mut trace = trace_rows_to_poly_values(rows);
+            let lookups = self.lookups();
+            trace[XOR_FREQUENCIES] = lookups[0].compute_frequencies(&trace).into();
+            trace[RANGE_FREQUENCIES] = lookups[1].compute_frequencies(&trace).into();
+            trace
+        }
+    }
+
+    impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for XorStark<F, D> {
+        type EvaluationFrame<FE, P, const D2: usize> = StarkFrame<P, P::Scalar, COLUMNS, 0>
+        where
+            FE: FieldExtension<D2, BaseField = F>,
+            P: PackedField<Scalar = FE>;
+
+        type EvaluationFrameTarget = StarkFrame<ExtensionTarget<D>, ExtensionTarget<D>, COLUMNS, 0>;
+
+        fn eval_packed_generic<FE, P, const D2: usize>(
+            &self,
+            _vars: &Self::EvaluationFrame<FE, P, D2>,
+            _yield_constr: &mut ConstraintConsumer<P>,
+        ) where
+            FE: FieldExtension<D2, BaseField = F>,
+            P: PackedField<Scalar = FE>,
+        {
+        }
+
+        fn eval_ext_circuit(
+            &self,
+            _builder: &mut CircuitBuilder<F, D>,
+            _vars: &Self::EvaluationFrameTarget,
+            _yield_constr: &mut RecursiveConstraintConsumer<F, D>,
+        ) {
+        }
+
+        fn constraint_degree(&self) -> usize {
+            3
+        }
+
+        fn lookups(&self) -> Vec<Lookup<F>> {
+            let xor_lookup = if self.preprocessed {
+                Lookup::new_preprocessed(
+                    vec![Column::singles([A, B, A_XOR_B]).collect()],
+                    Self::xor_table(),
+                    Column::single(XOR_FREQUENCIES),
+                    vec![None],
+                )
+            } else {
+                Lookup {
+                    columns: vec![Column::singles([A, B, A_XOR_B]).collect()],
+                    table_columns: Column::singles(XOR_TABLE).collect(),
+                    preprocessed_table_columns: vec![],
+                    frequencies_column: Column::single(XOR_FREQUENCIES),
+                    filter_columns: vec![None],
+                }
+            };
+            vec![
+                xor_lookup,
+                Lookup::new_single_column(
+                    Column::singles([A, B]).collect(),
+                    Column::single(RANGE_TABLE),
+                    Column::single(RANGE_FREQUENCIES),
+                    vec![None; 2],
+                ),
+            ]
+        }
+    }
+
+    fn prove_and_verify_recursively(config: StarkConfig, preprocessed: bool) -> Result<()> {
+        type S = XorStark<F, D>;
+
+        let stark = S::new(preprocessed);
+        let trace = stark.generate_trace(&[(1, 2), (3, 3), (0, 2)]);
+        assert_eq!(
+            trace[XOR_FREQUENCIES].values.iter().copied().sum::<F>(),
+            F::from_canonical_usize(NUM_ROWS)
+        );
+        let proof = prove::<F, C, S, D>(stark, &config, trace, &[], &mut TimingTree::default())?;
+        verify_stark_proof(stark, proof.clone(), &config)?;
+
+        let mut builder = CircuitBuilder::<F, D>::new(CircuitConfig::standard_recursion_config());
+        let mut pw = PartialWitness::new();
+        let degree_bits = proof.proof.recover_degree_bits(&config);
+        let pt = add_virtual_stark_proof_with_pis(&mut builder, stark, &config, degree_bits);
+        set_stark_proof_with_pis_target(&mut pw, &pt, &proof);
+        verify_stark_proof_circuit::<F, C, S, D>(&mut builder, stark, pt, &config);
+        let data = builder.build::<C>();
+        data.verify(data.prove(pw)?)
+    }
+
+    #[test]
+    fn test_multi_column_lookups() -> Result<()> {
+        prove_and_verify_recursively(StarkConfig::standard_fast_config(), false)
+    }
+
+    #[test]
+    fn test_preprocessed_lookups() -> Result<()> {
+        prove_and_verify_recursively(StarkConfig::standard_fast_config(), true)
+    }
+
+    #[test]
+    fn test_multi_column_lookups_with_extension_challenges() -> Result<()> {
+        prove_and_verify_recursively(
+            // A single Goldilocks challenge doesn't reach the security target, so it is drawn from
+            // the extension.
+            StarkConfig {
+                num_challenges: 1,
+                ..StarkConfig::standard_fast_config()
+            },
+            false,
+        )
+    }
+
+    #[test]
+    fn test_multi_column_lookups_small_field() -> Result<()> {
+        const D: usize = 4;
+        type C = PoseidonBabyBearConfig;
+        type F = BabyBearField;
+        type S = XorStark<F, D>;
+
+        let config = StarkConfig::standard_fast_small_field_config();
+        let stark = S::new(false);
+        let trace = stark.generate_trace(&[(1, 2), (3, 3), (0, 2)]);
+        let proof = prove::<F, C, S, D>(stark, &config, trace, &[], &mut TimingTree::default())?;
+        verify_stark_proof(stark, proof, &config)
+    }
+
+    #[test]
+    #[should_panic(expected = "Constraint failed")]
+    fn test_multi_column_lookup_soundness() {
+        type S = XorStark<F, D>;
+
+        let config = StarkConfig::standard_fast_config();
+        let stark = S::new(false);
+        let mut trace = stark.generate_trace(&[(1, 2)]);
+        // Both `(1, 2, 3)` and `(1, 3, 2)` are in the table, but `(1, 2, 2)` isn't.
+        trace[A_XOR_B].values[5] = F::TWO;
+        let _ = prove::<F, C, S, D>(stark, &config, trace, &[], &mut TimingTree::default());
+    }
+
+    #[test]
+    #[should_panic(expected = "Constraint failed")]
+    fn test_multi_column_lookup_soundness_small_field() {
+        const D: usize = 4;
+        type C = PoseidonBabyBearConfig;
+        type F = BabyBearField;
+        type S = XorStark<F, D>;
+
+        let config = StarkConfig::standard_fast_small_field_config();
+        let stark = S::new(false);
+        let mut trace = stark.generate_trace(&[(1, 2)]);
+        trace[A_XOR_B].values[5] = F::TWO;
+        let _ = prove::<F, C, S, D>(stark, &config, trace, &[], &mut TimingTree::default());
+    }
+
+    #[test]
+    #[should_panic(expected = "Constraint failed")]
+    fn test_preprocessed_lookup_soundness() {
+        type S = XorStark<F, D>;
+
+        let config = StarkConfig::standard_fast_config();
+        let stark = S::new(true);
+        let mut trace = stark.generate_trace(&[(1, 2)]);
+        // Changing the copy of the table in the trace doesn't change the preprocessed table.
+        trace[A_XOR_B].values[5] = F::TWO;
+        trace[XOR_TABLE[2]].values[6] = F::TWO;
+        let _ = prove::<F, C, S, D>(stark, &config, trace, &[], &mut TimingTree::default());
+    }
+
+    #[test]
+    #[should_panic(expected = "is not in the table")]
+    fn test_frequencies_of_missing_value() {
+        let stark = XorStark::<F, D>::new(false);
+        stark.generate_trace(&[(1, 4)]);
+    }
+}
@@ -22,6 +22,9 @@ use crate::lookup::GrandProductChallengeSet;
 
 #[derive(Debug, Clone)]
 pub struct StarkProof<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize> {
+    /// Length of the trace, of the form `2^k 3^j`. FRI works with the next power of two,
+    /// `2^recover_degree_bits()`, as degree bound.
+    pub trace_len: usize,
     /// Merkle cap of LDEs of trace values.
     pub trace_cap: MerkleCap<F, C::Hasher>,
     /// Merkle cap of LDEs of permutation Z values.
@@ -35,7 +38,8 @@ pub struct StarkProof<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>,
 }
 
 impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize> StarkProof<F, C, D> {
-    /// Recover the length of the trace from a STARK proof and a STARK config.
+    /// Recover the logarithm of the FRI degree bound, which is the length of the trace rounded
+    /// up to a power of two, from a STARK proof and a STARK config.
     pub fn recover_degree_bits(&self, config: &StarkConfig) -> usize {
         let initial_merkle_proof = &self.opening_proof.query_round_proofs[0]
             .initial_trees_proof
@@ -46,6 +50,7 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize> S
     }
 }
 
+#[derive(Debug, Clone)]
 pub struct StarkProofTarget<const D: usize> {
     pub trace_cap: MerkleCapTarget,
     pub auxiliary_polys_cap: Option<MerkleCapTarget>,
@@ -77,6 +82,7 @@ pub struct StarkProofWithPublicInputs<
     pub public_inputs: Vec<F>,
 }
 
+#[derive(Debug, Clone)]
 pub struct StarkProofWithPublicInputsTarget<const D: usize> {
     pub proof: StarkProofTarget<D>,
     pub public_inputs: Vec<Target>,
@@ -106,9 +112,10 @@ pub struct CompressedStarkProofWithPublicInputs<
 
 pub(crate) struct StarkProofChallenges<F: RichField + Extendable<D>, const D: usize> {
     /// Randomness used in any permutation arguments.
-    pub lookup_challenge_set: Option<GrandProductChallengeSet<F>>,
+    pub lookup_challenge_set: Option<GrandProductChallengeSet<F::Extension>>,
 
-    /// Random values used to combine STARK constraints.
+    /// Random values used to combine STARK constraints. When they are drawn from the extension,
+    /// these are their coefficients, `D` at a time.
     pub stark_alphas: Vec<F>,
 
     /// Point at which the STARK polynomials are opened.
@@ -118,7 +125,7 @@ pub(crate) struct StarkProofChallenges<F: RichField + Extendable<D>, const D: us
 }
 
 pub(crate) struct StarkProofChallengesTarget<const D: usize> {
-    pub lookup_challenge_set: Option<GrandProductChallengeSet<Target>>,
+    pub lookup_challenge_set: Option<GrandProductChallengeSet<ExtensionTarget<D>>>,
     pub stark_alphas: Vec<Target>,
     pub stark_zeta: ExtensionTarget<D>,
     pub fri_challenges: FriChallengesTarget<D>,
@@ -182,6 +189,7 @@ impl<F: RichField + Extendable<D>, const D: usize> StarkOpeningSet<F, D> {
     }
 }
 
+#[derive(Debug, Clone)]
 pub struct StarkOpeningSetTarget<const D: usize> {
     pub local_values: Vec<ExtensionTarget<D>>,
     pub next_values: Vec<ExtensionTarget<D>>,
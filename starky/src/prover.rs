@@ -3,7 +3,8 @@ use core::iter::once;
 
 use anyhow::{ensure, Result};
 use itertools::Itertools;
-use plonky2::field::extension::Extendable;
+use plonky2::field::extension::{Extendable, FieldExtension};
+use plonky2::field::fft::{smooth_factors, smooth_root_of_unity};
 use plonky2::field::packable::Packable;
 use plonky2::field::packed::PackedField;
 use plonky2::field::polynomial::{PolynomialCoeffs, PolynomialValues};
@@ -15,19 +16,22 @@ use plonky2::iop::challenger::Challenger;
 use plonky2::plonk::config::GenericConfig;
 use plonky2::timed;
 use plonky2::util::timing::TimingTree;
-use plonky2::util::{log2_ceil, log2_strict, transpose};
+use plonky2::util::{log2_ceil, transpose};
 use plonky2_maybe_rayon::*;
 
 use crate::config::StarkConfig;
 use crate::constraint_consumer::ConstraintConsumer;
 use crate::evaluation_frame::StarkEvaluationFrame;
 use crate::lookup::{
-    get_grand_product_challenge_set, lookup_helper_columns, Lookup, LookupCheckVars,
+    get_grand_product_challenge_set, lookup_helper_columns, GrandProductChallenge, Lookup,
+    LookupCheckVars,
 };
 use crate::proof::{StarkOpeningSet, StarkProof, StarkProofWithPublicInputs};
 use crate::stark::Stark;
 use crate::vanishing_poly::eval_vanishing_poly;
 
+/// Proves the given trace. Its length may be any `2^k 3^j` for which the field has a subgroup of
+/// that order, not just a power of two; FRI then works with the next power of two as degree bound.
 #[allow(clippy::useless_asref)]
 pub fn prove<F, C, S, const D: usize>(
     stark: S,
@@ -42,7 +46,11 @@ where
     S: Stark<F, D>,
 {
     let degree = trace_poly_values[0].len();
-    let degree_bits = log2_strict(degree);
+    assert!(
+        smooth_factors(degree).is_some(),
+        "Trace length {degree} is not of the form 2^k 3^j"
+    );
+    let degree_bits = log2_ceil(degree);
     let fri_params = config.fri_params(degree_bits);
     let rate_bits = config.fri_config.rate_bits;
     let cap_height = config.fri_config.cap_height;
@@ -54,35 +62,26 @@ where
     let trace_commitment = timed!(
         timing,
         "compute trace commitment",
-        PolynomialBatch::<F, C, D>::from_values(
-            trace_poly_values.clone(),
-            rate_bits,
-            false,
-            cap_height,
-            timing,
-            None,
-        )
+        commit_trace_values::<F, C, D>(trace_poly_values.clone(), rate_bits, cap_height, timing)
     );
 
     let trace_cap = trace_commitment.merkle_tree.cap.clone();
     let mut challenger = Challenger::new();
+    // Traces of lengths `2^k 3^j` with the same FRI degree bound differ in their domain, so the
+    // length is bound to the challenges.
+    challenger.observe_element(F::from_canonical_usize(degree));
     challenger.observe_cap(&trace_cap);
 
     // Lookup argument.
     let constraint_degree = stark.constraint_degree();
     let lookups = stark.lookups();
+    let challenge_degree = config.challenge_degree::<F>(D);
     let lookup_challenges = stark.uses_lookups().then(|| {
-        get_grand_product_challenge_set(&mut challenger, config.num_challenges)
+        get_grand_product_challenge_set(&mut challenger, config.num_challenges, challenge_degree)
             .challenges
-            .iter()
-            .map(|ch| ch.beta)
-            .collect::<Vec<_>>()
     });
 
-    let num_lookup_columns = lookups
-        .iter()
-        .map(|l| l.num_helper_columns(constraint_degree))
-        .sum();
+    let num_lookup_columns = stark.num_lookup_helper_columns(config);
 
     let auxiliary_polys_commitment = stark.uses_lookups().then(|| {
         let lookup_helper_columns = timed!(timing, "compute lookup helper columns", {
@@ -90,12 +89,26 @@ where
             let mut columns = Vec::with_capacity(num_lookup_columns);
             for lookup in &lookups {
                 for &challenge in challenges {
-                    columns.extend(lookup_helper_columns(
-                        lookup,
-                        &trace_poly_values,
-                        challenge,
-                        constraint_degree,
-                    ));
+                    if config.extension_challenges::<F>() {
+                        columns.extend(lookup_helper_columns::<F, F::Extension, D>(
+                            lookup,
+                            &trace_poly_values,
+                            challenge,
+                            constraint_degree,
+                        ));
+                    } else {
+                        // The challenges are in the base field, so are the helper columns.
+                        let challenge = GrandProductChallenge {
+                            beta: challenge.beta.to_basefield_array()[0],
+                            gamma: challenge.gamma.to_basefield_array()[0],
+                        };
+                        columns.extend(lookup_helper_columns::<F, F, 1>(
+                            lookup,
+                            &trace_poly_values,
+                            challenge,
+                            constraint_degree,
+                        ));
+                    }
                 }
             }
             columns
@@ -105,14 +118,7 @@ where
         let auxiliary_polys_commitment = timed!(
             timing,
             "compute permutation Z commitments",
-            PolynomialBatch::from_values(
-                lookup_helper_columns,
-                rate_bits,
-                false,
-                config.fri_config.cap_height,
-                timing,
-                None,
-            )
+            commit_trace_values(lookup_helper_columns, rate_bits, cap_height, timing)
         );
 
         auxiliary_polys_commitment
@@ -125,7 +131,8 @@ where
         challenger.observe_cap(cap);
     }
 
-    let alphas = challenger.get_n_challenges(config.num_challenges);
+    // Alphas drawn from the extension are sampled coefficient by coefficient.
+    let alphas = challenger.get_n_challenges(config.num_challenges * challenge_degree);
 
     #[cfg(test)]
     {
@@ -137,7 +144,8 @@ where
             lookup_challenges.as_ref(),
             &lookups,
             alphas.clone(),
-            degree_bits,
+            challenge_degree,
+            degree,
             num_lookup_columns,
         );
     }
@@ -153,7 +161,8 @@ where
             &lookups,
             public_inputs,
             alphas,
-            degree_bits,
+            challenge_degree,
+            degree,
             num_lookup_columns,
             config,
         )
@@ -170,8 +179,15 @@ where
                     .expect(
                         "Quotient has failed, the vanishing polynomial is not divisible by Z_H",
                     );
-                // Split quotient into degree-n chunks.
-                quotient_poly.chunks(degree)
+                // Split quotient into degree-n chunks, padded to the FRI degree bound.
+                quotient_poly
+                    .chunks(degree)
+                    .into_iter()
+                    .map(|mut chunk| {
+                        chunk.coeffs.resize(1 << degree_bits, F::ZERO);
+                        chunk
+                    })
+                    .collect::<Vec<_>>()
             })
             .collect()
     );
@@ -197,9 +213,9 @@ where
     // To avoid leaking witness data, we want to ensure that our opening locations, `zeta` and
     // `g * zeta`, are not in our subgroup `H`. It suffices to check `zeta` only, since
     // `(g * zeta)^n = zeta^n`, where `n` is the order of `g`.
-    let g = F::primitive_root_of_unity(degree_bits);
+    let g = smooth_root_of_unity::<F>(degree);
     ensure!(
-        zeta.exp_power_of_2(degree_bits) != F::Extension::ONE,
+        zeta.exp_u64(degree as u64) != F::Extension::ONE,
         "Opening point is in the subgroup."
     );
 
@@ -232,6 +248,7 @@ where
         )
     );
     let proof = StarkProof {
+        trace_len: degree,
         trace_cap,
         auxiliary_polys_cap,
         quotient_polys_cap,
@@ -245,17 +262,144 @@ where
     })
 }
 
+/// Commits to polynomials given by their values on the trace domain `H`. When `|H|` is not a power
+/// of two, they are interpolated on `H` and their coefficients padded to the next power of two.
+fn commit_trace_values<F, C, const D: usize>(
+    values: Vec<PolynomialValues<F>>,
+    rate_bits: usize,
+    cap_height: usize,
+    timing: &mut TimingTree,
+) -> PolynomialBatch<F, C, D>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+{
+    let degree = values[0].len();
+    if degree.is_power_of_two() {
+        return PolynomialBatch::from_values(values, rate_bits, false, cap_height, timing, None);
+    }
+
+    let coeffs = timed!(
+        timing,
+        "IFFT over the smooth subgroup",
+        values
+            .into_par_iter()
+            .map(|v| v.ifft_smooth().padded(degree.next_power_of_two()))
+            .collect()
+    );
+    PolynomialBatch::from_coeffs(coeffs, rate_bits, false, cap_height, timing, None)
+}
+
+/// The values of a batch of committed polynomials on the coset of order `degree << quotient_degree_bits`
+/// on which the quotient is computed.
+enum QuotientDomainValues<
+    'a,
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    const D: usize,
+> {
+    /// For power-of-two traces, every `step`-th point of the committed LDE lies on that coset.
+    Committed(&'a PolynomialBatch<F, C, D>, usize),
+    /// Otherwise the FRI domain is unrelated to the trace domain, so the polynomials are evaluated
+    /// on the coset separately, column by column.
+    Smooth(Vec<PolynomialValues<F>>),
+}
+
+impl<'a, F, C, const D: usize> QuotientDomainValues<'a, F, C, D>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+{
+    fn new(
+        commitment: &'a PolynomialBatch<F, C, D>,
+        degree: usize,
+        quotient_degree_bits: usize,
+        rate_bits: usize,
+    ) -> Self {
+        if degree.is_power_of_two() {
+            return Self::Committed(commitment, 1 << (rate_bits - quotient_degree_bits));
+        }
+
+        let size = degree << quotient_degree_bits;
+        Self::Smooth(
+            commitment
+                .polynomials
+                .par_iter()
+                .map(|poly| {
+                    PolynomialCoeffs::new(poly.coeffs[..degree].to_vec())
+                        .padded(size)
+                        .coset_fft_smooth(F::coset_shift())
+                })
+                .collect(),
+        )
+    }
+
+    /// Retrieves the values at the points `i_start..i_start + P::WIDTH` of the coset, wrapping
+    /// around its end.
+    fn get_packed<P: PackedField<Scalar = F>>(&self, i_start: usize) -> Vec<P> {
+        match self {
+            Self::Committed(commitment, step) => commitment.get_lde_values_packed(i_start, *step),
+            Self::Smooth(columns) => columns
+                .iter()
+                .map(|column| {
+                    let size = column.len();
+                    let mut packed = P::ZEROS;
+                    packed
+                        .as_slice_mut()
+                        .iter_mut()
+                        .enumerate()
+                        .for_each(|(i, v)| *v = column.values[(i_start + i) % size]);
+                    packed
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Creates the consumer combining the constraints with `alphas`, which are the coefficients of
+/// alphas drawn from the extension when `challenge_degree > 1`.
+fn constraint_consumer<F, P, const D: usize>(
+    alphas: &[F],
+    challenge_degree: usize,
+    z_last: P,
+    lagrange_basis_first: P,
+    lagrange_basis_last: P,
+) -> ConstraintConsumer<P>
+where
+    F: RichField + Extendable<D>,
+    P: PackedField<Scalar = F>,
+{
+    if challenge_degree == 1 {
+        ConstraintConsumer::new(
+            alphas.to_vec(),
+            z_last,
+            lagrange_basis_first,
+            lagrange_basis_last,
+        )
+    } else {
+        ConstraintConsumer::new_with_extension_alphas(
+            alphas.to_vec(),
+            challenge_degree,
+            F::W,
+            z_last,
+            lagrange_basis_first,
+            lagrange_basis_last,
+        )
+    }
+}
+
 /// Computes the quotient polynomials `(sum alpha^i C_i(x)) / Z_H(x)` for `alpha` in `alphas`,
 /// where the `C_i`s are the Stark constraints.
 fn compute_quotient_polys<'a, F, P, C, S, const D: usize>(
     stark: &S,
     trace_commitment: &'a PolynomialBatch<F, C, D>,
     auxiliary_polys_commitment: &'a Option<PolynomialBatch<F, C, D>>,
-    lookup_challenges: Option<&'a Vec<F>>,
+    lookup_challenges: Option<&'a Vec<GrandProductChallenge<F::Extension>>>,
     lookups: &[Lookup<F>],
     public_inputs: &[F],
     alphas: Vec<F>,
-    degree_bits: usize,
+    challenge_degree: usize,
+    degree: usize,
     num_lookup_columns: usize,
     config: &StarkConfig,
 ) -> Vec<PolynomialCoeffs<F>>
@@ -265,7 +409,6 @@ where
     C: GenericConfig<D, F = F>,
     S: Stark<F, D>,
 {
-    let degree = 1 << degree_bits;
     let rate_bits = config.fri_config.rate_bits;
 
     let quotient_degree_bits = log2_ceil(stark.quotient_degree_factor());
@@ -273,30 +416,45 @@ where
         quotient_degree_bits <= rate_bits,
         "Having constraints of degree higher than the rate is not supported yet."
     );
-    let step = 1 << (rate_bits - quotient_degree_bits);
     // When opening the `Z`s polys at the "next" point, need to look at the point `next_step` steps away.
     let next_step = 1 << quotient_degree_bits;
 
+    let size = degree << quotient_degree_bits;
+    let lde_onto_coset = |values: PolynomialValues<F>| {
+        values
+            .ifft_smooth()
+            .padded(size)
+            .coset_fft_smooth(F::coset_shift())
+    };
     // Evaluation of the first Lagrange polynomial on the LDE domain.
-    let lagrange_first = PolynomialValues::selector(degree, 0).lde_onto_coset(quotient_degree_bits);
+    let lagrange_first = lde_onto_coset(PolynomialValues::selector(degree, 0));
     // Evaluation of the last Lagrange polynomial on the LDE domain.
-    let lagrange_last =
-        PolynomialValues::selector(degree, degree - 1).lde_onto_coset(quotient_degree_bits);
+    let lagrange_last = lde_onto_coset(PolynomialValues::selector(degree, degree - 1));
 
-    let z_h_on_coset = ZeroPolyOnCoset::<F>::new(degree_bits, quotient_degree_bits);
+    let z_h_on_coset = ZeroPolyOnCoset::<F>::new_smooth(degree, quotient_degree_bits);
 
-    // Retrieve the LDE values at index `i`.
-    let get_trace_values_packed =
-        |i_start| -> Vec<P> { trace_commitment.get_lde_values_packed(i_start, step) };
+    let trace_values =
+        QuotientDomainValues::new(trace_commitment, degree, quotient_degree_bits, rate_bits);
+    let auxiliary_values = auxiliary_polys_commitment.as_ref().map(|commitment| {
+        QuotientDomainValues::new(commitment, degree, quotient_degree_bits, rate_bits)
+    });
+    let preprocessed_table_values = lookups
+        .iter()
+        .flat_map(|lookup| &lookup.preprocessed_table_columns)
+        .map(|col| {
+            assert_eq!(
+                col.len(),
+                degree,
+                "Preprocessed table column of the wrong length."
+            );
+            lde_onto_coset(col.clone())
+        })
+        .collect::<Vec<_>>();
 
     // Last element of the subgroup.
-    let last = F::primitive_root_of_unity(degree_bits).inverse();
-    let size = degree << quotient_degree_bits;
-    let coset = F::cyclic_subgroup_coset_known_order(
-        F::primitive_root_of_unity(degree_bits + quotient_degree_bits),
-        F::coset_shift(),
-        size,
-    );
+    let last = smooth_root_of_unity::<F>(degree).inverse();
+    let coset =
+        F::cyclic_subgroup_coset_known_order(smooth_root_of_unity(size), F::coset_shift(), size);
 
     // We will step by `P::WIDTH`, and in each iteration, evaluate the quotient polynomial at
     // a batch of `P::WIDTH` points.
@@ -310,10 +468,11 @@ where
             let x = *P::from_slice(&coset[i_range.clone()]);
             let z_last = x - last;
             let lagrange_basis_first = *P::from_slice(&lagrange_first.values[i_range.clone()]);
-            let lagrange_basis_last = *P::from_slice(&lagrange_last.values[i_range]);
+            let lagrange_basis_last = *P::from_slice(&lagrange_last.values[i_range.clone()]);
 
-            let mut consumer = ConstraintConsumer::new(
-                alphas.clone(),
+            let mut consumer = constraint_consumer::<F, P, D>(
+                &alphas,
+                challenge_degree,
                 z_last,
                 lagrange_basis_first,
                 lagrange_basis_last,
@@ -321,23 +480,21 @@ where
             // Get the local and next row evaluations for the current STARK,
             // as well as the public inputs.
             let vars = S::EvaluationFrame::from_values(
-                &get_trace_values_packed(i_start),
-                &get_trace_values_packed(i_next_start),
+                &trace_values.get_packed(i_start),
+                &trace_values.get_packed(i_next_start),
                 public_inputs,
             );
             // Get the local and next row evaluations for the permutation argument,
             // as well as the associated challenges.
             let lookup_vars = lookup_challenges.map(|challenges| LookupCheckVars {
-                local_values: auxiliary_polys_commitment
-                    .as_ref()
-                    .unwrap()
-                    .get_lde_values_packed(i_start, step)
-                    .to_vec(),
-                next_values: auxiliary_polys_commitment
-                    .as_ref()
-                    .unwrap()
-                    .get_lde_values_packed(i_next_start, step),
+                local_values: auxiliary_values.as_ref().unwrap().get_packed(i_start),
+                next_values: auxiliary_values.as_ref().unwrap().get_packed(i_next_start),
                 challenges: challenges.to_vec(),
+                preprocessed_table_values: preprocessed_table_values
+                    .iter()
+                    .map(|col| *P::from_slice(&col.values[i_range.clone()]))
+                    .collect(),
+                challenge_degree,
             });
 
             // Evaluate the polynomial combining all constraints, including
@@ -358,10 +515,10 @@ where
                 *eval *= denominator_inv;
             }
 
-            let num_challenges = alphas.len();
+            let num_accumulators = constraints_evals.len();
 
             (0..P::WIDTH).map(move |i| {
-                (0..num_challenges)
+                (0..num_accumulators)
                     .map(|j| constraints_evals[j].as_slice()[i])
                     .collect()
             })
@@ -371,7 +528,7 @@ where
     transpose(&quotient_values)
         .into_par_iter()
         .map(PolynomialValues::new)
-        .map(|values| values.coset_ifft(F::coset_shift()))
+        .map(|values| values.coset_ifft_smooth(F::coset_shift()))
         .collect()
 }
 
@@ -383,35 +540,42 @@ fn check_constraints<'a, F, C, S, const D: usize>(
     trace_commitment: &'a PolynomialBatch<F, C, D>,
     public_inputs: &[F],
     auxiliary_commitment: &'a Option<PolynomialBatch<F, C, D>>,
-    lookup_challenges: Option<&'a Vec<F>>,
+    lookup_challenges: Option<&'a Vec<GrandProductChallenge<F::Extension>>>,
     lookups: &[Lookup<F>],
     alphas: Vec<F>,
-    degree_bits: usize,
+    challenge_degree: usize,
+    degree: usize,
     num_lookup_columns: usize,
 ) where
     F: RichField + Extendable<D>,
     C: GenericConfig<D, F = F>,
     S: Stark<F, D>,
 {
-    let degree = 1 << degree_bits;
     let rate_bits = 0; // Set this to higher value to check constraint degree.
 
     let size = degree << rate_bits;
     let step = 1 << rate_bits;
 
     // Evaluation of the first Lagrange polynomial.
-    let lagrange_first = PolynomialValues::selector(degree, 0).lde(rate_bits);
+    let lde = |values: PolynomialValues<F>| values.ifft_smooth().padded(size).fft_smooth();
+    let lagrange_first = lde(PolynomialValues::selector(degree, 0));
     // Evaluation of the last Lagrange polynomial.
-    let lagrange_last = PolynomialValues::selector(degree, degree - 1).lde(rate_bits);
+    let lagrange_last = lde(PolynomialValues::selector(degree, degree - 1));
 
-    let subgroup = F::two_adic_subgroup(degree_bits + rate_bits);
+    let subgroup = F::cyclic_subgroup_known_order(smooth_root_of_unity(size), size);
 
-    // Get the evaluations of a batch of polynomials over our subgroup.
+    // Get the evaluations of a batch of polynomials over our subgroup. Committed coefficients may
+    // be padded beyond `degree` with zeros.
     let get_subgroup_evals = |comm: &PolynomialBatch<F, C, D>| -> Vec<Vec<F>> {
         let values = comm
             .polynomials
             .par_iter()
-            .map(|coeffs| coeffs.clone().fft().values)
+            .map(|coeffs| {
+                PolynomialCoeffs::new(coeffs.coeffs[..degree].to_vec())
+                    .padded(size)
+                    .fft_smooth()
+                    .values
+            })
             .collect::<Vec<_>>();
         transpose(&values)
     };
@@ -419,9 +583,14 @@ fn check_constraints<'a, F, C, S, const D: usize>(
     // Get batch evaluations of the trace and permutation polynomials over our subgroup.
     let trace_subgroup_evals = get_subgroup_evals(trace_commitment);
     let auxiliary_subgroup_evals = auxiliary_commitment.as_ref().map(get_subgroup_evals);
+    let preprocessed_table_evals = lookups
+        .iter()
+        .flat_map(|lookup| &lookup.preprocessed_table_columns)
+        .map(|col| lde(col.clone()))
+        .collect::<Vec<_>>();
 
     // Last element of the subgroup.
-    let last = F::primitive_root_of_unity(degree_bits).inverse();
+    let last = smooth_root_of_unity::<F>(degree).inverse();
 
     let constraint_values = (0..size)
         .map(|i| {
@@ -432,8 +601,9 @@ fn check_constraints<'a, F, C, S, const D: usize>(
             let lagrange_basis_first = lagrange_first.values[i];
             let lagrange_basis_last = lagrange_last.values[i];
 
-            let mut consumer = ConstraintConsumer::new(
-                alphas.clone(),
+            let mut consumer = constraint_consumer::<F, F, D>(
+                &alphas,
+                challenge_degree,
                 z_last,
                 lagrange_basis_first,
                 lagrange_basis_last,
@@ -449,6 +619,11 @@ fn check_constraints<'a, F, C, S, const D: usize>(
                 local_values: auxiliary_subgroup_evals.as_ref().unwrap()[i].clone(),
                 next_values: auxiliary_subgroup_evals.as_ref().unwrap()[i_next].clone(),
                 challenges: challenges.to_vec(),
+                preprocessed_table_values: preprocessed_table_evals
+                    .iter()
+                    .map(|col| col.values[i])
+                    .collect(),
+                challenge_degree,
             });
 
             // Evaluate the polynomial combining all constraints, including those associated
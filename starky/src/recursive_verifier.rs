@@ -1,14 +1,17 @@
 use alloc::vec;
 use alloc::vec::Vec;
 use core::iter::once;
+use core::ops::Range;
 
 use anyhow::{ensure, Result};
 use itertools::Itertools;
 use plonky2::field::extension::Extendable;
 use plonky2::field::types::Field;
-use plonky2::fri::witness_util::set_fri_proof_target;
+use plonky2::fri::recursive_verifier::FriDegreeRangeTarget;
+use plonky2::fri::witness_util::set_fri_proof_target_with_padding;
 use plonky2::hash::hash_types::RichField;
 use plonky2::iop::ext_target::ExtensionTarget;
+use plonky2::iop::target::Target;
 use plonky2::iop::witness::Witness;
 use plonky2::plonk::circuit_builder::CircuitBuilder;
 use plonky2::plonk::config::{AlgebraicHasher, GenericConfig};
@@ -18,7 +21,7 @@ use plonky2::with_context;
 use crate::config::StarkConfig;
 use crate::constraint_consumer::RecursiveConstraintConsumer;
 use crate::evaluation_frame::StarkEvaluationFrame;
-use crate::lookup::LookupCheckVarsTarget;
+use crate::lookup::{eval_preprocessed_table_columns_circuit, LookupCheckVarsTarget};
 use crate::proof::{
     StarkOpeningSetTarget, StarkProof, StarkProofChallengesTarget, StarkProofTarget,
     StarkProofWithPublicInputs, StarkProofWithPublicInputsTarget,
@@ -44,7 +47,7 @@ pub fn verify_stark_proof_circuit<
     let challenges = with_context!(
         builder,
         "compute challenges",
-        proof_with_pis.get_challenges::<F, C>(builder, inner_config)
+        proof_with_pis.get_challenges::<F, C>(builder, inner_config, None)
     );
 
     verify_stark_proof_with_challenges_circuit::<F, C, S, D>(
@@ -53,10 +56,70 @@ pub fn verify_stark_proof_circuit<
         proof_with_pis,
         challenges,
         inner_config,
+        InnerDegree::Fixed(degree_bits),
+    );
+}
+
+/// Like `verify_stark_proof_circuit`, but the inner proof may have any `degree_bits` in
+/// `degree_bits_range`, given by the `degree_bits` target. This lets a single circuit verify
+/// proofs of traces of different lengths. `degree_bits` is registered as a public input, so that
+/// the verifier of the outer proof knows the length of the verified trace.
+///
+/// `proof_with_pis` must be created by `add_virtual_stark_proof_with_pis_and_degree_range` for the
+/// same range. Proofs of smaller degrees are padded when setting the witness with
+/// `set_stark_proof_with_pis_target`. The reduction arities of the
+/// supported degrees must be prefixes of each other, as with
+/// `FriReductionStrategy::ConstantArityBits`.
+pub fn verify_stark_proof_with_degree_range_circuit<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    S: Stark<F, D>,
+    const D: usize,
+>(
+    builder: &mut CircuitBuilder<F, D>,
+    stark: S,
+    proof_with_pis: StarkProofWithPublicInputsTarget<D>,
+    degree_bits: Target,
+    degree_bits_range: Range<usize>,
+    inner_config: &StarkConfig,
+) where
+    C::Hasher: AlgebraicHasher<F>,
+{
+    assert_eq!(proof_with_pis.public_inputs.len(), S::PUBLIC_INPUTS);
+    assert_eq!(
+        proof_with_pis.proof.recover_degree_bits(inner_config),
+        degree_bits_range.end - 1,
+        "The proof target must be built for the largest degree of the range."
+    );
+    builder.register_public_input(degree_bits);
+    let degree = builder.fri_degree_range(
         degree_bits,
+        degree_bits_range,
+        &inner_config.fri_config,
+        false,
+    );
+    let challenges = with_context!(
+        builder,
+        "compute challenges",
+        proof_with_pis.get_challenges::<F, C>(builder, inner_config, Some(&degree))
+    );
+
+    verify_stark_proof_with_challenges_circuit::<F, C, S, D>(
+        builder,
+        stark,
+        proof_with_pis,
+        challenges,
+        inner_config,
+        InnerDegree::Range(&degree),
     );
 }
 
+/// The degree of the inner proof, either fixed when building the circuit, or given by a target.
+enum InnerDegree<'a> {
+    Fixed(usize),
+    Range(&'a FriDegreeRangeTarget),
+}
+
 /// Recursively verifies an inner proof.
 fn verify_stark_proof_with_challenges_circuit<
     F: RichField + Extendable<D>,
@@ -69,7 +132,7 @@ fn verify_stark_proof_with_challenges_circuit<
     proof_with_pis: StarkProofWithPublicInputsTarget<D>,
     challenges: StarkProofChallengesTarget<D>,
     inner_config: &StarkConfig,
-    degree_bits: usize,
+    degree: InnerDegree,
 ) where
     C::Hasher: AlgebraicHasher<F>,
 {
@@ -97,37 +160,105 @@ fn verify_stark_proof_with_challenges_circuit<
             .collect::<Vec<_>>(),
     );
 
-    let zeta_pow_deg = builder.exp_power_of_2_extension(challenges.stark_zeta, degree_bits);
-    let z_h_zeta = builder.sub_extension(zeta_pow_deg, one);
-    let (l_0, l_last) =
-        eval_l_0_and_l_last_circuit(builder, degree_bits, challenges.stark_zeta, z_h_zeta);
-    let last =
-        builder.constant_extension(F::Extension::primitive_root_of_unity(degree_bits).inverse());
-    let z_last = builder.sub_extension(challenges.stark_zeta, last);
-
-    let mut consumer = RecursiveConstraintConsumer::<F, D>::new(
-        builder.zero_extension(),
-        challenges.stark_alphas,
-        z_last,
-        l_0,
-        l_last,
-    );
+    // Preprocessed lookup tables are evaluated from their values, which requires a fixed length.
+    let lookups = stark.lookups();
+    let preprocessed_table_values = match &degree {
+        InnerDegree::Fixed(degree_bits) => {
+            assert!(
+                lookups
+                    .iter()
+                    .flat_map(|lookup| &lookup.preprocessed_table_columns)
+                    .all(|col| col.len() == 1 << degree_bits),
+                "Preprocessed table columns must be as long as the trace."
+            );
+            eval_preprocessed_table_columns_circuit(builder, &lookups, challenges.stark_zeta)
+        }
+        InnerDegree::Range(_) => {
+            assert!(
+                lookups
+                    .iter()
+                    .all(|lookup| lookup.preprocessed_table_columns.is_empty()),
+                "Preprocessed lookup tables are not supported with a range of degrees."
+            );
+            vec![]
+        }
+    };
+
+    // For a degree given by a target, we also compute the point of the next-row openings here.
+    let (zeta_pow_deg, z_h_zeta, l_0, l_last, z_last, zeta_next) = match degree {
+        InnerDegree::Fixed(degree_bits) => {
+            let zeta_pow_deg = builder.exp_power_of_2_extension(challenges.stark_zeta, degree_bits);
+            let z_h_zeta = builder.sub_extension(zeta_pow_deg, one);
+            let (l_0, l_last) =
+                eval_l_0_and_l_last_circuit(builder, degree_bits, challenges.stark_zeta, z_h_zeta);
+            let last = builder
+                .constant_extension(F::Extension::primitive_root_of_unity(degree_bits).inverse());
+            let z_last = builder.sub_extension(challenges.stark_zeta, last);
+            (zeta_pow_deg, z_h_zeta, l_0, l_last, z_last, None)
+        }
+        InnerDegree::Range(degree) => {
+            let mut zeta_pows = Vec::with_capacity(degree.params.len());
+            let mut zeta_pow = challenges.stark_zeta;
+            let mut zeta_pow_bits = 0;
+            for params in &degree.params {
+                zeta_pow =
+                    builder.exp_power_of_2_extension(zeta_pow, params.degree_bits - zeta_pow_bits);
+                zeta_pow_bits = params.degree_bits;
+                zeta_pows.push(zeta_pow);
+            }
+            let zeta_pow_deg = degree.select_extension(builder, zeta_pows);
+            let z_h_zeta = builder.sub_extension(zeta_pow_deg, one);
+            let (n, g, g_inv) = trace_domain_circuit(builder, degree);
+            let (l_0, l_last) = eval_l_0_and_l_last_with_degree_range_circuit(
+                builder,
+                n,
+                g,
+                challenges.stark_zeta,
+                z_h_zeta,
+            );
+            let last = builder.convert_to_ext(g_inv);
+            let z_last = builder.sub_extension(challenges.stark_zeta, last);
+            let zeta_next = builder.scalar_mul_ext(g, challenges.stark_zeta);
+            (zeta_pow_deg, z_h_zeta, l_0, l_last, z_last, Some(zeta_next))
+        }
+    };
+
+    let challenge_degree = inner_config.challenge_degree::<F>(D);
+    let mut consumer = if challenge_degree == 1 {
+        RecursiveConstraintConsumer::<F, D>::new(
+            builder.zero_extension(),
+            challenges.stark_alphas,
+            z_last,
+            l_0,
+            l_last,
+        )
+    } else {
+        let alphas = challenges
+            .stark_alphas
+            .chunks(D)
+            .map(|coeffs| ExtensionTarget(coeffs.try_into().unwrap()))
+            .collect();
+        RecursiveConstraintConsumer::<F, D>::new_with_extension_alphas(
+            builder.zero_extension(),
+            builder.one_extension(),
+            alphas,
+            z_last,
+            l_0,
+            l_last,
+        )
+    };
 
     let num_lookup_columns = stark.num_lookup_helper_columns(inner_config);
-    let lookup_challenges = stark.uses_lookups().then(|| {
-        challenges
-            .lookup_challenge_set
-            .unwrap()
-            .challenges
-            .iter()
-            .map(|ch| ch.beta)
-            .collect::<Vec<_>>()
-    });
+    let lookup_challenges = stark
+        .uses_lookups()
+        .then(|| challenges.lookup_challenge_set.unwrap().challenges);
 
     let lookup_vars = stark.uses_lookups().then(|| LookupCheckVarsTarget {
         local_values: auxiliary_polys.as_ref().unwrap()[..num_lookup_columns].to_vec(),
         next_values: auxiliary_polys_next.as_ref().unwrap()[..num_lookup_columns].to_vec(),
         challenges: lookup_challenges.unwrap(),
+        preprocessed_table_values,
+        challenge_degree,
     });
 
     with_context!(
@@ -153,20 +284,59 @@ fn verify_stark_proof_with_challenges_circuit<
         .chain(once(proof.quotient_polys_cap))
         .collect_vec();
 
-    let fri_instance = stark.fri_instance_target(
-        builder,
-        challenges.stark_zeta,
-        F::primitive_root_of_unity(degree_bits),
-        inner_config,
-    );
-    builder.verify_fri_proof::<C>(
-        &fri_instance,
-        &proof.openings.to_fri_openings(),
-        &challenges.fri_challenges,
-        &merkle_caps,
-        &proof.opening_proof,
-        &inner_config.fri_params(degree_bits),
-    );
+    match degree {
+        InnerDegree::Fixed(degree_bits) => {
+            let fri_instance = stark.fri_instance_target(
+                builder,
+                challenges.stark_zeta,
+                F::primitive_root_of_unity(degree_bits),
+                inner_config,
+            );
+            builder.verify_fri_proof::<C>(
+                &fri_instance,
+                &proof.openings.to_fri_openings(),
+                &challenges.fri_challenges,
+                &merkle_caps,
+                &proof.opening_proof,
+                &inner_config.fri_params(degree_bits),
+            );
+        }
+        InnerDegree::Range(degree) => {
+            let fri_instance = stark.fri_instance_target_with_next_point(
+                challenges.stark_zeta,
+                zeta_next.unwrap(),
+                inner_config,
+            );
+            builder.verify_fri_proof_with_degree_range::<C>(
+                &fri_instance,
+                &proof.openings.to_fri_openings(),
+                &challenges.fri_challenges,
+                &merkle_caps,
+                &proof.opening_proof,
+                degree,
+            );
+        }
+    }
+}
+
+/// Returns the size `n` of the trace domain whose degree is given by `degree`, its generator `g`,
+/// and the inverse of `g`.
+fn trace_domain_circuit<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    degree: &FriDegreeRangeTarget,
+) -> (Target, Target, Target) {
+    let mut constants = |f: fn(usize) -> F| {
+        let values = degree
+            .params
+            .iter()
+            .map(|p| builder.constant(f(p.degree_bits)))
+            .collect();
+        degree.select(builder, values)
+    };
+    let n = constants(|degree_bits| F::from_canonical_usize(1 << degree_bits));
+    let g = constants(F::primitive_root_of_unity);
+    let g_inv = constants(|degree_bits| F::primitive_root_of_unity(degree_bits).inverse());
+    (n, g, g_inv)
 }
 
 fn eval_l_0_and_l_last_circuit<F: RichField + Extendable<D>, const D: usize>(
@@ -188,6 +358,28 @@ fn eval_l_0_and_l_last_circuit<F: RichField + Extendable<D>, const D: usize>(
     )
 }
 
+/// Like `eval_l_0_and_l_last_circuit`, but for a trace domain of size `n` and generator `g` given
+/// by targets.
+fn eval_l_0_and_l_last_with_degree_range_circuit<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    n: Target,
+    g: Target,
+    x: ExtensionTarget<D>,
+    z_x: ExtensionTarget<D>,
+) -> (ExtensionTarget<D>, ExtensionTarget<D>) {
+    let n = builder.convert_to_ext(n);
+    let g = builder.convert_to_ext(g);
+    let one = builder.one_extension();
+    let l_0_deno = builder.mul_sub_extension(n, x, n);
+    let l_last_deno = builder.mul_sub_extension(g, x, one);
+    let l_last_deno = builder.mul_extension(n, l_last_deno);
+
+    (
+        builder.div_extension(z_x, l_0_deno),
+        builder.div_extension(z_x, l_last_deno),
+    )
+}
+
 pub fn add_virtual_stark_proof_with_pis<
     F: RichField + Extendable<D>,
     S: Stark<F, D>,
@@ -215,11 +407,7 @@ pub fn add_virtual_stark_proof<F: RichField + Extendable<D>, S: Stark<F, D>, con
     let fri_params = config.fri_params(degree_bits);
     let cap_height = fri_params.config.cap_height;
 
-    let num_leaves_per_oracle = vec![
-        S::COLUMNS,
-        stark.num_lookup_helper_columns(config),
-        stark.quotient_degree_factor() * config.num_challenges,
-    ];
+    let num_leaves_per_oracle = num_leaves_per_oracle(&stark, config);
 
     let auxiliary_polys_cap = stark
         .uses_lookups()
@@ -234,12 +422,84 @@ pub fn add_virtual_stark_proof<F: RichField + Extendable<D>, S: Stark<F, D>, con
     }
 }
 
+/// Like `add_virtual_stark_proof_with_pis`, but for proofs of any degree in `degree_bits_range`,
+/// as expected by `verify_stark_proof_with_degree_range_circuit`.
+pub fn add_virtual_stark_proof_with_pis_and_degree_range<
+    F: RichField + Extendable<D>,
+    S: Stark<F, D>,
+    const D: usize,
+>(
+    builder: &mut CircuitBuilder<F, D>,
+    stark: S,
+    config: &StarkConfig,
+    degree_bits_range: Range<usize>,
+) -> StarkProofWithPublicInputsTarget<D> {
+    let proof = add_virtual_stark_proof_with_degree_range::<F, S, D>(
+        builder,
+        stark,
+        config,
+        degree_bits_range,
+    );
+    let public_inputs = builder.add_virtual_targets(S::PUBLIC_INPUTS);
+    StarkProofWithPublicInputsTarget {
+        proof,
+        public_inputs,
+    }
+}
+
+/// Like `add_virtual_stark_proof`, but for proofs of any degree in `degree_bits_range`, as
+/// expected by `verify_stark_proof_with_degree_range_circuit`.
+pub fn add_virtual_stark_proof_with_degree_range<
+    F: RichField + Extendable<D>,
+    S: Stark<F, D>,
+    const D: usize,
+>(
+    builder: &mut CircuitBuilder<F, D>,
+    stark: S,
+    config: &StarkConfig,
+    degree_bits_range: Range<usize>,
+) -> StarkProofTarget<D> {
+    let cap_height = config.fri_config.cap_height;
+
+    let num_leaves_per_oracle = num_leaves_per_oracle(&stark, config);
+
+    let auxiliary_polys_cap = stark
+        .uses_lookups()
+        .then(|| builder.add_virtual_cap(cap_height));
+
+    StarkProofTarget {
+        trace_cap: builder.add_virtual_cap(cap_height),
+        auxiliary_polys_cap,
+        quotient_polys_cap: builder.add_virtual_cap(cap_height),
+        openings: add_stark_opening_set_target::<F, S, D>(builder, stark, config),
+        opening_proof: builder.add_virtual_fri_proof_with_degree_range(
+            &num_leaves_per_oracle,
+            degree_bits_range,
+            &config.fri_config,
+            false,
+        ),
+    }
+}
+
+/// The number of polynomials committed to by each oracle of a proof.
+fn num_leaves_per_oracle<F: RichField + Extendable<D>, S: Stark<F, D>, const D: usize>(
+    stark: &S,
+    config: &StarkConfig,
+) -> Vec<usize> {
+    // The auxiliary oracle is only committed to when the STARK uses lookups.
+    let mut num_leaves_per_oracle = vec![S::COLUMNS];
+    if stark.uses_lookups() {
+        num_leaves_per_oracle.push(stark.num_lookup_helper_columns(config));
+    }
+    num_leaves_per_oracle.push(stark.num_quotient_polys(config));
+    num_leaves_per_oracle
+}
+
 fn add_stark_opening_set_target<F: RichField + Extendable<D>, S: Stark<F, D>, const D: usize>(
     builder: &mut CircuitBuilder<F, D>,
     stark: S,
     config: &StarkConfig,
 ) -> StarkOpeningSetTarget<D> {
-    let num_challenges = config.num_challenges;
     StarkOpeningSetTarget {
         local_values: builder.add_virtual_extension_targets(S::COLUMNS),
         next_values: builder.add_virtual_extension_targets(S::COLUMNS),
@@ -249,11 +509,15 @@ fn add_stark_opening_set_target<F: RichField + Extendable<D>, S: Stark<F, D>, co
         auxiliary_polys_next: stark.uses_lookups().then(|| {
             builder.add_virtual_extension_targets(stark.num_lookup_helper_columns(config))
         }),
-        quotient_polys: builder
-            .add_virtual_extension_targets(stark.quotient_degree_factor() * num_challenges),
+        quotient_polys: builder.add_virtual_extension_targets(stark.num_quotient_polys(config)),
     }
 }
 
+/// Sets the targets of a proof and its public inputs, see `set_stark_proof_target`.
+///
+/// # Panics
+/// Panics if the proof is for a trace whose length is not a power of two, see
+/// `try_set_stark_proof_with_pis_target` for a version returning an error instead.
 pub fn set_stark_proof_with_pis_target<F, C: GenericConfig<D, F = F>, W, const D: usize>(
     witness: &mut W,
     stark_proof_with_pis_target: &StarkProofWithPublicInputsTarget<D>,
@@ -262,6 +526,21 @@ pub fn set_stark_proof_with_pis_target<F, C: GenericConfig<D, F = F>, W, const D
     F: RichField + Extendable<D>,
     C::Hasher: AlgebraicHasher<F>,
     W: Witness<F>,
+{
+    try_set_stark_proof_with_pis_target(witness, stark_proof_with_pis_target, stark_proof_with_pis)
+        .unwrap_or_else(|e| panic!("{e}"))
+}
+
+/// Sets the targets of a proof and its public inputs, see `try_set_stark_proof_target`.
+pub fn try_set_stark_proof_with_pis_target<F, C: GenericConfig<D, F = F>, W, const D: usize>(
+    witness: &mut W,
+    stark_proof_with_pis_target: &StarkProofWithPublicInputsTarget<D>,
+    stark_proof_with_pis: &StarkProofWithPublicInputs<F, C, D>,
+) -> Result<()>
+where
+    F: RichField + Extendable<D>,
+    C::Hasher: AlgebraicHasher<F>,
+    W: Witness<F>,
 {
     let StarkProofWithPublicInputs {
         proof,
@@ -277,9 +556,16 @@ pub fn set_stark_proof_with_pis_target<F, C: GenericConfig<D, F = F>, W, const D
         witness.set_target(pi_t, pi);
     }
 
-    set_stark_proof_target(witness, pt, proof);
+    try_set_stark_proof_target(witness, pt, proof)
 }
 
+/// Sets the targets of a proof. The proof may have a smaller degree than the one `proof_target` was
+/// built for, as supported by `verify_stark_proof_with_degree_range_circuit`, in which case the FRI
+/// proof is padded.
+///
+/// # Panics
+/// Panics if the proof is for a trace whose length is not a power of two, see
+/// `try_set_stark_proof_target` for a version returning an error instead.
 pub fn set_stark_proof_target<F, C: GenericConfig<D, F = F>, W, const D: usize>(
     witness: &mut W,
     proof_target: &StarkProofTarget<D>,
@@ -289,6 +575,27 @@ pub fn set_stark_proof_target<F, C: GenericConfig<D, F = F>, W, const D: usize>(
     C::Hasher: AlgebraicHasher<F>,
     W: Witness<F>,
 {
+    try_set_stark_proof_target(witness, proof_target, proof).unwrap_or_else(|e| panic!("{e}"))
+}
+
+/// Like `set_stark_proof_target`, but returns an error for proofs the recursive verifier can't
+/// handle. It only handles power-of-two trace lengths, so proofs of `2^k 3^j` traces with `j > 0`
+/// can only be verified natively.
+pub fn try_set_stark_proof_target<F, C: GenericConfig<D, F = F>, W, const D: usize>(
+    witness: &mut W,
+    proof_target: &StarkProofTarget<D>,
+    proof: &StarkProof<F, C, D>,
+) -> Result<()>
+where
+    F: RichField + Extendable<D>,
+    C::Hasher: AlgebraicHasher<F>,
+    W: Witness<F>,
+{
+    ensure!(
+        proof.trace_len.is_power_of_two(),
+        "Recursive verification only supports power-of-two trace lengths, got {}",
+        proof.trace_len
+    );
     witness.set_cap_target(&proof_target.trace_cap, &proof.trace_cap);
     witness.set_cap_target(&proof_target.quotient_polys_cap, &proof.quotient_polys_cap);
 
@@ -304,7 +611,8 @@ pub fn set_stark_proof_target<F, C: GenericConfig<D, F = F>, W, const D: usize>(
         witness.set_cap_target(auxiliary_polys_cap_target, auxiliary_polys_cap);
     }
 
-    set_fri_proof_target(witness, &proof_target.opening_proof, &proof.opening_proof);
+    set_fri_proof_target_with_padding(witness, &proof_target.opening_proof, &proof.opening_proof);
+    Ok(())
 }
 
 /// Utility function to check that all lookups data wrapped in `Option`s are `Some` iff
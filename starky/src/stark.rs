@@ -17,10 +17,6 @@ use crate::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer
 use crate::evaluation_frame::StarkEvaluationFrame;
 use crate::lookup::Lookup;
 
-const TRACE_ORACLE_INDEX: usize = 0;
-const AUXILIARY_ORACLE_INDEX: usize = 1;
-const QUOTIENT_ORACLE_INDEX: usize = 2;
-
 /// Represents a STARK system.
 pub trait Stark<F: RichField + Extendable<D>, const D: usize>: Sync {
     /// The total number of columns in the trace.
@@ -88,7 +84,7 @@ pub trait Stark<F: RichField + Extendable<D>, const D: usize>: Sync {
     }
 
     fn num_quotient_polys(&self, config: &StarkConfig) -> usize {
-        self.quotient_degree_factor() * config.num_challenges
+        self.quotient_degree_factor() * config.num_challenges * config.challenge_degree::<F>(D)
     }
 
     /// Computes the FRI instance used to prove this Stark.
@@ -98,28 +94,32 @@ pub trait Stark<F: RichField + Extendable<D>, const D: usize>: Sync {
         g: F,
         config: &StarkConfig,
     ) -> FriInstanceInfo<F, D> {
-        let trace_oracle = FriOracleInfo {
+        let mut oracles = vec![];
+        let trace_info = FriPolynomialInfo::from_range(oracles.len(), 0..Self::COLUMNS);
+        oracles.push(FriOracleInfo {
             num_polys: Self::COLUMNS,
             blinding: false,
+        });
+
+        // The auxiliary oracle is only committed to when the STARK uses lookups.
+        let num_auxiliary_polys = self.num_lookup_helper_columns(config);
+        let auxiliary_polys_info = if self.uses_lookups() {
+            let info = FriPolynomialInfo::from_range(oracles.len(), 0..num_auxiliary_polys);
+            oracles.push(FriOracleInfo {
+                num_polys: num_auxiliary_polys,
+                blinding: false,
+            });
+            info
+        } else {
+            vec![]
         };
-        let trace_info = FriPolynomialInfo::from_range(TRACE_ORACLE_INDEX, 0..Self::COLUMNS);
-
-        let num_lookup_columns = self.num_lookup_helper_columns(config);
-        let num_auxiliary_polys = num_lookup_columns;
-        let auxiliary_oracle = FriOracleInfo {
-            num_polys: num_auxiliary_polys,
-            blinding: false,
-        };
-        let auxiliary_polys_info =
-            FriPolynomialInfo::from_range(AUXILIARY_ORACLE_INDEX, 0..num_auxiliary_polys);
 
         let num_quotient_polys = self.num_quotient_polys(config);
-        let quotient_oracle = FriOracleInfo {
+        let quotient_info = FriPolynomialInfo::from_range(oracles.len(), 0..num_quotient_polys);
+        oracles.push(FriOracleInfo {
             num_polys: num_quotient_polys,
             blinding: false,
-        };
-        let quotient_info =
-            FriPolynomialInfo::from_range(QUOTIENT_ORACLE_INDEX, 0..num_quotient_polys);
+        });
 
         let zeta_batch = FriBatchInfo {
             point: zeta,
@@ -136,7 +136,7 @@ pub trait Stark<F: RichField + Extendable<D>, const D: usize>: Sync {
         };
 
         FriInstanceInfo {
-            oracles: vec![trace_oracle, auxiliary_oracle, quotient_oracle],
+            oracles,
             batches: vec![zeta_batch, zeta_next_batch],
         }
     }
@@ -149,28 +149,44 @@ pub trait Stark<F: RichField + Extendable<D>, const D: usize>: Sync {
         g: F,
         config: &StarkConfig,
     ) -> FriInstanceInfoTarget<D> {
-        let trace_oracle = FriOracleInfo {
-            num_polys: Self::COLUMNS,
-            blinding: false,
-        };
-        let trace_info = FriPolynomialInfo::from_range(TRACE_ORACLE_INDEX, 0..Self::COLUMNS);
+        let zeta_next = builder.mul_const_extension(g, zeta);
+        self.fri_instance_target_with_next_point(zeta, zeta_next, config)
+    }
 
-        let num_lookup_columns = self.num_lookup_helper_columns(config);
-        let num_auxiliary_polys = num_lookup_columns;
-        let auxiliary_oracle = FriOracleInfo {
-            num_polys: num_auxiliary_polys,
+    /// Like `fri_instance_target`, but with the point of the next-row openings given as a target,
+    /// for when the domain generator isn't known when building the circuit.
+    fn fri_instance_target_with_next_point(
+        &self,
+        zeta: ExtensionTarget<D>,
+        zeta_next: ExtensionTarget<D>,
+        config: &StarkConfig,
+    ) -> FriInstanceInfoTarget<D> {
+        let mut oracles = vec![];
+        let trace_info = FriPolynomialInfo::from_range(oracles.len(), 0..Self::COLUMNS);
+        oracles.push(FriOracleInfo {
+            num_polys: Self::COLUMNS,
             blinding: false,
+        });
+
+        // The auxiliary oracle is only committed to when the STARK uses lookups.
+        let num_auxiliary_polys = self.num_lookup_helper_columns(config);
+        let auxiliary_polys_info = if self.uses_lookups() {
+            let info = FriPolynomialInfo::from_range(oracles.len(), 0..num_auxiliary_polys);
+            oracles.push(FriOracleInfo {
+                num_polys: num_auxiliary_polys,
+                blinding: false,
+            });
+            info
+        } else {
+            vec![]
         };
-        let auxiliary_polys_info =
-            FriPolynomialInfo::from_range(AUXILIARY_ORACLE_INDEX, 0..num_auxiliary_polys);
 
         let num_quotient_polys = self.num_quotient_polys(config);
-        let quotient_oracle = FriOracleInfo {
+        let quotient_info = FriPolynomialInfo::from_range(oracles.len(), 0..num_quotient_polys);
+        oracles.push(FriOracleInfo {
             num_polys: num_quotient_polys,
             blinding: false,
-        };
-        let quotient_info =
-            FriPolynomialInfo::from_range(QUOTIENT_ORACLE_INDEX, 0..num_quotient_polys);
+        });
 
         let zeta_batch = FriBatchInfoTarget {
             point: zeta,
@@ -181,14 +197,13 @@ pub trait Stark<F: RichField + Extendable<D>, const D: usize>: Sync {
             ]
             .concat(),
         };
-        let zeta_next = builder.mul_const_extension(g, zeta);
         let zeta_next_batch = FriBatchInfoTarget {
             point: zeta_next,
             polynomials: [trace_info, auxiliary_polys_info].concat(),
         };
 
         FriInstanceInfoTarget {
-            oracles: vec![trace_oracle, auxiliary_oracle, quotient_oracle],
+            oracles,
             batches: vec![zeta_batch, zeta_next_batch],
         }
     }
@@ -203,6 +218,7 @@ pub trait Stark<F: RichField + Extendable<D>, const D: usize>: Sync {
             .map(|lookup| lookup.num_helper_columns(self.constraint_degree()))
             .sum::<usize>()
             * config.num_challenges
+            * config.challenge_degree::<F>(D)
     }
 
     fn uses_lookups(&self) -> bool {
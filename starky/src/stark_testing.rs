@@ -9,7 +9,7 @@ use plonky2::hash::hash_types::RichField;
 use plonky2::iop::witness::{PartialWitness, WitnessWrite};
 use plonky2::plonk::circuit_builder::CircuitBuilder;
 use plonky2::plonk::circuit_data::CircuitConfig;
-use plonky2::plonk::config::GenericConfig;
+use plonky2::plonk::config::{AlgebraicHasher, GenericConfig};
 use plonky2::util::{log2_ceil, log2_strict, transpose};
 
 use crate::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
@@ -78,7 +78,10 @@ pub fn test_stark_circuit_constraints<
     const D: usize,
 >(
     stark: S,
-) -> Result<()> {
+) -> Result<()>
+where
+    C::InnerHasher: AlgebraicHasher<F>,
+{
     // Compute native constraint evaluation on random values.
     let vars = S::EvaluationFrame::from_values(
         &F::Extension::rand_vec(S::COLUMNS),
@@ -0,0 +1,530 @@
+//! Symbolic evaluation of the constraints of a [`Stark`].
+//!
+//! [`SymbolicValue`] implements [`PackedField`] with multivariate polynomials over the trace
+//! values, so that running `eval_packed_generic` on it yields every constraint as an explicit
+//! polynomial. See [`symbolic_constraints`].
+
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::iter::{Product, Sum};
+use core::marker::PhantomData;
+use core::ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Sub, SubAssign};
+use std::panic::{self, AssertUnwindSafe};
+
+use anyhow::{anyhow, Result};
+use plonky2::field::babybear_field::BabyBearField;
+use plonky2::field::extension::{Extendable, FieldExtension};
+use plonky2::field::goldilocks_field::GoldilocksField;
+use plonky2::field::packed::PackedField;
+use plonky2::field::types::{Field, PrimeField64};
+use plonky2::hash::hash_types::RichField;
+
+use crate::constraint_consumer::ConstraintConsumer;
+use crate::evaluation_frame::StarkEvaluationFrame;
+use crate::stark::Stark;
+
+/// A monomial, given by the indices of its variables, sorted and repeated according to their
+/// exponents.
+type Monomial = Vec<usize>;
+
+/// The terms of a polynomial, with canonical coefficients. Terms with a zero coefficient are
+/// omitted.
+type Terms = BTreeMap<Monomial, u64>;
+
+enum Node {
+    Polynomial(Terms),
+    /// An accumulator of constraints, holding the previous accumulator and the last constraint
+    /// added to it, unless no constraint was added yet.
+    Accumulator(Option<(usize, usize)>),
+}
+
+const ZERO_INDEX: usize = 0;
+const ONE_INDEX: usize = 1;
+
+fn initial_nodes() -> Vec<Node> {
+    vec![
+        Node::Polynomial(Terms::new()),
+        Node::Polynomial(Terms::from([(Monomial::new(), 1)])),
+    ]
+}
+
+std::thread_local! {
+    /// The values of all the `SymbolicValue`s created by this thread.
+    static NODES: RefCell<Vec<Node>> = RefCell::new(initial_nodes());
+}
+
+/// A polynomial over the trace values, used as a [`PackedField`] to evaluate constraints
+/// symbolically. The polynomial itself is stored in a thread-local arena, so that this handle is
+/// `Copy` as `PackedField` requires.
+///
+/// A symbolic value can also be a constraint accumulator, which records the constraints added to
+/// it and ignores scalings by the alphas of a `ConstraintConsumer`. Any other operation on an
+/// accumulator panics.
+#[derive(Copy, Clone, Debug)]
+pub struct SymbolicValue<F: RichField> {
+    index: usize,
+    _phantom: PhantomData<F>,
+}
+
+impl<F: RichField> SymbolicValue<F> {
+    const fn new(index: usize) -> Self {
+        Self {
+            index,
+            _phantom: PhantomData,
+        }
+    }
+
+    fn push(node: Node) -> Self {
+        NODES.with(|nodes| {
+            let mut nodes = nodes.borrow_mut();
+            nodes.push(node);
+            Self::new(nodes.len() - 1)
+        })
+    }
+
+    /// The variable of the given index.
+    pub fn variable(index: usize) -> Self {
+        Self::push(Node::Polynomial(Terms::from([(vec![index], 1)])))
+    }
+
+    /// An accumulator to which no constraint was added yet.
+    fn accumulator() -> Self {
+        Self::push(Node::Accumulator(None))
+    }
+
+    fn map(self, f: impl FnOnce(&Terms) -> Terms) -> Self {
+        let terms = NODES.with(|nodes| match &nodes.borrow()[self.index] {
+            Node::Polynomial(terms) => f(terms),
+            Node::Accumulator(_) => {
+                panic!("Constraint accumulators can only be scaled and added to")
+            }
+        });
+        Self::push(Node::Polynomial(terms))
+    }
+
+    fn combine(self, rhs: Self, f: impl FnOnce(&Terms, &Terms) -> Terms) -> Self {
+        let terms = NODES.with(|nodes| {
+            let nodes = nodes.borrow();
+            match (&nodes[self.index], &nodes[rhs.index]) {
+                (Node::Polynomial(lhs), Node::Polynomial(rhs)) => f(lhs, rhs),
+                _ => panic!("Constraint accumulators can only be scaled and added to"),
+            }
+        });
+        Self::push(Node::Polynomial(terms))
+    }
+
+    fn is_accumulator(self) -> bool {
+        NODES.with(|nodes| matches!(nodes.borrow()[self.index], Node::Accumulator(_)))
+    }
+
+    /// The polynomial represented by this value.
+    ///
+    /// # Panics
+    /// Panics if this value is a constraint accumulator.
+    pub fn polynomial(self) -> SymbolicPolynomial<F> {
+        NODES.with(|nodes| match &nodes.borrow()[self.index] {
+            Node::Polynomial(terms) => SymbolicPolynomial::from_terms(terms),
+            Node::Accumulator(_) => panic!("Constraint accumulators aren't polynomials"),
+        })
+    }
+
+    /// The constraints added to this accumulator, in order.
+    fn constraints(self) -> Vec<SymbolicPolynomial<F>> {
+        let mut constraints = Vec::new();
+        let mut index = self.index;
+        while let Some((previous, constraint)) = NODES.with(|nodes| match nodes.borrow()[index] {
+            Node::Accumulator(last) => last,
+            Node::Polynomial(_) => unreachable!("Accumulators only follow other accumulators"),
+        }) {
+            constraints.push(Self::new(constraint).polynomial());
+            index = previous;
+        }
+        constraints.reverse();
+        constraints
+    }
+}
+
+fn add_terms<F: RichField>(lhs: &Terms, rhs: &Terms) -> Terms {
+    let mut sum = lhs.clone();
+    for (monomial, &coeff) in rhs {
+        add_term::<F>(&mut sum, monomial.clone(), F::from_canonical_u64(coeff));
+    }
+    sum
+}
+
+fn add_term<F: RichField>(terms: &mut Terms, monomial: Monomial, coeff: F) {
+    let existing = terms
+        .get(&monomial)
+        .map_or(F::ZERO, |&c| F::from_canonical_u64(c));
+    let sum = existing + coeff;
+    if sum.is_zero() {
+        terms.remove(&monomial);
+    } else {
+        terms.insert(monomial, sum.to_canonical_u64());
+    }
+}
+
+fn mul_terms<F: RichField>(lhs: &Terms, rhs: &Terms) -> Terms {
+    let mut product = Terms::new();
+    for (lhs_monomial, &lhs_coeff) in lhs {
+        for (rhs_monomial, &rhs_coeff) in rhs {
+            let mut monomial = lhs_monomial.clone();
+            monomial.extend(rhs_monomial);
+            monomial.sort_unstable();
+            let coeff = F::from_canonical_u64(lhs_coeff) * F::from_canonical_u64(rhs_coeff);
+            add_term(&mut product, monomial, coeff);
+        }
+    }
+    product
+}
+
+fn scale_terms<F: RichField>(terms: &Terms, scalar: F) -> Terms {
+    if scalar.is_zero() {
+        return Terms::new();
+    }
+    terms
+        .iter()
+        .map(|(monomial, &coeff)| {
+            let coeff = F::from_canonical_u64(coeff) * scalar;
+            (monomial.clone(), coeff.to_canonical_u64())
+        })
+        .collect()
+}
+
+impl<F: RichField> Default for SymbolicValue<F> {
+    fn default() -> Self {
+        Self::new(ZERO_INDEX)
+    }
+}
+
+impl<F: RichField> From<F> for SymbolicValue<F> {
+    fn from(value: F) -> Self {
+        let mut terms = Terms::new();
+        add_term(&mut terms, Monomial::new(), value);
+        Self::push(Node::Polynomial(terms))
+    }
+}
+
+impl<F: RichField> Add<Self> for SymbolicValue<F> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        if self.is_accumulator() && !rhs.is_accumulator() {
+            return Self::push(Node::Accumulator(Some((self.index, rhs.index))));
+        }
+        self.combine(rhs, add_terms::<F>)
+    }
+}
+
+impl<F: RichField> Add<F> for SymbolicValue<F> {
+    type Output = Self;
+
+    fn add(self, rhs: F) -> Self {
+        self + Self::from(rhs)
+    }
+}
+
+impl<F: RichField> AddAssign<Self> for SymbolicValue<F> {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl<F: RichField> AddAssign<F> for SymbolicValue<F> {
+    fn add_assign(&mut self, rhs: F) {
+        *self = *self + rhs;
+    }
+}
+
+impl<F: RichField> Sum for SymbolicValue<F> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), |acc, x| acc + x)
+    }
+}
+
+impl<F: RichField> Sub<Self> for SymbolicValue<F> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        self + -rhs
+    }
+}
+
+impl<F: RichField> Sub<F> for SymbolicValue<F> {
+    type Output = Self;
+
+    fn sub(self, rhs: F) -> Self {
+        self + -rhs
+    }
+}
+
+impl<F: RichField> SubAssign<Self> for SymbolicValue<F> {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl<F: RichField> SubAssign<F> for SymbolicValue<F> {
+    fn sub_assign(&mut self, rhs: F) {
+        *self = *self - rhs;
+    }
+}
+
+impl<F: RichField> Neg for SymbolicValue<F> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        self.map(|terms| scale_terms(terms, F::NEG_ONE))
+    }
+}
+
+impl<F: RichField> Mul<Self> for SymbolicValue<F> {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        self.combine(rhs, mul_terms::<F>)
+    }
+}
+
+impl<F: RichField> Mul<F> for SymbolicValue<F> {
+    type Output = Self;
+
+    fn mul(self, rhs: F) -> Self {
+        if self.is_accumulator() {
+            // Accumulators keep the constraints themselves rather than their combination.
+            return self;
+        }
+        self.map(|terms| scale_terms(terms, rhs))
+    }
+}
+
+impl<F: RichField> MulAssign<Self> for SymbolicValue<F> {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+impl<F: RichField> MulAssign<F> for SymbolicValue<F> {
+    fn mul_assign(&mut self, rhs: F) {
+        *self = *self * rhs;
+    }
+}
+
+impl<F: RichField> Product for SymbolicValue<F> {
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::new(ONE_INDEX), |acc, x| acc * x)
+    }
+}
+
+impl<F: RichField> Div<F> for SymbolicValue<F> {
+    type Output = Self;
+
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn div(self, rhs: F) -> Self {
+        self * rhs.inverse()
+    }
+}
+
+/// Implements the operations with a scalar on the left, which the orphan rule only allows for
+/// concrete fields. These are the fields whose constraints can be evaluated symbolically.
+macro_rules! impl_symbolic_scalar_ops {
+    ($($field:ty),*) => {
+        $(
+            impl Add<SymbolicValue<$field>> for $field {
+                type Output = SymbolicValue<$field>;
+
+                fn add(self, rhs: SymbolicValue<$field>) -> SymbolicValue<$field> {
+                    rhs + self
+                }
+            }
+
+            impl Sub<SymbolicValue<$field>> for $field {
+                type Output = SymbolicValue<$field>;
+
+                fn sub(self, rhs: SymbolicValue<$field>) -> SymbolicValue<$field> {
+                    -rhs + self
+                }
+            }
+
+            impl Mul<SymbolicValue<$field>> for $field {
+                type Output = SymbolicValue<$field>;
+
+                fn mul(self, rhs: SymbolicValue<$field>) -> SymbolicValue<$field> {
+                    rhs * self
+                }
+            }
+        )*
+    };
+}
+
+impl_symbolic_scalar_ops!(GoldilocksField, BabyBearField);
+
+/// Fails for the methods of `PackedField` which view packed values as slices of scalars, which a
+/// `SymbolicValue` is not. [`symbolic_constraints`] reports this failure as an error.
+fn unsupported(method: &str) -> ! {
+    panic!(
+        "`{method}` is not supported by symbolic evaluation, which only allows arithmetic on the \
+         values of the evaluation frame, not viewing them as slices of field elements"
+    )
+}
+
+// SAFETY: a `SymbolicValue` can't be cast to or from `[F; 1]`, so all the methods relying on such
+// casts fail instead.
+unsafe impl<F: RichField> PackedField for SymbolicValue<F>
+where
+    F: Add<Self, Output = Self> + Mul<Self, Output = Self> + Sub<Self, Output = Self>,
+{
+    type Scalar = F;
+
+    const WIDTH: usize = 1;
+    const ZEROS: Self = Self::new(ZERO_INDEX);
+    const ONES: Self = Self::new(ONE_INDEX);
+
+    fn from_slice(_slice: &[F]) -> &Self {
+        unsupported("PackedField::from_slice")
+    }
+    fn from_slice_mut(_slice: &mut [F]) -> &mut Self {
+        unsupported("PackedField::from_slice_mut")
+    }
+    fn as_slice(&self) -> &[F] {
+        unsupported("PackedField::as_slice")
+    }
+    fn as_slice_mut(&mut self) -> &mut [F] {
+        unsupported("PackedField::as_slice_mut")
+    }
+
+    fn interleave(&self, other: Self, block_len: usize) -> (Self, Self) {
+        match block_len {
+            1 => (*self, other),
+            _ => panic!("unsupported block length"),
+        }
+    }
+
+    fn pack_slice(_buf: &[F]) -> &[Self] {
+        unsupported("PackedField::pack_slice")
+    }
+    fn pack_slice_mut(_buf: &mut [F]) -> &mut [Self] {
+        unsupported("PackedField::pack_slice_mut")
+    }
+}
+
+/// A polynomial obtained by symbolic evaluation.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SymbolicPolynomial<F: Field> {
+    terms: BTreeMap<Monomial, F>,
+}
+
+impl<F: RichField> SymbolicPolynomial<F> {
+    fn from_terms(terms: &Terms) -> Self {
+        Self {
+            terms: terms
+                .iter()
+                .map(|(monomial, &coeff)| (monomial.clone(), F::from_canonical_u64(coeff)))
+                .collect(),
+        }
+    }
+
+    /// The total degree of this polynomial, or `None` if it is identically zero.
+    pub fn degree(&self) -> Option<usize> {
+        self.terms.keys().map(Vec::len).max()
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// Evaluates this polynomial, with the value of each variable given by its index.
+    pub fn eval<FE: FieldExtension<D, BaseField = F>, const D: usize>(
+        &self,
+        variables: &[FE],
+    ) -> FE {
+        self.terms
+            .iter()
+            .map(|(monomial, &coeff)| {
+                monomial
+                    .iter()
+                    .fold(FE::from_basefield(coeff), |acc, &i| acc * variables[i])
+            })
+            .sum()
+    }
+}
+
+/// Evaluates the constraints emitted by `eval_packed_base` symbolically, with the given public
+/// inputs. Returns the constraints in the order in which they are emitted, multiplied by their row
+/// filter.
+///
+/// The variables of the returned polynomials are, in order, the `S::COLUMNS` local values, the
+/// `S::COLUMNS` next values, and the row filters `z_last`, `lagrange_basis_first` and
+/// `lagrange_basis_last`.
+///
+/// Polynomials are kept expanded, so this can be slow for constraints with many terms.
+///
+/// # Errors
+/// Fails if the constraints use operations which can't be evaluated symbolically, such as viewing
+/// packed values as slices.
+pub fn symbolic_constraints<F, S, const D: usize>(
+    stark: &S,
+    public_inputs: &[F],
+) -> Result<Vec<SymbolicPolynomial<F>>>
+where
+    F: RichField + Extendable<D>,
+    S: Stark<F, D>,
+    SymbolicValue<F>: PackedField<Scalar = F>,
+{
+    let variables = (0..2 * S::COLUMNS + 3)
+        .map(SymbolicValue::variable)
+        .collect::<Vec<_>>();
+    let (local_values, rest) = variables.split_at(S::COLUMNS);
+    let (next_values, filters) = rest.split_at(S::COLUMNS);
+    let vars = S::EvaluationFrame::from_values(local_values, next_values, public_inputs);
+
+    let mut consumer = ConstraintConsumer::new(vec![F::ONE], filters[0], filters[1], filters[2]);
+    consumer.constraint_accs = vec![SymbolicValue::accumulator()];
+    let evaluation = panic::catch_unwind(AssertUnwindSafe(|| {
+        stark.eval_packed_base(&vars, &mut consumer);
+        consumer.accumulators()[0].constraints()
+    }));
+
+    // The values created by this evaluation are no longer needed.
+    NODES.with(|nodes| nodes.borrow_mut().truncate(2));
+
+    evaluation.map_err(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .copied()
+            .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+            .unwrap_or("unknown error");
+        anyhow!("Constraints can't be evaluated symbolically: {message}")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::field::types::Field;
+
+    use crate::symbolic::SymbolicValue;
+
+    type F = GoldilocksField;
+
+    #[test]
+    fn test_symbolic_arithmetic() {
+        let x = SymbolicValue::<F>::variable(0);
+        let y = SymbolicValue::<F>::variable(1);
+
+        let cancelled = (x + y) * (x - y) - x * x + y * y;
+        assert!(cancelled.polynomial().is_zero());
+        assert_eq!(cancelled.polynomial().degree(), None);
+
+        let cube = (x + F::ONE) * (x * y) * F::TWO - F::TWO * y;
+        assert_eq!(cube.polynomial().degree(), Some(3));
+        let (a, b) = (F::from_canonical_u64(3), F::from_canonical_u64(5));
+        assert_eq!(
+            cube.polynomial().eval(&[a, b]),
+            (a + F::ONE) * a * b * F::TWO - F::TWO * b
+        );
+    }
+}
@@ -0,0 +1,365 @@
+//! Utilities to generate the trace of a STARK with named columns.
+//!
+//! A table describes its columns with a `#[repr(C)]` struct generic over the column type, declared
+//! through the [`columns_view`](crate::columns_view) macro. The same struct can then be used to
+//! fill the trace with a [`TraceBuilder`], and to read the columns of an evaluation frame when
+//! evaluating constraints, either natively or recursively.
+
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::Write;
+use core::marker::PhantomData;
+use core::slice;
+
+use plonky2::field::fft::smooth_factors;
+use plonky2::field::polynomial::PolynomialValues;
+use plonky2::field::types::Field;
+use plonky2::util::transpose;
+
+/// A view of the columns of a trace row, where each column holds a `T`.
+///
+/// # Safety
+/// Implementors must be `#[repr(C)]` structs whose fields are all of type `T`, or (nested) arrays
+/// of `T`, for a total of `NUM_COLUMNS` values of type `T`, so that they can be cast from and to
+/// `[T; NUM_COLUMNS]`. Use the [`columns_view`](crate::columns_view) macro to implement this
+/// trait.
+pub unsafe trait ColumnsView<T: Copy>: Copy {
+    /// The number of columns of this view.
+    const NUM_COLUMNS: usize;
+
+    /// The names of the columns, in order. Array fields are named `field[i]`.
+    fn column_names() -> Vec<String>;
+
+    /// Views the given row, which must have exactly `NUM_COLUMNS` values, as `Self`.
+    fn from_slice(row: &[T]) -> &Self {
+        assert_eq!(row.len(), Self::NUM_COLUMNS, "Invalid row length");
+        unsafe { &*row.as_ptr().cast::<Self>() }
+    }
+
+    /// Views the given row, which must have exactly `NUM_COLUMNS` values, as `Self`.
+    fn from_slice_mut(row: &mut [T]) -> &mut Self {
+        assert_eq!(row.len(), Self::NUM_COLUMNS, "Invalid row length");
+        unsafe { &mut *row.as_mut_ptr().cast::<Self>() }
+    }
+
+    fn as_slice(&self) -> &[T] {
+        unsafe { slice::from_raw_parts((self as *const Self).cast::<T>(), Self::NUM_COLUMNS) }
+    }
+
+    fn as_slice_mut(&mut self) -> &mut [T] {
+        unsafe { slice::from_raw_parts_mut((self as *mut Self).cast::<T>(), Self::NUM_COLUMNS) }
+    }
+}
+
+/// Declares a `#[repr(C)]` struct viewing the columns of a trace row, and implements
+/// [`ColumnsView`] for it. The struct must have a single type parameter `T`, and all its fields
+/// must be of type `T` or (nested) arrays of `T`.
+///
+/// On top of [`ColumnsView`], this implements `Default` when `T: Default`, and a `col_map`
+/// constructor on the `usize` instance of the view, holding the index of each column:
+///
+/// ```
+/// starky::columns_view! {
+///     pub struct FibonacciColumns<T> {
+///         pub x: [T; 2],
+///         pub counter: T,
+///     }
+/// }
+///
+/// let col_map = FibonacciColumns::<usize>::col_map();
+/// assert_eq!(col_map.x, [0, 1]);
+/// assert_eq!(col_map.counter, 2);
+/// ```
+#[macro_export]
+macro_rules! columns_view {
+    (
+        $(#[$attr:meta])*
+        $vis:vis struct $name:ident<T> {
+            $($(#[$field_attr:meta])* $field_vis:vis $field:ident : $ty:ty),* $(,)?
+        }
+    ) => {
+        $(#[$attr])*
+        #[repr(C)]
+        #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+        $vis struct $name<T: Copy> {
+            $($(#[$field_attr])* $field_vis $field: $ty),*
+        }
+
+        unsafe impl<T: Copy> $crate::trace::ColumnsView<T> for $name<T> {
+            const NUM_COLUMNS: usize = ::core::mem::size_of::<$name<u8>>();
+
+            fn column_names() -> $crate::trace::__private::Vec<$crate::trace::__private::String> {
+                let view = $name::<usize>::col_map();
+                let mut names = $crate::trace::__private::Vec::new();
+                $(
+                    $crate::trace::__private::push_field_names(
+                        &mut names,
+                        stringify!($field),
+                        ::core::mem::size_of_val(&view.$field) / ::core::mem::size_of::<usize>(),
+                    );
+                )*
+                names
+            }
+        }
+
+        // All fields must hold values of type `T`.
+        const _: () = assert!(
+            ::core::mem::size_of::<$name<u64>>() == 8 * ::core::mem::size_of::<$name<u8>>()
+        );
+
+        impl<T: Copy + Default> Default for $name<T> {
+            fn default() -> Self {
+                *<Self as $crate::trace::ColumnsView<T>>::from_slice(
+                    &$crate::trace::__private::vec![
+                        T::default();
+                        <Self as $crate::trace::ColumnsView<T>>::NUM_COLUMNS
+                    ],
+                )
+            }
+        }
+
+        impl $name<usize> {
+            /// Returns the view whose values are the indices of the corresponding columns.
+            pub fn col_map() -> Self {
+                *<Self as $crate::trace::ColumnsView<usize>>::from_slice(
+                    &(0..<Self as $crate::trace::ColumnsView<usize>>::NUM_COLUMNS)
+                        .collect::<$crate::trace::__private::Vec<_>>(),
+                )
+            }
+        }
+    };
+}
+
+#[doc(hidden)]
+pub mod __private {
+    use alloc::format;
+    pub use alloc::string::String;
+    use alloc::string::ToString;
+    pub use alloc::vec;
+    pub use alloc::vec::Vec;
+
+    pub fn push_field_names(names: &mut Vec<String>, field: &str, width: usize) {
+        if width == 1 {
+            names.push(field.to_string());
+        } else {
+            names.extend((0..width).map(|i| format!("{field}[{i}]")));
+        }
+    }
+}
+
+/// How to pad a trace to a power-of-two number of rows.
+#[derive(Clone, Debug)]
+pub enum Padding<V> {
+    /// Repeat the last row of the trace.
+    RepeatLastRow,
+    /// Append rows whose columns are all zero.
+    ZeroRows,
+    /// Append copies of the given row.
+    Row(V),
+}
+
+/// A builder for the trace of a STARK, whose rows are viewed as `V`.
+#[derive(Clone, Debug)]
+pub struct TraceBuilder<F: Field, V: ColumnsView<F>> {
+    /// The values of the trace, row by row.
+    values: Vec<F>,
+    _phantom: PhantomData<V>,
+}
+
+impl<F: Field, V: ColumnsView<F>> Default for TraceBuilder<F, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F: Field, V: ColumnsView<F>> TraceBuilder<F, V> {
+    pub const fn new() -> Self {
+        Self {
+            values: Vec::new(),
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn with_capacity(num_rows: usize) -> Self {
+        Self {
+            values: Vec::with_capacity(num_rows * V::NUM_COLUMNS),
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn num_rows(&self) -> usize {
+        self.values.len() / V::NUM_COLUMNS
+    }
+
+    /// Appends a row whose columns are all zero, and returns it so that it can be filled.
+    pub fn push_row(&mut self) -> &mut V {
+        let start = self.values.len();
+        self.values.resize(start + V::NUM_COLUMNS, F::ZERO);
+        V::from_slice_mut(&mut self.values[start..])
+    }
+
+    /// Appends the given row.
+    pub fn push(&mut self, row: V) {
+        self.values.extend_from_slice(row.as_slice());
+    }
+
+    pub fn row(&self, index: usize) -> &V {
+        V::from_slice(&self.values[index * V::NUM_COLUMNS..(index + 1) * V::NUM_COLUMNS])
+    }
+
+    pub fn row_mut(&mut self, index: usize) -> &mut V {
+        V::from_slice_mut(&mut self.values[index * V::NUM_COLUMNS..(index + 1) * V::NUM_COLUMNS])
+    }
+
+    /// Pads the trace with the given strategy, to the smallest power of two which is at least
+    /// `min_rows` and the current number of rows.
+    pub fn pad(&mut self, padding: Padding<V>, min_rows: usize) {
+        let padded_rows = self.num_rows().max(min_rows).max(1).next_power_of_two();
+        self.pad_to(padding, padded_rows);
+    }
+
+    /// Pads the trace with the given strategy to exactly `num_rows` rows. This allows lengths of
+    /// the form `2^k 3^j`, which `prove` also accepts, to avoid up to doubling the trace.
+    pub fn pad_to(&mut self, padding: Padding<V>, num_rows: usize) {
+        let current_rows = self.num_rows();
+        assert!(
+            num_rows >= current_rows,
+            "Cannot pad a trace of {current_rows} rows to {num_rows} rows"
+        );
+        let padding_row = match padding {
+            Padding::RepeatLastRow => {
+                assert!(
+                    current_rows > 0,
+                    "Cannot repeat the last row of an empty trace"
+                );
+                *self.row(current_rows - 1)
+            }
+            Padding::ZeroRows => *V::from_slice(&vec![F::ZERO; V::NUM_COLUMNS]),
+            Padding::Row(row) => row,
+        };
+        self.values
+            .reserve((num_rows - current_rows) * V::NUM_COLUMNS);
+        for _ in current_rows..num_rows {
+            self.push(padding_row);
+        }
+    }
+
+    /// Converts the trace into the column polynomials expected by `prove`. The trace must have been
+    /// padded to a length of the form `2^k 3^j`.
+    pub fn into_polynomial_values(self) -> Vec<PolynomialValues<F>> {
+        assert!(
+            smooth_factors(self.num_rows()).is_some(),
+            "The trace must be padded to a length of the form 2^k 3^j"
+        );
+        let rows = self
+            .values
+            .chunks(V::NUM_COLUMNS)
+            .map(<[F]>::to_vec)
+            .collect::<Vec<_>>();
+        transpose(&rows)
+            .into_iter()
+            .map(PolynomialValues::new)
+            .collect()
+    }
+
+    /// Dumps the trace as CSV, with a header containing the column names, for debugging purposes.
+    pub fn to_csv(&self) -> String {
+        let mut csv = V::column_names().join(",");
+        csv.push('\n');
+        for row in self.values.chunks(V::NUM_COLUMNS) {
+            let line = row.iter().map(ToString::to_string).collect::<Vec<_>>();
+            writeln!(csv, "{}", line.join(",")).expect("Writing to a String cannot fail");
+        }
+        csv
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::field::types::Field;
+
+    use crate::trace::{ColumnsView, Padding, TraceBuilder};
+
+    type F = GoldilocksField;
+
+    columns_view! {
+        /// Columns of a toy table.
+        struct TestColumns<T> {
+            counter: T,
+            limbs: [T; 2],
+            is_padding: T,
+        }
+    }
+
+    fn test_trace() -> TraceBuilder<F, TestColumns<F>> {
+        let mut trace = TraceBuilder::<F, TestColumns<F>>::new();
+        for i in 0..3 {
+            let row = trace.push_row();
+            row.counter = F::from_canonical_usize(i);
+            row.limbs = [F::ONE, F::TWO];
+        }
+        trace
+    }
+
+    #[test]
+    fn test_columns_view() {
+        assert_eq!(TestColumns::<F>::NUM_COLUMNS, 4);
+        let col_map = TestColumns::<usize>::col_map();
+        assert_eq!(col_map.counter, 0);
+        assert_eq!(col_map.limbs, [1, 2]);
+        assert_eq!(col_map.is_padding, 3);
+        assert_eq!(
+            TestColumns::<F>::column_names(),
+            ["counter", "limbs[0]", "limbs[1]", "is_padding"]
+        );
+    }
+
+    #[test]
+    fn test_padding() {
+        let mut trace = test_trace();
+        trace.pad(Padding::RepeatLastRow, 0);
+        assert_eq!(trace.num_rows(), 4);
+        assert_eq!(trace.row(3), trace.row(2));
+
+        let mut trace = test_trace();
+        trace.pad(Padding::ZeroRows, 8);
+        assert_eq!(trace.num_rows(), 8);
+        assert_eq!(*trace.row(7), TestColumns::default());
+
+        let mut trace = test_trace();
+        trace.pad_to(Padding::RepeatLastRow, 6);
+        assert_eq!(trace.num_rows(), 6);
+        assert_eq!(trace.into_polynomial_values()[0].len(), 6);
+
+        let mut trace = test_trace();
+        let padding_row = TestColumns {
+            is_padding: F::ONE,
+            ..Default::default()
+        };
+        trace.pad(Padding::Row(padding_row), 0);
+        assert_eq!(*trace.row(3), padding_row);
+
+        let polys = trace.into_polynomial_values();
+        assert_eq!(polys.len(), 4);
+        assert_eq!(polys[0].values, [F::ZERO, F::ONE, F::TWO, F::ZERO].to_vec());
+        assert_eq!(
+            polys[3].values,
+            [F::ZERO, F::ZERO, F::ZERO, F::ONE].to_vec()
+        );
+    }
+
+    #[test]
+    fn test_csv() {
+        let mut trace = test_trace();
+        trace.row_mut(2).counter = F::NEG_ONE;
+        assert_eq!(
+            trace.to_csv(),
+            "counter,limbs[0],limbs[1],is_padding\n\
+             0,1,2,0\n\
+             1,1,2,0\n\
+             18446744069414584320,1,2,0\n"
+        );
+    }
+}
@@ -14,7 +14,7 @@ pub(crate) fn eval_vanishing_poly<F, FE, P, S, const D: usize, const D2: usize>(
     stark: &S,
     vars: &S::EvaluationFrame<FE, P, D2>,
     lookups: &[Lookup<F>],
-    lookup_vars: Option<LookupCheckVars<F, FE, P, D2>>,
+    lookup_vars: Option<LookupCheckVars<F, FE, P, D, D2>>,
     consumer: &mut ConstraintConsumer<P>,
 ) where
     F: RichField + Extendable<D>,
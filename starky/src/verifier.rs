@@ -4,17 +4,19 @@ use core::iter::once;
 use anyhow::{anyhow, ensure, Result};
 use itertools::Itertools;
 use plonky2::field::extension::{Extendable, FieldExtension};
+use plonky2::field::fft::{smooth_factors, smooth_root_of_unity};
 use plonky2::field::types::Field;
 use plonky2::fri::verifier::verify_fri_proof;
 use plonky2::hash::hash_types::RichField;
 use plonky2::hash::merkle_tree::MerkleCap;
 use plonky2::plonk::config::GenericConfig;
 use plonky2::plonk::plonk_common::reduce_with_powers;
+use plonky2::util::log2_ceil;
 
 use crate::config::StarkConfig;
 use crate::constraint_consumer::ConstraintConsumer;
 use crate::evaluation_frame::StarkEvaluationFrame;
-use crate::lookup::LookupCheckVars;
+use crate::lookup::{eval_preprocessed_table_columns, LookupCheckVars};
 use crate::proof::{StarkOpeningSet, StarkProof, StarkProofChallenges, StarkProofWithPublicInputs};
 use crate::stark::Stark;
 use crate::vanishing_poly::eval_vanishing_poly;
@@ -69,37 +71,45 @@ pub(crate) fn verify_stark_proof_with_challenges<
             .map(F::Extension::from_basefield)
             .collect::<Vec<_>>(),
     );
-    let (l_0, l_last) = eval_l_0_and_l_last(degree_bits, challenges.stark_zeta);
-    let last = F::primitive_root_of_unity(degree_bits).inverse();
+    let degree = proof.trace_len;
+    let g = smooth_root_of_unity::<F>(degree);
+    let (l_0, l_last) = eval_l_0_and_l_last(degree, g.into(), challenges.stark_zeta);
+    let last = g.inverse();
     let z_last = challenges.stark_zeta - last.into();
-    let mut consumer = ConstraintConsumer::<F::Extension>::new(
-        challenges
-            .stark_alphas
-            .iter()
-            .map(|&alpha| F::Extension::from_basefield(alpha))
-            .collect::<Vec<_>>(),
-        z_last,
-        l_0,
-        l_last,
-    );
+    let alphas = challenges
+        .stark_alphas
+        .iter()
+        .map(|&alpha| F::Extension::from_basefield(alpha))
+        .collect::<Vec<_>>();
+    let challenge_degree = config.challenge_degree::<F>(D);
+    let mut consumer = if challenge_degree == 1 {
+        ConstraintConsumer::<F::Extension>::new(alphas, z_last, l_0, l_last)
+    } else {
+        ConstraintConsumer::<F::Extension>::new_with_extension_alphas(
+            alphas,
+            challenge_degree,
+            F::Extension::from_basefield(F::W),
+            z_last,
+            l_0,
+            l_last,
+        )
+    };
 
     let num_lookup_columns = stark.num_lookup_helper_columns(config);
-    let lookup_challenges = (num_lookup_columns > 0).then(|| {
-        challenges
-            .lookup_challenge_set
-            .unwrap()
-            .challenges
-            .iter()
-            .map(|ch| ch.beta)
-            .collect::<Vec<_>>()
-    });
+    let lookup_challenges =
+        (num_lookup_columns > 0).then(|| challenges.lookup_challenge_set.unwrap().challenges);
 
+    let lookups = stark.lookups();
     let lookup_vars = stark.uses_lookups().then(|| LookupCheckVars {
         local_values: auxiliary_polys.as_ref().unwrap().clone(),
         next_values: auxiliary_polys_next.as_ref().unwrap().clone(),
         challenges: lookup_challenges.unwrap(),
+        preprocessed_table_values: eval_preprocessed_table_columns::<F, D>(
+            &lookups,
+            challenges.stark_zeta,
+        ),
+        challenge_degree,
     });
-    let lookups = stark.lookups();
 
     eval_vanishing_poly::<F, F::Extension, F::Extension, S, D, D>(
         &stark,
@@ -111,7 +121,7 @@ pub(crate) fn verify_stark_proof_with_challenges<
     let vanishing_polys_zeta = consumer.accumulators();
 
     // Check each polynomial identity, of the form `vanishing(x) = Z_H(x) quotient(x)`, at zeta.
-    let zeta_pow_deg = challenges.stark_zeta.exp_power_of_2(degree_bits);
+    let zeta_pow_deg = challenges.stark_zeta.exp_u64(degree as u64);
     let z_h_zeta = zeta_pow_deg - F::Extension::ONE;
     // `quotient_polys_zeta` holds `num_challenges * quotient_degree_factor` evaluations.
     // Each chunk of `quotient_degree_factor` holds the evaluations of `t_0(zeta),...,t_{quotient_degree_factor-1}(zeta)`
@@ -134,11 +144,7 @@ pub(crate) fn verify_stark_proof_with_challenges<
         .collect_vec();
 
     verify_fri_proof::<F, C, D>(
-        &stark.fri_instance(
-            challenges.stark_zeta,
-            F::primitive_root_of_unity(degree_bits),
-            config,
-        ),
+        &stark.fri_instance(challenges.stark_zeta, g, config),
         &proof.openings.to_fri_openings(),
         &challenges.fri_challenges,
         &merkle_caps,
@@ -166,6 +172,7 @@ where
     let degree_bits = proof.recover_degree_bits(config);
 
     let StarkProof {
+        trace_len,
         trace_cap,
         auxiliary_polys_cap,
         quotient_polys_cap,
@@ -184,6 +191,26 @@ where
     } = openings;
 
     ensure!(public_inputs.len() == S::PUBLIC_INPUTS);
+    let (k, j) = smooth_factors(*trace_len)
+        .ok_or_else(|| anyhow!("Trace length {} is not of the form 2^k 3^j", trace_len))?;
+    ensure!(
+        k + config.fri_config.rate_bits <= F::TWO_ADICITY && j <= F::THREE_ADICITY,
+        "The field has no subgroup of order {} for the LDE of a trace of length {}",
+        trace_len << config.fri_config.rate_bits,
+        trace_len
+    );
+    ensure!(
+        log2_ceil(*trace_len) == degree_bits,
+        "Trace length is inconsistent with the FRI degree bound."
+    );
+    ensure!(
+        stark
+            .lookups()
+            .iter()
+            .flat_map(|lookup| &lookup.preprocessed_table_columns)
+            .all(|col| col.len() == *trace_len),
+        "Preprocessed table columns must be as long as the trace."
+    );
 
     let fri_params = config.fri_params(degree_bits);
     let cap_height = fri_params.config.cap_height;
@@ -210,11 +237,11 @@ where
 
 /// Evaluate the Lagrange polynomials `L_0` and `L_(n-1)` at a point `x`.
 /// `L_0(x) = (x^n - 1)/(n * (x - 1))`
-/// `L_(n-1)(x) = (x^n - 1)/(n * (g * x - 1))`, with `g` the first element of the subgroup.
-fn eval_l_0_and_l_last<F: Field>(log_n: usize, x: F) -> (F, F) {
-    let n = F::from_canonical_usize(1 << log_n);
-    let g = F::primitive_root_of_unity(log_n);
-    let z_x = x.exp_power_of_2(log_n) - F::ONE;
+/// `L_(n-1)(x) = (x^n - 1)/(n * (g * x - 1))`, with `g` the generator of the subgroup, which is
+/// passed in as the subgroups of extension fields have different generators.
+fn eval_l_0_and_l_last<F: Field>(n: usize, g: F, x: F) -> (F, F) {
+    let z_x = x.exp_u64(n as u64) - F::ONE;
+    let n = F::from_canonical_usize(n);
     let invs = F::batch_multiplicative_inverse(&[n * (x - F::ONE), n * (g * x - F::ONE)]);
 
     (z_x * invs[0], z_x * invs[1])
@@ -262,6 +289,7 @@ fn check_lookup_options<
 
 #[cfg(test)]
 mod tests {
+    use plonky2::field::fft::smooth_root_of_unity;
     use plonky2::field::goldilocks_field::GoldilocksField;
     use plonky2::field::polynomial::PolynomialValues;
     use plonky2::field::types::Sample;
@@ -271,15 +299,14 @@ mod tests {
     #[test]
     fn test_eval_l_0_and_l_last() {
         type F = GoldilocksField;
-        let log_n = 5;
-        let n = 1 << log_n;
-
-        let x = F::rand(); // challenge point
-        let expected_l_first_x = PolynomialValues::selector(n, 0).ifft().eval(x);
-        let expected_l_last_x = PolynomialValues::selector(n, n - 1).ifft().eval(x);
-
-        let (l_first_x, l_last_x) = eval_l_0_and_l_last(log_n, x);
-        assert_eq!(l_first_x, expected_l_first_x);
-        assert_eq!(l_last_x, expected_l_last_x);
+        for n in [32, 48] {
+            let x = F::rand(); // challenge point
+            let expected_l_first_x = PolynomialValues::selector(n, 0).ifft_smooth().eval(x);
+            let expected_l_last_x = PolynomialValues::selector(n, n - 1).ifft_smooth().eval(x);
+
+            let (l_first_x, l_last_x) = eval_l_0_and_l_last(n, smooth_root_of_unity(n), x);
+            assert_eq!(l_first_x, expected_l_first_x);
+            assert_eq!(l_last_x, expected_l_last_x);
+        }
     }
 }
@@ -237,6 +237,17 @@ pub fn reverse_index_bits_in_place<T>(arr: &mut [T]) {
     }
 }
 
+/// Transposes in place a square matrix stored in row-major order in `arr`. Panics if the length of
+/// `arr` is not a power of 4.
+pub fn transpose_square_in_place<T>(arr: &mut [T]) {
+    let lb_n = log2_strict(arr.len());
+    assert!(lb_n % 2 == 0, "Not a square matrix: {}", arr.len());
+    let lb_size = lb_n / 2;
+    unsafe {
+        transpose_in_place_square(arr, lb_size, lb_size, 0);
+    }
+}
+
 // Lookup table of 6-bit reverses.
 // NB: 2^6=64 bytes is a cacheline. A smaller table wastes cache space.
 #[rustfmt::skip]
@@ -336,6 +347,27 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_transpose_square_in_place() {
+        for lb_size in [0, 1, 3, 4, 7] {
+            let size = 1 << lb_size;
+            let mut arr: Vec<usize> = (0..size * size).collect();
+            super::transpose_square_in_place(&mut arr);
+            for i in 0..size {
+                for j in 0..size {
+                    assert_eq!(arr[i * size + j], j * size + i);
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_transpose_square_in_place_non_square() {
+        let mut arr: Vec<usize> = (0..8).collect();
+        super::transpose_square_in_place(&mut arr);
+    }
+
     #[test]
     fn test_log2_strict() {
         assert_eq!(log2_strict(1), 0);